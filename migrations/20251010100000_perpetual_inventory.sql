@@ -0,0 +1,58 @@
+-- Perpetual Inventory
+-- Cost layers, stock movements and per-company costing method (FIFO / weighted average)
+
+-- Costing method is chosen per company
+ALTER TABLE companies
+    ADD COLUMN inventory_costing_method VARCHAR(20) NOT NULL DEFAULT 'FIFO'
+        CHECK (inventory_costing_method IN ('FIFO', 'Average'));
+
+-- Link sales and purchase lines to items
+ALTER TABLE invoice_line_items
+    ADD COLUMN item_id UUID REFERENCES items(id);
+
+ALTER TABLE bill_line_items
+    ADD COLUMN item_id UUID REFERENCES items(id),
+    ADD COLUMN quantity DECIMAL(10,3);
+
+-- Inventory cost layers (one per receipt of stock)
+CREATE TABLE inventory_cost_layers (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    item_id UUID NOT NULL REFERENCES items(id),
+    source_type VARCHAR(50) NOT NULL CHECK (source_type IN ('bill', 'adjustment', 'invoice_void')),
+    source_id UUID,
+    received_date DATE NOT NULL,
+    quantity_received DECIMAL(10,3) NOT NULL CHECK (quantity_received > 0),
+    quantity_remaining DECIMAL(10,3) NOT NULL CHECK (quantity_remaining >= 0),
+    unit_cost DECIMAL(15,4) NOT NULL CHECK (unit_cost >= 0),
+    company_id UUID REFERENCES companies(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Inventory movements (signed quantity and cost; positive = stock in, negative = stock out)
+CREATE TABLE inventory_movements (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    item_id UUID NOT NULL REFERENCES items(id),
+    movement_date DATE NOT NULL,
+    movement_type VARCHAR(50) NOT NULL CHECK (movement_type IN ('purchase', 'sale', 'adjustment', 'sale_reversal')),
+    quantity DECIMAL(10,3) NOT NULL,
+    unit_cost DECIMAL(15,4) NOT NULL,
+    total_cost DECIMAL(15,2) NOT NULL,
+    source_type VARCHAR(50),
+    source_id UUID,
+    transaction_id UUID REFERENCES transactions(id), -- COGS / adjustment journal entry
+    memo TEXT,
+    company_id UUID REFERENCES companies(id),
+    created_by UUID REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_cost_layers_item ON inventory_cost_layers(item_id, received_date);
+CREATE INDEX idx_cost_layers_open ON inventory_cost_layers(item_id) WHERE quantity_remaining > 0;
+CREATE INDEX idx_inventory_movements_item ON inventory_movements(item_id, movement_date);
+CREATE INDEX idx_inventory_movements_source ON inventory_movements(source_type, source_id);
+CREATE INDEX idx_invoice_line_items_item ON invoice_line_items(item_id);
+CREATE INDEX idx_bill_line_items_item ON bill_line_items(item_id);
+
+CREATE TRIGGER update_inventory_cost_layers_updated_at BEFORE UPDATE ON inventory_cost_layers
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
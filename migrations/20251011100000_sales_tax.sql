@@ -0,0 +1,67 @@
+-- Sales Tax
+-- Tax code / tax rate registry (GST, HST, PST, QST) and tax on invoices
+
+-- A tax code is what appears on a line (e.g. "HST-ON", "GST+PST-BC", "E")
+CREATE TABLE tax_codes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    code VARCHAR(50) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    is_active BOOLEAN NOT NULL DEFAULT true,
+    company_id UUID REFERENCES companies(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(code, company_id)
+);
+
+-- Component taxes of a code, each with its own rate history
+CREATE TABLE tax_rates (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tax_code_id UUID NOT NULL REFERENCES tax_codes(id) ON DELETE CASCADE,
+    tax_name VARCHAR(50) NOT NULL, -- GST, HST, PST, QST
+    agency VARCHAR(100), -- Authority the tax is remitted to (e.g. CRA)
+    rate DECIMAL(7,4) NOT NULL CHECK (rate >= 0), -- Percent
+    sequence INT NOT NULL DEFAULT 1, -- Calculation order (matters for compound taxes)
+    is_compound BOOLEAN NOT NULL DEFAULT false, -- Charged on amount plus preceding taxes
+    is_recoverable BOOLEAN NOT NULL DEFAULT false, -- Claimable as an input tax credit
+    liability_account_id UUID NOT NULL REFERENCES chart_of_accounts(id),
+    effective_from DATE NOT NULL,
+    effective_to DATE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (effective_to IS NULL OR effective_to >= effective_from)
+);
+
+-- Tax on invoices
+ALTER TABLE invoices
+    ADD COLUMN subtotal DECIMAL(15,2) NOT NULL DEFAULT 0,
+    ADD COLUMN tax_amount DECIMAL(15,2) NOT NULL DEFAULT 0,
+    ADD COLUMN ar_account_id UUID REFERENCES chart_of_accounts(id);
+
+UPDATE invoices SET subtotal = total_amount;
+
+ALTER TABLE invoice_line_items
+    ADD COLUMN tax_amount DECIMAL(15,2) NOT NULL DEFAULT 0;
+
+-- Tax charged per invoice line and component tax
+CREATE TABLE invoice_line_taxes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    invoice_id UUID NOT NULL REFERENCES invoices(id) ON DELETE CASCADE,
+    invoice_line_item_id UUID NOT NULL REFERENCES invoice_line_items(id) ON DELETE CASCADE,
+    tax_rate_id UUID NOT NULL REFERENCES tax_rates(id),
+    tax_name VARCHAR(50) NOT NULL,
+    rate DECIMAL(7,4) NOT NULL,
+    taxable_amount DECIMAL(15,2) NOT NULL,
+    tax_amount DECIMAL(15,2) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_tax_rates_code ON tax_rates(tax_code_id, effective_from);
+CREATE INDEX idx_invoice_line_taxes_invoice ON invoice_line_taxes(invoice_id);
+CREATE INDEX idx_invoice_line_taxes_rate ON invoice_line_taxes(tax_rate_id);
+
+CREATE TRIGGER update_tax_codes_updated_at BEFORE UPDATE ON tax_codes
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TRIGGER update_tax_rates_updated_at BEFORE UPDATE ON tax_rates
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,63 @@
+-- Sales Tax Returns
+-- Tax on bills (input tax credits) and GST/HST return filing with period locks
+
+-- Recoverable tax paid on purchases posts here (e.g. "GST/HST ITC Receivable")
+ALTER TABLE tax_rates
+    ADD COLUMN recoverable_account_id UUID REFERENCES chart_of_accounts(id);
+
+-- Tax on bills
+ALTER TABLE bills
+    ADD COLUMN subtotal DECIMAL(15,2) NOT NULL DEFAULT 0,
+    ADD COLUMN tax_amount DECIMAL(15,2) NOT NULL DEFAULT 0,
+    ADD COLUMN ap_account_id UUID REFERENCES chart_of_accounts(id);
+
+UPDATE bills SET subtotal = total_amount;
+
+ALTER TABLE bill_line_items
+    ADD COLUMN tax_code VARCHAR(50),
+    ADD COLUMN tax_amount DECIMAL(15,2) NOT NULL DEFAULT 0;
+
+-- Tax paid per bill line and component tax
+CREATE TABLE bill_line_taxes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    bill_id UUID NOT NULL REFERENCES bills(id) ON DELETE CASCADE,
+    bill_line_item_id UUID NOT NULL REFERENCES bill_line_items(id) ON DELETE CASCADE,
+    tax_rate_id UUID NOT NULL REFERENCES tax_rates(id),
+    tax_name VARCHAR(50) NOT NULL,
+    rate DECIMAL(7,4) NOT NULL,
+    taxable_amount DECIMAL(15,2) NOT NULL,
+    tax_amount DECIMAL(15,2) NOT NULL,
+    is_recoverable BOOLEAN NOT NULL DEFAULT false,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Sales tax returns (GST34 line structure)
+CREATE TABLE tax_returns (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    agency VARCHAR(100) NOT NULL DEFAULT 'CRA',
+    period_start DATE NOT NULL,
+    period_end DATE NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'draft' CHECK (status IN ('draft', 'filed')),
+    line_101_sales DECIMAL(15,2) NOT NULL DEFAULT 0, -- Sales and other revenue
+    taxable_sales DECIMAL(15,2) NOT NULL DEFAULT 0, -- Supplies taxed by the agency (incl. zero-rated)
+    line_103_tax_collected DECIMAL(15,2) NOT NULL DEFAULT 0, -- GST/HST collected or collectible
+    line_105_total_tax DECIMAL(15,2) NOT NULL DEFAULT 0, -- Total GST/HST and adjustments
+    line_106_itcs DECIMAL(15,2) NOT NULL DEFAULT 0, -- Current ITCs
+    line_108_total_itcs DECIMAL(15,2) NOT NULL DEFAULT 0, -- Total ITCs and adjustments
+    line_109_net_tax DECIMAL(15,2) NOT NULL DEFAULT 0, -- Net tax (negative = refund)
+    settlement_account_id UUID REFERENCES chart_of_accounts(id),
+    transaction_id UUID REFERENCES transactions(id), -- Settlement journal entry
+    filed_date DATE,
+    filed_by UUID REFERENCES users(id),
+    company_id UUID REFERENCES companies(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (period_end >= period_start)
+);
+
+CREATE INDEX idx_bill_line_taxes_bill ON bill_line_taxes(bill_id);
+CREATE INDEX idx_bill_line_taxes_rate ON bill_line_taxes(tax_rate_id);
+CREATE INDEX idx_tax_returns_period ON tax_returns(agency, period_start, period_end);
+
+CREATE TRIGGER update_tax_returns_updated_at BEFORE UPDATE ON tax_returns
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
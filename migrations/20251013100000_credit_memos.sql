@@ -0,0 +1,98 @@
+-- Credit Memos and Customer Refunds
+-- Customer credits that reverse revenue and tax, applied to invoices or refunded
+
+CREATE TABLE credit_memos (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    credit_memo_number VARCHAR(100) UNIQUE NOT NULL,
+    customer_id UUID NOT NULL REFERENCES contacts(id),
+    credit_memo_date DATE NOT NULL,
+    invoice_id UUID REFERENCES invoices(id), -- Original invoice being credited, if any
+    subtotal DECIMAL(15,2) NOT NULL DEFAULT 0,
+    tax_amount DECIMAL(15,2) NOT NULL DEFAULT 0,
+    total_amount DECIMAL(15,2) NOT NULL,
+    balance DECIMAL(15,2) NOT NULL, -- Credit not yet applied or refunded
+    status VARCHAR(20) NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'applied', 'void')),
+    memo TEXT,
+    ar_account_id UUID REFERENCES chart_of_accounts(id),
+    company_id UUID REFERENCES companies(id),
+    transaction_id UUID REFERENCES transactions(id),
+    created_by UUID REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (balance >= 0 AND balance <= total_amount)
+);
+
+CREATE TABLE credit_memo_line_items (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    credit_memo_id UUID NOT NULL REFERENCES credit_memos(id) ON DELETE CASCADE,
+    line_number INT NOT NULL,
+    item_description TEXT NOT NULL,
+    quantity DECIMAL(10,3) NOT NULL DEFAULT 1,
+    unit_price DECIMAL(15,2) NOT NULL,
+    amount DECIMAL(15,2) NOT NULL,
+    tax_code VARCHAR(50),
+    tax_amount DECIMAL(15,2) NOT NULL DEFAULT 0,
+    revenue_account_id UUID NOT NULL REFERENCES chart_of_accounts(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Tax reversed per credit memo line and component tax
+CREATE TABLE credit_memo_line_taxes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    credit_memo_id UUID NOT NULL REFERENCES credit_memos(id) ON DELETE CASCADE,
+    credit_memo_line_item_id UUID NOT NULL REFERENCES credit_memo_line_items(id) ON DELETE CASCADE,
+    tax_rate_id UUID NOT NULL REFERENCES tax_rates(id),
+    tax_name VARCHAR(50) NOT NULL,
+    rate DECIMAL(7,4) NOT NULL,
+    taxable_amount DECIMAL(15,2) NOT NULL,
+    tax_amount DECIMAL(15,2) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE credit_memo_applications (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    credit_memo_id UUID NOT NULL REFERENCES credit_memos(id) ON DELETE CASCADE,
+    invoice_id UUID NOT NULL REFERENCES invoices(id),
+    amount_applied DECIMAL(15,2) NOT NULL CHECK (amount_applied > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Unapplied credit (credit memos or overpayments) paid back to the customer
+CREATE TABLE customer_refunds (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    refund_number VARCHAR(100),
+    customer_id UUID NOT NULL REFERENCES contacts(id),
+    refund_date DATE NOT NULL,
+    amount DECIMAL(15,2) NOT NULL CHECK (amount > 0),
+    payment_method VARCHAR(50) NOT NULL,
+    reference_number VARCHAR(100),
+    bank_account_id UUID NOT NULL REFERENCES chart_of_accounts(id),
+    credit_memo_id UUID REFERENCES credit_memos(id),
+    payment_id UUID REFERENCES payments(id),
+    memo TEXT,
+    company_id UUID REFERENCES companies(id),
+    transaction_id UUID REFERENCES transactions(id),
+    created_by UUID REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((credit_memo_id IS NULL) <> (payment_id IS NULL))
+);
+
+CREATE INDEX idx_credit_memos_customer ON credit_memos(customer_id);
+CREATE INDEX idx_credit_memos_status ON credit_memos(status);
+CREATE INDEX idx_credit_memos_date ON credit_memos(credit_memo_date);
+CREATE INDEX idx_credit_memo_line_items_memo ON credit_memo_line_items(credit_memo_id);
+CREATE INDEX idx_credit_memo_line_taxes_memo ON credit_memo_line_taxes(credit_memo_id);
+CREATE INDEX idx_credit_memo_applications_memo ON credit_memo_applications(credit_memo_id);
+CREATE INDEX idx_credit_memo_applications_invoice ON credit_memo_applications(invoice_id);
+CREATE INDEX idx_customer_refunds_customer ON customer_refunds(customer_id);
+
+CREATE TRIGGER update_credit_memos_updated_at BEFORE UPDATE ON credit_memos
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TRIGGER update_credit_memo_line_items_updated_at BEFORE UPDATE ON credit_memo_line_items
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TRIGGER update_customer_refunds_updated_at BEFORE UPDATE ON customer_refunds
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
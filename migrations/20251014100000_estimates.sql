@@ -0,0 +1,65 @@
+-- Estimates (quotes)
+-- Non-posting sales documents that convert into invoices, in full or by progress billing
+
+CREATE TABLE estimates (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    estimate_number VARCHAR(100) UNIQUE NOT NULL,
+    customer_id UUID NOT NULL REFERENCES contacts(id),
+    estimate_date DATE NOT NULL,
+    expiry_date DATE NOT NULL,
+    subtotal DECIMAL(15,2) NOT NULL DEFAULT 0,
+    tax_amount DECIMAL(15,2) NOT NULL DEFAULT 0,
+    total_amount DECIMAL(15,2) NOT NULL,
+    invoiced_amount DECIMAL(15,2) NOT NULL DEFAULT 0, -- Total of invoices created from the estimate
+    status VARCHAR(20) NOT NULL DEFAULT 'draft' CHECK (status IN ('draft', 'sent', 'accepted', 'declined', 'expired')),
+    customer_memo TEXT,
+    billing_address TEXT,
+    shipping_address TEXT,
+    company_id UUID REFERENCES companies(id),
+    created_by UUID REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (expiry_date >= estimate_date)
+);
+
+-- Same line structure as invoice_line_items, plus the quantity billed so far
+CREATE TABLE estimate_line_items (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    estimate_id UUID NOT NULL REFERENCES estimates(id) ON DELETE CASCADE,
+    line_number INT NOT NULL,
+    item_description TEXT NOT NULL,
+    quantity DECIMAL(10,3) NOT NULL DEFAULT 1,
+    unit_price DECIMAL(15,2) NOT NULL,
+    amount DECIMAL(15,2) NOT NULL,
+    discount_percent DECIMAL(5,2),
+    discount_amount DECIMAL(15,2),
+    tax_code VARCHAR(50),
+    tax_amount DECIMAL(15,2) NOT NULL DEFAULT 0,
+    revenue_account_id UUID NOT NULL REFERENCES chart_of_accounts(id),
+    item_id UUID REFERENCES items(id),
+    invoiced_quantity DECIMAL(10,3) NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (invoiced_quantity >= 0 AND invoiced_quantity <= quantity)
+);
+
+-- Invoices created from an estimate
+CREATE TABLE estimate_invoices (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    estimate_id UUID NOT NULL REFERENCES estimates(id) ON DELETE CASCADE,
+    invoice_id UUID NOT NULL UNIQUE REFERENCES invoices(id),
+    amount DECIMAL(15,2) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_estimates_customer ON estimates(customer_id);
+CREATE INDEX idx_estimates_status ON estimates(status);
+CREATE INDEX idx_estimates_expiry ON estimates(expiry_date);
+CREATE INDEX idx_estimate_line_items_estimate ON estimate_line_items(estimate_id);
+CREATE INDEX idx_estimate_invoices_estimate ON estimate_invoices(estimate_id);
+
+CREATE TRIGGER update_estimates_updated_at BEFORE UPDATE ON estimates
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TRIGGER update_estimate_line_items_updated_at BEFORE UPDATE ON estimate_line_items
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,60 @@
+-- Purchase Orders
+-- Non-posting purchasing documents converted into bills as goods and services are billed
+
+CREATE TABLE purchase_orders (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    po_number VARCHAR(100) UNIQUE NOT NULL,
+    vendor_id UUID NOT NULL REFERENCES contacts(id),
+    order_date DATE NOT NULL,
+    expected_date DATE,
+    total_amount DECIMAL(15,2) NOT NULL, -- Before tax; tax is calculated on the bills
+    billed_amount DECIMAL(15,2) NOT NULL DEFAULT 0,
+    status VARCHAR(30) NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'partially_received', 'closed')),
+    memo TEXT,
+    shipping_address TEXT,
+    company_id UUID REFERENCES companies(id),
+    created_by UUID REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE purchase_order_line_items (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    purchase_order_id UUID NOT NULL REFERENCES purchase_orders(id) ON DELETE CASCADE,
+    line_number INT NOT NULL,
+    description TEXT,
+    quantity DECIMAL(10,3) NOT NULL CHECK (quantity > 0),
+    unit_price DECIMAL(15,2) NOT NULL,
+    amount DECIMAL(15,2) NOT NULL,
+    expense_account_id UUID NOT NULL REFERENCES chart_of_accounts(id),
+    item_id UUID REFERENCES items(id),
+    tax_code VARCHAR(50),
+    expected_date DATE,
+    billed_quantity DECIMAL(10,3) NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (billed_quantity >= 0 AND billed_quantity <= quantity)
+);
+
+-- Quantities billed against each order line; released when the bill is voided or deleted
+CREATE TABLE purchase_order_bills (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    purchase_order_id UUID NOT NULL REFERENCES purchase_orders(id) ON DELETE CASCADE,
+    purchase_order_line_id UUID NOT NULL REFERENCES purchase_order_line_items(id) ON DELETE CASCADE,
+    bill_id UUID NOT NULL REFERENCES bills(id) ON DELETE CASCADE,
+    quantity DECIMAL(10,3) NOT NULL CHECK (quantity > 0),
+    amount DECIMAL(15,2) NOT NULL, -- Pre-tax amount billed against the order line
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_purchase_orders_vendor ON purchase_orders(vendor_id);
+CREATE INDEX idx_purchase_orders_status ON purchase_orders(status);
+CREATE INDEX idx_purchase_order_line_items_po ON purchase_order_line_items(purchase_order_id);
+CREATE INDEX idx_purchase_order_bills_po ON purchase_order_bills(purchase_order_id);
+CREATE INDEX idx_purchase_order_bills_bill ON purchase_order_bills(bill_id);
+
+CREATE TRIGGER update_purchase_orders_updated_at BEFORE UPDATE ON purchase_orders
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TRIGGER update_purchase_order_line_items_updated_at BEFORE UPDATE ON purchase_order_line_items
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
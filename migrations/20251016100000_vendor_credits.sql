@@ -0,0 +1,72 @@
+-- Vendor Credits
+-- Vendor-issued credits that reverse expenses and input tax credits, applied to bills
+
+CREATE TABLE vendor_credits (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    vendor_credit_number VARCHAR(100) UNIQUE NOT NULL,
+    vendor_id UUID NOT NULL REFERENCES contacts(id),
+    credit_date DATE NOT NULL,
+    bill_id UUID REFERENCES bills(id) ON DELETE SET NULL, -- Original bill being credited, if any
+    subtotal DECIMAL(15,2) NOT NULL DEFAULT 0,
+    tax_amount DECIMAL(15,2) NOT NULL DEFAULT 0,
+    total_amount DECIMAL(15,2) NOT NULL,
+    balance DECIMAL(15,2) NOT NULL, -- Credit not yet applied to bills
+    status VARCHAR(20) NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'applied', 'void')),
+    memo TEXT,
+    ap_account_id UUID REFERENCES chart_of_accounts(id),
+    company_id UUID REFERENCES companies(id),
+    transaction_id UUID REFERENCES transactions(id),
+    created_by UUID REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (balance >= 0 AND balance <= total_amount)
+);
+
+CREATE TABLE vendor_credit_line_items (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    vendor_credit_id UUID NOT NULL REFERENCES vendor_credits(id) ON DELETE CASCADE,
+    line_number INT NOT NULL,
+    description TEXT,
+    amount DECIMAL(15,2) NOT NULL CHECK (amount > 0),
+    expense_account_id UUID NOT NULL REFERENCES chart_of_accounts(id),
+    tax_code VARCHAR(50),
+    tax_amount DECIMAL(15,2) NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Tax reversed per vendor credit line and component tax
+CREATE TABLE vendor_credit_line_taxes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    vendor_credit_id UUID NOT NULL REFERENCES vendor_credits(id) ON DELETE CASCADE,
+    vendor_credit_line_item_id UUID NOT NULL REFERENCES vendor_credit_line_items(id) ON DELETE CASCADE,
+    tax_rate_id UUID NOT NULL REFERENCES tax_rates(id),
+    tax_name VARCHAR(50) NOT NULL,
+    rate DECIMAL(7,4) NOT NULL,
+    taxable_amount DECIMAL(15,2) NOT NULL,
+    tax_amount DECIMAL(15,2) NOT NULL,
+    is_recoverable BOOLEAN NOT NULL DEFAULT false,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE vendor_credit_applications (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    vendor_credit_id UUID NOT NULL REFERENCES vendor_credits(id) ON DELETE CASCADE,
+    bill_id UUID NOT NULL REFERENCES bills(id),
+    amount_applied DECIMAL(15,2) NOT NULL CHECK (amount_applied > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_vendor_credits_vendor ON vendor_credits(vendor_id);
+CREATE INDEX idx_vendor_credits_status ON vendor_credits(status);
+CREATE INDEX idx_vendor_credits_date ON vendor_credits(credit_date);
+CREATE INDEX idx_vendor_credit_line_items_credit ON vendor_credit_line_items(vendor_credit_id);
+CREATE INDEX idx_vendor_credit_line_taxes_credit ON vendor_credit_line_taxes(vendor_credit_id);
+CREATE INDEX idx_vendor_credit_applications_credit ON vendor_credit_applications(vendor_credit_id);
+CREATE INDEX idx_vendor_credit_applications_bill ON vendor_credit_applications(bill_id);
+
+CREATE TRIGGER update_vendor_credits_updated_at BEFORE UPDATE ON vendor_credits
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TRIGGER update_vendor_credit_line_items_updated_at BEFORE UPDATE ON vendor_credit_line_items
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
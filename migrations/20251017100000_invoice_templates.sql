@@ -0,0 +1,23 @@
+-- Invoice Templates
+-- Per-company branding and payment instructions for printed (PDF) invoices
+
+CREATE TABLE invoice_templates (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    company_id UUID REFERENCES companies(id), -- NULL is the default template
+    business_name VARCHAR(255),
+    address TEXT,
+    phone VARCHAR(50),
+    email VARCHAR(255),
+    tax_registration_number VARCHAR(100), -- e.g. GST/HST business number
+    payment_instructions TEXT,
+    footer TEXT,
+    logo BYTEA, -- JPEG
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- One template per company, including a single default template
+CREATE UNIQUE INDEX idx_invoice_templates_company ON invoice_templates(company_id) NULLS NOT DISTINCT;
+
+CREATE TRIGGER update_invoice_templates_updated_at BEFORE UPDATE ON invoice_templates
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
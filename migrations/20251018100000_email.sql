@@ -0,0 +1,35 @@
+-- Email
+-- Per-company subject/body templates and a history of documents emailed to customers
+
+CREATE TABLE email_templates (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    company_id UUID REFERENCES companies(id), -- NULL is the default template
+    document_type VARCHAR(20) NOT NULL CHECK (document_type IN ('invoice', 'statement')),
+    subject VARCHAR(255) NOT NULL,
+    body TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- One template per company and document type, including the defaults
+CREATE UNIQUE INDEX idx_email_templates_company_type ON email_templates(company_id, document_type) NULLS NOT DISTINCT;
+
+CREATE TRIGGER update_email_templates_updated_at BEFORE UPDATE ON email_templates
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TABLE email_deliveries (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    document_type VARCHAR(20) NOT NULL CHECK (document_type IN ('invoice', 'statement')),
+    document_id UUID, -- invoice ID; NULL for statements
+    contact_id UUID NOT NULL REFERENCES contacts(id),
+    recipient VARCHAR(255) NOT NULL,
+    subject VARCHAR(255) NOT NULL,
+    status VARCHAR(20) NOT NULL CHECK (status IN ('sent', 'failed')),
+    error TEXT,
+    company_id UUID REFERENCES companies(id),
+    sent_by UUID REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_email_deliveries_document ON email_deliveries(document_type, document_id);
+CREATE INDEX idx_email_deliveries_contact ON email_deliveries(contact_id);
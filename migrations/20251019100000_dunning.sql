@@ -0,0 +1,44 @@
+-- Dunning
+-- Overdue reminder schedule per company, reminders sent per invoice and per-customer opt-out
+
+ALTER TABLE contacts ADD COLUMN dunning_opt_out BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Reminder levels; companies without levels use the default (NULL company) schedule,
+-- and without a default schedule the built-in 7/30/60 day schedule applies
+CREATE TABLE dunning_levels (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    company_id UUID REFERENCES companies(id),
+    level INTEGER NOT NULL CHECK (level > 0),
+    days_overdue INTEGER NOT NULL CHECK (days_overdue > 0),
+    subject VARCHAR(255) NOT NULL,
+    body TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX idx_dunning_levels_company_level ON dunning_levels(company_id, level) NULLS NOT DISTINCT;
+CREATE UNIQUE INDEX idx_dunning_levels_company_days ON dunning_levels(company_id, days_overdue) NULLS NOT DISTINCT;
+
+CREATE TRIGGER update_dunning_levels_updated_at BEFORE UPDATE ON dunning_levels
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Each reminder level is queued once per invoice, then sent by the dunning job
+CREATE TABLE invoice_reminders (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    invoice_id UUID NOT NULL REFERENCES invoices(id),
+    level INTEGER NOT NULL,
+    days_overdue INTEGER NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'queued' CHECK (status IN ('queued', 'sent', 'failed')),
+    error TEXT,
+    email_delivery_id UUID REFERENCES email_deliveries(id),
+    queued_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    sent_at TIMESTAMPTZ,
+    UNIQUE (invoice_id, level)
+);
+
+CREATE INDEX idx_invoice_reminders_status ON invoice_reminders(status);
+
+-- Reminders are recorded in the send history alongside invoices and statements
+ALTER TABLE email_deliveries DROP CONSTRAINT email_deliveries_document_type_check;
+ALTER TABLE email_deliveries ADD CONSTRAINT email_deliveries_document_type_check
+    CHECK (document_type IN ('invoice', 'statement', 'reminder'));
@@ -0,0 +1,40 @@
+-- Finance Charges
+-- Interest on overdue invoices, billed on finance-charge invoices
+
+ALTER TABLE contacts ADD COLUMN finance_charge_exempt BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE finance_charge_settings (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    company_id UUID REFERENCES companies(id), -- NULL is the default
+    monthly_rate NUMERIC(7, 4) NOT NULL CHECK (monthly_rate > 0), -- percent per month, e.g. 2
+    minimum_charge NUMERIC(15, 2) NOT NULL DEFAULT 0 CHECK (minimum_charge >= 0),
+    grace_days INTEGER NOT NULL DEFAULT 0 CHECK (grace_days >= 0),
+    compound BOOLEAN NOT NULL DEFAULT FALSE, -- charge interest on unpaid finance charges
+    income_account_id UUID NOT NULL REFERENCES chart_of_accounts(id),
+    ar_account_id UUID NOT NULL REFERENCES chart_of_accounts(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX idx_finance_charge_settings_company ON finance_charge_settings(company_id) NULLS NOT DISTINCT;
+
+CREATE TRIGGER update_finance_charge_settings_updated_at BEFORE UPDATE ON finance_charge_settings
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Interest assessed on one overdue invoice, billed on a finance-charge invoice
+CREATE TABLE finance_charges (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    invoice_id UUID NOT NULL REFERENCES invoices(id), -- overdue invoice
+    finance_charge_invoice_id UUID NOT NULL REFERENCES invoices(id),
+    customer_id UUID NOT NULL REFERENCES contacts(id),
+    period_start DATE NOT NULL,
+    period_end DATE NOT NULL, -- the run's as-of date; the next charge starts here
+    days INTEGER NOT NULL,
+    base_amount NUMERIC(15, 2) NOT NULL,
+    amount NUMERIC(15, 2) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_finance_charges_invoice ON finance_charges(invoice_id);
+CREATE INDEX idx_finance_charges_fc_invoice ON finance_charges(finance_charge_invoice_id);
+CREATE INDEX idx_finance_charges_customer ON finance_charges(customer_id);
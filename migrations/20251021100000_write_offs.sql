@@ -0,0 +1,40 @@
+-- Bad Debt Write-offs
+-- Uncollectible invoice balances written off to bad-debt expense or an allowance,
+-- and recoveries when the customer pays after all
+
+ALTER TABLE invoices DROP CONSTRAINT invoices_status_check;
+ALTER TABLE invoices ADD CONSTRAINT invoices_status_check
+    CHECK (status IN ('draft', 'sent', 'paid', 'partial', 'overdue', 'void', 'written_off'));
+
+CREATE TABLE invoice_write_offs (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    invoice_id UUID NOT NULL REFERENCES invoices(id),
+    customer_id UUID NOT NULL REFERENCES contacts(id),
+    write_off_date DATE NOT NULL,
+    amount NUMERIC(15, 2) NOT NULL CHECK (amount > 0), -- invoice balance at the time
+    account_id UUID NOT NULL REFERENCES chart_of_accounts(id), -- bad-debt expense or allowance
+    reason TEXT,
+    recovered_amount NUMERIC(15, 2) NOT NULL DEFAULT 0 CHECK (recovered_amount >= 0 AND recovered_amount <= amount),
+    transaction_id UUID REFERENCES transactions(id),
+    company_id UUID REFERENCES companies(id),
+    created_by UUID REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_invoice_write_offs_invoice ON invoice_write_offs(invoice_id);
+CREATE INDEX idx_invoice_write_offs_customer ON invoice_write_offs(customer_id);
+
+-- Written-off balance reinstated on the invoice so a late payment can be applied
+CREATE TABLE write_off_recoveries (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    write_off_id UUID NOT NULL REFERENCES invoice_write_offs(id),
+    invoice_id UUID NOT NULL REFERENCES invoices(id),
+    recovery_date DATE NOT NULL,
+    amount NUMERIC(15, 2) NOT NULL CHECK (amount > 0),
+    transaction_id UUID REFERENCES transactions(id),
+    created_by UUID REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_write_off_recoveries_write_off ON write_off_recoveries(write_off_id);
+CREATE INDEX idx_write_off_recoveries_invoice ON write_off_recoveries(invoice_id);
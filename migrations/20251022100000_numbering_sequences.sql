@@ -0,0 +1,45 @@
+-- Document Numbering Sequences
+-- Per-company, per-document-type numbers assigned when the client leaves the number blank
+
+CREATE TABLE numbering_sequences (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    company_id UUID REFERENCES companies(id), -- NULL is the default, copied to companies on first use
+    document_type VARCHAR(20) NOT NULL
+        CHECK (document_type IN ('invoice', 'bill', 'payment', 'journal_entry', 'credit_memo')),
+    prefix VARCHAR(50) NOT NULL DEFAULT '', -- may contain {YYYY} or {YY}
+    padding INTEGER NOT NULL DEFAULT 5 CHECK (padding BETWEEN 0 AND 20),
+    yearly_reset BOOLEAN NOT NULL DEFAULT FALSE, -- restart at 1 each calendar year of the document date
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX idx_numbering_sequences_company_type
+    ON numbering_sequences(company_id, document_type) NULLS NOT DISTINCT;
+
+CREATE TRIGGER update_numbering_sequences_updated_at BEFORE UPDATE ON numbering_sequences
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Last number handed out. The row stays locked until the document's transaction commits,
+-- so a rolled-back document gives its number back instead of leaving a gap.
+CREATE TABLE numbering_sequence_counters (
+    sequence_id UUID NOT NULL REFERENCES numbering_sequences(id) ON DELETE CASCADE,
+    year INTEGER NOT NULL, -- 0 for sequences that never reset
+    last_number BIGINT NOT NULL CHECK (last_number >= 0),
+    PRIMARY KEY (sequence_id, year)
+);
+
+-- Journal entry numbers
+ALTER TABLE transactions ADD COLUMN entry_number VARCHAR(100);
+
+CREATE UNIQUE INDEX idx_transactions_company_entry_number
+    ON transactions(company_id, entry_number) NULLS NOT DISTINCT
+    WHERE entry_number IS NOT NULL;
+
+-- Document numbers are unique within a company rather than globally
+ALTER TABLE invoices DROP CONSTRAINT invoices_invoice_number_key;
+CREATE UNIQUE INDEX idx_invoices_company_number
+    ON invoices(company_id, invoice_number) NULLS NOT DISTINCT;
+
+ALTER TABLE credit_memos DROP CONSTRAINT credit_memos_credit_memo_number_key;
+CREATE UNIQUE INDEX idx_credit_memos_company_number
+    ON credit_memos(company_id, credit_memo_number) NULLS NOT DISTINCT;
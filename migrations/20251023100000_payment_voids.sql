@@ -0,0 +1,10 @@
+-- Payment Voids
+-- Payments post to the ledger when they carry an AR account, and can be voided
+-- (e.g. a bounced cheque) with the posting reversed and an NSF fee billed
+
+ALTER TABLE payments
+    ADD COLUMN ar_account_id UUID REFERENCES chart_of_accounts(id),
+    ADD COLUMN void_date DATE, -- NULL while the payment stands
+    ADD COLUMN void_reason TEXT,
+    ADD COLUMN void_transaction_id UUID REFERENCES transactions(id),
+    ADD COLUMN nsf_invoice_id UUID REFERENCES invoices(id); -- returned-payment fee billed to the customer
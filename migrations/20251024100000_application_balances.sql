@@ -0,0 +1,34 @@
+-- Application Balances
+-- Invoice and bill balances are recomputed from everything applied to them instead of
+-- being decremented, so the same rules serve the apply paths and the consistency check
+
+CREATE OR REPLACE FUNCTION invoice_applied_amount(p_invoice_id UUID) RETURNS DECIMAL(15,2) AS $$
+    SELECT COALESCE((SELECT SUM(amount_applied) FROM payment_applications WHERE invoice_id = p_invoice_id), 0)
+         + COALESCE((SELECT SUM(amount_applied) FROM credit_memo_applications WHERE invoice_id = p_invoice_id), 0)
+         + COALESCE((SELECT SUM(amount - recovered_amount) FROM invoice_write_offs WHERE invoice_id = p_invoice_id), 0)
+$$ LANGUAGE SQL STABLE;
+
+-- Void and written-off invoices keep their status; overdue invoices stay overdue until paid
+CREATE OR REPLACE FUNCTION invoice_status_for_balance(p_status VARCHAR, p_balance DECIMAL, p_total DECIMAL) RETURNS VARCHAR AS $$
+    SELECT CASE
+        WHEN p_status IN ('void', 'written_off') THEN p_status
+        WHEN p_balance >= p_total THEN CASE WHEN p_status IN ('paid', 'partial') THEN 'sent' ELSE p_status END
+        WHEN p_balance <= 0 THEN 'paid'
+        WHEN p_status = 'overdue' THEN 'overdue'
+        ELSE 'partial'
+    END
+$$ LANGUAGE SQL IMMUTABLE;
+
+CREATE OR REPLACE FUNCTION bill_applied_amount(p_bill_id UUID) RETURNS DECIMAL(15,2) AS $$
+    SELECT COALESCE((SELECT SUM(amount_applied) FROM bill_payment_applications WHERE bill_id = p_bill_id), 0)
+         + COALESCE((SELECT SUM(amount_applied) FROM vendor_credit_applications WHERE bill_id = p_bill_id), 0)
+$$ LANGUAGE SQL STABLE;
+
+CREATE OR REPLACE FUNCTION bill_status_for_balance(p_status VARCHAR, p_balance DECIMAL, p_total DECIMAL) RETURNS VARCHAR AS $$
+    SELECT CASE
+        WHEN p_status = 'void' THEN p_status
+        WHEN p_balance >= p_total THEN CASE WHEN p_status IN ('paid', 'partial') THEN 'open' ELSE p_status END
+        WHEN p_balance <= 0 THEN 'paid'
+        ELSE 'partial'
+    END
+$$ LANGUAGE SQL IMMUTABLE;
@@ -0,0 +1,9 @@
+-- Billable Expenses
+-- Billable bill lines are recharged to their customer on an invoice line; the link
+-- keeps a line from being invoiced twice and is released if the invoice is voided
+
+ALTER TABLE bill_line_items
+    ADD COLUMN invoice_line_item_id UUID REFERENCES invoice_line_items(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_bill_line_items_billable_customer ON bill_line_items(customer_id) WHERE billable;
+CREATE INDEX idx_bill_line_items_invoice_line ON bill_line_items(invoice_line_item_id);
@@ -0,0 +1,70 @@
+-- Dimensions
+-- User-defined tags (projects, classes, departments, ...) on journal, invoice and bill
+-- lines for reporting. A line carries at most one value of each dimension type.
+
+CREATE TABLE dimension_types (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    code VARCHAR(50) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    company_id UUID REFERENCES companies(id),
+    is_active BOOLEAN NOT NULL DEFAULT true,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE NULLS NOT DISTINCT (company_id, code)
+);
+
+CREATE TABLE dimension_values (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    dimension_type_id UUID NOT NULL REFERENCES dimension_types(id) ON DELETE CASCADE,
+    code VARCHAR(50) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT true,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (dimension_type_id, code),
+    UNIQUE (id, dimension_type_id) -- target for the line tag foreign keys
+);
+
+-- Lines posting to a matching account must carry a value of the dimension type
+CREATE TABLE dimension_requirements (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    dimension_type_id UUID NOT NULL REFERENCES dimension_types(id) ON DELETE CASCADE,
+    account_type VARCHAR(50) CHECK (account_type IN ('Asset', 'Liability', 'Equity', 'Revenue', 'Expense')),
+    account_id UUID REFERENCES chart_of_accounts(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((account_type IS NULL) <> (account_id IS NULL)),
+    UNIQUE NULLS NOT DISTINCT (dimension_type_id, account_type, account_id)
+);
+
+CREATE TABLE transaction_line_dimensions (
+    transaction_line_item_id UUID NOT NULL REFERENCES transaction_line_items(id) ON DELETE CASCADE,
+    dimension_type_id UUID NOT NULL,
+    dimension_value_id UUID NOT NULL,
+    PRIMARY KEY (transaction_line_item_id, dimension_type_id),
+    FOREIGN KEY (dimension_value_id, dimension_type_id) REFERENCES dimension_values(id, dimension_type_id)
+);
+
+CREATE TABLE invoice_line_dimensions (
+    invoice_line_item_id UUID NOT NULL REFERENCES invoice_line_items(id) ON DELETE CASCADE,
+    dimension_type_id UUID NOT NULL,
+    dimension_value_id UUID NOT NULL,
+    PRIMARY KEY (invoice_line_item_id, dimension_type_id),
+    FOREIGN KEY (dimension_value_id, dimension_type_id) REFERENCES dimension_values(id, dimension_type_id)
+);
+
+CREATE TABLE bill_line_dimensions (
+    bill_line_item_id UUID NOT NULL REFERENCES bill_line_items(id) ON DELETE CASCADE,
+    dimension_type_id UUID NOT NULL,
+    dimension_value_id UUID NOT NULL,
+    PRIMARY KEY (bill_line_item_id, dimension_type_id),
+    FOREIGN KEY (dimension_value_id, dimension_type_id) REFERENCES dimension_values(id, dimension_type_id)
+);
+
+CREATE INDEX idx_transaction_line_dimensions_value ON transaction_line_dimensions(dimension_value_id);
+CREATE INDEX idx_invoice_line_dimensions_value ON invoice_line_dimensions(dimension_value_id);
+CREATE INDEX idx_bill_line_dimensions_value ON bill_line_dimensions(dimension_value_id);
+
+CREATE TRIGGER update_dimension_types_updated_at BEFORE UPDATE ON dimension_types
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+CREATE TRIGGER update_dimension_values_updated_at BEFORE UPDATE ON dimension_values
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
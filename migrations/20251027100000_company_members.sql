@@ -0,0 +1,34 @@
+-- Multi-company
+-- Users work in the companies they are members of; every record belongs to one company
+-- (or to none, for data created before companies were set up).
+
+CREATE TABLE company_members (
+    company_id UUID NOT NULL REFERENCES companies(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (company_id, user_id)
+);
+
+CREATE INDEX idx_company_members_user ON company_members(user_id);
+
+-- Codes and document numbers are unique within a company, including among records
+-- without one
+ALTER TABLE chart_of_accounts DROP CONSTRAINT chart_of_accounts_code_company_id_key;
+CREATE UNIQUE INDEX idx_chart_of_accounts_company_code
+    ON chart_of_accounts(company_id, code) NULLS NOT DISTINCT;
+
+ALTER TABLE tax_codes DROP CONSTRAINT tax_codes_code_company_id_key;
+CREATE UNIQUE INDEX idx_tax_codes_company_code
+    ON tax_codes(company_id, code) NULLS NOT DISTINCT;
+
+ALTER TABLE estimates DROP CONSTRAINT estimates_estimate_number_key;
+CREATE UNIQUE INDEX idx_estimates_company_number
+    ON estimates(company_id, estimate_number) NULLS NOT DISTINCT;
+
+ALTER TABLE purchase_orders DROP CONSTRAINT purchase_orders_po_number_key;
+CREATE UNIQUE INDEX idx_purchase_orders_company_number
+    ON purchase_orders(company_id, po_number) NULLS NOT DISTINCT;
+
+ALTER TABLE vendor_credits DROP CONSTRAINT vendor_credits_vendor_credit_number_key;
+CREATE UNIQUE INDEX idx_vendor_credits_company_number
+    ON vendor_credits(company_id, vendor_credit_number) NULLS NOT DISTINCT;
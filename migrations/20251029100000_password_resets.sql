@@ -0,0 +1,16 @@
+-- Password changes and resets
+-- Reset tokens are emailed to the user and stored as SHA-256 hashes; each can be used once
+-- before it expires. Access tokens issued before the last password change stop working.
+
+ALTER TABLE users ADD COLUMN password_changed_at TIMESTAMPTZ;
+
+CREATE TABLE password_reset_tokens (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_password_reset_tokens_user ON password_reset_tokens(user_id) WHERE used_at IS NULL;
@@ -0,0 +1,28 @@
+-- Two-factor authentication
+-- TOTP (RFC 6238) secrets per user. Enrollment starts unconfirmed and only takes effect once the
+-- user proves their authenticator app works. last_used_step stops a code being replayed.
+
+CREATE TABLE totp_credentials (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    secret VARCHAR(64) NOT NULL,
+    confirmed_at TIMESTAMPTZ,
+    last_used_step BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Single-use recovery codes, stored as SHA-256 hashes
+CREATE TABLE mfa_recovery_codes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    code_hash VARCHAR(64) NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_mfa_recovery_codes_user ON mfa_recovery_codes(user_id) WHERE used_at IS NULL;
+
+-- Roles whose users must have two-factor authentication set up, managed by admins
+CREATE TABLE mfa_required_roles (
+    role VARCHAR(50) PRIMARY KEY CHECK (role IN ('admin', 'accountant', 'viewer')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,4 @@
+-- User administration
+-- Admins can disable accounts; disabled users can't log in and their tokens stop working.
+
+ALTER TABLE users ADD COLUMN disabled_at TIMESTAMPTZ;
@@ -0,0 +1,10 @@
+-- Reminder Claims
+-- Dunning runs claim reminders before sending so concurrent runs can't send one twice,
+-- and failed reminders are retried a limited number of times
+
+ALTER TABLE invoice_reminders DROP CONSTRAINT invoice_reminders_status_check;
+ALTER TABLE invoice_reminders ADD CONSTRAINT invoice_reminders_status_check
+    CHECK (status IN ('queued', 'sending', 'sent', 'failed'));
+
+ALTER TABLE invoice_reminders ADD COLUMN attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE invoice_reminders ADD COLUMN claimed_at TIMESTAMPTZ;
@@ -0,0 +1,15 @@
+-- Two-factor login challenges
+-- The password step of a two-factor login hands out an opaque challenge token, stored as a
+-- SHA-256 hash. It completes one login, and is spent after too many wrong codes.
+
+CREATE TABLE mfa_challenges (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    failed_attempts INTEGER NOT NULL DEFAULT 0,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_mfa_challenges_user ON mfa_challenges(user_id, created_at);
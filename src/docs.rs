@@ -4,6 +4,9 @@ use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
 use crate::models::{
     // User models
     User, UserRole, UserResponse, CreateUserRequest, LoginRequest, AuthResponse,
+    ChangePasswordRequest, ForgotPasswordRequest, ResetPasswordRequest,
+    LoginResponse, MfaChallengeResponse, MfaLoginRequest, MfaCodeRequest, DisableMfaRequest, MfaSetupResponse,
+    RecoveryCodesResponse, MfaStatusResponse, MfaPolicy, InviteUserRequest, UpdateUserRoleRequest, UserAccount,
     // Account models
     Account, AccountType, CreateAccountRequest, UpdateAccountRequest,
     // Transaction models
@@ -13,9 +16,11 @@ use crate::models::{
     Contact, ContactType, CreateContactRequest, UpdateContactRequest,
     // Invoice models
     Invoice, InvoiceLineItem, InvoiceStatus, CreateInvoiceRequest,
-    CreateInvoiceLineItemRequest, InvoiceWithLineItems,
+    CreateInvoiceLineItemRequest, InvoiceWithLineItems, InvoiceTemplate, UpdateInvoiceTemplateRequest,
     // Payment models
-    Payment, PaymentApplication, CreatePaymentRequest, PaymentApplicationRequest,
+    Payment, PaymentApplication, CreatePaymentRequest, PaymentApplicationRequest, VoidPaymentRequest,
+    AutoApplySource, AutoApplyRequest, AutoApplication, CustomerAutoApplication, AutoApplyRun,
+    BalanceDrift, ApplicationConsistencyReport,
     BillPayment, CreateBillPaymentRequest, BillPaymentApplicationRequest,
     // Bill models
     Bill, BillLineItem, BillStatus, CreateBillRequest, CreateBillLineItemRequest,
@@ -23,7 +28,57 @@ use crate::models::{
     // Reporting models
     TrialBalance, TrialBalanceEntry, ProfitLossStatement, ProfitLossEntry,
     BalanceSheet, BalanceSheetEntry, AccountsReceivableAging, AgingBucket,
-    DateRangeRequest, DateRequest,
+    DateRangeRequest, DateRequest, DimensionFilter,
+    // Inventory models
+    Item, ItemType, CreateItemRequest, InventoryCostingMethod, InventoryCostLayer,
+    InventoryMovement, InventoryMovementType, CreateStockAdjustmentRequest,
+    UpdateCostingMethodRequest, InventoryValuationEntry, InventoryValuationReport,
+    // Tax models
+    TaxCode, TaxRate, TaxCodeWithRates, CreateTaxCodeRequest, CreateTaxRateRequest,
+    SetupCanadianTaxCodesRequest, InvoiceLineTax, TaxSummary, BillLineTax, TaxReturn,
+    TaxReturnStatus, TaxReturnLines, TaxReturnWorksheet, TaxReturnPeriodRequest,
+    FileTaxReturnRequest,
+    // Credit memo models
+    CreditMemo, CreditMemoStatus, CreditMemoLineItem, CreditMemoApplication,
+    CreateCreditMemoRequest, CreateCreditMemoLineItemRequest, CreditMemoApplicationRequest,
+    CreditMemoWithLineItems, CustomerRefund, CreateCustomerRefundRequest,
+    // Statement models
+    CustomerStatement, StatementLine, StatementStyle, StatementFormat, StatementRequest,
+    BatchStatementRequest,
+    // Estimate models
+    Estimate, EstimateStatus, EstimateLineItem, EstimateInvoice, EstimateWithLineItems,
+    EstimateConversion, CreateEstimateRequest, ConvertEstimateRequest, ConvertEstimateLineRequest,
+    // Purchase order models
+    PurchaseOrder, PurchaseOrderStatus, PurchaseOrderLineItem, PurchaseOrderBill,
+    PurchaseOrderWithLineItems, PurchaseOrderConversion, CreatePurchaseOrderRequest,
+    CreatePurchaseOrderLineItemRequest, ConvertPurchaseOrderRequest, ConvertPurchaseOrderLineRequest,
+    OpenPurchaseOrder, CommittedSpend, OpenPurchaseOrdersReport,
+    // Vendor credit models
+    VendorCredit, VendorCreditStatus, VendorCreditLineItem, VendorCreditApplication,
+    CreateVendorCreditRequest, CreateVendorCreditLineItemRequest, VendorCreditApplicationRequest,
+    VendorCreditWithLineItems,
+    // Email models
+    EmailDocumentType, EmailDeliveryStatus, EmailTemplate, UpdateEmailTemplateRequest, EmailDelivery,
+    SendInvoiceRequest, SendStatementRequest,
+    // Dunning models
+    DunningLevel, DunningLevelRequest, SetDunningLevelsRequest, ReminderStatus, InvoiceReminder,
+    RunDunningRequest, DunningRun, DunningOptOutRequest,
+    // Finance charge models
+    FinanceChargeSettings, UpdateFinanceChargeSettingsRequest, FinanceCharge, FinanceChargeRunRequest,
+    FinanceChargeLine, CustomerFinanceCharge, FinanceChargeRun, FinanceChargeExemptRequest,
+    // Write-off models
+    InvoiceWriteOff, WriteOffRecovery, WriteOffInvoicesRequest, RecoverWriteOffRequest,
+    WriteOffWithRecoveries,
+    // Numbering models
+    SequenceDocumentType, NumberingSequence, NumberingSequenceWithNext, UpdateNumberingSequenceRequest,
+    // Billable expense models
+    UnbilledExpense, BillExpensesRequest, BilledExpenses,
+    // Dimension models
+    DimensionType, DimensionValue, DimensionRequirement, DimensionTypeWithValues,
+    CreateDimensionTypeRequest, CreateDimensionValueRequest, UpdateDimensionValueRequest,
+    CreateDimensionRequirementRequest,
+    // Company models
+    Company, CreateCompanyRequest, UpdateCompanyRequest, CompanyMember, AddCompanyMemberRequest,
 };
 use crate::utils::{ApiResponse, HealthResponse};
 
@@ -36,6 +91,19 @@ use crate::utils::{ApiResponse, HealthResponse};
         crate::handlers::auth::register,
         crate::handlers::auth::login,
         crate::handlers::auth::refresh_token,
+        crate::handlers::auth::logout,
+        crate::handlers::auth::logout_all,
+        crate::handlers::auth::change_password,
+        crate::handlers::auth::forgot_password,
+        crate::handlers::auth::reset_password,
+        crate::handlers::auth::verify_mfa_login,
+        crate::handlers::auth::mfa_status,
+        crate::handlers::auth::setup_mfa,
+        crate::handlers::auth::confirm_mfa,
+        crate::handlers::auth::regenerate_recovery_codes,
+        crate::handlers::auth::disable_mfa,
+        crate::handlers::auth::get_mfa_policy,
+        crate::handlers::auth::update_mfa_policy,
         crate::handlers::auth::me,
         // Accounts
         crate::handlers::account::list_accounts,
@@ -67,6 +135,9 @@ use crate::utils::{ApiResponse, HealthResponse};
         crate::handlers::invoice::update_invoice_status,
         crate::handlers::invoice::get_customer_invoices,
         crate::handlers::invoice::get_overdue_invoices,
+        crate::handlers::invoice::get_invoice_pdf,
+        crate::handlers::invoice::get_invoice_template,
+        crate::handlers::invoice::update_invoice_template,
         crate::handlers::payment::get_invoice_payments,
         // Payments
         crate::handlers::payment::create_payment,
@@ -74,6 +145,11 @@ use crate::utils::{ApiResponse, HealthResponse};
         crate::handlers::payment::get_payment,
         crate::handlers::payment::apply_payment,
         crate::handlers::payment::get_unapplied_payments,
+        crate::handlers::payment::list_payment_applications,
+        crate::handlers::payment::unapply_payment,
+        crate::handlers::payment::void_payment,
+        crate::handlers::payment::auto_apply_payments,
+        crate::handlers::payment::check_application_consistency,
         crate::handlers::payment::create_bill_payment,
         // Bills
         crate::handlers::bill::create_bill,
@@ -91,6 +167,110 @@ use crate::utils::{ApiResponse, HealthResponse};
         crate::handlers::reporting::get_profit_loss,
         crate::handlers::reporting::get_balance_sheet,
         crate::handlers::reporting::get_ar_aging,
+        crate::handlers::inventory::get_inventory_valuation,
+        // Inventory
+        crate::handlers::inventory::create_item,
+        crate::handlers::inventory::list_items,
+        crate::handlers::inventory::get_item,
+        crate::handlers::inventory::get_item_cost_layers,
+        crate::handlers::inventory::get_item_movements,
+        crate::handlers::inventory::create_stock_adjustment,
+        crate::handlers::inventory::update_costing_method,
+        // Taxes
+        crate::handlers::tax::create_tax_code,
+        crate::handlers::tax::list_tax_codes,
+        crate::handlers::tax::get_tax_code,
+        crate::handlers::tax::add_tax_rate,
+        crate::handlers::tax::setup_canadian_tax_codes,
+        crate::handlers::tax::get_sales_tax_return,
+        crate::handlers::tax::create_tax_return,
+        crate::handlers::tax::list_tax_returns,
+        crate::handlers::tax::get_tax_return,
+        crate::handlers::tax::file_tax_return,
+        // Credit memos
+        crate::handlers::credit_memo::create_credit_memo,
+        crate::handlers::credit_memo::list_credit_memos,
+        crate::handlers::credit_memo::get_credit_memo,
+        crate::handlers::credit_memo::apply_credit_memo,
+        crate::handlers::credit_memo::void_credit_memo,
+        crate::handlers::credit_memo::create_customer_refund,
+        crate::handlers::credit_memo::get_customer_refunds,
+        // Statements
+        crate::handlers::statement::get_customer_statement,
+        crate::handlers::statement::generate_batch_statements,
+        // Estimates
+        crate::handlers::estimate::create_estimate,
+        crate::handlers::estimate::list_estimates,
+        crate::handlers::estimate::get_estimate,
+        crate::handlers::estimate::update_estimate_status,
+        crate::handlers::estimate::convert_estimate,
+        crate::handlers::estimate::expire_estimates,
+        // Purchase orders
+        crate::handlers::purchase_order::create_purchase_order,
+        crate::handlers::purchase_order::list_purchase_orders,
+        crate::handlers::purchase_order::get_purchase_order,
+        crate::handlers::purchase_order::preview_purchase_order_bill,
+        crate::handlers::purchase_order::convert_purchase_order,
+        crate::handlers::purchase_order::close_purchase_order,
+        crate::handlers::purchase_order::get_open_purchase_orders,
+        // Vendor credits
+        crate::handlers::vendor_credit::create_vendor_credit,
+        crate::handlers::vendor_credit::list_vendor_credits,
+        crate::handlers::vendor_credit::get_vendor_credit,
+        crate::handlers::vendor_credit::apply_vendor_credit,
+        crate::handlers::vendor_credit::void_vendor_credit,
+        // Email
+        crate::handlers::email::send_invoice,
+        crate::handlers::email::send_statement,
+        crate::handlers::email::list_email_deliveries,
+        crate::handlers::email::get_email_template,
+        crate::handlers::email::update_email_template,
+        // Dunning
+        crate::handlers::dunning::run_dunning,
+        crate::handlers::dunning::get_dunning_levels,
+        crate::handlers::dunning::set_dunning_levels,
+        crate::handlers::dunning::list_reminders,
+        crate::handlers::dunning::set_dunning_opt_out,
+        // Finance charges
+        crate::handlers::finance_charge::get_finance_charge_settings,
+        crate::handlers::finance_charge::update_finance_charge_settings,
+        crate::handlers::finance_charge::run_finance_charges,
+        crate::handlers::finance_charge::list_finance_charges,
+        crate::handlers::finance_charge::set_finance_charge_exempt,
+        // Write-offs
+        crate::handlers::write_off::write_off_invoices,
+        crate::handlers::write_off::list_write_offs,
+        crate::handlers::write_off::recover_write_off,
+        // Numbering sequences
+        crate::handlers::numbering::list_numbering_sequences,
+        crate::handlers::numbering::update_numbering_sequence,
+        // Billable expenses
+        crate::handlers::billable_expense::list_unbilled_expenses,
+        crate::handlers::billable_expense::bill_expenses,
+        // Dimensions
+        crate::handlers::dimension::create_dimension_type,
+        crate::handlers::dimension::list_dimension_types,
+        crate::handlers::dimension::get_dimension_type,
+        crate::handlers::dimension::create_dimension_value,
+        crate::handlers::dimension::update_dimension_value,
+        crate::handlers::dimension::add_dimension_requirement,
+        crate::handlers::dimension::remove_dimension_requirement,
+        crate::handlers::company::create_company,
+        crate::handlers::company::list_companies,
+        crate::handlers::company::get_company,
+        crate::handlers::company::update_company,
+        crate::handlers::company::delete_company,
+        crate::handlers::company::list_company_members,
+        crate::handlers::company::add_company_member,
+        crate::handlers::company::remove_company_member,
+        crate::handlers::company::switch_company,
+        crate::handlers::user::list_users,
+        crate::handlers::user::invite_user,
+        crate::handlers::user::get_user,
+        crate::handlers::user::update_user_role,
+        crate::handlers::user::disable_user,
+        crate::handlers::user::enable_user,
+        crate::handlers::user::force_password_reset,
     ),
     components(
         schemas(
@@ -116,6 +296,16 @@ use crate::utils::{ApiResponse, HealthResponse};
             ApiResponse<ProfitLossStatement>,
             ApiResponse<BalanceSheet>,
             ApiResponse<AccountsReceivableAging>,
+            ApiResponse<Vec<Item>>,
+            ApiResponse<Item>,
+            ApiResponse<Vec<InventoryCostLayer>>,
+            ApiResponse<Vec<InventoryMovement>>,
+            ApiResponse<InventoryMovement>,
+            ApiResponse<InventoryValuationReport>,
+            ApiResponse<Vec<TaxCode>>,
+            ApiResponse<TaxCodeWithRates>,
+            ApiResponse<Vec<TaxCodeWithRates>>,
+            ApiResponse<TaxRate>,
             HealthResponse,
             // User types
             User,
@@ -124,6 +314,21 @@ use crate::utils::{ApiResponse, HealthResponse};
             CreateUserRequest,
             LoginRequest,
             AuthResponse,
+            ChangePasswordRequest,
+            ForgotPasswordRequest,
+            ResetPasswordRequest,
+            LoginResponse,
+            MfaChallengeResponse,
+            MfaLoginRequest,
+            MfaCodeRequest,
+            DisableMfaRequest,
+            MfaSetupResponse,
+            RecoveryCodesResponse,
+            MfaStatusResponse,
+            MfaPolicy,
+            InviteUserRequest,
+            UpdateUserRoleRequest,
+            UserAccount,
             // Account types
             Account,
             AccountType,
@@ -149,11 +354,21 @@ use crate::utils::{ApiResponse, HealthResponse};
             CreateInvoiceRequest,
             CreateInvoiceLineItemRequest,
             InvoiceWithLineItems,
+            InvoiceTemplate,
+            UpdateInvoiceTemplateRequest,
             // Payment types
             Payment,
             PaymentApplication,
             CreatePaymentRequest,
             PaymentApplicationRequest,
+            VoidPaymentRequest,
+            AutoApplySource,
+            AutoApplyRequest,
+            AutoApplication,
+            CustomerAutoApplication,
+            AutoApplyRun,
+            BalanceDrift,
+            ApplicationConsistencyReport,
             BillPayment,
             CreateBillPaymentRequest,
             BillPaymentApplicationRequest,
@@ -175,6 +390,142 @@ use crate::utils::{ApiResponse, HealthResponse};
             AgingBucket,
             DateRangeRequest,
             DateRequest,
+            DimensionFilter,
+            // Inventory types
+            Item,
+            ItemType,
+            CreateItemRequest,
+            InventoryCostingMethod,
+            InventoryCostLayer,
+            InventoryMovement,
+            InventoryMovementType,
+            CreateStockAdjustmentRequest,
+            UpdateCostingMethodRequest,
+            InventoryValuationEntry,
+            InventoryValuationReport,
+            // Tax types
+            TaxCode,
+            TaxRate,
+            TaxCodeWithRates,
+            CreateTaxCodeRequest,
+            CreateTaxRateRequest,
+            SetupCanadianTaxCodesRequest,
+            InvoiceLineTax,
+            TaxSummary,
+            BillLineTax,
+            TaxReturn,
+            TaxReturnStatus,
+            TaxReturnLines,
+            TaxReturnWorksheet,
+            TaxReturnPeriodRequest,
+            FileTaxReturnRequest,
+            // Credit memo types
+            CreditMemo,
+            CreditMemoStatus,
+            CreditMemoLineItem,
+            CreditMemoApplication,
+            CreateCreditMemoRequest,
+            CreateCreditMemoLineItemRequest,
+            CreditMemoApplicationRequest,
+            CreditMemoWithLineItems,
+            CustomerRefund,
+            CreateCustomerRefundRequest,
+            // Statement types
+            CustomerStatement,
+            StatementLine,
+            StatementStyle,
+            StatementFormat,
+            StatementRequest,
+            BatchStatementRequest,
+            // Estimate types
+            Estimate,
+            EstimateStatus,
+            EstimateLineItem,
+            EstimateInvoice,
+            EstimateWithLineItems,
+            EstimateConversion,
+            CreateEstimateRequest,
+            ConvertEstimateRequest,
+            ConvertEstimateLineRequest,
+            // Purchase order types
+            PurchaseOrder,
+            PurchaseOrderStatus,
+            PurchaseOrderLineItem,
+            PurchaseOrderBill,
+            PurchaseOrderWithLineItems,
+            PurchaseOrderConversion,
+            CreatePurchaseOrderRequest,
+            CreatePurchaseOrderLineItemRequest,
+            ConvertPurchaseOrderRequest,
+            ConvertPurchaseOrderLineRequest,
+            OpenPurchaseOrder,
+            CommittedSpend,
+            OpenPurchaseOrdersReport,
+            // Vendor credit types
+            VendorCredit,
+            VendorCreditStatus,
+            VendorCreditLineItem,
+            VendorCreditApplication,
+            CreateVendorCreditRequest,
+            CreateVendorCreditLineItemRequest,
+            VendorCreditApplicationRequest,
+            VendorCreditWithLineItems,
+            // Email types
+            EmailDocumentType,
+            EmailDeliveryStatus,
+            EmailTemplate,
+            UpdateEmailTemplateRequest,
+            EmailDelivery,
+            SendInvoiceRequest,
+            SendStatementRequest,
+            // Dunning types
+            DunningLevel,
+            DunningLevelRequest,
+            SetDunningLevelsRequest,
+            ReminderStatus,
+            InvoiceReminder,
+            RunDunningRequest,
+            DunningRun,
+            DunningOptOutRequest,
+            // Finance charge types
+            FinanceChargeSettings,
+            UpdateFinanceChargeSettingsRequest,
+            FinanceCharge,
+            FinanceChargeRunRequest,
+            FinanceChargeLine,
+            CustomerFinanceCharge,
+            FinanceChargeRun,
+            FinanceChargeExemptRequest,
+            // Write-off types
+            InvoiceWriteOff,
+            WriteOffRecovery,
+            WriteOffInvoicesRequest,
+            RecoverWriteOffRequest,
+            WriteOffWithRecoveries,
+            // Numbering types
+            SequenceDocumentType,
+            NumberingSequence,
+            NumberingSequenceWithNext,
+            UpdateNumberingSequenceRequest,
+            // Billable expense types
+            UnbilledExpense,
+            BillExpensesRequest,
+            BilledExpenses,
+            // Dimension types
+            DimensionType,
+            DimensionValue,
+            DimensionRequirement,
+            DimensionTypeWithValues,
+            CreateDimensionTypeRequest,
+            CreateDimensionValueRequest,
+            UpdateDimensionValueRequest,
+            CreateDimensionRequirementRequest,
+            // Company types
+            Company,
+            CreateCompanyRequest,
+            UpdateCompanyRequest,
+            CompanyMember,
+            AddCompanyMemberRequest,
             // Additional request/response types
             crate::handlers::auth::RefreshTokenRequest,
             crate::handlers::auth::TokenResponse,
@@ -182,6 +533,10 @@ use crate::utils::{ApiResponse, HealthResponse};
             crate::handlers::invoice::UpdateInvoiceStatusRequest,
             crate::handlers::payment::ApplyPaymentRequest,
             crate::handlers::bill::UpdateBillStatusRequest,
+            crate::handlers::credit_memo::ApplyCreditMemoRequest,
+            crate::handlers::vendor_credit::ApplyVendorCreditRequest,
+            crate::handlers::estimate::UpdateEstimateStatusRequest,
+            crate::handlers::estimate::ExpireEstimatesRequest,
             crate::handlers::import::ImportResultResponse,
             crate::handlers::import::ImportErrorResponse,
         )
@@ -198,6 +553,22 @@ use crate::utils::{ApiResponse, HealthResponse};
         (name = "bills", description = "Bill management (Accounts Payable)"),
         (name = "import", description = "Data import from CSV files"),
         (name = "reporting", description = "Financial reporting and analysis"),
+        (name = "inventory", description = "Items, perpetual inventory and stock adjustments"),
+        (name = "taxes", description = "Sales tax codes, rates and returns (GST/HST/PST/QST)"),
+        (name = "credit-memos", description = "Customer credit memos, credit applications and refunds"),
+        (name = "statements", description = "Customer statements (open-item and balance-forward)"),
+        (name = "estimates", description = "Estimates (quotes) and conversion to invoices"),
+        (name = "purchase-orders", description = "Purchase orders, conversion to bills and committed spend"),
+        (name = "vendor-credits", description = "Vendor credits applied against bills"),
+        (name = "email", description = "Emailing invoices and statements, templates and send history"),
+        (name = "dunning", description = "Overdue marking and reminder schedules"),
+        (name = "finance-charges", description = "Interest on overdue invoices"),
+        (name = "write-offs", description = "Bad debt write-offs and recoveries"),
+        (name = "numbering", description = "Document numbering sequences"),
+        (name = "billable-expenses", description = "Recharging billable bill lines to customers"),
+        (name = "dimensions", description = "Projects, classes, departments and other reporting dimensions"),
+        (name = "companies", description = "Companies, their members and switching the active company"),
+        (name = "users", description = "User administration: invitations, roles and disabling accounts (admin only)"),
     ),
     info(
         title = "LedgerForge API",
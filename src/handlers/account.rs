@@ -6,6 +6,7 @@ use serde::Deserialize;
 use uuid::Uuid;
 
 use crate::models::{Account, AccountType, CreateAccountRequest, UpdateAccountRequest};
+use crate::middleware::ActiveCompany;
 use crate::routes::AppState;
 use crate::utils::{created, no_content, success, ApiResponse, Result};
 
@@ -40,6 +41,7 @@ pub struct ListAccountsQuery {
 )]
 pub async fn list_accounts(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Query(params): Query<ListAccountsQuery>,
 ) -> Result<impl axum::response::IntoResponse> {
     // Parse account type if provided
@@ -51,6 +53,7 @@ pub async fn list_accounts(
 
     let accounts = state
         .account_service
+        .for_company(Some(company_id))
         .list_accounts(
             &state.pool,
             account_type,
@@ -79,9 +82,10 @@ pub async fn list_accounts(
 )]
 pub async fn create_account(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Json(req): Json<CreateAccountRequest>,
 ) -> Result<impl axum::response::IntoResponse> {
-    let account = state.account_service.create_account(&state.pool, req).await?;
+    let account = state.account_service.for_company(Some(company_id)).create_account(&state.pool, req).await?;
     Ok(created(account))
 }
 
@@ -103,9 +107,10 @@ pub async fn create_account(
 )]
 pub async fn get_account(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Path(id): Path<Uuid>,
 ) -> Result<impl axum::response::IntoResponse> {
-    let account = state.account_service.get_account_by_id(&state.pool, id).await?;
+    let account = state.account_service.for_company(Some(company_id)).get_account_by_id(&state.pool, id).await?;
     Ok(success(account))
 }
 
@@ -129,11 +134,13 @@ pub async fn get_account(
 )]
 pub async fn update_account(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Path(id): Path<Uuid>,
     Json(req): Json<UpdateAccountRequest>,
 ) -> Result<impl axum::response::IntoResponse> {
     let account = state
         .account_service
+        .for_company(Some(company_id))
         .update_account(&state.pool, id, req)
         .await?;
     Ok(success(account))
@@ -158,10 +165,12 @@ pub async fn update_account(
 )]
 pub async fn deactivate_account(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Path(id): Path<Uuid>,
 ) -> Result<impl axum::response::IntoResponse> {
     state
         .account_service
+        .for_company(Some(company_id))
         .deactivate_account(&state.pool, id)
         .await?;
     Ok(no_content())
@@ -185,10 +194,12 @@ pub async fn deactivate_account(
 )]
 pub async fn get_account_hierarchy(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Path(id): Path<Uuid>,
 ) -> Result<impl axum::response::IntoResponse> {
     let hierarchy = state
         .account_service
+        .for_company(Some(company_id))
         .get_account_hierarchy(&state.pool, id)
         .await?;
     Ok(success(hierarchy))
@@ -1,11 +1,18 @@
 use axum::{extract::State, Json};
 
 #[allow(unused_imports)]
-use crate::models::{AuthResponse, CreateUserRequest, LoginRequest, User, UserResponse};
+use crate::models::{
+    AuthResponse, ChangePasswordRequest, CreateUserRequest, DisableMfaRequest, ForgotPasswordRequest, LoginRequest,
+    LoginResponse, MfaCodeRequest, MfaLoginRequest, MfaPolicy, MfaSetupResponse,
+    MfaStatusResponse, RecoveryCodesResponse, ResetPasswordRequest, User, UserResponse,
+};
+use crate::middleware::{ActiveCompany, AuthUser};
 use crate::routes::AppState;
-use crate::utils::{created, success, ApiResponse, AppError, Result};
+use crate::services::auth::LoginOutcome;
+use crate::services::CompanyService;
+use crate::utils::{created, no_content, success, ApiResponse, AppError, Result};
 
-/// Register a new user
+/// Sign up. Depending on `SELF_REGISTRATION` this is disabled or only creates viewers.
 #[utoipa::path(
     post,
     path = "/api/v1/auth/register",
@@ -14,6 +21,7 @@ use crate::utils::{created, success, ApiResponse, AppError, Result};
     responses(
         (status = 201, description = "User registered successfully", body = ApiResponse<AuthResponse>),
         (status = 400, description = "Invalid request data"),
+        (status = 403, description = "Self-registration is disabled or the role isn't allowed"),
         (status = 409, description = "User already exists")
     )
 )]
@@ -22,11 +30,11 @@ pub async fn register(
     Json(req): Json<CreateUserRequest>,
 ) -> Result<impl axum::response::IntoResponse> {
     // Register user
-    let user = state.auth_service.register(&state.pool, req).await?;
+    let user = state.auth_service.self_register(&state.pool, req).await?;
 
-    // Generate tokens
-    let access_token = state.auth_service.generate_access_token(&user)?;
-    let refresh_token = state.auth_service.generate_refresh_token(&user)?;
+    // Generate tokens; a new user belongs to no company yet
+    let access_token = state.auth_service.generate_access_token(&user, None)?;
+    let refresh_token = state.auth_service.generate_refresh_token(&state.pool, &user, None).await?;
 
     // Create response
     let response = AuthResponse {
@@ -38,14 +46,14 @@ pub async fn register(
     Ok(created(response))
 }
 
-/// Login user
+/// Login user. Users with two-factor authentication get a challenge token instead of tokens.
 #[utoipa::path(
     post,
     path = "/api/v1/auth/login",
     tag = "auth",
     request_body = LoginRequest,
     responses(
-        (status = 200, description = "Login successful", body = ApiResponse<AuthResponse>),
+        (status = 200, description = "Login successful, or a second factor is needed", body = ApiResponse<LoginResponse>),
         (status = 401, description = "Invalid credentials"),
         (status = 404, description = "User not found")
     )
@@ -53,25 +61,49 @@ pub async fn register(
 pub async fn login(
     State(state): State<AppState>,
     Json(req): Json<LoginRequest>,
-) -> Result<ApiResponse<AuthResponse>> {
+) -> Result<ApiResponse<LoginResponse>> {
     // Authenticate user
-    let user = state.auth_service.login(&state.pool, req).await?;
+    let user = match state.auth_service.login(&state.pool, req).await? {
+        LoginOutcome::Authenticated(user) => user,
+        LoginOutcome::MfaRequired(challenge) => return Ok(success(LoginResponse::MfaRequired(challenge))),
+    };
 
-    // Generate tokens
-    let access_token = state.auth_service.generate_access_token(&user)?;
-    let refresh_token = state.auth_service.generate_refresh_token(&user)?;
+    Ok(success(LoginResponse::Authenticated(start_session(&state, user).await?)))
+}
 
-    // Create response
-    let response = AuthResponse {
+/// Finish a two-factor login with the challenge token and a TOTP or recovery code
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/mfa/verify",
+    tag = "auth",
+    request_body = MfaLoginRequest,
+    responses(
+        (status = 200, description = "Login successful", body = ApiResponse<AuthResponse>),
+        (status = 401, description = "Invalid or expired challenge token, or wrong code")
+    )
+)]
+pub async fn verify_mfa_login(
+    State(state): State<AppState>,
+    Json(req): Json<MfaLoginRequest>,
+) -> Result<ApiResponse<AuthResponse>> {
+    let user = state.auth_service.complete_mfa_login(&state.pool, req).await?;
+    Ok(success(start_session(&state, user).await?))
+}
+
+/// Generate tokens for a logged in user, starting in the user's first company
+async fn start_session(state: &AppState, user: User) -> Result<AuthResponse> {
+    let company_id = CompanyService::default_company(&state.pool, user.id).await?;
+    let access_token = state.auth_service.generate_access_token(&user, company_id)?;
+    let refresh_token = state.auth_service.generate_refresh_token(&state.pool, &user, company_id).await?;
+
+    Ok(AuthResponse {
         access_token,
         refresh_token,
         user: user.into(),
-    };
-
-    Ok(success(response))
+    })
 }
 
-/// Refresh access token using refresh token
+/// Exchange a refresh token for a new access token; the refresh token is rotated
 #[utoipa::path(
     post,
     path = "/api/v1/auth/refresh",
@@ -79,28 +111,303 @@ pub async fn login(
     request_body = RefreshTokenRequest,
     responses(
         (status = 200, description = "Token refreshed successfully", body = ApiResponse<TokenResponse>),
-        (status = 401, description = "Invalid or expired refresh token")
+        (status = 401, description = "Invalid, expired, revoked or reused refresh token")
     )
 )]
 pub async fn refresh_token(
     State(state): State<AppState>,
     Json(payload): Json<RefreshTokenRequest>,
 ) -> Result<ApiResponse<TokenResponse>> {
-    // Validate refresh token
-    let token_data = state.auth_service.validate_token(&payload.refresh_token)?;
+    // Use up the refresh token and get the next one in its family
+    let rotated = state.auth_service.rotate_refresh_token(&state.pool, &payload.refresh_token).await?;
 
-    // Get user from token claims
-    let user_id = uuid::Uuid::parse_str(&token_data.claims.sub)
-        .map_err(|_| AppError::InvalidToken)?;
+    // Generate new access token for the same company
+    let access_token = state.auth_service.generate_access_token(&rotated.user, rotated.company_id)?;
 
-    let user = state.auth_service.get_user_by_id(&state.pool, user_id).await?;
+    let response = TokenResponse {
+        access_token,
+        refresh_token: rotated.refresh_token,
+    };
 
-    // Generate new access token
-    let access_token = state.auth_service.generate_access_token(&user)?;
+    Ok(success(response))
+}
 
-    let response = TokenResponse { access_token };
+/// Log out: revoke the session of a refresh token
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/logout",
+    tag = "auth",
+    request_body = RefreshTokenRequest,
+    responses(
+        (status = 204, description = "Logged out"),
+        (status = 401, description = "Unauthorized or unknown refresh token")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn logout(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Json(payload): Json<RefreshTokenRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    state.auth_service.revoke_refresh_token(&state.pool, user.id, &payload.refresh_token).await?;
+    Ok(no_content())
+}
 
-    Ok(success(response))
+/// Log out everywhere: revoke every refresh token of the current user
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/logout-all",
+    tag = "auth",
+    responses(
+        (status = 204, description = "Logged out of all sessions"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn logout_all(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+) -> Result<impl axum::response::IntoResponse> {
+    state.auth_service.revoke_all_refresh_tokens(&state.pool, user.id).await?;
+    Ok(no_content())
+}
+
+/// Change the current user's password. Every session is logged out; the response carries new tokens.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/change-password",
+    tag = "auth",
+    request_body = ChangePasswordRequest,
+    responses(
+        (status = 200, description = "Password changed", body = ApiResponse<AuthResponse>),
+        (status = 400, description = "New password doesn't meet the password policy"),
+        (status = 401, description = "Unauthorized or wrong current password")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn change_password(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    active_company: Option<ActiveCompany>,
+    Json(req): Json<ChangePasswordRequest>,
+) -> Result<ApiResponse<AuthResponse>> {
+    let user = state.auth_service.change_password(&state.pool, user.id, req).await?;
+
+    // Start a new session in the same company
+    let company_id = active_company.map(|ActiveCompany(company_id)| company_id);
+    let access_token = state.auth_service.generate_access_token(&user, company_id)?;
+    let refresh_token = state.auth_service.generate_refresh_token(&state.pool, &user, company_id).await?;
+
+    Ok(success(AuthResponse {
+        access_token,
+        refresh_token,
+        user: user.into(),
+    }))
+}
+
+/// Email a password reset token. The response is the same whether or not the address has an account.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/forgot-password",
+    tag = "auth",
+    request_body = ForgotPasswordRequest,
+    responses(
+        (status = 204, description = "Reset email sent if the address belongs to an account"),
+        (status = 400, description = "Invalid email address")
+    )
+)]
+pub async fn forgot_password(
+    State(state): State<AppState>,
+    Json(req): Json<ForgotPasswordRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    state.auth_service.request_password_reset(&state.pool, req).await?;
+    Ok(no_content())
+}
+
+/// Choose a new password with a reset token; every session is logged out
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/reset-password",
+    tag = "auth",
+    request_body = ResetPasswordRequest,
+    responses(
+        (status = 204, description = "Password reset"),
+        (status = 400, description = "New password doesn't meet the password policy"),
+        (status = 401, description = "Invalid, used or expired reset token")
+    )
+)]
+pub async fn reset_password(
+    State(state): State<AppState>,
+    Json(req): Json<ResetPasswordRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    state.auth_service.reset_password(&state.pool, req).await?;
+    Ok(no_content())
+}
+
+/// Two-factor status of the current user
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/mfa",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Two-factor status", body = ApiResponse<MfaStatusResponse>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn mfa_status(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+) -> Result<ApiResponse<MfaStatusResponse>> {
+    let status = state.auth_service.mfa_status(&state.pool, &user).await?;
+    Ok(success(status))
+}
+
+/// Start two-factor setup: a new TOTP secret and its provisioning URI for a QR code
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/mfa/setup",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Secret created, confirm it with a code", body = ApiResponse<MfaSetupResponse>),
+        (status = 401, description = "Unauthorized"),
+        (status = 409, description = "Two-factor authentication is already enabled")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn setup_mfa(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+) -> Result<ApiResponse<MfaSetupResponse>> {
+    let setup = state.auth_service.begin_mfa_enrollment(&state.pool, &user).await?;
+    Ok(success(setup))
+}
+
+/// Turn two-factor authentication on with a code from the authenticator app
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/mfa/confirm",
+    tag = "auth",
+    request_body = MfaCodeRequest,
+    responses(
+        (status = 200, description = "Enabled; the recovery codes are only shown now", body = ApiResponse<RecoveryCodesResponse>),
+        (status = 400, description = "Setup wasn't started"),
+        (status = 401, description = "Unauthorized or wrong code")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn confirm_mfa(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Json(req): Json<MfaCodeRequest>,
+) -> Result<ApiResponse<RecoveryCodesResponse>> {
+    let recovery_codes = state.auth_service.confirm_mfa_enrollment(&state.pool, user.id, req).await?;
+    Ok(success(RecoveryCodesResponse { recovery_codes }))
+}
+
+/// Replace the recovery codes
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/mfa/recovery-codes",
+    tag = "auth",
+    request_body = MfaCodeRequest,
+    responses(
+        (status = 200, description = "New recovery codes; the old ones no longer work", body = ApiResponse<RecoveryCodesResponse>),
+        (status = 400, description = "Two-factor authentication isn't enabled"),
+        (status = 401, description = "Unauthorized or wrong code")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn regenerate_recovery_codes(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Json(req): Json<MfaCodeRequest>,
+) -> Result<ApiResponse<RecoveryCodesResponse>> {
+    let recovery_codes = state.auth_service.regenerate_recovery_codes(&state.pool, user.id, req).await?;
+    Ok(success(RecoveryCodesResponse { recovery_codes }))
+}
+
+/// Turn two-factor authentication off
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/mfa/disable",
+    tag = "auth",
+    request_body = DisableMfaRequest,
+    responses(
+        (status = 204, description = "Two-factor authentication disabled"),
+        (status = 400, description = "Two-factor authentication isn't enabled"),
+        (status = 401, description = "Unauthorized, wrong password or wrong code"),
+        (status = 403, description = "The user's role requires two-factor authentication")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn disable_mfa(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Json(req): Json<DisableMfaRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    state.auth_service.disable_mfa(&state.pool, &user, req).await?;
+    Ok(no_content())
+}
+
+/// Roles that must use two-factor authentication
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/mfa/policy",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Two-factor policy", body = ApiResponse<MfaPolicy>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_mfa_policy(
+    State(state): State<AppState>,
+) -> Result<ApiResponse<MfaPolicy>> {
+    let policy = state.auth_service.mfa_policy(&state.pool).await?;
+    Ok(success(policy))
+}
+
+/// Set the roles that must use two-factor authentication (admin only). Their users
+/// can only set it up until they have.
+#[utoipa::path(
+    put,
+    path = "/api/v1/auth/mfa/policy",
+    tag = "auth",
+    request_body = MfaPolicy,
+    responses(
+        (status = 200, description = "Two-factor policy updated", body = ApiResponse<MfaPolicy>),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Admins only")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_mfa_policy(
+    State(state): State<AppState>,
+    Json(policy): Json<MfaPolicy>,
+) -> Result<ApiResponse<MfaPolicy>> {
+    let policy = state.auth_service.set_mfa_policy(&state.pool, policy).await?;
+    Ok(success(policy))
 }
 
 /// Get current user profile (requires authentication)
@@ -134,11 +441,9 @@ pub async fn me(
 
     // Validate token and extract claims
     let token_data = state.auth_service.validate_token(token)?;
-    let user_id = uuid::Uuid::parse_str(&token_data.claims.sub)
-        .map_err(|_| AppError::InvalidToken)?;
 
     // Fetch user from database
-    let user = state.auth_service.get_user_by_id(&state.pool, user_id).await?;
+    let user = state.auth_service.authenticated_user(&state.pool, &token_data.claims).await?;
 
     Ok(success(user.into()))
 }
@@ -151,4 +456,6 @@ pub struct RefreshTokenRequest {
 #[derive(serde::Serialize, utoipa::ToSchema)]
 pub struct TokenResponse {
     pub access_token: String,
+    /// Replaces the refresh token that was presented, which can't be used again
+    pub refresh_token: String,
 }
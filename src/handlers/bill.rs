@@ -7,6 +7,7 @@ use uuid::Uuid;
 use validator::Validate;
 
 use crate::models::{Bill, BillStatus, CreateBillRequest, BillWithLineItems};
+use crate::middleware::ActiveCompany;
 use crate::routes::AppState;
 use crate::utils::{created, success, ApiResponse, AppError, Result};
 
@@ -47,12 +48,14 @@ pub struct UpdateBillStatusRequest {
 )]
 pub async fn create_bill(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Json(req): Json<CreateBillRequest>,
 ) -> Result<impl axum::response::IntoResponse> {
     // Validate request
     req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
 
     let bill = state.bill_service
+        .for_company(Some(company_id))
         .create_bill(&state.pool, req)
         .await?;
 
@@ -80,9 +83,11 @@ pub async fn create_bill(
 )]
 pub async fn list_bills(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Query(params): Query<ListBillsQuery>,
 ) -> Result<impl axum::response::IntoResponse> {
     let bills = state.bill_service
+        .for_company(Some(company_id))
         .list_bills(
             &state.pool,
             params.vendor_id,
@@ -114,9 +119,11 @@ pub async fn list_bills(
 )]
 pub async fn get_bill(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Path(bill_id): Path<Uuid>,
 ) -> Result<impl axum::response::IntoResponse> {
     let bill = state.bill_service
+        .for_company(Some(company_id))
         .get_bill_by_id(&state.pool, bill_id)
         .await?;
 
@@ -147,10 +154,12 @@ pub async fn get_bill(
 )]
 pub async fn update_bill_status(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Path(bill_id): Path<Uuid>,
     Json(req): Json<UpdateBillStatusRequest>,
 ) -> Result<impl axum::response::IntoResponse> {
     let bill = state.bill_service
+        .for_company(Some(company_id))
         .update_bill_status(&state.pool, bill_id, req.status)
         .await?;
 
@@ -177,9 +186,11 @@ pub async fn update_bill_status(
 )]
 pub async fn delete_bill(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Path(bill_id): Path<Uuid>,
 ) -> Result<impl axum::response::IntoResponse> {
     state.bill_service
+        .for_company(Some(company_id))
         .delete_bill(&state.pool, bill_id)
         .await?;
 
@@ -205,9 +216,11 @@ pub async fn delete_bill(
 )]
 pub async fn get_vendor_bills(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Path(vendor_id): Path<Uuid>,
 ) -> Result<impl axum::response::IntoResponse> {
     let bills = state.bill_service
+        .for_company(Some(company_id))
         .get_vendor_bills(&state.pool, vendor_id)
         .await?;
 
@@ -229,8 +242,10 @@ pub async fn get_vendor_bills(
 )]
 pub async fn get_overdue_bills(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
 ) -> Result<impl axum::response::IntoResponse> {
     let bills = state.bill_service
+        .for_company(Some(company_id))
         .get_overdue_bills(&state.pool)
         .await?;
 
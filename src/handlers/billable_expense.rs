@@ -0,0 +1,73 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use uuid::Uuid;
+
+use crate::models::{UnbilledExpense, BillExpensesRequest, BilledExpenses};
+use crate::middleware::ActiveCompany;
+use crate::routes::AppState;
+use crate::utils::{success, ApiResponse, Result};
+
+/// List a customer's billable expenses that haven't been invoiced yet
+#[utoipa::path(
+    get,
+    path = "/api/v1/customers/{id}/unbilled-expenses",
+    tag = "billable-expenses",
+    params(
+        ("id" = Uuid, Path, description = "Customer ID")
+    ),
+    responses(
+        (status = 200, description = "Unbilled expenses retrieved successfully", body = ApiResponse<Vec<UnbilledExpense>>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_unbilled_expenses(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(customer_id): Path<Uuid>,
+) -> Result<impl axum::response::IntoResponse> {
+    let expenses = state.billable_expense_service
+        .for_company(Some(company_id))
+        .list_unbilled_expenses(&state.pool, customer_id)
+        .await?;
+
+    Ok(success(expenses))
+}
+
+/// Bill selected expenses to the customer on a new or draft invoice
+#[utoipa::path(
+    post,
+    path = "/api/v1/customers/{id}/bill-expenses",
+    tag = "billable-expenses",
+    params(
+        ("id" = Uuid, Path, description = "Customer ID")
+    ),
+    request_body = BillExpensesRequest,
+    responses(
+        (status = 200, description = "Expenses added to the invoice", body = ApiResponse<BilledExpenses>),
+        (status = 400, description = "Expense not billable, already billed, or invoice not a draft"),
+        (status = 404, description = "Bill line or invoice not found"),
+        (status = 409, description = "Invoice number already exists"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn bill_expenses(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(customer_id): Path<Uuid>,
+    Json(req): Json<BillExpensesRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let billed = state.billable_expense_service
+        .for_company(Some(company_id))
+        .bill_expenses(&state.pool, customer_id, req)
+        .await?;
+
+    Ok(success(billed))
+}
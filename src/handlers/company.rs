@@ -0,0 +1,259 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use uuid::Uuid;
+
+use crate::models::{
+    AddCompanyMemberRequest, AuthResponse, Company, CompanyMember, CreateCompanyRequest, UpdateCompanyRequest,
+};
+use crate::middleware::AuthUser;
+use crate::routes::AppState;
+use crate::services::CompanyService;
+use crate::utils::{created, no_content, success, ApiResponse, AppError, Result};
+
+/// Create a company; the caller becomes its first member
+#[utoipa::path(
+    post,
+    path = "/api/v1/companies",
+    tag = "companies",
+    request_body = CreateCompanyRequest,
+    responses(
+        (status = 201, description = "Company created successfully", body = ApiResponse<Company>),
+        (status = 400, description = "Invalid request data"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_company(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Json(req): Json<CreateCompanyRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let company = state.company_service.create_company(&state.pool, user.id, req).await?;
+    Ok(created(company))
+}
+
+/// List the companies the caller is a member of
+#[utoipa::path(
+    get,
+    path = "/api/v1/companies",
+    tag = "companies",
+    responses(
+        (status = 200, description = "Companies retrieved successfully", body = ApiResponse<Vec<Company>>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_companies(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+) -> Result<impl axum::response::IntoResponse> {
+    let companies = state.company_service.list_companies(&state.pool, user.id).await?;
+    Ok(success(companies))
+}
+
+/// Get a company by ID
+#[utoipa::path(
+    get,
+    path = "/api/v1/companies/{id}",
+    tag = "companies",
+    params(
+        ("id" = Uuid, Path, description = "Company ID")
+    ),
+    responses(
+        (status = 200, description = "Company retrieved successfully", body = ApiResponse<Company>),
+        (status = 404, description = "Company not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_company(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<impl axum::response::IntoResponse> {
+    let company = state.company_service.get_company(&state.pool, user.id, id).await?;
+    Ok(success(company))
+}
+
+/// Update a company's name or address
+#[utoipa::path(
+    put,
+    path = "/api/v1/companies/{id}",
+    tag = "companies",
+    params(
+        ("id" = Uuid, Path, description = "Company ID")
+    ),
+    request_body = UpdateCompanyRequest,
+    responses(
+        (status = 200, description = "Company updated successfully", body = ApiResponse<Company>),
+        (status = 400, description = "Invalid request data"),
+        (status = 404, description = "Company not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_company(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateCompanyRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let company = state.company_service.update_company(&state.pool, user.id, id, req).await?;
+    Ok(success(company))
+}
+
+/// Delete a company that has no records
+#[utoipa::path(
+    delete,
+    path = "/api/v1/companies/{id}",
+    tag = "companies",
+    params(
+        ("id" = Uuid, Path, description = "Company ID")
+    ),
+    responses(
+        (status = 204, description = "Company deleted successfully"),
+        (status = 404, description = "Company not found"),
+        (status = 409, description = "Company has records"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_company(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<impl axum::response::IntoResponse> {
+    state.company_service.delete_company(&state.pool, user.id, id).await?;
+    Ok(no_content())
+}
+
+/// List the users with access to a company
+#[utoipa::path(
+    get,
+    path = "/api/v1/companies/{id}/members",
+    tag = "companies",
+    params(
+        ("id" = Uuid, Path, description = "Company ID")
+    ),
+    responses(
+        (status = 200, description = "Members retrieved successfully", body = ApiResponse<Vec<CompanyMember>>),
+        (status = 404, description = "Company not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_company_members(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<impl axum::response::IntoResponse> {
+    let members = state.company_service.list_members(&state.pool, user.id, id).await?;
+    Ok(success(members))
+}
+
+/// Give a user access to a company
+#[utoipa::path(
+    post,
+    path = "/api/v1/companies/{id}/members",
+    tag = "companies",
+    params(
+        ("id" = Uuid, Path, description = "Company ID")
+    ),
+    request_body = AddCompanyMemberRequest,
+    responses(
+        (status = 201, description = "Member added successfully", body = ApiResponse<CompanyMember>),
+        (status = 404, description = "Company or user not found"),
+        (status = 409, description = "User is already a member"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn add_company_member(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(id): Path<Uuid>,
+    Json(req): Json<AddCompanyMemberRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let member = state.company_service.add_member(&state.pool, user.id, id, req).await?;
+    Ok(created(member))
+}
+
+/// Take a user's access to a company away
+#[utoipa::path(
+    delete,
+    path = "/api/v1/companies/{id}/members/{user_id}",
+    tag = "companies",
+    params(
+        ("id" = Uuid, Path, description = "Company ID"),
+        ("user_id" = Uuid, Path, description = "User ID of the member")
+    ),
+    responses(
+        (status = 204, description = "Member removed successfully"),
+        (status = 400, description = "The last member can't be removed"),
+        (status = 404, description = "Company or member not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn remove_company_member(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path((id, member_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl axum::response::IntoResponse> {
+    state.company_service.remove_member(&state.pool, user.id, id, member_id).await?;
+    Ok(no_content())
+}
+
+/// Make a company the active one; the new tokens carry it
+#[utoipa::path(
+    post,
+    path = "/api/v1/companies/{id}/switch",
+    tag = "companies",
+    params(
+        ("id" = Uuid, Path, description = "Company ID")
+    ),
+    responses(
+        (status = 200, description = "Switched company", body = ApiResponse<AuthResponse>),
+        (status = 403, description = "Not a member of the company"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn switch_company(
+    State(state): State<AppState>,
+    AuthUser(user): AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<ApiResponse<AuthResponse>> {
+    if !CompanyService::is_member(&state.pool, id, user.id).await? {
+        return Err(AppError::Forbidden("You are not a member of this company".to_string()));
+    }
+
+    let access_token = state.auth_service.generate_access_token(&user, Some(id))?;
+    let refresh_token = state.auth_service.generate_refresh_token(&state.pool, &user, Some(id)).await?;
+
+    Ok(success(AuthResponse {
+        access_token,
+        refresh_token,
+        user: user.into(),
+    }))
+}
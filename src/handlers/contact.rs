@@ -6,6 +6,7 @@ use serde::Deserialize;
 use uuid::Uuid;
 
 use crate::models::{Contact, ContactType, CreateContactRequest, UpdateContactRequest};
+use crate::middleware::ActiveCompany;
 use crate::routes::AppState;
 use crate::utils::{created, no_content, success, ApiResponse, AppError, Result};
 
@@ -15,8 +16,6 @@ pub struct ListContactsQuery {
     #[serde(default)]
     pub contact_type: Option<String>,
     #[serde(default)]
-    pub company_id: Option<Uuid>,
-    #[serde(default)]
     pub limit: Option<i64>,
 }
 
@@ -27,7 +26,6 @@ pub struct ListContactsQuery {
     tag = "contacts",
     params(
         ("contact_type" = Option<String>, Query, description = "Filter by contact type (Customer, Vendor, Employee)"),
-        ("company_id" = Option<String>, Query, description = "Filter by company ID"),
         ("limit" = Option<i64>, Query, description = "Limit number of results")
     ),
     responses(
@@ -40,6 +38,7 @@ pub struct ListContactsQuery {
 )]
 pub async fn list_contacts(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Query(params): Query<ListContactsQuery>,
 ) -> Result<impl axum::response::IntoResponse> {
     // Parse contact type if provided
@@ -51,10 +50,10 @@ pub async fn list_contacts(
 
     let contacts = state
         .contact_service
+        .for_company(Some(company_id))
         .list_contacts(
             &state.pool,
             contact_type,
-            params.company_id,
             params.limit,
         )
         .await?;
@@ -78,9 +77,10 @@ pub async fn list_contacts(
 )]
 pub async fn create_contact(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Json(req): Json<CreateContactRequest>,
 ) -> Result<impl axum::response::IntoResponse> {
-    let contact = state.contact_service.create_contact(&state.pool, req).await?;
+    let contact = state.contact_service.for_company(Some(company_id)).create_contact(&state.pool, req).await?;
     Ok(created(contact))
 }
 
@@ -102,9 +102,10 @@ pub async fn create_contact(
 )]
 pub async fn get_contact(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Path(id): Path<Uuid>,
 ) -> Result<impl axum::response::IntoResponse> {
-    let contact = state.contact_service.get_contact_by_id(&state.pool, id).await?;
+    let contact = state.contact_service.for_company(Some(company_id)).get_contact_by_id(&state.pool, id).await?;
     Ok(success(contact))
 }
 
@@ -128,11 +129,13 @@ pub async fn get_contact(
 )]
 pub async fn update_contact(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Path(id): Path<Uuid>,
     Json(req): Json<UpdateContactRequest>,
 ) -> Result<impl axum::response::IntoResponse> {
     let contact = state
         .contact_service
+        .for_company(Some(company_id))
         .update_contact(&state.pool, id, req)
         .await?;
     Ok(success(contact))
@@ -157,9 +160,10 @@ pub async fn update_contact(
 )]
 pub async fn delete_contact(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Path(id): Path<Uuid>,
 ) -> Result<impl axum::response::IntoResponse> {
-    state.contact_service.delete_contact(&state.pool, id).await?;
+    state.contact_service.for_company(Some(company_id)).delete_contact(&state.pool, id).await?;
     Ok(no_content())
 }
 
@@ -177,8 +181,9 @@ pub async fn delete_contact(
 )]
 pub async fn get_customers(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
 ) -> Result<impl axum::response::IntoResponse> {
-    let customers = state.contact_service.get_customers(&state.pool).await?;
+    let customers = state.contact_service.for_company(Some(company_id)).get_customers(&state.pool).await?;
     Ok(success(customers))
 }
 
@@ -196,8 +201,9 @@ pub async fn get_customers(
 )]
 pub async fn get_vendors(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
 ) -> Result<impl axum::response::IntoResponse> {
-    let vendors = state.contact_service.get_vendors(&state.pool).await?;
+    let vendors = state.contact_service.for_company(Some(company_id)).get_vendors(&state.pool).await?;
     Ok(success(vendors))
 }
 
@@ -215,8 +221,9 @@ pub async fn get_vendors(
 )]
 pub async fn get_employees(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
 ) -> Result<impl axum::response::IntoResponse> {
-    let employees = state.contact_service.get_employees(&state.pool).await?;
+    let employees = state.contact_service.for_company(Some(company_id)).get_employees(&state.pool).await?;
     Ok(success(employees))
 }
 
@@ -0,0 +1,247 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{
+    CreditMemo, CreditMemoStatus, CreditMemoWithLineItems, CreateCreditMemoRequest,
+    CreditMemoApplicationRequest, CustomerRefund, CreateCustomerRefundRequest,
+};
+use crate::middleware::ActiveCompany;
+use crate::routes::AppState;
+use crate::utils::{created, success, ApiResponse, AppError, Result};
+
+/// Query parameters for listing credit memos
+#[derive(Debug, Deserialize)]
+pub struct ListCreditMemosQuery {
+    #[serde(default)]
+    pub customer_id: Option<Uuid>,
+    #[serde(default)]
+    pub status: Option<CreditMemoStatus>,
+}
+
+/// Apply credit memo request
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct ApplyCreditMemoRequest {
+    pub applications: Vec<CreditMemoApplicationRequest>,
+}
+
+/// Create a credit memo
+#[utoipa::path(
+    post,
+    path = "/api/v1/credit-memos",
+    tag = "credit-memos",
+    request_body = CreateCreditMemoRequest,
+    responses(
+        (status = 201, description = "Credit memo created successfully", body = ApiResponse<CreditMemoWithLineItems>),
+        (status = 400, description = "Invalid request data"),
+        (status = 409, description = "Credit memo number already exists"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_credit_memo(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Json(req): Json<CreateCreditMemoRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    // Validate request
+    req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let credit_memo = state.credit_memo_service
+        .for_company(Some(company_id))
+        .create_credit_memo(&state.pool, req)
+        .await?;
+
+    Ok(created(credit_memo))
+}
+
+/// List credit memos
+#[utoipa::path(
+    get,
+    path = "/api/v1/credit-memos",
+    tag = "credit-memos",
+    params(
+        ("customer_id" = Option<Uuid>, Query, description = "Filter by customer"),
+        ("status" = Option<String>, Query, description = "Filter by status (open, applied, void)")
+    ),
+    responses(
+        (status = 200, description = "Credit memos retrieved successfully", body = ApiResponse<Vec<CreditMemo>>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_credit_memos(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Query(params): Query<ListCreditMemosQuery>,
+) -> Result<impl axum::response::IntoResponse> {
+    let credit_memos = state.credit_memo_service
+        .for_company(Some(company_id))
+        .list_credit_memos(&state.pool, params.customer_id, params.status)
+        .await?;
+
+    Ok(success(credit_memos))
+}
+
+/// Get a credit memo with line items and applications
+#[utoipa::path(
+    get,
+    path = "/api/v1/credit-memos/{id}",
+    tag = "credit-memos",
+    params(
+        ("id" = Uuid, Path, description = "Credit memo ID")
+    ),
+    responses(
+        (status = 200, description = "Credit memo retrieved successfully", body = ApiResponse<CreditMemoWithLineItems>),
+        (status = 404, description = "Credit memo not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_credit_memo(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(credit_memo_id): Path<Uuid>,
+) -> Result<impl axum::response::IntoResponse> {
+    let credit_memo = state.credit_memo_service
+        .for_company(Some(company_id))
+        .get_credit_memo(&state.pool, credit_memo_id)
+        .await?;
+
+    Ok(success(credit_memo))
+}
+
+/// Apply a credit memo to open invoices
+#[utoipa::path(
+    put,
+    path = "/api/v1/credit-memos/{id}/apply",
+    tag = "credit-memos",
+    params(
+        ("id" = Uuid, Path, description = "Credit memo ID")
+    ),
+    request_body = ApplyCreditMemoRequest,
+    responses(
+        (status = 200, description = "Credit memo applied successfully", body = ApiResponse<CreditMemo>),
+        (status = 400, description = "Invalid request data"),
+        (status = 404, description = "Credit memo not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn apply_credit_memo(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(credit_memo_id): Path<Uuid>,
+    Json(req): Json<ApplyCreditMemoRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let credit_memo = state.credit_memo_service
+        .for_company(Some(company_id))
+        .apply_credit_memo(&state.pool, credit_memo_id, req.applications)
+        .await?;
+
+    Ok(success(credit_memo))
+}
+
+/// Void an unapplied credit memo
+#[utoipa::path(
+    post,
+    path = "/api/v1/credit-memos/{id}/void",
+    tag = "credit-memos",
+    params(
+        ("id" = Uuid, Path, description = "Credit memo ID")
+    ),
+    responses(
+        (status = 200, description = "Credit memo voided successfully", body = ApiResponse<CreditMemo>),
+        (status = 400, description = "Credit memo has been applied or refunded"),
+        (status = 404, description = "Credit memo not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn void_credit_memo(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(credit_memo_id): Path<Uuid>,
+) -> Result<impl axum::response::IntoResponse> {
+    let credit_memo = state.credit_memo_service
+        .for_company(Some(company_id))
+        .void_credit_memo(&state.pool, credit_memo_id)
+        .await?;
+
+    Ok(success(credit_memo))
+}
+
+/// Refund unapplied credit to a customer
+#[utoipa::path(
+    post,
+    path = "/api/v1/customer-refunds",
+    tag = "credit-memos",
+    request_body = CreateCustomerRefundRequest,
+    responses(
+        (status = 201, description = "Refund created successfully", body = ApiResponse<CustomerRefund>),
+        (status = 400, description = "Invalid request data or insufficient credit"),
+        (status = 404, description = "Credit memo or payment not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_customer_refund(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Json(req): Json<CreateCustomerRefundRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    // Validate request
+    req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let refund = state.credit_memo_service
+        .for_company(Some(company_id))
+        .create_refund(&state.pool, req)
+        .await?;
+
+    Ok(created(refund))
+}
+
+/// List refunds for a customer
+#[utoipa::path(
+    get,
+    path = "/api/v1/customers/{id}/refunds",
+    tag = "credit-memos",
+    params(
+        ("id" = Uuid, Path, description = "Customer ID")
+    ),
+    responses(
+        (status = 200, description = "Refunds retrieved successfully", body = ApiResponse<Vec<CustomerRefund>>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_customer_refunds(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(customer_id): Path<Uuid>,
+) -> Result<impl axum::response::IntoResponse> {
+    let refunds = state.credit_memo_service
+        .for_company(Some(company_id))
+        .list_customer_refunds(&state.pool, customer_id)
+        .await?;
+
+    Ok(success(refunds))
+}
@@ -0,0 +1,230 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use uuid::Uuid;
+
+use crate::models::{
+    DimensionType, DimensionValue, DimensionRequirement, DimensionTypeWithValues,
+    CreateDimensionTypeRequest, CreateDimensionValueRequest, UpdateDimensionValueRequest,
+    CreateDimensionRequirementRequest,
+};
+use crate::middleware::ActiveCompany;
+use crate::routes::AppState;
+use crate::utils::{created, no_content, success, ApiResponse, Result};
+
+/// Create a dimension type such as Project, Class or Department
+#[utoipa::path(
+    post,
+    path = "/api/v1/dimensions",
+    tag = "dimensions",
+    request_body = CreateDimensionTypeRequest,
+    responses(
+        (status = 201, description = "Dimension type created successfully", body = ApiResponse<DimensionType>),
+        (status = 400, description = "Invalid request data"),
+        (status = 409, description = "Dimension type code already exists"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_dimension_type(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Json(req): Json<CreateDimensionTypeRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let dimension_type = state.dimension_service
+        .for_company(Some(company_id))
+        .create_dimension_type(&state.pool, req)
+        .await?;
+
+    Ok(created(dimension_type))
+}
+
+/// List a company's dimension types
+#[utoipa::path(
+    get,
+    path = "/api/v1/dimensions",
+    tag = "dimensions",
+    responses(
+        (status = 200, description = "Dimension types retrieved successfully", body = ApiResponse<Vec<DimensionType>>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_dimension_types(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+) -> Result<impl axum::response::IntoResponse> {
+    let dimension_types = state.dimension_service
+        .for_company(Some(company_id))
+        .list_dimension_types(&state.pool)
+        .await?;
+
+    Ok(success(dimension_types))
+}
+
+/// Get a dimension type with its values and account requirements
+#[utoipa::path(
+    get,
+    path = "/api/v1/dimensions/{id}",
+    tag = "dimensions",
+    params(
+        ("id" = Uuid, Path, description = "Dimension type ID")
+    ),
+    responses(
+        (status = 200, description = "Dimension type retrieved successfully", body = ApiResponse<DimensionTypeWithValues>),
+        (status = 404, description = "Dimension type not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_dimension_type(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(id): Path<Uuid>,
+) -> Result<impl axum::response::IntoResponse> {
+    let dimension_type = state.dimension_service
+        .for_company(Some(company_id))
+        .get_dimension_type(&state.pool, id)
+        .await?;
+
+    Ok(success(dimension_type))
+}
+
+/// Add a value to a dimension type
+#[utoipa::path(
+    post,
+    path = "/api/v1/dimensions/{id}/values",
+    tag = "dimensions",
+    params(
+        ("id" = Uuid, Path, description = "Dimension type ID")
+    ),
+    request_body = CreateDimensionValueRequest,
+    responses(
+        (status = 201, description = "Dimension value created successfully", body = ApiResponse<DimensionValue>),
+        (status = 400, description = "Invalid request data"),
+        (status = 404, description = "Dimension type not found"),
+        (status = 409, description = "Dimension value code already exists"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_dimension_value(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(id): Path<Uuid>,
+    Json(req): Json<CreateDimensionValueRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let value = state.dimension_service
+        .for_company(Some(company_id))
+        .create_dimension_value(&state.pool, id, req)
+        .await?;
+
+    Ok(created(value))
+}
+
+/// Rename or deactivate a dimension value
+#[utoipa::path(
+    put,
+    path = "/api/v1/dimension-values/{id}",
+    tag = "dimensions",
+    params(
+        ("id" = Uuid, Path, description = "Dimension value ID")
+    ),
+    request_body = UpdateDimensionValueRequest,
+    responses(
+        (status = 200, description = "Dimension value updated successfully", body = ApiResponse<DimensionValue>),
+        (status = 400, description = "Invalid request data"),
+        (status = 404, description = "Dimension value not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_dimension_value(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateDimensionValueRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let value = state.dimension_service
+        .for_company(Some(company_id))
+        .update_dimension_value(&state.pool, id, req)
+        .await?;
+
+    Ok(success(value))
+}
+
+/// Require the dimension on lines posting to an account type or account
+#[utoipa::path(
+    post,
+    path = "/api/v1/dimensions/{id}/requirements",
+    tag = "dimensions",
+    params(
+        ("id" = Uuid, Path, description = "Dimension type ID")
+    ),
+    request_body = CreateDimensionRequirementRequest,
+    responses(
+        (status = 201, description = "Requirement added successfully", body = ApiResponse<DimensionRequirement>),
+        (status = 400, description = "Neither or both of account type and account given"),
+        (status = 404, description = "Dimension type not found"),
+        (status = 409, description = "Requirement already exists"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn add_dimension_requirement(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(id): Path<Uuid>,
+    Json(req): Json<CreateDimensionRequirementRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let requirement = state.dimension_service
+        .for_company(Some(company_id))
+        .add_requirement(&state.pool, id, req)
+        .await?;
+
+    Ok(created(requirement))
+}
+
+/// Remove an account requirement from a dimension type
+#[utoipa::path(
+    delete,
+    path = "/api/v1/dimensions/{id}/requirements/{requirement_id}",
+    tag = "dimensions",
+    params(
+        ("id" = Uuid, Path, description = "Dimension type ID"),
+        ("requirement_id" = Uuid, Path, description = "Requirement ID")
+    ),
+    responses(
+        (status = 204, description = "Requirement removed successfully"),
+        (status = 404, description = "Requirement not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn remove_dimension_requirement(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path((id, requirement_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl axum::response::IntoResponse> {
+    state.dimension_service
+        .for_company(Some(company_id))
+        .remove_requirement(&state.pool, id, requirement_id)
+        .await?;
+
+    Ok(no_content())
+}
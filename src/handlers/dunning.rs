@@ -0,0 +1,168 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::models::{
+    Contact, DunningLevel, DunningOptOutRequest, DunningRun, InvoiceReminder, RunDunningRequest,
+    SetDunningLevelsRequest,
+};
+use crate::middleware::ActiveCompany;
+use crate::routes::AppState;
+use crate::utils::{success, ApiResponse, Result};
+
+/// Query parameters for listing reminders
+#[derive(Debug, Deserialize)]
+pub struct ListRemindersQuery {
+    #[serde(default)]
+    pub invoice_id: Option<Uuid>,
+    #[serde(default)]
+    pub customer_id: Option<Uuid>,
+}
+
+/// Mark overdue invoices and send the dunning reminders they have reached
+#[utoipa::path(
+    post,
+    path = "/api/v1/dunning/run",
+    tag = "dunning",
+    request_body = RunDunningRequest,
+    responses(
+        (status = 200, description = "Dunning run completed", body = ApiResponse<DunningRun>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn run_dunning(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Json(req): Json<RunDunningRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let as_of_date = req.as_of_date.unwrap_or_else(|| chrono::Utc::now().date_naive());
+
+    let run = state.dunning_service
+        .for_company(Some(company_id))
+        .run(&state.pool, as_of_date)
+        .await?;
+
+    Ok(success(run))
+}
+
+/// Get the dunning schedule for a company
+#[utoipa::path(
+    get,
+    path = "/api/v1/dunning/levels",
+    tag = "dunning",
+    responses(
+        (status = 200, description = "Dunning schedule retrieved successfully", body = ApiResponse<Vec<DunningLevel>>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_dunning_levels(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+) -> Result<impl axum::response::IntoResponse> {
+    let levels = state.dunning_service
+        .for_company(Some(company_id))
+        .get_dunning_levels(&state.pool)
+        .await?;
+
+    Ok(success(levels))
+}
+
+/// Replace the dunning schedule for a company
+#[utoipa::path(
+    put,
+    path = "/api/v1/dunning/levels",
+    tag = "dunning",
+    request_body = SetDunningLevelsRequest,
+    responses(
+        (status = 200, description = "Dunning schedule saved successfully", body = ApiResponse<Vec<DunningLevel>>),
+        (status = 400, description = "Invalid request data"),
+        (status = 409, description = "Two levels at the same number of days"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn set_dunning_levels(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Json(req): Json<SetDunningLevelsRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let levels = state.dunning_service
+        .for_company(Some(company_id))
+        .set_dunning_levels(&state.pool, req)
+        .await?;
+
+    Ok(success(levels))
+}
+
+/// List dunning reminders
+#[utoipa::path(
+    get,
+    path = "/api/v1/dunning/reminders",
+    tag = "dunning",
+    params(
+        ("invoice_id" = Option<Uuid>, Query, description = "Filter by invoice"),
+        ("customer_id" = Option<Uuid>, Query, description = "Filter by customer")
+    ),
+    responses(
+        (status = 200, description = "Reminders retrieved successfully", body = ApiResponse<Vec<InvoiceReminder>>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_reminders(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Query(params): Query<ListRemindersQuery>,
+) -> Result<impl axum::response::IntoResponse> {
+    let reminders = state.dunning_service
+        .for_company(Some(company_id))
+        .list_reminders(&state.pool, params.invoice_id, params.customer_id)
+        .await?;
+
+    Ok(success(reminders))
+}
+
+/// Opt a customer out of or back into dunning reminders
+#[utoipa::path(
+    put,
+    path = "/api/v1/customers/{id}/dunning",
+    tag = "dunning",
+    params(
+        ("id" = Uuid, Path, description = "Customer ID")
+    ),
+    request_body = DunningOptOutRequest,
+    responses(
+        (status = 200, description = "Customer updated successfully", body = ApiResponse<Contact>),
+        (status = 404, description = "Customer not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn set_dunning_opt_out(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(customer_id): Path<Uuid>,
+    Json(req): Json<DunningOptOutRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let contact = state.dunning_service
+        .for_company(Some(company_id))
+        .set_opt_out(&state.pool, customer_id, req.opt_out)
+        .await?;
+
+    Ok(success(contact))
+}
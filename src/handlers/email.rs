@@ -0,0 +1,192 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{
+    EmailDelivery, EmailDocumentType, EmailTemplate, SendInvoiceRequest, SendStatementRequest,
+    UpdateEmailTemplateRequest,
+};
+use crate::middleware::ActiveCompany;
+use crate::routes::AppState;
+use crate::utils::{success, ApiResponse, AppError, Result};
+
+/// Query parameters for listing email deliveries
+#[derive(Debug, Deserialize)]
+pub struct ListEmailDeliveriesQuery {
+    #[serde(default)]
+    pub document_type: Option<EmailDocumentType>,
+    #[serde(default)]
+    pub document_id: Option<Uuid>,
+    #[serde(default)]
+    pub contact_id: Option<Uuid>,
+}
+
+/// Query parameters for fetching an email template
+#[derive(Debug, Deserialize)]
+pub struct EmailTemplateQuery {
+    pub document_type: EmailDocumentType,
+}
+
+/// Email an invoice PDF to the customer
+#[utoipa::path(
+    post,
+    path = "/api/v1/invoices/{id}/send",
+    tag = "email",
+    params(
+        ("id" = Uuid, Path, description = "Invoice ID")
+    ),
+    request_body = SendInvoiceRequest,
+    responses(
+        (status = 200, description = "Invoice emailed; draft invoices are marked sent", body = ApiResponse<EmailDelivery>),
+        (status = 400, description = "Invoice is void or the customer has no email address"),
+        (status = 404, description = "Invoice not found"),
+        (status = 500, description = "SMTP delivery failed; the failure is recorded"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn send_invoice(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(invoice_id): Path<Uuid>,
+    Json(req): Json<SendInvoiceRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    // Validate request
+    req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let delivery = state.email_service
+        .for_company(Some(company_id))
+        .send_invoice(&state.pool, invoice_id, req.to, None)
+        .await?;
+
+    Ok(success(delivery))
+}
+
+/// Email a statement PDF to the customer
+#[utoipa::path(
+    post,
+    path = "/api/v1/customers/{id}/statement/send",
+    tag = "email",
+    params(
+        ("id" = Uuid, Path, description = "Customer ID")
+    ),
+    request_body = SendStatementRequest,
+    responses(
+        (status = 200, description = "Statement emailed", body = ApiResponse<EmailDelivery>),
+        (status = 400, description = "Invalid date range or the customer has no email address"),
+        (status = 404, description = "Customer not found"),
+        (status = 500, description = "SMTP delivery failed; the failure is recorded"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn send_statement(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(customer_id): Path<Uuid>,
+    Json(req): Json<SendStatementRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let delivery = state.email_service
+        .for_company(Some(company_id))
+        .send_statement(&state.pool, customer_id, req, None)
+        .await?;
+
+    Ok(success(delivery))
+}
+
+/// List emailed documents
+#[utoipa::path(
+    get,
+    path = "/api/v1/email-deliveries",
+    tag = "email",
+    params(
+        ("document_type" = Option<String>, Query, description = "Filter by document type (invoice, statement, reminder)"),
+        ("document_id" = Option<Uuid>, Query, description = "Filter by invoice"),
+        ("contact_id" = Option<Uuid>, Query, description = "Filter by customer")
+    ),
+    responses(
+        (status = 200, description = "Email deliveries retrieved successfully", body = ApiResponse<Vec<EmailDelivery>>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_email_deliveries(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Query(params): Query<ListEmailDeliveriesQuery>,
+) -> Result<impl axum::response::IntoResponse> {
+    let deliveries = state.email_service
+        .for_company(Some(company_id))
+        .list_deliveries(&state.pool, params.document_type, params.document_id, params.contact_id)
+        .await?;
+
+    Ok(success(deliveries))
+}
+
+/// Get the email template for a company and document type
+#[utoipa::path(
+    get,
+    path = "/api/v1/email-templates",
+    tag = "email",
+    params(
+        ("document_type" = String, Query, description = "invoice or statement")
+    ),
+    responses(
+        (status = 200, description = "Email template retrieved successfully", body = ApiResponse<EmailTemplate>),
+        (status = 404, description = "No email template; built-in wording is used"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_email_template(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Query(params): Query<EmailTemplateQuery>,
+) -> Result<impl axum::response::IntoResponse> {
+    let template = state.email_service
+        .for_company(Some(company_id))
+        .get_email_template(&state.pool, Some(company_id), params.document_type)
+        .await?;
+
+    Ok(success(template))
+}
+
+/// Create or replace the email template for a company and document type
+#[utoipa::path(
+    put,
+    path = "/api/v1/email-templates",
+    tag = "email",
+    request_body = UpdateEmailTemplateRequest,
+    responses(
+        (status = 200, description = "Email template saved successfully", body = ApiResponse<EmailTemplate>),
+        (status = 400, description = "Invalid request data"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_email_template(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Json(req): Json<UpdateEmailTemplateRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let template = state.email_service
+        .for_company(Some(company_id))
+        .update_email_template(&state.pool, req)
+        .await?;
+
+    Ok(success(template))
+}
@@ -0,0 +1,227 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{
+    Estimate, EstimateStatus, EstimateWithLineItems, EstimateConversion, CreateEstimateRequest,
+    ConvertEstimateRequest,
+};
+use crate::middleware::ActiveCompany;
+use crate::routes::AppState;
+use crate::utils::{created, success, ApiResponse, AppError, Result};
+
+/// Query parameters for listing estimates
+#[derive(Debug, Deserialize)]
+pub struct ListEstimatesQuery {
+    #[serde(default)]
+    pub customer_id: Option<Uuid>,
+    #[serde(default)]
+    pub status: Option<EstimateStatus>,
+}
+
+/// Update estimate status request
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct UpdateEstimateStatusRequest {
+    pub status: EstimateStatus,
+}
+
+/// Expire estimates request
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct ExpireEstimatesRequest {
+    /// Sent estimates with an expiry date before this date expire
+    pub as_of_date: NaiveDate,
+}
+
+/// Create an estimate
+#[utoipa::path(
+    post,
+    path = "/api/v1/estimates",
+    tag = "estimates",
+    request_body = CreateEstimateRequest,
+    responses(
+        (status = 201, description = "Estimate created successfully", body = ApiResponse<EstimateWithLineItems>),
+        (status = 400, description = "Invalid request data"),
+        (status = 409, description = "Estimate number already exists"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_estimate(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Json(req): Json<CreateEstimateRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    // Validate request
+    req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let estimate = state.estimate_service
+        .for_company(Some(company_id))
+        .create_estimate(&state.pool, req)
+        .await?;
+
+    Ok(created(estimate))
+}
+
+/// List estimates
+#[utoipa::path(
+    get,
+    path = "/api/v1/estimates",
+    tag = "estimates",
+    params(
+        ("customer_id" = Option<Uuid>, Query, description = "Filter by customer"),
+        ("status" = Option<String>, Query, description = "Filter by status (draft, sent, accepted, declined, expired)")
+    ),
+    responses(
+        (status = 200, description = "Estimates retrieved successfully", body = ApiResponse<Vec<Estimate>>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_estimates(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Query(params): Query<ListEstimatesQuery>,
+) -> Result<impl axum::response::IntoResponse> {
+    let estimates = state.estimate_service
+        .for_company(Some(company_id))
+        .list_estimates(&state.pool, params.customer_id, params.status)
+        .await?;
+
+    Ok(success(estimates))
+}
+
+/// Get an estimate with line items and linked invoices
+#[utoipa::path(
+    get,
+    path = "/api/v1/estimates/{id}",
+    tag = "estimates",
+    params(
+        ("id" = Uuid, Path, description = "Estimate ID")
+    ),
+    responses(
+        (status = 200, description = "Estimate retrieved successfully", body = ApiResponse<EstimateWithLineItems>),
+        (status = 404, description = "Estimate not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_estimate(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(estimate_id): Path<Uuid>,
+) -> Result<impl axum::response::IntoResponse> {
+    let estimate = state.estimate_service
+        .for_company(Some(company_id))
+        .get_estimate(&state.pool, estimate_id)
+        .await?;
+
+    Ok(success(estimate))
+}
+
+/// Update estimate status
+#[utoipa::path(
+    put,
+    path = "/api/v1/estimates/{id}/status",
+    tag = "estimates",
+    params(
+        ("id" = Uuid, Path, description = "Estimate ID")
+    ),
+    request_body = UpdateEstimateStatusRequest,
+    responses(
+        (status = 200, description = "Estimate status updated", body = ApiResponse<Estimate>),
+        (status = 400, description = "Invalid status transition or estimate expired"),
+        (status = 404, description = "Estimate not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_estimate_status(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(estimate_id): Path<Uuid>,
+    Json(req): Json<UpdateEstimateStatusRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let estimate = state.estimate_service
+        .for_company(Some(company_id))
+        .update_estimate_status(&state.pool, estimate_id, req.status)
+        .await?;
+
+    Ok(success(estimate))
+}
+
+/// Convert an accepted estimate into an invoice, in full or for progress billing
+#[utoipa::path(
+    post,
+    path = "/api/v1/estimates/{id}/convert",
+    tag = "estimates",
+    params(
+        ("id" = Uuid, Path, description = "Estimate ID")
+    ),
+    request_body = ConvertEstimateRequest,
+    responses(
+        (status = 201, description = "Invoice created from estimate", body = ApiResponse<EstimateConversion>),
+        (status = 400, description = "Estimate not accepted or quantity exceeds what is left to bill"),
+        (status = 404, description = "Estimate not found"),
+        (status = 409, description = "Invoice number already exists"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn convert_estimate(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(estimate_id): Path<Uuid>,
+    Json(req): Json<ConvertEstimateRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    // Validate request
+    req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let conversion = state.estimate_service
+        .for_company(Some(company_id))
+        .convert_to_invoice(&state.pool, estimate_id, req)
+        .await?;
+
+    Ok(created(conversion))
+}
+
+/// Expire sent estimates past their expiry date
+#[utoipa::path(
+    post,
+    path = "/api/v1/estimates/expire",
+    tag = "estimates",
+    request_body = ExpireEstimatesRequest,
+    responses(
+        (status = 200, description = "Estimates expired", body = ApiResponse<Vec<Estimate>>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn expire_estimates(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Json(req): Json<ExpireEstimatesRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let estimates = state.estimate_service
+        .for_company(Some(company_id))
+        .expire_estimates(&state.pool, req.as_of_date)
+        .await?;
+
+    Ok(success(estimates))
+}
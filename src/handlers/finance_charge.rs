@@ -0,0 +1,168 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::models::{
+    Contact, FinanceCharge, FinanceChargeExemptRequest, FinanceChargeRun, FinanceChargeRunRequest,
+    FinanceChargeSettings, UpdateFinanceChargeSettingsRequest,
+};
+use crate::middleware::ActiveCompany;
+use crate::routes::AppState;
+use crate::utils::{success, ApiResponse, Result};
+
+/// Query parameters for listing finance charges
+#[derive(Debug, Deserialize)]
+pub struct ListFinanceChargesQuery {
+    #[serde(default)]
+    pub customer_id: Option<Uuid>,
+    #[serde(default)]
+    pub invoice_id: Option<Uuid>,
+}
+
+/// Get finance charge settings for a company
+#[utoipa::path(
+    get,
+    path = "/api/v1/finance-charges/settings",
+    tag = "finance-charges",
+    responses(
+        (status = 200, description = "Finance charge settings retrieved successfully", body = ApiResponse<FinanceChargeSettings>),
+        (status = 404, description = "Finance charges not configured"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_finance_charge_settings(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+) -> Result<impl axum::response::IntoResponse> {
+    let settings = state.finance_charge_service
+        .for_company(Some(company_id))
+        .get_settings(&state.pool, Some(company_id))
+        .await?;
+
+    Ok(success(settings))
+}
+
+/// Create or replace finance charge settings for a company
+#[utoipa::path(
+    put,
+    path = "/api/v1/finance-charges/settings",
+    tag = "finance-charges",
+    request_body = UpdateFinanceChargeSettingsRequest,
+    responses(
+        (status = 200, description = "Finance charge settings saved successfully", body = ApiResponse<FinanceChargeSettings>),
+        (status = 400, description = "Invalid request data"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_finance_charge_settings(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Json(req): Json<UpdateFinanceChargeSettingsRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let settings = state.finance_charge_service
+        .for_company(Some(company_id))
+        .update_settings(&state.pool, req)
+        .await?;
+
+    Ok(success(settings))
+}
+
+/// Assess finance charges on overdue invoices, or preview them
+#[utoipa::path(
+    post,
+    path = "/api/v1/finance-charges/run",
+    tag = "finance-charges",
+    request_body = FinanceChargeRunRequest,
+    responses(
+        (status = 200, description = "Finance charges calculated, and billed unless previewing", body = ApiResponse<FinanceChargeRun>),
+        (status = 400, description = "Accounting period is closed"),
+        (status = 404, description = "Finance charges not configured"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn run_finance_charges(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Json(req): Json<FinanceChargeRunRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let run = state.finance_charge_service
+        .for_company(Some(company_id))
+        .run(&state.pool, req.as_of_date, req.company_id, req.preview)
+        .await?;
+
+    Ok(success(run))
+}
+
+/// List assessed finance charges
+#[utoipa::path(
+    get,
+    path = "/api/v1/finance-charges",
+    tag = "finance-charges",
+    params(
+        ("customer_id" = Option<Uuid>, Query, description = "Filter by customer"),
+        ("invoice_id" = Option<Uuid>, Query, description = "Filter by overdue or finance-charge invoice")
+    ),
+    responses(
+        (status = 200, description = "Finance charges retrieved successfully", body = ApiResponse<Vec<FinanceCharge>>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_finance_charges(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Query(params): Query<ListFinanceChargesQuery>,
+) -> Result<impl axum::response::IntoResponse> {
+    let charges = state.finance_charge_service
+        .for_company(Some(company_id))
+        .list_finance_charges(&state.pool, params.customer_id, params.invoice_id)
+        .await?;
+
+    Ok(success(charges))
+}
+
+/// Exempt a customer from finance charges
+#[utoipa::path(
+    put,
+    path = "/api/v1/customers/{id}/finance-charges",
+    tag = "finance-charges",
+    params(
+        ("id" = Uuid, Path, description = "Customer ID")
+    ),
+    request_body = FinanceChargeExemptRequest,
+    responses(
+        (status = 200, description = "Customer updated successfully", body = ApiResponse<Contact>),
+        (status = 404, description = "Customer not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn set_finance_charge_exempt(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(customer_id): Path<Uuid>,
+    Json(req): Json<FinanceChargeExemptRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let contact = state.finance_charge_service
+        .for_company(Some(company_id))
+        .set_exempt(&state.pool, customer_id, req.exempt)
+        .await?;
+
+    Ok(success(contact))
+}
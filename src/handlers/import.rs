@@ -6,6 +6,7 @@ use axum::{
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+use crate::middleware::ActiveCompany;
 use crate::routes::AppState;
 use crate::utils::{success, AppError, Result, ImportResult};
 
@@ -57,10 +58,12 @@ impl From<ImportResult> for ImportResultResponse {
 )]
 pub async fn import_accounts_csv(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     body: Bytes,
 ) -> Result<impl axum::response::IntoResponse> {
     // Import accounts
     let result = state.import_service
+        .for_company(Some(company_id))
         .import_accounts_from_csv(&state.pool, &body)
         .await?;
 
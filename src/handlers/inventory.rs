@@ -0,0 +1,280 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+use validator::Validate;
+use utoipa::ToSchema;
+
+use crate::models::{
+    Item, ItemType, CreateItemRequest, InventoryCostLayer, InventoryMovement,
+    CreateStockAdjustmentRequest, UpdateCostingMethodRequest, InventoryValuationReport,
+    DateRequest,
+};
+use crate::middleware::ActiveCompany;
+use crate::routes::AppState;
+use crate::utils::{created, success, ApiResponse, AppError, Result};
+
+/// Query parameters for listing items
+#[derive(Debug, Deserialize)]
+pub struct ListItemsQuery {
+    #[serde(default)]
+    pub item_type: Option<ItemType>,
+}
+
+/// Query parameters for inventory valuation
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct InventoryValuationQuery {
+    #[serde(flatten)]
+    pub date: DateRequest,
+}
+
+/// Create a new item
+#[utoipa::path(
+    post,
+    path = "/api/v1/items",
+    tag = "inventory",
+    request_body = CreateItemRequest,
+    responses(
+        (status = 201, description = "Item created successfully", body = ApiResponse<Item>),
+        (status = 400, description = "Invalid request data"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_item(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Json(req): Json<CreateItemRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    // Validate request
+    req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let item = state.inventory_service
+        .for_company(Some(company_id))
+        .create_item(&state.pool, req)
+        .await?;
+
+    Ok(created(item))
+}
+
+/// List items
+#[utoipa::path(
+    get,
+    path = "/api/v1/items",
+    tag = "inventory",
+    params(
+        ("item_type" = Option<ItemType>, Query, description = "Filter by item type")
+    ),
+    responses(
+        (status = 200, description = "Items retrieved successfully", body = ApiResponse<Vec<Item>>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_items(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Query(params): Query<ListItemsQuery>,
+) -> Result<impl axum::response::IntoResponse> {
+    let items = state.inventory_service
+        .for_company(Some(company_id))
+        .list_items(&state.pool, params.item_type)
+        .await?;
+
+    Ok(success(items))
+}
+
+/// Get item by ID
+#[utoipa::path(
+    get,
+    path = "/api/v1/items/{id}",
+    tag = "inventory",
+    params(
+        ("id" = Uuid, Path, description = "Item ID")
+    ),
+    responses(
+        (status = 200, description = "Item retrieved successfully", body = ApiResponse<Item>),
+        (status = 404, description = "Item not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_item(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(item_id): Path<Uuid>,
+) -> Result<impl axum::response::IntoResponse> {
+    let item = state.inventory_service
+        .for_company(Some(company_id))
+        .get_item(&state.pool, item_id)
+        .await?;
+
+    Ok(success(item))
+}
+
+/// Get the cost layers of an inventory item
+#[utoipa::path(
+    get,
+    path = "/api/v1/items/{id}/cost-layers",
+    tag = "inventory",
+    params(
+        ("id" = Uuid, Path, description = "Item ID")
+    ),
+    responses(
+        (status = 200, description = "Cost layers retrieved successfully", body = ApiResponse<Vec<InventoryCostLayer>>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_item_cost_layers(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(item_id): Path<Uuid>,
+) -> Result<impl axum::response::IntoResponse> {
+    let layers = state.inventory_service
+        .for_company(Some(company_id))
+        .list_cost_layers(&state.pool, item_id)
+        .await?;
+
+    Ok(success(layers))
+}
+
+/// Get the stock movements of an inventory item
+#[utoipa::path(
+    get,
+    path = "/api/v1/items/{id}/movements",
+    tag = "inventory",
+    params(
+        ("id" = Uuid, Path, description = "Item ID")
+    ),
+    responses(
+        (status = 200, description = "Movements retrieved successfully", body = ApiResponse<Vec<InventoryMovement>>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_item_movements(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(item_id): Path<Uuid>,
+) -> Result<impl axum::response::IntoResponse> {
+    let movements = state.inventory_service
+        .for_company(Some(company_id))
+        .list_movements(&state.pool, item_id)
+        .await?;
+
+    Ok(success(movements))
+}
+
+/// Create a stock adjustment
+#[utoipa::path(
+    post,
+    path = "/api/v1/inventory/adjustments",
+    tag = "inventory",
+    request_body = CreateStockAdjustmentRequest,
+    responses(
+        (status = 201, description = "Stock adjusted successfully", body = ApiResponse<InventoryMovement>),
+        (status = 400, description = "Invalid request data or insufficient stock"),
+        (status = 404, description = "Item not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_stock_adjustment(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Json(req): Json<CreateStockAdjustmentRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    // Validate request
+    req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let movement = state.inventory_service
+        .for_company(Some(company_id))
+        .create_stock_adjustment(&state.pool, req, None)
+        .await?;
+
+    Ok(created(movement))
+}
+
+/// Set the inventory costing method of a company
+#[utoipa::path(
+    put,
+    path = "/api/v1/companies/{id}/inventory-costing-method",
+    tag = "inventory",
+    params(
+        ("id" = Uuid, Path, description = "Company ID")
+    ),
+    request_body = UpdateCostingMethodRequest,
+    responses(
+        (status = 200, description = "Costing method updated successfully", body = ApiResponse<String>),
+        (status = 404, description = "Company not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_costing_method(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateCostingMethodRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    // Only the active company's costing method can be changed
+    if company_id != id {
+        return Err(AppError::NotFound("Company not found".to_string()));
+    }
+
+    state.inventory_service
+        .set_costing_method(&state.pool, id, req.inventory_costing_method)
+        .await?;
+
+    Ok(success("Costing method updated successfully".to_string()))
+}
+
+/// Generate inventory valuation report
+#[utoipa::path(
+    get,
+    path = "/api/v1/reports/inventory-valuation",
+    tag = "reporting",
+    params(
+        ("as_of_date" = chrono::NaiveDate, Query, description = "As of date for the valuation")
+    ),
+    responses(
+        (status = 200, description = "Inventory valuation generated successfully", body = ApiResponse<InventoryValuationReport>),
+        (status = 400, description = "Invalid request data"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_inventory_valuation(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Query(params): Query<InventoryValuationQuery>,
+) -> Result<impl axum::response::IntoResponse> {
+    // Validate request
+    params.date.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let report = state.inventory_service
+        .for_company(Some(company_id))
+        .generate_valuation_report(&state.pool, params.date)
+        .await?;
+
+    Ok(success(report))
+}
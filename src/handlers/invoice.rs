@@ -1,5 +1,6 @@
 use axum::{
     extract::{Path, Query, State},
+    response::Response,
     Json,
 };
 use serde::Deserialize;
@@ -7,10 +8,12 @@ use uuid::Uuid;
 use validator::Validate;
 
 use crate::models::{
-    Invoice, InvoiceStatus, CreateInvoiceRequest, InvoiceWithLineItems
+    Invoice, InvoiceStatus, CreateInvoiceRequest, InvoiceWithLineItems, InvoiceTemplate,
+    UpdateInvoiceTemplateRequest,
 };
+use crate::middleware::ActiveCompany;
 use crate::routes::AppState;
-use crate::utils::{created, success, ApiResponse, AppError, Result};
+use crate::utils::{created, pdf, success, ApiResponse, AppError, Result};
 
 /// Query parameters for listing invoices
 #[derive(Debug, Deserialize)]
@@ -49,6 +52,7 @@ pub struct UpdateInvoiceStatusRequest {
 )]
 pub async fn create_invoice(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Json(req): Json<CreateInvoiceRequest>,
 ) -> Result<impl axum::response::IntoResponse> {
     // Validate request
@@ -56,6 +60,7 @@ pub async fn create_invoice(
 
     // Create invoice
     let invoice = state.invoice_service
+        .for_company(Some(company_id))
         .create_invoice(&state.pool, req)
         .await?;
 
@@ -84,6 +89,7 @@ pub async fn create_invoice(
 )]
 pub async fn list_invoices(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Query(params): Query<ListInvoicesQuery>,
 ) -> Result<impl axum::response::IntoResponse> {
     // Parse status if provided
@@ -95,6 +101,7 @@ pub async fn list_invoices(
 
     // List invoices
     let invoices = state.invoice_service
+        .for_company(Some(company_id))
         .list_invoices(
             &state.pool,
             params.customer_id,
@@ -126,10 +133,12 @@ pub async fn list_invoices(
 )]
 pub async fn get_invoice(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Path(invoice_id): Path<Uuid>,
 ) -> Result<impl axum::response::IntoResponse> {
     // Get invoice
     let invoice = state.invoice_service
+        .for_company(Some(company_id))
         .get_invoice(&state.pool, invoice_id)
         .await?
         .ok_or_else(|| AppError::NotFound("Invoice not found".to_string()))?;
@@ -158,6 +167,7 @@ pub async fn get_invoice(
 )]
 pub async fn update_invoice_status(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Path(invoice_id): Path<Uuid>,
     Json(req): Json<UpdateInvoiceStatusRequest>,
 ) -> Result<impl axum::response::IntoResponse> {
@@ -166,6 +176,7 @@ pub async fn update_invoice_status(
 
     // Update invoice status
     let invoice = state.invoice_service
+        .for_company(Some(company_id))
         .update_invoice_status(&state.pool, invoice_id, new_status)
         .await?;
 
@@ -191,10 +202,12 @@ pub async fn update_invoice_status(
 )]
 pub async fn get_customer_invoices(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Path(customer_id): Path<Uuid>,
 ) -> Result<impl axum::response::IntoResponse> {
     // Get customer invoices
     let invoices = state.invoice_service
+        .for_company(Some(company_id))
         .get_customer_invoices(&state.pool, customer_id)
         .await?;
 
@@ -216,9 +229,11 @@ pub async fn get_customer_invoices(
 )]
 pub async fn get_overdue_invoices(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
 ) -> Result<impl axum::response::IntoResponse> {
     // Get overdue invoices
     let invoices = state.invoice_service
+        .for_company(Some(company_id))
         .get_overdue_invoices(&state.pool)
         .await?;
 
@@ -235,9 +250,96 @@ fn parse_invoice_status(status_str: &str) -> Result<InvoiceStatus> {
         "partial" => Ok(InvoiceStatus::Partial),
         "overdue" => Ok(InvoiceStatus::Overdue),
         "void" => Ok(InvoiceStatus::Void),
+        "written_off" => Ok(InvoiceStatus::WrittenOff),
         _ => Err(AppError::ValidationError(
             format!("Invalid invoice status: {}", status_str)
         )),
     }
 }
 
+/// Download an invoice as a PDF
+#[utoipa::path(
+    get,
+    path = "/api/v1/invoices/{id}/pdf",
+    tag = "invoices",
+    params(
+        ("id" = Uuid, Path, description = "Invoice ID")
+    ),
+    responses(
+        (status = 200, description = "Invoice PDF", content_type = "application/pdf"),
+        (status = 404, description = "Invoice not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_invoice_pdf(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(invoice_id): Path<Uuid>,
+) -> Result<Response> {
+    let bytes = state.invoice_service
+        .for_company(Some(company_id))
+        .render_invoice_pdf(&state.pool, invoice_id)
+        .await?;
+
+    Ok(pdf(bytes, &format!("invoice_{}.pdf", invoice_id)))
+}
+
+/// Get the invoice template for a company
+#[utoipa::path(
+    get,
+    path = "/api/v1/invoice-template",
+    tag = "invoices",
+    responses(
+        (status = 200, description = "Invoice template retrieved successfully", body = ApiResponse<InvoiceTemplate>),
+        (status = 404, description = "No invoice template for the company"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_invoice_template(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+) -> Result<impl axum::response::IntoResponse> {
+    let template = state.invoice_service
+        .for_company(Some(company_id))
+        .get_invoice_template(&state.pool, Some(company_id))
+        .await?;
+
+    Ok(success(template))
+}
+
+/// Create or replace the invoice template for a company
+#[utoipa::path(
+    put,
+    path = "/api/v1/invoice-template",
+    tag = "invoices",
+    request_body = UpdateInvoiceTemplateRequest,
+    responses(
+        (status = 200, description = "Invoice template saved successfully", body = ApiResponse<InvoiceTemplate>),
+        (status = 400, description = "Invalid request data or logo"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_invoice_template(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Json(req): Json<UpdateInvoiceTemplateRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    // Validate request
+    req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let template = state.invoice_service
+        .for_company(Some(company_id))
+        .update_invoice_template(&state.pool, req)
+        .await?;
+
+    Ok(success(template))
+}
@@ -7,8 +7,24 @@ pub mod payment;
 pub mod bill;
 pub mod import;
 pub mod reporting;
+pub mod inventory;
+pub mod tax;
+pub mod credit_memo;
+pub mod statement;
+pub mod estimate;
+pub mod purchase_order;
+pub mod vendor_credit;
+pub mod email;
+pub mod dunning;
+pub mod finance_charge;
+pub mod write_off;
+pub mod numbering;
+pub mod billable_expense;
+pub mod dimension;
+pub mod company;
+pub mod user;
 
-pub use auth::{login, register, refresh_token, me};
+pub use auth::{login, register, refresh_token, logout, logout_all, change_password, forgot_password, reset_password, verify_mfa_login, mfa_status, setup_mfa, confirm_mfa, regenerate_recovery_codes, disable_mfa, get_mfa_policy, update_mfa_policy, me};
 pub use account::{
     list_accounts, create_account, get_account,
     update_account, deactivate_account, get_account_hierarchy
@@ -23,11 +39,14 @@ pub use contact::{
 };
 pub use invoice::{
     create_invoice, list_invoices, get_invoice, update_invoice_status,
-    get_customer_invoices, get_overdue_invoices
+    get_customer_invoices, get_overdue_invoices, get_invoice_pdf, get_invoice_template,
+    update_invoice_template
 };
 pub use payment::{
     create_payment, list_payments, get_payment, apply_payment,
-    get_invoice_payments, get_unapplied_payments, create_bill_payment
+    get_invoice_payments, get_unapplied_payments, create_bill_payment,
+    list_payment_applications, unapply_payment, void_payment, auto_apply_payments,
+    check_application_consistency
 };
 pub use bill::{
     create_bill, list_bills, get_bill, update_bill_status,
@@ -39,3 +58,54 @@ pub use import::{
 pub use reporting::{
     get_trial_balance, get_profit_loss, get_balance_sheet, get_ar_aging
 };
+pub use inventory::{
+    create_item, list_items, get_item, get_item_cost_layers, get_item_movements,
+    create_stock_adjustment, update_costing_method, get_inventory_valuation
+};
+pub use tax::{
+    create_tax_code, list_tax_codes, get_tax_code, add_tax_rate, setup_canadian_tax_codes,
+    get_sales_tax_return, create_tax_return, list_tax_returns, get_tax_return, file_tax_return
+};
+pub use credit_memo::{
+    create_credit_memo, list_credit_memos, get_credit_memo, apply_credit_memo,
+    void_credit_memo, create_customer_refund, get_customer_refunds
+};
+pub use statement::{
+    get_customer_statement, generate_batch_statements
+};
+pub use estimate::{
+    create_estimate, list_estimates, get_estimate, update_estimate_status, convert_estimate,
+    expire_estimates
+};
+pub use purchase_order::{
+    create_purchase_order, list_purchase_orders, get_purchase_order, preview_purchase_order_bill,
+    convert_purchase_order, close_purchase_order, get_open_purchase_orders
+};
+pub use vendor_credit::{
+    create_vendor_credit, list_vendor_credits, get_vendor_credit, apply_vendor_credit,
+    void_vendor_credit
+};
+pub use email::{
+    send_invoice, send_statement, list_email_deliveries, get_email_template, update_email_template
+};
+pub use dunning::{
+    run_dunning, get_dunning_levels, set_dunning_levels, list_reminders, set_dunning_opt_out
+};
+pub use finance_charge::{
+    get_finance_charge_settings, update_finance_charge_settings, run_finance_charges,
+    list_finance_charges, set_finance_charge_exempt
+};
+pub use write_off::{write_off_invoices, list_write_offs, recover_write_off};
+pub use numbering::{list_numbering_sequences, update_numbering_sequence};
+pub use billable_expense::{list_unbilled_expenses, bill_expenses};
+pub use dimension::{
+    create_dimension_type, list_dimension_types, get_dimension_type, create_dimension_value,
+    update_dimension_value, add_dimension_requirement, remove_dimension_requirement
+};
+pub use company::{
+    create_company, list_companies, get_company, update_company, delete_company,
+    list_company_members, add_company_member, remove_company_member, switch_company
+};
+pub use user::{
+    list_users, invite_user, get_user, update_user_role, disable_user, enable_user, force_password_reset
+};
@@ -0,0 +1,66 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+
+use crate::models::{NumberingSequenceWithNext, SequenceDocumentType, UpdateNumberingSequenceRequest};
+use crate::middleware::ActiveCompany;
+use crate::routes::AppState;
+use crate::utils::{success, ApiResponse, Result};
+
+/// List a company's numbering sequences with the next number of each
+#[utoipa::path(
+    get,
+    path = "/api/v1/numbering-sequences",
+    tag = "numbering",
+    responses(
+        (status = 200, description = "Numbering sequences retrieved successfully", body = ApiResponse<Vec<NumberingSequenceWithNext>>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_numbering_sequences(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+) -> Result<impl axum::response::IntoResponse> {
+    let sequences = state.numbering_service
+        .for_company(Some(company_id))
+        .list_sequences(&state.pool)
+        .await?;
+
+    Ok(success(sequences))
+}
+
+/// Configure a numbering sequence, optionally setting the next number
+#[utoipa::path(
+    put,
+    path = "/api/v1/numbering-sequences/{document_type}",
+    tag = "numbering",
+    params(
+        ("document_type" = SequenceDocumentType, Path, description = "invoice, bill, payment, journal_entry or credit_memo")
+    ),
+    request_body = UpdateNumberingSequenceRequest,
+    responses(
+        (status = 200, description = "Numbering sequence saved successfully", body = ApiResponse<NumberingSequenceWithNext>),
+        (status = 400, description = "Invalid request data"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_numbering_sequence(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(document_type): Path<SequenceDocumentType>,
+    Json(req): Json<UpdateNumberingSequenceRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let sequence = state.numbering_service
+        .for_company(Some(company_id))
+        .update_sequence(&state.pool, document_type, req)
+        .await?;
+
+    Ok(success(sequence))
+}
@@ -8,8 +8,10 @@ use validator::Validate;
 
 use crate::models::{
     Payment, CreatePaymentRequest, CreateBillPaymentRequest,
-    PaymentApplicationRequest, BillPayment
+    PaymentApplicationRequest, BillPayment, PaymentApplication, VoidPaymentRequest,
+    AutoApplyRequest, AutoApplyRun, ApplicationConsistencyReport
 };
+use crate::middleware::ActiveCompany;
 use crate::routes::AppState;
 use crate::utils::{created, success, ApiResponse, AppError, Result};
 
@@ -50,12 +52,14 @@ pub struct ApplyPaymentRequest {
 )]
 pub async fn create_payment(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Json(req): Json<CreatePaymentRequest>,
 ) -> Result<impl axum::response::IntoResponse> {
     // Validate request
     req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
 
     let payment = state.payment_service
+        .for_company(Some(company_id))
         .create_payment(&state.pool, req)
         .await?;
 
@@ -83,25 +87,30 @@ pub async fn create_payment(
 )]
 pub async fn list_payments(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Query(params): Query<ListPaymentsQuery>,
 ) -> Result<impl axum::response::IntoResponse> {
     let payments = if let Some(customer_id) = params.customer_id {
         if params.unapplied_only.unwrap_or(false) {
             state.payment_service
+                .for_company(Some(company_id))
                 .list_unapplied_payments(&state.pool, Some(customer_id))
                 .await?
         } else {
             state.payment_service
+                .for_company(Some(company_id))
                 .list_customer_payments(&state.pool, customer_id)
                 .await?
         }
     } else {
         if params.unapplied_only.unwrap_or(false) {
             state.payment_service
+                .for_company(Some(company_id))
                 .list_unapplied_payments(&state.pool, None)
                 .await?
         } else {
             state.payment_service
+                .for_company(Some(company_id))
                 .list_payments(&state.pool, params.limit, params.offset)
                 .await?
         }
@@ -129,9 +138,11 @@ pub async fn list_payments(
 )]
 pub async fn get_payment(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Path(payment_id): Path<Uuid>,
 ) -> Result<impl axum::response::IntoResponse> {
     let payment = state.payment_service
+        .for_company(Some(company_id))
         .get_payment_by_id(&state.pool, payment_id)
         .await?;
 
@@ -162,10 +173,12 @@ pub async fn get_payment(
 )]
 pub async fn apply_payment(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Path(payment_id): Path<Uuid>,
     Json(req): Json<ApplyPaymentRequest>,
 ) -> Result<impl axum::response::IntoResponse> {
     state.payment_service
+        .for_company(Some(company_id))
         .apply_payment_to_invoices(&state.pool, payment_id, req.applications)
         .await?;
 
@@ -191,9 +204,11 @@ pub async fn apply_payment(
 )]
 pub async fn get_invoice_payments(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Path(invoice_id): Path<Uuid>,
 ) -> Result<impl axum::response::IntoResponse> {
     let payments = state.payment_service
+        .for_company(Some(company_id))
         .get_invoice_payments(&state.pool, invoice_id)
         .await?;
 
@@ -218,15 +233,164 @@ pub async fn get_invoice_payments(
 )]
 pub async fn get_unapplied_payments(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Query(params): Query<ListPaymentsQuery>,
 ) -> Result<impl axum::response::IntoResponse> {
     let payments = state.payment_service
+        .for_company(Some(company_id))
         .list_unapplied_payments(&state.pool, params.customer_id)
         .await?;
 
     Ok(success(payments))
 }
 
+/// List the invoices a payment has been applied to
+#[utoipa::path(
+    get,
+    path = "/api/v1/payments/{id}/applications",
+    tag = "payments",
+    params(
+        ("id" = Uuid, Path, description = "Payment ID")
+    ),
+    responses(
+        (status = 200, description = "Payment applications retrieved successfully", body = ApiResponse<Vec<PaymentApplication>>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_payment_applications(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(payment_id): Path<Uuid>,
+) -> Result<impl axum::response::IntoResponse> {
+    let applications = state.payment_service
+        .for_company(Some(company_id))
+        .list_payment_applications(&state.pool, payment_id)
+        .await?;
+
+    Ok(success(applications))
+}
+
+/// Remove a payment application, reopening the invoice
+#[utoipa::path(
+    delete,
+    path = "/api/v1/payments/{id}/applications/{application_id}",
+    tag = "payments",
+    params(
+        ("id" = Uuid, Path, description = "Payment ID"),
+        ("application_id" = Uuid, Path, description = "Payment application ID")
+    ),
+    responses(
+        (status = 200, description = "Payment application removed successfully", body = ApiResponse<Payment>),
+        (status = 400, description = "Payment is voided or invoice cannot be reopened"),
+        (status = 404, description = "Payment or application not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn unapply_payment(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path((payment_id, application_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl axum::response::IntoResponse> {
+    let payment = state.payment_service
+        .for_company(Some(company_id))
+        .unapply_payment_application(&state.pool, payment_id, application_id)
+        .await?;
+
+    Ok(success(payment))
+}
+
+/// Void a payment, reversing its applications and ledger posting
+#[utoipa::path(
+    post,
+    path = "/api/v1/payments/{id}/void",
+    tag = "payments",
+    params(
+        ("id" = Uuid, Path, description = "Payment ID")
+    ),
+    request_body = VoidPaymentRequest,
+    responses(
+        (status = 200, description = "Payment voided successfully", body = ApiResponse<Payment>),
+        (status = 400, description = "Invalid request data or payment has been refunded"),
+        (status = 404, description = "Payment not found"),
+        (status = 409, description = "Payment has already been voided"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn void_payment(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(payment_id): Path<Uuid>,
+    Json(req): Json<VoidPaymentRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let payment = state.payment_service
+        .for_company(Some(company_id))
+        .void_payment(&state.pool, payment_id, req, None)
+        .await?;
+
+    Ok(success(payment))
+}
+
+/// Apply unapplied payments and credits to the oldest open invoices, or preview doing so
+#[utoipa::path(
+    post,
+    path = "/api/v1/payments/auto-apply",
+    tag = "payments",
+    request_body = AutoApplyRequest,
+    responses(
+        (status = 200, description = "Payments and credits applied (or previewed) successfully", body = ApiResponse<AutoApplyRun>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn auto_apply_payments(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Json(req): Json<AutoApplyRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let run = state.payment_service
+        .for_company(Some(company_id))
+        .auto_apply(&state.pool, req)
+        .await?;
+
+    Ok(success(run))
+}
+
+/// Find invoices, bills, payments and credits whose balances have drifted from their applications
+#[utoipa::path(
+    get,
+    path = "/api/v1/payments/consistency-check",
+    tag = "payments",
+    responses(
+        (status = 200, description = "Consistency check completed", body = ApiResponse<ApplicationConsistencyReport>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn check_application_consistency(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+) -> Result<impl axum::response::IntoResponse> {
+    let report = state.payment_service
+        .for_company(Some(company_id))
+        .check_application_consistency(&state.pool)
+        .await?;
+
+    Ok(success(report))
+}
+
 /// Create a new vendor bill payment
 #[utoipa::path(
     post,
@@ -245,12 +409,14 @@ pub async fn get_unapplied_payments(
 )]
 pub async fn create_bill_payment(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Json(req): Json<CreateBillPaymentRequest>,
 ) -> Result<impl axum::response::IntoResponse> {
     // Validate request
     req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
 
     let bill_payment = state.payment_service
+        .for_company(Some(company_id))
         .create_bill_payment(&state.pool, req)
         .await?;
 
@@ -0,0 +1,249 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{
+    PurchaseOrder, PurchaseOrderStatus, PurchaseOrderWithLineItems, PurchaseOrderConversion,
+    CreatePurchaseOrderRequest, ConvertPurchaseOrderRequest, OpenPurchaseOrdersReport,
+    CreateBillRequest, DateRequest,
+};
+use crate::middleware::ActiveCompany;
+use crate::routes::AppState;
+use crate::utils::{created, success, ApiResponse, AppError, Result};
+
+/// Query parameters for listing purchase orders
+#[derive(Debug, Deserialize)]
+pub struct ListPurchaseOrdersQuery {
+    #[serde(default)]
+    pub vendor_id: Option<Uuid>,
+    #[serde(default)]
+    pub status: Option<PurchaseOrderStatus>,
+}
+
+/// Create a purchase order
+#[utoipa::path(
+    post,
+    path = "/api/v1/purchase-orders",
+    tag = "purchase-orders",
+    request_body = CreatePurchaseOrderRequest,
+    responses(
+        (status = 201, description = "Purchase order created successfully", body = ApiResponse<PurchaseOrderWithLineItems>),
+        (status = 400, description = "Invalid request data"),
+        (status = 409, description = "Purchase order number already exists"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_purchase_order(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Json(req): Json<CreatePurchaseOrderRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    // Validate request
+    req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let purchase_order = state.purchase_order_service
+        .for_company(Some(company_id))
+        .create_purchase_order(&state.pool, req)
+        .await?;
+
+    Ok(created(purchase_order))
+}
+
+/// List purchase orders
+#[utoipa::path(
+    get,
+    path = "/api/v1/purchase-orders",
+    tag = "purchase-orders",
+    params(
+        ("vendor_id" = Option<Uuid>, Query, description = "Filter by vendor"),
+        ("status" = Option<String>, Query, description = "Filter by status (open, partially_received, closed)")
+    ),
+    responses(
+        (status = 200, description = "Purchase orders retrieved successfully", body = ApiResponse<Vec<PurchaseOrder>>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_purchase_orders(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Query(params): Query<ListPurchaseOrdersQuery>,
+) -> Result<impl axum::response::IntoResponse> {
+    let purchase_orders = state.purchase_order_service
+        .for_company(Some(company_id))
+        .list_purchase_orders(&state.pool, params.vendor_id, params.status)
+        .await?;
+
+    Ok(success(purchase_orders))
+}
+
+/// Get a purchase order with line items and billed quantities
+#[utoipa::path(
+    get,
+    path = "/api/v1/purchase-orders/{id}",
+    tag = "purchase-orders",
+    params(
+        ("id" = Uuid, Path, description = "Purchase order ID")
+    ),
+    responses(
+        (status = 200, description = "Purchase order retrieved successfully", body = ApiResponse<PurchaseOrderWithLineItems>),
+        (status = 404, description = "Purchase order not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_purchase_order(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(purchase_order_id): Path<Uuid>,
+) -> Result<impl axum::response::IntoResponse> {
+    let purchase_order = state.purchase_order_service
+        .for_company(Some(company_id))
+        .get_purchase_order(&state.pool, purchase_order_id)
+        .await?;
+
+    Ok(success(purchase_order))
+}
+
+/// Preview the bill a conversion would create, pre-filled from the purchase order
+#[utoipa::path(
+    post,
+    path = "/api/v1/purchase-orders/{id}/bill-preview",
+    tag = "purchase-orders",
+    params(
+        ("id" = Uuid, Path, description = "Purchase order ID")
+    ),
+    request_body = ConvertPurchaseOrderRequest,
+    responses(
+        (status = 200, description = "Pre-filled bill request", body = ApiResponse<CreateBillRequest>),
+        (status = 400, description = "Purchase order closed or quantity exceeds what is left to bill"),
+        (status = 404, description = "Purchase order not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn preview_purchase_order_bill(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(purchase_order_id): Path<Uuid>,
+    Json(req): Json<ConvertPurchaseOrderRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let bill_request = state.purchase_order_service
+        .for_company(Some(company_id))
+        .prefill_bill_request(&state.pool, purchase_order_id, req)
+        .await?;
+
+    Ok(success(bill_request))
+}
+
+/// Convert a purchase order into a bill, in full or for the quantities received
+#[utoipa::path(
+    post,
+    path = "/api/v1/purchase-orders/{id}/convert",
+    tag = "purchase-orders",
+    params(
+        ("id" = Uuid, Path, description = "Purchase order ID")
+    ),
+    request_body = ConvertPurchaseOrderRequest,
+    responses(
+        (status = 201, description = "Bill created from purchase order", body = ApiResponse<PurchaseOrderConversion>),
+        (status = 400, description = "Purchase order closed or quantity exceeds what is left to bill"),
+        (status = 404, description = "Purchase order not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn convert_purchase_order(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(purchase_order_id): Path<Uuid>,
+    Json(req): Json<ConvertPurchaseOrderRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    // Validate request
+    req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let conversion = state.purchase_order_service
+        .for_company(Some(company_id))
+        .convert_to_bill(&state.pool, purchase_order_id, req)
+        .await?;
+
+    Ok(created(conversion))
+}
+
+/// Close a purchase order, releasing its unbilled commitment
+#[utoipa::path(
+    post,
+    path = "/api/v1/purchase-orders/{id}/close",
+    tag = "purchase-orders",
+    params(
+        ("id" = Uuid, Path, description = "Purchase order ID")
+    ),
+    responses(
+        (status = 200, description = "Purchase order closed", body = ApiResponse<PurchaseOrder>),
+        (status = 404, description = "Purchase order not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn close_purchase_order(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(purchase_order_id): Path<Uuid>,
+) -> Result<impl axum::response::IntoResponse> {
+    let purchase_order = state.purchase_order_service
+        .for_company(Some(company_id))
+        .close_purchase_order(&state.pool, purchase_order_id)
+        .await?;
+
+    Ok(success(purchase_order))
+}
+
+/// Generate the open purchase orders report with committed spend
+#[utoipa::path(
+    get,
+    path = "/api/v1/reports/open-purchase-orders",
+    tag = "reporting",
+    params(
+        ("as_of_date" = chrono::NaiveDate, Query, description = "As of date for the report")
+    ),
+    responses(
+        (status = 200, description = "Open purchase orders report generated successfully", body = ApiResponse<OpenPurchaseOrdersReport>),
+        (status = 400, description = "Invalid request data"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_open_purchase_orders(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Query(params): Query<DateRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    // Validate request
+    params.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let report = state.purchase_order_service
+        .for_company(Some(company_id))
+        .generate_open_purchase_orders_report(&state.pool, params.as_of_date)
+        .await?;
+
+    Ok(success(report))
+}
@@ -2,21 +2,54 @@ use axum::{
     extract::{Query, State},
 };
 use serde::Deserialize;
+use uuid::Uuid;
 use validator::Validate;
 use utoipa::ToSchema;
 
 use crate::models::{
     TrialBalance, ProfitLossStatement, BalanceSheet, AccountsReceivableAging,
-    DateRangeRequest, DateRequest
+    DateRangeRequest, DateRequest, DimensionFilter
 };
+use crate::middleware::ActiveCompany;
 use crate::routes::AppState;
 use crate::utils::{success, ApiResponse, AppError, Result};
 
+/// Dimension filter and group-by shared by all reports
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct DimensionQuery {
+    /// Comma-separated dimension value ids
+    pub dimension_value_ids: Option<String>,
+    pub group_by_dimension_type_id: Option<Uuid>,
+}
+
+impl DimensionQuery {
+    fn to_filter(&self) -> Result<DimensionFilter> {
+        let dimension_value_ids = match &self.dimension_value_ids {
+            Some(ids) => ids
+                .split(',')
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(|id| Uuid::parse_str(id).map_err(|_| AppError::BadRequest(
+                    format!("Invalid dimension value id {}", id)
+                )))
+                .collect::<Result<Vec<_>>>()?,
+            None => Vec::new(),
+        };
+
+        Ok(DimensionFilter {
+            dimension_value_ids,
+            group_by_dimension_type_id: self.group_by_dimension_type_id,
+        })
+    }
+}
+
 /// Query parameters for trial balance
 #[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct TrialBalanceQuery {
     #[serde(flatten)]
     pub date: DateRequest,
+    #[serde(flatten)]
+    pub dimensions: DimensionQuery,
 }
 
 /// Query parameters for profit and loss statement
@@ -24,6 +57,8 @@ pub struct TrialBalanceQuery {
 pub struct ProfitLossQuery {
     #[serde(flatten)]
     pub date_range: DateRangeRequest,
+    #[serde(flatten)]
+    pub dimensions: DimensionQuery,
 }
 
 /// Query parameters for balance sheet
@@ -31,6 +66,8 @@ pub struct ProfitLossQuery {
 pub struct BalanceSheetQuery {
     #[serde(flatten)]
     pub date: DateRequest,
+    #[serde(flatten)]
+    pub dimensions: DimensionQuery,
 }
 
 /// Query parameters for accounts receivable aging
@@ -38,6 +75,8 @@ pub struct BalanceSheetQuery {
 pub struct AgingQuery {
     #[serde(flatten)]
     pub date: DateRequest,
+    #[serde(flatten)]
+    pub dimensions: DimensionQuery,
 }
 
 /// Generate trial balance
@@ -46,7 +85,9 @@ pub struct AgingQuery {
     path = "/api/v1/reports/trial-balance",
     tag = "reporting",
     params(
-        ("as_of_date" = chrono::NaiveDate, Query, description = "As of date for the trial balance")
+        ("as_of_date" = chrono::NaiveDate, Query, description = "As of date for the trial balance"),
+        ("dimension_value_ids" = Option<String>, Query, description = "Comma-separated dimension values lines must carry"),
+        ("group_by_dimension_type_id" = Option<uuid::Uuid>, Query, description = "Split each line by the values of this dimension type")
     ),
     responses(
         (status = 200, description = "Trial balance generated successfully", body = ApiResponse<TrialBalance>),
@@ -59,13 +100,17 @@ pub struct AgingQuery {
 )]
 pub async fn get_trial_balance(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Query(params): Query<TrialBalanceQuery>,
 ) -> Result<impl axum::response::IntoResponse> {
     // Validate request
     params.date.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
 
+    let filter = params.dimensions.to_filter()?;
+
     let trial_balance = state.reporting_service
-        .generate_trial_balance(&state.pool, params.date)
+        .for_company(Some(company_id))
+        .generate_trial_balance(&state.pool, params.date, &filter)
         .await?;
 
     Ok(success(trial_balance))
@@ -78,7 +123,9 @@ pub async fn get_trial_balance(
     tag = "reporting",
     params(
         ("start_date" = chrono::NaiveDate, Query, description = "Start date for the reporting period"),
-        ("end_date" = chrono::NaiveDate, Query, description = "End date for the reporting period")
+        ("end_date" = chrono::NaiveDate, Query, description = "End date for the reporting period"),
+        ("dimension_value_ids" = Option<String>, Query, description = "Comma-separated dimension values lines must carry"),
+        ("group_by_dimension_type_id" = Option<uuid::Uuid>, Query, description = "Split each line by the values of this dimension type")
     ),
     responses(
         (status = 200, description = "Profit and loss statement generated successfully", body = ApiResponse<ProfitLossStatement>),
@@ -91,6 +138,7 @@ pub async fn get_trial_balance(
 )]
 pub async fn get_profit_loss(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Query(params): Query<ProfitLossQuery>,
 ) -> Result<impl axum::response::IntoResponse> {
     // Validate request
@@ -102,8 +150,11 @@ pub async fn get_profit_loss(
         ));
     }
 
+    let filter = params.dimensions.to_filter()?;
+
     let profit_loss = state.reporting_service
-        .generate_profit_loss(&state.pool, params.date_range)
+        .for_company(Some(company_id))
+        .generate_profit_loss(&state.pool, params.date_range, &filter)
         .await?;
 
     Ok(success(profit_loss))
@@ -115,7 +166,9 @@ pub async fn get_profit_loss(
     path = "/api/v1/reports/balance-sheet",
     tag = "reporting",
     params(
-        ("as_of_date" = chrono::NaiveDate, Query, description = "As of date for the balance sheet")
+        ("as_of_date" = chrono::NaiveDate, Query, description = "As of date for the balance sheet"),
+        ("dimension_value_ids" = Option<String>, Query, description = "Comma-separated dimension values lines must carry"),
+        ("group_by_dimension_type_id" = Option<uuid::Uuid>, Query, description = "Split each line by the values of this dimension type")
     ),
     responses(
         (status = 200, description = "Balance sheet generated successfully", body = ApiResponse<BalanceSheet>),
@@ -128,13 +181,17 @@ pub async fn get_profit_loss(
 )]
 pub async fn get_balance_sheet(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Query(params): Query<BalanceSheetQuery>,
 ) -> Result<impl axum::response::IntoResponse> {
     // Validate request
     params.date.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
 
+    let filter = params.dimensions.to_filter()?;
+
     let balance_sheet = state.reporting_service
-        .generate_balance_sheet(&state.pool, params.date)
+        .for_company(Some(company_id))
+        .generate_balance_sheet(&state.pool, params.date, &filter)
         .await?;
 
     Ok(success(balance_sheet))
@@ -146,7 +203,9 @@ pub async fn get_balance_sheet(
     path = "/api/v1/reports/ar-aging",
     tag = "reporting",
     params(
-        ("as_of_date" = chrono::NaiveDate, Query, description = "As of date for the aging report")
+        ("as_of_date" = chrono::NaiveDate, Query, description = "As of date for the aging report"),
+        ("dimension_value_ids" = Option<String>, Query, description = "Comma-separated dimension values lines must carry"),
+        ("group_by_dimension_type_id" = Option<uuid::Uuid>, Query, description = "Split each line by the values of this dimension type")
     ),
     responses(
         (status = 200, description = "Accounts receivable aging report generated successfully", body = ApiResponse<AccountsReceivableAging>),
@@ -159,13 +218,17 @@ pub async fn get_balance_sheet(
 )]
 pub async fn get_ar_aging(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Query(params): Query<AgingQuery>,
 ) -> Result<impl axum::response::IntoResponse> {
     // Validate request
     params.date.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
 
+    let filter = params.dimensions.to_filter()?;
+
     let ar_aging = state.reporting_service
-        .generate_ar_aging(&state.pool, params.date)
+        .for_company(Some(company_id))
+        .generate_ar_aging(&state.pool, params.date, &filter)
         .await?;
 
     Ok(success(ar_aging))
@@ -0,0 +1,99 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::{IntoResponse, Response},
+    Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{
+    BatchStatementRequest, CustomerStatement, StatementFormat, StatementRequest,
+};
+use crate::middleware::ActiveCompany;
+use crate::routes::AppState;
+use crate::services::StatementService;
+use crate::utils::{pdf, success, ApiResponse, AppError, Result};
+
+/// Get a customer statement as JSON or PDF
+#[utoipa::path(
+    get,
+    path = "/api/v1/customers/{id}/statement",
+    tag = "statements",
+    params(
+        ("id" = Uuid, Path, description = "Customer ID"),
+        ("start_date" = Option<String>, Query, description = "Period start (YYYY-MM-DD), defaults to the first of the end month"),
+        ("end_date" = String, Query, description = "Statement date (YYYY-MM-DD)"),
+        ("style" = Option<String>, Query, description = "balance_forward (default) or open_item"),
+        ("format" = Option<String>, Query, description = "json (default) or pdf")
+    ),
+    responses(
+        (status = 200, description = "Statement generated successfully", body = ApiResponse<CustomerStatement>),
+        (status = 200, description = "Statement PDF", content_type = "application/pdf"),
+        (status = 400, description = "Invalid date range"),
+        (status = 404, description = "Customer not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_customer_statement(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(customer_id): Path<Uuid>,
+    Query(req): Query<StatementRequest>,
+) -> Result<Response> {
+    // Validate request
+    req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let statement = state.statement_service
+        .for_company(Some(company_id))
+        .generate_statement(&state.pool, customer_id, req.start_date, req.end_date, req.style)
+        .await?;
+
+    match req.format {
+        StatementFormat::Json => Ok(success(statement).into_response()),
+        StatementFormat::Pdf => {
+            let filename = format!("statement_{}_{}.pdf", customer_id, statement.end_date);
+            Ok(pdf(StatementService::render_pdf(&[statement]), &filename))
+        }
+    }
+}
+
+/// Generate statements for every customer with an outstanding balance
+#[utoipa::path(
+    post,
+    path = "/api/v1/statements/batch",
+    tag = "statements",
+    request_body = BatchStatementRequest,
+    responses(
+        (status = 200, description = "Statements generated successfully", body = ApiResponse<Vec<CustomerStatement>>),
+        (status = 200, description = "All statements in one PDF", content_type = "application/pdf"),
+        (status = 400, description = "Invalid date range"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn generate_batch_statements(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Json(req): Json<BatchStatementRequest>,
+) -> Result<Response> {
+    // Validate request
+    req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let statements = state.statement_service
+        .for_company(Some(company_id))
+        .generate_batch(&state.pool, req.start_date, req.end_date, req.style, req.company_id)
+        .await?;
+
+    match req.format {
+        StatementFormat::Json => Ok(success(statements).into_response()),
+        StatementFormat::Pdf => {
+            let filename = format!("statements_{}.pdf", req.end_date);
+            Ok(pdf(StatementService::render_pdf(&statements), &filename))
+        }
+    }
+}
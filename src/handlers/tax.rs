@@ -0,0 +1,331 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{
+    TaxCode, TaxRate, TaxCodeWithRates, CreateTaxCodeRequest, CreateTaxRateRequest,
+    SetupCanadianTaxCodesRequest, TaxReturn, TaxReturnWorksheet, TaxReturnPeriodRequest,
+    FileTaxReturnRequest,
+};
+use crate::middleware::ActiveCompany;
+use crate::routes::AppState;
+use crate::utils::{created, success, ApiResponse, AppError, Result};
+
+/// Query parameters for listing tax codes
+#[derive(Debug, Deserialize)]
+pub struct ListTaxCodesQuery {
+    #[serde(default)]
+    pub include_inactive: Option<bool>,
+}
+
+/// Create a tax code with its component rates
+#[utoipa::path(
+    post,
+    path = "/api/v1/tax-codes",
+    tag = "taxes",
+    request_body = CreateTaxCodeRequest,
+    responses(
+        (status = 201, description = "Tax code created successfully", body = ApiResponse<TaxCodeWithRates>),
+        (status = 400, description = "Invalid request data"),
+        (status = 409, description = "Tax code already exists"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_tax_code(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Json(req): Json<CreateTaxCodeRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    // Validate request
+    req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let tax_code = state.tax_service
+        .for_company(Some(company_id))
+        .create_tax_code(&state.pool, req)
+        .await?;
+
+    Ok(created(tax_code))
+}
+
+/// List tax codes
+#[utoipa::path(
+    get,
+    path = "/api/v1/tax-codes",
+    tag = "taxes",
+    params(
+        ("include_inactive" = Option<bool>, Query, description = "Include inactive tax codes")
+    ),
+    responses(
+        (status = 200, description = "Tax codes retrieved successfully", body = ApiResponse<Vec<TaxCode>>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_tax_codes(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Query(params): Query<ListTaxCodesQuery>,
+) -> Result<impl axum::response::IntoResponse> {
+    let tax_codes = state.tax_service
+        .for_company(Some(company_id))
+        .list_tax_codes(&state.pool, params.include_inactive.unwrap_or(false))
+        .await?;
+
+    Ok(success(tax_codes))
+}
+
+/// Get a tax code with its rate history
+#[utoipa::path(
+    get,
+    path = "/api/v1/tax-codes/{id}",
+    tag = "taxes",
+    params(
+        ("id" = Uuid, Path, description = "Tax code ID")
+    ),
+    responses(
+        (status = 200, description = "Tax code retrieved successfully", body = ApiResponse<TaxCodeWithRates>),
+        (status = 404, description = "Tax code not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_tax_code(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(tax_code_id): Path<Uuid>,
+) -> Result<impl axum::response::IntoResponse> {
+    let tax_code = state.tax_service
+        .for_company(Some(company_id))
+        .get_tax_code(&state.pool, tax_code_id)
+        .await?;
+
+    Ok(success(tax_code))
+}
+
+/// Add a rate to a tax code (e.g. a rate change from a new effective date)
+#[utoipa::path(
+    post,
+    path = "/api/v1/tax-codes/{id}/rates",
+    tag = "taxes",
+    params(
+        ("id" = Uuid, Path, description = "Tax code ID")
+    ),
+    request_body = CreateTaxRateRequest,
+    responses(
+        (status = 201, description = "Tax rate added successfully", body = ApiResponse<TaxRate>),
+        (status = 400, description = "Invalid request data"),
+        (status = 404, description = "Tax code not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn add_tax_rate(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(tax_code_id): Path<Uuid>,
+    Json(req): Json<CreateTaxRateRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    // Validate request
+    req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let rate = state.tax_service
+        .for_company(Some(company_id))
+        .add_tax_rate(&state.pool, tax_code_id, req)
+        .await?;
+
+    Ok(created(rate))
+}
+
+/// Create the standard Canadian GST/HST/PST/QST tax codes
+#[utoipa::path(
+    post,
+    path = "/api/v1/tax-codes/canadian-defaults",
+    tag = "taxes",
+    request_body = SetupCanadianTaxCodesRequest,
+    responses(
+        (status = 201, description = "Tax codes created successfully", body = ApiResponse<Vec<TaxCodeWithRates>>),
+        (status = 409, description = "A tax code already exists"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn setup_canadian_tax_codes(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Json(req): Json<SetupCanadianTaxCodesRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let tax_codes = state.tax_service
+        .for_company(Some(company_id))
+        .setup_canadian_tax_codes(&state.pool, req)
+        .await?;
+
+    Ok(created(tax_codes))
+}
+
+/// Sales tax return worksheet (GST34 lines) for a filing period
+#[utoipa::path(
+    get,
+    path = "/api/v1/reports/sales-tax-return",
+    tag = "reporting",
+    params(
+        ("period_start" = chrono::NaiveDate, Query, description = "First day of the filing period"),
+        ("period_end" = chrono::NaiveDate, Query, description = "Last day of the filing period"),
+        ("agency" = Option<String>, Query, description = "Tax agency (defaults to CRA)"),
+        ("company_id" = Option<Uuid>, Query, description = "Company ID")
+    ),
+    responses(
+        (status = 200, description = "Sales tax return generated successfully", body = ApiResponse<TaxReturnWorksheet>),
+        (status = 400, description = "Invalid request data"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_sales_tax_return(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Query(params): Query<TaxReturnPeriodRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let worksheet = state.tax_service
+        .for_company(Some(company_id))
+        .generate_return_worksheet(&state.pool, params)
+        .await?;
+
+    Ok(success(worksheet))
+}
+
+/// Prepare a draft tax return for a filing period
+#[utoipa::path(
+    post,
+    path = "/api/v1/tax-returns",
+    tag = "taxes",
+    request_body = TaxReturnPeriodRequest,
+    responses(
+        (status = 201, description = "Tax return created successfully", body = ApiResponse<TaxReturn>),
+        (status = 400, description = "Invalid request data"),
+        (status = 409, description = "A tax return already covers the period"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_tax_return(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Json(req): Json<TaxReturnPeriodRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let tax_return = state.tax_service
+        .for_company(Some(company_id))
+        .create_tax_return(&state.pool, req)
+        .await?;
+
+    Ok(created(tax_return))
+}
+
+/// List tax returns
+#[utoipa::path(
+    get,
+    path = "/api/v1/tax-returns",
+    tag = "taxes",
+    responses(
+        (status = 200, description = "Tax returns retrieved successfully", body = ApiResponse<Vec<TaxReturn>>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_tax_returns(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+) -> Result<impl axum::response::IntoResponse> {
+    let tax_returns = state.tax_service
+        .for_company(Some(company_id))
+        .list_tax_returns(&state.pool)
+        .await?;
+
+    Ok(success(tax_returns))
+}
+
+/// Get a tax return
+#[utoipa::path(
+    get,
+    path = "/api/v1/tax-returns/{id}",
+    tag = "taxes",
+    params(
+        ("id" = Uuid, Path, description = "Tax return ID")
+    ),
+    responses(
+        (status = 200, description = "Tax return retrieved successfully", body = ApiResponse<TaxReturn>),
+        (status = 404, description = "Tax return not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_tax_return(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(tax_return_id): Path<Uuid>,
+) -> Result<impl axum::response::IntoResponse> {
+    let tax_return = state.tax_service
+        .for_company(Some(company_id))
+        .get_tax_return(&state.pool, tax_return_id)
+        .await?;
+
+    Ok(success(tax_return))
+}
+
+/// Mark a tax return as filed, posting the settlement entry and locking the period
+#[utoipa::path(
+    post,
+    path = "/api/v1/tax-returns/{id}/file",
+    tag = "taxes",
+    params(
+        ("id" = Uuid, Path, description = "Tax return ID")
+    ),
+    request_body = FileTaxReturnRequest,
+    responses(
+        (status = 200, description = "Tax return filed successfully", body = ApiResponse<TaxReturn>),
+        (status = 404, description = "Tax return not found"),
+        (status = 409, description = "Tax return already filed"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn file_tax_return(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(tax_return_id): Path<Uuid>,
+    Json(req): Json<FileTaxReturnRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    // TODO: Extract user_id from JWT token when auth middleware is fully integrated
+    let filed_by = None;
+
+    let tax_return = state.tax_service
+        .for_company(Some(company_id))
+        .file_tax_return(&state.pool, tax_return_id, req, filed_by)
+        .await?;
+
+    Ok(success(tax_return))
+}
@@ -6,6 +6,7 @@ use serde::Deserialize;
 use uuid::Uuid;
 
 use crate::models::{CreateTransactionRequest, Transaction, TransactionWithLineItems, TransactionStatus};
+use crate::middleware::ActiveCompany;
 use crate::routes::AppState;
 use crate::utils::{created, no_content, success, ApiResponse, AppError, Result};
 
@@ -15,8 +16,6 @@ pub struct ListTransactionsQuery {
     #[serde(default)]
     pub status: Option<String>,
     #[serde(default)]
-    pub company_id: Option<Uuid>,
-    #[serde(default)]
     pub limit: Option<i64>,
 }
 
@@ -33,7 +32,6 @@ pub struct UpdateStatusRequest {
     tag = "transactions",
     params(
         ("status" = Option<String>, Query, description = "Filter by status (draft, posted, void)"),
-        ("company_id" = Option<String>, Query, description = "Filter by company ID"),
         ("limit" = Option<i64>, Query, description = "Maximum number of results")
     ),
     responses(
@@ -46,6 +44,7 @@ pub struct UpdateStatusRequest {
 )]
 pub async fn list_transactions(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Query(params): Query<ListTransactionsQuery>,
 ) -> Result<impl axum::response::IntoResponse> {
     // Parse status if provided
@@ -57,7 +56,8 @@ pub async fn list_transactions(
 
     let transactions = state
         .transaction_service
-        .list_transactions(&state.pool, status, params.company_id, params.limit)
+        .for_company(Some(company_id))
+        .list_transactions(&state.pool, status, params.limit)
         .await?;
 
     Ok(success(transactions))
@@ -80,6 +80,7 @@ pub async fn list_transactions(
 )]
 pub async fn create_transaction(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Json(req): Json<CreateTransactionRequest>,
 ) -> Result<impl axum::response::IntoResponse> {
     // TODO: Extract user_id from JWT token when auth middleware is fully integrated
@@ -87,6 +88,7 @@ pub async fn create_transaction(
 
     let transaction = state
         .transaction_service
+        .for_company(Some(company_id))
         .create_transaction(&state.pool, req, created_by)
         .await?;
 
@@ -111,10 +113,12 @@ pub async fn create_transaction(
 )]
 pub async fn get_transaction(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Path(id): Path<Uuid>,
 ) -> Result<impl axum::response::IntoResponse> {
     let transaction = state
         .transaction_service
+        .for_company(Some(company_id))
         .get_transaction_by_id(&state.pool, id)
         .await?;
 
@@ -141,6 +145,7 @@ pub async fn get_transaction(
 )]
 pub async fn update_transaction_status(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Path(id): Path<Uuid>,
     Json(req): Json<UpdateStatusRequest>,
 ) -> Result<impl axum::response::IntoResponse> {
@@ -148,6 +153,7 @@ pub async fn update_transaction_status(
 
     let transaction = state
         .transaction_service
+        .for_company(Some(company_id))
         .update_transaction_status(&state.pool, id, new_status)
         .await?;
 
@@ -173,10 +179,12 @@ pub async fn update_transaction_status(
 )]
 pub async fn delete_transaction(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Path(id): Path<Uuid>,
 ) -> Result<impl axum::response::IntoResponse> {
     state
         .transaction_service
+        .for_company(Some(company_id))
         .delete_transaction(&state.pool, id)
         .await?;
 
@@ -201,10 +209,12 @@ pub async fn delete_transaction(
 )]
 pub async fn get_account_balance(
     State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
     Path(account_id): Path<Uuid>,
 ) -> Result<impl axum::response::IntoResponse> {
     let balance = state
         .transaction_service
+        .for_company(Some(company_id))
         .get_account_balance(&state.pool, account_id)
         .await?;
 
@@ -0,0 +1,214 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::models::{InviteUserRequest, UpdateUserRoleRequest, UserAccount, UserRole};
+use crate::middleware::AuthUser;
+use crate::routes::AppState;
+use crate::utils::{created, no_content, success, ApiResponse, Result};
+
+/// Query parameters for listing users
+#[derive(Debug, Deserialize)]
+pub struct ListUsersQuery {
+    /// Part of a username or email address
+    #[serde(default)]
+    pub search: Option<String>,
+    #[serde(default)]
+    pub role: Option<UserRole>,
+    #[serde(default)]
+    pub disabled: Option<bool>,
+}
+
+/// List and search users (admin only)
+#[utoipa::path(
+    get,
+    path = "/api/v1/users",
+    tag = "users",
+    params(
+        ("search" = Option<String>, Query, description = "Part of a username or email address"),
+        ("role" = Option<String>, Query, description = "Filter by role (admin, accountant, viewer)"),
+        ("disabled" = Option<bool>, Query, description = "Only disabled or only active users")
+    ),
+    responses(
+        (status = 200, description = "Users retrieved successfully", body = ApiResponse<Vec<UserAccount>>),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Admins only")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_users(
+    State(state): State<AppState>,
+    Query(params): Query<ListUsersQuery>,
+) -> Result<ApiResponse<Vec<UserAccount>>> {
+    let users = state.auth_service
+        .list_users(&state.pool, params.search, params.role, params.disabled)
+        .await?;
+
+    Ok(success(users))
+}
+
+/// Invite a user with a role; they get an email to choose a password (admin only)
+#[utoipa::path(
+    post,
+    path = "/api/v1/users",
+    tag = "users",
+    request_body = InviteUserRequest,
+    responses(
+        (status = 201, description = "User invited", body = ApiResponse<UserAccount>),
+        (status = 400, description = "Invalid request data"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Admins only"),
+        (status = 409, description = "Username or email already taken")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn invite_user(
+    State(state): State<AppState>,
+    Json(req): Json<InviteUserRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let user = state.auth_service.invite_user(&state.pool, req).await?;
+    Ok(created(user))
+}
+
+/// Get a user (admin only)
+#[utoipa::path(
+    get,
+    path = "/api/v1/users/{id}",
+    tag = "users",
+    params(
+        ("id" = Uuid, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "User retrieved successfully", body = ApiResponse<UserAccount>),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Admins only"),
+        (status = 404, description = "User not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_user(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<ApiResponse<UserAccount>> {
+    let user = state.auth_service.get_user_account(&state.pool, id).await?;
+    Ok(success(user))
+}
+
+/// Change a user's role (admin only)
+#[utoipa::path(
+    put,
+    path = "/api/v1/users/{id}/role",
+    tag = "users",
+    params(
+        ("id" = Uuid, Path, description = "User ID")
+    ),
+    request_body = UpdateUserRoleRequest,
+    responses(
+        (status = 200, description = "Role changed", body = ApiResponse<UserAccount>),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Admins only"),
+        (status = 404, description = "User not found"),
+        (status = 409, description = "That would leave no active admin")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_user_role(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateUserRoleRequest>,
+) -> Result<ApiResponse<UserAccount>> {
+    let user = state.auth_service.set_user_role(&state.pool, id, req.role).await?;
+    Ok(success(user))
+}
+
+/// Disable an account and log it out everywhere (admin only)
+#[utoipa::path(
+    post,
+    path = "/api/v1/users/{id}/disable",
+    tag = "users",
+    params(
+        ("id" = Uuid, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "Account disabled", body = ApiResponse<UserAccount>),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Admins only"),
+        (status = 404, description = "User not found"),
+        (status = 409, description = "Own account, or the last active admin")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn disable_user(
+    State(state): State<AppState>,
+    AuthUser(admin): AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<ApiResponse<UserAccount>> {
+    let user = state.auth_service.set_user_disabled(&state.pool, admin.id, id, true).await?;
+    Ok(success(user))
+}
+
+/// Re-enable a disabled account (admin only)
+#[utoipa::path(
+    post,
+    path = "/api/v1/users/{id}/enable",
+    tag = "users",
+    params(
+        ("id" = Uuid, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "Account enabled", body = ApiResponse<UserAccount>),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Admins only"),
+        (status = 404, description = "User not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn enable_user(
+    State(state): State<AppState>,
+    AuthUser(admin): AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<ApiResponse<UserAccount>> {
+    let user = state.auth_service.set_user_disabled(&state.pool, admin.id, id, false).await?;
+    Ok(success(user))
+}
+
+/// Make a user choose a new password: the current one stops working and a reset email is sent (admin only)
+#[utoipa::path(
+    post,
+    path = "/api/v1/users/{id}/reset-password",
+    tag = "users",
+    params(
+        ("id" = Uuid, Path, description = "User ID")
+    ),
+    responses(
+        (status = 204, description = "Password reset and email sent"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Admins only"),
+        (status = 404, description = "User not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn force_password_reset(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl axum::response::IntoResponse> {
+    state.auth_service.force_password_reset(&state.pool, id).await?;
+    Ok(no_content())
+}
@@ -0,0 +1,186 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{
+    VendorCredit, VendorCreditStatus, VendorCreditWithLineItems, CreateVendorCreditRequest,
+    VendorCreditApplicationRequest,
+};
+use crate::middleware::ActiveCompany;
+use crate::routes::AppState;
+use crate::utils::{created, success, ApiResponse, AppError, Result};
+
+/// Query parameters for listing vendor credits
+#[derive(Debug, Deserialize)]
+pub struct ListVendorCreditsQuery {
+    #[serde(default)]
+    pub vendor_id: Option<Uuid>,
+    #[serde(default)]
+    pub status: Option<VendorCreditStatus>,
+}
+
+/// Apply vendor credit request
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct ApplyVendorCreditRequest {
+    pub applications: Vec<VendorCreditApplicationRequest>,
+}
+
+/// Create a vendor credit
+#[utoipa::path(
+    post,
+    path = "/api/v1/vendor-credits",
+    tag = "vendor-credits",
+    request_body = CreateVendorCreditRequest,
+    responses(
+        (status = 201, description = "Vendor credit created successfully", body = ApiResponse<VendorCreditWithLineItems>),
+        (status = 400, description = "Invalid request data"),
+        (status = 409, description = "Vendor credit number already exists"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_vendor_credit(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Json(req): Json<CreateVendorCreditRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    // Validate request
+    req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let vendor_credit = state.vendor_credit_service
+        .for_company(Some(company_id))
+        .create_vendor_credit(&state.pool, req)
+        .await?;
+
+    Ok(created(vendor_credit))
+}
+
+/// List vendor credits
+#[utoipa::path(
+    get,
+    path = "/api/v1/vendor-credits",
+    tag = "vendor-credits",
+    params(
+        ("vendor_id" = Option<Uuid>, Query, description = "Filter by vendor"),
+        ("status" = Option<String>, Query, description = "Filter by status (open, applied, void)")
+    ),
+    responses(
+        (status = 200, description = "Vendor credits retrieved successfully", body = ApiResponse<Vec<VendorCredit>>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_vendor_credits(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Query(params): Query<ListVendorCreditsQuery>,
+) -> Result<impl axum::response::IntoResponse> {
+    let vendor_credits = state.vendor_credit_service
+        .for_company(Some(company_id))
+        .list_vendor_credits(&state.pool, params.vendor_id, params.status)
+        .await?;
+
+    Ok(success(vendor_credits))
+}
+
+/// Get a vendor credit with line items and applications
+#[utoipa::path(
+    get,
+    path = "/api/v1/vendor-credits/{id}",
+    tag = "vendor-credits",
+    params(
+        ("id" = Uuid, Path, description = "Vendor credit ID")
+    ),
+    responses(
+        (status = 200, description = "Vendor credit retrieved successfully", body = ApiResponse<VendorCreditWithLineItems>),
+        (status = 404, description = "Vendor credit not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_vendor_credit(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(vendor_credit_id): Path<Uuid>,
+) -> Result<impl axum::response::IntoResponse> {
+    let vendor_credit = state.vendor_credit_service
+        .for_company(Some(company_id))
+        .get_vendor_credit(&state.pool, vendor_credit_id)
+        .await?;
+
+    Ok(success(vendor_credit))
+}
+
+/// Apply a vendor credit to open bills
+#[utoipa::path(
+    put,
+    path = "/api/v1/vendor-credits/{id}/apply",
+    tag = "vendor-credits",
+    params(
+        ("id" = Uuid, Path, description = "Vendor credit ID")
+    ),
+    request_body = ApplyVendorCreditRequest,
+    responses(
+        (status = 200, description = "Vendor credit applied successfully", body = ApiResponse<VendorCredit>),
+        (status = 400, description = "Invalid request data"),
+        (status = 404, description = "Vendor credit not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn apply_vendor_credit(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(vendor_credit_id): Path<Uuid>,
+    Json(req): Json<ApplyVendorCreditRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let vendor_credit = state.vendor_credit_service
+        .for_company(Some(company_id))
+        .apply_vendor_credit(&state.pool, vendor_credit_id, req.applications)
+        .await?;
+
+    Ok(success(vendor_credit))
+}
+
+/// Void an unapplied vendor credit
+#[utoipa::path(
+    post,
+    path = "/api/v1/vendor-credits/{id}/void",
+    tag = "vendor-credits",
+    params(
+        ("id" = Uuid, Path, description = "Vendor credit ID")
+    ),
+    responses(
+        (status = 200, description = "Vendor credit voided successfully", body = ApiResponse<VendorCredit>),
+        (status = 400, description = "Vendor credit has been applied"),
+        (status = 404, description = "Vendor credit not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn void_vendor_credit(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(vendor_credit_id): Path<Uuid>,
+) -> Result<impl axum::response::IntoResponse> {
+    let vendor_credit = state.vendor_credit_service
+        .for_company(Some(company_id))
+        .void_vendor_credit(&state.pool, vendor_credit_id)
+        .await?;
+
+    Ok(success(vendor_credit))
+}
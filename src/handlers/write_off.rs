@@ -0,0 +1,114 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::models::{
+    Invoice, InvoiceWriteOff, RecoverWriteOffRequest, WriteOffInvoicesRequest, WriteOffWithRecoveries,
+};
+use crate::middleware::ActiveCompany;
+use crate::routes::AppState;
+use crate::utils::{created, success, ApiResponse, Result};
+
+/// Query parameters for listing write-offs
+#[derive(Debug, Deserialize)]
+pub struct ListWriteOffsQuery {
+    #[serde(default)]
+    pub customer_id: Option<Uuid>,
+    #[serde(default)]
+    pub invoice_id: Option<Uuid>,
+}
+
+/// Write off the remaining balance of one or more invoices as bad debt
+#[utoipa::path(
+    post,
+    path = "/api/v1/write-offs",
+    tag = "write-offs",
+    request_body = WriteOffInvoicesRequest,
+    responses(
+        (status = 201, description = "Invoices written off successfully", body = ApiResponse<Vec<InvoiceWriteOff>>),
+        (status = 400, description = "Invalid request data or an invoice has no open balance"),
+        (status = 404, description = "Invoice or account not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn write_off_invoices(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Json(req): Json<WriteOffInvoicesRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let write_offs = state.write_off_service
+        .for_company(Some(company_id))
+        .write_off_invoices(&state.pool, req, None)
+        .await?;
+
+    Ok(created(write_offs))
+}
+
+/// List write-offs and their recoveries
+#[utoipa::path(
+    get,
+    path = "/api/v1/write-offs",
+    tag = "write-offs",
+    params(
+        ("customer_id" = Option<Uuid>, Query, description = "Filter by customer"),
+        ("invoice_id" = Option<Uuid>, Query, description = "Filter by invoice")
+    ),
+    responses(
+        (status = 200, description = "Write-offs retrieved successfully", body = ApiResponse<Vec<WriteOffWithRecoveries>>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_write_offs(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Query(params): Query<ListWriteOffsQuery>,
+) -> Result<impl axum::response::IntoResponse> {
+    let write_offs = state.write_off_service
+        .for_company(Some(company_id))
+        .list_write_offs(&state.pool, params.customer_id, params.invoice_id)
+        .await?;
+
+    Ok(success(write_offs))
+}
+
+/// Reinstate written-off balance on an invoice when the customer pays after all
+#[utoipa::path(
+    post,
+    path = "/api/v1/invoices/{id}/recover",
+    tag = "write-offs",
+    params(
+        ("id" = Uuid, Path, description = "Invoice ID")
+    ),
+    request_body = RecoverWriteOffRequest,
+    responses(
+        (status = 200, description = "Balance reinstated; apply the payment to the invoice as usual", body = ApiResponse<Invoice>),
+        (status = 400, description = "Nothing to recover or invalid amount"),
+        (status = 404, description = "Invoice not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn recover_write_off(
+    State(state): State<AppState>,
+    ActiveCompany(company_id): ActiveCompany,
+    Path(invoice_id): Path<Uuid>,
+    Json(req): Json<RecoverWriteOffRequest>,
+) -> Result<impl axum::response::IntoResponse> {
+    let invoice = state.write_off_service
+        .for_company(Some(company_id))
+        .recover(&state.pool, invoice_id, req, None)
+        .await?;
+
+    Ok(success(invoice))
+}
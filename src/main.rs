@@ -13,7 +13,9 @@ use tower_http::cors::{Any, CorsLayer};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use crate::routes::create_routes;
-use crate::services::{AuthService, AccountService, TransactionService, ContactService, InvoiceService, PaymentService, BillService, ImportService, ReportingService, CacheService};
+use crate::services::{AuthService, AccountService, TransactionService, ContactService, InvoiceService, PaymentService, BillService, ImportService, ReportingService, InventoryService, TaxService, CreditMemoService, StatementService, EstimateService, PurchaseOrderService, VendorCreditService, EmailService, SmtpConfig, DunningService, FinanceChargeService, WriteOffService, NumberingService, BillableExpenseService, DimensionService, CompanyService, CacheService, Mailer};
+use crate::models::{CreateUserRequest, UserRole};
+use crate::services::auth::{PasswordPolicy, RegistrationMode};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -67,7 +69,11 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("✅ Redis cache service initialized");
 
     // Initialize services with cache
-    let auth_service = AuthService::new(jwt_secret);
+    let auth_service = AuthService::new(jwt_secret)
+        .with_password_policy(PasswordPolicy::from_env())
+        .with_registration(RegistrationMode::from_env())
+        .with_mailer(Mailer::from_env())
+        .with_password_reset_url(env::var("PASSWORD_RESET_URL").ok().filter(|url| !url.is_empty()));
     let account_service = AccountService::new_with_cache(cache_service.clone());
     let transaction_service = TransactionService::new_with_cache(cache_service.clone());
     let contact_service = ContactService::new_with_cache(cache_service.clone());
@@ -76,6 +82,69 @@ async fn main() -> anyhow::Result<()> {
     let bill_service = BillService::new_with_cache(cache_service.clone());
     let import_service = ImportService::new(cache_service.clone());
     let reporting_service = ReportingService::new_with_cache(cache_service.clone());
+    let inventory_service = InventoryService::new_with_cache(cache_service.clone());
+    let tax_service = TaxService::new_with_cache(cache_service.clone());
+    let credit_memo_service = CreditMemoService::new_with_cache(cache_service.clone());
+    let statement_service = StatementService::new_with_cache(cache_service.clone());
+    let estimate_service = EstimateService::new_with_cache(cache_service.clone());
+    let purchase_order_service = PurchaseOrderService::new_with_cache(cache_service.clone());
+    let vendor_credit_service = VendorCreditService::new_with_cache(cache_service.clone());
+    let email_service = EmailService::new(SmtpConfig::from_env(), cache_service.clone());
+    let dunning_service = DunningService::new(email_service.clone(), cache_service.clone());
+    let finance_charge_service = FinanceChargeService::new_with_cache(cache_service.clone());
+    let write_off_service = WriteOffService::new_with_cache(cache_service.clone());
+    let numbering_service = NumberingService::new();
+    let billable_expense_service = BillableExpenseService::new_with_cache(cache_service.clone());
+    let dimension_service = DimensionService::new_with_cache(cache_service.clone());
+    let company_service = CompanyService::new_with_cache(cache_service.clone());
+
+    // Create the first admin of a new install; self-registration can't
+    let admin_setting = |name: &str| env::var(name).ok().filter(|value| !value.is_empty());
+    if let (Some(username), Some(email), Some(password)) =
+        (admin_setting("ADMIN_USERNAME"), admin_setting("ADMIN_EMAIL"), admin_setting("ADMIN_PASSWORD"))
+    {
+        let admin = auth_service
+            .bootstrap_admin(&pool, CreateUserRequest { username, email, password, role: UserRole::Admin })
+            .await?;
+        if let Some(admin) = admin {
+            tracing::info!("✅ Created admin user {}", admin.username);
+        }
+    }
+
+    // Mark overdue invoices and send dunning reminders on a schedule (0 disables the job)
+    let dunning_interval = env::var("DUNNING_INTERVAL_SECONDS")
+        .ok()
+        .and_then(|seconds| seconds.parse::<u64>().ok())
+        .unwrap_or(86400);
+    if dunning_interval > 0 {
+        let pool = pool.clone();
+        let dunning_service = dunning_service.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(dunning_interval));
+            loop {
+                interval.tick().await;
+                let scopes = match CompanyService::all_scopes(&pool).await {
+                    Ok(scopes) => scopes,
+                    Err(e) => {
+                        tracing::error!("Dunning run failed: {}", e);
+                        continue;
+                    }
+                };
+                // Each company runs on its own schedule and templates
+                for company_id in scopes {
+                    match dunning_service.for_company(company_id).run(&pool, chrono::Utc::now().date_naive()).await {
+                        Ok(run) => tracing::info!(
+                            "Dunning run: {} invoices marked overdue, {} reminders processed",
+                            run.marked_overdue.len(),
+                            run.reminders.len()
+                        ),
+                        Err(e) => tracing::error!("Dunning run failed: {}", e),
+                    }
+                }
+            }
+        });
+        tracing::info!("✅ Dunning job scheduled every {} seconds", dunning_interval);
+    }
 
     // Configure CORS
     let cors = CorsLayer::new()
@@ -84,7 +153,7 @@ async fn main() -> anyhow::Result<()> {
         .allow_headers(Any);
 
     // Create application routes
-    let app = create_routes(pool, auth_service, account_service, transaction_service, contact_service, invoice_service, payment_service, bill_service, import_service, reporting_service, cache_service)
+    let app = create_routes(pool, auth_service, account_service, transaction_service, contact_service, invoice_service, payment_service, bill_service, import_service, reporting_service, inventory_service, tax_service, credit_memo_service, statement_service, estimate_service, purchase_order_service, vendor_credit_service, email_service, dunning_service, finance_charge_service, write_off_service, numbering_service, billable_expense_service, dimension_service, company_service, cache_service)
         .layer(cors)
         .layer(tower_http::trace::TraceLayer::new_for_http());
 
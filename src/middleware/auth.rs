@@ -1,48 +1,55 @@
 use axum::{
-    extract::{Request, State},
-    http::header::AUTHORIZATION,
+    extract::{FromRequestParts, MatchedPath, Request, State},
+    http::{header::AUTHORIZATION, request::Parts, HeaderMap},
     middleware::Next,
     response::Response,
 };
-use sqlx::PgPool;
-use uuid::Uuid;
 
+use crate::middleware::permission::{required_permission, role_permission, MFA_SETUP_ROUTES, PUBLIC_ROUTES};
 use crate::models::User;
+use crate::routes::AppState;
 use crate::services::AuthService;
+use crate::services::auth::Claims;
 use crate::utils::AppError;
 
 /// Extension to inject authenticated user into request
-#[allow(dead_code)]
 #[derive(Clone)]
 pub struct AuthUser(pub User);
 
-/// Middleware to validate JWT and inject user into request
-#[allow(dead_code)]
+/// Middleware to validate JWT, check the caller's role against the route and inject user into request
 pub async fn auth_middleware(
-    State(auth_service): State<AuthService>,
-    State(pool): State<PgPool>,
+    State(state): State<AppState>,
     mut request: Request,
     next: Next,
 ) -> Result<Response, AppError> {
-    // Extract authorization header
-    let auth_header = request
-        .headers()
-        .get(AUTHORIZATION)
-        .and_then(|h| h.to_str().ok())
-        .ok_or_else(|| AppError::Unauthorized("Missing authorization header".to_string()))?;
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
 
-    // Extract token from "Bearer <token>" format
-    let token = auth_header
-        .strip_prefix("Bearer ")
-        .ok_or_else(|| AppError::Unauthorized("Invalid authorization format".to_string()))?;
+    if PUBLIC_ROUTES.contains(&route.as_str()) {
+        return Ok(next.run(request).await);
+    }
 
     // Validate token and extract claims
-    let token_data = auth_service.validate_token(token)?;
-    let user_id = Uuid::parse_str(&token_data.claims.sub)
-        .map_err(|_| AppError::InvalidToken)?;
+    let claims = bearer_claims(request.headers(), &state.auth_service)?
+        .ok_or_else(|| AppError::Unauthorized("Missing authorization header".to_string()))?;
 
     // Fetch user from database
-    let user = auth_service.get_user_by_id(&pool, user_id).await?;
+    let user = state.auth_service.authenticated_user(&state.pool, &claims).await?;
+
+    if role_permission(&user.role) < required_permission(request.method(), &route) {
+        return Err(AppError::Forbidden(format!(
+            "The {} role is not allowed to {} {}", user.role, request.method(), route
+        )));
+    }
+
+    if !MFA_SETUP_ROUTES.contains(&route.as_str()) && state.auth_service.mfa_setup_required(&state.pool, &user).await? {
+        return Err(AppError::Forbidden(format!(
+            "The {} role requires two-factor authentication; set it up at /api/v1/auth/mfa/setup", user.role
+        )));
+    }
 
     // Inject user into request extensions
     request.extensions_mut().insert(AuthUser(user));
@@ -59,3 +66,35 @@ pub async fn extract_auth_user(request: &Request) -> Result<User, AppError> {
         .map(|auth_user| auth_user.0.clone())
         .ok_or_else(|| AppError::Unauthorized("No authenticated user found".to_string()))
 }
+
+/// Claims of the bearer token, if the request carries one
+pub fn bearer_claims(headers: &HeaderMap, auth_service: &AuthService) -> Result<Option<Claims>, AppError> {
+    let Some(auth_header) = headers.get(AUTHORIZATION) else {
+        return Ok(None);
+    };
+
+    let token = auth_header
+        .to_str()
+        .ok()
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .ok_or_else(|| AppError::Unauthorized("Invalid authorization format".to_string()))?;
+
+    Ok(Some(auth_service.validate_token(token)?.claims))
+}
+
+impl FromRequestParts<AppState> for AuthUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        if let Some(auth_user) = parts.extensions.get::<AuthUser>() {
+            return Ok(auth_user.clone());
+        }
+
+        let claims = bearer_claims(&parts.headers, &state.auth_service)?
+            .ok_or_else(|| AppError::Unauthorized("Missing authorization header".to_string()))?;
+
+        let user = state.auth_service.authenticated_user(&state.pool, &claims).await?;
+
+        Ok(AuthUser(user))
+    }
+}
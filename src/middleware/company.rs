@@ -0,0 +1,50 @@
+use axum::{
+    extract::{FromRequestParts, OptionalFromRequestParts},
+    http::request::Parts,
+};
+use uuid::Uuid;
+
+use crate::middleware::auth::bearer_claims;
+use crate::routes::AppState;
+use crate::services::CompanyService;
+use crate::utils::AppError;
+
+/// The caller's active company, from the `company_id` claim of their bearer token.
+/// Company records are only reachable through one, so requests without it are rejected;
+/// use `Option<ActiveCompany>` where no company is fine.
+#[derive(Debug, Clone, Copy)]
+pub struct ActiveCompany(pub Uuid);
+
+impl FromRequestParts<AppState> for ActiveCompany {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        <Self as OptionalFromRequestParts<AppState>>::from_request_parts(parts, state)
+            .await?
+            .ok_or_else(|| AppError::Forbidden(
+                "No active company; create one or switch to one at /api/v1/companies/{id}/switch".to_string()
+            ))
+    }
+}
+
+impl OptionalFromRequestParts<AppState> for ActiveCompany {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Option<Self>, Self::Rejection> {
+        let Some(claims) = bearer_claims(&parts.headers, &state.auth_service)? else {
+            return Ok(None);
+        };
+        let Some(company_id) = claims.company_id else {
+            return Ok(None);
+        };
+        let user_id = Uuid::parse_str(&claims.sub)
+            .map_err(|_| AppError::InvalidToken)?;
+
+        // Membership may have been revoked since the token was issued
+        if !CompanyService::is_member(&state.pool, company_id, user_id).await? {
+            return Err(AppError::Forbidden("You are not a member of this company".to_string()));
+        }
+
+        Ok(Some(Self(company_id)))
+    }
+}
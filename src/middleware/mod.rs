@@ -1,5 +1,6 @@
 pub mod auth;
+pub mod company;
+pub mod permission;
 
-// Middleware functions available but not currently used (handlers do manual auth checking)
-// Uncomment when switching to middleware-based auth:
-// pub use auth::{auth_middleware, AuthUser, extract_auth_user};
+pub use auth::{auth_middleware, AuthUser};
+pub use company::ActiveCompany;
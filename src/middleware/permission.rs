@@ -0,0 +1,89 @@
+use axum::http::Method;
+
+use crate::models::UserRole;
+
+/// Routes that can be called without a token
+pub const PUBLIC_ROUTES: &[&str] = &[
+    "/api/v1/health",
+    "/api/v1/auth/register",
+    "/api/v1/auth/login",
+    "/api/v1/auth/refresh",
+    "/api/v1/auth/forgot-password",
+    "/api/v1/auth/reset-password",
+    "/api/v1/auth/mfa/verify",
+];
+
+/// Routes open to users whose role requires two-factor authentication but who haven't set it up yet
+pub const MFA_SETUP_ROUTES: &[&str] = &[
+    "/api/v1/auth/me",
+    "/api/v1/auth/logout",
+    "/api/v1/auth/logout-all",
+    "/api/v1/auth/change-password",
+    "/api/v1/auth/mfa",
+    "/api/v1/auth/mfa/setup",
+    "/api/v1/auth/mfa/confirm",
+];
+
+/// What a route lets the caller do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Permission {
+    /// Look at the books
+    Read,
+    /// Create, post and change records
+    Write,
+    /// Manage users and lock periods
+    Admin,
+}
+
+/// Routes that need admin rights: users and company members, closing tax periods and the two-factor policy
+const ADMIN_ROUTES: &[(Method, &str)] = &[
+    (Method::GET, "/api/v1/users"),
+    (Method::POST, "/api/v1/users"),
+    (Method::GET, "/api/v1/users/{id}"),
+    (Method::PUT, "/api/v1/users/{id}/role"),
+    (Method::POST, "/api/v1/users/{id}/disable"),
+    (Method::POST, "/api/v1/users/{id}/enable"),
+    (Method::POST, "/api/v1/users/{id}/reset-password"),
+    (Method::PUT, "/api/v1/companies/{id}"),
+    (Method::DELETE, "/api/v1/companies/{id}"),
+    (Method::POST, "/api/v1/companies/{id}/members"),
+    (Method::DELETE, "/api/v1/companies/{id}/members/{user_id}"),
+    (Method::POST, "/api/v1/tax-returns/{id}/file"),
+    (Method::PUT, "/api/v1/auth/mfa/policy"),
+];
+
+/// Non-GET routes that don't touch the books, open to every role
+const READ_ROUTES: &[(Method, &str)] = &[
+    (Method::POST, "/api/v1/auth/logout"),
+    (Method::POST, "/api/v1/auth/logout-all"),
+    (Method::POST, "/api/v1/auth/change-password"),
+    (Method::POST, "/api/v1/auth/mfa/setup"),
+    (Method::POST, "/api/v1/auth/mfa/confirm"),
+    (Method::POST, "/api/v1/auth/mfa/recovery-codes"),
+    (Method::POST, "/api/v1/auth/mfa/disable"),
+    (Method::POST, "/api/v1/companies/{id}/switch"),
+    (Method::POST, "/api/v1/purchase-orders/{id}/bill-preview"),
+];
+
+/// The permission a route needs, by its matched path template.
+/// Reads are open to every role and everything else needs write access unless listed above.
+pub fn required_permission(method: &Method, route: &str) -> Permission {
+    let listed = |routes: &[(Method, &str)]| routes.iter().any(|(m, r)| m == method && *r == route);
+
+    if listed(ADMIN_ROUTES) {
+        Permission::Admin
+    } else if *method == Method::GET || *method == Method::HEAD || listed(READ_ROUTES) {
+        Permission::Read
+    } else {
+        Permission::Write
+    }
+}
+
+/// The most a role is allowed to do
+pub fn role_permission(role: &UserRole) -> Permission {
+    match role {
+        UserRole::Viewer => Permission::Read,
+        UserRole::Accountant => Permission::Write,
+        UserRole::Admin => Permission::Admin,
+    }
+}
@@ -7,6 +7,8 @@ use uuid::Uuid;
 use validator::Validate;
 use utoipa::ToSchema;
 
+use super::TaxSummary;
+
 #[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
 pub struct Bill {
     pub id: Uuid,
@@ -24,6 +26,9 @@ pub struct Bill {
     pub created_by: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub subtotal: Decimal,
+    pub tax_amount: Decimal,
+    pub ap_account_id: Option<Uuid>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, sqlx::Type, PartialEq, ToSchema)]
@@ -49,18 +54,27 @@ pub struct BillLineItem {
     pub expense_account_id: Uuid,
     pub billable: Option<bool>,
     pub customer_id: Option<Uuid>,
+    pub item_id: Option<Uuid>,
+    pub quantity: Option<Decimal>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub tax_code: Option<String>,
+    pub tax_amount: Decimal,
+    /// Invoice line the expense was billed to the customer on
+    pub invoice_line_item_id: Option<Uuid>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
 pub struct CreateBillRequest {
+    /// Assigned from the bill numbering sequence when omitted
     pub bill_number: Option<String>,
     pub vendor_id: Uuid,
     pub bill_date: NaiveDate,
     pub due_date: NaiveDate,
     pub memo: Option<String>,
     pub company_id: Option<Uuid>,
+    /// Accounts payable account; bills without one are not posted to the ledger
+    pub ap_account_id: Option<Uuid>,
 
     #[validate(length(min = 1))]
     pub line_items: Vec<CreateBillLineItemRequest>,
@@ -74,6 +88,14 @@ pub struct CreateBillLineItemRequest {
     pub expense_account_id: Uuid,
     pub billable: Option<bool>,
     pub customer_id: Option<Uuid>,
+    /// Inventory items add a cost layer of `quantity` units at `amount / quantity`
+    pub item_id: Option<Uuid>,
+    pub quantity: Option<Decimal>,
+    /// Tax code charged on the line; `amount` excludes the tax
+    pub tax_code: Option<String>,
+    /// Dimension values (project, department, ...) carried to the expense posting
+    #[serde(default)]
+    pub dimension_value_ids: Vec<Uuid>,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -81,4 +103,6 @@ pub struct BillWithLineItems {
     #[serde(flatten)]
     pub bill: Bill,
     pub line_items: Vec<BillLineItem>,
+    #[serde(default)]
+    pub taxes: Vec<TaxSummary>,
 }
@@ -0,0 +1,57 @@
+#![allow(dead_code)]
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+use utoipa::ToSchema;
+
+use super::InvoiceWithLineItems;
+
+/// A billable bill line not yet invoiced to its customer
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct UnbilledExpense {
+    pub bill_line_item_id: Uuid,
+    pub bill_id: Uuid,
+    pub bill_number: Option<String>,
+    pub bill_date: NaiveDate,
+    pub vendor_id: Uuid,
+    pub vendor_name: String,
+    pub line_number: i32,
+    pub description: Option<String>,
+    /// Cost of the expense, excluding tax
+    pub amount: Decimal,
+    pub expense_account_id: Uuid,
+}
+
+/// Bill expenses to the customer, on a new invoice or appended to one of their drafts
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct BillExpensesRequest {
+    #[validate(length(min = 1))]
+    pub bill_line_item_ids: Vec<Uuid>,
+    /// Draft invoice to add the expenses to; a new draft invoice is created when omitted
+    pub invoice_id: Option<Uuid>,
+    /// Percentage added on top of each expense's cost
+    pub markup_percent: Option<Decimal>,
+    /// Income account for the invoice lines; each expense's own account when omitted
+    pub revenue_account_id: Option<Uuid>,
+    /// Assigned from the invoice numbering sequence when omitted (new invoices only)
+    #[validate(length(min = 1))]
+    pub invoice_number: Option<String>,
+    /// Required for a new invoice
+    pub invoice_date: Option<NaiveDate>,
+    /// Required for a new invoice
+    pub due_date: Option<NaiveDate>,
+    /// Accounts receivable account for a new invoice
+    pub ar_account_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BilledExpenses {
+    pub invoice: InvoiceWithLineItems,
+    /// Cost of the expenses billed
+    pub expense_total: Decimal,
+    /// Markup charged on top of the cost
+    pub markup_amount: Decimal,
+}
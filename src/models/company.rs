@@ -4,26 +4,46 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 use validator::Validate;
+use utoipa::ToSchema;
 
-#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+use super::InventoryCostingMethod;
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
 pub struct Company {
     pub id: Uuid,
     pub name: String,
     pub address: Option<String>,
+    pub inventory_costing_method: InventoryCostingMethod,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct CreateCompanyRequest {
     #[validate(length(min = 1, max = 255))]
+    #[schema(example = "Maple Consulting Inc.")]
     pub name: String,
     pub address: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct UpdateCompanyRequest {
     #[validate(length(min = 1, max = 255))]
     pub name: Option<String>,
     pub address: Option<String>,
 }
+
+/// A user with access to a company
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct CompanyMember {
+    pub company_id: Uuid,
+    pub user_id: Uuid,
+    pub username: String,
+    pub email: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AddCompanyMemberRequest {
+    pub user_id: Uuid,
+}
@@ -16,6 +16,10 @@ pub struct Contact {
     pub billing_address: Option<String>,
     pub shipping_address: Option<String>,
     pub company_id: Option<Uuid>,
+    /// Excluded from dunning reminders
+    pub dunning_opt_out: bool,
+    /// Excluded from finance charges
+    pub finance_charge_exempt: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -0,0 +1,168 @@
+#![allow(dead_code)]
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+use utoipa::ToSchema;
+
+use super::TaxSummary;
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct CreditMemo {
+    pub id: Uuid,
+    pub credit_memo_number: String,
+    pub customer_id: Uuid,
+    pub credit_memo_date: NaiveDate,
+    /// Original invoice being credited, if any
+    pub invoice_id: Option<Uuid>,
+    pub subtotal: Decimal,
+    pub tax_amount: Decimal,
+    pub total_amount: Decimal,
+    /// Credit not yet applied to invoices or refunded
+    pub balance: Decimal,
+    pub status: CreditMemoStatus,
+    pub memo: Option<String>,
+    pub ar_account_id: Option<Uuid>,
+    pub company_id: Option<Uuid>,
+    pub transaction_id: Option<Uuid>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::Type, PartialEq, ToSchema)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum CreditMemoStatus {
+    Open,
+    Applied,
+    Void,
+}
+
+impl std::fmt::Display for CreditMemoStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CreditMemoStatus::Open => write!(f, "open"),
+            CreditMemoStatus::Applied => write!(f, "applied"),
+            CreditMemoStatus::Void => write!(f, "void"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct CreditMemoLineItem {
+    pub id: Uuid,
+    pub credit_memo_id: Uuid,
+    pub line_number: i32,
+    pub item_description: String,
+    pub quantity: Decimal,
+    pub unit_price: Decimal,
+    pub amount: Decimal,
+    pub tax_code: Option<String>,
+    pub tax_amount: Decimal,
+    pub revenue_account_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct CreditMemoApplication {
+    pub id: Uuid,
+    pub credit_memo_id: Uuid,
+    pub invoice_id: Uuid,
+    pub amount_applied: Decimal,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct CreateCreditMemoRequest {
+    /// Assigned from the credit memo numbering sequence when omitted
+    #[validate(length(min = 1))]
+    pub credit_memo_number: Option<String>,
+    pub customer_id: Uuid,
+    pub credit_memo_date: NaiveDate,
+    pub invoice_id: Option<Uuid>,
+    pub memo: Option<String>,
+    pub company_id: Option<Uuid>,
+    /// Accounts receivable account; the credit memo posts to the ledger when set
+    pub ar_account_id: Option<Uuid>,
+
+    #[validate(length(min = 1))]
+    pub line_items: Vec<CreateCreditMemoLineItemRequest>,
+    /// Invoices to apply the credit to straight away
+    #[serde(default)]
+    pub applications: Vec<CreditMemoApplicationRequest>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct CreateCreditMemoLineItemRequest {
+    pub line_number: i32,
+    pub item_description: String,
+    pub quantity: Decimal,
+    pub unit_price: Decimal,
+    /// Tax code to reverse (e.g. "HST-ON"); no tax when omitted
+    pub tax_code: Option<String>,
+    pub revenue_account_id: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct CreditMemoApplicationRequest {
+    pub invoice_id: Uuid,
+    pub amount_applied: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreditMemoWithLineItems {
+    #[serde(flatten)]
+    pub credit_memo: CreditMemo,
+    pub line_items: Vec<CreditMemoLineItem>,
+    /// Tax reversed, totalled by component tax
+    #[serde(default)]
+    pub taxes: Vec<TaxSummary>,
+    #[serde(default)]
+    pub applications: Vec<CreditMemoApplication>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct CustomerRefund {
+    pub id: Uuid,
+    pub refund_number: Option<String>,
+    pub customer_id: Uuid,
+    pub refund_date: NaiveDate,
+    pub amount: Decimal,
+    pub payment_method: String,
+    pub reference_number: Option<String>,
+    pub bank_account_id: Uuid,
+    pub credit_memo_id: Option<Uuid>,
+    pub payment_id: Option<Uuid>,
+    pub memo: Option<String>,
+    pub company_id: Option<Uuid>,
+    pub transaction_id: Option<Uuid>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Refund unapplied credit from a credit memo or an overpayment
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct CreateCustomerRefundRequest {
+    pub refund_number: Option<String>,
+    pub customer_id: Uuid,
+    pub refund_date: NaiveDate,
+    pub amount: Decimal,
+    #[validate(length(min = 1))]
+    pub payment_method: String,
+    pub reference_number: Option<String>,
+    /// Bank account the refund is paid from
+    pub bank_account_id: Uuid,
+    /// Refund from this credit memo's balance (exactly one of credit_memo_id and payment_id)
+    pub credit_memo_id: Option<Uuid>,
+    /// Refund from this payment's unapplied amount
+    pub payment_id: Option<Uuid>,
+    /// Accounts receivable account; defaults to the credit memo's
+    pub ar_account_id: Option<Uuid>,
+    pub memo: Option<String>,
+    pub company_id: Option<Uuid>,
+}
@@ -0,0 +1,105 @@
+#![allow(dead_code)]
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+use utoipa::ToSchema;
+
+use super::AccountType;
+
+/// A reporting dimension such as Project, Class or Department
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct DimensionType {
+    pub id: Uuid,
+    pub code: String,
+    pub name: String,
+    pub company_id: Option<Uuid>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct DimensionValue {
+    pub id: Uuid,
+    pub dimension_type_id: Uuid,
+    pub code: String,
+    pub name: String,
+    /// Inactive values stay on existing lines but can't be used on new ones
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Lines posting to an account of `account_type`, or to `account_id`, must carry a
+/// value of the dimension type. Exactly one of the two is set.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct DimensionRequirement {
+    pub id: Uuid,
+    pub dimension_type_id: Uuid,
+    pub account_type: Option<AccountType>,
+    pub account_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DimensionTypeWithValues {
+    #[serde(flatten)]
+    pub dimension_type: DimensionType,
+    pub values: Vec<DimensionValue>,
+    pub requirements: Vec<DimensionRequirement>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct CreateDimensionTypeRequest {
+    #[validate(length(min = 1, max = 50))]
+    #[schema(example = "DEPT")]
+    pub code: String,
+    #[validate(length(min = 1, max = 255))]
+    #[schema(example = "Department")]
+    pub name: String,
+    pub company_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct CreateDimensionValueRequest {
+    #[validate(length(min = 1, max = 50))]
+    #[schema(example = "SALES")]
+    pub code: String,
+    #[validate(length(min = 1, max = 255))]
+    #[schema(example = "Sales")]
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct UpdateDimensionValueRequest {
+    #[validate(length(min = 1, max = 255))]
+    pub name: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreateDimensionRequirementRequest {
+    pub account_type: Option<AccountType>,
+    pub account_id: Option<Uuid>,
+}
+
+/// Line tables that can carry dimension values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DimensionLine {
+    Transaction,
+    Invoice,
+    Bill,
+}
+
+impl DimensionLine {
+    /// Tag table and its line column
+    pub fn table(&self) -> (&'static str, &'static str) {
+        match self {
+            DimensionLine::Transaction => ("transaction_line_dimensions", "transaction_line_item_id"),
+            DimensionLine::Invoice => ("invoice_line_dimensions", "invoice_line_item_id"),
+            DimensionLine::Bill => ("bill_line_dimensions", "bill_line_item_id"),
+        }
+    }
+}
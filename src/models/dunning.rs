@@ -0,0 +1,103 @@
+#![allow(dead_code)]
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+use utoipa::ToSchema;
+
+use super::Invoice;
+
+/// One step of a dunning schedule.
+///
+/// Subject and body accept the invoice email placeholders plus `{{days_overdue}}`.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct DunningLevel {
+    pub level: i32,
+    /// Days past the due date before this reminder is sent
+    pub days_overdue: i32,
+    pub subject: String,
+    pub body: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct DunningLevelRequest {
+    #[validate(range(min = 1))]
+    #[schema(example = 30)]
+    pub days_overdue: i32,
+    #[validate(length(min = 1, max = 255))]
+    #[schema(example = "Reminder: invoice {{invoice_number}} is {{days_overdue}} days overdue")]
+    pub subject: String,
+    #[validate(length(min = 1))]
+    pub body: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct SetDunningLevelsRequest {
+    /// Company the schedule belongs to; the default schedule when omitted
+    pub company_id: Option<Uuid>,
+    /// Levels are numbered in order of days overdue; an empty list restores the default schedule
+    #[validate(nested)]
+    pub levels: Vec<DunningLevelRequest>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::Type, PartialEq, ToSchema)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ReminderStatus {
+    Queued,
+    /// Claimed by a dunning run that is sending it
+    Sending,
+    Sent,
+    Failed,
+}
+
+impl std::fmt::Display for ReminderStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReminderStatus::Queued => write!(f, "queued"),
+            ReminderStatus::Sending => write!(f, "sending"),
+            ReminderStatus::Sent => write!(f, "sent"),
+            ReminderStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+/// A dunning level reached by an invoice
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct InvoiceReminder {
+    pub id: Uuid,
+    pub invoice_id: Uuid,
+    pub level: i32,
+    pub days_overdue: i32,
+    pub status: ReminderStatus,
+    pub error: Option<String>,
+    /// Send attempts so far; failed reminders are retried up to a limit
+    pub attempts: i32,
+    pub email_delivery_id: Option<Uuid>,
+    pub queued_at: DateTime<Utc>,
+    pub claimed_at: Option<DateTime<Utc>>,
+    pub sent_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RunDunningRequest {
+    /// Defaults to today
+    #[schema(example = "2025-11-30")]
+    pub as_of_date: Option<NaiveDate>,
+}
+
+/// Result of one run of the overdue and dunning job
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct DunningRun {
+    pub as_of_date: NaiveDate,
+    /// Invoices moved to overdue by this run
+    pub marked_overdue: Vec<Invoice>,
+    /// Reminders queued or retried by this run, after sending
+    pub reminders: Vec<InvoiceReminder>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DunningOptOutRequest {
+    pub opt_out: bool,
+}
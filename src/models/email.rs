@@ -0,0 +1,116 @@
+#![allow(dead_code)]
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+use utoipa::ToSchema;
+
+use super::StatementStyle;
+
+/// Kind of document sent by email
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, sqlx::Type, PartialEq, ToSchema)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum EmailDocumentType {
+    Invoice,
+    Statement,
+    /// Dunning reminder for an overdue invoice
+    Reminder,
+}
+
+impl std::fmt::Display for EmailDocumentType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmailDocumentType::Invoice => write!(f, "invoice"),
+            EmailDocumentType::Statement => write!(f, "statement"),
+            EmailDocumentType::Reminder => write!(f, "reminder"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::Type, PartialEq, ToSchema)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum EmailDeliveryStatus {
+    Sent,
+    Failed,
+}
+
+impl std::fmt::Display for EmailDeliveryStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmailDeliveryStatus::Sent => write!(f, "sent"),
+            EmailDeliveryStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+/// Subject and body used when emailing a document.
+///
+/// Both may use placeholders such as `{{customer_name}}`, `{{invoice_number}}`,
+/// `{{total}}`, `{{balance}}`, `{{due_date}}`, `{{statement_date}}` and `{{company_name}}`.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct EmailTemplate {
+    pub id: Uuid,
+    pub company_id: Option<Uuid>,
+    pub document_type: EmailDocumentType,
+    pub subject: String,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct UpdateEmailTemplateRequest {
+    /// Company the template belongs to; the default template when omitted
+    pub company_id: Option<Uuid>,
+    pub document_type: EmailDocumentType,
+    #[validate(length(min = 1, max = 255))]
+    #[schema(example = "Invoice {{invoice_number}} from {{company_name}}")]
+    pub subject: String,
+    #[validate(length(min = 1))]
+    pub body: String,
+}
+
+/// A document emailed to a contact, successful or not
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct EmailDelivery {
+    pub id: Uuid,
+    pub document_type: EmailDocumentType,
+    /// Invoice ID; empty for statements
+    pub document_id: Option<Uuid>,
+    pub contact_id: Uuid,
+    pub recipient: String,
+    pub subject: String,
+    pub status: EmailDeliveryStatus,
+    /// SMTP error for failed deliveries
+    pub error: Option<String>,
+    pub company_id: Option<Uuid>,
+    pub sent_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema, Default)]
+pub struct SendInvoiceRequest {
+    /// Recipient; defaults to the customer's email
+    #[validate(email)]
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct SendStatementRequest {
+    /// Recipient; defaults to the customer's email
+    #[validate(email)]
+    pub to: Option<String>,
+
+    /// Defaults to the first day of the end date's month
+    #[schema(example = "2025-10-01")]
+    pub start_date: Option<NaiveDate>,
+
+    #[schema(example = "2025-10-31")]
+    pub end_date: NaiveDate,
+
+    #[serde(default)]
+    pub style: StatementStyle,
+}
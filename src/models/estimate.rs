@@ -0,0 +1,141 @@
+#![allow(dead_code)]
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+use utoipa::ToSchema;
+
+use super::{CreateInvoiceLineItemRequest, InvoiceWithLineItems};
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct Estimate {
+    pub id: Uuid,
+    pub estimate_number: String,
+    pub customer_id: Uuid,
+    pub estimate_date: NaiveDate,
+    pub expiry_date: NaiveDate,
+    pub subtotal: Decimal,
+    pub tax_amount: Decimal,
+    pub total_amount: Decimal,
+    /// Total of invoices created from the estimate so far
+    pub invoiced_amount: Decimal,
+    pub status: EstimateStatus,
+    pub customer_memo: Option<String>,
+    pub billing_address: Option<String>,
+    pub shipping_address: Option<String>,
+    pub company_id: Option<Uuid>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::Type, PartialEq, ToSchema)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum EstimateStatus {
+    Draft,
+    Sent,
+    Accepted,
+    Declined,
+    Expired,
+}
+
+impl std::fmt::Display for EstimateStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EstimateStatus::Draft => write!(f, "draft"),
+            EstimateStatus::Sent => write!(f, "sent"),
+            EstimateStatus::Accepted => write!(f, "accepted"),
+            EstimateStatus::Declined => write!(f, "declined"),
+            EstimateStatus::Expired => write!(f, "expired"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct EstimateLineItem {
+    pub id: Uuid,
+    pub estimate_id: Uuid,
+    pub line_number: i32,
+    pub item_description: String,
+    pub quantity: Decimal,
+    pub unit_price: Decimal,
+    pub amount: Decimal,
+    pub discount_percent: Option<Decimal>,
+    pub discount_amount: Option<Decimal>,
+    pub tax_code: Option<String>,
+    pub tax_amount: Decimal,
+    pub revenue_account_id: Uuid,
+    pub item_id: Option<Uuid>,
+    /// Quantity already billed on invoices created from the estimate
+    pub invoiced_quantity: Decimal,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct EstimateInvoice {
+    pub id: Uuid,
+    pub estimate_id: Uuid,
+    pub invoice_id: Uuid,
+    pub amount: Decimal,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct CreateEstimateRequest {
+    #[validate(length(min = 1))]
+    pub estimate_number: String,
+    pub customer_id: Uuid,
+    pub estimate_date: NaiveDate,
+    pub expiry_date: NaiveDate,
+    pub customer_memo: Option<String>,
+    pub billing_address: Option<String>,
+    pub shipping_address: Option<String>,
+    pub company_id: Option<Uuid>,
+
+    /// Lines use the invoice line structure so they carry over on conversion
+    #[validate(length(min = 1))]
+    pub line_items: Vec<CreateInvoiceLineItemRequest>,
+}
+
+/// Convert an accepted estimate into an invoice. With neither `percent` nor `lines`
+/// everything not yet billed is invoiced.
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct ConvertEstimateRequest {
+    /// Assigned from the invoice numbering sequence when omitted
+    #[validate(length(min = 1))]
+    pub invoice_number: Option<String>,
+    pub invoice_date: NaiveDate,
+    pub due_date: NaiveDate,
+    /// Accounts receivable account for the new invoice
+    pub ar_account_id: Option<Uuid>,
+    /// Progress billing: bill this percentage of every line's estimated quantity
+    pub percent: Option<Decimal>,
+    /// Progress billing: bill these quantities of specific lines
+    pub lines: Option<Vec<ConvertEstimateLineRequest>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ConvertEstimateLineRequest {
+    pub estimate_line_item_id: Uuid,
+    pub quantity: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct EstimateWithLineItems {
+    #[serde(flatten)]
+    pub estimate: Estimate,
+    pub line_items: Vec<EstimateLineItem>,
+    /// Invoices created from the estimate
+    #[serde(default)]
+    pub invoices: Vec<EstimateInvoice>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct EstimateConversion {
+    pub estimate: Estimate,
+    pub invoice: InvoiceWithLineItems,
+}
@@ -0,0 +1,110 @@
+#![allow(dead_code)]
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+use utoipa::ToSchema;
+
+/// How finance charges are calculated for a company
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct FinanceChargeSettings {
+    pub id: Uuid,
+    pub company_id: Option<Uuid>,
+    /// Interest rate in percent per month, e.g. 2 for 2% per month
+    pub monthly_rate: Decimal,
+    /// Smallest finance charge billed to a customer in one run
+    pub minimum_charge: Decimal,
+    /// Days past the due date before an invoice is charged
+    pub grace_days: i32,
+    /// Charge interest on unpaid finance charges as well
+    pub compound: bool,
+    pub income_account_id: Uuid,
+    pub ar_account_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct UpdateFinanceChargeSettingsRequest {
+    /// Company the settings belong to; the default settings when omitted
+    pub company_id: Option<Uuid>,
+    #[schema(example = "2")]
+    pub monthly_rate: Decimal,
+    #[serde(default)]
+    #[schema(example = "5.00")]
+    pub minimum_charge: Decimal,
+    #[serde(default)]
+    #[validate(range(min = 0))]
+    #[schema(example = 10)]
+    pub grace_days: i32,
+    #[serde(default)]
+    pub compound: bool,
+    pub income_account_id: Uuid,
+    pub ar_account_id: Uuid,
+}
+
+/// Interest assessed on one overdue invoice
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct FinanceCharge {
+    pub id: Uuid,
+    /// Overdue invoice the interest was charged on
+    pub invoice_id: Uuid,
+    pub finance_charge_invoice_id: Uuid,
+    pub customer_id: Uuid,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub days: i32,
+    pub base_amount: Decimal,
+    pub amount: Decimal,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FinanceChargeRunRequest {
+    #[schema(example = "2025-11-30")]
+    pub as_of_date: NaiveDate,
+    pub company_id: Option<Uuid>,
+    /// Calculate without creating invoices
+    #[serde(default)]
+    pub preview: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct FinanceChargeLine {
+    pub invoice_id: Uuid,
+    pub invoice_number: String,
+    pub due_date: NaiveDate,
+    pub period_start: NaiveDate,
+    pub days: i32,
+    /// Invoice balance, plus unpaid finance charges when compounding
+    pub base_amount: Decimal,
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct CustomerFinanceCharge {
+    pub customer_id: Uuid,
+    pub customer_name: String,
+    pub lines: Vec<FinanceChargeLine>,
+    /// Interest before the minimum charge
+    pub interest: Decimal,
+    pub minimum_applied: bool,
+    pub total: Decimal,
+    /// Empty in preview mode
+    pub finance_charge_invoice_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct FinanceChargeRun {
+    pub as_of_date: NaiveDate,
+    pub preview: bool,
+    pub customers: Vec<CustomerFinanceCharge>,
+    pub total: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FinanceChargeExemptRequest {
+    pub exempt: bool,
+}
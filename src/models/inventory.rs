@@ -0,0 +1,118 @@
+#![allow(dead_code)]
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, sqlx::Type, PartialEq, ToSchema)]
+#[sqlx(type_name = "varchar")]
+pub enum InventoryCostingMethod {
+    #[serde(rename = "FIFO")]
+    #[sqlx(rename = "FIFO")]
+    Fifo,
+    Average,
+}
+
+impl std::fmt::Display for InventoryCostingMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InventoryCostingMethod::Fifo => write!(f, "FIFO"),
+            InventoryCostingMethod::Average => write!(f, "Average"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct InventoryCostLayer {
+    pub id: Uuid,
+    pub item_id: Uuid,
+    pub source_type: String,
+    pub source_id: Option<Uuid>,
+    pub received_date: NaiveDate,
+    pub quantity_received: Decimal,
+    pub quantity_remaining: Decimal,
+    pub unit_cost: Decimal,
+    pub company_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::Type, PartialEq, ToSchema)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum InventoryMovementType {
+    Purchase,
+    Sale,
+    Adjustment,
+    SaleReversal,
+}
+
+impl std::fmt::Display for InventoryMovementType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InventoryMovementType::Purchase => write!(f, "purchase"),
+            InventoryMovementType::Sale => write!(f, "sale"),
+            InventoryMovementType::Adjustment => write!(f, "adjustment"),
+            InventoryMovementType::SaleReversal => write!(f, "sale_reversal"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct InventoryMovement {
+    pub id: Uuid,
+    pub item_id: Uuid,
+    pub movement_date: NaiveDate,
+    pub movement_type: InventoryMovementType,
+    pub quantity: Decimal,
+    pub unit_cost: Decimal,
+    pub total_cost: Decimal,
+    pub source_type: Option<String>,
+    pub source_id: Option<Uuid>,
+    pub transaction_id: Option<Uuid>,
+    pub memo: Option<String>,
+    pub company_id: Option<Uuid>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct CreateStockAdjustmentRequest {
+    pub item_id: Uuid,
+    #[schema(example = "2025-10-10")]
+    pub adjustment_date: NaiveDate,
+    /// Signed quantity change: positive adds stock, negative removes it
+    #[schema(example = -2.0)]
+    pub quantity_change: Decimal,
+    /// Unit cost for stock added by the adjustment (defaults to the current average cost)
+    pub unit_cost: Option<Decimal>,
+    /// Offsetting account, e.g. Inventory Shrinkage
+    pub adjustment_account_id: Uuid,
+    #[validate(length(max = 1000))]
+    pub memo: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct UpdateCostingMethodRequest {
+    pub inventory_costing_method: InventoryCostingMethod,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct InventoryValuationEntry {
+    pub item_id: Uuid,
+    pub item_name: String,
+    pub sku: Option<String>,
+    pub quantity_on_hand: Decimal,
+    pub total_value: Decimal,
+    pub average_unit_cost: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct InventoryValuationReport {
+    pub as_of_date: NaiveDate,
+    pub total_value: Decimal,
+    pub entries: Vec<InventoryValuationEntry>,
+}
@@ -7,6 +7,8 @@ use uuid::Uuid;
 use validator::Validate;
 use utoipa::ToSchema;
 
+use super::TaxSummary;
+
 #[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
 pub struct Invoice {
     pub id: Uuid,
@@ -17,6 +19,8 @@ pub struct Invoice {
     pub due_date: NaiveDate,
     pub ship_date: Option<NaiveDate>,
     pub tracking_number: Option<String>,
+    pub subtotal: Decimal,
+    pub tax_amount: Decimal,
     pub total_amount: Decimal,
     pub balance: Decimal,
     pub status: InvoiceStatus,
@@ -24,6 +28,7 @@ pub struct Invoice {
     pub billing_address: Option<String>,
     pub shipping_address: Option<String>,
     pub company_id: Option<Uuid>,
+    pub ar_account_id: Option<Uuid>,
     pub transaction_id: Option<Uuid>,
     pub created_by: Option<Uuid>,
     pub created_at: DateTime<Utc>,
@@ -45,6 +50,9 @@ pub enum InvoiceStatus {
     Overdue,
     #[serde(rename = "void")]
     Void,
+    #[serde(rename = "written_off")]
+    #[sqlx(rename = "written_off")]
+    WrittenOff,
 }
 
 impl InvoiceStatus {
@@ -56,6 +64,7 @@ impl InvoiceStatus {
             InvoiceStatus::Partial => "partial".to_string(),
             InvoiceStatus::Overdue => "overdue".to_string(),
             InvoiceStatus::Void => "void".to_string(),
+            InvoiceStatus::WrittenOff => "written_off".to_string(),
         }
     }
 }
@@ -72,15 +81,18 @@ pub struct InvoiceLineItem {
     pub discount_percent: Option<Decimal>,
     pub discount_amount: Option<Decimal>,
     pub tax_code: Option<String>,
+    pub tax_amount: Decimal,
     pub revenue_account_id: Uuid,
+    pub item_id: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
 pub struct CreateInvoiceRequest {
+    /// Assigned from the invoice numbering sequence when omitted
     #[validate(length(min = 1))]
-    pub invoice_number: String,
+    pub invoice_number: Option<String>,
     pub customer_id: Uuid,
     pub invoice_date: NaiveDate,
     pub due_date: NaiveDate,
@@ -89,6 +101,8 @@ pub struct CreateInvoiceRequest {
     pub billing_address: Option<String>,
     pub shipping_address: Option<String>,
     pub company_id: Option<Uuid>,
+    /// Accounts receivable account; the invoice posts to the ledger when sent
+    pub ar_account_id: Option<Uuid>,
 
     #[validate(length(min = 1))]
     pub line_items: Vec<CreateInvoiceLineItemRequest>,
@@ -101,8 +115,14 @@ pub struct CreateInvoiceLineItemRequest {
     pub quantity: Decimal,
     pub unit_price: Decimal,
     pub discount_percent: Option<Decimal>,
+    /// Tax code from the tax registry (e.g. "HST-ON"); no tax when omitted
     pub tax_code: Option<String>,
     pub revenue_account_id: Uuid,
+    /// Inventory items relieve stock and post cost of goods sold
+    pub item_id: Option<Uuid>,
+    /// Dimension values (project, department, ...) carried to the revenue posting
+    #[serde(default)]
+    pub dimension_value_ids: Vec<Uuid>,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -110,4 +130,50 @@ pub struct InvoiceWithLineItems {
     #[serde(flatten)]
     pub invoice: Invoice,
     pub line_items: Vec<InvoiceLineItem>,
+    /// Tax totalled by component tax
+    #[serde(default)]
+    pub taxes: Vec<TaxSummary>,
+}
+
+/// Branding and payment instructions printed on invoice PDFs; one per company
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct InvoiceTemplate {
+    pub id: Uuid,
+    pub company_id: Option<Uuid>,
+    /// Name printed at the top; defaults to the company name
+    pub business_name: Option<String>,
+    pub address: Option<String>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    /// Tax registration printed on the invoice, e.g. a GST/HST business number
+    pub tax_registration_number: Option<String>,
+    pub payment_instructions: Option<String>,
+    pub footer: Option<String>,
+    /// JPEG logo; not serialized
+    #[serde(skip)]
+    pub logo: Option<Vec<u8>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct UpdateInvoiceTemplateRequest {
+    /// Company the template belongs to; the default template when omitted
+    pub company_id: Option<Uuid>,
+    #[validate(length(max = 255))]
+    pub business_name: Option<String>,
+    pub address: Option<String>,
+    #[validate(length(max = 50))]
+    pub phone: Option<String>,
+    #[validate(email)]
+    pub email: Option<String>,
+    #[validate(length(max = 100))]
+    pub tax_registration_number: Option<String>,
+    pub payment_instructions: Option<String>,
+    pub footer: Option<String>,
+    /// Base64-encoded JPEG logo; the current logo is kept when omitted
+    pub logo_base64: Option<String>,
+    /// Remove the current logo
+    #[serde(default)]
+    pub remove_logo: bool,
 }
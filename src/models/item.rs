@@ -5,8 +5,9 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 use validator::Validate;
+use utoipa::ToSchema;
 
-#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
 pub struct Item {
     pub id: Uuid,
     pub quickbooks_id: Option<String>,
@@ -27,7 +28,7 @@ pub struct Item {
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, sqlx::Type, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::Type, PartialEq, ToSchema)]
 #[sqlx(type_name = "varchar", rename_all = "PascalCase")]
 pub enum ItemType {
     Service,
@@ -37,7 +38,7 @@ pub enum ItemType {
     NonInventory,
 }
 
-#[derive(Debug, Serialize, Deserialize, Validate)]
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
 pub struct CreateItemRequest {
     #[validate(length(min = 1, max = 255))]
     pub name: String,
@@ -53,7 +54,7 @@ pub struct CreateItemRequest {
     pub company_id: Option<Uuid>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Validate)]
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
 pub struct UpdateItemRequest {
     #[validate(length(min = 1, max = 255))]
     pub name: Option<String>,
@@ -8,6 +8,20 @@ pub mod bill;
 pub mod payment;
 pub mod item;
 pub mod reporting;
+pub mod inventory;
+pub mod tax;
+pub mod credit_memo;
+pub mod statement;
+pub mod estimate;
+pub mod purchase_order;
+pub mod vendor_credit;
+pub mod email;
+pub mod dunning;
+pub mod finance_charge;
+pub mod write_off;
+pub mod numbering;
+pub mod billable_expense;
+pub mod dimension;
 
 pub use user::*;
 pub use account::*;
@@ -15,8 +29,21 @@ pub use transaction::*;
 pub use contact::*;
 pub use invoice::*;
 pub use bill::*;
-// Uncomment when implementing features:
-// pub use company::*;
+pub use company::*;
 pub use payment::*;
-// pub use item::*;
+pub use item::*;
 pub use reporting::*;
+pub use inventory::*;
+pub use tax::*;
+pub use credit_memo::*;
+pub use statement::*;
+pub use estimate::*;
+pub use purchase_order::*;
+pub use vendor_credit::*;
+pub use email::*;
+pub use dunning::*;
+pub use finance_charge::*;
+pub use write_off::*;
+pub use numbering::*;
+pub use billable_expense::*;
+pub use dimension::*;
@@ -0,0 +1,90 @@
+#![allow(dead_code)]
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+use utoipa::ToSchema;
+
+/// Documents numbered from a sequence
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, sqlx::Type, PartialEq, Eq, ToSchema)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum SequenceDocumentType {
+    Invoice,
+    Bill,
+    Payment,
+    JournalEntry,
+    CreditMemo,
+}
+
+impl SequenceDocumentType {
+    /// Prefix used until a sequence is configured
+    pub fn default_prefix(&self) -> &'static str {
+        match self {
+            SequenceDocumentType::Invoice => "INV-",
+            SequenceDocumentType::Bill => "BILL-",
+            SequenceDocumentType::Payment => "PMT-",
+            SequenceDocumentType::JournalEntry => "JE-",
+            SequenceDocumentType::CreditMemo => "CM-",
+        }
+    }
+}
+
+impl std::fmt::Display for SequenceDocumentType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SequenceDocumentType::Invoice => write!(f, "invoice"),
+            SequenceDocumentType::Bill => write!(f, "bill"),
+            SequenceDocumentType::Payment => write!(f, "payment"),
+            SequenceDocumentType::JournalEntry => write!(f, "journal_entry"),
+            SequenceDocumentType::CreditMemo => write!(f, "credit_memo"),
+        }
+    }
+}
+
+/// How numbers are formatted for one document type in a company
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct NumberingSequence {
+    pub id: Uuid,
+    pub company_id: Option<Uuid>,
+    pub document_type: SequenceDocumentType,
+    /// Text before the number; {YYYY} and {YY} are replaced with the document year
+    pub prefix: String,
+    /// Minimum digits, zero-padded
+    pub padding: i32,
+    /// Restart at 1 each calendar year of the document date
+    pub yearly_reset: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct NumberingSequenceWithNext {
+    #[serde(flatten)]
+    pub sequence: NumberingSequence,
+    /// Counter year the next number is for; 0 when the sequence never resets
+    pub year: i32,
+    pub next_number: i64,
+    /// The number the next document will get, e.g. INV-2025-00042
+    pub next_document_number: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct UpdateNumberingSequenceRequest {
+    pub company_id: Option<Uuid>,
+    #[serde(default)]
+    #[validate(length(max = 50))]
+    #[schema(example = "INV-{YYYY}-")]
+    pub prefix: String,
+    #[validate(range(min = 0, max = 20))]
+    #[schema(example = 5)]
+    pub padding: i32,
+    #[serde(default)]
+    pub yearly_reset: bool,
+    /// Manually move the counter so the next document gets this number
+    #[validate(range(min = 1))]
+    pub next_number: Option<i64>,
+    /// Year whose counter `next_number` applies to; the current year when omitted
+    pub year: Option<i32>,
+}
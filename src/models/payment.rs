@@ -25,6 +25,13 @@ pub struct Payment {
     pub created_by: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub ar_account_id: Option<Uuid>,
+    /// Set once the payment has been voided
+    pub void_date: Option<NaiveDate>,
+    pub void_reason: Option<String>,
+    pub void_transaction_id: Option<Uuid>,
+    /// Invoice billing the customer for the returned payment
+    pub nsf_invoice_id: Option<Uuid>,
 }
 
 #[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
@@ -66,6 +73,7 @@ pub struct BillPaymentApplication {
 
 #[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
 pub struct CreatePaymentRequest {
+    /// Assigned from the payment numbering sequence when omitted
     pub payment_number: Option<String>,
     pub customer_id: Uuid,
     pub payment_date: NaiveDate,
@@ -74,6 +82,8 @@ pub struct CreatePaymentRequest {
     pub payment_method: String,
     pub reference_number: Option<String>,
     pub deposit_to_account_id: Option<Uuid>,
+    /// Accounts receivable account; with a deposit account the payment posts to the ledger
+    pub ar_account_id: Option<Uuid>,
     pub memo: Option<String>,
     pub company_id: Option<Uuid>,
     pub applications: Vec<PaymentApplicationRequest>,
@@ -85,8 +95,25 @@ pub struct PaymentApplicationRequest {
     pub amount_applied: Decimal,
 }
 
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct VoidPaymentRequest {
+    pub void_date: NaiveDate,
+    #[validate(length(max = 500))]
+    #[schema(example = "Returned NSF")]
+    pub reason: Option<String>,
+    /// Fee billed to the customer for the returned payment
+    pub nsf_fee: Option<Decimal>,
+    /// Income account the NSF fee is credited to; required with `nsf_fee`
+    pub nsf_fee_account_id: Option<Uuid>,
+    /// Charge from our bank for the returned item, taken from the deposit account
+    pub bank_fee: Option<Decimal>,
+    /// Expense account for the bank charge; required with `bank_fee`
+    pub bank_fee_account_id: Option<Uuid>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
 pub struct CreateBillPaymentRequest {
+    /// Assigned from the payment numbering sequence when omitted
     pub payment_number: Option<String>,
     pub vendor_id: Uuid,
     pub payment_date: NaiveDate,
@@ -105,3 +132,72 @@ pub struct BillPaymentApplicationRequest {
     pub bill_id: Uuid,
     pub amount_applied: Decimal,
 }
+
+/// Where auto-applied money comes from
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoApplySource {
+    Payment,
+    CreditMemo,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AutoApplyRequest {
+    /// Apply for one customer; every customer with open invoices and unapplied money when omitted
+    pub customer_id: Option<Uuid>,
+    pub company_id: Option<Uuid>,
+    /// Settle an invoice whose balance equals a payment or credit exactly before going oldest first
+    #[serde(default)]
+    pub prefer_exact_match: bool,
+    /// Calculate without applying anything
+    #[serde(default)]
+    pub preview: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct AutoApplication {
+    pub source_type: AutoApplySource,
+    pub source_id: Uuid,
+    pub source_number: Option<String>,
+    pub invoice_id: Uuid,
+    pub invoice_number: String,
+    pub amount: Decimal,
+    pub exact_match: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct CustomerAutoApplication {
+    pub customer_id: Uuid,
+    pub customer_name: String,
+    pub applications: Vec<AutoApplication>,
+    pub total_applied: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct AutoApplyRun {
+    pub preview: bool,
+    pub customers: Vec<CustomerAutoApplication>,
+    pub total_applied: Decimal,
+}
+
+/// A document whose stored balance or status disagrees with what has been applied to it
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct BalanceDrift {
+    /// invoice, bill, payment, credit_memo or vendor_credit
+    pub document_type: String,
+    pub document_id: Uuid,
+    pub document_number: Option<String>,
+    /// Balance, or unapplied amount for payments, as stored
+    pub recorded_balance: Decimal,
+    /// Balance implied by the applications, refunds and write-offs against the document
+    pub expected_balance: Decimal,
+    /// Empty for payments, which have no status
+    pub recorded_status: Option<String>,
+    pub expected_status: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ApplicationConsistencyReport {
+    pub consistent: bool,
+    pub drifts: Vec<BalanceDrift>,
+}
@@ -0,0 +1,175 @@
+#![allow(dead_code)]
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+use utoipa::ToSchema;
+
+use super::Bill;
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct PurchaseOrder {
+    pub id: Uuid,
+    pub po_number: String,
+    pub vendor_id: Uuid,
+    pub order_date: NaiveDate,
+    pub expected_date: Option<NaiveDate>,
+    /// Ordered amount before tax
+    pub total_amount: Decimal,
+    /// Amount billed against the order so far, before tax
+    pub billed_amount: Decimal,
+    pub status: PurchaseOrderStatus,
+    pub memo: Option<String>,
+    pub shipping_address: Option<String>,
+    pub company_id: Option<Uuid>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::Type, PartialEq, ToSchema)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum PurchaseOrderStatus {
+    Open,
+    PartiallyReceived,
+    Closed,
+}
+
+impl std::fmt::Display for PurchaseOrderStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PurchaseOrderStatus::Open => write!(f, "open"),
+            PurchaseOrderStatus::PartiallyReceived => write!(f, "partially_received"),
+            PurchaseOrderStatus::Closed => write!(f, "closed"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct PurchaseOrderLineItem {
+    pub id: Uuid,
+    pub purchase_order_id: Uuid,
+    pub line_number: i32,
+    pub description: Option<String>,
+    pub quantity: Decimal,
+    pub unit_price: Decimal,
+    pub amount: Decimal,
+    pub expense_account_id: Uuid,
+    pub item_id: Option<Uuid>,
+    pub tax_code: Option<String>,
+    pub expected_date: Option<NaiveDate>,
+    /// Quantity billed so far
+    pub billed_quantity: Decimal,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct PurchaseOrderBill {
+    pub id: Uuid,
+    pub purchase_order_id: Uuid,
+    pub purchase_order_line_id: Uuid,
+    pub bill_id: Uuid,
+    pub quantity: Decimal,
+    pub amount: Decimal,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct CreatePurchaseOrderRequest {
+    #[validate(length(min = 1))]
+    pub po_number: String,
+    pub vendor_id: Uuid,
+    pub order_date: NaiveDate,
+    pub expected_date: Option<NaiveDate>,
+    pub memo: Option<String>,
+    pub shipping_address: Option<String>,
+    pub company_id: Option<Uuid>,
+
+    #[validate(length(min = 1))]
+    pub line_items: Vec<CreatePurchaseOrderLineItemRequest>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct CreatePurchaseOrderLineItemRequest {
+    pub line_number: i32,
+    pub description: Option<String>,
+    pub quantity: Decimal,
+    pub unit_price: Decimal,
+    pub expense_account_id: Uuid,
+    pub item_id: Option<Uuid>,
+    /// Tax code carried onto the bill
+    pub tax_code: Option<String>,
+    /// Defaults to the order's expected date
+    pub expected_date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PurchaseOrderWithLineItems {
+    #[serde(flatten)]
+    pub purchase_order: PurchaseOrder,
+    pub line_items: Vec<PurchaseOrderLineItem>,
+    /// Quantities billed against the order, per bill and line
+    #[serde(default)]
+    pub bills: Vec<PurchaseOrderBill>,
+}
+
+/// Convert a purchase order into a bill. Without `lines` everything not yet billed is billed.
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct ConvertPurchaseOrderRequest {
+    pub bill_number: Option<String>,
+    pub bill_date: NaiveDate,
+    pub due_date: NaiveDate,
+    /// Accounts payable account for the new bill
+    pub ap_account_id: Option<Uuid>,
+    /// Bill only these quantities of specific lines
+    pub lines: Option<Vec<ConvertPurchaseOrderLineRequest>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ConvertPurchaseOrderLineRequest {
+    pub purchase_order_line_id: Uuid,
+    pub quantity: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PurchaseOrderConversion {
+    pub purchase_order: PurchaseOrder,
+    pub bill: Bill,
+}
+
+/// Open purchase order with the amount still to be billed
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct OpenPurchaseOrder {
+    pub id: Uuid,
+    pub po_number: String,
+    pub vendor_id: Uuid,
+    pub vendor_name: String,
+    pub order_date: NaiveDate,
+    pub expected_date: Option<NaiveDate>,
+    pub status: PurchaseOrderStatus,
+    pub total_amount: Decimal,
+    pub billed_amount: Decimal,
+    pub open_amount: Decimal,
+}
+
+/// Committed but unbilled spend by expense account
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct CommittedSpend {
+    pub account_id: Uuid,
+    pub account_code: String,
+    pub account_name: String,
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct OpenPurchaseOrdersReport {
+    pub as_of_date: NaiveDate,
+    /// Total ordered but not yet billed, before tax
+    pub total_committed: Decimal,
+    pub purchase_orders: Vec<OpenPurchaseOrder>,
+    pub committed_by_account: Vec<CommittedSpend>,
+}
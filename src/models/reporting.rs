@@ -14,6 +14,9 @@ pub struct TrialBalanceEntry {
     pub debit: Decimal,
     pub credit: Decimal,
     pub balance: Decimal,
+    /// Value of the group-by dimension; None for untagged lines or when not grouping
+    pub dimension_value_id: Option<sqlx::types::Uuid>,
+    pub dimension_value_name: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
@@ -32,6 +35,9 @@ pub struct ProfitLossEntry {
     pub account_name: String,
     pub account_type: String,
     pub amount: Decimal,
+    /// Value of the group-by dimension; None for untagged lines or when not grouping
+    pub dimension_value_id: Option<sqlx::types::Uuid>,
+    pub dimension_value_name: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
@@ -51,6 +57,9 @@ pub struct BalanceSheetEntry {
     pub account_code: String,
     pub account_name: String,
     pub amount: Decimal,
+    /// Value of the group-by dimension; None for untagged lines or when not grouping
+    pub dimension_value_id: Option<sqlx::types::Uuid>,
+    pub dimension_value_name: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
@@ -74,12 +83,18 @@ pub struct AgingBucket {
     pub days_61_90: Decimal,
     pub days_91_plus: Decimal,
     pub total: Decimal,
+    /// Written off as bad debt, net of recoveries; not part of the total
+    pub written_off: Decimal,
+    /// Value of the group-by dimension; invoices are split across values by line amount
+    pub dimension_value_id: Option<sqlx::types::Uuid>,
+    pub dimension_value_name: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct AccountsReceivableAging {
     pub as_of_date: NaiveDate,
     pub total_outstanding: Decimal,
+    pub total_written_off: Decimal,
     pub buckets: Vec<AgingBucket>,
 }
 
@@ -96,4 +111,31 @@ pub struct DateRangeRequest {
 pub struct DateRequest {
     #[schema(example = "2023-12-31")]
     pub as_of_date: NaiveDate,
-}
\ No newline at end of file
+}
+
+/// Restrict a report to tagged lines and/or break it down by a dimension
+#[derive(Debug, Default, Serialize, Deserialize, Clone, ToSchema)]
+pub struct DimensionFilter {
+    /// Only lines carrying one of these values for each dimension the values belong to
+    #[serde(default)]
+    pub dimension_value_ids: Vec<sqlx::types::Uuid>,
+    /// Report a separate line per value of this dimension type
+    pub group_by_dimension_type_id: Option<sqlx::types::Uuid>,
+}
+
+impl DimensionFilter {
+    /// Cache key suffix; empty for an unfiltered report
+    pub fn cache_key(&self) -> String {
+        let mut value_ids = self.dimension_value_ids.clone();
+        value_ids.sort();
+        let mut key = String::new();
+        if !value_ids.is_empty() {
+            let ids: Vec<String> = value_ids.iter().map(|id| id.to_string()).collect();
+            key.push_str(&format!(":dims:{}", ids.join(",")));
+        }
+        if let Some(dimension_type_id) = self.group_by_dimension_type_id {
+            key.push_str(&format!(":by:{}", dimension_type_id));
+        }
+        key
+    }
+}
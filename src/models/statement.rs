@@ -0,0 +1,106 @@
+#![allow(dead_code)]
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use utoipa::ToSchema;
+use validator::Validate;
+
+use super::AgingBucket;
+
+/// How a statement presents the account
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StatementStyle {
+    /// Every unpaid invoice and unused credit as of the statement date
+    OpenItem,
+    /// Opening balance, then all activity in the period with a running balance
+    #[default]
+    BalanceForward,
+}
+
+impl std::fmt::Display for StatementStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StatementStyle::OpenItem => write!(f, "open_item"),
+            StatementStyle::BalanceForward => write!(f, "balance_forward"),
+        }
+    }
+}
+
+/// Output format for statements
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum StatementFormat {
+    #[default]
+    Json,
+    Pdf,
+}
+
+/// One document on a customer statement
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone, ToSchema)]
+pub struct StatementLine {
+    pub date: NaiveDate,
+    /// invoice, payment, payment_void, credit_memo, refund, write_off or write_off_recovery
+    pub document_type: String,
+    pub document_id: Uuid,
+    pub document_number: Option<String>,
+    pub due_date: Option<NaiveDate>,
+    /// Positive for charges, negative for payments and credits
+    pub amount: Decimal,
+    /// Amount still open as of the statement date (open-item statements)
+    pub open_balance: Decimal,
+    /// Customer balance after this line
+    pub running_balance: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct CustomerStatement {
+    pub customer_id: Uuid,
+    pub customer_name: String,
+    pub billing_address: Option<String>,
+    pub style: StatementStyle,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub opening_balance: Decimal,
+    pub lines: Vec<StatementLine>,
+    pub total_charges: Decimal,
+    pub total_credits: Decimal,
+    pub closing_balance: Decimal,
+    /// Aging of open invoices as of the end date, bucketed like the AR aging report
+    pub aging: AgingBucket,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema, Clone)]
+pub struct StatementRequest {
+    /// Defaults to the first day of the end date's month
+    #[schema(example = "2025-10-01")]
+    pub start_date: Option<NaiveDate>,
+
+    #[schema(example = "2025-10-31")]
+    pub end_date: NaiveDate,
+
+    #[serde(default)]
+    pub style: StatementStyle,
+
+    #[serde(default)]
+    pub format: StatementFormat,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema, Clone)]
+pub struct BatchStatementRequest {
+    #[schema(example = "2025-10-01")]
+    pub start_date: Option<NaiveDate>,
+
+    #[schema(example = "2025-10-31")]
+    pub end_date: NaiveDate,
+
+    #[serde(default)]
+    pub style: StatementStyle,
+
+    #[serde(default)]
+    pub format: StatementFormat,
+
+    pub company_id: Option<Uuid>,
+}
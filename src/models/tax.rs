@@ -0,0 +1,232 @@
+#![allow(dead_code)]
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct TaxCode {
+    pub id: Uuid,
+    pub code: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub is_active: bool,
+    pub company_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct TaxRate {
+    pub id: Uuid,
+    pub tax_code_id: Uuid,
+    pub tax_name: String,
+    pub agency: Option<String>,
+    /// Percent, e.g. 13.0000 for Ontario HST
+    pub rate: Decimal,
+    pub sequence: i32,
+    pub is_compound: bool,
+    pub is_recoverable: bool,
+    pub liability_account_id: Uuid,
+    pub effective_from: NaiveDate,
+    pub effective_to: Option<NaiveDate>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Input tax credit receivable; falls back to the liability account
+    pub recoverable_account_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct TaxCodeWithRates {
+    #[serde(flatten)]
+    pub tax_code: TaxCode,
+    pub rates: Vec<TaxRate>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct CreateTaxCodeRequest {
+    #[validate(length(min = 1, max = 50))]
+    #[schema(example = "HST-ON")]
+    pub code: String,
+    #[validate(length(min = 1, max = 255))]
+    #[schema(example = "HST Ontario")]
+    pub name: String,
+    pub description: Option<String>,
+    pub company_id: Option<Uuid>,
+    /// Component taxes; leave empty for exempt codes
+    #[validate(nested)]
+    pub rates: Vec<CreateTaxRateRequest>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct CreateTaxRateRequest {
+    #[validate(length(min = 1, max = 50))]
+    #[schema(example = "HST")]
+    pub tax_name: String,
+    #[schema(example = "CRA")]
+    pub agency: Option<String>,
+    #[schema(example = 13.0)]
+    pub rate: Decimal,
+    pub sequence: Option<i32>,
+    pub is_compound: Option<bool>,
+    pub is_recoverable: Option<bool>,
+    pub liability_account_id: Uuid,
+    /// Where recoverable tax paid on bills posts (e.g. GST/HST ITC Receivable)
+    pub recoverable_account_id: Option<Uuid>,
+    #[schema(example = "2025-01-01")]
+    pub effective_from: NaiveDate,
+    pub effective_to: Option<NaiveDate>,
+}
+
+/// Creates the standard Canadian GST/HST/PST/QST codes
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct SetupCanadianTaxCodesRequest {
+    /// GST/HST Payable
+    pub gst_hst_account_id: Uuid,
+    /// GST/HST ITC Receivable; ITCs reduce GST/HST Payable directly without it
+    pub itc_account_id: Option<Uuid>,
+    /// PST Payable; BC, Saskatchewan and Manitoba codes are skipped without it
+    pub pst_account_id: Option<Uuid>,
+    /// QST Payable; the Quebec code is skipped without it
+    pub qst_account_id: Option<Uuid>,
+    #[schema(example = "2025-04-01")]
+    pub effective_from: NaiveDate,
+    pub company_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct InvoiceLineTax {
+    pub id: Uuid,
+    pub invoice_id: Uuid,
+    pub invoice_line_item_id: Uuid,
+    pub tax_rate_id: Uuid,
+    pub tax_name: String,
+    pub rate: Decimal,
+    pub taxable_amount: Decimal,
+    pub tax_amount: Decimal,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Tax on a document totalled by component tax
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct TaxSummary {
+    pub tax_name: String,
+    pub rate: Decimal,
+    pub taxable_amount: Decimal,
+    pub tax_amount: Decimal,
+}
+
+/// One component tax calculated for a line
+#[derive(Debug, Clone)]
+pub struct CalculatedTax {
+    pub tax_rate_id: Uuid,
+    pub tax_name: String,
+    pub rate: Decimal,
+    pub taxable_amount: Decimal,
+    pub tax_amount: Decimal,
+    pub liability_account_id: Uuid,
+    pub recoverable_account_id: Option<Uuid>,
+    pub is_recoverable: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct BillLineTax {
+    pub id: Uuid,
+    pub bill_id: Uuid,
+    pub bill_line_item_id: Uuid,
+    pub tax_rate_id: Uuid,
+    pub tax_name: String,
+    pub rate: Decimal,
+    pub taxable_amount: Decimal,
+    pub tax_amount: Decimal,
+    pub is_recoverable: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct TaxReturn {
+    pub id: Uuid,
+    pub agency: String,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub status: TaxReturnStatus,
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub lines: TaxReturnLines,
+    pub settlement_account_id: Option<Uuid>,
+    pub transaction_id: Option<Uuid>,
+    pub filed_date: Option<NaiveDate>,
+    pub filed_by: Option<Uuid>,
+    pub company_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::Type, PartialEq, ToSchema)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum TaxReturnStatus {
+    Draft,
+    Filed,
+}
+
+impl std::fmt::Display for TaxReturnStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaxReturnStatus::Draft => write!(f, "draft"),
+            TaxReturnStatus::Filed => write!(f, "filed"),
+        }
+    }
+}
+
+/// Return lines numbered as on the GST34
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, Default, ToSchema)]
+pub struct TaxReturnLines {
+    /// Line 101: sales and other revenue (excluding tax)
+    pub line_101_sales: Decimal,
+    /// Supplies taxed by the agency, including zero-rated supplies
+    pub taxable_sales: Decimal,
+    /// Line 103: tax collected or collectible
+    pub line_103_tax_collected: Decimal,
+    /// Line 105: total tax and adjustments
+    pub line_105_total_tax: Decimal,
+    /// Line 106: input tax credits
+    pub line_106_itcs: Decimal,
+    /// Line 108: total ITCs and adjustments
+    pub line_108_total_itcs: Decimal,
+    /// Line 109: net tax (negative means a refund is claimed)
+    pub line_109_net_tax: Decimal,
+}
+
+/// Sales tax return worksheet for a period (not saved)
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct TaxReturnWorksheet {
+    pub agency: String,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    #[serde(flatten)]
+    pub lines: TaxReturnLines,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct TaxReturnPeriodRequest {
+    /// Tax agency to report on; defaults to CRA (GST/HST)
+    #[schema(example = "CRA")]
+    pub agency: Option<String>,
+    #[schema(example = "2025-07-01")]
+    pub period_start: NaiveDate,
+    #[schema(example = "2025-09-30")]
+    pub period_end: NaiveDate,
+    pub company_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct FileTaxReturnRequest {
+    #[schema(example = "2025-10-31")]
+    pub filed_date: NaiveDate,
+    /// Balancing account for the net tax, e.g. the bank account or a remittance payable
+    pub settlement_account_id: Uuid,
+}
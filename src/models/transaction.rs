@@ -13,6 +13,8 @@ pub struct Transaction {
     pub transaction_date: NaiveDate,
     pub description: Option<String>,
     pub reference_number: Option<String>,
+    /// Journal entry number; set on manual entries
+    pub entry_number: Option<String>,
     pub contact_id: Option<Uuid>,
     pub company_id: Option<Uuid>,
     pub journal_type: Option<JournalType>,
@@ -85,6 +87,9 @@ pub struct CreateTransactionRequest {
     pub description: Option<String>,
     #[schema(example = "INV-001")]
     pub reference_number: Option<String>,
+    /// Manual entries are numbered from the journal entry sequence when omitted
+    #[schema(example = "JE-00001")]
+    pub entry_number: Option<String>,
     pub contact_id: Option<Uuid>,
     pub company_id: Option<Uuid>,
     pub journal_type: Option<JournalType>,
@@ -105,6 +110,71 @@ pub struct CreateLineItemRequest {
 
     #[schema(example = 500.00)]
     pub credit_amount: Option<Decimal>,
+
+    /// Dimension values (project, department, ...) for reporting
+    #[serde(default)]
+    pub dimension_value_ids: Vec<Uuid>,
+}
+
+impl CreateLineItemRequest {
+    /// Debit line for system-generated journal entries
+    pub fn debit(account_id: Uuid, description: &str, amount: Decimal) -> Self {
+        Self {
+            account_id,
+            description: Some(description.to_string()),
+            debit_amount: Some(amount),
+            credit_amount: None,
+            dimension_value_ids: Vec::new(),
+        }
+    }
+
+    /// Credit line for system-generated journal entries
+    pub fn credit(account_id: Uuid, description: &str, amount: Decimal) -> Self {
+        Self {
+            account_id,
+            description: Some(description.to_string()),
+            debit_amount: None,
+            credit_amount: Some(amount),
+            dimension_value_ids: Vec::new(),
+        }
+    }
+
+    /// Net signed amounts per account (positive = debit, negative = credit)
+    /// into journal lines, keeping the order accounts first appear in.
+    pub fn from_signed_amounts(amounts: &[(Uuid, Decimal)], description: &str) -> Vec<Self> {
+        let tagged: Vec<(Uuid, Decimal, Vec<Uuid>)> = amounts
+            .iter()
+            .map(|(account_id, amount)| (*account_id, *amount, Vec::new()))
+            .collect();
+
+        Self::from_tagged_amounts(&tagged, description)
+    }
+
+    /// Like `from_signed_amounts`, netting per account and set of dimension values
+    pub fn from_tagged_amounts(amounts: &[(Uuid, Decimal, Vec<Uuid>)], description: &str) -> Vec<Self> {
+        let mut totals: Vec<(Uuid, Decimal, Vec<Uuid>)> = Vec::new();
+        for (account_id, amount, dimension_value_ids) in amounts {
+            let mut dimension_value_ids = dimension_value_ids.clone();
+            dimension_value_ids.sort();
+            match totals.iter_mut().find(|(id, _, dims)| id == account_id && *dims == dimension_value_ids) {
+                Some((_, total, _)) => *total += *amount,
+                None => totals.push((*account_id, *amount, dimension_value_ids)),
+            }
+        }
+
+        totals
+            .into_iter()
+            .filter(|(_, total, _)| !total.is_zero())
+            .map(|(account_id, total, dimension_value_ids)| {
+                let line = if total > Decimal::ZERO {
+                    Self::debit(account_id, description, total)
+                } else {
+                    Self::credit(account_id, description, -total)
+                };
+                Self { dimension_value_ids, ..line }
+            })
+            .collect()
+    }
 }
 
 // Validation function for balanced double-entry
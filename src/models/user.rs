@@ -13,17 +13,24 @@ pub struct User {
     #[serde(skip_serializing)]
     pub password_hash: String,
     pub role: UserRole,
+    /// Access tokens issued before this are no longer accepted
+    #[serde(skip_serializing)]
+    pub password_changed_at: Option<DateTime<Utc>>,
+    /// Disabled accounts can't log in
+    pub disabled_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, sqlx::Type, PartialEq, ToSchema)]
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::Type, PartialEq, Default, ToSchema)]
 #[sqlx(type_name = "varchar", rename_all = "lowercase")]
 pub enum UserRole {
     #[serde(rename = "admin")]
     Admin,
     #[serde(rename = "accountant")]
     Accountant,
+    /// Also the role of self-registered users
+    #[default]
     #[serde(rename = "viewer")]
     Viewer,
 }
@@ -52,10 +59,47 @@ pub struct CreateUserRequest {
     #[schema(example = "SecurePass123")]
     pub password: String,
 
-    #[schema(example = "admin")]
+    /// Self-registration may be limited to viewers, see `SELF_REGISTRATION`
+    #[serde(default)]
+    #[schema(example = "viewer")]
     pub role: UserRole,
 }
 
+/// Create an account for someone else; they get an email to choose a password
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct InviteUserRequest {
+    #[validate(length(min = 3, max = 100))]
+    #[schema(example = "janedoe")]
+    pub username: String,
+
+    #[validate(email)]
+    #[schema(example = "jane@example.com")]
+    pub email: String,
+
+    #[schema(example = "accountant")]
+    pub role: UserRole,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateUserRoleRequest {
+    #[schema(example = "accountant")]
+    pub role: UserRole,
+}
+
+/// A user as admins see it
+#[derive(Debug, Serialize, FromRow, ToSchema)]
+pub struct UserAccount {
+    pub id: Uuid,
+    pub username: String,
+    pub email: String,
+    pub role: UserRole,
+    pub disabled_at: Option<DateTime<Utc>>,
+    pub mfa_enabled: bool,
+    pub password_changed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct LoginRequest {
     #[validate(length(min = 1))]
@@ -67,6 +111,32 @@ pub struct LoginRequest {
     pub password: String,
 }
 
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ChangePasswordRequest {
+    #[validate(length(min = 1))]
+    pub current_password: String,
+
+    #[schema(example = "NewSecurePass456")]
+    pub new_password: String,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ForgotPasswordRequest {
+    #[validate(email)]
+    #[schema(example = "john@example.com")]
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ResetPasswordRequest {
+    /// Token from the reset email
+    #[validate(length(min = 1))]
+    pub token: String,
+
+    #[schema(example = "NewSecurePass456")]
+    pub new_password: String,
+}
+
 #[derive(Debug, Serialize, ToSchema)]
 pub struct AuthResponse {
     #[schema(example = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9...")]
@@ -76,6 +146,83 @@ pub struct AuthResponse {
     pub user: UserResponse,
 }
 
+/// Returned by login instead of tokens when the user has two-factor authentication
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MfaChallengeResponse {
+    pub mfa_required: bool,
+    /// Exchange at `/api/v1/auth/mfa/verify` together with a code
+    pub challenge_token: String,
+    /// Seconds left to finish logging in
+    pub expires_in: i64,
+}
+
+/// Tokens, or a challenge when the user still has to give a second factor
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum LoginResponse {
+    Authenticated(AuthResponse),
+    MfaRequired(MfaChallengeResponse),
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct MfaLoginRequest {
+    #[validate(length(min = 1))]
+    pub challenge_token: String,
+
+    /// Code from the authenticator app, or an unused recovery code
+    #[validate(length(min = 1))]
+    #[schema(example = "123456")]
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct MfaCodeRequest {
+    /// Code from the authenticator app, or an unused recovery code
+    #[validate(length(min = 1))]
+    #[schema(example = "123456")]
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct DisableMfaRequest {
+    #[validate(length(min = 1))]
+    pub password: String,
+
+    #[validate(length(min = 1))]
+    #[schema(example = "123456")]
+    pub code: String,
+}
+
+/// A new TOTP secret, to be confirmed with a code before it takes effect
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MfaSetupResponse {
+    /// Base32 secret, for entering into the authenticator app by hand
+    pub secret: String,
+    /// `otpauth://` URI to show as a QR code
+    #[schema(example = "otpauth://totp/LedgerForge:johndoe?secret=JBSWY3DPEHPK3PXP&issuer=LedgerForge")]
+    pub provisioning_uri: String,
+}
+
+/// Single-use recovery codes; they are only ever shown once
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MfaStatusResponse {
+    pub enabled: bool,
+    /// Whether the user's role must use two-factor authentication
+    pub required: bool,
+    pub recovery_codes_left: i64,
+}
+
+/// Roles whose users must set up two-factor authentication
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MfaPolicy {
+    pub required_roles: Vec<UserRole>,
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UserResponse {
     pub id: Uuid,
@@ -0,0 +1,124 @@
+#![allow(dead_code)]
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+use utoipa::ToSchema;
+
+use super::TaxSummary;
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct VendorCredit {
+    pub id: Uuid,
+    pub vendor_credit_number: String,
+    pub vendor_id: Uuid,
+    pub credit_date: NaiveDate,
+    /// Original bill being credited, if any
+    pub bill_id: Option<Uuid>,
+    pub subtotal: Decimal,
+    pub tax_amount: Decimal,
+    pub total_amount: Decimal,
+    /// Credit not yet applied to bills
+    pub balance: Decimal,
+    pub status: VendorCreditStatus,
+    pub memo: Option<String>,
+    pub ap_account_id: Option<Uuid>,
+    pub company_id: Option<Uuid>,
+    pub transaction_id: Option<Uuid>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::Type, PartialEq, ToSchema)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum VendorCreditStatus {
+    Open,
+    Applied,
+    Void,
+}
+
+impl std::fmt::Display for VendorCreditStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VendorCreditStatus::Open => write!(f, "open"),
+            VendorCreditStatus::Applied => write!(f, "applied"),
+            VendorCreditStatus::Void => write!(f, "void"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct VendorCreditLineItem {
+    pub id: Uuid,
+    pub vendor_credit_id: Uuid,
+    pub line_number: i32,
+    pub description: Option<String>,
+    pub amount: Decimal,
+    pub expense_account_id: Uuid,
+    pub tax_code: Option<String>,
+    pub tax_amount: Decimal,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct VendorCreditApplication {
+    pub id: Uuid,
+    pub vendor_credit_id: Uuid,
+    pub bill_id: Uuid,
+    pub amount_applied: Decimal,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct CreateVendorCreditRequest {
+    #[validate(length(min = 1))]
+    pub vendor_credit_number: String,
+    pub vendor_id: Uuid,
+    pub credit_date: NaiveDate,
+    pub bill_id: Option<Uuid>,
+    pub memo: Option<String>,
+    pub company_id: Option<Uuid>,
+    /// Accounts payable account; the vendor credit posts to the ledger when set
+    pub ap_account_id: Option<Uuid>,
+
+    #[validate(length(min = 1))]
+    pub line_items: Vec<CreateVendorCreditLineItemRequest>,
+    /// Bills to apply the credit to straight away
+    #[serde(default)]
+    pub applications: Vec<VendorCreditApplicationRequest>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct CreateVendorCreditLineItemRequest {
+    pub line_number: i32,
+    pub description: Option<String>,
+    /// Amount credited, excluding tax
+    pub amount: Decimal,
+    /// Expense account the credit is taken off
+    pub expense_account_id: Uuid,
+    /// Tax code to reverse (e.g. "HST-ON"); no tax when omitted
+    pub tax_code: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct VendorCreditApplicationRequest {
+    pub bill_id: Uuid,
+    pub amount_applied: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct VendorCreditWithLineItems {
+    #[serde(flatten)]
+    pub vendor_credit: VendorCredit,
+    pub line_items: Vec<VendorCreditLineItem>,
+    /// Tax reversed, totalled by component tax
+    #[serde(default)]
+    pub taxes: Vec<TaxSummary>,
+    #[serde(default)]
+    pub applications: Vec<VendorCreditApplication>,
+}
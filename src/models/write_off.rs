@@ -0,0 +1,67 @@
+#![allow(dead_code)]
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+use utoipa::ToSchema;
+
+/// Remaining balance of an invoice written off as uncollectible
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct InvoiceWriteOff {
+    pub id: Uuid,
+    pub invoice_id: Uuid,
+    pub customer_id: Uuid,
+    pub write_off_date: NaiveDate,
+    pub amount: Decimal,
+    /// Bad-debt expense or allowance account debited
+    pub account_id: Uuid,
+    pub reason: Option<String>,
+    /// Portion reinstated because the customer paid after all
+    pub recovered_amount: Decimal,
+    pub transaction_id: Option<Uuid>,
+    pub company_id: Option<Uuid>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Written-off balance reinstated on its invoice
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
+pub struct WriteOffRecovery {
+    pub id: Uuid,
+    pub write_off_id: Uuid,
+    pub invoice_id: Uuid,
+    pub recovery_date: NaiveDate,
+    pub amount: Decimal,
+    pub transaction_id: Option<Uuid>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct WriteOffInvoicesRequest {
+    #[validate(length(min = 1, message = "At least one invoice is required"))]
+    pub invoice_ids: Vec<Uuid>,
+    #[schema(example = "2025-12-31")]
+    pub write_off_date: NaiveDate,
+    /// Bad-debt expense account, or the allowance for doubtful accounts
+    pub account_id: Uuid,
+    #[validate(length(max = 500))]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RecoverWriteOffRequest {
+    #[schema(example = "2026-02-15")]
+    pub recovery_date: NaiveDate,
+    /// Amount to reinstate; everything still written off when omitted
+    pub amount: Option<Decimal>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct WriteOffWithRecoveries {
+    #[serde(flatten)]
+    pub write_off: InvoiceWriteOff,
+    pub recoveries: Vec<WriteOffRecovery>,
+}
@@ -1,5 +1,6 @@
 use axum::{
     extract::State,
+    middleware as axum_middleware,
     routing::{delete, get, post, put},
     Json, Router,
 };
@@ -9,7 +10,8 @@ use utoipa_swagger_ui::SwaggerUi;
 
 use crate::{
     handlers,
-    services::{AuthService, AccountService, TransactionService, ContactService, InvoiceService, PaymentService, BillService, ImportService, CacheService, ReportingService},
+    middleware::auth_middleware,
+    services::{AuthService, AccountService, TransactionService, ContactService, InvoiceService, PaymentService, BillService, ImportService, CacheService, ReportingService, InventoryService, TaxService, CreditMemoService, StatementService, EstimateService, PurchaseOrderService, VendorCreditService, EmailService, DunningService, FinanceChargeService, WriteOffService, NumberingService, BillableExpenseService, DimensionService, CompanyService},
     utils::HealthResponse
 };
 
@@ -26,6 +28,21 @@ pub struct AppState {
     pub bill_service: BillService,
     pub import_service: ImportService,
     pub reporting_service: ReportingService,
+    pub inventory_service: InventoryService,
+    pub tax_service: TaxService,
+    pub credit_memo_service: CreditMemoService,
+    pub statement_service: StatementService,
+    pub estimate_service: EstimateService,
+    pub purchase_order_service: PurchaseOrderService,
+    pub vendor_credit_service: VendorCreditService,
+    pub email_service: EmailService,
+    pub dunning_service: DunningService,
+    pub finance_charge_service: FinanceChargeService,
+    pub write_off_service: WriteOffService,
+    pub numbering_service: NumberingService,
+    pub billable_expense_service: BillableExpenseService,
+    pub dimension_service: DimensionService,
+    pub company_service: CompanyService,
     pub cache_service: CacheService,
 }
 
@@ -61,7 +78,7 @@ pub async fn health_check(State(state): State<AppState>) -> Json<HealthResponse>
 }
 
 /// Create all application routes
-pub fn create_routes(pool: PgPool, auth_service: AuthService, account_service: AccountService, transaction_service: TransactionService, contact_service: ContactService, invoice_service: InvoiceService, payment_service: PaymentService, bill_service: BillService, import_service: ImportService, reporting_service: ReportingService, cache_service: CacheService) -> Router {
+pub fn create_routes(pool: PgPool, auth_service: AuthService, account_service: AccountService, transaction_service: TransactionService, contact_service: ContactService, invoice_service: InvoiceService, payment_service: PaymentService, bill_service: BillService, import_service: ImportService, reporting_service: ReportingService, inventory_service: InventoryService, tax_service: TaxService, credit_memo_service: CreditMemoService, statement_service: StatementService, estimate_service: EstimateService, purchase_order_service: PurchaseOrderService, vendor_credit_service: VendorCreditService, email_service: EmailService, dunning_service: DunningService, finance_charge_service: FinanceChargeService, write_off_service: WriteOffService, numbering_service: NumberingService, billable_expense_service: BillableExpenseService, dimension_service: DimensionService, company_service: CompanyService, cache_service: CacheService) -> Router {
     let app_state = AppState {
         pool,
         auth_service,
@@ -73,6 +90,21 @@ pub fn create_routes(pool: PgPool, auth_service: AuthService, account_service: A
         bill_service,
         import_service,
         reporting_service,
+        inventory_service,
+        tax_service,
+        credit_memo_service,
+        statement_service,
+        estimate_service,
+        purchase_order_service,
+        vendor_credit_service,
+        email_service,
+        dunning_service,
+        finance_charge_service,
+        write_off_service,
+        numbering_service,
+        billable_expense_service,
+        dimension_service,
+        company_service,
         cache_service,
     };
 
@@ -83,7 +115,26 @@ pub fn create_routes(pool: PgPool, auth_service: AuthService, account_service: A
         .route("/api/v1/auth/register", post(handlers::register))
         .route("/api/v1/auth/login", post(handlers::login))
         .route("/api/v1/auth/refresh", post(handlers::refresh_token))
+        .route("/api/v1/auth/logout", post(handlers::logout))
+        .route("/api/v1/auth/logout-all", post(handlers::logout_all))
+        .route("/api/v1/auth/change-password", post(handlers::change_password))
+        .route("/api/v1/auth/forgot-password", post(handlers::forgot_password))
+        .route("/api/v1/auth/reset-password", post(handlers::reset_password))
+        .route("/api/v1/auth/mfa", get(handlers::mfa_status))
+        .route("/api/v1/auth/mfa/setup", post(handlers::setup_mfa))
+        .route("/api/v1/auth/mfa/confirm", post(handlers::confirm_mfa))
+        .route("/api/v1/auth/mfa/verify", post(handlers::verify_mfa_login))
+        .route("/api/v1/auth/mfa/recovery-codes", post(handlers::regenerate_recovery_codes))
+        .route("/api/v1/auth/mfa/disable", post(handlers::disable_mfa))
+        .route("/api/v1/auth/mfa/policy", get(handlers::get_mfa_policy).put(handlers::update_mfa_policy))
         .route("/api/v1/auth/me", get(handlers::me))
+        // User administration (admin only)
+        .route("/api/v1/users", get(handlers::list_users).post(handlers::invite_user))
+        .route("/api/v1/users/{id}", get(handlers::get_user))
+        .route("/api/v1/users/{id}/role", put(handlers::update_user_role))
+        .route("/api/v1/users/{id}/disable", post(handlers::disable_user))
+        .route("/api/v1/users/{id}/enable", post(handlers::enable_user))
+        .route("/api/v1/users/{id}/reset-password", post(handlers::force_password_reset))
         // Account routes
         .route("/api/v1/accounts", get(handlers::list_accounts))
         .route("/api/v1/accounts", post(handlers::create_account))
@@ -112,6 +163,9 @@ pub fn create_routes(pool: PgPool, auth_service: AuthService, account_service: A
         .route("/api/v1/invoices", post(handlers::create_invoice))
         .route("/api/v1/invoices/{id}", get(handlers::get_invoice))
         .route("/api/v1/invoices/{id}/status", put(handlers::update_invoice_status))
+        .route("/api/v1/invoices/{id}/pdf", get(handlers::get_invoice_pdf))
+        .route("/api/v1/invoice-template", get(handlers::get_invoice_template))
+        .route("/api/v1/invoice-template", put(handlers::update_invoice_template))
         .route("/api/v1/invoices/overdue", get(handlers::get_overdue_invoices))
         .route("/api/v1/customers/{id}/invoices", get(handlers::get_customer_invoices))
         .route("/api/v1/invoices/{id}/payments", get(handlers::get_invoice_payments))
@@ -121,6 +175,11 @@ pub fn create_routes(pool: PgPool, auth_service: AuthService, account_service: A
         .route("/api/v1/payments/{id}", get(handlers::get_payment))
         .route("/api/v1/payments/{id}/apply", put(handlers::apply_payment))
         .route("/api/v1/payments/unapplied", get(handlers::get_unapplied_payments))
+        .route("/api/v1/payments/{id}/applications", get(handlers::list_payment_applications))
+        .route("/api/v1/payments/{id}/applications/{application_id}", delete(handlers::unapply_payment))
+        .route("/api/v1/payments/{id}/void", post(handlers::void_payment))
+        .route("/api/v1/payments/auto-apply", post(handlers::auto_apply_payments))
+        .route("/api/v1/payments/consistency-check", get(handlers::check_application_consistency))
         .route("/api/v1/bill-payments", post(handlers::create_bill_payment))
         // Bill routes
         .route("/api/v1/bills", get(handlers::list_bills))
@@ -138,6 +197,106 @@ pub fn create_routes(pool: PgPool, auth_service: AuthService, account_service: A
         .route("/api/v1/reports/profit-loss", get(handlers::get_profit_loss))
         .route("/api/v1/reports/balance-sheet", get(handlers::get_balance_sheet))
         .route("/api/v1/reports/ar-aging", get(handlers::get_ar_aging))
+        .route("/api/v1/reports/inventory-valuation", get(handlers::get_inventory_valuation))
+        // Item and inventory routes
+        .route("/api/v1/items", get(handlers::list_items))
+        .route("/api/v1/items", post(handlers::create_item))
+        .route("/api/v1/items/{id}", get(handlers::get_item))
+        .route("/api/v1/items/{id}/cost-layers", get(handlers::get_item_cost_layers))
+        .route("/api/v1/items/{id}/movements", get(handlers::get_item_movements))
+        .route("/api/v1/inventory/adjustments", post(handlers::create_stock_adjustment))
+        .route("/api/v1/companies/{id}/inventory-costing-method", put(handlers::update_costing_method))
+        // Tax routes
+        .route("/api/v1/tax-codes", get(handlers::list_tax_codes))
+        .route("/api/v1/tax-codes", post(handlers::create_tax_code))
+        .route("/api/v1/tax-codes/canadian-defaults", post(handlers::setup_canadian_tax_codes))
+        .route("/api/v1/tax-codes/{id}", get(handlers::get_tax_code))
+        .route("/api/v1/tax-codes/{id}/rates", post(handlers::add_tax_rate))
+        .route("/api/v1/tax-returns", get(handlers::list_tax_returns))
+        .route("/api/v1/tax-returns", post(handlers::create_tax_return))
+        .route("/api/v1/tax-returns/{id}", get(handlers::get_tax_return))
+        .route("/api/v1/tax-returns/{id}/file", post(handlers::file_tax_return))
+        .route("/api/v1/reports/sales-tax-return", get(handlers::get_sales_tax_return))
+        // Credit memo routes
+        .route("/api/v1/credit-memos", get(handlers::list_credit_memos))
+        .route("/api/v1/credit-memos", post(handlers::create_credit_memo))
+        .route("/api/v1/credit-memos/{id}", get(handlers::get_credit_memo))
+        .route("/api/v1/credit-memos/{id}/apply", put(handlers::apply_credit_memo))
+        .route("/api/v1/credit-memos/{id}/void", post(handlers::void_credit_memo))
+        .route("/api/v1/customer-refunds", post(handlers::create_customer_refund))
+        .route("/api/v1/customers/{id}/refunds", get(handlers::get_customer_refunds))
+        // Statement routes
+        .route("/api/v1/customers/{id}/statement", get(handlers::get_customer_statement))
+        .route("/api/v1/statements/batch", post(handlers::generate_batch_statements))
+        // Estimate routes
+        .route("/api/v1/estimates", get(handlers::list_estimates))
+        .route("/api/v1/estimates", post(handlers::create_estimate))
+        .route("/api/v1/estimates/expire", post(handlers::expire_estimates))
+        .route("/api/v1/estimates/{id}", get(handlers::get_estimate))
+        .route("/api/v1/estimates/{id}/status", put(handlers::update_estimate_status))
+        .route("/api/v1/estimates/{id}/convert", post(handlers::convert_estimate))
+        // Purchase order routes
+        .route("/api/v1/purchase-orders", get(handlers::list_purchase_orders))
+        .route("/api/v1/purchase-orders", post(handlers::create_purchase_order))
+        .route("/api/v1/purchase-orders/{id}", get(handlers::get_purchase_order))
+        .route("/api/v1/purchase-orders/{id}/bill-preview", post(handlers::preview_purchase_order_bill))
+        .route("/api/v1/purchase-orders/{id}/convert", post(handlers::convert_purchase_order))
+        .route("/api/v1/purchase-orders/{id}/close", post(handlers::close_purchase_order))
+        .route("/api/v1/reports/open-purchase-orders", get(handlers::get_open_purchase_orders))
+        // Vendor credit routes
+        .route("/api/v1/vendor-credits", get(handlers::list_vendor_credits))
+        .route("/api/v1/vendor-credits", post(handlers::create_vendor_credit))
+        .route("/api/v1/vendor-credits/{id}", get(handlers::get_vendor_credit))
+        .route("/api/v1/vendor-credits/{id}/apply", put(handlers::apply_vendor_credit))
+        .route("/api/v1/vendor-credits/{id}/void", post(handlers::void_vendor_credit))
+        // Email routes
+        .route("/api/v1/invoices/{id}/send", post(handlers::send_invoice))
+        .route("/api/v1/customers/{id}/statement/send", post(handlers::send_statement))
+        .route("/api/v1/email-deliveries", get(handlers::list_email_deliveries))
+        .route("/api/v1/email-templates", get(handlers::get_email_template))
+        .route("/api/v1/email-templates", put(handlers::update_email_template))
+        // Dunning routes
+        .route("/api/v1/dunning/run", post(handlers::run_dunning))
+        .route("/api/v1/dunning/levels", get(handlers::get_dunning_levels))
+        .route("/api/v1/dunning/levels", put(handlers::set_dunning_levels))
+        .route("/api/v1/dunning/reminders", get(handlers::list_reminders))
+        .route("/api/v1/customers/{id}/dunning", put(handlers::set_dunning_opt_out))
+        // Finance charge routes
+        .route("/api/v1/finance-charges", get(handlers::list_finance_charges))
+        .route("/api/v1/finance-charges/settings", get(handlers::get_finance_charge_settings))
+        .route("/api/v1/finance-charges/settings", put(handlers::update_finance_charge_settings))
+        .route("/api/v1/finance-charges/run", post(handlers::run_finance_charges))
+        .route("/api/v1/customers/{id}/finance-charges", put(handlers::set_finance_charge_exempt))
+        // Write-off routes
+        .route("/api/v1/write-offs", post(handlers::write_off_invoices))
+        .route("/api/v1/write-offs", get(handlers::list_write_offs))
+        .route("/api/v1/invoices/{id}/recover", post(handlers::recover_write_off))
+        // Numbering sequence routes
+        .route("/api/v1/numbering-sequences", get(handlers::list_numbering_sequences))
+        .route("/api/v1/numbering-sequences/{document_type}", put(handlers::update_numbering_sequence))
+        // Billable expense routes
+        .route("/api/v1/customers/{id}/unbilled-expenses", get(handlers::list_unbilled_expenses))
+        .route("/api/v1/customers/{id}/bill-expenses", post(handlers::bill_expenses))
+        // Dimension routes
+        .route("/api/v1/dimensions", post(handlers::create_dimension_type))
+        .route("/api/v1/dimensions", get(handlers::list_dimension_types))
+        .route("/api/v1/dimensions/{id}", get(handlers::get_dimension_type))
+        .route("/api/v1/dimensions/{id}/values", post(handlers::create_dimension_value))
+        .route("/api/v1/dimension-values/{id}", put(handlers::update_dimension_value))
+        .route("/api/v1/dimensions/{id}/requirements", post(handlers::add_dimension_requirement))
+        .route("/api/v1/dimensions/{id}/requirements/{requirement_id}", delete(handlers::remove_dimension_requirement))
+        // Company routes
+        .route("/api/v1/companies", post(handlers::create_company))
+        .route("/api/v1/companies", get(handlers::list_companies))
+        .route("/api/v1/companies/{id}", get(handlers::get_company))
+        .route("/api/v1/companies/{id}", put(handlers::update_company))
+        .route("/api/v1/companies/{id}", delete(handlers::delete_company))
+        .route("/api/v1/companies/{id}/members", get(handlers::list_company_members))
+        .route("/api/v1/companies/{id}/members", post(handlers::add_company_member))
+        .route("/api/v1/companies/{id}/members/{user_id}", delete(handlers::remove_company_member))
+        .route("/api/v1/companies/{id}/switch", post(handlers::switch_company))
+        // Every route needs a token except the public ones, and the caller's role must allow it
+        .route_layer(axum_middleware::from_fn_with_state(app_state.clone(), auth_middleware))
         .with_state(app_state);
 
     // Combine all routes including Swagger UI
@@ -4,39 +4,48 @@ use validator::Validate;
 
 use crate::models::{Account, AccountType, CreateAccountRequest, UpdateAccountRequest};
 use crate::utils::{AppError, Result};
-use crate::services::CacheService;
+use crate::services::{CacheService, CompanyService};
 
 #[derive(Clone)]
 pub struct AccountService {
     cache: CacheService,
+    company_id: Option<Uuid>,
 }
 
 impl AccountService {
     pub fn new() -> Self {
         Self {
             cache: CacheService::default(),
+            company_id: None,
         }
     }
 
     pub fn new_with_cache(cache: CacheService) -> Self {
-        Self { cache }
+        Self { cache, company_id: None }
+    }
+
+    /// Scope the service to a company's records
+    pub fn for_company(&self, company_id: Option<Uuid>) -> Self {
+        Self { company_id, ..self.clone() }
     }
 
     /// Create a new account
     pub async fn create_account(&self, pool: &PgPool, req: CreateAccountRequest) -> Result<Account> {
         // Validate request
         req.validate()?;
+        let company_id = CompanyService::record_company(self.company_id, req.company_id)?;
 
         // Validate parent account exists if specified
         if let Some(parent_id) = req.parent_account_id {
             self.get_account_by_id(pool, parent_id).await?;
         }
 
-        // Check if account code already exists
+        // Check if account code already exists in the company
         let existing = sqlx::query_scalar::<_, i64>(
-            "SELECT COUNT(*) FROM chart_of_accounts WHERE code = $1"
+            "SELECT COUNT(*) FROM chart_of_accounts WHERE code = $1 AND company_id IS NOT DISTINCT FROM $2"
         )
         .bind(&req.code)
+        .bind(company_id)
         .fetch_one(pool)
         .await?;
 
@@ -59,7 +68,7 @@ impl AccountService {
         .bind(req.account_type.to_string())
         .bind(req.parent_account_id)
         .bind(true) // is_active defaults to true
-        .bind(req.company_id)
+        .bind(company_id)
         .fetch_one(pool)
         .await?;
 
@@ -83,7 +92,7 @@ impl AccountService {
             r#"
             SELECT id, code, name, account_type, parent_account_id, is_active, company_id, created_at, updated_at
             FROM chart_of_accounts
-            WHERE 1=1
+            WHERE company_id IS NOT DISTINCT FROM $1
             "#
         );
 
@@ -93,21 +102,21 @@ impl AccountService {
         }
 
         if account_type.is_some() {
-            query.push_str(" AND account_type = $1");
+            query.push_str(" AND account_type = $2");
         }
 
         if parent_id.is_some() {
             if account_type.is_some() {
-                query.push_str(" AND parent_account_id = $2");
+                query.push_str(" AND parent_account_id = $3");
             } else {
-                query.push_str(" AND parent_account_id = $1");
+                query.push_str(" AND parent_account_id = $2");
             }
         }
 
         query.push_str(" ORDER BY code ASC");
 
         // Build and execute query dynamically
-        let mut sql_query = sqlx::query_as::<_, Account>(&query);
+        let mut sql_query = sqlx::query_as::<_, Account>(&query).bind(self.company_id);
 
         if let Some(acc_type) = account_type {
             sql_query = sql_query.bind(acc_type.to_string());
@@ -128,7 +137,7 @@ impl AccountService {
         if let Ok(Some(cached_json)) = self.cache.get_account(id).await {
             if let Ok(account) = serde_json::from_str::<Account>(&cached_json) {
                 tracing::debug!("Cache hit for account {}", id);
-                return Ok(account);
+                return self.in_company(account);
             }
         }
 
@@ -151,7 +160,7 @@ impl AccountService {
             let _ = self.cache.set_account(id, &account_json).await;
         }
 
-        Ok(account)
+        self.in_company(account)
     }
 
     /// Update account
@@ -260,6 +269,7 @@ impl AccountService {
         if let Ok(Some(cached_json)) = self.cache.get_account_hierarchy(id).await {
             if let Ok(hierarchy) = serde_json::from_str::<AccountHierarchy>(&cached_json) {
                 tracing::debug!("Cache hit for account hierarchy {}", id);
+                self.in_company(hierarchy.account.clone())?;
                 return Ok(hierarchy);
             }
         }
@@ -301,6 +311,15 @@ impl AccountService {
 
         Ok(hierarchy)
     }
+
+    /// Accounts of other companies are reported as not found
+    fn in_company(&self, account: Account) -> Result<Account> {
+        if account.company_id != self.company_id {
+            return Err(AppError::NotFound(format!("Account with id {} not found", account.id)));
+        }
+
+        Ok(account)
+    }
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
@@ -1,15 +1,23 @@
 use argon2::{
-    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    password_hash::{rand_core::{OsRng, RngCore}, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
     Argon2,
 };
-use chrono::{Duration, Utc};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Duration, Utc};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, TokenData, Validation};
 use serde::{Deserialize, Serialize};
-use sqlx::PgPool;
+use sha2::{Digest, Sha256};
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 use validator::Validate;
 
-use crate::models::{CreateUserRequest, LoginRequest, User, UserRole};
+use crate::models::{
+    ChangePasswordRequest, CreateUserRequest, DisableMfaRequest, ForgotPasswordRequest, InviteUserRequest,
+    LoginRequest, MfaChallengeResponse, MfaCodeRequest, MfaLoginRequest, MfaPolicy, MfaSetupResponse,
+    MfaStatusResponse, ResetPasswordRequest, User, UserAccount, UserRole,
+};
+use crate::services::mailer::{MailMessage, Mailer};
+use crate::services::totp;
 use crate::utils::{AppError, Result};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,8 +26,214 @@ pub struct Claims {
     pub username: String,
     pub email: String,
     pub role: UserRole,
+    /// Active company; records of other companies are out of reach
+    #[serde(default)]
+    pub company_id: Option<Uuid>,
+    /// What the token is for; only access tokens are accepted as bearer tokens
+    pub token_type: TokenType,
     pub exp: i64,          // Expiration time
     pub iat: i64,          // Issued at
+    /// Issued at in milliseconds, so a token from the same second as a password change can
+    /// be told apart; tokens without it count as issued before any change
+    #[serde(default)]
+    pub iat_ms: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenType {
+    Access,
+}
+
+/// Outcome of checking a username and password
+pub enum LoginOutcome {
+    /// No second factor needed, tokens can be issued
+    Authenticated(User),
+    /// The user has two-factor authentication; finish with `complete_mfa_login`
+    MfaRequired(MfaChallengeResponse),
+}
+
+/// Columns of `UserAccount`, selected from `users u`
+const USER_ACCOUNT_SELECT: &str = r#"
+    SELECT u.id, u.username, u.email, u.role, u.disabled_at,
+           EXISTS(
+               SELECT 1 FROM totp_credentials t WHERE t.user_id = u.id AND t.confirmed_at IS NOT NULL
+           ) AS mfa_enabled,
+           u.password_changed_at, u.created_at, u.updated_at
+    FROM users u
+"#;
+
+/// Issuer shown next to the account in authenticator apps
+const MFA_ISSUER: &str = "LedgerForge";
+/// Recovery codes handed out at a time
+const RECOVERY_CODE_COUNT: usize = 10;
+/// Wrong codes after which a two-factor challenge is spent
+const MFA_CHALLENGE_ATTEMPTS: i32 = 5;
+/// Wrong codes across a user's recent challenges after which no new challenge is issued
+const MFA_LOCKOUT_FAILURES: i64 = 10;
+/// How far back wrong codes count towards the lockout, in minutes
+const MFA_LOCKOUT_MINUTES: i32 = 15;
+
+/// Stored refresh token, looked up by its hash
+#[derive(sqlx::FromRow)]
+struct RefreshTokenRecord {
+    id: Uuid,
+    user_id: Uuid,
+    family_id: Uuid,
+    company_id: Option<Uuid>,
+    expires_at: DateTime<Utc>,
+    used_at: Option<DateTime<Utc>>,
+    revoked_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+}
+
+/// Stored two-factor challenge, looked up by its hash
+#[derive(sqlx::FromRow)]
+struct MfaChallengeRecord {
+    id: Uuid,
+    user_id: Uuid,
+    failed_attempts: i32,
+    expires_at: DateTime<Utc>,
+    used_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+}
+
+/// Result of rotating a refresh token
+pub struct RotatedRefreshToken {
+    pub user: User,
+    pub company_id: Option<Uuid>,
+    pub refresh_token: String,
+}
+
+/// A random token for the client to hold; only its hash is stored
+pub fn generate_opaque_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Hex SHA-256 of an opaque token, as stored in the database
+pub fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// A recovery code such as `k3jd9-x2mqa`
+fn generate_recovery_code() -> String {
+    let mut bytes = [0u8; 8];
+    OsRng.fill_bytes(&mut bytes);
+    let code = base32::encode(base32::Alphabet::Rfc4648 { padding: false }, &bytes).to_lowercase();
+    format!("{}-{}", &code[..5], &code[5..10])
+}
+
+/// A second-factor code as typed, without spaces or dashes
+fn normalize_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// Who may create an account through `/api/v1/auth/register`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RegistrationMode {
+    /// Only admins create accounts, by inviting users
+    Disabled,
+    /// Anyone can sign up, as a viewer
+    #[default]
+    ViewerOnly,
+    /// Anyone can sign up with any role, admin included; for local development only
+    Open,
+}
+
+impl RegistrationMode {
+    /// Read `SELF_REGISTRATION` (`disabled`, `viewer` or `open`), defaulting to viewer-only
+    pub fn from_env() -> Self {
+        match std::env::var("SELF_REGISTRATION").unwrap_or_default().to_lowercase().as_str() {
+            "disabled" => Self::Disabled,
+            "open" => {
+                tracing::warn!("SELF_REGISTRATION=open lets anyone sign up as an admin (NOT FOR PRODUCTION!)");
+                Self::Open
+            }
+            _ => Self::ViewerOnly,
+        }
+    }
+}
+
+/// Rules new passwords must meet
+#[derive(Debug, Clone)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub require_uppercase: bool,
+    pub require_lowercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            require_uppercase: true,
+            require_lowercase: true,
+            require_digit: true,
+            require_symbol: false,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    /// Read `PASSWORD_MIN_LENGTH` and `PASSWORD_REQUIRE_{UPPERCASE,LOWERCASE,DIGIT,SYMBOL}`,
+    /// keeping the default for anything unset
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let flag = |name: &str, default: bool| {
+            std::env::var(name)
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(default)
+        };
+
+        Self {
+            min_length: std::env::var("PASSWORD_MIN_LENGTH")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(defaults.min_length),
+            require_uppercase: flag("PASSWORD_REQUIRE_UPPERCASE", defaults.require_uppercase),
+            require_lowercase: flag("PASSWORD_REQUIRE_LOWERCASE", defaults.require_lowercase),
+            require_digit: flag("PASSWORD_REQUIRE_DIGIT", defaults.require_digit),
+            require_symbol: flag("PASSWORD_REQUIRE_SYMBOL", defaults.require_symbol),
+        }
+    }
+
+    /// Check a password, listing every rule it breaks
+    pub fn check(&self, password: &str) -> Result<()> {
+        let mut problems = Vec::new();
+
+        if password.chars().count() < self.min_length {
+            problems.push(format!("be at least {} characters long", self.min_length));
+        }
+        if self.require_uppercase && !password.chars().any(char::is_uppercase) {
+            problems.push("contain an uppercase letter".to_string());
+        }
+        if self.require_lowercase && !password.chars().any(char::is_lowercase) {
+            problems.push("contain a lowercase letter".to_string());
+        }
+        if self.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            problems.push("contain a digit".to_string());
+        }
+        if self.require_symbol && password.chars().all(char::is_alphanumeric) {
+            problems.push("contain a symbol".to_string());
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::ValidationError(format!("Password must {}", problems.join(", "))))
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -27,6 +241,13 @@ pub struct AuthService {
     jwt_secret: String,
     access_token_expiry: i64,  // in minutes
     refresh_token_expiry: i64, // in minutes
+    password_reset_expiry: i64, // in minutes
+    invitation_expiry: i64,     // in minutes
+    mfa_challenge_expiry: i64, // in minutes
+    password_policy: PasswordPolicy,
+    registration: RegistrationMode,
+    mailer: Mailer,
+    password_reset_url: Option<String>,
 }
 
 impl AuthService {
@@ -35,9 +256,37 @@ impl AuthService {
             jwt_secret,
             access_token_expiry: 60,      // 1 hour
             refresh_token_expiry: 10080,  // 7 days
+            password_reset_expiry: 60,    // 1 hour
+            invitation_expiry: 10080,     // 7 days
+            mfa_challenge_expiry: 5,
+            password_policy: PasswordPolicy::default(),
+            registration: RegistrationMode::default(),
+            mailer: Mailer::Log,
+            password_reset_url: None,
         }
     }
 
+    pub fn with_password_policy(mut self, password_policy: PasswordPolicy) -> Self {
+        self.password_policy = password_policy;
+        self
+    }
+
+    pub fn with_registration(mut self, registration: RegistrationMode) -> Self {
+        self.registration = registration;
+        self
+    }
+
+    pub fn with_mailer(mut self, mailer: Mailer) -> Self {
+        self.mailer = mailer;
+        self
+    }
+
+    /// Page the reset email links to; the token is appended as `?token=`
+    pub fn with_password_reset_url(mut self, password_reset_url: Option<String>) -> Self {
+        self.password_reset_url = password_reset_url;
+        self
+    }
+
     /// Hash a password using Argon2
     pub fn hash_password(&self, password: &str) -> Result<String> {
         let salt = SaltString::generate(&mut OsRng);
@@ -61,39 +310,24 @@ impl AuthService {
     }
 
     /// Generate JWT access token
-    pub fn generate_access_token(&self, user: &User) -> Result<String> {
-        let now = Utc::now();
-        let expiry = now + Duration::minutes(self.access_token_expiry);
-
-        let claims = Claims {
-            sub: user.id.to_string(),
-            username: user.username.clone(),
-            email: user.email.clone(),
-            role: user.role.clone(),
-            exp: expiry.timestamp(),
-            iat: now.timestamp(),
-        };
-
-        encode(
-            &Header::default(),
-            &claims,
-            &EncodingKey::from_secret(self.jwt_secret.as_bytes()),
-        )
-        .map_err(|e| AppError::InternalError(format!("Token generation failed: {}", e)))
+    pub fn generate_access_token(&self, user: &User, company_id: Option<Uuid>) -> Result<String> {
+        self.encode_token(user, company_id, TokenType::Access, self.access_token_expiry)
     }
 
-    /// Generate JWT refresh token (longer expiry)
-    pub fn generate_refresh_token(&self, user: &User) -> Result<String> {
+    fn encode_token(&self, user: &User, company_id: Option<Uuid>, token_type: TokenType, expiry_minutes: i64) -> Result<String> {
         let now = Utc::now();
-        let expiry = now + Duration::minutes(self.refresh_token_expiry);
+        let expiry = now + Duration::minutes(expiry_minutes);
 
         let claims = Claims {
             sub: user.id.to_string(),
             username: user.username.clone(),
             email: user.email.clone(),
             role: user.role.clone(),
+            company_id,
+            token_type,
             exp: expiry.timestamp(),
             iat: now.timestamp(),
+            iat_ms: now.timestamp_millis(),
         };
 
         encode(
@@ -104,9 +338,13 @@ impl AuthService {
         .map_err(|e| AppError::InternalError(format!("Token generation failed: {}", e)))
     }
 
-    /// Validate and decode JWT token
+    /// Validate and decode a JWT access token
     pub fn validate_token(&self, token: &str) -> Result<TokenData<Claims>> {
-        decode::<Claims>(
+        self.decode_token(token, TokenType::Access)
+    }
+
+    fn decode_token(&self, token: &str, token_type: TokenType) -> Result<TokenData<Claims>> {
+        let token_data = decode::<Claims>(
             token,
             &DecodingKey::from_secret(self.jwt_secret.as_bytes()),
             &Validation::default(),
@@ -114,13 +352,179 @@ impl AuthService {
         .map_err(|e| match e.kind() {
             jsonwebtoken::errors::ErrorKind::ExpiredSignature => AppError::TokenExpired,
             _ => AppError::InvalidToken,
+        })?;
+
+        if token_data.claims.token_type != token_type {
+            return Err(AppError::InvalidToken);
+        }
+
+        Ok(token_data)
+    }
+
+    /// Issue a refresh token starting a new family, i.e. a new session
+    pub async fn generate_refresh_token(&self, pool: &PgPool, user: &User, company_id: Option<Uuid>) -> Result<String> {
+        let mut conn = pool.acquire().await?;
+        self.insert_refresh_token(&mut conn, user.id, Uuid::new_v4(), company_id).await
+    }
+
+    async fn insert_refresh_token(
+        &self,
+        conn: &mut PgConnection,
+        user_id: Uuid,
+        family_id: Uuid,
+        company_id: Option<Uuid>,
+    ) -> Result<String> {
+        let token = generate_opaque_token();
+
+        sqlx::query(
+            r#"
+            INSERT INTO refresh_tokens (user_id, family_id, token_hash, company_id, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(user_id)
+        .bind(family_id)
+        .bind(hash_token(&token))
+        .bind(company_id)
+        .bind(Utc::now() + Duration::minutes(self.refresh_token_expiry))
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(token)
+    }
+
+    /// Use up a refresh token and issue the next one in its family.
+    /// A token that was already used has leaked, so presenting it again revokes the whole family.
+    pub async fn rotate_refresh_token(&self, pool: &PgPool, token: &str) -> Result<RotatedRefreshToken> {
+        let mut tx = pool.begin().await?;
+
+        let stored = sqlx::query_as::<_, RefreshTokenRecord>(
+            r#"
+            SELECT id, user_id, family_id, company_id, expires_at, used_at, revoked_at, created_at
+            FROM refresh_tokens
+            WHERE token_hash = $1
+            FOR UPDATE
+            "#,
+        )
+        .bind(hash_token(token))
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::InvalidToken)?;
+
+        if stored.revoked_at.is_some() {
+            return Err(AppError::InvalidToken);
+        }
+
+        if stored.used_at.is_some() {
+            Self::revoke_family(&mut tx, stored.family_id).await?;
+            tx.commit().await?;
+            tracing::warn!("Refresh token reuse for user {}; revoked the session", stored.user_id);
+            return Err(AppError::Unauthorized("Refresh token has already been used".to_string()));
+        }
+
+        if stored.expires_at <= Utc::now() {
+            return Err(AppError::TokenExpired);
+        }
+        let user = Self::credential_user(&mut tx, stored.user_id, stored.created_at).await?;
+
+        sqlx::query("UPDATE refresh_tokens SET used_at = NOW() WHERE id = $1")
+            .bind(stored.id)
+            .execute(&mut *tx)
+            .await?;
+
+        let refresh_token = self
+            .insert_refresh_token(&mut tx, stored.user_id, stored.family_id, stored.company_id)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(RotatedRefreshToken {
+            user,
+            company_id: stored.company_id,
+            refresh_token,
         })
     }
 
-    /// Register a new user
+    /// Log out one session: revoke the family of a user's refresh token
+    pub async fn revoke_refresh_token(&self, pool: &PgPool, user_id: Uuid, token: &str) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let family_id: Uuid = sqlx::query_scalar(
+            "SELECT family_id FROM refresh_tokens WHERE token_hash = $1 AND user_id = $2",
+        )
+        .bind(hash_token(token))
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::InvalidToken)?;
+
+        Self::revoke_family(&mut tx, family_id).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Log out everywhere: revoke every refresh token of a user. Returns the number of sessions ended.
+    pub async fn revoke_all_refresh_tokens(&self, pool: &PgPool, user_id: Uuid) -> Result<i64> {
+        let sessions: i64 = sqlx::query_scalar(
+            r#"
+            WITH revoked AS (
+                UPDATE refresh_tokens SET revoked_at = NOW()
+                WHERE user_id = $1 AND revoked_at IS NULL
+                RETURNING family_id
+            )
+            SELECT COUNT(DISTINCT family_id) FROM revoked
+            "#,
+        )
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(sessions)
+    }
+
+    async fn revoke_family(conn: &mut PgConnection, family_id: Uuid) -> Result<()> {
+        sqlx::query(
+            "UPDATE refresh_tokens SET revoked_at = NOW() WHERE family_id = $1 AND revoked_at IS NULL",
+        )
+        .bind(family_id)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Sign-up through the public endpoint, as far as the registration mode allows
+    pub async fn self_register(&self, pool: &PgPool, req: CreateUserRequest) -> Result<User> {
+        match self.registration {
+            RegistrationMode::Disabled => Err(AppError::Forbidden(
+                "Self-registration is disabled; ask an admin for an invitation".to_string(),
+            )),
+            RegistrationMode::ViewerOnly if req.role != UserRole::Viewer => Err(AppError::Forbidden(
+                "Self-registration only creates viewer accounts; ask an admin for another role".to_string(),
+            )),
+            _ => self.register(pool, req).await,
+        }
+    }
+
+    /// Create the first admin of a new install. Does nothing once there is an admin.
+    pub async fn bootstrap_admin(&self, pool: &PgPool, req: CreateUserRequest) -> Result<Option<User>> {
+        let has_admin: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE role = 'admin')")
+            .fetch_one(pool)
+            .await?;
+        if has_admin {
+            return Ok(None);
+        }
+
+        let admin = self.register(pool, CreateUserRequest { role: UserRole::Admin, ..req }).await?;
+        Ok(Some(admin))
+    }
+
+    /// Create a user with the requested role. Public sign-ups go through `self_register`.
     pub async fn register(&self, pool: &PgPool, req: CreateUserRequest) -> Result<User> {
         // Validate request
         req.validate()?;
+        self.password_policy.check(&req.password)?;
 
         // Hash password
         let password_hash = self.hash_password(&req.password)?;
@@ -130,7 +534,7 @@ impl AuthService {
             r#"
             INSERT INTO users (id, username, email, password_hash, role, created_at, updated_at)
             VALUES ($1, $2, $3, $4, $5, NOW(), NOW())
-            RETURNING id, username, email, password_hash, role, created_at, updated_at
+            RETURNING id, username, email, password_hash, role, password_changed_at, disabled_at, created_at, updated_at
             "#,
         )
         .bind(Uuid::new_v4())
@@ -144,15 +548,16 @@ impl AuthService {
         Ok(user)
     }
 
-    /// Login user
-    pub async fn login(&self, pool: &PgPool, req: LoginRequest) -> Result<User> {
+    /// Check a username and password. Users with two-factor authentication get a challenge
+    /// token instead, to be completed with `complete_mfa_login`.
+    pub async fn login(&self, pool: &PgPool, req: LoginRequest) -> Result<LoginOutcome> {
         // Validate request
         req.validate()?;
 
         // Find user by username
         let user = sqlx::query_as::<_, User>(
             r#"
-            SELECT id, username, email, password_hash, role, created_at, updated_at
+            SELECT id, username, email, password_hash, role, password_changed_at, disabled_at, created_at, updated_at
             FROM users
             WHERE username = $1
             "#,
@@ -166,6 +571,100 @@ impl AuthService {
         if !self.verify_password(&req.password, &user.password_hash)? {
             return Err(AppError::InvalidCredentials);
         }
+        if user.disabled_at.is_some() {
+            return Err(AppError::Forbidden("This account has been disabled".to_string()));
+        }
+
+        if self.mfa_enabled(pool, user.id).await? {
+            let challenge_token = self.issue_mfa_challenge(pool, user.id).await?;
+            return Ok(LoginOutcome::MfaRequired(MfaChallengeResponse {
+                mfa_required: true,
+                challenge_token,
+                expires_in: self.mfa_challenge_expiry * 60,
+            }));
+        }
+
+        Ok(LoginOutcome::Authenticated(user))
+    }
+
+    /// Store a challenge for the second step of a login. None is issued while the user has
+    /// had too many wrong codes lately, so knowing the password isn't enough to keep guessing.
+    async fn issue_mfa_challenge(&self, pool: &PgPool, user_id: Uuid) -> Result<String> {
+        let recent_failures: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COALESCE(SUM(failed_attempts), 0)
+            FROM mfa_challenges
+            WHERE user_id = $1 AND created_at > NOW() - make_interval(mins => $2)
+            "#,
+        )
+        .bind(user_id)
+        .bind(MFA_LOCKOUT_MINUTES)
+        .fetch_one(pool)
+        .await?;
+        if recent_failures >= MFA_LOCKOUT_FAILURES {
+            return Err(AppError::Unauthorized(
+                "Too many wrong two-factor codes; try again later".to_string()
+            ));
+        }
+
+        let token = generate_opaque_token();
+        sqlx::query(
+            r#"
+            INSERT INTO mfa_challenges (user_id, token_hash, expires_at)
+            VALUES ($1, $2, $3)
+            "#,
+        )
+        .bind(user_id)
+        .bind(hash_token(&token))
+        .bind(Utc::now() + Duration::minutes(self.mfa_challenge_expiry))
+        .execute(pool)
+        .await?;
+
+        Ok(token)
+    }
+
+    /// Second step of a two-factor login: a challenge token from `login` plus a TOTP or recovery
+    /// code. The challenge is used up by a right code, and after too many wrong ones.
+    pub async fn complete_mfa_login(&self, pool: &PgPool, req: MfaLoginRequest) -> Result<User> {
+        req.validate()?;
+
+        let mut tx = pool.begin().await?;
+
+        let challenge = sqlx::query_as::<_, MfaChallengeRecord>(
+            r#"
+            SELECT id, user_id, failed_attempts, expires_at, used_at, created_at
+            FROM mfa_challenges
+            WHERE token_hash = $1
+            FOR UPDATE
+            "#,
+        )
+        .bind(hash_token(&req.challenge_token))
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::InvalidToken)?;
+
+        if challenge.used_at.is_some() || challenge.failed_attempts >= MFA_CHALLENGE_ATTEMPTS {
+            return Err(AppError::InvalidToken);
+        }
+        if challenge.expires_at <= Utc::now() {
+            return Err(AppError::TokenExpired);
+        }
+        let user = Self::credential_user(&mut tx, challenge.user_id, challenge.created_at).await?;
+
+        if let Err(e) = self.verify_second_factor(&mut tx, user.id, &req.code).await {
+            sqlx::query("UPDATE mfa_challenges SET failed_attempts = failed_attempts + 1 WHERE id = $1")
+                .bind(challenge.id)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            return Err(e);
+        }
+
+        sqlx::query("UPDATE mfa_challenges SET used_at = NOW() WHERE id = $1")
+            .bind(challenge.id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
 
         Ok(user)
     }
@@ -174,7 +673,7 @@ impl AuthService {
     pub async fn get_user_by_id(&self, pool: &PgPool, user_id: Uuid) -> Result<User> {
         let user = sqlx::query_as::<_, User>(
             r#"
-            SELECT id, username, email, password_hash, role, created_at, updated_at
+            SELECT id, username, email, password_hash, role, password_changed_at, disabled_at, created_at, updated_at
             FROM users
             WHERE id = $1
             "#,
@@ -186,6 +685,619 @@ impl AuthService {
 
         Ok(user)
     }
+
+    /// User behind an access token. Tokens of disabled users, and tokens issued before the
+    /// user's last password change, are rejected.
+    pub async fn authenticated_user(&self, pool: &PgPool, claims: &Claims) -> Result<User> {
+        let user_id = Uuid::parse_str(&claims.sub)
+            .map_err(|_| AppError::InvalidToken)?;
+        let issued_at = DateTime::from_timestamp_millis(claims.iat_ms)
+            .ok_or(AppError::InvalidToken)?;
+
+        let mut conn = pool.acquire().await?;
+        Self::credential_user(&mut conn, user_id, issued_at).await
+    }
+
+    /// The user a credential issued at `issued_at` belongs to, as long as it still stands:
+    /// the user exists, isn't disabled and hasn't changed their password since
+    async fn credential_user(conn: &mut PgConnection, user_id: Uuid, issued_at: DateTime<Utc>) -> Result<User> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            SELECT id, username, email, password_hash, role, password_changed_at, disabled_at, created_at, updated_at
+            FROM users
+            WHERE id = $1
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or(AppError::InvalidToken)?;
+
+        if user.disabled_at.is_some() {
+            return Err(AppError::Unauthorized("This account has been disabled".to_string()));
+        }
+        if user.password_changed_at.is_some_and(|changed| issued_at <= changed) {
+            return Err(AppError::TokenExpired);
+        }
+
+        Ok(user)
+    }
+
+    /// Change a password after re-checking the current one; every session is logged out
+    pub async fn change_password(&self, pool: &PgPool, user_id: Uuid, req: ChangePasswordRequest) -> Result<User> {
+        req.validate()?;
+
+        let user = self.get_user_by_id(pool, user_id).await?;
+        if !self.verify_password(&req.current_password, &user.password_hash)? {
+            return Err(AppError::InvalidCredentials);
+        }
+        if self.verify_password(&req.new_password, &user.password_hash)? {
+            return Err(AppError::ValidationError("The new password must be different".to_string()));
+        }
+
+        let mut tx = pool.begin().await?;
+        let user = self.set_password(&mut tx, user_id, &req.new_password).await?;
+        tx.commit().await?;
+
+        Ok(user)
+    }
+
+    /// Email a single-use reset token to the user with this address. Unknown addresses and
+    /// delivery failures are only logged, so the response doesn't reveal who has an account.
+    pub async fn request_password_reset(&self, pool: &PgPool, req: ForgotPasswordRequest) -> Result<()> {
+        req.validate()?;
+
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            SELECT id, username, email, password_hash, role, password_changed_at, disabled_at, created_at, updated_at
+            FROM users
+            WHERE LOWER(email) = LOWER($1)
+            "#,
+        )
+        .bind(&req.email)
+        .fetch_optional(pool)
+        .await?;
+
+        let Some(user) = user else {
+            tracing::info!("Password reset requested for unknown address {}", req.email);
+            return Ok(());
+        };
+        if user.disabled_at.is_some() {
+            tracing::info!("Password reset requested for disabled user {}", user.id);
+            return Ok(());
+        }
+
+        self.send_password_token(
+            pool,
+            &user,
+            self.password_reset_expiry,
+            "Reset your password",
+            "Someone asked to reset the password for your account.",
+        )
+        .await
+    }
+
+    /// Email a single-use token for choosing a new password. Delivery failures are only logged.
+    async fn send_password_token(&self, pool: &PgPool, user: &User, expiry_minutes: i64, subject: &str, reason: &str) -> Result<()> {
+        let token = generate_opaque_token();
+        sqlx::query(
+            r#"
+            INSERT INTO password_reset_tokens (user_id, token_hash, expires_at)
+            VALUES ($1, $2, $3)
+            "#,
+        )
+        .bind(user.id)
+        .bind(hash_token(&token))
+        .bind(Utc::now() + Duration::minutes(expiry_minutes))
+        .execute(pool)
+        .await?;
+
+        let instructions = match &self.password_reset_url {
+            Some(url) => format!("Open this link to choose a new password:\n{}?token={}", url, token),
+            None => format!("Use this code to choose a new password:\n{}", token),
+        };
+        let expiry = if expiry_minutes % 1440 == 0 {
+            format!("{} days", expiry_minutes / 1440)
+        } else {
+            format!("{} minutes", expiry_minutes)
+        };
+        let message = MailMessage {
+            to: user.email.clone(),
+            subject: subject.to_string(),
+            body: format!(
+                "Hello {},\n\n{}\n{}\n\n\
+                It expires in {} and can be used once. If you didn't expect this email, you can ignore it.",
+                user.username, reason, instructions, expiry
+            ),
+        };
+
+        if let Err(e) = self.mailer.send(&message).await {
+            tracing::warn!("Password email for user {} failed: {}", user.id, e);
+        }
+
+        Ok(())
+    }
+
+    /// Set a new password with a reset token. The token is used up, along with any other
+    /// outstanding ones, and every session is logged out.
+    pub async fn reset_password(&self, pool: &PgPool, req: ResetPasswordRequest) -> Result<User> {
+        req.validate()?;
+
+        let mut tx = pool.begin().await?;
+
+        let (user_id, expires_at, used_at): (Uuid, DateTime<Utc>, Option<DateTime<Utc>>) = sqlx::query_as(
+            r#"
+            SELECT user_id, expires_at, used_at
+            FROM password_reset_tokens
+            WHERE token_hash = $1
+            FOR UPDATE
+            "#,
+        )
+        .bind(hash_token(&req.token))
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::InvalidToken)?;
+
+        if used_at.is_some() {
+            return Err(AppError::InvalidToken);
+        }
+        if expires_at <= Utc::now() {
+            return Err(AppError::TokenExpired);
+        }
+
+        let user = self.set_password(&mut tx, user_id, &req.new_password).await?;
+
+        sqlx::query("UPDATE password_reset_tokens SET used_at = NOW() WHERE user_id = $1 AND used_at IS NULL")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(user)
+    }
+
+    /// Users matching a search on username or email, a role and whether they are disabled
+    pub async fn list_users(
+        &self,
+        pool: &PgPool,
+        search: Option<String>,
+        role: Option<UserRole>,
+        disabled: Option<bool>,
+    ) -> Result<Vec<UserAccount>> {
+        let users = sqlx::query_as::<_, UserAccount>(&format!(
+            r#"
+            {USER_ACCOUNT_SELECT}
+            WHERE ($1::varchar IS NULL
+                   OR STRPOS(LOWER(u.username), LOWER($1)) > 0
+                   OR STRPOS(LOWER(u.email), LOWER($1)) > 0)
+              AND ($2::varchar IS NULL OR u.role = $2)
+              AND ($3::boolean IS NULL OR (u.disabled_at IS NOT NULL) = $3)
+            ORDER BY u.username
+            "#
+        ))
+        .bind(search.filter(|search| !search.trim().is_empty()).map(|search| search.trim().to_string()))
+        .bind(role.map(|role| role.to_string()))
+        .bind(disabled)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(users)
+    }
+
+    pub async fn get_user_account(&self, pool: &PgPool, user_id: Uuid) -> Result<UserAccount> {
+        sqlx::query_as::<_, UserAccount>(&format!("{USER_ACCOUNT_SELECT} WHERE u.id = $1"))
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))
+    }
+
+    /// Create an account with the given role. The user gets an email to choose their password;
+    /// until then nobody can log in with it.
+    pub async fn invite_user(&self, pool: &PgPool, req: InviteUserRequest) -> Result<UserAccount> {
+        req.validate()?;
+
+        let password_hash = self.hash_password(&generate_opaque_token())?;
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            INSERT INTO users (id, username, email, password_hash, role, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, NOW(), NOW())
+            RETURNING id, username, email, password_hash, role, password_changed_at, disabled_at, created_at, updated_at
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(&req.username)
+        .bind(&req.email)
+        .bind(&password_hash)
+        .bind(req.role.to_string())
+        .fetch_one(pool)
+        .await?;
+
+        self.send_password_token(
+            pool,
+            &user,
+            self.invitation_expiry,
+            "You're invited to LedgerForge",
+            &format!("An admin created a LedgerForge {} account for you with the username {}.", user.role, user.username),
+        )
+        .await?;
+
+        self.get_user_account(pool, user.id).await
+    }
+
+    /// Change a user's role. Takes effect on the user's next request.
+    pub async fn set_user_role(&self, pool: &PgPool, user_id: Uuid, role: UserRole) -> Result<UserAccount> {
+        let mut tx = pool.begin().await?;
+
+        if role != UserRole::Admin {
+            self.ensure_other_active_admin(&mut tx, user_id).await?;
+        }
+
+        let updated = sqlx::query("UPDATE users SET role = $2, updated_at = NOW() WHERE id = $1")
+            .bind(user_id)
+            .bind(role.to_string())
+            .execute(&mut *tx)
+            .await?;
+        if updated.rows_affected() == 0 {
+            return Err(AppError::NotFound("User not found".to_string()));
+        }
+
+        tx.commit().await?;
+
+        self.get_user_account(pool, user_id).await
+    }
+
+    /// Disable or re-enable an account. Disabling logs the user out everywhere.
+    pub async fn set_user_disabled(&self, pool: &PgPool, actor_id: Uuid, user_id: Uuid, disabled: bool) -> Result<UserAccount> {
+        let mut tx = pool.begin().await?;
+
+        if disabled {
+            if actor_id == user_id {
+                return Err(AppError::Conflict("You can't disable your own account".to_string()));
+            }
+            self.ensure_other_active_admin(&mut tx, user_id).await?;
+        }
+
+        let updated = sqlx::query(
+            r#"
+            UPDATE users
+            SET disabled_at = CASE WHEN $2 THEN COALESCE(disabled_at, NOW()) END, updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(user_id)
+        .bind(disabled)
+        .execute(&mut *tx)
+        .await?;
+        if updated.rows_affected() == 0 {
+            return Err(AppError::NotFound("User not found".to_string()));
+        }
+
+        if disabled {
+            sqlx::query("UPDATE refresh_tokens SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL")
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        self.get_user_account(pool, user_id).await
+    }
+
+    /// Make a user choose a new password: the current one stops working, every session is
+    /// logged out and the user gets a reset email
+    pub async fn force_password_reset(&self, pool: &PgPool, user_id: Uuid) -> Result<()> {
+        let password_hash = self.hash_password(&generate_opaque_token())?;
+
+        let mut tx = pool.begin().await?;
+        let user = self.replace_password_hash(&mut tx, user_id, &password_hash).await?;
+        tx.commit().await?;
+
+        self.send_password_token(
+            pool,
+            &user,
+            self.password_reset_expiry,
+            "Choose a new password",
+            "An admin reset the password of your account, so you need to choose a new one.",
+        )
+        .await
+    }
+
+    /// Refuse to demote or disable the last active admin. The admins are locked so two such
+    /// changes can't both pass.
+    async fn ensure_other_active_admin(&self, conn: &mut PgConnection, user_id: Uuid) -> Result<()> {
+        let admins: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT id FROM users WHERE role = 'admin' AND disabled_at IS NULL FOR UPDATE",
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        if admins == [user_id] {
+            return Err(AppError::Conflict("At least one active admin is needed".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Start two-factor enrollment with a new secret. Nothing changes for the user until
+    /// the secret is confirmed; starting again replaces an unconfirmed secret.
+    pub async fn begin_mfa_enrollment(&self, pool: &PgPool, user: &User) -> Result<MfaSetupResponse> {
+        let secret = totp::generate_secret();
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO totp_credentials (user_id, secret)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id) DO UPDATE
+            SET secret = EXCLUDED.secret, last_used_step = NULL, created_at = NOW()
+            WHERE totp_credentials.confirmed_at IS NULL
+            "#,
+        )
+        .bind(user.id)
+        .bind(&secret)
+        .execute(pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::Conflict("Two-factor authentication is already enabled".to_string()));
+        }
+
+        Ok(MfaSetupResponse {
+            provisioning_uri: totp::provisioning_uri(&secret, MFA_ISSUER, &user.username),
+            secret,
+        })
+    }
+
+    /// Turn two-factor authentication on with a code from the new secret; returns the recovery codes
+    pub async fn confirm_mfa_enrollment(&self, pool: &PgPool, user_id: Uuid, req: MfaCodeRequest) -> Result<Vec<String>> {
+        req.validate()?;
+
+        let mut tx = pool.begin().await?;
+
+        let pending: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM totp_credentials WHERE user_id = $1 AND confirmed_at IS NULL)",
+        )
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if !pending {
+            return Err(AppError::ValidationError(
+                "Start two-factor setup first, or it is already enabled".to_string(),
+            ));
+        }
+
+        self.use_totp_code(&mut tx, user_id, &normalize_code(&req.code), false).await?;
+
+        sqlx::query("UPDATE totp_credentials SET confirmed_at = NOW() WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        let recovery_codes = self.replace_recovery_codes(&mut tx, user_id).await?;
+
+        tx.commit().await?;
+
+        Ok(recovery_codes)
+    }
+
+    /// Replace the recovery codes after checking a second factor
+    pub async fn regenerate_recovery_codes(&self, pool: &PgPool, user_id: Uuid, req: MfaCodeRequest) -> Result<Vec<String>> {
+        req.validate()?;
+
+        if !self.mfa_enabled(pool, user_id).await? {
+            return Err(AppError::ValidationError("Two-factor authentication isn't enabled".to_string()));
+        }
+
+        let mut tx = pool.begin().await?;
+        self.verify_second_factor(&mut tx, user_id, &req.code).await?;
+        let recovery_codes = self.replace_recovery_codes(&mut tx, user_id).await?;
+        tx.commit().await?;
+
+        Ok(recovery_codes)
+    }
+
+    /// Turn two-factor authentication off, unless the user's role requires it
+    pub async fn disable_mfa(&self, pool: &PgPool, user: &User, req: DisableMfaRequest) -> Result<()> {
+        req.validate()?;
+
+        if self.mfa_required_for(pool, &user.role).await? {
+            return Err(AppError::Forbidden(format!(
+                "Two-factor authentication is required for the {} role", user.role
+            )));
+        }
+        if !self.verify_password(&req.password, &user.password_hash)? {
+            return Err(AppError::InvalidCredentials);
+        }
+        if !self.mfa_enabled(pool, user.id).await? {
+            return Err(AppError::ValidationError("Two-factor authentication isn't enabled".to_string()));
+        }
+
+        let mut tx = pool.begin().await?;
+        self.verify_second_factor(&mut tx, user.id, &req.code).await?;
+
+        sqlx::query("DELETE FROM totp_credentials WHERE user_id = $1")
+            .bind(user.id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM mfa_recovery_codes WHERE user_id = $1")
+            .bind(user.id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn mfa_status(&self, pool: &PgPool, user: &User) -> Result<MfaStatusResponse> {
+        let recovery_codes_left: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM mfa_recovery_codes WHERE user_id = $1 AND used_at IS NULL",
+        )
+        .bind(user.id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(MfaStatusResponse {
+            enabled: self.mfa_enabled(pool, user.id).await?,
+            required: self.mfa_required_for(pool, &user.role).await?,
+            recovery_codes_left,
+        })
+    }
+
+    /// Whether the user has to set up two-factor authentication before doing anything else
+    pub async fn mfa_setup_required(&self, pool: &PgPool, user: &User) -> Result<bool> {
+        Ok(self.mfa_required_for(pool, &user.role).await? && !self.mfa_enabled(pool, user.id).await?)
+    }
+
+    pub async fn mfa_policy(&self, pool: &PgPool) -> Result<MfaPolicy> {
+        let required_roles = sqlx::query_scalar::<_, UserRole>("SELECT role FROM mfa_required_roles ORDER BY role")
+            .fetch_all(pool)
+            .await?;
+
+        Ok(MfaPolicy { required_roles })
+    }
+
+    /// Set the roles that must use two-factor authentication. Users of those roles without it
+    /// can only set it up until they do.
+    pub async fn set_mfa_policy(&self, pool: &PgPool, policy: MfaPolicy) -> Result<MfaPolicy> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM mfa_required_roles")
+            .execute(&mut *tx)
+            .await?;
+        for role in &policy.required_roles {
+            sqlx::query("INSERT INTO mfa_required_roles (role) VALUES ($1) ON CONFLICT (role) DO NOTHING")
+                .bind(role.to_string())
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        self.mfa_policy(pool).await
+    }
+
+    async fn mfa_enabled(&self, pool: &PgPool, user_id: Uuid) -> Result<bool> {
+        Ok(sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM totp_credentials WHERE user_id = $1 AND confirmed_at IS NOT NULL)",
+        )
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?)
+    }
+
+    async fn mfa_required_for(&self, pool: &PgPool, role: &UserRole) -> Result<bool> {
+        Ok(sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM mfa_required_roles WHERE role = $1)")
+            .bind(role.to_string())
+            .fetch_one(pool)
+            .await?)
+    }
+
+    /// Check a TOTP code or use up a recovery code of a user with two-factor authentication
+    async fn verify_second_factor(&self, conn: &mut PgConnection, user_id: Uuid, code: &str) -> Result<()> {
+        let code = normalize_code(code);
+        if totp::is_totp_code(&code) {
+            return self.use_totp_code(conn, user_id, &code, true).await;
+        }
+
+        let used = sqlx::query(
+            "UPDATE mfa_recovery_codes SET used_at = NOW() WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL",
+        )
+        .bind(user_id)
+        .bind(hash_token(&code))
+        .execute(&mut *conn)
+        .await?;
+
+        if used.rows_affected() == 0 {
+            return Err(AppError::Unauthorized("Invalid two-factor code".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Check a TOTP code against the user's confirmed or pending secret. The code's time step
+    /// is remembered so the same code can't be used twice.
+    async fn use_totp_code(&self, conn: &mut PgConnection, user_id: Uuid, code: &str, confirmed: bool) -> Result<()> {
+        let invalid = || AppError::Unauthorized("Invalid two-factor code".to_string());
+
+        let (secret, last_used_step): (String, Option<i64>) = sqlx::query_as(
+            r#"
+            SELECT secret, last_used_step
+            FROM totp_credentials
+            WHERE user_id = $1 AND (confirmed_at IS NOT NULL) = $2
+            FOR UPDATE
+            "#,
+        )
+        .bind(user_id)
+        .bind(confirmed)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(invalid)?;
+
+        let step = totp::matching_step(&secret, code, Utc::now())?.ok_or_else(invalid)?;
+        if last_used_step.is_some_and(|last| step <= last) {
+            return Err(invalid());
+        }
+
+        sqlx::query("UPDATE totp_credentials SET last_used_step = $2 WHERE user_id = $1")
+            .bind(user_id)
+            .bind(step)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Replace a user's recovery codes with a fresh set, returning them in plain text
+    async fn replace_recovery_codes(&self, conn: &mut PgConnection, user_id: Uuid) -> Result<Vec<String>> {
+        sqlx::query("DELETE FROM mfa_recovery_codes WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *conn)
+            .await?;
+
+        let codes: Vec<String> = (0..RECOVERY_CODE_COUNT).map(|_| generate_recovery_code()).collect();
+        for code in &codes {
+            sqlx::query("INSERT INTO mfa_recovery_codes (user_id, code_hash) VALUES ($1, $2)")
+                .bind(user_id)
+                .bind(hash_token(&normalize_code(code)))
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        Ok(codes)
+    }
+
+    /// Check a new password against the policy and store it
+    async fn set_password(&self, conn: &mut PgConnection, user_id: Uuid, new_password: &str) -> Result<User> {
+        self.password_policy.check(new_password)?;
+        let password_hash = self.hash_password(new_password)?;
+        self.replace_password_hash(conn, user_id, &password_hash).await
+    }
+
+    /// Store a new password hash and revoke every session of the user
+    async fn replace_password_hash(&self, conn: &mut PgConnection, user_id: Uuid, password_hash: &str) -> Result<User> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET password_hash = $2, password_changed_at = NOW(), updated_at = NOW()
+            WHERE id = $1
+            RETURNING id, username, email, password_hash, role, password_changed_at, disabled_at, created_at, updated_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(password_hash)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        sqlx::query("UPDATE refresh_tokens SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL")
+            .bind(user_id)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(user)
+    }
 }
 
 // Helper trait for pipeline operations
@@ -1,92 +1,205 @@
-use sqlx::PgPool;
+use chrono::Utc;
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 use validator::Validate;
 use rust_decimal::Decimal;
 
 use crate::models::{
-    Bill, BillLineItem, BillStatus, CreateBillRequest, BillWithLineItems
+    Bill, BillLineItem, BillStatus, CreateBillRequest, BillWithLineItems, TaxSummary,
+    CreateTransactionRequest, CreateLineItemRequest, JournalType, SequenceDocumentType, DimensionLine,
 };
 use crate::utils::{AppError, Result};
-use crate::services::CacheService;
+use crate::services::{
+    CacheService, CompanyRecord, CompanyService, DimensionService, InventoryService, NumberingService,
+    PurchaseOrderService, TaxService, TransactionService,
+};
 
 #[derive(Clone)]
 pub struct BillService {
     cache: CacheService,
+    company_id: Option<Uuid>,
 }
 
 impl BillService {
     pub fn new_with_cache(cache: CacheService) -> Self {
-        Self { cache }
+        Self { cache, company_id: None }
+    }
+
+    /// Scope the service to a company's records
+    pub fn for_company(&self, company_id: Option<Uuid>) -> Self {
+        Self { company_id, ..self.clone() }
     }
 
     /// Create a new bill with line items
     pub async fn create_bill(&self, pool: &PgPool, req: CreateBillRequest) -> Result<Bill> {
         // Validate request
         req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+        let company_id = CompanyService::record_company(self.company_id, req.company_id)?;
 
         // Start a transaction for atomic bill creation
         let mut tx = pool.begin().await?;
 
-        // Calculate total amount from line items
-        let total_amount: Decimal = req.line_items.iter()
+        // Everything the bill refers to must belong to its company
+        let mut contact_ids = vec![req.vendor_id];
+        contact_ids.extend(req.line_items.iter().filter_map(|l| l.customer_id));
+        let mut account_ids: Vec<Uuid> = req.line_items.iter().map(|l| l.expense_account_id).collect();
+        account_ids.extend(req.ap_account_id);
+        let item_ids: Vec<Uuid> = req.line_items.iter().filter_map(|l| l.item_id).collect();
+        CompanyService::ensure_in_company(&mut tx, company_id, CompanyRecord::Contact, &contact_ids).await?;
+        CompanyService::ensure_in_company(&mut tx, company_id, CompanyRecord::Account, &account_ids).await?;
+        CompanyService::ensure_in_company(&mut tx, company_id, CompanyRecord::Item, &item_ids).await?;
+
+        // Bills count towards the tax return for their date
+        TaxService::ensure_period_open(&mut tx, company_id, req.bill_date).await?;
+
+        // Calculate subtotal and tax from line items
+        let subtotal: Decimal = req.line_items.iter()
             .map(|item| item.amount)
             .sum();
+        let mut line_taxes = Vec::with_capacity(req.line_items.len());
+        for line_item in &req.line_items {
+            let taxes = match &line_item.tax_code {
+                Some(tax_code) => {
+                    TaxService::calculate_tax(&mut tx, company_id, tax_code, line_item.amount, req.bill_date).await?
+                }
+                None => Vec::new(),
+            };
+            line_taxes.push(taxes);
+        }
+        let tax_amount: Decimal = line_taxes.iter().flatten().map(|t| t.tax_amount).sum();
+        let total_amount = subtotal + tax_amount;
+
+        let bill_number = NumberingService::assign_number(
+            &mut tx, company_id, SequenceDocumentType::Bill, req.bill_date, req.bill_number.clone(),
+        ).await?;
 
         // Create bill record
-        let bill = sqlx::query_as::<_, Bill>(
+        let mut bill = sqlx::query_as::<_, Bill>(
             r#"
             INSERT INTO bills
-                (id, bill_number, vendor_id, bill_date, due_date, total_amount, balance,
-                 status, memo, company_id, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, NOW(), NOW())
+                (id, bill_number, vendor_id, bill_date, due_date, subtotal, tax_amount, total_amount,
+                 balance, status, memo, company_id, ap_account_id, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, NOW(), NOW())
             RETURNING id, quickbooks_id, bill_number, vendor_id, bill_date, due_date,
                      total_amount, balance, status, memo, company_id, transaction_id,
-                     created_by, created_at, updated_at
+                     created_by, created_at, updated_at, subtotal, tax_amount, ap_account_id
             "#,
         )
         .bind(Uuid::new_v4())
-        .bind(&req.bill_number)
+        .bind(&bill_number)
         .bind(req.vendor_id)
         .bind(req.bill_date)
         .bind(req.due_date)
+        .bind(subtotal)
+        .bind(tax_amount)
         .bind(total_amount)
         .bind(total_amount) // Initial balance equals total
         .bind(BillStatus::Open.to_string())
         .bind(&req.memo)
-        .bind(req.company_id)
+        .bind(company_id)
+        .bind(req.ap_account_id)
         .fetch_one(&mut *tx)
         .await
         .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
         // Create line items
-        for line_item in req.line_items {
+        let mut stock_received = false;
+        for (line_item, taxes) in req.line_items.into_iter().zip(line_taxes) {
+            let line_item_id = Uuid::new_v4();
+            let line_tax: Decimal = taxes.iter().map(|t| t.tax_amount).sum();
+
+            // Inventory is bought into the item's asset account, whatever account the line names
+            let expense_account_id = match line_item.item_id {
+                Some(item_id) => InventoryService::purchase_account(&mut tx, item_id)
+                    .await?
+                    .unwrap_or(line_item.expense_account_id),
+                None => line_item.expense_account_id,
+            };
+
             sqlx::query(
                 r#"
                 INSERT INTO bill_line_items
                     (id, bill_id, line_number, description, amount, expense_account_id,
-                     billable, customer_id, created_at, updated_at)
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW(), NOW())
+                     billable, customer_id, item_id, quantity, tax_code, tax_amount,
+                     created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, NOW(), NOW())
                 "#,
             )
-            .bind(Uuid::new_v4())
+            .bind(line_item_id)
             .bind(bill.id)
             .bind(line_item.line_number)
             .bind(&line_item.description)
             .bind(line_item.amount)
-            .bind(line_item.expense_account_id)
+            .bind(expense_account_id)
             .bind(line_item.billable)
             .bind(line_item.customer_id)
+            .bind(line_item.item_id)
+            .bind(line_item.quantity)
+            .bind(&line_item.tax_code)
+            .bind(line_tax)
             .execute(&mut *tx)
             .await
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+            DimensionService::tag_line(
+                &mut tx,
+                company_id,
+                DimensionLine::Bill,
+                line_item_id,
+                Some(expense_account_id),
+                &line_item.dimension_value_ids,
+            )
+            .await?;
+
+            // Record each component tax paid on the line
+            for tax in &taxes {
+                sqlx::query(
+                    r#"
+                    INSERT INTO bill_line_taxes
+                        (id, bill_id, bill_line_item_id, tax_rate_id, tax_name, rate,
+                         taxable_amount, tax_amount, is_recoverable, created_at)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, NOW())
+                    "#,
+                )
+                .bind(Uuid::new_v4())
+                .bind(bill.id)
+                .bind(line_item_id)
+                .bind(tax.tax_rate_id)
+                .bind(&tax.tax_name)
+                .bind(tax.rate)
+                .bind(tax.taxable_amount)
+                .bind(tax.tax_amount)
+                .bind(tax.is_recoverable)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            // Inventory lines add a cost layer
+            if let Some(item_id) = line_item.item_id {
+                let quantity = line_item.quantity.unwrap_or(Decimal::ZERO);
+                let movement = InventoryService::record_purchase(
+                    &mut tx, item_id, quantity, line_item.amount, req.bill_date, bill.id, None,
+                )
+                .await?;
+                stock_received |= movement.is_some();
+            }
         }
 
+        // Post expenses, input tax credits and payables to the ledger
+        bill.transaction_id = Self::post_bill(&mut tx, &bill).await?;
+
         // Commit transaction
         tx.commit().await.map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
         // Invalidate relevant cache entries
         let _ = self.cache.delete_pattern(&format!("vendor:*")).await;
         let _ = self.cache.delete_pattern(&format!("bill:*")).await;
+        if stock_received {
+            let _ = self.cache.delete_pattern("item:*").await;
+        }
+        if bill.transaction_id.is_some() {
+            let _ = self.cache.invalidate_all_account_balances().await;
+        }
 
         Ok(bill)
     }
@@ -97,14 +210,15 @@ impl BillService {
 
         // Try cache first
         if let Ok(Some(cached_bill)) = self.cache.get::<BillWithLineItems>(&cache_key).await {
-            return Ok(Some(cached_bill));
+            return Ok(Some(cached_bill).filter(|b| b.bill.company_id == self.company_id));
         }
 
         // Query bill
         let bill = sqlx::query_as::<_, Bill>(
-            "SELECT * FROM bills WHERE id = $1"
+            "SELECT * FROM bills WHERE id = $1 AND company_id IS NOT DISTINCT FROM $2"
         )
         .bind(bill_id)
+        .bind(self.company_id)
         .fetch_optional(pool)
         .await
         .map_err(|e| AppError::DatabaseError(e.to_string()))?;
@@ -119,9 +233,23 @@ impl BillService {
             .await
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
+            let taxes = sqlx::query_as::<_, TaxSummary>(
+                r#"
+                SELECT tax_name, rate, SUM(taxable_amount) as taxable_amount, SUM(tax_amount) as tax_amount
+                FROM bill_line_taxes
+                WHERE bill_id = $1
+                GROUP BY tax_name, rate
+                ORDER BY tax_name, rate
+                "#
+            )
+            .bind(bill_id)
+            .fetch_all(pool)
+            .await?;
+
             let bill_with_items = BillWithLineItems {
                 bill,
                 line_items,
+                taxes,
             };
 
             // Cache the result
@@ -145,6 +273,11 @@ impl BillService {
         let mut query = String::from("SELECT * FROM bills WHERE 1=1");
         let mut params: Vec<String> = Vec::new();
 
+        params.push(match self.company_id {
+            Some(company_id) => format!("company_id = '{}'", company_id),
+            None => "company_id IS NULL".to_string(),
+        });
+
         if vendor_id.is_some() {
             params.push(format!("vendor_id = '{}'", vendor_id.unwrap()));
         }
@@ -170,7 +303,7 @@ impl BillService {
 
     /// Get bills for a specific vendor
     pub async fn get_vendor_bills(&self, pool: &PgPool, vendor_id: Uuid) -> Result<Vec<Bill>> {
-        let cache_key = format!("vendor:bills:{}", vendor_id);
+        let cache_key = format!("vendor:bills:{}:{}", CompanyService::cache_scope(self.company_id), vendor_id);
 
         // Try cache first
         if let Ok(Some(cached_bills)) = self.cache.get::<Vec<Bill>>(&cache_key).await {
@@ -180,11 +313,12 @@ impl BillService {
         let bills = sqlx::query_as::<_, Bill>(
             r#"
             SELECT * FROM bills
-            WHERE vendor_id = $1
+            WHERE vendor_id = $1 AND company_id IS NOT DISTINCT FROM $2
             ORDER BY bill_date DESC, created_at DESC
             "#
         )
         .bind(vendor_id)
+        .bind(self.company_id)
         .fetch_all(pool)
         .await
         .map_err(|e| AppError::DatabaseError(e.to_string()))?;
@@ -195,13 +329,29 @@ impl BillService {
         Ok(bills)
     }
 
-    /// Update bill status
+    /// Update bill status. Voiding a posted bill reverses its ledger entry.
     pub async fn update_bill_status(
         &self,
         pool: &PgPool,
         bill_id: Uuid,
         new_status: BillStatus,
     ) -> Result<Bill> {
+        let mut tx = pool.begin().await?;
+
+        let current = sqlx::query_as::<_, Bill>(
+            "SELECT * FROM bills WHERE id = $1 AND company_id IS NOT DISTINCT FROM $2 FOR UPDATE"
+        )
+        .bind(bill_id)
+        .bind(self.company_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Bill not found".to_string()))?;
+
+        let voiding = new_status == BillStatus::Void && current.status != BillStatus::Void;
+        if voiding {
+            TaxService::ensure_period_open(&mut tx, current.company_id, current.bill_date).await?;
+        }
+
         let bill = sqlx::query_as::<_, Bill>(
             r#"
             UPDATE bills
@@ -209,27 +359,60 @@ impl BillService {
             WHERE id = $2
             RETURNING id, quickbooks_id, bill_number, vendor_id, bill_date, due_date,
                      total_amount, balance, status, memo, company_id, transaction_id,
-                     created_by, created_at, updated_at
+                     created_by, created_at, updated_at, subtotal, tax_amount, ap_account_id
             "#
         )
         .bind(new_status.to_string())
         .bind(bill_id)
-        .fetch_one(pool)
+        .fetch_one(&mut *tx)
         .await
         .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
+        let reversed = match bill.transaction_id {
+            Some(transaction_id) if voiding => {
+                TransactionService::reverse_entry(&mut tx, transaction_id, Utc::now().date_naive(), None).await?;
+                true
+            }
+            _ => false,
+        };
+
+        // A voided bill no longer counts against its purchase orders
+        let orders_released = voiding && PurchaseOrderService::release_bill(&mut tx, bill_id).await?;
+
+        tx.commit().await?;
+
         // Invalidate cache
         let _ = self.cache.delete_pattern(&format!("bill:*")).await;
-        let _ = self.cache.delete_pattern(&format!("vendor:bills:{}", bill.vendor_id)).await;
+        if orders_released {
+            let _ = self.cache.delete_pattern("purchase_order:*").await;
+        }
+        let _ = self.cache.delete_pattern(&format!("vendor:bills:*:{}", bill.vendor_id)).await;
+        if reversed {
+            let _ = self.cache.invalidate_all_account_balances().await;
+        }
 
         Ok(bill)
     }
 
-    /// Delete a bill (only if no payments have been applied)
+    /// Delete a bill (only if no payments or vendor credits have been applied)
     pub async fn delete_bill(&self, pool: &PgPool, bill_id: Uuid) -> Result<()> {
-        // Check if bill has any payments
+        // Get the bill before deletion for cache invalidation and the ledger
+        let bill = sqlx::query_as::<_, Bill>(
+            "SELECT * FROM bills WHERE id = $1 AND company_id IS NOT DISTINCT FROM $2"
+        )
+        .bind(bill_id)
+        .bind(self.company_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Bill not found".to_string()))?;
+
+        // Check if bill has any payments or vendor credits
         let payment_count: i64 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM bill_payment_applications WHERE bill_id = $1"
+            r#"
+            SELECT (SELECT COUNT(*) FROM bill_payment_applications WHERE bill_id = $1)
+                 + (SELECT COUNT(*) FROM vendor_credit_applications WHERE bill_id = $1)
+            "#
         )
         .bind(bill_id)
         .fetch_one(pool)
@@ -238,31 +421,51 @@ impl BillService {
 
         if payment_count > 0 {
             return Err(AppError::ValidationError(
-                "Cannot delete bill with payments applied".to_string()
+                "Cannot delete bill with payments or vendor credits applied".to_string()
             ));
         }
 
-        // Get vendor_id before deletion for cache invalidation
-        let vendor_id: Option<Uuid> = sqlx::query_scalar(
-            "SELECT vendor_id FROM bills WHERE id = $1"
-        )
-        .bind(bill_id)
-        .fetch_optional(pool)
-        .await
-        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        let mut tx = pool.begin().await?;
+
+        // Reverse the ledger posting, if any
+        let mut reversed = false;
+        TaxService::ensure_period_open(&mut tx, bill.company_id, bill.bill_date).await?;
+        if let Some(transaction_id) = bill.transaction_id.filter(|_| bill.status != BillStatus::Void) {
+            sqlx::query("UPDATE bills SET transaction_id = NULL WHERE id = $1")
+                .bind(bill_id)
+                .execute(&mut *tx)
+                .await?;
+            TransactionService::reverse_entry(&mut tx, transaction_id, Utc::now().date_naive(), None).await?;
+            reversed = true;
+        }
+
+        // Remove any inventory received on the bill
+        let removed_layers = InventoryService::remove_purchases(&mut tx, bill_id).await?;
+
+        // Reopen the quantities billed against purchase orders
+        let orders_released = PurchaseOrderService::release_bill(&mut tx, bill_id).await?;
 
         // Delete bill (line items will be cascade deleted)
         sqlx::query("DELETE FROM bills WHERE id = $1")
             .bind(bill_id)
-            .execute(pool)
+            .execute(&mut *tx)
             .await
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
+        tx.commit().await.map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
         // Invalidate cache
         let _ = self.cache.delete_pattern(&format!("bill:*")).await;
-        if let Some(vid) = vendor_id {
-            let _ = self.cache.delete_pattern(&format!("vendor:bills:{}", vid)).await;
+        if removed_layers > 0 {
+            let _ = self.cache.delete_pattern("item:*").await;
+        }
+        if orders_released {
+            let _ = self.cache.delete_pattern("purchase_order:*").await;
+        }
+        if reversed {
+            let _ = self.cache.invalidate_all_account_balances().await;
         }
+        let _ = self.cache.delete_pattern(&format!("vendor:bills:*:{}", bill.vendor_id)).await;
 
         Ok(())
     }
@@ -274,15 +477,161 @@ impl BillService {
             SELECT * FROM bills
             WHERE status IN ('open', 'partial')
               AND due_date < CURRENT_DATE
+              AND company_id IS NOT DISTINCT FROM $1
             ORDER BY due_date ASC
             "#
         )
+        .bind(self.company_id)
         .fetch_all(pool)
         .await
         .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
         Ok(bills)
     }
+
+    /// Lock the bills a vendor payment or credit is about to be applied to, checking none would
+    /// be over-applied. Bills are locked in id order so concurrent applications queue up
+    /// rather than deadlock.
+    pub async fn lock_for_application(
+        conn: &mut PgConnection,
+        vendor_id: Uuid,
+        applications: &[(Uuid, Decimal)],
+    ) -> Result<()> {
+        let mut totals: Vec<(Uuid, Decimal)> = Vec::new();
+        for (bill_id, amount) in applications {
+            if *amount <= Decimal::ZERO {
+                return Err(AppError::ValidationError("Applied amounts must be positive".to_string()));
+            }
+            match totals.iter_mut().find(|(id, _)| id == bill_id) {
+                Some((_, total)) => *total += *amount,
+                None => totals.push((*bill_id, *amount)),
+            }
+        }
+        let bill_ids: Vec<Uuid> = totals.iter().map(|(id, _)| *id).collect();
+
+        let bills = sqlx::query_as::<_, Bill>(
+            "SELECT * FROM bills WHERE id = ANY($1) ORDER BY id FOR UPDATE"
+        )
+        .bind(&bill_ids)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        for (bill_id, amount) in totals {
+            let bill = bills.iter()
+                .find(|b| b.id == bill_id && b.vendor_id == vendor_id)
+                .ok_or_else(|| AppError::ValidationError(
+                    format!("Bill {} does not exist or does not belong to vendor", bill_id)
+                ))?;
+            if bill.status == BillStatus::Void {
+                return Err(AppError::ValidationError(format!("Bill {} is void", bill_id)));
+            }
+            if amount > bill.balance {
+                return Err(AppError::ValidationError(
+                    format!("Applied amount exceeds the balance of bill {}", bill_id)
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Recompute a bill's balance and status from everything applied to it
+    pub async fn recalculate_balance(conn: &mut PgConnection, bill_id: Uuid) -> Result<Bill> {
+        let bill = sqlx::query_as::<_, Bill>(
+            r#"
+            UPDATE bills
+            SET balance = total_amount - bill_applied_amount(id),
+                status = bill_status_for_balance(status, total_amount - bill_applied_amount(id), total_amount),
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#
+        )
+        .bind(bill_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(bill)
+    }
+
+    /// Post the bill to the ledger: Dr expenses and input tax credits, Cr accounts
+    /// payable. Non-recoverable tax is part of the expense. Bills without an AP
+    /// account are not posted.
+    async fn post_bill(conn: &mut PgConnection, bill: &Bill) -> Result<Option<Uuid>> {
+        let Some(ap_account_id) = bill.ap_account_id else {
+            return Ok(None);
+        };
+
+        if bill.total_amount.is_zero() {
+            return Ok(None);
+        }
+
+        let expenses: Vec<(Uuid, Decimal, Vec<Uuid>)> = sqlx::query_as(
+            r#"
+            SELECT bli.expense_account_id, bli.amount,
+                   ARRAY(SELECT d.dimension_value_id FROM bill_line_dimensions d WHERE d.bill_line_item_id = bli.id)
+            FROM bill_line_items bli
+            WHERE bli.bill_id = $1
+            ORDER BY bli.line_number
+            "#
+        )
+        .bind(bill.id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        // Tax that can't be recovered is part of the line's cost and carries its dimensions
+        let taxes: Vec<(Uuid, Decimal, Vec<Uuid>)> = sqlx::query_as(
+            r#"
+            SELECT
+                CASE WHEN blt.is_recoverable
+                     THEN COALESCE(tr.recoverable_account_id, tr.liability_account_id)
+                     ELSE bli.expense_account_id
+                END,
+                blt.tax_amount,
+                CASE WHEN blt.is_recoverable
+                     THEN ARRAY[]::uuid[]
+                     ELSE ARRAY(SELECT d.dimension_value_id FROM bill_line_dimensions d WHERE d.bill_line_item_id = bli.id)
+                END
+            FROM bill_line_taxes blt
+            INNER JOIN tax_rates tr ON tr.id = blt.tax_rate_id
+            INNER JOIN bill_line_items bli ON bli.id = blt.bill_line_item_id
+            WHERE blt.bill_id = $1
+            ORDER BY bli.line_number, blt.created_at
+            "#
+        )
+        .bind(bill.id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut amounts = expenses;
+        amounts.extend(taxes);
+        amounts.push((ap_account_id, -bill.total_amount, Vec::new()));
+
+        let reference = bill.bill_number.clone();
+        let description = match &reference {
+            Some(number) => format!("Bill {}", number),
+            None => "Bill".to_string(),
+        };
+        let entry = CreateTransactionRequest {
+            transaction_date: bill.bill_date,
+            description: Some(description.clone()),
+            reference_number: reference,
+            entry_number: None,
+            contact_id: Some(bill.vendor_id),
+            company_id: bill.company_id,
+            journal_type: Some(JournalType::Purchases),
+            line_items: CreateLineItemRequest::from_tagged_amounts(&amounts, &description),
+        };
+        let transaction_id = TransactionService::post_entry(conn, &entry, None).await?;
+
+        sqlx::query("UPDATE bills SET transaction_id = $1 WHERE id = $2")
+            .bind(transaction_id)
+            .bind(bill.id)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(Some(transaction_id))
+    }
 }
 
 impl ToString for BillStatus {
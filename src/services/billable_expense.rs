@@ -0,0 +1,241 @@
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use validator::Validate;
+use rust_decimal::{Decimal, RoundingStrategy};
+
+use crate::models::{
+    Invoice, InvoiceStatus, BillLineItem, UnbilledExpense, BillExpensesRequest, BilledExpenses,
+    CreateInvoiceRequest, CreateInvoiceLineItemRequest, DimensionLine,
+};
+use crate::utils::{AppError, Result};
+use crate::services::{CacheService, CompanyRecord, CompanyService, DimensionService, InvoiceService};
+
+#[derive(Clone)]
+pub struct BillableExpenseService {
+    cache: CacheService,
+    invoice_service: InvoiceService,
+    company_id: Option<Uuid>,
+}
+
+impl BillableExpenseService {
+    pub fn new_with_cache(cache: CacheService) -> Self {
+        Self {
+            invoice_service: InvoiceService::new_with_cache(cache.clone()),
+            cache,
+            company_id: None,
+        }
+    }
+
+    /// Scope the service to a company's records
+    pub fn for_company(&self, company_id: Option<Uuid>) -> Self {
+        Self {
+            cache: self.cache.clone(),
+            invoice_service: self.invoice_service.for_company(company_id),
+            company_id,
+        }
+    }
+
+    /// Billable bill lines for a customer that haven't been invoiced yet
+    pub async fn list_unbilled_expenses(&self, pool: &PgPool, customer_id: Uuid) -> Result<Vec<UnbilledExpense>> {
+        let mut conn = pool.acquire().await?;
+        CompanyService::ensure_in_company(&mut conn, self.company_id, CompanyRecord::Contact, &[customer_id]).await?;
+        Self::unbilled_expenses(&mut conn, customer_id, None).await
+    }
+
+    /// Put billable bill lines on a new or draft invoice for the customer, optionally marked up.
+    /// The bill lines are linked to the invoice lines so they can't be billed twice.
+    pub async fn bill_expenses(
+        &self,
+        pool: &PgPool,
+        customer_id: Uuid,
+        req: BillExpensesRequest,
+    ) -> Result<BilledExpenses> {
+        // Validate request
+        req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+        let markup_percent = req.markup_percent.unwrap_or(Decimal::ZERO);
+        if markup_percent < Decimal::ZERO {
+            return Err(AppError::ValidationError("Markup percent cannot be negative".to_string()));
+        }
+
+        let mut line_ids = req.bill_line_item_ids.clone();
+        line_ids.sort();
+        line_ids.dedup();
+        if line_ids.len() != req.bill_line_item_ids.len() {
+            return Err(AppError::ValidationError("Each bill line can only be billed once".to_string()));
+        }
+
+        let mut tx = pool.begin().await?;
+        CompanyService::ensure_in_company(&mut tx, self.company_id, CompanyRecord::Contact, &[customer_id]).await?;
+
+        // The bill lines stay locked until they're linked, so concurrent requests
+        // can't bill the same expense twice
+        let lines = sqlx::query_as::<_, BillLineItem>(
+            "SELECT * FROM bill_line_items WHERE id = ANY($1) ORDER BY id FOR UPDATE"
+        )
+        .bind(&line_ids)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if lines.len() != line_ids.len() {
+            return Err(AppError::NotFound("Bill line not found".to_string()));
+        }
+        for line in &lines {
+            if line.billable != Some(true) || line.customer_id != Some(customer_id) {
+                return Err(AppError::ValidationError(format!(
+                    "Bill line {} is not billable to this customer", line.id
+                )));
+            }
+        }
+
+        let expenses = Self::unbilled_expenses(&mut tx, customer_id, Some(&line_ids)).await?;
+        if expenses.len() != line_ids.len() {
+            return Err(AppError::ValidationError(
+                "Some of the expenses have already been billed or are on a void bill".to_string()
+            ));
+        }
+
+        // Rebilled lines keep the expense's project, department, ...
+        let dimensions = DimensionService::line_values(&mut tx, DimensionLine::Bill, &line_ids).await?;
+
+        // Keep the order the lines were requested in
+        let expenses: Vec<UnbilledExpense> = req.bill_line_item_ids.iter()
+            .filter_map(|id| expenses.iter().find(|e| e.bill_line_item_id == *id).cloned())
+            .collect();
+
+        let markup = Decimal::ONE + markup_percent / Decimal::ONE_HUNDRED;
+        let invoice_lines: Vec<CreateInvoiceLineItemRequest> = expenses.iter()
+            .enumerate()
+            .map(|(index, expense)| CreateInvoiceLineItemRequest {
+                line_number: index as i32 + 1,
+                item_description: expense.description.clone().unwrap_or_else(|| match &expense.bill_number {
+                    Some(number) => format!("{} - bill {}", expense.vendor_name, number),
+                    None => expense.vendor_name.clone(),
+                }),
+                quantity: Decimal::ONE,
+                unit_price: (expense.amount * markup)
+                    .round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero),
+                discount_percent: None,
+                tax_code: None,
+                revenue_account_id: req.revenue_account_id.unwrap_or(expense.expense_account_id),
+                item_id: None,
+                dimension_value_ids: dimensions.get(&expense.bill_line_item_id).cloned().unwrap_or_default(),
+            })
+            .collect();
+        let expense_total: Decimal = expenses.iter().map(|e| e.amount).sum();
+        let billed_total: Decimal = invoice_lines.iter().map(|l| l.unit_price).sum();
+
+        let (invoice_id, created_lines) = match req.invoice_id {
+            Some(invoice_id) => {
+                let invoice = sqlx::query_as::<_, Invoice>(
+                    "SELECT * FROM invoices WHERE id = $1 AND company_id IS NOT DISTINCT FROM $2 FOR UPDATE"
+                )
+                .bind(invoice_id)
+                .bind(self.company_id)
+                .fetch_optional(&mut *tx)
+                .await?
+                .ok_or_else(|| AppError::NotFound(format!("Invoice with id {} not found", invoice_id)))?;
+                if invoice.customer_id != customer_id {
+                    return Err(AppError::ValidationError(format!(
+                        "Invoice {} belongs to a different customer", invoice.invoice_number
+                    )));
+                }
+                if invoice.status != InvoiceStatus::Draft {
+                    return Err(AppError::ValidationError(
+                        "Expenses can only be added to a draft invoice".to_string()
+                    ));
+                }
+
+                let (_, created) = InvoiceService::add_line_items(&mut tx, &invoice, &invoice_lines).await?;
+                (invoice.id, created)
+            }
+            None => {
+                let (Some(invoice_date), Some(due_date)) = (req.invoice_date, req.due_date) else {
+                    return Err(AppError::ValidationError(
+                        "Invoice and due dates are required for a new invoice".to_string()
+                    ));
+                };
+                if due_date < invoice_date {
+                    return Err(AppError::ValidationError(
+                        "Due date cannot be before the invoice date".to_string()
+                    ));
+                }
+
+                let invoice = InvoiceService::insert_invoice(&mut tx, &CreateInvoiceRequest {
+                    invoice_number: req.invoice_number.clone(),
+                    customer_id,
+                    invoice_date,
+                    due_date,
+                    ship_date: None,
+                    customer_memo: None,
+                    billing_address: None,
+                    shipping_address: None,
+                    company_id: self.company_id,
+                    ar_account_id: req.ar_account_id,
+                    line_items: invoice_lines,
+                }).await?;
+                (invoice.invoice.id, invoice.line_items)
+            }
+        };
+
+        for (expense, invoice_line) in expenses.iter().zip(&created_lines) {
+            sqlx::query(
+                "UPDATE bill_line_items SET invoice_line_item_id = $1, updated_at = NOW() WHERE id = $2"
+            )
+            .bind(invoice_line.id)
+            .bind(expense.bill_line_item_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        let _ = self.cache.delete(&format!("invoice:{}", invoice_id)).await;
+
+        let invoice = self.invoice_service
+            .get_invoice(pool, invoice_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Invoice with id {} not found", invoice_id)))?;
+
+        Ok(BilledExpenses {
+            invoice,
+            expense_total,
+            markup_amount: billed_total - expense_total,
+        })
+    }
+
+    /// A line counts as billed while it's linked to a line on an invoice that hasn't been voided
+    async fn unbilled_expenses(
+        conn: &mut PgConnection,
+        customer_id: Uuid,
+        line_ids: Option<&[Uuid]>,
+    ) -> Result<Vec<UnbilledExpense>> {
+        let expenses = sqlx::query_as::<_, UnbilledExpense>(
+            r#"
+            SELECT bli.id AS bill_line_item_id, bli.bill_id, b.bill_number, b.bill_date,
+                   b.vendor_id, v.name AS vendor_name, bli.line_number, bli.description,
+                   bli.amount, bli.expense_account_id
+            FROM bill_line_items bli
+            JOIN bills b ON b.id = bli.bill_id
+            JOIN contacts v ON v.id = b.vendor_id
+            WHERE bli.billable
+              AND bli.customer_id = $1
+              AND b.status <> 'void'
+              AND ($2::uuid[] IS NULL OR bli.id = ANY($2))
+              AND NOT EXISTS (
+                  SELECT 1
+                  FROM invoice_line_items ili
+                  JOIN invoices i ON i.id = ili.invoice_id
+                  WHERE ili.id = bli.invoice_line_item_id AND i.status <> 'void'
+              )
+            ORDER BY b.bill_date, b.bill_number, bli.line_number
+            "#
+        )
+        .bind(customer_id)
+        .bind(line_ids)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(expenses)
+    }
+}
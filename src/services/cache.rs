@@ -138,6 +138,11 @@ impl CacheService {
         self.delete(&key).await
     }
 
+    /// Invalidate every cached account balance (after sub-ledger postings)
+    pub async fn invalidate_all_account_balances(&self) -> Result<()> {
+        self.delete_pattern("account:balance:*").await.map(|_| ())
+    }
+
     /// Invalidate transaction list caches (simple pattern deletion)
     pub async fn invalidate_transaction_lists(&self) -> Result<()> {
         self.delete_pattern("transactions:list:*").await.map(|_| ())
@@ -0,0 +1,316 @@
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{Company, CompanyMember, CreateCompanyRequest, UpdateCompanyRequest, AddCompanyMemberRequest};
+use crate::utils::{AppError, Result};
+use crate::services::CacheService;
+
+/// Records that other records refer to, checked to belong to the same company
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompanyRecord {
+    Account,
+    Contact,
+    Item,
+}
+
+impl CompanyRecord {
+    /// Table and the name used in errors
+    fn table(&self) -> (&'static str, &'static str) {
+        match self {
+            CompanyRecord::Account => ("chart_of_accounts", "Account"),
+            CompanyRecord::Contact => ("contacts", "Contact"),
+            CompanyRecord::Item => ("items", "Item"),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CompanyService {
+    cache: CacheService,
+}
+
+impl CompanyService {
+    pub fn new_with_cache(cache: CacheService) -> Self {
+        Self { cache }
+    }
+
+    /// Create a company; the user creating it becomes its first member
+    pub async fn create_company(&self, pool: &PgPool, user_id: Uuid, req: CreateCompanyRequest) -> Result<Company> {
+        // Validate request
+        req.validate()?;
+
+        let mut tx = pool.begin().await?;
+
+        let company = sqlx::query_as::<_, Company>(
+            r#"
+            INSERT INTO companies (id, name, address, created_at, updated_at)
+            VALUES ($1, $2, $3, NOW(), NOW())
+            RETURNING *
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(&req.name)
+        .bind(&req.address)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query("INSERT INTO company_members (company_id, user_id, created_at) VALUES ($1, $2, NOW())")
+            .bind(company.id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(company)
+    }
+
+    /// Companies the user is a member of
+    pub async fn list_companies(&self, pool: &PgPool, user_id: Uuid) -> Result<Vec<Company>> {
+        let companies = sqlx::query_as::<_, Company>(
+            r#"
+            SELECT c.*
+            FROM companies c
+            INNER JOIN company_members m ON m.company_id = c.id
+            WHERE m.user_id = $1
+            ORDER BY c.name
+            "#
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(companies)
+    }
+
+    /// Get a company the user is a member of
+    pub async fn get_company(&self, pool: &PgPool, user_id: Uuid, id: Uuid) -> Result<Company> {
+        sqlx::query_as::<_, Company>(
+            r#"
+            SELECT c.*
+            FROM companies c
+            INNER JOIN company_members m ON m.company_id = c.id
+            WHERE c.id = $1 AND m.user_id = $2
+            "#
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Company with id {} not found", id)))
+    }
+
+    /// Rename a company or change its address
+    pub async fn update_company(&self, pool: &PgPool, user_id: Uuid, id: Uuid, req: UpdateCompanyRequest) -> Result<Company> {
+        // Validate request
+        req.validate()?;
+
+        self.get_company(pool, user_id, id).await?;
+
+        let company = sqlx::query_as::<_, Company>(
+            r#"
+            UPDATE companies
+            SET name = COALESCE($1, name),
+                address = COALESCE($2, address),
+                updated_at = NOW()
+            WHERE id = $3
+            RETURNING *
+            "#
+        )
+        .bind(&req.name)
+        .bind(&req.address)
+        .bind(id)
+        .fetch_one(pool)
+        .await?;
+
+        // Statements, emails and invoice PDFs print the company name
+        let _ = self.cache.delete_pattern("invoice:*").await;
+
+        Ok(company)
+    }
+
+    /// Delete a company that has no records
+    pub async fn delete_company(&self, pool: &PgPool, user_id: Uuid, id: Uuid) -> Result<()> {
+        self.get_company(pool, user_id, id).await?;
+
+        sqlx::query("DELETE FROM companies WHERE id = $1")
+            .bind(id)
+            .execute(pool)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => AppError::Conflict(
+                    "Cannot delete a company that has records".to_string()
+                ),
+                e => AppError::from(e),
+            })?;
+
+        Ok(())
+    }
+
+    /// Members of a company the user belongs to
+    pub async fn list_members(&self, pool: &PgPool, user_id: Uuid, company_id: Uuid) -> Result<Vec<CompanyMember>> {
+        self.get_company(pool, user_id, company_id).await?;
+
+        let members = sqlx::query_as::<_, CompanyMember>(
+            r#"
+            SELECT m.company_id, m.user_id, u.username, u.email, m.created_at
+            FROM company_members m
+            INNER JOIN users u ON u.id = m.user_id
+            WHERE m.company_id = $1
+            ORDER BY u.username
+            "#
+        )
+        .bind(company_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(members)
+    }
+
+    /// Give another user access to the company
+    pub async fn add_member(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        company_id: Uuid,
+        req: AddCompanyMemberRequest,
+    ) -> Result<CompanyMember> {
+        self.get_company(pool, user_id, company_id).await?;
+
+        sqlx::query_as::<_, CompanyMember>(
+            r#"
+            WITH added AS (
+                INSERT INTO company_members (company_id, user_id, created_at)
+                VALUES ($1, $2, NOW())
+                RETURNING *
+            )
+            SELECT a.company_id, a.user_id, u.username, u.email, a.created_at
+            FROM added a
+            INNER JOIN users u ON u.id = a.user_id
+            "#
+        )
+        .bind(company_id)
+        .bind(req.user_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => AppError::Conflict(
+                "The user is already a member of this company".to_string()
+            ),
+            sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => AppError::NotFound(
+                format!("User with id {} not found", req.user_id)
+            ),
+            e => AppError::from(e),
+        })
+    }
+
+    /// Take a user's access to the company away. The last member can't be removed.
+    pub async fn remove_member(&self, pool: &PgPool, user_id: Uuid, company_id: Uuid, member_id: Uuid) -> Result<()> {
+        self.get_company(pool, user_id, company_id).await?;
+
+        let mut tx = pool.begin().await?;
+
+        // Lock the membership rows so two removals can't both leave the company empty
+        let members: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT user_id FROM company_members WHERE company_id = $1 FOR UPDATE"
+        )
+        .bind(company_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if !members.contains(&member_id) {
+            return Err(AppError::NotFound(format!("User {} is not a member of this company", member_id)));
+        }
+        if members.len() == 1 {
+            return Err(AppError::ValidationError("A company needs at least one member".to_string()));
+        }
+
+        sqlx::query("DELETE FROM company_members WHERE company_id = $1 AND user_id = $2")
+            .bind(company_id)
+            .bind(member_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn is_member(pool: &PgPool, company_id: Uuid, user_id: Uuid) -> Result<bool> {
+        let is_member = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM company_members WHERE company_id = $1 AND user_id = $2)"
+        )
+        .bind(company_id)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(is_member)
+    }
+
+    /// Company a user starts in after logging in: the first one they joined
+    pub async fn default_company(pool: &PgPool, user_id: Uuid) -> Result<Option<Uuid>> {
+        let company_id = sqlx::query_scalar(
+            "SELECT company_id FROM company_members WHERE user_id = $1 ORDER BY created_at, company_id LIMIT 1"
+        )
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(company_id)
+    }
+
+    /// Company a new record belongs to: the active one. A company named in the request
+    /// must match it.
+    pub fn record_company(active: Option<Uuid>, requested: Option<Uuid>) -> Result<Option<Uuid>> {
+        match requested {
+            Some(company_id) if Some(company_id) != active => Err(AppError::ValidationError(
+                format!("Company {} is not the active company", company_id)
+            )),
+            _ => Ok(active),
+        }
+    }
+
+    /// Check that referenced records belong to the company. Records of another company
+    /// are reported as not found.
+    pub async fn ensure_in_company(
+        conn: &mut PgConnection,
+        company_id: Option<Uuid>,
+        record: CompanyRecord,
+        ids: &[Uuid],
+    ) -> Result<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let (table, name) = record.table();
+        let outside: Option<Uuid> = sqlx::query_scalar(&format!(
+            "SELECT id FROM {} WHERE id = ANY($1) AND company_id IS DISTINCT FROM $2 LIMIT 1",
+            table
+        ))
+        .bind(ids)
+        .bind(company_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        match outside {
+            Some(id) => Err(AppError::NotFound(format!("{} with id {} not found", name, id))),
+            None => Ok(()),
+        }
+    }
+
+    /// Every company, plus the records that belong to none, for jobs that run across all of them
+    pub async fn all_scopes(pool: &PgPool) -> Result<Vec<Option<Uuid>>> {
+        let company_ids: Vec<Uuid> = sqlx::query_scalar("SELECT id FROM companies ORDER BY created_at, id")
+            .fetch_all(pool)
+            .await?;
+
+        Ok(std::iter::once(None).chain(company_ids.into_iter().map(Some)).collect())
+    }
+
+    /// Cache key segment separating one company's cached reports from another's
+    pub fn cache_scope(company_id: Option<Uuid>) -> String {
+        company_id.map_or_else(|| "none".to_string(), |id| id.to_string())
+    }
+}
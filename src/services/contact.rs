@@ -4,28 +4,36 @@ use validator::Validate;
 
 use crate::models::{Contact, ContactType, CreateContactRequest, UpdateContactRequest};
 use crate::utils::{AppError, Result};
-use crate::services::CacheService;
+use crate::services::{CacheService, CompanyService};
 
 #[derive(Clone)]
 pub struct ContactService {
     cache: CacheService,
+    company_id: Option<Uuid>,
 }
 
 impl ContactService {
     pub fn new() -> Self {
         Self {
             cache: CacheService::default(),
+            company_id: None,
         }
     }
 
     pub fn new_with_cache(cache: CacheService) -> Self {
-        Self { cache }
+        Self { cache, company_id: None }
+    }
+
+    /// Scope the service to a company's records
+    pub fn for_company(&self, company_id: Option<Uuid>) -> Self {
+        Self { company_id, ..self.clone() }
     }
 
     /// Create a new contact
     pub async fn create_contact(&self, pool: &PgPool, req: CreateContactRequest) -> Result<Contact> {
         // Validate request
         req.validate()?;
+        let company_id = CompanyService::record_company(self.company_id, req.company_id)?;
 
         // Create contact in database
         let contact = sqlx::query_as::<_, Contact>(
@@ -33,7 +41,7 @@ impl ContactService {
             INSERT INTO contacts
                 (id, contact_type, name, email, phone, billing_address, shipping_address, company_id, created_at, updated_at)
             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW(), NOW())
-            RETURNING id, contact_type, name, email, phone, billing_address, shipping_address, company_id, created_at, updated_at
+            RETURNING id, contact_type, name, email, phone, billing_address, shipping_address, company_id, dunning_opt_out, finance_charge_exempt, created_at, updated_at
             "#,
         )
         .bind(Uuid::new_v4())
@@ -43,7 +51,7 @@ impl ContactService {
         .bind(&req.phone)
         .bind(&req.billing_address)
         .bind(&req.shipping_address)
-        .bind(req.company_id)
+        .bind(company_id)
         .fetch_one(pool)
         .await?;
 
@@ -55,55 +63,36 @@ impl ContactService {
         &self,
         pool: &PgPool,
         contact_type: Option<ContactType>,
-        company_id: Option<Uuid>,
         limit: Option<i64>,
     ) -> Result<Vec<Contact>> {
         let mut query = String::from(
             r#"
-            SELECT id, contact_type, name, email, phone, billing_address, shipping_address, company_id, created_at, updated_at
+            SELECT id, contact_type, name, email, phone, billing_address, shipping_address, company_id, dunning_opt_out, finance_charge_exempt, created_at, updated_at
             FROM contacts
-            WHERE 1=1
+            WHERE company_id IS NOT DISTINCT FROM $1
             "#
         );
 
         // Add filters
         if contact_type.is_some() {
-            query.push_str(" AND contact_type = $1");
-        }
-
-        if company_id.is_some() {
-            if contact_type.is_some() {
-                query.push_str(" AND company_id = $2");
-            } else {
-                query.push_str(" AND company_id = $1");
-            }
+            query.push_str(" AND contact_type = $2");
         }
 
         query.push_str(" ORDER BY name ASC");
 
         // Add limit if specified
         if limit.is_some() {
-            let param_num = if contact_type.is_some() && company_id.is_some() {
-                3
-            } else if contact_type.is_some() || company_id.is_some() {
-                2
-            } else {
-                1
-            };
+            let param_num = if contact_type.is_some() { 3 } else { 2 };
             query.push_str(&format!(" LIMIT ${}", param_num));
         }
 
         // Build and execute query dynamically
-        let mut sql_query = sqlx::query_as::<_, Contact>(&query);
+        let mut sql_query = sqlx::query_as::<_, Contact>(&query).bind(self.company_id);
 
         if let Some(ct) = contact_type {
             sql_query = sql_query.bind(ct.to_string());
         }
 
-        if let Some(cid) = company_id {
-            sql_query = sql_query.bind(cid);
-        }
-
         if let Some(lim) = limit {
             sql_query = sql_query.bind(lim);
         }
@@ -120,7 +109,7 @@ impl ContactService {
         if let Ok(Some(cached_json)) = self.cache.get::<String>(&cache_key).await {
             if let Ok(contact) = serde_json::from_str::<Contact>(&cached_json) {
                 tracing::debug!("Cache hit for contact {}", id);
-                return Ok(contact);
+                return self.in_company(contact);
             }
         }
 
@@ -128,7 +117,7 @@ impl ContactService {
         tracing::debug!("Cache miss for contact {}", id);
         let contact = sqlx::query_as::<_, Contact>(
             r#"
-            SELECT id, contact_type, name, email, phone, billing_address, shipping_address, company_id, created_at, updated_at
+            SELECT id, contact_type, name, email, phone, billing_address, shipping_address, company_id, dunning_opt_out, finance_charge_exempt, created_at, updated_at
             FROM contacts
             WHERE id = $1
             "#,
@@ -143,7 +132,7 @@ impl ContactService {
             let _ = self.cache.set_with_ttl(&cache_key, &contact_json, 600).await;
         }
 
-        Ok(contact)
+        self.in_company(contact)
     }
 
     /// Update contact
@@ -191,7 +180,7 @@ impl ContactService {
             UPDATE contacts
             SET {}
             WHERE id = ${}
-            RETURNING id, contact_type, name, email, phone, billing_address, shipping_address, company_id, created_at, updated_at
+            RETURNING id, contact_type, name, email, phone, billing_address, shipping_address, company_id, dunning_opt_out, finance_charge_exempt, created_at, updated_at
             "#,
             updates.join(", "),
             bind_count
@@ -248,7 +237,7 @@ impl ContactService {
 
     /// Get contacts by type
     pub async fn get_contacts_by_type(&self, pool: &PgPool, contact_type: ContactType) -> Result<Vec<Contact>> {
-        self.list_contacts(pool, Some(contact_type), None, None).await
+        self.list_contacts(pool, Some(contact_type), None).await
     }
 
     /// Get customers (convenience method)
@@ -265,6 +254,15 @@ impl ContactService {
     pub async fn get_employees(&self, pool: &PgPool) -> Result<Vec<Contact>> {
         self.get_contacts_by_type(pool, ContactType::Employee).await
     }
+
+    /// Contacts of other companies are reported as not found
+    fn in_company(&self, contact: Contact) -> Result<Contact> {
+        if contact.company_id != self.company_id {
+            return Err(AppError::NotFound(format!("Contact with id {} not found", contact.id)));
+        }
+
+        Ok(contact)
+    }
 }
 
 impl Default for ContactService {
@@ -0,0 +1,658 @@
+use chrono::Utc;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use validator::Validate;
+use rust_decimal::{Decimal, RoundingStrategy};
+
+use crate::models::{
+    CreditMemo, CreditMemoLineItem, CreditMemoStatus, CreditMemoApplication,
+    CreditMemoApplicationRequest, CreditMemoWithLineItems, CreateCreditMemoRequest,
+    CreateCreditMemoLineItemRequest, CustomerRefund, CreateCustomerRefundRequest, Payment,
+    TaxSummary, CreateTransactionRequest, CreateLineItemRequest, JournalType, SequenceDocumentType,
+};
+use crate::utils::{AppError, Result};
+use crate::services::{
+    CacheService, CompanyRecord, CompanyService, InvoiceService, NumberingService, TaxService, TransactionService,
+};
+
+#[derive(Clone)]
+pub struct CreditMemoService {
+    cache: CacheService,
+    company_id: Option<Uuid>,
+}
+
+impl CreditMemoService {
+    pub fn new_with_cache(cache: CacheService) -> Self {
+        Self { cache, company_id: None }
+    }
+
+    /// Scope the service to a company's records
+    pub fn for_company(&self, company_id: Option<Uuid>) -> Self {
+        Self { company_id, ..self.clone() }
+    }
+
+    /// Create a credit memo, post the revenue and tax reversal and apply it to any invoices given
+    pub async fn create_credit_memo(&self, pool: &PgPool, req: CreateCreditMemoRequest) -> Result<CreditMemoWithLineItems> {
+        // Validate request
+        req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+        let company_id = CompanyService::record_company(self.company_id, req.company_id)?;
+
+        let mut tx = pool.begin().await?;
+
+        let mut account_ids: Vec<Uuid> = req.line_items.iter().map(|l| l.revenue_account_id).collect();
+        account_ids.extend(req.ar_account_id);
+        CompanyService::ensure_in_company(&mut tx, company_id, CompanyRecord::Contact, &[req.customer_id]).await?;
+        CompanyService::ensure_in_company(&mut tx, company_id, CompanyRecord::Account, &account_ids).await?;
+
+        // Credit memos reduce the tax return for their date
+        TaxService::ensure_period_open(&mut tx, company_id, req.credit_memo_date).await?;
+
+        // Calculate line amounts and tax
+        let mut subtotal = Decimal::ZERO;
+        let mut line_taxes = Vec::with_capacity(req.line_items.len());
+        for line_item_req in &req.line_items {
+            let line_amount = Self::calculate_line_item_amount(line_item_req);
+            if line_amount <= Decimal::ZERO {
+                return Err(AppError::ValidationError(
+                    format!("Line {} must have a positive amount", line_item_req.line_number)
+                ));
+            }
+            subtotal += line_amount;
+
+            let taxes = match &line_item_req.tax_code {
+                Some(tax_code) => {
+                    TaxService::calculate_tax(&mut tx, company_id, tax_code, line_amount, req.credit_memo_date).await?
+                }
+                None => Vec::new(),
+            };
+            line_taxes.push(taxes);
+        }
+        let tax_amount: Decimal = line_taxes.iter().flatten().map(|t| t.tax_amount).sum();
+        let total_amount = subtotal + tax_amount;
+
+        let credit_memo_number = NumberingService::assign_number(
+            &mut tx, company_id, SequenceDocumentType::CreditMemo, req.credit_memo_date, req.credit_memo_number.clone(),
+        ).await?;
+
+        // Create credit memo header
+        let mut credit_memo = sqlx::query_as::<_, CreditMemo>(
+            r#"
+            INSERT INTO credit_memos
+                (id, credit_memo_number, customer_id, credit_memo_date, invoice_id, subtotal,
+                 tax_amount, total_amount, balance, status, memo, ar_account_id, company_id,
+                 created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, NOW(), NOW())
+            RETURNING *
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(&credit_memo_number)
+        .bind(req.customer_id)
+        .bind(req.credit_memo_date)
+        .bind(req.invoice_id)
+        .bind(subtotal)
+        .bind(tax_amount)
+        .bind(total_amount)
+        .bind(total_amount) // Initially, all of the credit is available
+        .bind(CreditMemoStatus::Open.to_string())
+        .bind(&req.memo)
+        .bind(req.ar_account_id)
+        .bind(company_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => AppError::Conflict(
+                format!("Credit memo number {} already exists", credit_memo_number)
+            ),
+            e => AppError::from(e),
+        })?;
+
+        // Create line items
+        let mut line_items = Vec::new();
+        for (line_item_req, taxes) in req.line_items.iter().zip(line_taxes) {
+            let line_amount = Self::calculate_line_item_amount(line_item_req);
+            let line_tax: Decimal = taxes.iter().map(|t| t.tax_amount).sum();
+
+            let line_item = sqlx::query_as::<_, CreditMemoLineItem>(
+                r#"
+                INSERT INTO credit_memo_line_items
+                    (id, credit_memo_id, line_number, item_description, quantity, unit_price,
+                     amount, tax_code, tax_amount, revenue_account_id, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, NOW(), NOW())
+                RETURNING *
+                "#,
+            )
+            .bind(Uuid::new_v4())
+            .bind(credit_memo.id)
+            .bind(line_item_req.line_number)
+            .bind(&line_item_req.item_description)
+            .bind(line_item_req.quantity)
+            .bind(line_item_req.unit_price)
+            .bind(line_amount)
+            .bind(&line_item_req.tax_code)
+            .bind(line_tax)
+            .bind(line_item_req.revenue_account_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            // Record each component tax reversed on the line
+            for tax in &taxes {
+                sqlx::query(
+                    r#"
+                    INSERT INTO credit_memo_line_taxes
+                        (id, credit_memo_id, credit_memo_line_item_id, tax_rate_id, tax_name, rate,
+                         taxable_amount, tax_amount, created_at)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW())
+                    "#,
+                )
+                .bind(Uuid::new_v4())
+                .bind(credit_memo.id)
+                .bind(line_item.id)
+                .bind(tax.tax_rate_id)
+                .bind(&tax.tax_name)
+                .bind(tax.rate)
+                .bind(tax.taxable_amount)
+                .bind(tax.tax_amount)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            line_items.push(line_item);
+        }
+
+        // Post the reversal: Dr revenue and tax liabilities, Cr accounts receivable
+        credit_memo.transaction_id = Self::post_credit_memo(&mut tx, &credit_memo).await?;
+
+        // Apply to invoices
+        let applications = if req.applications.is_empty() {
+            Vec::new()
+        } else {
+            let (updated, applications) = Self::apply_to_invoices(&mut tx, &credit_memo, &req.applications).await?;
+            credit_memo = updated;
+            applications
+        };
+
+        let taxes = Self::tax_summary(&mut tx, credit_memo.id).await?;
+
+        tx.commit().await?;
+
+        // Invalidate cache
+        self.invalidate_cache(!applications.is_empty(), credit_memo.transaction_id.is_some()).await;
+
+        Ok(CreditMemoWithLineItems {
+            credit_memo,
+            line_items,
+            taxes,
+            applications,
+        })
+    }
+
+    /// Get a credit memo with line items, taxes and applications
+    pub async fn get_credit_memo(&self, pool: &PgPool, credit_memo_id: Uuid) -> Result<CreditMemoWithLineItems> {
+        let cache_key = format!("credit_memo:{}", credit_memo_id);
+        if let Ok(Some(cached)) = self.cache.get::<CreditMemoWithLineItems>(&cache_key).await
+            && cached.credit_memo.company_id == self.company_id
+        {
+            return Ok(cached);
+        }
+
+        let credit_memo = sqlx::query_as::<_, CreditMemo>(
+            "SELECT * FROM credit_memos WHERE id = $1 AND company_id IS NOT DISTINCT FROM $2"
+        )
+        .bind(credit_memo_id)
+        .bind(self.company_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Credit memo with id {} not found", credit_memo_id)))?;
+
+        let line_items = sqlx::query_as::<_, CreditMemoLineItem>(
+            "SELECT * FROM credit_memo_line_items WHERE credit_memo_id = $1 ORDER BY line_number"
+        )
+        .bind(credit_memo_id)
+        .fetch_all(pool)
+        .await?;
+
+        let applications = sqlx::query_as::<_, CreditMemoApplication>(
+            "SELECT * FROM credit_memo_applications WHERE credit_memo_id = $1 ORDER BY created_at"
+        )
+        .bind(credit_memo_id)
+        .fetch_all(pool)
+        .await?;
+
+        let mut conn = pool.acquire().await?;
+        let taxes = Self::tax_summary(&mut conn, credit_memo_id).await?;
+
+        let result = CreditMemoWithLineItems {
+            credit_memo,
+            line_items,
+            taxes,
+            applications,
+        };
+        let _ = self.cache.set_with_ttl(&cache_key, &result, 3600).await;
+
+        Ok(result)
+    }
+
+    /// List credit memos, optionally for one customer
+    pub async fn list_credit_memos(
+        &self,
+        pool: &PgPool,
+        customer_id: Option<Uuid>,
+        status: Option<CreditMemoStatus>,
+    ) -> Result<Vec<CreditMemo>> {
+        let credit_memos = sqlx::query_as::<_, CreditMemo>(
+            r#"
+            SELECT * FROM credit_memos
+            WHERE ($1::uuid IS NULL OR customer_id = $1)
+              AND ($2::varchar IS NULL OR status = $2)
+              AND company_id IS NOT DISTINCT FROM $3
+            ORDER BY credit_memo_date DESC, created_at DESC
+            "#
+        )
+        .bind(customer_id)
+        .bind(status.map(|s| s.to_string()))
+        .bind(self.company_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(credit_memos)
+    }
+
+    /// Apply a credit memo's remaining balance to open invoices
+    pub async fn apply_credit_memo(
+        &self,
+        pool: &PgPool,
+        credit_memo_id: Uuid,
+        applications: Vec<CreditMemoApplicationRequest>,
+    ) -> Result<CreditMemo> {
+        let mut tx = pool.begin().await?;
+
+        let credit_memo = Self::lock_credit_memo(&mut tx, self.company_id, credit_memo_id).await?;
+        let (credit_memo, _) = Self::apply_to_invoices(&mut tx, &credit_memo, &applications).await?;
+
+        tx.commit().await?;
+
+        self.invalidate_cache(true, false).await;
+
+        Ok(credit_memo)
+    }
+
+    /// Void a credit memo that hasn't been applied or refunded, reversing its posting
+    pub async fn void_credit_memo(&self, pool: &PgPool, credit_memo_id: Uuid) -> Result<CreditMemo> {
+        let mut tx = pool.begin().await?;
+
+        let credit_memo = Self::lock_credit_memo(&mut tx, self.company_id, credit_memo_id).await?;
+        if credit_memo.balance != credit_memo.total_amount {
+            return Err(AppError::ValidationError(
+                "Cannot void a credit memo that has been applied or refunded".to_string()
+            ));
+        }
+
+        TaxService::ensure_period_open(&mut tx, credit_memo.company_id, credit_memo.credit_memo_date).await?;
+
+        let voided = sqlx::query_as::<_, CreditMemo>(
+            r#"
+            UPDATE credit_memos
+            SET status = $1, balance = 0, updated_at = NOW()
+            WHERE id = $2
+            RETURNING *
+            "#
+        )
+        .bind(CreditMemoStatus::Void.to_string())
+        .bind(credit_memo_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if let Some(transaction_id) = voided.transaction_id {
+            TransactionService::reverse_entry(&mut tx, transaction_id, Utc::now().date_naive(), None).await?;
+        }
+
+        tx.commit().await?;
+
+        self.invalidate_cache(false, voided.transaction_id.is_some()).await;
+
+        Ok(voided)
+    }
+
+    /// Refund unapplied credit from a credit memo or an overpayment, paid from a bank account.
+    ///
+    /// Posts Dr accounts receivable, Cr bank when an AR account is known.
+    pub async fn create_refund(&self, pool: &PgPool, req: CreateCustomerRefundRequest) -> Result<CustomerRefund> {
+        req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+        if req.amount <= Decimal::ZERO {
+            return Err(AppError::ValidationError("Refund amount must be positive".to_string()));
+        }
+
+        let company_id = CompanyService::record_company(self.company_id, req.company_id)?;
+
+        let mut tx = pool.begin().await?;
+
+        let account_ids: Vec<Uuid> = std::iter::once(req.bank_account_id).chain(req.ar_account_id).collect();
+        CompanyService::ensure_in_company(&mut tx, company_id, CompanyRecord::Contact, &[req.customer_id]).await?;
+        CompanyService::ensure_in_company(&mut tx, company_id, CompanyRecord::Account, &account_ids).await?;
+
+        let ar_account_id = match (req.credit_memo_id, req.payment_id) {
+            (Some(credit_memo_id), None) => {
+                let credit_memo = Self::lock_credit_memo(&mut tx, self.company_id, credit_memo_id).await?;
+                if credit_memo.customer_id != req.customer_id {
+                    return Err(AppError::ValidationError("Credit memo does not belong to customer".to_string()));
+                }
+                if req.amount > credit_memo.balance {
+                    return Err(AppError::ValidationError(
+                        format!("Refund amount exceeds the credit memo's remaining credit of {}", credit_memo.balance)
+                    ));
+                }
+
+                Self::reduce_balance(&mut tx, credit_memo_id, req.amount).await?;
+                req.ar_account_id.or(credit_memo.ar_account_id)
+            }
+            (None, Some(payment_id)) => {
+                let payment = sqlx::query_as::<_, Payment>(
+                    "SELECT * FROM payments WHERE id = $1 AND company_id IS NOT DISTINCT FROM $2 FOR UPDATE"
+                )
+                .bind(payment_id)
+                .bind(company_id)
+                .fetch_optional(&mut *tx)
+                .await?
+                .ok_or_else(|| AppError::NotFound(format!("Payment with id {} not found", payment_id)))?;
+                if payment.customer_id != req.customer_id {
+                    return Err(AppError::ValidationError("Payment does not belong to customer".to_string()));
+                }
+                let unapplied = payment.unapplied_amount.unwrap_or(Decimal::ZERO);
+                if req.amount > unapplied {
+                    return Err(AppError::ValidationError(
+                        format!("Refund amount exceeds the payment's unapplied amount of {}", unapplied)
+                    ));
+                }
+
+                sqlx::query("UPDATE payments SET unapplied_amount = unapplied_amount - $1, updated_at = NOW() WHERE id = $2")
+                    .bind(req.amount)
+                    .bind(payment_id)
+                    .execute(&mut *tx)
+                    .await?;
+                req.ar_account_id
+            }
+            _ => {
+                return Err(AppError::ValidationError(
+                    "Exactly one of credit_memo_id and payment_id is required".to_string()
+                ));
+            }
+        };
+
+        let mut refund = sqlx::query_as::<_, CustomerRefund>(
+            r#"
+            INSERT INTO customer_refunds
+                (id, refund_number, customer_id, refund_date, amount, payment_method, reference_number,
+                 bank_account_id, credit_memo_id, payment_id, memo, company_id, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, NOW(), NOW())
+            RETURNING *
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(&req.refund_number)
+        .bind(req.customer_id)
+        .bind(req.refund_date)
+        .bind(req.amount)
+        .bind(&req.payment_method)
+        .bind(&req.reference_number)
+        .bind(req.bank_account_id)
+        .bind(req.credit_memo_id)
+        .bind(req.payment_id)
+        .bind(&req.memo)
+        .bind(company_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if let Some(ar_account_id) = ar_account_id {
+            let description = match &refund.refund_number {
+                Some(number) => format!("Customer refund {}", number),
+                None => "Customer refund".to_string(),
+            };
+            let entry = CreateTransactionRequest {
+                transaction_date: refund.refund_date,
+                description: Some(description.clone()),
+                reference_number: refund.reference_number.clone(),
+                entry_number: None,
+                contact_id: Some(refund.customer_id),
+                company_id: refund.company_id,
+                journal_type: Some(JournalType::General),
+                line_items: vec![
+                    CreateLineItemRequest::debit(ar_account_id, &description, refund.amount),
+                    CreateLineItemRequest::credit(refund.bank_account_id, &description, refund.amount),
+                ],
+            };
+            let transaction_id = TransactionService::post_entry(&mut tx, &entry, None).await?;
+
+            sqlx::query("UPDATE customer_refunds SET transaction_id = $1 WHERE id = $2")
+                .bind(transaction_id)
+                .bind(refund.id)
+                .execute(&mut *tx)
+                .await?;
+            refund.transaction_id = Some(transaction_id);
+        }
+
+        tx.commit().await?;
+
+        self.invalidate_cache(false, refund.transaction_id.is_some()).await;
+        let _ = self.cache.delete_pattern("payment:*").await;
+        let _ = self.cache.delete_pattern(&format!("customer:payments:*:{}", refund.customer_id)).await;
+
+        Ok(refund)
+    }
+
+    /// List refunds for a customer
+    pub async fn list_customer_refunds(&self, pool: &PgPool, customer_id: Uuid) -> Result<Vec<CustomerRefund>> {
+        let refunds = sqlx::query_as::<_, CustomerRefund>(
+            r#"
+            SELECT * FROM customer_refunds
+            WHERE customer_id = $1 AND company_id IS NOT DISTINCT FROM $2
+            ORDER BY refund_date DESC, created_at DESC
+            "#
+        )
+        .bind(customer_id)
+        .bind(self.company_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(refunds)
+    }
+
+    // Helper methods
+
+    async fn lock_credit_memo(conn: &mut PgConnection, company_id: Option<Uuid>, credit_memo_id: Uuid) -> Result<CreditMemo> {
+        let credit_memo = sqlx::query_as::<_, CreditMemo>(
+            "SELECT * FROM credit_memos WHERE id = $1 AND company_id IS NOT DISTINCT FROM $2 FOR UPDATE"
+        )
+        .bind(credit_memo_id)
+        .bind(company_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Credit memo with id {} not found", credit_memo_id)))?;
+
+        if credit_memo.status == CreditMemoStatus::Void {
+            return Err(AppError::ValidationError("Credit memo is void".to_string()));
+        }
+
+        Ok(credit_memo)
+    }
+
+    /// Apply credit to invoices of the same customer, recalculating invoice balances the
+    /// way payment applications do. No ledger entry: both sides are in receivables.
+    pub async fn apply_to_invoices(
+        conn: &mut PgConnection,
+        credit_memo: &CreditMemo,
+        applications: &[CreditMemoApplicationRequest],
+    ) -> Result<(CreditMemo, Vec<CreditMemoApplication>)> {
+        let total_applications: Decimal = applications.iter().map(|app| app.amount_applied).sum();
+        if total_applications > credit_memo.balance {
+            return Err(AppError::ValidationError(
+                "Total application amount cannot exceed the credit memo's remaining credit".to_string()
+            ));
+        }
+
+        let amounts: Vec<(Uuid, Decimal)> = applications.iter()
+            .map(|app| (app.invoice_id, app.amount_applied))
+            .collect();
+        InvoiceService::lock_for_application(conn, credit_memo.customer_id, &amounts).await?;
+
+        let mut created = Vec::with_capacity(applications.len());
+        for application in applications {
+            if application.amount_applied <= Decimal::ZERO {
+                return Err(AppError::ValidationError("Applied amounts must be positive".to_string()));
+            }
+
+            let invoice_balance: Option<Decimal> = sqlx::query_scalar(
+                r#"
+                SELECT balance FROM invoices
+                WHERE id = $1 AND customer_id = $2 AND status NOT IN ('draft', 'void')
+                FOR UPDATE
+                "#
+            )
+            .bind(application.invoice_id)
+            .bind(credit_memo.customer_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+
+            let Some(invoice_balance) = invoice_balance else {
+                return Err(AppError::ValidationError(
+                    format!("Invoice {} is not an open invoice of the customer", application.invoice_id)
+                ));
+            };
+            if application.amount_applied > invoice_balance {
+                return Err(AppError::ValidationError(
+                    format!("Applied amount exceeds the balance of invoice {}", application.invoice_id)
+                ));
+            }
+
+            let applied = sqlx::query_as::<_, CreditMemoApplication>(
+                r#"
+                INSERT INTO credit_memo_applications
+                    (id, credit_memo_id, invoice_id, amount_applied, created_at)
+                VALUES ($1, $2, $3, $4, NOW())
+                RETURNING id, credit_memo_id, invoice_id, amount_applied, created_at
+                "#,
+            )
+            .bind(Uuid::new_v4())
+            .bind(credit_memo.id)
+            .bind(application.invoice_id)
+            .bind(application.amount_applied)
+            .fetch_one(&mut *conn)
+            .await?;
+
+            InvoiceService::recalculate_balance(conn, application.invoice_id).await?;
+
+            created.push(applied);
+        }
+
+        let updated = Self::reduce_balance(conn, credit_memo.id, total_applications).await?;
+
+        Ok((updated, created))
+    }
+
+    /// Use up part of a credit memo's remaining credit
+    async fn reduce_balance(conn: &mut PgConnection, credit_memo_id: Uuid, amount: Decimal) -> Result<CreditMemo> {
+        let credit_memo = sqlx::query_as::<_, CreditMemo>(
+            r#"
+            UPDATE credit_memos
+            SET balance = balance - $1,
+                status = CASE WHEN balance - $1 <= 0 THEN 'applied' ELSE status END,
+                updated_at = NOW()
+            WHERE id = $2
+            RETURNING *
+            "#
+        )
+        .bind(amount)
+        .bind(credit_memo_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(credit_memo)
+    }
+
+    /// Tax on a credit memo totalled by component tax
+    async fn tax_summary(conn: &mut PgConnection, credit_memo_id: Uuid) -> Result<Vec<TaxSummary>> {
+        let taxes = sqlx::query_as::<_, TaxSummary>(
+            r#"
+            SELECT tax_name, rate, SUM(taxable_amount) as taxable_amount, SUM(tax_amount) as tax_amount
+            FROM credit_memo_line_taxes
+            WHERE credit_memo_id = $1
+            GROUP BY tax_name, rate
+            ORDER BY tax_name, rate
+            "#
+        )
+        .bind(credit_memo_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(taxes)
+    }
+
+    /// Post the credit memo to the ledger: Dr revenue and tax liabilities, Cr accounts
+    /// receivable. Credit memos without an AR account are not posted.
+    async fn post_credit_memo(conn: &mut PgConnection, credit_memo: &CreditMemo) -> Result<Option<Uuid>> {
+        let Some(ar_account_id) = credit_memo.ar_account_id else {
+            return Ok(None);
+        };
+
+        let revenue: Vec<(Uuid, Decimal)> = sqlx::query_as(
+            "SELECT revenue_account_id, amount FROM credit_memo_line_items WHERE credit_memo_id = $1 ORDER BY line_number"
+        )
+        .bind(credit_memo.id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let taxes: Vec<(Uuid, Decimal)> = sqlx::query_as(
+            r#"
+            SELECT tr.liability_account_id, cmlt.tax_amount
+            FROM credit_memo_line_taxes cmlt
+            INNER JOIN tax_rates tr ON tr.id = cmlt.tax_rate_id
+            WHERE cmlt.credit_memo_id = $1
+            ORDER BY cmlt.created_at
+            "#
+        )
+        .bind(credit_memo.id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut amounts = revenue;
+        amounts.extend(taxes);
+        amounts.push((ar_account_id, -credit_memo.total_amount));
+
+        let description = format!("Credit memo {}", credit_memo.credit_memo_number);
+        let entry = CreateTransactionRequest {
+            transaction_date: credit_memo.credit_memo_date,
+            description: Some(description.clone()),
+            reference_number: Some(credit_memo.credit_memo_number.clone()),
+            entry_number: None,
+            contact_id: Some(credit_memo.customer_id),
+            company_id: credit_memo.company_id,
+            journal_type: Some(JournalType::Sales),
+            line_items: CreateLineItemRequest::from_signed_amounts(&amounts, &description),
+        };
+        let transaction_id = TransactionService::post_entry(conn, &entry, None).await?;
+
+        sqlx::query("UPDATE credit_memos SET transaction_id = $1 WHERE id = $2")
+            .bind(transaction_id)
+            .bind(credit_memo.id)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(Some(transaction_id))
+    }
+
+    fn calculate_line_item_amount(line_item: &CreateCreditMemoLineItemRequest) -> Decimal {
+        (line_item.quantity * line_item.unit_price).round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero)
+    }
+
+    async fn invalidate_cache(&self, invoices_changed: bool, ledger_changed: bool) {
+        let _ = self.cache.delete_pattern("credit_memo:*").await;
+        if invoices_changed {
+            let _ = self.cache.delete_pattern("invoice:*").await;
+            let _ = self.cache.delete_pattern("customer:*").await;
+        }
+        if ledger_changed {
+            let _ = self.cache.invalidate_all_account_balances().await;
+        }
+    }
+}
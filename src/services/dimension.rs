@@ -0,0 +1,350 @@
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use validator::Validate;
+use std::collections::HashMap;
+
+use crate::models::{
+    DimensionType, DimensionValue, DimensionRequirement, DimensionTypeWithValues, DimensionLine,
+    CreateDimensionTypeRequest, CreateDimensionValueRequest, UpdateDimensionValueRequest,
+    CreateDimensionRequirementRequest,
+};
+use crate::utils::{AppError, Result};
+use crate::services::{CacheService, CompanyRecord, CompanyService};
+
+#[derive(Clone)]
+pub struct DimensionService {
+    cache: CacheService,
+    company_id: Option<Uuid>,
+}
+
+impl DimensionService {
+    pub fn new_with_cache(cache: CacheService) -> Self {
+        Self { cache, company_id: None }
+    }
+
+    /// Scope the service to a company's records
+    pub fn for_company(&self, company_id: Option<Uuid>) -> Self {
+        Self { company_id, ..self.clone() }
+    }
+
+    /// Create a dimension type such as Project or Department
+    pub async fn create_dimension_type(&self, pool: &PgPool, req: CreateDimensionTypeRequest) -> Result<DimensionType> {
+        // Validate request
+        req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+        let company_id = CompanyService::record_company(self.company_id, req.company_id)?;
+
+        sqlx::query_as::<_, DimensionType>(
+            r#"
+            INSERT INTO dimension_types (id, code, name, company_id, is_active, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, true, NOW(), NOW())
+            RETURNING *
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(&req.code)
+        .bind(&req.name)
+        .bind(company_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => AppError::Conflict(
+                format!("Dimension type {} already exists", req.code)
+            ),
+            e => AppError::from(e),
+        })
+    }
+
+    /// List the company's dimension types
+    pub async fn list_dimension_types(&self, pool: &PgPool) -> Result<Vec<DimensionType>> {
+        let dimension_types = sqlx::query_as::<_, DimensionType>(
+            "SELECT * FROM dimension_types WHERE company_id IS NOT DISTINCT FROM $1 ORDER BY name"
+        )
+        .bind(self.company_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(dimension_types)
+    }
+
+    /// Get a dimension type with its values and account requirements
+    pub async fn get_dimension_type(&self, pool: &PgPool, id: Uuid) -> Result<DimensionTypeWithValues> {
+        let dimension_type = sqlx::query_as::<_, DimensionType>(
+            "SELECT * FROM dimension_types WHERE id = $1 AND company_id IS NOT DISTINCT FROM $2"
+        )
+        .bind(id)
+        .bind(self.company_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Dimension type with id {} not found", id)))?;
+
+        let values = sqlx::query_as::<_, DimensionValue>(
+            "SELECT * FROM dimension_values WHERE dimension_type_id = $1 ORDER BY code"
+        )
+        .bind(id)
+        .fetch_all(pool)
+        .await?;
+
+        let requirements = sqlx::query_as::<_, DimensionRequirement>(
+            "SELECT * FROM dimension_requirements WHERE dimension_type_id = $1 ORDER BY created_at"
+        )
+        .bind(id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(DimensionTypeWithValues {
+            dimension_type,
+            values,
+            requirements,
+        })
+    }
+
+    /// Add a value to a dimension type
+    pub async fn create_dimension_value(
+        &self,
+        pool: &PgPool,
+        dimension_type_id: Uuid,
+        req: CreateDimensionValueRequest,
+    ) -> Result<DimensionValue> {
+        // Validate request
+        req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+        self.ensure_dimension_type(pool, dimension_type_id).await?;
+
+        sqlx::query_as::<_, DimensionValue>(
+            r#"
+            INSERT INTO dimension_values (id, dimension_type_id, code, name, is_active, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, true, NOW(), NOW())
+            RETURNING *
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(dimension_type_id)
+        .bind(&req.code)
+        .bind(&req.name)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => AppError::Conflict(
+                format!("Dimension value {} already exists", req.code)
+            ),
+            e => AppError::from(e),
+        })
+    }
+
+    /// Rename or deactivate a dimension value
+    pub async fn update_dimension_value(
+        &self,
+        pool: &PgPool,
+        id: Uuid,
+        req: UpdateDimensionValueRequest,
+    ) -> Result<DimensionValue> {
+        // Validate request
+        req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+        let value = sqlx::query_as::<_, DimensionValue>(
+            r#"
+            UPDATE dimension_values
+            SET name = COALESCE($1, name),
+                is_active = COALESCE($2, is_active)
+            WHERE id = $3
+              AND dimension_type_id IN (SELECT id FROM dimension_types WHERE company_id IS NOT DISTINCT FROM $4)
+            RETURNING *
+            "#
+        )
+        .bind(&req.name)
+        .bind(req.is_active)
+        .bind(id)
+        .bind(self.company_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Dimension value with id {} not found", id)))?;
+
+        // Reports grouped by the dimension show the value's name
+        if req.name.is_some() {
+            for pattern in ["trial_balance:*", "profit_loss:*", "balance_sheet:*", "ar_aging:*"] {
+                let _ = self.cache.delete_pattern(pattern).await;
+            }
+        }
+
+        Ok(value)
+    }
+
+    /// Require a value of the dimension type on lines posting to an account type or account
+    pub async fn add_requirement(
+        &self,
+        pool: &PgPool,
+        dimension_type_id: Uuid,
+        req: CreateDimensionRequirementRequest,
+    ) -> Result<DimensionRequirement> {
+        if req.account_type.is_some() == req.account_id.is_some() {
+            return Err(AppError::ValidationError(
+                "Specify either an account type or an account".to_string()
+            ));
+        }
+
+        self.ensure_dimension_type(pool, dimension_type_id).await?;
+        if let Some(account_id) = req.account_id {
+            let mut conn = pool.acquire().await?;
+            CompanyService::ensure_in_company(&mut conn, self.company_id, CompanyRecord::Account, &[account_id]).await?;
+        }
+
+        sqlx::query_as::<_, DimensionRequirement>(
+            r#"
+            INSERT INTO dimension_requirements (id, dimension_type_id, account_type, account_id, created_at)
+            VALUES ($1, $2, $3, $4, NOW())
+            RETURNING *
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(dimension_type_id)
+        .bind(&req.account_type)
+        .bind(req.account_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => AppError::Conflict(
+                "The dimension is already required for this account".to_string()
+            ),
+            e => AppError::from(e),
+        })
+    }
+
+    /// Remove an account requirement from a dimension type
+    pub async fn remove_requirement(&self, pool: &PgPool, dimension_type_id: Uuid, requirement_id: Uuid) -> Result<()> {
+        self.ensure_dimension_type(pool, dimension_type_id).await?;
+
+        let result = sqlx::query("DELETE FROM dimension_requirements WHERE id = $1 AND dimension_type_id = $2")
+            .bind(requirement_id)
+            .bind(dimension_type_id)
+            .execute(pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("Dimension requirement with id {} not found", requirement_id)));
+        }
+
+        Ok(())
+    }
+
+    /// Tag a line with dimension values on the caller's transaction. With an account the
+    /// line must also carry every dimension the account requires.
+    pub async fn tag_line(
+        conn: &mut PgConnection,
+        company_id: Option<Uuid>,
+        line: DimensionLine,
+        line_id: Uuid,
+        account_id: Option<Uuid>,
+        value_ids: &[Uuid],
+    ) -> Result<()> {
+        let values = if value_ids.is_empty() {
+            Vec::new()
+        } else {
+            sqlx::query_as::<_, DimensionValue>(
+                r#"
+                SELECT v.*
+                FROM dimension_values v
+                INNER JOIN dimension_types t ON t.id = v.dimension_type_id
+                WHERE v.id = ANY($1) AND v.is_active AND t.is_active
+                  AND t.company_id IS NOT DISTINCT FROM $2
+                "#
+            )
+            .bind(value_ids)
+            .bind(company_id)
+            .fetch_all(&mut *conn)
+            .await?
+        };
+
+        if let Some(missing) = value_ids.iter().find(|id| !values.iter().any(|v| v.id == **id)) {
+            return Err(AppError::ValidationError(format!(
+                "Dimension value {} does not exist or is inactive", missing
+            )));
+        }
+        for (index, value) in values.iter().enumerate() {
+            if values[..index].iter().any(|v| v.dimension_type_id == value.dimension_type_id) {
+                return Err(AppError::ValidationError(
+                    "A line can carry only one value of each dimension".to_string()
+                ));
+            }
+        }
+
+        if let Some(account_id) = account_id {
+            let type_ids: Vec<Uuid> = values.iter().map(|v| v.dimension_type_id).collect();
+            let missing: Vec<String> = sqlx::query_scalar(
+                r#"
+                SELECT DISTINCT t.name
+                FROM dimension_requirements r
+                INNER JOIN dimension_types t ON t.id = r.dimension_type_id
+                INNER JOIN chart_of_accounts a ON a.id = $1
+                WHERE t.is_active
+                  AND (r.account_id = a.id OR r.account_type = a.account_type)
+                  AND NOT (t.id = ANY($2))
+                ORDER BY t.name
+                "#
+            )
+            .bind(account_id)
+            .bind(&type_ids)
+            .fetch_all(&mut *conn)
+            .await?;
+
+            if !missing.is_empty() {
+                return Err(AppError::ValidationError(format!(
+                    "Lines posting to this account need a {}", missing.join(", ")
+                )));
+            }
+        }
+
+        let (table, column) = line.table();
+        for value in &values {
+            sqlx::query(&format!(
+                "INSERT INTO {} ({}, dimension_type_id, dimension_value_id) VALUES ($1, $2, $3)",
+                table, column
+            ))
+            .bind(line_id)
+            .bind(value.dimension_type_id)
+            .bind(value.id)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Dimension values on each of the given lines
+    pub async fn line_values(
+        conn: &mut PgConnection,
+        line: DimensionLine,
+        line_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Vec<Uuid>>> {
+        let (table, column) = line.table();
+        let rows: Vec<(Uuid, Uuid)> = sqlx::query_as(&format!(
+            "SELECT {0}, dimension_value_id FROM {1} WHERE {0} = ANY($1) ORDER BY dimension_type_id",
+            column, table
+        ))
+        .bind(line_ids)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut values: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for (line_id, value_id) in rows {
+            values.entry(line_id).or_default().push(value_id);
+        }
+
+        Ok(values)
+    }
+
+    async fn ensure_dimension_type(&self, pool: &PgPool, id: Uuid) -> Result<()> {
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM dimension_types WHERE id = $1 AND company_id IS NOT DISTINCT FROM $2)"
+        )
+        .bind(id)
+        .bind(self.company_id)
+        .fetch_one(pool)
+        .await?;
+
+        if !exists {
+            return Err(AppError::NotFound(format!("Dimension type with id {} not found", id)));
+        }
+
+        Ok(())
+    }
+}
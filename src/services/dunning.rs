@@ -0,0 +1,364 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{
+    Contact, DunningLevel, DunningRun, Invoice, InvoiceReminder, SetDunningLevelsRequest,
+};
+use crate::utils::{AppError, Result};
+use crate::services::{CacheService, CompanyService, EmailService};
+
+/// Schedule used when neither the company nor the default schedule has levels
+const BUILT_IN_LEVELS: [(i32, &str, &str); 3] = [
+    (
+        7,
+        "Reminder: invoice {{invoice_number}} is past due",
+        "Hello {{customer_name}},\n\n\
+        This is a friendly reminder that invoice {{invoice_number}} was due on {{due_date}}.\n\
+        The balance due is {{balance}}. If you have already paid, please disregard this message.\n\n\
+        Thank you,\n{{company_name}}",
+    ),
+    (
+        30,
+        "Second notice: invoice {{invoice_number}} is {{days_overdue}} days overdue",
+        "Hello {{customer_name}},\n\n\
+        Invoice {{invoice_number}} is now {{days_overdue}} days overdue with {{balance}} outstanding.\n\
+        Please arrange payment at your earliest convenience.\n\n\
+        Thank you,\n{{company_name}}",
+    ),
+    (
+        60,
+        "Final notice: invoice {{invoice_number}} is {{days_overdue}} days overdue",
+        "Hello {{customer_name}},\n\n\
+        Invoice {{invoice_number}} is {{days_overdue}} days overdue with {{balance}} outstanding.\n\
+        Please contact us immediately to settle your account.\n\n\
+        {{company_name}}",
+    ),
+];
+
+/// Send attempts after which a failed reminder is no longer retried
+const MAX_REMINDER_ATTEMPTS: i32 = 5;
+
+/// Minutes after which a reminder still being sent is assumed abandoned by its run
+const STALE_CLAIM_MINUTES: i32 = 60;
+
+/// Overdue invoice eligible for a reminder
+#[derive(sqlx::FromRow)]
+struct DunningCandidate {
+    invoice_id: Uuid,
+    due_date: NaiveDate,
+    last_level: Option<i32>,
+}
+
+#[derive(Clone)]
+pub struct DunningService {
+    cache: CacheService,
+    email_service: EmailService,
+    company_id: Option<Uuid>,
+}
+
+impl DunningService {
+    pub fn new(email_service: EmailService, cache: CacheService) -> Self {
+        Self { cache, email_service, company_id: None }
+    }
+
+    /// Scope the service to a company's records
+    pub fn for_company(&self, company_id: Option<Uuid>) -> Self {
+        Self {
+            cache: self.cache.clone(),
+            email_service: self.email_service.for_company(company_id),
+            company_id,
+        }
+    }
+
+    /// Mark overdue invoices, queue the reminders they have reached and send queued reminders
+    pub async fn run(&self, pool: &PgPool, as_of_date: NaiveDate) -> Result<DunningRun> {
+        let marked_overdue = self.mark_overdue(pool, as_of_date).await?;
+        self.queue_reminders(pool, as_of_date).await?;
+        let reminders = self.send_queued_reminders(pool, as_of_date).await?;
+
+        Ok(DunningRun {
+            as_of_date,
+            marked_overdue,
+            reminders,
+        })
+    }
+
+    /// Move sent and partially paid invoices past their due date to overdue
+    pub async fn mark_overdue(&self, pool: &PgPool, as_of_date: NaiveDate) -> Result<Vec<Invoice>> {
+        let invoices = sqlx::query_as::<_, Invoice>(
+            r#"
+            UPDATE invoices
+            SET status = 'overdue', updated_at = NOW()
+            WHERE status IN ('sent', 'partial') AND due_date < $1 AND balance > 0
+              AND company_id IS NOT DISTINCT FROM $2
+            RETURNING *
+            "#
+        )
+        .bind(as_of_date)
+        .bind(self.company_id)
+        .fetch_all(pool)
+        .await?;
+
+        if !invoices.is_empty() {
+            let _ = self.cache.delete_pattern("invoice:*").await;
+        }
+
+        Ok(invoices)
+    }
+
+    /// Get the company's dunning schedule, falling back to the default schedule
+    pub async fn get_dunning_levels(&self, pool: &PgPool) -> Result<Vec<DunningLevel>> {
+        let levels = sqlx::query_as::<_, DunningLevel>(
+            r#"
+            SELECT level, days_overdue, subject, body
+            FROM dunning_levels
+            WHERE company_id IS NOT DISTINCT FROM (
+                SELECT company_id FROM dunning_levels
+                WHERE company_id IS NOT DISTINCT FROM $1 OR company_id IS NULL
+                ORDER BY company_id NULLS LAST
+                LIMIT 1
+            )
+            ORDER BY level
+            "#
+        )
+        .bind(self.company_id)
+        .fetch_all(pool)
+        .await?;
+
+        if !levels.is_empty() {
+            return Ok(levels);
+        }
+
+        Ok(BUILT_IN_LEVELS
+            .iter()
+            .enumerate()
+            .map(|(index, (days_overdue, subject, body))| DunningLevel {
+                level: index as i32 + 1,
+                days_overdue: *days_overdue,
+                subject: subject.to_string(),
+                body: body.to_string(),
+            })
+            .collect())
+    }
+
+    /// Replace a company's dunning schedule
+    pub async fn set_dunning_levels(&self, pool: &PgPool, req: SetDunningLevelsRequest) -> Result<Vec<DunningLevel>> {
+        req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+        let company_id = CompanyService::record_company(self.company_id, req.company_id)?;
+
+        let mut levels = req.levels;
+        levels.sort_by_key(|level| level.days_overdue);
+
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM dunning_levels WHERE company_id IS NOT DISTINCT FROM $1")
+            .bind(company_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for (index, level) in levels.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO dunning_levels (id, company_id, level, days_overdue, subject, body, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, NOW(), NOW())
+                "#
+            )
+            .bind(Uuid::new_v4())
+            .bind(company_id)
+            .bind(index as i32 + 1)
+            .bind(level.days_overdue)
+            .bind(&level.subject)
+            .bind(&level.body)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+                    AppError::Conflict(format!("More than one level at {} days overdue", level.days_overdue))
+                }
+                e => AppError::from(e),
+            })?;
+        }
+
+        tx.commit().await?;
+
+        self.get_dunning_levels(pool).await
+    }
+
+    /// Opt a customer out of (or back into) dunning reminders
+    pub async fn set_opt_out(&self, pool: &PgPool, customer_id: Uuid, opt_out: bool) -> Result<Contact> {
+        let contact = sqlx::query_as::<_, Contact>(
+            r#"
+            UPDATE contacts
+            SET dunning_opt_out = $2, updated_at = NOW()
+            WHERE id = $1 AND contact_type = 'Customer' AND company_id IS NOT DISTINCT FROM $3
+            RETURNING id, contact_type, name, email, phone, billing_address, shipping_address, company_id, dunning_opt_out, finance_charge_exempt, created_at, updated_at
+            "#
+        )
+        .bind(customer_id)
+        .bind(opt_out)
+        .bind(self.company_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Customer with id {} not found", customer_id)))?;
+
+        let _ = self.cache.delete(&format!("contact:data:{}", customer_id)).await;
+
+        Ok(contact)
+    }
+
+    /// List reminders, newest first
+    pub async fn list_reminders(
+        &self,
+        pool: &PgPool,
+        invoice_id: Option<Uuid>,
+        customer_id: Option<Uuid>,
+    ) -> Result<Vec<InvoiceReminder>> {
+        let reminders = sqlx::query_as::<_, InvoiceReminder>(
+            r#"
+            SELECT r.*
+            FROM invoice_reminders r
+            INNER JOIN invoices i ON i.id = r.invoice_id
+            WHERE ($1::uuid IS NULL OR r.invoice_id = $1)
+              AND ($2::uuid IS NULL OR i.customer_id = $2)
+              AND i.company_id IS NOT DISTINCT FROM $3
+            ORDER BY r.queued_at DESC, r.level DESC
+            "#
+        )
+        .bind(invoice_id)
+        .bind(customer_id)
+        .bind(self.company_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(reminders)
+    }
+
+    /// Queue the highest level each overdue invoice has reached but not yet been sent.
+    ///
+    /// Levels skipped while the job wasn't running are not sent retroactively.
+    async fn queue_reminders(&self, pool: &PgPool, as_of_date: NaiveDate) -> Result<()> {
+        let candidates = sqlx::query_as::<_, DunningCandidate>(
+            r#"
+            SELECT i.id AS invoice_id, i.due_date, MAX(r.level) AS last_level
+            FROM invoices i
+            INNER JOIN contacts c ON c.id = i.customer_id
+            LEFT JOIN invoice_reminders r ON r.invoice_id = i.id
+            WHERE i.status = 'overdue' AND i.balance > 0 AND i.due_date < $1
+              AND i.company_id IS NOT DISTINCT FROM $2
+              AND NOT c.dunning_opt_out
+            GROUP BY i.id, i.due_date
+            "#
+        )
+        .bind(as_of_date)
+        .bind(self.company_id)
+        .fetch_all(pool)
+        .await?;
+
+        let levels = self.get_dunning_levels(pool).await?;
+        for candidate in candidates {
+            let days_overdue = (as_of_date - candidate.due_date).num_days();
+            let reached = levels
+                .iter()
+                .filter(|level| i64::from(level.days_overdue) <= days_overdue)
+                .filter(|level| candidate.last_level.is_none_or(|last| level.level > last))
+                .max_by_key(|level| level.level);
+
+            if let Some(level) = reached {
+                sqlx::query(
+                    r#"
+                    INSERT INTO invoice_reminders (id, invoice_id, level, days_overdue, status, queued_at)
+                    VALUES ($1, $2, $3, $4, 'queued', NOW())
+                    ON CONFLICT (invoice_id, level) DO NOTHING
+                    "#
+                )
+                .bind(Uuid::new_v4())
+                .bind(candidate.invoice_id)
+                .bind(level.level)
+                .bind(days_overdue as i32)
+                .execute(pool)
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Send queued reminders and retry failed ones while the invoice is still overdue.
+    ///
+    /// Reminders are claimed before sending, so a concurrent run skips them rather than
+    /// sending them twice. A claim left behind by a run that died is taken over once it is
+    /// stale.
+    async fn send_queued_reminders(&self, pool: &PgPool, as_of_date: NaiveDate) -> Result<Vec<InvoiceReminder>> {
+        let mut pending = sqlx::query_as::<_, (Uuid, Uuid, i32, NaiveDate, DateTime<Utc>)>(
+            r#"
+            UPDATE invoice_reminders r
+            SET status = 'sending', attempts = r.attempts + 1, claimed_at = NOW()
+            FROM (
+                SELECT r.id, i.due_date
+                FROM invoice_reminders r
+                INNER JOIN invoices i ON i.id = r.invoice_id
+                INNER JOIN contacts c ON c.id = i.customer_id
+                WHERE (r.status = 'queued'
+                       OR (r.status = 'failed' AND r.attempts < $2)
+                       OR (r.status = 'sending' AND r.claimed_at < NOW() - make_interval(mins => $3)))
+                  AND i.status = 'overdue' AND i.balance > 0
+                  AND i.company_id IS NOT DISTINCT FROM $1
+                  AND NOT c.dunning_opt_out
+                FOR UPDATE OF r SKIP LOCKED
+            ) claimed
+            WHERE r.id = claimed.id
+            RETURNING r.id, r.invoice_id, r.level, claimed.due_date, r.queued_at
+            "#
+        )
+        .bind(self.company_id)
+        .bind(MAX_REMINDER_ATTEMPTS)
+        .bind(STALE_CLAIM_MINUTES)
+        .fetch_all(pool)
+        .await?;
+        pending.sort_by_key(|(_, _, level, _, queued_at)| (*queued_at, *level));
+
+        let levels = self.get_dunning_levels(pool).await?;
+        let mut reminders = Vec::with_capacity(pending.len());
+        for (reminder_id, invoice_id, level_number, due_date, _) in pending {
+            let days_overdue = (as_of_date - due_date).num_days();
+            let result = match levels.iter().find(|level| level.level == level_number) {
+                Some(level) => self.email_service
+                    .send_invoice_reminder(pool, invoice_id, level, days_overdue)
+                    .await,
+                None => Err(AppError::NotFound(format!("Dunning level {} is no longer in the schedule", level_number))),
+            };
+
+            let reminder = match result {
+                Ok(delivery) => {
+                    sqlx::query_as::<_, InvoiceReminder>(
+                        r#"
+                        UPDATE invoice_reminders
+                        SET status = 'sent', error = NULL, email_delivery_id = $2, sent_at = NOW()
+                        WHERE id = $1
+                        RETURNING *
+                        "#
+                    )
+                    .bind(reminder_id)
+                    .bind(delivery.id)
+                    .fetch_one(pool)
+                    .await?
+                }
+                Err(e) => {
+                    tracing::warn!("Dunning reminder for invoice {} failed: {}", invoice_id, e);
+                    sqlx::query_as::<_, InvoiceReminder>(
+                        "UPDATE invoice_reminders SET status = 'failed', error = $2 WHERE id = $1 RETURNING *"
+                    )
+                    .bind(reminder_id)
+                    .bind(e.to_string())
+                    .fetch_one(pool)
+                    .await?
+                }
+            };
+            reminders.push(reminder);
+        }
+
+        Ok(reminders)
+    }
+}
@@ -0,0 +1,519 @@
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use sqlx::PgPool;
+use std::env;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{
+    Contact, DunningLevel, EmailDelivery, EmailDeliveryStatus, EmailDocumentType, EmailTemplate, Invoice,
+    InvoiceStatus, SendStatementRequest, UpdateEmailTemplateRequest,
+};
+use crate::utils::{AppError, Result};
+use crate::utils::pdf::format_amount;
+use crate::services::{CacheService, CompanyService, InvoiceService, StatementService};
+
+const DEFAULT_INVOICE_SUBJECT: &str = "Invoice {{invoice_number}} from {{company_name}}";
+const DEFAULT_INVOICE_BODY: &str = "Hello {{customer_name}},\n\n\
+    Please find attached invoice {{invoice_number}} for {{total}}, due {{due_date}}.\n\
+    The balance due is {{balance}}.\n\n\
+    Thank you,\n{{company_name}}";
+const DEFAULT_STATEMENT_SUBJECT: &str = "Statement from {{company_name}} as of {{statement_date}}";
+const DEFAULT_STATEMENT_BODY: &str = "Hello {{customer_name}},\n\n\
+    Please find attached your statement as of {{statement_date}}.\n\
+    The balance on your account is {{balance}}.\n\n\
+    Thank you,\n{{company_name}}";
+
+/// How the SMTP connection is secured
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SmtpSecurity {
+    /// Plain connection, e.g. a local mail catcher
+    None,
+    /// Upgrade with STARTTLS (usually port 587)
+    StartTls,
+    /// Implicit TLS (usually port 465)
+    Tls,
+}
+
+/// SMTP transport settings
+#[derive(Debug, Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from_address: String,
+    pub from_name: Option<String>,
+}
+
+impl SmtpConfig {
+    /// Read `SMTP_*` variables, defaulting to a mail catcher on localhost:1025
+    pub fn from_env() -> Self {
+        let host = env::var("SMTP_HOST").unwrap_or_else(|_| {
+            tracing::info!("SMTP_HOST not set, using default localhost:1025");
+            "localhost".to_string()
+        });
+        let port = env::var("SMTP_PORT")
+            .ok()
+            .and_then(|port| port.parse().ok())
+            .unwrap_or(1025);
+        let security = match env::var("SMTP_SECURITY").unwrap_or_default().to_lowercase().as_str() {
+            "starttls" => SmtpSecurity::StartTls,
+            "tls" => SmtpSecurity::Tls,
+            _ => SmtpSecurity::None,
+        };
+
+        Self {
+            host,
+            port,
+            security,
+            username: env::var("SMTP_USERNAME").ok().filter(|value| !value.is_empty()),
+            password: env::var("SMTP_PASSWORD").ok().filter(|value| !value.is_empty()),
+            from_address: env::var("SMTP_FROM_ADDRESS").unwrap_or_else(|_| "billing@localhost".to_string()),
+            from_name: env::var("SMTP_FROM_NAME").ok().filter(|value| !value.is_empty()),
+        }
+    }
+
+    /// The configured sender
+    pub(crate) fn sender_mailbox(&self) -> std::result::Result<Mailbox, String> {
+        let from_address = self.from_address.parse()
+            .map_err(|e| format!("Invalid SMTP_FROM_ADDRESS: {}", e))?;
+        Ok(Mailbox::new(self.from_name.clone(), from_address))
+    }
+
+    /// Transport for the configured server
+    pub(crate) fn transport(&self) -> std::result::Result<AsyncSmtpTransport<Tokio1Executor>, String> {
+        let builder = match self.security {
+            SmtpSecurity::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&self.host),
+            SmtpSecurity::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&self.host)
+                .map_err(|e| e.to_string())?,
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&self.host)
+                .map_err(|e| e.to_string())?,
+        };
+        let mut builder = builder.port(self.port);
+        if let (Some(username), Some(password)) = (&self.username, &self.password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        Ok(builder.build())
+    }
+}
+
+#[derive(Clone)]
+pub struct EmailService {
+    config: SmtpConfig,
+    invoice_service: InvoiceService,
+    statement_service: StatementService,
+    company_id: Option<Uuid>,
+}
+
+impl EmailService {
+    pub fn new(config: SmtpConfig, cache: CacheService) -> Self {
+        Self {
+            config,
+            invoice_service: InvoiceService::new_with_cache(cache.clone()),
+            statement_service: StatementService::new_with_cache(cache),
+            company_id: None,
+        }
+    }
+
+    /// Scope the service to a company's records
+    pub fn for_company(&self, company_id: Option<Uuid>) -> Self {
+        Self {
+            config: self.config.clone(),
+            invoice_service: self.invoice_service.for_company(company_id),
+            statement_service: self.statement_service.for_company(company_id),
+            company_id,
+        }
+    }
+
+    /// Email an invoice PDF to its customer, marking a draft invoice as sent first.
+    ///
+    /// The draft is posted before anything goes out, so a customer never receives an
+    /// invoice the books don't contain.
+    pub async fn send_invoice(
+        &self,
+        pool: &PgPool,
+        invoice_id: Uuid,
+        to: Option<String>,
+        sent_by: Option<Uuid>,
+    ) -> Result<EmailDelivery> {
+        let mut invoice = self.get_invoice(pool, invoice_id).await?;
+
+        if invoice.status == InvoiceStatus::Void {
+            return Err(AppError::BadRequest("Cannot send a void invoice".to_string()));
+        }
+
+        let customer = self.get_contact(pool, invoice.customer_id).await?;
+        Self::recipient(to.clone(), &customer)?;
+        let template = self.resolve_template(pool, invoice.company_id, EmailDocumentType::Invoice).await?;
+
+        if invoice.status == InvoiceStatus::Draft {
+            invoice = self.invoice_service
+                .update_invoice_status(pool, invoice.id, InvoiceStatus::Sent)
+                .await?;
+        }
+
+        self.send_invoice_email(
+            pool, &invoice, to, sent_by, EmailDocumentType::Invoice, template, Vec::new(),
+        ).await
+    }
+
+    /// Email a dunning reminder with the invoice PDF attached
+    pub async fn send_invoice_reminder(
+        &self,
+        pool: &PgPool,
+        invoice_id: Uuid,
+        level: &DunningLevel,
+        days_overdue: i64,
+    ) -> Result<EmailDelivery> {
+        let invoice = self.get_invoice(pool, invoice_id).await?;
+
+        self.send_invoice_email(
+            pool,
+            &invoice,
+            None,
+            None,
+            EmailDocumentType::Reminder,
+            (level.subject.clone(), level.body.clone()),
+            vec![("days_overdue", days_overdue.to_string())],
+        ).await
+    }
+
+    /// Email a statement PDF to a customer
+    pub async fn send_statement(
+        &self,
+        pool: &PgPool,
+        customer_id: Uuid,
+        req: SendStatementRequest,
+        sent_by: Option<Uuid>,
+    ) -> Result<EmailDelivery> {
+        req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+        let statement = self.statement_service
+            .generate_statement(pool, customer_id, req.start_date, req.end_date, req.style)
+            .await?;
+
+        let customer = self.get_contact(pool, customer_id).await?;
+        let recipient = Self::recipient(req.to, &customer)?;
+        let company_name = self.company_name(pool, customer.company_id).await?;
+
+        let (subject, body) = self.resolve_template(pool, customer.company_id, EmailDocumentType::Statement).await?;
+        let values = [
+            ("customer_name", customer.name.clone()),
+            ("statement_date", statement.end_date.to_string()),
+            ("balance", format_amount(statement.closing_balance)),
+            ("company_name", company_name),
+        ];
+        let subject = render(&subject, &values);
+        let body = render(&body, &values);
+
+        let filename = format!("statement_{}.pdf", statement.end_date);
+        let pdf = StatementService::render_pdf(&[statement]);
+        let result = self.deliver(&recipient, &subject, &body, &filename, pdf).await;
+
+        let delivery = self.record_delivery(
+            pool,
+            EmailDocumentType::Statement,
+            None,
+            &customer,
+            &recipient,
+            &subject,
+            result.as_ref().err(),
+            sent_by,
+        ).await?;
+
+        if let Err(e) = result {
+            return Err(AppError::InternalError(format!("Failed to send email: {}", e)));
+        }
+
+        Ok(delivery)
+    }
+
+    /// List send history, newest first
+    pub async fn list_deliveries(
+        &self,
+        pool: &PgPool,
+        document_type: Option<EmailDocumentType>,
+        document_id: Option<Uuid>,
+        contact_id: Option<Uuid>,
+    ) -> Result<Vec<EmailDelivery>> {
+        let deliveries = sqlx::query_as::<_, EmailDelivery>(
+            r#"
+            SELECT * FROM email_deliveries
+            WHERE ($1::varchar IS NULL OR document_type = $1)
+              AND ($2::uuid IS NULL OR document_id = $2)
+              AND ($3::uuid IS NULL OR contact_id = $3)
+              AND company_id IS NOT DISTINCT FROM $4
+            ORDER BY created_at DESC
+            "#
+        )
+        .bind(document_type.map(|t| t.to_string()))
+        .bind(document_id)
+        .bind(contact_id)
+        .bind(self.company_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(deliveries)
+    }
+
+    /// Get a company's email template for a document type, or the default template
+    pub async fn get_email_template(
+        &self,
+        pool: &PgPool,
+        company_id: Option<Uuid>,
+        document_type: EmailDocumentType,
+    ) -> Result<EmailTemplate> {
+        let template = sqlx::query_as::<_, EmailTemplate>(
+            "SELECT * FROM email_templates WHERE company_id IS NOT DISTINCT FROM $1 AND document_type = $2"
+        )
+        .bind(company_id)
+        .bind(document_type)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Email template not found".to_string()))?;
+
+        Ok(template)
+    }
+
+    /// Create or replace a company's email template for a document type
+    pub async fn update_email_template(&self, pool: &PgPool, req: UpdateEmailTemplateRequest) -> Result<EmailTemplate> {
+        req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+        if req.document_type == EmailDocumentType::Reminder {
+            return Err(AppError::ValidationError(
+                "Reminder wording is set on each dunning level".to_string()
+            ));
+        }
+        let company_id = CompanyService::record_company(self.company_id, req.company_id)?;
+
+        let template = sqlx::query_as::<_, EmailTemplate>(
+            r#"
+            INSERT INTO email_templates (id, company_id, document_type, subject, body, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, NOW(), NOW())
+            ON CONFLICT (company_id, document_type) DO UPDATE
+            SET subject = EXCLUDED.subject,
+                body = EXCLUDED.body,
+                updated_at = NOW()
+            RETURNING *
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(company_id)
+        .bind(req.document_type)
+        .bind(&req.subject)
+        .bind(&req.body)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(template)
+    }
+
+    async fn get_invoice(&self, pool: &PgPool, invoice_id: Uuid) -> Result<Invoice> {
+        Ok(self.invoice_service.get_invoice(pool, invoice_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Invoice with id {} not found", invoice_id)))?
+            .invoice)
+    }
+
+    /// Render the templates for an invoice, send it with the PDF attached and record the delivery
+    #[allow(clippy::too_many_arguments)]
+    async fn send_invoice_email(
+        &self,
+        pool: &PgPool,
+        invoice: &Invoice,
+        to: Option<String>,
+        sent_by: Option<Uuid>,
+        document_type: EmailDocumentType,
+        (subject, body): (String, String),
+        extra_values: Vec<(&str, String)>,
+    ) -> Result<EmailDelivery> {
+        let customer = self.get_contact(pool, invoice.customer_id).await?;
+        let recipient = Self::recipient(to, &customer)?;
+        let company_name = self.company_name(pool, invoice.company_id).await?;
+
+        let mut values = vec![
+            ("customer_name", customer.name.clone()),
+            ("invoice_number", invoice.invoice_number.clone()),
+            ("total", format_amount(invoice.total_amount)),
+            ("balance", format_amount(invoice.balance)),
+            ("due_date", invoice.due_date.to_string()),
+            ("company_name", company_name),
+        ];
+        values.extend(extra_values);
+        let subject = render(&subject, &values);
+        let body = render(&body, &values);
+
+        let pdf = self.invoice_service.render_invoice_pdf(pool, invoice.id).await?;
+        let filename = format!("invoice_{}.pdf", invoice.invoice_number);
+        let result = self.deliver(&recipient, &subject, &body, &filename, pdf).await;
+
+        let delivery = self.record_delivery(
+            pool,
+            document_type,
+            Some(invoice.id),
+            &customer,
+            &recipient,
+            &subject,
+            result.as_ref().err(),
+            sent_by,
+        ).await?;
+
+        if let Err(e) = result {
+            return Err(AppError::InternalError(format!("Failed to send email: {}", e)));
+        }
+
+        Ok(delivery)
+    }
+
+    async fn get_contact(&self, pool: &PgPool, contact_id: Uuid) -> Result<Contact> {
+        sqlx::query_as::<_, Contact>("SELECT * FROM contacts WHERE id = $1")
+            .bind(contact_id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Contact with id {} not found", contact_id)))
+    }
+
+    /// Explicit recipient, else the contact's email address
+    fn recipient(to: Option<String>, contact: &Contact) -> Result<String> {
+        let recipient = to
+            .or_else(|| contact.email.clone())
+            .filter(|address| !address.trim().is_empty())
+            .ok_or_else(|| AppError::ValidationError(format!("{} has no email address", contact.name)))?;
+
+        recipient.trim().parse::<Mailbox>()
+            .map_err(|_| AppError::ValidationError(format!("Invalid email address: {}", recipient)))?;
+
+        Ok(recipient.trim().to_string())
+    }
+
+    async fn company_name(&self, pool: &PgPool, company_id: Option<Uuid>) -> Result<String> {
+        // Prefer the name printed on invoices, then the company record
+        let name: Option<String> = sqlx::query_scalar(
+            r#"
+            SELECT COALESCE(
+                (SELECT business_name FROM invoice_templates
+                 WHERE (company_id IS NOT DISTINCT FROM $1 OR company_id IS NULL) AND business_name IS NOT NULL
+                 ORDER BY company_id NULLS LAST LIMIT 1),
+                (SELECT name FROM companies WHERE id = $1)
+            )
+            "#
+        )
+        .bind(company_id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(name
+            .or_else(|| self.config.from_name.clone())
+            .unwrap_or_default())
+    }
+
+    /// Company template, then the default template, then the built-in wording
+    async fn resolve_template(
+        &self,
+        pool: &PgPool,
+        company_id: Option<Uuid>,
+        document_type: EmailDocumentType,
+    ) -> Result<(String, String)> {
+        let template = sqlx::query_as::<_, EmailTemplate>(
+            r#"
+            SELECT * FROM email_templates
+            WHERE document_type = $2 AND (company_id IS NOT DISTINCT FROM $1 OR company_id IS NULL)
+            ORDER BY company_id NULLS LAST
+            LIMIT 1
+            "#
+        )
+        .bind(company_id)
+        .bind(document_type)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(match (template, document_type) {
+            (Some(template), _) => (template.subject, template.body),
+            (None, EmailDocumentType::Invoice | EmailDocumentType::Reminder) => (DEFAULT_INVOICE_SUBJECT.to_string(), DEFAULT_INVOICE_BODY.to_string()),
+            (None, EmailDocumentType::Statement) => (DEFAULT_STATEMENT_SUBJECT.to_string(), DEFAULT_STATEMENT_BODY.to_string()),
+        })
+    }
+
+    /// Send one message with a PDF attachment; errors are returned as text for the send history
+    async fn deliver(
+        &self,
+        recipient: &str,
+        subject: &str,
+        body: &str,
+        filename: &str,
+        pdf: Vec<u8>,
+    ) -> std::result::Result<(), String> {
+        let from = self.config.sender_mailbox()?;
+        let to: Mailbox = recipient.parse().map_err(|e| format!("Invalid recipient: {}", e))?;
+
+        let message = Message::builder()
+            .from(from)
+            .to(to)
+            .subject(subject)
+            .multipart(
+                MultiPart::mixed()
+                    .singlepart(SinglePart::plain(body.to_string()))
+                    .singlepart(
+                        Attachment::new(filename.to_string())
+                            .body(pdf, ContentType::parse("application/pdf").expect("valid content type")),
+                    ),
+            )
+            .map_err(|e| e.to_string())?;
+
+        self.config.transport()?
+            .send(message)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn record_delivery(
+        &self,
+        pool: &PgPool,
+        document_type: EmailDocumentType,
+        document_id: Option<Uuid>,
+        contact: &Contact,
+        recipient: &str,
+        subject: &str,
+        error: Option<&String>,
+        sent_by: Option<Uuid>,
+    ) -> Result<EmailDelivery> {
+        let status = if error.is_some() { EmailDeliveryStatus::Failed } else { EmailDeliveryStatus::Sent };
+
+        let delivery = sqlx::query_as::<_, EmailDelivery>(
+            r#"
+            INSERT INTO email_deliveries
+                (id, document_type, document_id, contact_id, recipient, subject, status, error,
+                 company_id, sent_by, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, NOW())
+            RETURNING *
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(document_type)
+        .bind(document_id)
+        .bind(contact.id)
+        .bind(recipient)
+        .bind(subject)
+        .bind(status)
+        .bind(error)
+        .bind(contact.company_id)
+        .bind(sent_by)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(delivery)
+    }
+}
+
+/// Replace `{{name}}` placeholders; unknown placeholders are left as written
+fn render(template: &str, values: &[(&str, String)]) -> String {
+    values.iter().fold(template.to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{{{}}}}}", name), value)
+    })
+}
@@ -0,0 +1,486 @@
+use chrono::{NaiveDate, Utc};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use validator::Validate;
+use rust_decimal::{Decimal, RoundingStrategy};
+use std::collections::HashMap;
+
+use crate::models::{
+    Estimate, EstimateLineItem, EstimateStatus, EstimateInvoice, EstimateWithLineItems,
+    EstimateConversion, CreateEstimateRequest, ConvertEstimateRequest, CreateInvoiceRequest,
+    CreateInvoiceLineItemRequest,
+};
+use crate::utils::{AppError, Result};
+use crate::services::{CacheService, CompanyRecord, CompanyService, InvoiceService, TaxService};
+
+#[derive(Clone)]
+pub struct EstimateService {
+    cache: CacheService,
+    invoice_service: InvoiceService,
+    company_id: Option<Uuid>,
+}
+
+impl EstimateService {
+    pub fn new_with_cache(cache: CacheService) -> Self {
+        Self {
+            invoice_service: InvoiceService::new_with_cache(cache.clone()),
+            cache,
+            company_id: None,
+        }
+    }
+
+    /// Scope the service to a company's records
+    pub fn for_company(&self, company_id: Option<Uuid>) -> Self {
+        Self {
+            cache: self.cache.clone(),
+            invoice_service: self.invoice_service.for_company(company_id),
+            company_id,
+        }
+    }
+
+    /// Create an estimate in draft. Estimates never post to the ledger.
+    pub async fn create_estimate(&self, pool: &PgPool, req: CreateEstimateRequest) -> Result<EstimateWithLineItems> {
+        // Validate request
+        req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+        if req.expiry_date < req.estimate_date {
+            return Err(AppError::ValidationError(
+                "Expiry date cannot be before the estimate date".to_string()
+            ));
+        }
+
+        let company_id = CompanyService::record_company(self.company_id, req.company_id)?;
+
+        let mut tx = pool.begin().await?;
+
+        let account_ids: Vec<Uuid> = req.line_items.iter().map(|l| l.revenue_account_id).collect();
+        let item_ids: Vec<Uuid> = req.line_items.iter().filter_map(|l| l.item_id).collect();
+        CompanyService::ensure_in_company(&mut tx, company_id, CompanyRecord::Contact, &[req.customer_id]).await?;
+        CompanyService::ensure_in_company(&mut tx, company_id, CompanyRecord::Account, &account_ids).await?;
+        CompanyService::ensure_in_company(&mut tx, company_id, CompanyRecord::Item, &item_ids).await?;
+
+        // Calculate line amounts and tax the same way invoices do
+        let mut subtotal = Decimal::ZERO;
+        let mut line_taxes = Vec::with_capacity(req.line_items.len());
+        for line_item_req in &req.line_items {
+            let line_amount = Self::calculate_line_item_amount(line_item_req);
+            subtotal += line_amount;
+
+            let line_tax: Decimal = match &line_item_req.tax_code {
+                Some(tax_code) => {
+                    TaxService::calculate_tax(&mut tx, company_id, tax_code, line_amount, req.estimate_date).await?
+                        .iter()
+                        .map(|t| t.tax_amount)
+                        .sum()
+                }
+                None => Decimal::ZERO,
+            };
+            line_taxes.push(line_tax);
+        }
+        let tax_amount: Decimal = line_taxes.iter().sum();
+        let total_amount = subtotal + tax_amount;
+
+        // Create estimate header
+        let estimate = sqlx::query_as::<_, Estimate>(
+            r#"
+            INSERT INTO estimates
+                (id, estimate_number, customer_id, estimate_date, expiry_date, subtotal,
+                 tax_amount, total_amount, status, customer_memo, billing_address,
+                 shipping_address, company_id, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, NOW(), NOW())
+            RETURNING *
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(&req.estimate_number)
+        .bind(req.customer_id)
+        .bind(req.estimate_date)
+        .bind(req.expiry_date)
+        .bind(subtotal)
+        .bind(tax_amount)
+        .bind(total_amount)
+        .bind(EstimateStatus::Draft.to_string())
+        .bind(&req.customer_memo)
+        .bind(&req.billing_address)
+        .bind(&req.shipping_address)
+        .bind(company_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => AppError::Conflict(
+                format!("Estimate number {} already exists", req.estimate_number)
+            ),
+            e => AppError::from(e),
+        })?;
+
+        // Create line items
+        let mut line_items = Vec::with_capacity(req.line_items.len());
+        for (index, (line_item_req, line_tax)) in req.line_items.iter().zip(line_taxes).enumerate() {
+            let line_item = sqlx::query_as::<_, EstimateLineItem>(
+                r#"
+                INSERT INTO estimate_line_items
+                    (id, estimate_id, line_number, item_description, quantity, unit_price,
+                     amount, discount_percent, tax_code, tax_amount, revenue_account_id,
+                     item_id, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, NOW(), NOW())
+                RETURNING *
+                "#,
+            )
+            .bind(Uuid::new_v4())
+            .bind(estimate.id)
+            .bind(index as i32 + 1) // Line numbers start at 1
+            .bind(&line_item_req.item_description)
+            .bind(line_item_req.quantity)
+            .bind(line_item_req.unit_price)
+            .bind(Self::calculate_line_item_amount(line_item_req))
+            .bind(line_item_req.discount_percent)
+            .bind(&line_item_req.tax_code)
+            .bind(line_tax)
+            .bind(line_item_req.revenue_account_id)
+            .bind(line_item_req.item_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            line_items.push(line_item);
+        }
+
+        tx.commit().await?;
+
+        Ok(EstimateWithLineItems {
+            estimate,
+            line_items,
+            invoices: Vec::new(),
+        })
+    }
+
+    /// Get an estimate with line items and the invoices created from it
+    pub async fn get_estimate(&self, pool: &PgPool, estimate_id: Uuid) -> Result<EstimateWithLineItems> {
+        let cache_key = format!("estimate:{}", estimate_id);
+        if let Ok(Some(cached)) = self.cache.get::<EstimateWithLineItems>(&cache_key).await
+            && cached.estimate.company_id == self.company_id
+        {
+            return Ok(cached);
+        }
+
+        let estimate = sqlx::query_as::<_, Estimate>(
+            "SELECT * FROM estimates WHERE id = $1 AND company_id IS NOT DISTINCT FROM $2"
+        )
+        .bind(estimate_id)
+        .bind(self.company_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Estimate with id {} not found", estimate_id)))?;
+
+        let line_items = sqlx::query_as::<_, EstimateLineItem>(
+            "SELECT * FROM estimate_line_items WHERE estimate_id = $1 ORDER BY line_number"
+        )
+        .bind(estimate_id)
+        .fetch_all(pool)
+        .await?;
+
+        let invoices = sqlx::query_as::<_, EstimateInvoice>(
+            "SELECT * FROM estimate_invoices WHERE estimate_id = $1 ORDER BY created_at"
+        )
+        .bind(estimate_id)
+        .fetch_all(pool)
+        .await?;
+
+        let result = EstimateWithLineItems {
+            estimate,
+            line_items,
+            invoices,
+        };
+
+        let _ = self.cache.set_with_ttl(&cache_key, &result, 600).await;
+
+        Ok(result)
+    }
+
+    /// List estimates, optionally by customer and status
+    pub async fn list_estimates(
+        &self,
+        pool: &PgPool,
+        customer_id: Option<Uuid>,
+        status: Option<EstimateStatus>,
+    ) -> Result<Vec<Estimate>> {
+        let estimates = sqlx::query_as::<_, Estimate>(
+            r#"
+            SELECT * FROM estimates
+            WHERE ($1::uuid IS NULL OR customer_id = $1)
+              AND ($2::varchar IS NULL OR status = $2)
+              AND company_id IS NOT DISTINCT FROM $3
+            ORDER BY estimate_date DESC, created_at DESC
+            "#
+        )
+        .bind(customer_id)
+        .bind(status.map(|s| s.to_string()))
+        .bind(self.company_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(estimates)
+    }
+
+    /// Move an estimate through draft -> sent -> accepted/declined/expired
+    pub async fn update_estimate_status(
+        &self,
+        pool: &PgPool,
+        estimate_id: Uuid,
+        new_status: EstimateStatus,
+    ) -> Result<Estimate> {
+        let mut tx = pool.begin().await?;
+
+        let estimate = Self::lock_estimate(&mut tx, self.company_id, estimate_id).await?;
+        Self::validate_status_transition(&estimate.status, &new_status)?;
+
+        if new_status == EstimateStatus::Accepted && estimate.expiry_date < Utc::now().date_naive() {
+            return Err(AppError::ValidationError(
+                format!("Estimate expired on {}", estimate.expiry_date)
+            ));
+        }
+
+        let updated = sqlx::query_as::<_, Estimate>(
+            "UPDATE estimates SET status = $1, updated_at = NOW() WHERE id = $2 RETURNING *"
+        )
+        .bind(new_status.to_string())
+        .bind(estimate_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        self.invalidate_estimate_cache(&estimate_id).await;
+
+        Ok(updated)
+    }
+
+    /// Mark sent estimates past their expiry date as expired
+    pub async fn expire_estimates(&self, pool: &PgPool, as_of_date: NaiveDate) -> Result<Vec<Estimate>> {
+        let expired = sqlx::query_as::<_, Estimate>(
+            r#"
+            UPDATE estimates
+            SET status = 'expired', updated_at = NOW()
+            WHERE status = 'sent' AND expiry_date < $1 AND company_id IS NOT DISTINCT FROM $2
+            RETURNING *
+            "#
+        )
+        .bind(as_of_date)
+        .bind(self.company_id)
+        .fetch_all(pool)
+        .await?;
+
+        for estimate in &expired {
+            self.invalidate_estimate_cache(&estimate.id).await;
+        }
+
+        Ok(expired)
+    }
+
+    /// Create an invoice from an accepted estimate, in full or partially for progress billing
+    pub async fn convert_to_invoice(
+        &self,
+        pool: &PgPool,
+        estimate_id: Uuid,
+        req: ConvertEstimateRequest,
+    ) -> Result<EstimateConversion> {
+        // Validate request
+        req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+        if req.percent.is_some() && req.lines.is_some() {
+            return Err(AppError::ValidationError(
+                "Specify either a percentage or line quantities, not both".to_string()
+            ));
+        }
+
+        let mut tx = pool.begin().await?;
+
+        // The estimate stays locked until the link is recorded, so concurrent
+        // conversions can't bill the same quantity twice
+        let estimate = Self::lock_estimate(&mut tx, self.company_id, estimate_id).await?;
+        if estimate.status != EstimateStatus::Accepted {
+            return Err(AppError::ValidationError(
+                "Only accepted estimates can be converted to invoices".to_string()
+            ));
+        }
+
+        let line_items = sqlx::query_as::<_, EstimateLineItem>(
+            "SELECT * FROM estimate_line_items WHERE estimate_id = $1 ORDER BY line_number FOR UPDATE"
+        )
+        .bind(estimate_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let quantities = Self::quantities_to_bill(&line_items, &req)?;
+
+        let mut invoice_lines = Vec::new();
+        for (line_item, quantity) in line_items.iter().zip(&quantities) {
+            if quantity.is_zero() {
+                continue;
+            }
+
+            sqlx::query(
+                "UPDATE estimate_line_items SET invoiced_quantity = invoiced_quantity + $1, updated_at = NOW() WHERE id = $2"
+            )
+            .bind(quantity)
+            .bind(line_item.id)
+            .execute(&mut *tx)
+            .await?;
+
+            invoice_lines.push(CreateInvoiceLineItemRequest {
+                line_number: invoice_lines.len() as i32 + 1,
+                item_description: line_item.item_description.clone(),
+                quantity: *quantity,
+                unit_price: line_item.unit_price,
+                discount_percent: line_item.discount_percent,
+                tax_code: line_item.tax_code.clone(),
+                revenue_account_id: line_item.revenue_account_id,
+                item_id: line_item.item_id,
+                dimension_value_ids: Vec::new(),
+            });
+        }
+
+        if invoice_lines.is_empty() {
+            return Err(AppError::ValidationError(
+                "Nothing left to invoice on this estimate".to_string()
+            ));
+        }
+
+        let invoice = self.invoice_service.create_invoice(pool, CreateInvoiceRequest {
+            invoice_number: req.invoice_number,
+            customer_id: estimate.customer_id,
+            invoice_date: req.invoice_date,
+            due_date: req.due_date,
+            ship_date: None,
+            customer_memo: estimate.customer_memo.clone(),
+            billing_address: estimate.billing_address.clone(),
+            shipping_address: estimate.shipping_address.clone(),
+            company_id: estimate.company_id,
+            ar_account_id: req.ar_account_id,
+            line_items: invoice_lines,
+        }).await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO estimate_invoices (id, estimate_id, invoice_id, amount, created_at)
+            VALUES ($1, $2, $3, $4, NOW())
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(estimate_id)
+        .bind(invoice.invoice.id)
+        .bind(invoice.invoice.total_amount)
+        .execute(&mut *tx)
+        .await?;
+
+        let estimate = sqlx::query_as::<_, Estimate>(
+            r#"
+            UPDATE estimates
+            SET invoiced_amount = invoiced_amount + $1, updated_at = NOW()
+            WHERE id = $2
+            RETURNING *
+            "#
+        )
+        .bind(invoice.invoice.total_amount)
+        .bind(estimate_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        self.invalidate_estimate_cache(&estimate_id).await;
+
+        Ok(EstimateConversion { estimate, invoice })
+    }
+
+    // Helper methods
+
+    async fn lock_estimate(conn: &mut PgConnection, company_id: Option<Uuid>, estimate_id: Uuid) -> Result<Estimate> {
+        sqlx::query_as::<_, Estimate>(
+            "SELECT * FROM estimates WHERE id = $1 AND company_id IS NOT DISTINCT FROM $2 FOR UPDATE"
+        )
+        .bind(estimate_id)
+        .bind(company_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Estimate with id {} not found", estimate_id)))
+    }
+
+    /// Quantity to bill on each estimate line, in line order
+    fn quantities_to_bill(line_items: &[EstimateLineItem], req: &ConvertEstimateRequest) -> Result<Vec<Decimal>> {
+        let remaining = |line: &EstimateLineItem| line.quantity - line.invoiced_quantity;
+
+        if let Some(percent) = req.percent {
+            if percent <= Decimal::ZERO || percent > Decimal::ONE_HUNDRED {
+                return Err(AppError::ValidationError(
+                    "Percent must be greater than 0 and at most 100".to_string()
+                ));
+            }
+
+            // Percent of the estimated quantity, capped at what's left to bill
+            return Ok(line_items.iter()
+                .map(|line| {
+                    let quantity = (line.quantity * percent / Decimal::ONE_HUNDRED)
+                        .round_dp_with_strategy(3, RoundingStrategy::MidpointAwayFromZero);
+                    quantity.min(remaining(line))
+                })
+                .collect());
+        }
+
+        let Some(lines) = &req.lines else {
+            return Ok(line_items.iter().map(remaining).collect());
+        };
+
+        let mut requested: HashMap<Uuid, Decimal> = HashMap::new();
+        for line in lines {
+            if line.quantity <= Decimal::ZERO {
+                return Err(AppError::ValidationError("Quantities to invoice must be positive".to_string()));
+            }
+            if !line_items.iter().any(|l| l.id == line.estimate_line_item_id) {
+                return Err(AppError::ValidationError(
+                    format!("Line {} is not on this estimate", line.estimate_line_item_id)
+                ));
+            }
+            *requested.entry(line.estimate_line_item_id).or_default() += line.quantity;
+        }
+
+        line_items.iter()
+            .map(|line| {
+                let quantity = requested.get(&line.id).copied().unwrap_or(Decimal::ZERO);
+                if quantity > remaining(line) {
+                    return Err(AppError::ValidationError(format!(
+                        "Line {} has only {} left to invoice", line.line_number, remaining(line)
+                    )));
+                }
+                Ok(quantity)
+            })
+            .collect()
+    }
+
+    fn calculate_line_item_amount(line_item: &CreateInvoiceLineItemRequest) -> Decimal {
+        let line_total = line_item.quantity * line_item.unit_price;
+
+        let discount_amount = match line_item.discount_percent {
+            Some(discount_percent) => line_total * (discount_percent / Decimal::ONE_HUNDRED),
+            None => Decimal::ZERO,
+        };
+
+        (line_total - discount_amount).round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero)
+    }
+
+    fn validate_status_transition(from: &EstimateStatus, to: &EstimateStatus) -> Result<()> {
+        use EstimateStatus::*;
+
+        match (from, to) {
+            (Draft, Sent | Accepted | Declined) => Ok(()),
+            (Sent, Accepted | Declined | Expired) => Ok(()),
+            _ => Err(AppError::ValidationError(
+                format!("Invalid status transition from {} to {}", from, to)
+            )),
+        }
+    }
+
+    async fn invalidate_estimate_cache(&self, estimate_id: &Uuid) {
+        let cache_key = format!("estimate:{}", estimate_id);
+        let _ = self.cache.delete(&cache_key).await;
+    }
+}
@@ -0,0 +1,389 @@
+use chrono::{Duration, NaiveDate};
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{
+    Contact, CreateInvoiceLineItemRequest, CreateInvoiceRequest, CustomerFinanceCharge, FinanceCharge,
+    FinanceChargeLine, FinanceChargeRun, FinanceChargeSettings, InvoiceStatus,
+    UpdateFinanceChargeSettingsRequest,
+};
+use crate::utils::{AppError, Result};
+use crate::utils::pdf::format_amount;
+use crate::services::{CacheService, CompanyRecord, CompanyService, InvoiceService};
+
+/// Finance-charge invoices are due this many days after the run date
+const FINANCE_CHARGE_TERMS_DAYS: i64 = 30;
+
+/// Overdue invoice that may be charged interest
+#[derive(sqlx::FromRow)]
+struct ChargeableInvoice {
+    invoice_id: Uuid,
+    invoice_number: String,
+    customer_id: Uuid,
+    customer_name: String,
+    due_date: NaiveDate,
+    balance: Decimal,
+    /// Due date, or the end of the last charged period
+    period_start: NaiveDate,
+    /// Share of earlier finance charges on this invoice still unpaid
+    unpaid_charges: Decimal,
+}
+
+#[derive(Clone)]
+pub struct FinanceChargeService {
+    cache: CacheService,
+    invoice_service: InvoiceService,
+    company_id: Option<Uuid>,
+}
+
+impl FinanceChargeService {
+    pub fn new_with_cache(cache: CacheService) -> Self {
+        Self {
+            invoice_service: InvoiceService::new_with_cache(cache.clone()),
+            cache,
+            company_id: None,
+        }
+    }
+
+    /// Scope the service to a company's records
+    pub fn for_company(&self, company_id: Option<Uuid>) -> Self {
+        Self {
+            cache: self.cache.clone(),
+            invoice_service: self.invoice_service.for_company(company_id),
+            company_id,
+        }
+    }
+
+    /// Get a company's finance charge settings. They name the company's own accounts,
+    /// so there is no fallback to another company's.
+    pub async fn get_settings(&self, pool: &PgPool, company_id: Option<Uuid>) -> Result<FinanceChargeSettings> {
+        let settings = sqlx::query_as::<_, FinanceChargeSettings>(
+            "SELECT * FROM finance_charge_settings WHERE company_id IS NOT DISTINCT FROM $1"
+        )
+        .bind(company_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Finance charge settings not configured".to_string()))?;
+
+        Ok(settings)
+    }
+
+    /// Create or replace a company's finance charge settings
+    pub async fn update_settings(&self, pool: &PgPool, req: UpdateFinanceChargeSettingsRequest) -> Result<FinanceChargeSettings> {
+        req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+        if req.monthly_rate <= Decimal::ZERO || req.monthly_rate > Decimal::from(100) {
+            return Err(AppError::ValidationError(
+                "monthly_rate must be a percentage between 0 and 100".to_string()
+            ));
+        }
+        if req.minimum_charge < Decimal::ZERO {
+            return Err(AppError::ValidationError(
+                "minimum_charge cannot be negative".to_string()
+            ));
+        }
+
+        let company_id = CompanyService::record_company(self.company_id, req.company_id)?;
+        let mut conn = pool.acquire().await?;
+        CompanyService::ensure_in_company(
+            &mut conn, company_id, CompanyRecord::Account, &[req.income_account_id, req.ar_account_id],
+        ).await?;
+
+        let settings = sqlx::query_as::<_, FinanceChargeSettings>(
+            r#"
+            INSERT INTO finance_charge_settings
+                (id, company_id, monthly_rate, minimum_charge, grace_days, compound,
+                 income_account_id, ar_account_id, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW(), NOW())
+            ON CONFLICT (company_id) DO UPDATE
+            SET monthly_rate = EXCLUDED.monthly_rate,
+                minimum_charge = EXCLUDED.minimum_charge,
+                grace_days = EXCLUDED.grace_days,
+                compound = EXCLUDED.compound,
+                income_account_id = EXCLUDED.income_account_id,
+                ar_account_id = EXCLUDED.ar_account_id,
+                updated_at = NOW()
+            RETURNING *
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(company_id)
+        .bind(req.monthly_rate)
+        .bind(req.minimum_charge.round_dp(2))
+        .bind(req.grace_days)
+        .bind(req.compound)
+        .bind(req.income_account_id)
+        .bind(req.ar_account_id)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+                AppError::ValidationError("Income or AR account not found".to_string())
+            }
+            e => AppError::from(e),
+        })?;
+
+        Ok(settings)
+    }
+
+    /// Calculate interest on overdue invoices as of a date and, unless previewing,
+    /// bill it on one finance-charge invoice per customer
+    pub async fn run(
+        &self,
+        pool: &PgPool,
+        as_of_date: NaiveDate,
+        company_id: Option<Uuid>,
+        preview: bool,
+    ) -> Result<FinanceChargeRun> {
+        let company_id = CompanyService::record_company(self.company_id, company_id)?;
+        let settings = self.get_settings(pool, company_id).await?;
+        let mut customers = self.calculate(pool, &settings, as_of_date, company_id).await?;
+
+        if !preview {
+            for customer in customers.iter_mut() {
+                let invoice_id = self.bill_customer(pool, &settings, as_of_date, company_id, customer).await?;
+                customer.finance_charge_invoice_id = Some(invoice_id);
+            }
+        }
+
+        let total = customers.iter().map(|c| c.total).sum();
+
+        Ok(FinanceChargeRun {
+            as_of_date,
+            preview,
+            customers,
+            total,
+        })
+    }
+
+    /// Exempt a customer from (or return them to) finance charges
+    pub async fn set_exempt(&self, pool: &PgPool, customer_id: Uuid, exempt: bool) -> Result<Contact> {
+        let contact = sqlx::query_as::<_, Contact>(
+            r#"
+            UPDATE contacts
+            SET finance_charge_exempt = $2, updated_at = NOW()
+            WHERE id = $1 AND contact_type = 'Customer' AND company_id IS NOT DISTINCT FROM $3
+            RETURNING id, contact_type, name, email, phone, billing_address, shipping_address, company_id, dunning_opt_out, finance_charge_exempt, created_at, updated_at
+            "#
+        )
+        .bind(customer_id)
+        .bind(exempt)
+        .bind(self.company_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Customer with id {} not found", customer_id)))?;
+
+        let _ = self.cache.delete(&format!("contact:data:{}", customer_id)).await;
+
+        Ok(contact)
+    }
+
+    /// List assessed finance charges, newest first
+    pub async fn list_finance_charges(
+        &self,
+        pool: &PgPool,
+        customer_id: Option<Uuid>,
+        invoice_id: Option<Uuid>,
+    ) -> Result<Vec<FinanceCharge>> {
+        let charges = sqlx::query_as::<_, FinanceCharge>(
+            r#"
+            SELECT fc.* FROM finance_charges fc
+            INNER JOIN invoices i ON i.id = fc.invoice_id
+            WHERE ($1::uuid IS NULL OR fc.customer_id = $1)
+              AND ($2::uuid IS NULL OR fc.invoice_id = $2 OR fc.finance_charge_invoice_id = $2)
+              AND i.company_id IS NOT DISTINCT FROM $3
+            ORDER BY fc.period_end DESC, fc.created_at DESC
+            "#
+        )
+        .bind(customer_id)
+        .bind(invoice_id)
+        .bind(self.company_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(charges)
+    }
+
+    /// Interest per overdue invoice since its due date or last charge, grouped by customer
+    async fn calculate(
+        &self,
+        pool: &PgPool,
+        settings: &FinanceChargeSettings,
+        as_of_date: NaiveDate,
+        company_id: Option<Uuid>,
+    ) -> Result<Vec<CustomerFinanceCharge>> {
+        // Finance-charge invoices are never charged themselves; compounding
+        // adds their unpaid share to the base of the invoice they came from
+        let invoices = sqlx::query_as::<_, ChargeableInvoice>(
+            r#"
+            SELECT i.id AS invoice_id, i.invoice_number, i.customer_id, c.name AS customer_name,
+                   i.due_date, i.balance,
+                   COALESCE(charged.period_end, i.due_date) AS period_start,
+                   COALESCE(charged.unpaid, 0) AS unpaid_charges
+            FROM invoices i
+            INNER JOIN contacts c ON c.id = i.customer_id
+            LEFT JOIN LATERAL (
+                SELECT MAX(fc.period_end) AS period_end,
+                       SUM(ROUND(fc.amount * fi.balance / NULLIF(fi.total_amount, 0), 2)) AS unpaid
+                FROM finance_charges fc
+                INNER JOIN invoices fi ON fi.id = fc.finance_charge_invoice_id
+                WHERE fc.invoice_id = i.id AND fi.status <> 'void'
+            ) charged ON TRUE
+            WHERE i.status IN ('sent', 'partial', 'overdue')
+              AND i.balance > 0
+              AND i.company_id IS NOT DISTINCT FROM $2
+              AND i.due_date + $3::integer < $1
+              AND NOT c.finance_charge_exempt
+              AND NOT EXISTS (SELECT 1 FROM finance_charges fc WHERE fc.finance_charge_invoice_id = i.id)
+            ORDER BY c.name, c.id, i.due_date, i.invoice_number
+            "#
+        )
+        .bind(as_of_date)
+        .bind(company_id)
+        .bind(settings.grace_days)
+        .fetch_all(pool)
+        .await?;
+
+        // Monthly rate as a daily rate: percent * 12 months / 365 days
+        let daily_rate = settings.monthly_rate / Decimal::from(100) * Decimal::from(12) / Decimal::from(365);
+
+        let mut customers: Vec<CustomerFinanceCharge> = Vec::new();
+        for invoice in invoices {
+            let days = (as_of_date - invoice.period_start).num_days();
+            if days <= 0 {
+                continue;
+            }
+
+            let base_amount = if settings.compound {
+                invoice.balance + invoice.unpaid_charges
+            } else {
+                invoice.balance
+            };
+            let amount = (base_amount * daily_rate * Decimal::from(days)).round_dp(2);
+            if amount <= Decimal::ZERO {
+                continue;
+            }
+
+            let line = FinanceChargeLine {
+                invoice_id: invoice.invoice_id,
+                invoice_number: invoice.invoice_number,
+                due_date: invoice.due_date,
+                period_start: invoice.period_start,
+                days: days as i32,
+                base_amount,
+                amount,
+            };
+
+            match customers.last_mut() {
+                Some(customer) if customer.customer_id == invoice.customer_id => customer.lines.push(line),
+                _ => customers.push(CustomerFinanceCharge {
+                    customer_id: invoice.customer_id,
+                    customer_name: invoice.customer_name,
+                    lines: vec![line],
+                    interest: Decimal::ZERO,
+                    minimum_applied: false,
+                    total: Decimal::ZERO,
+                    finance_charge_invoice_id: None,
+                }),
+            }
+        }
+
+        for customer in customers.iter_mut() {
+            customer.interest = customer.lines.iter().map(|l| l.amount).sum();
+            customer.minimum_applied = customer.interest < settings.minimum_charge;
+            customer.total = customer.interest.max(settings.minimum_charge);
+        }
+
+        Ok(customers)
+    }
+
+    /// Create and post the finance-charge invoice for one customer
+    async fn bill_customer(
+        &self,
+        pool: &PgPool,
+        settings: &FinanceChargeSettings,
+        as_of_date: NaiveDate,
+        company_id: Option<Uuid>,
+        customer: &CustomerFinanceCharge,
+    ) -> Result<Uuid> {
+        let rate = settings.monthly_rate.normalize();
+        let mut line_items: Vec<CreateInvoiceLineItemRequest> = customer.lines
+            .iter()
+            .enumerate()
+            .map(|(index, line)| CreateInvoiceLineItemRequest {
+                line_number: index as i32 + 1,
+                item_description: format!(
+                    "Finance charge on invoice {}: {} at {}% per month for {} days ({} to {})",
+                    line.invoice_number, format_amount(line.base_amount), rate, line.days,
+                    line.period_start, as_of_date,
+                ),
+                quantity: Decimal::ONE,
+                unit_price: line.amount,
+                discount_percent: None,
+                tax_code: None,
+                revenue_account_id: settings.income_account_id,
+                item_id: None,
+                dimension_value_ids: Vec::new(),
+            })
+            .collect();
+
+        if customer.minimum_applied {
+            line_items.push(CreateInvoiceLineItemRequest {
+                line_number: line_items.len() as i32 + 1,
+                item_description: "Minimum finance charge adjustment".to_string(),
+                quantity: Decimal::ONE,
+                unit_price: customer.total - customer.interest,
+                discount_percent: None,
+                tax_code: None,
+                revenue_account_id: settings.income_account_id,
+                item_id: None,
+                dimension_value_ids: Vec::new(),
+            });
+        }
+
+        let invoice = self.invoice_service.create_invoice(pool, CreateInvoiceRequest {
+            invoice_number: None,
+            customer_id: customer.customer_id,
+            invoice_date: as_of_date,
+            due_date: as_of_date + Duration::days(FINANCE_CHARGE_TERMS_DAYS),
+            ship_date: None,
+            customer_memo: Some(format!("Finance charges on overdue invoices as of {}", as_of_date)),
+            billing_address: None,
+            shipping_address: None,
+            company_id,
+            ar_account_id: Some(settings.ar_account_id),
+            line_items,
+        }).await?;
+
+        // Posting moves the interest to the income account
+        self.invoice_service
+            .update_invoice_status(pool, invoice.invoice.id, InvoiceStatus::Sent)
+            .await?;
+
+        for line in &customer.lines {
+            sqlx::query(
+                r#"
+                INSERT INTO finance_charges
+                    (id, invoice_id, finance_charge_invoice_id, customer_id, period_start, period_end,
+                     days, base_amount, amount, created_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, NOW())
+                "#
+            )
+            .bind(Uuid::new_v4())
+            .bind(line.invoice_id)
+            .bind(invoice.invoice.id)
+            .bind(customer.customer_id)
+            .bind(line.period_start)
+            .bind(as_of_date)
+            .bind(line.days)
+            .bind(line.base_amount)
+            .bind(line.amount)
+            .execute(pool)
+            .await?;
+        }
+
+        Ok(invoice.invoice.id)
+    }
+}
@@ -20,6 +20,13 @@ impl ImportService {
         }
     }
 
+    /// Scope the service to a company's records
+    pub fn for_company(&self, company_id: Option<Uuid>) -> Self {
+        Self {
+            account_service: self.account_service.for_company(company_id),
+        }
+    }
+
     /// Import Chart of Accounts from CSV data
     pub async fn import_accounts_from_csv(
         &self,
@@ -0,0 +1,765 @@
+use chrono::NaiveDate;
+use rust_decimal::{Decimal, RoundingStrategy};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{
+    Item, ItemType, CreateItemRequest, InventoryCostLayer, InventoryCostingMethod,
+    InventoryMovement, InventoryMovementType, CreateStockAdjustmentRequest,
+    InventoryValuationEntry, InventoryValuationReport, CreateTransactionRequest,
+    CreateLineItemRequest, JournalType, DateRequest,
+};
+use crate::utils::{AppError, Result};
+use crate::services::{CacheService, CompanyRecord, CompanyService, TransactionService};
+
+#[derive(Clone)]
+pub struct InventoryService {
+    cache: CacheService,
+    company_id: Option<Uuid>,
+}
+
+impl InventoryService {
+    pub fn new_with_cache(cache: CacheService) -> Self {
+        Self { cache, company_id: None }
+    }
+
+    /// Scope the service to a company's records
+    pub fn for_company(&self, company_id: Option<Uuid>) -> Self {
+        Self { company_id, ..self.clone() }
+    }
+
+    /// Create a new item (service, inventory or non-inventory)
+    pub async fn create_item(&self, pool: &PgPool, req: CreateItemRequest) -> Result<Item> {
+        req.validate()?;
+
+        if req.item_type == ItemType::Inventory
+            && (req.asset_account_id.is_none() || req.expense_account_id.is_none())
+        {
+            return Err(AppError::ValidationError(
+                "Inventory items require an asset account and an expense (COGS) account".to_string()
+            ));
+        }
+
+        let company_id = CompanyService::record_company(self.company_id, req.company_id)?;
+        let account_ids: Vec<Uuid> = [req.income_account_id, req.expense_account_id, req.asset_account_id]
+            .into_iter()
+            .flatten()
+            .collect();
+        let mut conn = pool.acquire().await?;
+        CompanyService::ensure_in_company(&mut conn, company_id, CompanyRecord::Account, &account_ids).await?;
+
+        let item = sqlx::query_as::<_, Item>(
+            r#"
+            INSERT INTO items
+                (id, name, sku, item_type, description, unit_price, purchase_cost, quantity_on_hand,
+                 income_account_id, expense_account_id, asset_account_id, active, taxable, company_id,
+                 created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, 0, $8, $9, $10, true, $11, $12, NOW(), NOW())
+            RETURNING *
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(&req.name)
+        .bind(&req.sku)
+        .bind(&req.item_type)
+        .bind(&req.description)
+        .bind(req.unit_price)
+        .bind(req.purchase_cost)
+        .bind(req.income_account_id)
+        .bind(req.expense_account_id)
+        .bind(req.asset_account_id)
+        .bind(req.taxable.unwrap_or(false))
+        .bind(company_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(item)
+    }
+
+    /// List items, optionally filtered by type
+    pub async fn list_items(&self, pool: &PgPool, item_type: Option<ItemType>) -> Result<Vec<Item>> {
+        let items = match item_type {
+            Some(item_type) => {
+                sqlx::query_as::<_, Item>(
+                    "SELECT * FROM items WHERE item_type = $1 AND company_id IS NOT DISTINCT FROM $2 ORDER BY name"
+                )
+                .bind(item_type)
+                .bind(self.company_id)
+                .fetch_all(pool)
+                    .await?
+            }
+            None => {
+                sqlx::query_as::<_, Item>("SELECT * FROM items WHERE company_id IS NOT DISTINCT FROM $1 ORDER BY name")
+                    .bind(self.company_id)
+                    .fetch_all(pool)
+                    .await?
+            }
+        };
+
+        Ok(items)
+    }
+
+    /// Get item by ID
+    pub async fn get_item(&self, pool: &PgPool, item_id: Uuid) -> Result<Item> {
+        let cache_key = format!("item:{}", item_id);
+        if let Ok(Some(cached)) = self.cache.get::<Item>(&cache_key).await
+            && cached.company_id == self.company_id
+        {
+            return Ok(cached);
+        }
+
+        let item = sqlx::query_as::<_, Item>("SELECT * FROM items WHERE id = $1 AND company_id IS NOT DISTINCT FROM $2")
+            .bind(item_id)
+            .bind(self.company_id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Item with id {} not found", item_id)))?;
+
+        let _ = self.cache.set_with_ttl(&cache_key, &item, 300).await;
+
+        Ok(item)
+    }
+
+    /// Open cost layers for an item, oldest first
+    pub async fn list_cost_layers(&self, pool: &PgPool, item_id: Uuid) -> Result<Vec<InventoryCostLayer>> {
+        let layers = sqlx::query_as::<_, InventoryCostLayer>(
+            r#"
+            SELECT * FROM inventory_cost_layers
+            WHERE item_id = $1 AND quantity_remaining > 0 AND company_id IS NOT DISTINCT FROM $2
+            ORDER BY received_date, created_at
+            "#
+        )
+        .bind(item_id)
+        .bind(self.company_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(layers)
+    }
+
+    /// Stock movement history for an item
+    pub async fn list_movements(&self, pool: &PgPool, item_id: Uuid) -> Result<Vec<InventoryMovement>> {
+        let movements = sqlx::query_as::<_, InventoryMovement>(
+            r#"
+            SELECT * FROM inventory_movements
+            WHERE item_id = $1 AND company_id IS NOT DISTINCT FROM $2
+            ORDER BY movement_date, created_at
+            "#
+        )
+        .bind(item_id)
+        .bind(self.company_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(movements)
+    }
+
+    /// Change the costing method used for a company's inventory
+    pub async fn set_costing_method(
+        &self,
+        pool: &PgPool,
+        company_id: Uuid,
+        method: InventoryCostingMethod,
+    ) -> Result<()> {
+        let result = sqlx::query(
+            "UPDATE companies SET inventory_costing_method = $1, updated_at = NOW() WHERE id = $2"
+        )
+        .bind(method)
+        .bind(company_id)
+        .execute(pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("Company with id {} not found", company_id)));
+        }
+
+        Ok(())
+    }
+
+    /// Adjust stock on hand (count corrections, shrinkage, write-ups)
+    pub async fn create_stock_adjustment(
+        &self,
+        pool: &PgPool,
+        req: CreateStockAdjustmentRequest,
+        created_by: Option<Uuid>,
+    ) -> Result<InventoryMovement> {
+        req.validate()?;
+
+        if req.quantity_change.is_zero() {
+            return Err(AppError::ValidationError("Quantity change cannot be zero".to_string()));
+        }
+
+        let mut tx = pool.begin().await?;
+
+        CompanyService::ensure_in_company(&mut tx, self.company_id, CompanyRecord::Item, &[req.item_id]).await?;
+        CompanyService::ensure_in_company(&mut tx, self.company_id, CompanyRecord::Account, &[req.adjustment_account_id]).await?;
+
+        let item = Self::lock_inventory_item(&mut tx, req.item_id).await?
+            .ok_or_else(|| AppError::ValidationError(
+                format!("Item {} is not an inventory item", req.item_id)
+            ))?;
+        let asset_account_id = Self::required_account(&item, item.asset_account_id)?;
+        let description = req.memo.clone()
+            .unwrap_or_else(|| format!("Inventory adjustment: {}", item.name));
+
+        let (quantity, unit_cost, total_cost, lines) = if req.quantity_change > Decimal::ZERO {
+            let unit_cost = match req.unit_cost {
+                Some(cost) if cost < Decimal::ZERO => {
+                    return Err(AppError::ValidationError("Unit cost cannot be negative".to_string()));
+                }
+                Some(cost) => cost,
+                None => Self::current_average_cost(&mut tx, item.id).await?,
+            };
+            Self::add_cost_layer(&mut tx, &item, req.quantity_change, unit_cost, req.adjustment_date, "adjustment", None).await?;
+            let total_cost = round_money(req.quantity_change * unit_cost);
+
+            let lines = vec![
+                CreateLineItemRequest::debit(asset_account_id, &description, total_cost),
+                CreateLineItemRequest::credit(req.adjustment_account_id, &description, total_cost),
+            ];
+            (req.quantity_change, unit_cost, total_cost, lines)
+        } else {
+            let quantity = -req.quantity_change;
+            let total_cost = Self::consume_layers(&mut tx, &item, quantity).await?;
+            let unit_cost = unit_cost_of(total_cost, quantity);
+
+            let lines = vec![
+                CreateLineItemRequest::debit(req.adjustment_account_id, &description, total_cost),
+                CreateLineItemRequest::credit(asset_account_id, &description, total_cost),
+            ];
+            (req.quantity_change, unit_cost, -total_cost, lines)
+        };
+
+        let transaction_id = if total_cost.is_zero() {
+            None
+        } else {
+            let entry = CreateTransactionRequest {
+                transaction_date: req.adjustment_date,
+                description: Some(description),
+                reference_number: None,
+                entry_number: None,
+                contact_id: None,
+                company_id: item.company_id,
+                journal_type: Some(JournalType::General),
+                line_items: lines,
+            };
+            Some(TransactionService::post_entry(&mut tx, &entry, created_by).await?)
+        };
+
+        let movement = Self::insert_movement(
+            &mut tx,
+            &item,
+            MovementRecord {
+                movement_date: req.adjustment_date,
+                movement_type: InventoryMovementType::Adjustment,
+                quantity,
+                unit_cost,
+                total_cost,
+                source_type: Some("adjustment"),
+                source_id: None,
+                transaction_id,
+                memo: req.memo.clone(),
+                created_by,
+            },
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        self.invalidate_inventory_cache(item.id).await;
+
+        Ok(movement)
+    }
+
+    /// Inventory valuation (quantity and cost on hand) as of a date
+    pub async fn generate_valuation_report(&self, pool: &PgPool, req: DateRequest) -> Result<InventoryValuationReport> {
+        let entries = sqlx::query_as::<_, InventoryValuationEntry>(
+            r#"
+            SELECT
+                i.id as item_id,
+                i.name as item_name,
+                i.sku,
+                COALESCE(SUM(m.quantity), 0) as quantity_on_hand,
+                COALESCE(SUM(m.total_cost), 0) as total_value,
+                CASE
+                    WHEN COALESCE(SUM(m.quantity), 0) > 0 THEN ROUND(SUM(m.total_cost) / SUM(m.quantity), 4)
+                    ELSE 0
+                END as average_unit_cost
+            FROM items i
+            LEFT JOIN inventory_movements m ON m.item_id = i.id
+                AND m.movement_date <= $1
+            WHERE i.item_type = 'Inventory' AND i.company_id IS NOT DISTINCT FROM $2
+            GROUP BY i.id, i.name, i.sku
+            ORDER BY i.name
+            "#
+        )
+        .bind(req.as_of_date)
+        .bind(self.company_id)
+        .fetch_all(pool)
+        .await?;
+
+        let total_value: Decimal = entries.iter().map(|e| e.total_value).sum();
+
+        Ok(InventoryValuationReport {
+            as_of_date: req.as_of_date,
+            total_value,
+            entries,
+        })
+    }
+
+    // Sub-ledger hooks used by invoicing and purchasing. They run on the
+    // caller's database transaction so stock and documents stay in step.
+
+    /// Account a bill line for the item is debited to: purchases of inventory items are
+    /// capitalized to their asset account and only expensed as COGS when sold. Returns
+    /// `None` for items that are not tracked as inventory.
+    pub async fn purchase_account(conn: &mut PgConnection, item_id: Uuid) -> Result<Option<Uuid>> {
+        let Some(item) = Self::lock_inventory_item(conn, item_id).await? else {
+            return Ok(None);
+        };
+
+        Self::required_account(&item, item.asset_account_id).map(Some)
+    }
+
+    /// Receive purchased stock from a bill line. Returns `None` for items
+    /// that are not tracked as inventory.
+    pub async fn record_purchase(
+        conn: &mut PgConnection,
+        item_id: Uuid,
+        quantity: Decimal,
+        amount: Decimal,
+        bill_date: NaiveDate,
+        bill_id: Uuid,
+        created_by: Option<Uuid>,
+    ) -> Result<Option<InventoryMovement>> {
+        let Some(item) = Self::lock_inventory_item(conn, item_id).await? else {
+            return Ok(None);
+        };
+
+        if quantity <= Decimal::ZERO {
+            return Err(AppError::ValidationError(
+                format!("Bill lines for inventory item '{}' require a positive quantity", item.name)
+            ));
+        }
+
+        let unit_cost = (amount / quantity).round_dp_with_strategy(4, RoundingStrategy::MidpointAwayFromZero);
+        Self::add_cost_layer(conn, &item, quantity, unit_cost, bill_date, "bill", Some(bill_id)).await?;
+
+        let movement = Self::insert_movement(
+            conn,
+            &item,
+            MovementRecord {
+                movement_date: bill_date,
+                movement_type: InventoryMovementType::Purchase,
+                quantity,
+                unit_cost,
+                total_cost: amount,
+                source_type: Some("bill"),
+                source_id: Some(bill_id),
+                transaction_id: None,
+                memo: None,
+                created_by,
+            },
+        )
+        .await?;
+
+        Ok(Some(movement))
+    }
+
+    /// Relieve stock sold on an invoice line and post cost of goods sold.
+    /// Returns `None` for items that are not tracked as inventory.
+    pub async fn record_sale(
+        conn: &mut PgConnection,
+        item_id: Uuid,
+        quantity: Decimal,
+        invoice_date: NaiveDate,
+        invoice_id: Uuid,
+        invoice_number: &str,
+        created_by: Option<Uuid>,
+    ) -> Result<Option<InventoryMovement>> {
+        let Some(item) = Self::lock_inventory_item(conn, item_id).await? else {
+            return Ok(None);
+        };
+
+        if quantity <= Decimal::ZERO {
+            return Err(AppError::ValidationError(
+                format!("Invoice lines for inventory item '{}' require a positive quantity", item.name)
+            ));
+        }
+
+        let asset_account_id = Self::required_account(&item, item.asset_account_id)?;
+        let cogs_account_id = Self::required_account(&item, item.expense_account_id)?;
+
+        let total_cost = Self::consume_layers(conn, &item, quantity).await?;
+        let description = format!("COGS: {} x {} (invoice {})", quantity.normalize(), item.name, invoice_number);
+
+        let transaction_id = if total_cost.is_zero() {
+            None
+        } else {
+            let entry = CreateTransactionRequest {
+                transaction_date: invoice_date,
+                description: Some(description.clone()),
+                reference_number: Some(invoice_number.to_string()),
+                entry_number: None,
+                contact_id: None,
+                company_id: item.company_id,
+                journal_type: Some(JournalType::Sales),
+                line_items: vec![
+                    CreateLineItemRequest::debit(cogs_account_id, &description, total_cost),
+                    CreateLineItemRequest::credit(asset_account_id, &description, total_cost),
+                ],
+            };
+            Some(TransactionService::post_entry(conn, &entry, created_by).await?)
+        };
+
+        let movement = Self::insert_movement(
+            conn,
+            &item,
+            MovementRecord {
+                movement_date: invoice_date,
+                movement_type: InventoryMovementType::Sale,
+                quantity: -quantity,
+                unit_cost: unit_cost_of(total_cost, quantity),
+                total_cost: -total_cost,
+                source_type: Some("invoice"),
+                source_id: Some(invoice_id),
+                transaction_id,
+                memo: None,
+                created_by,
+            },
+        )
+        .await?;
+
+        Ok(Some(movement))
+    }
+
+    /// Return stock relieved by an invoice (e.g. when the invoice is voided),
+    /// reversing its cost of goods sold at the original cost.
+    pub async fn reverse_sales(
+        conn: &mut PgConnection,
+        invoice_id: Uuid,
+        reversal_date: NaiveDate,
+        created_by: Option<Uuid>,
+    ) -> Result<Vec<InventoryMovement>> {
+        let sales = sqlx::query_as::<_, InventoryMovement>(
+            r#"
+            SELECT * FROM inventory_movements m
+            WHERE m.source_type = 'invoice' AND m.source_id = $1 AND m.movement_type = 'sale'
+              AND NOT EXISTS (
+                  SELECT 1 FROM inventory_movements r
+                  WHERE r.source_type = 'invoice' AND r.source_id = $1 AND r.movement_type = 'sale_reversal'
+              )
+            ORDER BY m.created_at
+            "#
+        )
+        .bind(invoice_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut reversals = Vec::new();
+        for sale in sales {
+            let item = Self::lock_inventory_item(conn, sale.item_id).await?
+                .ok_or_else(|| AppError::InternalError(format!("Item {} is no longer an inventory item", sale.item_id)))?;
+
+            let quantity = -sale.quantity;
+            let total_cost = -sale.total_cost;
+            Self::add_cost_layer(conn, &item, quantity, sale.unit_cost, reversal_date, "invoice_void", Some(invoice_id)).await?;
+
+            let transaction_id = if total_cost.is_zero() {
+                None
+            } else {
+                let description = format!("COGS reversal: {} x {}", quantity.normalize(), item.name);
+                let entry = CreateTransactionRequest {
+                    transaction_date: reversal_date,
+                    description: Some(description.clone()),
+                    reference_number: None,
+                    entry_number: None,
+                    contact_id: None,
+                    company_id: item.company_id,
+                    journal_type: Some(JournalType::Sales),
+                    line_items: vec![
+                        CreateLineItemRequest::debit(Self::required_account(&item, item.asset_account_id)?, &description, total_cost),
+                        CreateLineItemRequest::credit(Self::required_account(&item, item.expense_account_id)?, &description, total_cost),
+                    ],
+                };
+                Some(TransactionService::post_entry(conn, &entry, created_by).await?)
+            };
+
+            let movement = Self::insert_movement(
+                conn,
+                &item,
+                MovementRecord {
+                    movement_date: reversal_date,
+                    movement_type: InventoryMovementType::SaleReversal,
+                    quantity,
+                    unit_cost: sale.unit_cost,
+                    total_cost,
+                    source_type: Some("invoice"),
+                    source_id: Some(invoice_id),
+                    transaction_id,
+                    memo: None,
+                    created_by,
+                },
+            )
+            .await?;
+
+            reversals.push(movement);
+        }
+
+        Ok(reversals)
+    }
+
+    /// Remove the stock received on a bill (e.g. when the bill is deleted).
+    /// Fails if any of the bill's stock has already been sold or adjusted out.
+    pub async fn remove_purchases(conn: &mut PgConnection, bill_id: Uuid) -> Result<u64> {
+        let layers = sqlx::query_as::<_, InventoryCostLayer>(
+            "SELECT * FROM inventory_cost_layers WHERE source_type = 'bill' AND source_id = $1 FOR UPDATE"
+        )
+        .bind(bill_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        if layers.iter().any(|l| l.quantity_remaining < l.quantity_received) {
+            return Err(AppError::ValidationError(
+                "Cannot delete bill: inventory received on it has already been consumed".to_string()
+            ));
+        }
+
+        for layer in &layers {
+            sqlx::query(
+                "UPDATE items SET quantity_on_hand = COALESCE(quantity_on_hand, 0) - $1, updated_at = NOW() WHERE id = $2"
+            )
+            .bind(layer.quantity_received)
+            .bind(layer.item_id)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        sqlx::query("DELETE FROM inventory_movements WHERE source_type = 'bill' AND source_id = $1")
+            .bind(bill_id)
+            .execute(&mut *conn)
+            .await?;
+
+        sqlx::query("DELETE FROM inventory_cost_layers WHERE source_type = 'bill' AND source_id = $1")
+            .bind(bill_id)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(layers.len() as u64)
+    }
+
+    // Helper methods
+
+    /// Lock an item row; returns `None` if the item is not an inventory item
+    async fn lock_inventory_item(conn: &mut PgConnection, item_id: Uuid) -> Result<Option<Item>> {
+        let item = sqlx::query_as::<_, Item>("SELECT * FROM items WHERE id = $1 FOR UPDATE")
+            .bind(item_id)
+            .fetch_optional(&mut *conn)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Item with id {} not found", item_id)))?;
+
+        if item.item_type != ItemType::Inventory {
+            return Ok(None);
+        }
+
+        Ok(Some(item))
+    }
+
+    fn required_account(item: &Item, account_id: Option<Uuid>) -> Result<Uuid> {
+        account_id.ok_or_else(|| AppError::ValidationError(format!(
+            "Inventory item '{}' requires both an asset account and an expense (COGS) account",
+            item.name
+        )))
+    }
+
+    async fn costing_method(conn: &mut PgConnection, company_id: Option<Uuid>) -> Result<InventoryCostingMethod> {
+        let Some(company_id) = company_id else {
+            return Ok(InventoryCostingMethod::Fifo);
+        };
+
+        let method = sqlx::query_scalar::<_, InventoryCostingMethod>(
+            "SELECT inventory_costing_method FROM companies WHERE id = $1"
+        )
+        .bind(company_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        Ok(method.unwrap_or(InventoryCostingMethod::Fifo))
+    }
+
+    /// Quantity and cost of the stock on hand, from the item's movements
+    async fn stock_on_hand(conn: &mut PgConnection, item_id: Uuid) -> Result<(Decimal, Decimal)> {
+        let stock = sqlx::query_as(
+            "SELECT COALESCE(SUM(quantity), 0), COALESCE(SUM(total_cost), 0) FROM inventory_movements WHERE item_id = $1"
+        )
+        .bind(item_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(stock)
+    }
+
+    /// Weighted average unit cost of the stock on hand
+    async fn current_average_cost(conn: &mut PgConnection, item_id: Uuid) -> Result<Decimal> {
+        let (quantity, value) = Self::stock_on_hand(conn, item_id).await?;
+        if quantity <= Decimal::ZERO {
+            return Ok(Decimal::ZERO);
+        }
+
+        Ok(unit_cost_of(value, quantity))
+    }
+
+    async fn add_cost_layer(
+        conn: &mut PgConnection,
+        item: &Item,
+        quantity: Decimal,
+        unit_cost: Decimal,
+        received_date: NaiveDate,
+        source_type: &str,
+        source_id: Option<Uuid>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO inventory_cost_layers
+                (id, item_id, source_type, source_id, received_date, quantity_received,
+                 quantity_remaining, unit_cost, company_id, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $6, $7, $8, NOW(), NOW())
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(item.id)
+        .bind(source_type)
+        .bind(source_id)
+        .bind(received_date)
+        .bind(quantity)
+        .bind(unit_cost)
+        .bind(item.company_id)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Consume `quantity` from the open layers according to the company's
+    /// costing method and return the cost relieved (rounded to cents).
+    async fn consume_layers(conn: &mut PgConnection, item: &Item, quantity: Decimal) -> Result<Decimal> {
+        let method = Self::costing_method(conn, item.company_id).await?;
+
+        let layers = sqlx::query_as::<_, InventoryCostLayer>(
+            r#"
+            SELECT * FROM inventory_cost_layers
+            WHERE item_id = $1 AND quantity_remaining > 0
+            ORDER BY received_date, created_at
+            FOR UPDATE
+            "#
+        )
+        .bind(item.id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let on_hand: Decimal = layers.iter().map(|l| l.quantity_remaining).sum();
+        if on_hand < quantity {
+            return Err(AppError::ValidationError(format!(
+                "Insufficient stock for item '{}': {} on hand, {} required",
+                item.name, on_hand.normalize(), quantity.normalize()
+            )));
+        }
+
+        // Layers keep their purchase cost; the average is taken over the cost of the stock on hand
+        let average_total = if method == InventoryCostingMethod::Average {
+            let (stock, value) = Self::stock_on_hand(conn, item.id).await?;
+            Some(if stock.is_zero() { Decimal::ZERO } else { value * quantity / stock })
+        } else {
+            None
+        };
+
+        // Quantities always leave the oldest layers first; FIFO also takes their cost
+        let mut remaining = quantity;
+        let mut fifo_cost = Decimal::ZERO;
+        for layer in &layers {
+            if remaining.is_zero() {
+                break;
+            }
+            let take = remaining.min(layer.quantity_remaining);
+            fifo_cost += take * layer.unit_cost;
+            remaining -= take;
+
+            sqlx::query(
+                "UPDATE inventory_cost_layers SET quantity_remaining = quantity_remaining - $1, updated_at = NOW() WHERE id = $2"
+            )
+            .bind(take)
+            .bind(layer.id)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(round_money(average_total.unwrap_or(fifo_cost)))
+    }
+
+    async fn insert_movement(conn: &mut PgConnection, item: &Item, record: MovementRecord<'_>) -> Result<InventoryMovement> {
+        sqlx::query(
+            "UPDATE items SET quantity_on_hand = COALESCE(quantity_on_hand, 0) + $1, updated_at = NOW() WHERE id = $2"
+        )
+        .bind(record.quantity)
+        .bind(item.id)
+        .execute(&mut *conn)
+        .await?;
+
+        let movement = sqlx::query_as::<_, InventoryMovement>(
+            r#"
+            INSERT INTO inventory_movements
+                (id, item_id, movement_date, movement_type, quantity, unit_cost, total_cost,
+                 source_type, source_id, transaction_id, memo, company_id, created_by, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, NOW())
+            RETURNING *
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(item.id)
+        .bind(record.movement_date)
+        .bind(record.movement_type.to_string())
+        .bind(record.quantity)
+        .bind(record.unit_cost)
+        .bind(record.total_cost)
+        .bind(record.source_type)
+        .bind(record.source_id)
+        .bind(record.transaction_id)
+        .bind(&record.memo)
+        .bind(item.company_id)
+        .bind(record.created_by)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(movement)
+    }
+
+    async fn invalidate_inventory_cache(&self, item_id: Uuid) {
+        let _ = self.cache.delete(&format!("item:{}", item_id)).await;
+        let _ = self.cache.invalidate_all_account_balances().await;
+    }
+}
+
+struct MovementRecord<'a> {
+    movement_date: NaiveDate,
+    movement_type: InventoryMovementType,
+    quantity: Decimal,
+    unit_cost: Decimal,
+    total_cost: Decimal,
+    source_type: Option<&'a str>,
+    source_id: Option<Uuid>,
+    transaction_id: Option<Uuid>,
+    memo: Option<String>,
+    created_by: Option<Uuid>,
+}
+
+fn round_money(amount: Decimal) -> Decimal {
+    amount.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero)
+}
+
+fn unit_cost_of(total_cost: Decimal, quantity: Decimal) -> Decimal {
+    if quantity.is_zero() {
+        return Decimal::ZERO;
+    }
+    (total_cost / quantity).round_dp_with_strategy(4, RoundingStrategy::MidpointAwayFromZero)
+}
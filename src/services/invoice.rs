@@ -1,86 +1,168 @@
-use sqlx::PgPool;
+use base64::Engine;
+use chrono::Utc;
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 use validator::Validate;
-use rust_decimal::Decimal;
+use rust_decimal::{Decimal, RoundingStrategy};
 
 use crate::models::{
     Invoice, InvoiceLineItem, InvoiceStatus, CreateInvoiceRequest,
-    CreateInvoiceLineItemRequest, InvoiceWithLineItems
+    CreateInvoiceLineItemRequest, InvoiceWithLineItems, TaxSummary, InvoiceTemplate,
+    UpdateInvoiceTemplateRequest, CreateTransactionRequest, CreateLineItemRequest, JournalType,
+    SequenceDocumentType, DimensionLine,
 };
+use crate::utils::pdf::{format_amount, jpeg_info, wrap, Align, PdfDocument};
 use crate::utils::{AppError, Result};
-use crate::services::CacheService;
+use crate::services::{
+    CacheService, CompanyRecord, CompanyService, DimensionService, InventoryService, NumberingService,
+    TaxService, TransactionService,
+};
 
 #[derive(Clone)]
 pub struct InvoiceService {
     cache: CacheService,
+    company_id: Option<Uuid>,
 }
 
 impl InvoiceService {
     pub fn new_with_cache(cache: CacheService) -> Self {
-        Self { cache }
+        Self { cache, company_id: None }
+    }
+
+    /// Scope the service to a company's records
+    pub fn for_company(&self, company_id: Option<Uuid>) -> Self {
+        Self { company_id, ..self.clone() }
     }
 
     /// Create a new invoice with line items
-    pub async fn create_invoice(&self, pool: &PgPool, req: CreateInvoiceRequest) -> Result<InvoiceWithLineItems> {
+    pub async fn create_invoice(&self, pool: &PgPool, mut req: CreateInvoiceRequest) -> Result<InvoiceWithLineItems> {
         // Validate request
         req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+        req.company_id = CompanyService::record_company(self.company_id, req.company_id)?;
 
         // Start a transaction for atomic invoice creation
         let mut tx = pool.begin().await?;
+        let invoice = Self::insert_invoice(&mut tx, &req).await?;
+
+        // Commit transaction
+        tx.commit().await?;
 
-        // Calculate total amount
-        let total_amount = self.calculate_invoice_total(&req.line_items)?;
+        // Invalidate cache
+        self.invalidate_invoice_cache(&invoice.invoice.id).await;
 
-        // Create invoice header
+        Ok(invoice)
+    }
+
+    /// Insert a draft invoice and its lines on the caller's transaction
+    pub async fn insert_invoice(conn: &mut PgConnection, req: &CreateInvoiceRequest) -> Result<InvoiceWithLineItems> {
+        CompanyService::ensure_in_company(conn, req.company_id, CompanyRecord::Contact, &[req.customer_id]).await?;
+        if let Some(ar_account_id) = req.ar_account_id {
+            CompanyService::ensure_in_company(conn, req.company_id, CompanyRecord::Account, &[ar_account_id]).await?;
+        }
+
+        let invoice_number = NumberingService::assign_number(
+            conn, req.company_id, SequenceDocumentType::Invoice, req.invoice_date, req.invoice_number.clone(),
+        ).await?;
+
+        // Create invoice header; totals grow as the lines are added
         let invoice = sqlx::query_as::<_, Invoice>(
             r#"
             INSERT INTO invoices
                 (id, invoice_number, customer_id, invoice_date, due_date, ship_date,
-                 total_amount, balance, status, customer_memo, billing_address,
-                 shipping_address, company_id, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, NOW(), NOW())
+                 subtotal, tax_amount, total_amount, balance, status, customer_memo,
+                 billing_address, shipping_address, company_id, ar_account_id, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, 0, 0, 0, 0, $7, $8, $9, $10, $11, $12, NOW(), NOW())
             RETURNING id, quickbooks_id, invoice_number, customer_id, invoice_date, due_date,
-                     ship_date, tracking_number, total_amount, balance, status, customer_memo,
-                     billing_address, shipping_address, company_id, transaction_id,
-                     created_by, created_at, updated_at
+                     ship_date, tracking_number, subtotal, tax_amount, total_amount, balance,
+                     status, customer_memo, billing_address, shipping_address, company_id,
+                     ar_account_id, transaction_id, created_by, created_at, updated_at
             "#,
         )
         .bind(Uuid::new_v4())
-        .bind(&req.invoice_number)
+        .bind(&invoice_number)
         .bind(req.customer_id)
         .bind(req.invoice_date)
         .bind(req.due_date)
         .bind(req.ship_date)
-        .bind(total_amount)
-        .bind(total_amount) // Initially, balance equals total amount
         .bind(InvoiceStatus::Draft.to_string())
         .bind(&req.customer_memo)
         .bind(&req.billing_address)
         .bind(&req.shipping_address)
         .bind(req.company_id)
-        .fetch_one(&mut *tx)
+        .bind(req.ar_account_id)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => AppError::Conflict(
+                format!("Invoice number {} already exists", invoice_number)
+            ),
+            e => AppError::from(e),
+        })?;
+
+        let (invoice, line_items) = Self::add_line_items(conn, &invoice, &req.line_items).await?;
+        let taxes = Self::tax_summary(conn, invoice.id).await?;
+
+        Ok(InvoiceWithLineItems {
+            invoice,
+            line_items,
+            taxes,
+        })
+    }
+
+    /// Append lines to a draft invoice, adding their amounts and tax to its totals
+    pub async fn add_line_items(
+        conn: &mut PgConnection,
+        invoice: &Invoice,
+        line_items: &[CreateInvoiceLineItemRequest],
+    ) -> Result<(Invoice, Vec<InvoiceLineItem>)> {
+        if invoice.status != InvoiceStatus::Draft {
+            return Err(AppError::ValidationError("Lines can only be added to a draft invoice".to_string()));
+        }
+
+        let last_line_number: Option<i32> = sqlx::query_scalar(
+            "SELECT MAX(line_number) FROM invoice_line_items WHERE invoice_id = $1"
+        )
+        .bind(invoice.id)
+        .fetch_one(&mut *conn)
         .await?;
+        let first_line_number = last_line_number.unwrap_or(0) + 1;
+
+        let account_ids: Vec<Uuid> = line_items.iter().map(|l| l.revenue_account_id).collect();
+        let item_ids: Vec<Uuid> = line_items.iter().filter_map(|l| l.item_id).collect();
+        CompanyService::ensure_in_company(conn, invoice.company_id, CompanyRecord::Account, &account_ids).await?;
+        CompanyService::ensure_in_company(conn, invoice.company_id, CompanyRecord::Item, &item_ids).await?;
 
         // Create line items
-        let mut line_items = Vec::new();
-        for (index, line_item_req) in req.line_items.iter().enumerate() {
-            let line_amount = self.calculate_line_item_amount(line_item_req)?;
+        let mut created = Vec::with_capacity(line_items.len());
+        let mut subtotal = Decimal::ZERO;
+        let mut tax_amount = Decimal::ZERO;
+        for (index, line_item_req) in line_items.iter().enumerate() {
+            let line_amount = Self::calculate_line_item_amount(line_item_req)?;
+            let taxes = match &line_item_req.tax_code {
+                Some(tax_code) => {
+                    TaxService::calculate_tax(conn, invoice.company_id, tax_code, line_amount, invoice.invoice_date).await?
+                }
+                None => Vec::new(),
+            };
+            let line_tax: Decimal = taxes.iter().map(|t| t.tax_amount).sum();
+            subtotal += line_amount;
+            tax_amount += line_tax;
 
             let line_item = sqlx::query_as::<_, InvoiceLineItem>(
                 r#"
                 INSERT INTO invoice_line_items
                     (id, invoice_id, line_number, item_description, quantity, unit_price,
-                     amount, discount_percent, discount_amount, tax_code, revenue_account_id,
-                     created_at, updated_at)
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, NOW(), NOW())
+                     amount, discount_percent, discount_amount, tax_code, tax_amount,
+                     revenue_account_id, item_id, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, NOW(), NOW())
                 RETURNING id, invoice_id, line_number, item_description, quantity, unit_price,
-                         amount, discount_percent, discount_amount, tax_code, revenue_account_id,
-                         created_at, updated_at
+                         amount, discount_percent, discount_amount, tax_code, tax_amount,
+                         revenue_account_id, item_id, created_at, updated_at
                 "#,
             )
             .bind(Uuid::new_v4())
             .bind(invoice.id)
-            .bind(index as i32 + 1) // Line numbers start at 1
+            .bind(first_line_number + index as i32) // Line numbers start at 1
             .bind(&line_item_req.item_description)
             .bind(line_item_req.quantity)
             .bind(line_item_req.unit_price)
@@ -88,23 +170,94 @@ impl InvoiceService {
             .bind(line_item_req.discount_percent)
             .bind(None::<rust_decimal::Decimal>) // discount_amount calculated in DB
             .bind(&line_item_req.tax_code)
+            .bind(line_tax)
             .bind(line_item_req.revenue_account_id)
-            .fetch_one(&mut *tx)
+            .bind(line_item_req.item_id)
+            .fetch_one(&mut *conn)
             .await?;
 
-            line_items.push(line_item);
+            DimensionService::tag_line(
+                conn,
+                invoice.company_id,
+                DimensionLine::Invoice,
+                line_item.id,
+                Some(line_item.revenue_account_id),
+                &line_item_req.dimension_value_ids,
+            )
+            .await?;
+
+            // Record each component tax charged on the line
+            for tax in &taxes {
+                sqlx::query(
+                    r#"
+                    INSERT INTO invoice_line_taxes
+                        (id, invoice_id, invoice_line_item_id, tax_rate_id, tax_name, rate,
+                         taxable_amount, tax_amount, created_at)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW())
+                    "#,
+                )
+                .bind(Uuid::new_v4())
+                .bind(invoice.id)
+                .bind(line_item.id)
+                .bind(tax.tax_rate_id)
+                .bind(&tax.tax_name)
+                .bind(tax.rate)
+                .bind(tax.taxable_amount)
+                .bind(tax.tax_amount)
+                .execute(&mut *conn)
+                .await?;
+            }
+
+            created.push(line_item);
         }
 
-        // Commit transaction
-        tx.commit().await?;
+        let invoice = sqlx::query_as::<_, Invoice>(
+            r#"
+            UPDATE invoices
+            SET subtotal = subtotal + $1,
+                tax_amount = tax_amount + $2,
+                total_amount = total_amount + $1 + $2,
+                balance = balance + $1 + $2,
+                updated_at = NOW()
+            WHERE id = $3
+            RETURNING *
+            "#
+        )
+        .bind(subtotal)
+        .bind(tax_amount)
+        .bind(invoice.id)
+        .fetch_one(&mut *conn)
+        .await?;
 
-        // Invalidate cache
-        self.invalidate_invoice_cache(&invoice.id).await;
+        Ok((invoice, created))
+    }
 
-        Ok(InvoiceWithLineItems {
-            invoice,
-            line_items,
-        })
+    /// Relieve stock and post COGS for the inventory items sold on an invoice.
+    /// Returns whether any stock moved.
+    async fn record_sales(conn: &mut PgConnection, invoice: &Invoice) -> Result<bool> {
+        let lines: Vec<(Uuid, Decimal)> = sqlx::query_as(
+            "SELECT item_id, quantity FROM invoice_line_items WHERE invoice_id = $1 AND item_id IS NOT NULL ORDER BY line_number"
+        )
+        .bind(invoice.id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut stock_moved = false;
+        for (item_id, quantity) in lines {
+            let movement = InventoryService::record_sale(
+                conn,
+                item_id,
+                quantity,
+                invoice.invoice_date,
+                invoice.id,
+                &invoice.invoice_number,
+                None,
+            )
+            .await?;
+            stock_moved |= movement.is_some();
+        }
+
+        Ok(stock_moved)
     }
 
     /// Get invoice by ID with line items
@@ -112,18 +265,11 @@ impl InvoiceService {
         // Check cache first
         let cache_key = format!("invoice:{}", invoice_id);
         if let Ok(Some(cached)) = self.cache.get::<InvoiceWithLineItems>(&cache_key).await {
-            return Ok(Some(cached));
+            return Ok(Some(cached).filter(|i| i.invoice.company_id == self.company_id));
         }
 
         // Get invoice header
-        let invoice = sqlx::query_as::<_, Invoice>(
-            "SELECT * FROM invoices WHERE id = $1"
-        )
-        .bind(invoice_id)
-        .fetch_optional(pool)
-        .await?;
-
-        let Some(invoice) = invoice else {
+        let Some(invoice) = self.get_invoice_header(pool, invoice_id).await? else {
             return Ok(None);
         };
 
@@ -135,9 +281,13 @@ impl InvoiceService {
         .fetch_all(pool)
         .await?;
 
+        let mut conn = pool.acquire().await?;
+        let taxes = Self::tax_summary(&mut conn, invoice_id).await?;
+
         let invoice_with_items = InvoiceWithLineItems {
             invoice,
             line_items,
+            taxes,
         };
 
         // Cache the result
@@ -158,160 +308,176 @@ impl InvoiceService {
         match (customer_id, status, limit, offset) {
             (Some(customer_id), Some(status), Some(limit), Some(offset)) => {
                 let invoices = sqlx::query_as::<_, Invoice>(
-                    "SELECT * FROM invoices WHERE customer_id = $1 AND status = $2 ORDER BY invoice_date DESC, created_at DESC LIMIT $3 OFFSET $4"
+                    "SELECT * FROM invoices WHERE customer_id = $1 AND status = $2 AND company_id IS NOT DISTINCT FROM $5 ORDER BY invoice_date DESC, created_at DESC LIMIT $3 OFFSET $4"
                 )
                 .bind(customer_id)
                 .bind(status.to_string())
                 .bind(limit)
                 .bind(offset)
+                .bind(self.company_id)
                 .fetch_all(pool)
                 .await?;
                 Ok(invoices)
             }
             (Some(customer_id), Some(status), Some(limit), None) => {
                 let invoices = sqlx::query_as::<_, Invoice>(
-                    "SELECT * FROM invoices WHERE customer_id = $1 AND status = $2 ORDER BY invoice_date DESC, created_at DESC LIMIT $3"
+                    "SELECT * FROM invoices WHERE customer_id = $1 AND status = $2 AND company_id IS NOT DISTINCT FROM $4 ORDER BY invoice_date DESC, created_at DESC LIMIT $3"
                 )
                 .bind(customer_id)
                 .bind(status.to_string())
                 .bind(limit)
+                .bind(self.company_id)
                 .fetch_all(pool)
                 .await?;
                 Ok(invoices)
             }
             (Some(customer_id), Some(status), None, Some(offset)) => {
                 let invoices = sqlx::query_as::<_, Invoice>(
-                    "SELECT * FROM invoices WHERE customer_id = $1 AND status = $2 ORDER BY invoice_date DESC, created_at DESC OFFSET $3"
+                    "SELECT * FROM invoices WHERE customer_id = $1 AND status = $2 AND company_id IS NOT DISTINCT FROM $4 ORDER BY invoice_date DESC, created_at DESC OFFSET $3"
                 )
                 .bind(customer_id)
                 .bind(status.to_string())
                 .bind(offset)
+                .bind(self.company_id)
                 .fetch_all(pool)
                 .await?;
                 Ok(invoices)
             }
             (Some(customer_id), Some(status), None, None) => {
                 let invoices = sqlx::query_as::<_, Invoice>(
-                    "SELECT * FROM invoices WHERE customer_id = $1 AND status = $2 ORDER BY invoice_date DESC, created_at DESC"
+                    "SELECT * FROM invoices WHERE customer_id = $1 AND status = $2 AND company_id IS NOT DISTINCT FROM $3 ORDER BY invoice_date DESC, created_at DESC"
                 )
                 .bind(customer_id)
                 .bind(status.to_string())
+                .bind(self.company_id)
                 .fetch_all(pool)
                 .await?;
                 Ok(invoices)
             }
             (Some(customer_id), None, Some(limit), Some(offset)) => {
                 let invoices = sqlx::query_as::<_, Invoice>(
-                    "SELECT * FROM invoices WHERE customer_id = $1 ORDER BY invoice_date DESC, created_at DESC LIMIT $2 OFFSET $3"
+                    "SELECT * FROM invoices WHERE customer_id = $1 AND company_id IS NOT DISTINCT FROM $4 ORDER BY invoice_date DESC, created_at DESC LIMIT $2 OFFSET $3"
                 )
                 .bind(customer_id)
                 .bind(limit)
                 .bind(offset)
+                .bind(self.company_id)
                 .fetch_all(pool)
                 .await?;
                 Ok(invoices)
             }
             (Some(customer_id), None, Some(limit), None) => {
                 let invoices = sqlx::query_as::<_, Invoice>(
-                    "SELECT * FROM invoices WHERE customer_id = $1 ORDER BY invoice_date DESC, created_at DESC LIMIT $2"
+                    "SELECT * FROM invoices WHERE customer_id = $1 AND company_id IS NOT DISTINCT FROM $3 ORDER BY invoice_date DESC, created_at DESC LIMIT $2"
                 )
                 .bind(customer_id)
                 .bind(limit)
+                .bind(self.company_id)
                 .fetch_all(pool)
                 .await?;
                 Ok(invoices)
             }
             (Some(customer_id), None, None, Some(offset)) => {
                 let invoices = sqlx::query_as::<_, Invoice>(
-                    "SELECT * FROM invoices WHERE customer_id = $1 ORDER BY invoice_date DESC, created_at DESC OFFSET $2"
+                    "SELECT * FROM invoices WHERE customer_id = $1 AND company_id IS NOT DISTINCT FROM $3 ORDER BY invoice_date DESC, created_at DESC OFFSET $2"
                 )
                 .bind(customer_id)
                 .bind(offset)
+                .bind(self.company_id)
                 .fetch_all(pool)
                 .await?;
                 Ok(invoices)
             }
             (Some(customer_id), None, None, None) => {
                 let invoices = sqlx::query_as::<_, Invoice>(
-                    "SELECT * FROM invoices WHERE customer_id = $1 ORDER BY invoice_date DESC, created_at DESC"
+                    "SELECT * FROM invoices WHERE customer_id = $1 AND company_id IS NOT DISTINCT FROM $2 ORDER BY invoice_date DESC, created_at DESC"
                 )
                 .bind(customer_id)
+                .bind(self.company_id)
                 .fetch_all(pool)
                 .await?;
                 Ok(invoices)
             }
             (None, Some(status), Some(limit), Some(offset)) => {
                 let invoices = sqlx::query_as::<_, Invoice>(
-                    "SELECT * FROM invoices WHERE status = $1 ORDER BY invoice_date DESC, created_at DESC LIMIT $2 OFFSET $3"
+                    "SELECT * FROM invoices WHERE status = $1 AND company_id IS NOT DISTINCT FROM $4 ORDER BY invoice_date DESC, created_at DESC LIMIT $2 OFFSET $3"
                 )
                 .bind(status.to_string())
                 .bind(limit)
                 .bind(offset)
+                .bind(self.company_id)
                 .fetch_all(pool)
                 .await?;
                 Ok(invoices)
             }
             (None, Some(status), Some(limit), None) => {
                 let invoices = sqlx::query_as::<_, Invoice>(
-                    "SELECT * FROM invoices WHERE status = $1 ORDER BY invoice_date DESC, created_at DESC LIMIT $2"
+                    "SELECT * FROM invoices WHERE status = $1 AND company_id IS NOT DISTINCT FROM $3 ORDER BY invoice_date DESC, created_at DESC LIMIT $2"
                 )
                 .bind(status.to_string())
                 .bind(limit)
+                .bind(self.company_id)
                 .fetch_all(pool)
                 .await?;
                 Ok(invoices)
             }
             (None, Some(status), None, Some(offset)) => {
                 let invoices = sqlx::query_as::<_, Invoice>(
-                    "SELECT * FROM invoices WHERE status = $1 ORDER BY invoice_date DESC, created_at DESC OFFSET $2"
+                    "SELECT * FROM invoices WHERE status = $1 AND company_id IS NOT DISTINCT FROM $3 ORDER BY invoice_date DESC, created_at DESC OFFSET $2"
                 )
                 .bind(status.to_string())
                 .bind(offset)
+                .bind(self.company_id)
                 .fetch_all(pool)
                 .await?;
                 Ok(invoices)
             }
             (None, Some(status), None, None) => {
                 let invoices = sqlx::query_as::<_, Invoice>(
-                    "SELECT * FROM invoices WHERE status = $1 ORDER BY invoice_date DESC, created_at DESC"
+                    "SELECT * FROM invoices WHERE status = $1 AND company_id IS NOT DISTINCT FROM $2 ORDER BY invoice_date DESC, created_at DESC"
                 )
                 .bind(status.to_string())
+                .bind(self.company_id)
                 .fetch_all(pool)
                 .await?;
                 Ok(invoices)
             }
             (None, None, Some(limit), Some(offset)) => {
                 let invoices = sqlx::query_as::<_, Invoice>(
-                    "SELECT * FROM invoices ORDER BY invoice_date DESC, created_at DESC LIMIT $1 OFFSET $2"
+                    "SELECT * FROM invoices WHERE company_id IS NOT DISTINCT FROM $3 ORDER BY invoice_date DESC, created_at DESC LIMIT $1 OFFSET $2"
                 )
                 .bind(limit)
                 .bind(offset)
+                .bind(self.company_id)
                 .fetch_all(pool)
                 .await?;
                 Ok(invoices)
             }
             (None, None, Some(limit), None) => {
                 let invoices = sqlx::query_as::<_, Invoice>(
-                    "SELECT * FROM invoices ORDER BY invoice_date DESC, created_at DESC LIMIT $1"
+                    "SELECT * FROM invoices WHERE company_id IS NOT DISTINCT FROM $2 ORDER BY invoice_date DESC, created_at DESC LIMIT $1"
                 )
                 .bind(limit)
+                .bind(self.company_id)
                 .fetch_all(pool)
                 .await?;
                 Ok(invoices)
             }
             (None, None, None, Some(offset)) => {
                 let invoices = sqlx::query_as::<_, Invoice>(
-                    "SELECT * FROM invoices ORDER BY invoice_date DESC, created_at DESC OFFSET $1"
+                    "SELECT * FROM invoices WHERE company_id IS NOT DISTINCT FROM $2 ORDER BY invoice_date DESC, created_at DESC OFFSET $1"
                 )
                 .bind(offset)
+                .bind(self.company_id)
                 .fetch_all(pool)
                 .await?;
                 Ok(invoices)
             }
             (None, None, None, None) => {
                 let invoices = sqlx::query_as::<_, Invoice>(
-                    "SELECT * FROM invoices ORDER BY invoice_date DESC, created_at DESC"
+                    "SELECT * FROM invoices WHERE company_id IS NOT DISTINCT FROM $1 ORDER BY invoice_date DESC, created_at DESC"
                 )
+                .bind(self.company_id)
                 .fetch_all(pool)
                 .await?;
                 Ok(invoices)
@@ -332,41 +498,113 @@ impl InvoiceService {
 
         self.validate_status_transition(&current_invoice.status, &new_status)?;
 
-        // Update status
-        let updated_invoice = sqlx::query_as::<_, Invoice>(
+        let mut tx = pool.begin().await?;
+
+        let (updated_invoice, ledger_changed, stock_changed) = if current_invoice.status == InvoiceStatus::Draft
+            && !matches!(new_status, InvoiceStatus::Draft | InvoiceStatus::Void)
+        {
+            let (updated_invoice, stock_changed) = Self::post_draft(&mut tx, &current_invoice, new_status).await?;
+            let ledger_changed = updated_invoice.transaction_id.is_some();
+            (updated_invoice, ledger_changed, stock_changed)
+        } else {
+            // Voiding changes the tax return for the invoice date
+            if current_invoice.status != InvoiceStatus::Draft && new_status == InvoiceStatus::Void {
+                TaxService::ensure_period_open(&mut tx, current_invoice.company_id, current_invoice.invoice_date).await?;
+            }
+
+            let updated_invoice = Self::set_status(&mut tx, invoice_id, &new_status).await?;
+
+            let mut ledger_changed = false;
+            let mut stock_changed = false;
+            if new_status == InvoiceStatus::Void {
+                // The row is locked now, so no payment can be applied while this is checked
+                let applied: Decimal = sqlx::query_scalar("SELECT invoice_applied_amount($1)")
+                    .bind(invoice_id)
+                    .fetch_one(&mut *tx)
+                    .await?;
+                if applied > Decimal::ZERO {
+                    return Err(AppError::ValidationError(
+                        "Unapply the payments and credits on this invoice before voiding it".to_string()
+                    ));
+                }
+
+                // Reverse the ledger posting, if any, and return the stock sold
+                if let Some(transaction_id) = updated_invoice.transaction_id {
+                    TransactionService::reverse_entry(&mut tx, transaction_id, Utc::now().date_naive(), None).await?;
+                    ledger_changed = true;
+                }
+                stock_changed = !InventoryService::reverse_sales(&mut tx, invoice_id, Utc::now().date_naive(), None).await?.is_empty();
+            }
+            (updated_invoice, ledger_changed, stock_changed)
+        };
+
+        tx.commit().await?;
+
+        // Invalidate cache
+        self.invalidate_invoice_cache(&invoice_id).await;
+        if ledger_changed || stock_changed {
+            let _ = self.cache.invalidate_all_account_balances().await;
+        }
+        if stock_changed {
+            let _ = self.cache.delete_pattern("item:*").await;
+        }
+
+        Ok(updated_invoice)
+    }
+
+    /// Take a draft invoice out of draft on the caller's transaction: revenue and tax are
+    /// posted to the ledger and the stock it sells is relieved. Returns the updated invoice
+    /// and whether any stock moved.
+    pub async fn post_draft(
+        conn: &mut PgConnection,
+        invoice: &Invoice,
+        new_status: InvoiceStatus,
+    ) -> Result<(Invoice, bool)> {
+        if invoice.status != InvoiceStatus::Draft {
+            return Err(AppError::ValidationError(
+                format!("Invoice {} has already been posted", invoice.invoice_number)
+            ));
+        }
+
+        // Posting changes the tax return for the invoice date
+        TaxService::ensure_period_open(conn, invoice.company_id, invoice.invoice_date).await?;
+
+        let mut updated_invoice = Self::set_status(conn, invoice.id, &new_status).await?;
+        if updated_invoice.transaction_id.is_none() {
+            updated_invoice.transaction_id = Self::post_invoice(conn, &updated_invoice).await?;
+        }
+        let stock_changed = Self::record_sales(conn, &updated_invoice).await?;
+
+        Ok((updated_invoice, stock_changed))
+    }
+
+    async fn set_status(conn: &mut PgConnection, invoice_id: Uuid, status: &InvoiceStatus) -> Result<Invoice> {
+        let invoice = sqlx::query_as::<_, Invoice>(
             r#"
             UPDATE invoices
             SET status = $1, updated_at = NOW()
             WHERE id = $2
             RETURNING id, quickbooks_id, invoice_number, customer_id, invoice_date, due_date,
-                     ship_date, tracking_number, total_amount, balance, status, customer_memo,
-                     billing_address, shipping_address, company_id, transaction_id,
-                     created_by, created_at, updated_at
+                     ship_date, tracking_number, subtotal, tax_amount, total_amount, balance,
+                     status, customer_memo, billing_address, shipping_address, company_id,
+                     ar_account_id, transaction_id, created_by, created_at, updated_at
             "#,
         )
-        .bind(new_status.to_string())
+        .bind(status.to_string())
         .bind(invoice_id)
-        .fetch_one(pool)
+        .fetch_one(&mut *conn)
         .await?;
 
-        // If posting invoice, create transaction (disabled for now - will be implemented in payment processing)
-        // TODO: Implement proper transaction creation when posting invoices
-        // if new_status == InvoiceStatus::Sent && current_invoice.transaction_id.is_none() {
-        //     self.create_invoice_transaction(pool, &updated_invoice).await?;
-        // }
-
-        // Invalidate cache
-        self.invalidate_invoice_cache(&invoice_id).await;
-
-        Ok(updated_invoice)
+        Ok(invoice)
     }
 
     /// Get customer invoices
     pub async fn get_customer_invoices(&self, pool: &PgPool, customer_id: Uuid) -> Result<Vec<Invoice>> {
         let invoices = sqlx::query_as::<_, Invoice>(
-            "SELECT * FROM invoices WHERE customer_id = $1 ORDER BY invoice_date DESC, created_at DESC"
+            "SELECT * FROM invoices WHERE customer_id = $1 AND company_id IS NOT DISTINCT FROM $2 ORDER BY invoice_date DESC, created_at DESC"
         )
         .bind(customer_id)
+        .bind(self.company_id)
         .fetch_all(pool)
         .await?;
 
@@ -379,31 +617,432 @@ impl InvoiceService {
             r#"
             SELECT * FROM invoices
             WHERE due_date < CURRENT_DATE
-            AND status IN ('sent', 'partial')
+            AND status IN ('sent', 'partial', 'overdue')
             AND balance > 0
+            AND company_id IS NOT DISTINCT FROM $1
             ORDER BY due_date ASC
             "#
         )
+        .bind(self.company_id)
         .fetch_all(pool)
         .await?;
 
         Ok(invoices)
     }
 
+    /// Get the invoice template for a company, or the default template
+    pub async fn get_invoice_template(&self, pool: &PgPool, company_id: Option<Uuid>) -> Result<InvoiceTemplate> {
+        let template = sqlx::query_as::<_, InvoiceTemplate>(
+            "SELECT * FROM invoice_templates WHERE company_id IS NOT DISTINCT FROM $1"
+        )
+        .bind(company_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Invoice template not found".to_string()))?;
+
+        Ok(template)
+    }
+
+    /// Create or replace a company's invoice template
+    pub async fn update_invoice_template(&self, pool: &PgPool, req: UpdateInvoiceTemplateRequest) -> Result<InvoiceTemplate> {
+        req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+        let company_id = CompanyService::record_company(self.company_id, req.company_id)?;
+
+        let logo = match &req.logo_base64 {
+            Some(encoded) => {
+                let data = base64::engine::general_purpose::STANDARD
+                    .decode(encoded.trim())
+                    .map_err(|_| AppError::ValidationError("logo_base64 is not valid base64".to_string()))?;
+                if jpeg_info(&data).is_none() {
+                    return Err(AppError::ValidationError("Logo must be a JPEG image".to_string()));
+                }
+                Some(data)
+            }
+            None => None,
+        };
+
+        let template = sqlx::query_as::<_, InvoiceTemplate>(
+            r#"
+            INSERT INTO invoice_templates
+                (id, company_id, business_name, address, phone, email, tax_registration_number,
+                 payment_instructions, footer, logo, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, NOW(), NOW())
+            ON CONFLICT (company_id) DO UPDATE
+            SET business_name = EXCLUDED.business_name,
+                address = EXCLUDED.address,
+                phone = EXCLUDED.phone,
+                email = EXCLUDED.email,
+                tax_registration_number = EXCLUDED.tax_registration_number,
+                payment_instructions = EXCLUDED.payment_instructions,
+                footer = EXCLUDED.footer,
+                logo = CASE WHEN $11 THEN NULL ELSE COALESCE(EXCLUDED.logo, invoice_templates.logo) END,
+                updated_at = NOW()
+            RETURNING *
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(company_id)
+        .bind(&req.business_name)
+        .bind(&req.address)
+        .bind(&req.phone)
+        .bind(&req.email)
+        .bind(&req.tax_registration_number)
+        .bind(&req.payment_instructions)
+        .bind(&req.footer)
+        .bind(logo)
+        .bind(req.remove_logo)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(template)
+    }
+
+    /// Render an invoice as a PDF using its company's invoice template
+    pub async fn render_invoice_pdf(&self, pool: &PgPool, invoice_id: Uuid) -> Result<Vec<u8>> {
+        let invoice = self.get_invoice(pool, invoice_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Invoice with id {} not found", invoice_id)))?;
+
+        let customer_name: String = sqlx::query_scalar("SELECT name FROM contacts WHERE id = $1")
+            .bind(invoice.invoice.customer_id)
+            .fetch_one(pool)
+            .await?;
+
+        // Fall back to the default template, then to the company name alone
+        let template = sqlx::query_as::<_, InvoiceTemplate>(
+            r#"
+            SELECT * FROM invoice_templates
+            WHERE company_id IS NOT DISTINCT FROM $1 OR company_id IS NULL
+            ORDER BY company_id NULLS LAST
+            LIMIT 1
+            "#
+        )
+        .bind(invoice.invoice.company_id)
+        .fetch_optional(pool)
+        .await?;
+
+        let company_name: Option<String> = match invoice.invoice.company_id {
+            Some(company_id) => {
+                sqlx::query_scalar("SELECT name FROM companies WHERE id = $1")
+                    .bind(company_id)
+                    .fetch_optional(pool)
+                    .await?
+            }
+            None => None,
+        };
+
+        Ok(Self::render_pdf(&invoice, &customer_name, template.as_ref(), company_name.as_deref()))
+    }
+
     // Helper methods
 
+    fn render_pdf(
+        invoice: &InvoiceWithLineItems,
+        customer_name: &str,
+        template: Option<&InvoiceTemplate>,
+        company_name: Option<&str>,
+    ) -> Vec<u8> {
+        let header = &invoice.invoice;
+        let mut doc = PdfDocument::new();
+        let left = doc.left();
+        let right = doc.right();
+
+        // Business details
+        if let Some(logo) = template.and_then(|t| t.logo.as_deref()) {
+            doc.jpeg(logo, 160.0, 60.0);
+        }
+        let business_name = template
+            .and_then(|t| t.business_name.as_deref())
+            .or(company_name);
+        if let Some(name) = business_name {
+            doc.heading(name);
+        }
+        if let Some(template) = template {
+            for line in template.address.iter().flat_map(|a| a.lines()) {
+                doc.text(line);
+            }
+            for contact in [&template.phone, &template.email].into_iter().flatten() {
+                doc.text(contact);
+            }
+            if let Some(number) = &template.tax_registration_number {
+                doc.text(&format!("Tax registration: {}", number));
+            }
+        }
+        doc.space(12.0);
+
+        // Invoice details
+        let title = if header.status == InvoiceStatus::Void {
+            format!("Invoice {} (VOID)", header.invoice_number)
+        } else {
+            format!("Invoice {}", header.invoice_number)
+        };
+        doc.heading(&title);
+        doc.text(&format!("Invoice date: {}", header.invoice_date));
+        doc.text(&format!("Due date: {}", header.due_date));
+        if let Some(ship_date) = header.ship_date {
+            doc.text(&format!("Ship date: {}", ship_date));
+        }
+        if let Some(tracking) = &header.tracking_number {
+            doc.text(&format!("Tracking number: {}", tracking));
+        }
+        doc.space(10.0);
+
+        // Addresses side by side
+        let ship_col = left + 260.0;
+        doc.row(&[(left, Align::Left, "Bill to"), (ship_col, Align::Left, "Ship to")], true);
+        let mut bill_to = vec![customer_name];
+        bill_to.extend(header.billing_address.iter().flat_map(|a| a.lines()));
+        let ship_to: Vec<&str> = header.shipping_address
+            .iter()
+            .flat_map(|a| a.lines())
+            .collect();
+        for i in 0..bill_to.len().max(ship_to.len()) {
+            doc.row(&[
+                (left, Align::Left, bill_to.get(i).copied().unwrap_or("")),
+                (ship_col, Align::Left, ship_to.get(i).copied().unwrap_or("")),
+            ], false);
+        }
+        doc.space(12.0);
+
+        // Line items
+        let quantity_col = right - 200.0;
+        let price_col = right - 135.0;
+        let discount_col = right - 75.0;
+        doc.row(&[
+            (left, Align::Left, "Description"),
+            (quantity_col, Align::Right, "Qty"),
+            (price_col, Align::Right, "Unit price"),
+            (discount_col, Align::Right, "Discount"),
+            (right, Align::Right, "Amount"),
+        ], true);
+        doc.rule();
+
+        for line in &invoice.line_items {
+            let description = wrap(&line.item_description, 45);
+            let quantity = line.quantity.normalize().to_string();
+            let unit_price = format_amount(line.unit_price);
+            let discount = match line.discount_percent {
+                Some(percent) if !percent.is_zero() => format!("{}%", percent.normalize()),
+                _ => String::new(),
+            };
+            let amount = format_amount(line.amount);
+            doc.row(&[
+                (left, Align::Left, description.first().map(String::as_str).unwrap_or("")),
+                (quantity_col, Align::Right, &quantity),
+                (price_col, Align::Right, &unit_price),
+                (discount_col, Align::Right, &discount),
+                (right, Align::Right, &amount),
+            ], false);
+            for continuation in description.iter().skip(1) {
+                doc.row(&[(left, Align::Left, continuation)], false);
+            }
+        }
+        doc.rule();
+
+        // Totals
+        let label_col = right - 90.0;
+        let mut totals = vec![("Subtotal".to_string(), header.subtotal, false)];
+        for tax in &invoice.taxes {
+            totals.push((format!("{} {}%", tax.tax_name, tax.rate.normalize()), tax.tax_amount, false));
+        }
+        totals.push(("Total".to_string(), header.total_amount, true));
+        let paid = header.total_amount - header.balance;
+        if header.status != InvoiceStatus::Void && !paid.is_zero() {
+            totals.push(("Paid".to_string(), -paid, false));
+        }
+        let balance_due = if header.status == InvoiceStatus::Void { Decimal::ZERO } else { header.balance };
+        totals.push(("Balance due".to_string(), balance_due, true));
+        for (label, amount, bold) in &totals {
+            let amount = format_amount(*amount);
+            doc.row(&[(label_col, Align::Right, label), (right, Align::Right, &amount)], *bold);
+        }
+
+        // Notes, payment instructions and footer
+        if let Some(memo) = &header.customer_memo {
+            doc.space(12.0);
+            doc.row(&[(left, Align::Left, "Notes")], true);
+            for line in wrap(memo, 95) {
+                doc.text(&line);
+            }
+        }
+        if let Some(instructions) = template.and_then(|t| t.payment_instructions.as_ref()) {
+            doc.space(12.0);
+            doc.row(&[(left, Align::Left, "Payment instructions")], true);
+            for line in wrap(instructions, 95) {
+                doc.text(&line);
+            }
+        }
+        if let Some(footer) = template.and_then(|t| t.footer.as_ref()) {
+            doc.space(12.0);
+            for line in wrap(footer, 95) {
+                doc.text(&line);
+            }
+        }
+
+        doc.finish()
+    }
+
+    /// Lock the invoices a customer's money is about to be applied to, checking none would be
+    /// over-applied. Invoices are locked in id order so concurrent applications queue up
+    /// rather than deadlock.
+    pub async fn lock_for_application(
+        conn: &mut PgConnection,
+        customer_id: Uuid,
+        applications: &[(Uuid, Decimal)],
+    ) -> Result<()> {
+        let mut totals: Vec<(Uuid, Decimal)> = Vec::new();
+        for (invoice_id, amount) in applications {
+            if *amount <= Decimal::ZERO {
+                return Err(AppError::ValidationError("Applied amounts must be positive".to_string()));
+            }
+            match totals.iter_mut().find(|(id, _)| id == invoice_id) {
+                Some((_, total)) => *total += *amount,
+                None => totals.push((*invoice_id, *amount)),
+            }
+        }
+        let invoice_ids: Vec<Uuid> = totals.iter().map(|(id, _)| *id).collect();
+
+        let invoices = sqlx::query_as::<_, Invoice>(
+            "SELECT * FROM invoices WHERE id = ANY($1) ORDER BY id FOR UPDATE"
+        )
+        .bind(&invoice_ids)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        for (invoice_id, amount) in totals {
+            let invoice = invoices.iter()
+                .find(|i| i.id == invoice_id && i.customer_id == customer_id)
+                .ok_or_else(|| AppError::ValidationError(
+                    format!("Invoice {} does not exist or does not belong to customer", invoice_id)
+                ))?;
+            if matches!(invoice.status, InvoiceStatus::Draft | InvoiceStatus::Void | InvoiceStatus::WrittenOff) {
+                return Err(AppError::ValidationError(
+                    format!("Invoice {} is {} and cannot be paid", invoice.invoice_number, invoice.status.to_string().replace('_', " "))
+                ));
+            }
+            if amount > invoice.balance {
+                return Err(AppError::ValidationError(
+                    format!("Applied amount exceeds the balance of invoice {}", invoice.invoice_number)
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Recompute an invoice's balance and status from everything applied to it
+    pub async fn recalculate_balance(conn: &mut PgConnection, invoice_id: Uuid) -> Result<Invoice> {
+        let invoice = sqlx::query_as::<_, Invoice>(
+            r#"
+            UPDATE invoices
+            SET balance = total_amount - invoice_applied_amount(id),
+                status = invoice_status_for_balance(status, total_amount - invoice_applied_amount(id), total_amount),
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#
+        )
+        .bind(invoice_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(invoice)
+    }
+
     async fn get_invoice_header(&self, pool: &PgPool, invoice_id: Uuid) -> Result<Option<Invoice>> {
         let invoice = sqlx::query_as::<_, Invoice>(
-            "SELECT * FROM invoices WHERE id = $1"
+            "SELECT * FROM invoices WHERE id = $1 AND company_id IS NOT DISTINCT FROM $2"
         )
         .bind(invoice_id)
+        .bind(self.company_id)
         .fetch_optional(pool)
         .await?;
 
         Ok(invoice)
     }
 
-    fn calculate_line_item_amount(&self, line_item: &CreateInvoiceLineItemRequest) -> Result<Decimal> {
+    /// Tax on an invoice totalled by component tax
+    async fn tax_summary(conn: &mut PgConnection, invoice_id: Uuid) -> Result<Vec<TaxSummary>> {
+        let taxes = sqlx::query_as::<_, TaxSummary>(
+            r#"
+            SELECT tax_name, rate, SUM(taxable_amount) as taxable_amount, SUM(tax_amount) as tax_amount
+            FROM invoice_line_taxes
+            WHERE invoice_id = $1
+            GROUP BY tax_name, rate
+            ORDER BY tax_name, rate
+            "#
+        )
+        .bind(invoice_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(taxes)
+    }
+
+    /// Post the invoice to the ledger: Dr accounts receivable, Cr revenue and
+    /// tax liabilities. Invoices without an AR account are not posted.
+    async fn post_invoice(conn: &mut PgConnection, invoice: &Invoice) -> Result<Option<Uuid>> {
+        let Some(ar_account_id) = invoice.ar_account_id else {
+            return Ok(None);
+        };
+
+        if invoice.total_amount.is_zero() {
+            return Ok(None);
+        }
+
+        let revenue: Vec<(Uuid, Decimal, Vec<Uuid>)> = sqlx::query_as(
+            r#"
+            SELECT il.revenue_account_id, il.amount,
+                   ARRAY(SELECT d.dimension_value_id FROM invoice_line_dimensions d WHERE d.invoice_line_item_id = il.id)
+            FROM invoice_line_items il
+            WHERE il.invoice_id = $1
+            ORDER BY il.line_number
+            "#
+        )
+        .bind(invoice.id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let taxes: Vec<(Uuid, Decimal)> = sqlx::query_as(
+            r#"
+            SELECT tr.liability_account_id, ilt.tax_amount
+            FROM invoice_line_taxes ilt
+            INNER JOIN tax_rates tr ON tr.id = ilt.tax_rate_id
+            WHERE ilt.invoice_id = $1
+            ORDER BY ilt.created_at
+            "#
+        )
+        .bind(invoice.id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut amounts = vec![(ar_account_id, invoice.total_amount, Vec::new())];
+        amounts.extend(revenue.into_iter().map(|(account_id, amount, dimensions)| (account_id, -amount, dimensions)));
+        amounts.extend(taxes.into_iter().map(|(account_id, amount)| (account_id, -amount, Vec::new())));
+
+        let description = format!("Invoice {}", invoice.invoice_number);
+        let entry = CreateTransactionRequest {
+            transaction_date: invoice.invoice_date,
+            description: Some(description.clone()),
+            reference_number: Some(invoice.invoice_number.clone()),
+            entry_number: None,
+            contact_id: Some(invoice.customer_id),
+            company_id: invoice.company_id,
+            journal_type: Some(JournalType::Sales),
+            line_items: CreateLineItemRequest::from_tagged_amounts(&amounts, &description),
+        };
+        let transaction_id = TransactionService::post_entry(conn, &entry, None).await?;
+
+        sqlx::query("UPDATE invoices SET transaction_id = $1 WHERE id = $2")
+            .bind(transaction_id)
+            .bind(invoice.id)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(Some(transaction_id))
+    }
+
+    fn calculate_line_item_amount(line_item: &CreateInvoiceLineItemRequest) -> Result<Decimal> {
         let line_total = line_item.quantity * line_item.unit_price;
 
         let discount_amount = if let Some(discount_percent) = line_item.discount_percent {
@@ -412,34 +1051,35 @@ impl InvoiceService {
             Decimal::ZERO
         };
 
-        Ok(line_total - discount_amount)
-    }
-
-    fn calculate_invoice_total(&self, line_items: &[CreateInvoiceLineItemRequest]) -> Result<Decimal> {
-        let mut total = Decimal::ZERO;
-
-        for line_item in line_items {
-            total += self.calculate_line_item_amount(line_item)?;
-        }
-
-        Ok(total)
+        // Amounts are stored to the cent; tax is calculated on the stored amount
+        Ok((line_total - discount_amount).round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero))
     }
 
     fn validate_status_transition(&self, from: &InvoiceStatus, to: &InvoiceStatus) -> Result<()> {
         use InvoiceStatus::*;
 
         match (from, to) {
-            // Allow all transitions from Draft
-            (Draft, _) => Ok(()),
+            // Write-offs and recoveries post to the ledger through the write-off service
+            (_, WrittenOff) | (WrittenOff, _) => Err(AppError::ValidationError(
+                "Written-off status is changed by writing off or recovering the invoice".to_string()
+            )),
+
+            // Balances move only by applying payments, credits and write-offs
+            (_, Paid | Partial) => Err(AppError::ValidationError(
+                "Paid and partial statuses follow the payments and credits applied to the invoice".to_string()
+            )),
+
+            // Allow transitions from Draft
+            (Draft, Draft | Sent | Overdue | Void) => Ok(()),
 
             // Allow transitions from Sent
-            (Sent, Partial | Paid | Overdue | Void) => Ok(()),
+            (Sent, Overdue | Void) => Ok(()),
 
             // Allow transitions from Partial
-            (Partial, Paid | Overdue | Void) => Ok(()),
+            (Partial, Overdue | Void) => Ok(()),
 
             // Allow transitions from Overdue
-            (Overdue, Partial | Paid | Void) => Ok(()),
+            (Overdue, Void) => Ok(()),
 
             // Allow transitions from Paid
             (Paid, Void) => Ok(()),
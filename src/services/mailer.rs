@@ -0,0 +1,85 @@
+use lettre::message::Mailbox;
+use lettre::{AsyncTransport, Message};
+use std::env;
+use std::path::PathBuf;
+use tokio::io::AsyncWriteExt;
+
+use crate::services::SmtpConfig;
+use crate::utils::{AppError, Result};
+
+/// A plain-text account email, e.g. a password reset link
+#[derive(Debug, Clone)]
+pub struct MailMessage {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Where account emails are delivered
+#[derive(Debug, Clone)]
+pub enum Mailer {
+    /// Send through the SMTP server
+    Smtp(SmtpConfig),
+    /// Write the message to the application log, for local development
+    Log,
+    /// Append the message to a file, for local development and tests
+    File(PathBuf),
+}
+
+impl Mailer {
+    /// Read `MAILER` (`smtp`, `log` or `file`, with `MAILER_FILE`), defaulting to the log
+    pub fn from_env() -> Self {
+        match env::var("MAILER").unwrap_or_default().to_lowercase().as_str() {
+            "smtp" => Self::Smtp(SmtpConfig::from_env()),
+            "file" => Self::File(
+                env::var("MAILER_FILE").unwrap_or_else(|_| "mail.log".to_string()).into(),
+            ),
+            _ => {
+                tracing::info!("MAILER not set, writing account emails to the log");
+                Self::Log
+            }
+        }
+    }
+
+    pub async fn send(&self, message: &MailMessage) -> Result<()> {
+        match self {
+            Self::Smtp(config) => {
+                let to: Mailbox = message.to.parse()
+                    .map_err(|e| AppError::ValidationError(format!("Invalid recipient: {}", e)))?;
+                let email = Message::builder()
+                    .from(config.sender_mailbox().map_err(AppError::InternalError)?)
+                    .to(to)
+                    .subject(&message.subject)
+                    .body(message.body.clone())
+                    .map_err(|e| AppError::InternalError(e.to_string()))?;
+
+                config.transport()
+                    .map_err(AppError::InternalError)?
+                    .send(email)
+                    .await
+                    .map_err(|e| AppError::InternalError(format!("Failed to send email: {}", e)))?;
+            }
+            Self::Log => {
+                tracing::info!("Email to {}: {}\n{}", message.to, message.subject, message.body);
+            }
+            Self::File(path) => {
+                let mut file = tokio::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .await
+                    .map_err(|e| AppError::InternalError(format!("Failed to open {}: {}", path.display(), e)))?;
+                let entry = format!("To: {}\nSubject: {}\n\n{}\n\n", message.to, message.subject, message.body);
+                // Flush so the message is on disk once `send` returns
+                async {
+                    file.write_all(entry.as_bytes()).await?;
+                    file.flush().await
+                }
+                .await
+                .map_err(|e| AppError::InternalError(format!("Failed to write {}: {}", path.display(), e)))?;
+            }
+        }
+
+        Ok(())
+    }
+}
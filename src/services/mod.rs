@@ -8,6 +8,23 @@ pub mod cache;
 pub mod reporting;
 pub mod bill;
 pub mod import;
+pub mod inventory;
+pub mod tax;
+pub mod credit_memo;
+pub mod statement;
+pub mod estimate;
+pub mod purchase_order;
+pub mod vendor_credit;
+pub mod email;
+pub mod mailer;
+pub mod totp;
+pub mod dunning;
+pub mod finance_charge;
+pub mod write_off;
+pub mod numbering;
+pub mod billable_expense;
+pub mod dimension;
+pub mod company;
 
 pub use auth::AuthService;
 pub use account::AccountService;
@@ -17,5 +34,21 @@ pub use invoice::InvoiceService;
 pub use payment::PaymentService;
 pub use bill::BillService;
 pub use import::ImportService;
+pub use inventory::InventoryService;
+pub use tax::TaxService;
+pub use credit_memo::CreditMemoService;
+pub use statement::StatementService;
+pub use estimate::EstimateService;
+pub use purchase_order::PurchaseOrderService;
+pub use vendor_credit::VendorCreditService;
+pub use email::{EmailService, SmtpConfig};
+pub use mailer::Mailer;
+pub use dunning::DunningService;
+pub use finance_charge::FinanceChargeService;
+pub use write_off::WriteOffService;
+pub use numbering::NumberingService;
+pub use billable_expense::BillableExpenseService;
+pub use dimension::DimensionService;
+pub use company::{CompanyService, CompanyRecord};
 pub use cache::CacheService;
 pub use reporting::ReportingService;
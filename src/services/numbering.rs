@@ -0,0 +1,251 @@
+use chrono::{Datelike, NaiveDate, Utc};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{
+    NumberingSequence, NumberingSequenceWithNext, SequenceDocumentType, UpdateNumberingSequenceRequest,
+};
+use crate::utils::{AppError, Result};
+use crate::services::CompanyService;
+
+/// Digits used until a sequence is configured
+const DEFAULT_PADDING: i32 = 5;
+
+const DOCUMENT_TYPES: [SequenceDocumentType; 5] = [
+    SequenceDocumentType::Invoice,
+    SequenceDocumentType::Bill,
+    SequenceDocumentType::Payment,
+    SequenceDocumentType::JournalEntry,
+    SequenceDocumentType::CreditMemo,
+];
+
+/// Numbers are always read and assigned in the database, so nothing is cached
+#[derive(Clone, Default)]
+pub struct NumberingService {
+    company_id: Option<Uuid>,
+}
+
+impl NumberingService {
+    pub fn new() -> Self {
+        Self { company_id: None }
+    }
+
+    /// Scope the service to a company's records
+    pub fn for_company(&self, company_id: Option<Uuid>) -> Self {
+        Self { company_id }
+    }
+
+    /// Number a new document: the number given by hand, or else the next in sequence.
+    ///
+    /// A hand-picked number the sequence would reach later moves the counter past it, so the
+    /// sequence never hands it out again. Like `next_number`, this must run on the
+    /// connection that inserts the document.
+    pub async fn assign_number(
+        conn: &mut PgConnection,
+        company_id: Option<Uuid>,
+        document_type: SequenceDocumentType,
+        date: NaiveDate,
+        number: Option<String>,
+    ) -> Result<String> {
+        let Some(number) = number else {
+            return Self::next_number(conn, company_id, document_type, date).await;
+        };
+
+        let sequence = Self::ensure_sequence(conn, company_id, document_type).await?;
+        let taken = number
+            .strip_prefix(Self::prefix(&sequence, date.year()).as_str())
+            .and_then(|digits| digits.parse::<i64>().ok())
+            .filter(|taken| Self::format(&sequence, date.year(), *taken) == number);
+        if let Some(taken) = taken {
+            sqlx::query(
+                r#"
+                INSERT INTO numbering_sequence_counters (sequence_id, year, last_number)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (sequence_id, year)
+                DO UPDATE SET last_number = GREATEST(numbering_sequence_counters.last_number, EXCLUDED.last_number)
+                "#
+            )
+            .bind(sequence.id)
+            .bind(Self::counter_year(&sequence, date.year()))
+            .bind(taken)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(number)
+    }
+
+    /// Assign the next number for a document dated `date`.
+    ///
+    /// Must run on the connection that inserts the document: the counter row stays locked
+    /// until that transaction ends, so concurrent documents queue up behind it and a
+    /// rollback releases the number rather than leaving a gap.
+    pub async fn next_number(
+        conn: &mut PgConnection,
+        company_id: Option<Uuid>,
+        document_type: SequenceDocumentType,
+        date: NaiveDate,
+    ) -> Result<String> {
+        let sequence = Self::ensure_sequence(conn, company_id, document_type).await?;
+        let year = Self::counter_year(&sequence, date.year());
+
+        let number: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO numbering_sequence_counters (sequence_id, year, last_number)
+            VALUES ($1, $2, 1)
+            ON CONFLICT (sequence_id, year)
+            DO UPDATE SET last_number = numbering_sequence_counters.last_number + 1
+            RETURNING last_number
+            "#
+        )
+        .bind(sequence.id)
+        .bind(year)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(Self::format(&sequence, date.year(), number))
+    }
+
+    /// List every document type's sequence with the number each will assign next
+    pub async fn list_sequences(&self, pool: &PgPool) -> Result<Vec<NumberingSequenceWithNext>> {
+        let mut conn = pool.acquire().await?;
+        let year = Utc::now().date_naive().year();
+
+        let mut sequences = Vec::with_capacity(DOCUMENT_TYPES.len());
+        for document_type in DOCUMENT_TYPES {
+            let sequence = Self::ensure_sequence(&mut conn, self.company_id, document_type).await?;
+            sequences.push(Self::with_next(&mut conn, sequence, year).await?);
+        }
+
+        Ok(sequences)
+    }
+
+    /// Change how a document type is numbered, and optionally where its counter stands
+    pub async fn update_sequence(
+        &self,
+        pool: &PgPool,
+        document_type: SequenceDocumentType,
+        req: UpdateNumberingSequenceRequest,
+    ) -> Result<NumberingSequenceWithNext> {
+        req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+        // Without the year in the number, a reset would reuse last year's numbers
+        if req.yearly_reset && !req.prefix.contains("{YYYY}") && !req.prefix.contains("{YY}") {
+            return Err(AppError::ValidationError(
+                "A sequence that resets yearly needs {YYYY} or {YY} in its prefix".to_string()
+            ));
+        }
+
+        let company_id = CompanyService::record_company(self.company_id, req.company_id)?;
+        let mut tx = pool.begin().await?;
+
+        let sequence = sqlx::query_as::<_, NumberingSequence>(
+            r#"
+            INSERT INTO numbering_sequences (id, company_id, document_type, prefix, padding, yearly_reset, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, NOW(), NOW())
+            ON CONFLICT (company_id, document_type)
+            DO UPDATE SET prefix = EXCLUDED.prefix, padding = EXCLUDED.padding, yearly_reset = EXCLUDED.yearly_reset
+            RETURNING *
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(company_id)
+        .bind(document_type.to_string())
+        .bind(&req.prefix)
+        .bind(req.padding)
+        .bind(req.yearly_reset)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let year = req.year.unwrap_or_else(|| Utc::now().date_naive().year());
+        if let Some(next_number) = req.next_number {
+            sqlx::query(
+                r#"
+                INSERT INTO numbering_sequence_counters (sequence_id, year, last_number)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (sequence_id, year) DO UPDATE SET last_number = EXCLUDED.last_number
+                "#
+            )
+            .bind(sequence.id)
+            .bind(Self::counter_year(&sequence, year))
+            .bind(next_number - 1)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let result = Self::with_next(&mut tx, sequence, year).await?;
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    /// The company's sequence, created from the default sequence (or built-in settings) on first use
+    async fn ensure_sequence(
+        conn: &mut PgConnection,
+        company_id: Option<Uuid>,
+        document_type: SequenceDocumentType,
+    ) -> Result<NumberingSequence> {
+        sqlx::query(
+            r#"
+            INSERT INTO numbering_sequences (id, company_id, document_type, prefix, padding, yearly_reset, created_at, updated_at)
+            SELECT $1, $2, $3,
+                   COALESCE(d.prefix, $4), COALESCE(d.padding, $5), COALESCE(d.yearly_reset, FALSE),
+                   NOW(), NOW()
+            FROM (SELECT 1) AS one
+            LEFT JOIN numbering_sequences d ON d.company_id IS NULL AND d.document_type = $3
+            ON CONFLICT (company_id, document_type) DO NOTHING
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(company_id)
+        .bind(document_type.to_string())
+        .bind(document_type.default_prefix())
+        .bind(DEFAULT_PADDING)
+        .execute(&mut *conn)
+        .await?;
+
+        let sequence = sqlx::query_as::<_, NumberingSequence>(
+            "SELECT * FROM numbering_sequences WHERE company_id IS NOT DISTINCT FROM $1 AND document_type = $2"
+        )
+        .bind(company_id)
+        .bind(document_type.to_string())
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(sequence)
+    }
+
+    async fn with_next(conn: &mut PgConnection, sequence: NumberingSequence, year: i32) -> Result<NumberingSequenceWithNext> {
+        let counter_year = Self::counter_year(&sequence, year);
+        let last_number: Option<i64> = sqlx::query_scalar(
+            "SELECT last_number FROM numbering_sequence_counters WHERE sequence_id = $1 AND year = $2"
+        )
+        .bind(sequence.id)
+        .bind(counter_year)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        let next_number = last_number.unwrap_or(0) + 1;
+        Ok(NumberingSequenceWithNext {
+            next_document_number: Self::format(&sequence, year, next_number),
+            year: counter_year,
+            next_number,
+            sequence,
+        })
+    }
+
+    fn counter_year(sequence: &NumberingSequence, year: i32) -> i32 {
+        if sequence.yearly_reset { year } else { 0 }
+    }
+
+    fn prefix(sequence: &NumberingSequence, year: i32) -> String {
+        sequence.prefix
+            .replace("{YYYY}", &format!("{:04}", year))
+            .replace("{YY}", &format!("{:02}", year % 100))
+    }
+
+    fn format(sequence: &NumberingSequence, year: i32, number: i64) -> String {
+        format!("{}{:0width$}", Self::prefix(sequence, year), number, width = sequence.padding as usize)
+    }
+}
@@ -1,33 +1,73 @@
-use sqlx::PgPool;
+use chrono::NaiveDate;
+use sqlx::{FromRow, PgConnection, PgPool};
 use uuid::Uuid;
 use validator::Validate;
 use rust_decimal::Decimal;
 
 use crate::models::{
     Payment, PaymentApplication, CreatePaymentRequest, PaymentApplicationRequest,
-    CreateBillPaymentRequest, BillPayment, BillPaymentApplication
+    CreateBillPaymentRequest, BillPayment, BillPaymentApplication, SequenceDocumentType,
+    VoidPaymentRequest, CreateInvoiceRequest, CreateInvoiceLineItemRequest, InvoiceStatus,
+    CreateTransactionRequest, CreateLineItemRequest, JournalType, AutoApplyRequest, AutoApplyRun,
+    AutoApplySource, AutoApplication, CustomerAutoApplication, CreditMemo, CreditMemoApplicationRequest,
+    Invoice, BalanceDrift, ApplicationConsistencyReport,
 };
 use crate::utils::{AppError, Result};
-use crate::services::CacheService;
+use crate::services::{
+    BillService, CacheService, CompanyRecord, CompanyService, CreditMemoService, InvoiceService, NumberingService,
+    TransactionService,
+};
+
+/// Unapplied money available to auto-apply
+struct OpenCredit {
+    source_type: AutoApplySource,
+    id: Uuid,
+    number: Option<String>,
+    date: NaiveDate,
+    remaining: Decimal,
+}
+
+#[derive(FromRow)]
+struct AutoApplyCustomer {
+    id: Uuid,
+    name: String,
+}
 
 #[derive(Clone)]
 pub struct PaymentService {
     cache: CacheService,
+    company_id: Option<Uuid>,
 }
 
 impl PaymentService {
     pub fn new_with_cache(cache: CacheService) -> Self {
-        Self { cache }
+        Self {
+            cache,
+            company_id: None,
+        }
+    }
+
+    /// Scope the service to a company's records
+    pub fn for_company(&self, company_id: Option<Uuid>) -> Self {
+        Self {
+            cache: self.cache.clone(),
+            company_id,
+        }
     }
 
     /// Create a new customer payment with optional invoice applications
     pub async fn create_payment(&self, pool: &PgPool, req: CreatePaymentRequest) -> Result<Payment> {
         // Validate request
         req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+        let company_id = CompanyService::record_company(self.company_id, req.company_id)?;
 
         // Start a transaction for atomic payment creation
         let mut tx = pool.begin().await?;
 
+        let account_ids: Vec<Uuid> = req.deposit_to_account_id.into_iter().chain(req.ar_account_id).collect();
+        CompanyService::ensure_in_company(&mut tx, company_id, CompanyRecord::Contact, &[req.customer_id]).await?;
+        CompanyService::ensure_in_company(&mut tx, company_id, CompanyRecord::Account, &account_ids).await?;
+
         // Validate that total applications don't exceed payment amount
         let total_applications: Decimal = req.applications.iter()
             .map(|app| app.amount_applied)
@@ -39,90 +79,62 @@ impl PaymentService {
             ));
         }
 
+        let payment_number = NumberingService::assign_number(
+            &mut tx, company_id, SequenceDocumentType::Payment, req.payment_date, req.payment_number.clone(),
+        ).await?;
+
         // Create payment record
-        let payment = sqlx::query_as::<_, Payment>(
+        let mut payment = sqlx::query_as::<_, Payment>(
             r#"
             INSERT INTO payments
                 (id, payment_number, customer_id, payment_date, amount, unapplied_amount,
                  payment_method, reference_number, deposit_to_account_id, memo, company_id,
-                 created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, NOW(), NOW())
-            RETURNING id, quickbooks_id, payment_number, customer_id, payment_date, amount,
-                     unapplied_amount, payment_method, reference_number, deposit_to_account_id,
-                     memo, company_id, transaction_id, created_by, created_at, updated_at
+                 ar_account_id, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, NOW(), NOW())
+            RETURNING *
             "#,
         )
         .bind(Uuid::new_v4())
-        .bind(&req.payment_number)
+        .bind(&payment_number)
         .bind(req.customer_id)
         .bind(req.payment_date)
         .bind(req.amount)
-        .bind(req.amount - total_applications) // Calculate unapplied amount
+        .bind(req.amount) // Reduced as the applications are made
         .bind(&req.payment_method)
         .bind(&req.reference_number)
         .bind(req.deposit_to_account_id)
         .bind(&req.memo)
-        .bind(req.company_id)
+        .bind(company_id)
+        .bind(req.ar_account_id)
         .fetch_one(&mut *tx)
         .await
         .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
-        // Create payment applications if any
-        if !req.applications.is_empty() {
-            for application in req.applications {
-                // Validate that invoice exists and belongs to the same customer
-                let invoice_exists = sqlx::query_scalar::<_, bool>(
-                    "SELECT EXISTS(SELECT 1 FROM invoices WHERE id = $1 AND customer_id = $2)"
-                )
-                .bind(application.invoice_id)
-                .bind(req.customer_id)
-                .fetch_one(&mut *tx)
-                .await
-                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
-
-                if !invoice_exists {
-                    return Err(AppError::ValidationError(
-                        format!("Invoice {} does not exist or does not belong to customer", application.invoice_id)
-                    ));
-                }
-
-                // Create payment application
-                sqlx::query_as::<_, PaymentApplication>(
-                    r#"
-                    INSERT INTO payment_applications
-                        (id, payment_id, invoice_id, amount_applied, created_at)
-                    VALUES ($1, $2, $3, $4, NOW())
-                    RETURNING id, payment_id, invoice_id, amount_applied, created_at
-                    "#,
-                )
-                .bind(Uuid::new_v4())
+        // Post the receipt to the ledger when both sides are known
+        let posted = if let (Some(deposit_account_id), Some(ar_account_id)) = (payment.deposit_to_account_id, payment.ar_account_id) {
+            let transaction_id = Self::post_receipt(
+                &mut tx,
+                &payment,
+                payment.payment_date,
+                &[(deposit_account_id, payment.amount), (ar_account_id, -payment.amount)],
+                "Customer payment",
+                payment.created_by,
+            ).await?;
+
+            sqlx::query("UPDATE payments SET transaction_id = $1 WHERE id = $2")
+                .bind(transaction_id)
                 .bind(payment.id)
-                .bind(application.invoice_id)
-                .bind(application.amount_applied)
-                .fetch_one(&mut *tx)
-                .await
-                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
-
-                // Update invoice balance
-                sqlx::query(
-                    r#"
-                    UPDATE invoices
-                    SET balance = balance - $1,
-                        status = CASE
-                            WHEN balance - $1 <= 0 THEN 'paid'
-                            WHEN balance - $1 < total_amount THEN 'partial'
-                            ELSE status
-                        END,
-                        updated_at = NOW()
-                    WHERE id = $2
-                    "#
-                )
-                .bind(application.amount_applied)
-                .bind(application.invoice_id)
                 .execute(&mut *tx)
-                .await
-                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
-            }
+                .await?;
+            payment.transaction_id = Some(transaction_id);
+            true
+        } else {
+            false
+        };
+
+        // Create payment applications if any
+        if !req.applications.is_empty() {
+            payment = Self::apply_to_invoices(&mut tx, &payment, &req.applications).await?;
         }
 
         // Commit transaction
@@ -131,6 +143,9 @@ impl PaymentService {
         // Invalidate relevant cache entries
         let _ = self.cache.delete_pattern(&format!("customer:*")).await;
         let _ = self.cache.delete_pattern(&format!("invoice:*")).await;
+        if posted {
+            let _ = self.cache.invalidate_all_account_balances().await;
+        }
 
         Ok(payment)
     }
@@ -141,14 +156,15 @@ impl PaymentService {
 
         // Try cache first
         if let Ok(Some(cached_payment)) = self.cache.get::<Payment>(&cache_key).await {
-            return Ok(Some(cached_payment));
+            return Ok(Some(cached_payment).filter(|p| p.company_id == self.company_id));
         }
 
         // Query database
         let payment = sqlx::query_as::<_, Payment>(
-            "SELECT * FROM payments WHERE id = $1"
+            "SELECT * FROM payments WHERE id = $1 AND company_id IS NOT DISTINCT FROM $2"
         )
         .bind(payment_id)
+        .bind(self.company_id)
         .fetch_optional(pool)
         .await
         .map_err(|e| AppError::DatabaseError(e.to_string()))?;
@@ -163,7 +179,7 @@ impl PaymentService {
 
     /// List payments for a customer
     pub async fn list_customer_payments(&self, pool: &PgPool, customer_id: Uuid) -> Result<Vec<Payment>> {
-        let cache_key = format!("customer:payments:{}", customer_id);
+        let cache_key = format!("customer:payments:{}:{}", CompanyService::cache_scope(self.company_id), customer_id);
 
         // Try cache first
         if let Ok(Some(cached_payments)) = self.cache.get::<Vec<Payment>>(&cache_key).await {
@@ -174,11 +190,12 @@ impl PaymentService {
         let payments = sqlx::query_as::<_, Payment>(
             r#"
             SELECT * FROM payments
-            WHERE customer_id = $1
+            WHERE customer_id = $1 AND company_id IS NOT DISTINCT FROM $2
             ORDER BY payment_date DESC, created_at DESC
             "#
         )
         .bind(customer_id)
+        .bind(self.company_id)
         .fetch_all(pool)
         .await
         .map_err(|e| AppError::DatabaseError(e.to_string()))?;
@@ -194,12 +211,14 @@ impl PaymentService {
         let payments = sqlx::query_as::<_, Payment>(
             r#"
             SELECT * FROM payments
+            WHERE company_id IS NOT DISTINCT FROM $3
             ORDER BY payment_date DESC, created_at DESC
             LIMIT $1 OFFSET $2
             "#
         )
         .bind(limit.unwrap_or(100))
         .bind(offset.unwrap_or(0))
+        .bind(self.company_id)
         .fetch_all(pool)
         .await
         .map_err(|e| AppError::DatabaseError(e.to_string()))?;
@@ -212,18 +231,18 @@ impl PaymentService {
         let query = if customer_id.is_some() {
             r#"
             SELECT * FROM payments
-            WHERE unapplied_amount > 0 AND customer_id = $1
+            WHERE unapplied_amount > 0 AND company_id IS NOT DISTINCT FROM $1 AND customer_id = $2
             ORDER BY payment_date ASC
             "#
         } else {
             r#"
             SELECT * FROM payments
-            WHERE unapplied_amount > 0
+            WHERE unapplied_amount > 0 AND company_id IS NOT DISTINCT FROM $1
             ORDER BY payment_date ASC
             "#
         };
 
-        let mut query_builder = sqlx::query_as::<_, Payment>(query);
+        let mut query_builder = sqlx::query_as::<_, Payment>(query).bind(self.company_id);
 
         if let Some(cid) = customer_id {
             query_builder = query_builder.bind(cid);
@@ -248,14 +267,27 @@ impl PaymentService {
         let mut tx = pool.begin().await?;
 
         // Get payment details
-        let payment = sqlx::query_as::<_, Payment>(
-            "SELECT * FROM payments WHERE id = $1 FOR UPDATE"
-        )
-        .bind(payment_id)
-        .fetch_one(&mut *tx)
-        .await
-        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        let payment = Self::lock_payment(&mut tx, self.company_id, payment_id).await?;
+
+        Self::apply_to_invoices(&mut tx, &payment, &applications).await?;
+
+        // Commit transaction
+        tx.commit().await.map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        // Invalidate cache
+        let _ = self.cache.delete_pattern("payment:*").await;
+        let _ = self.cache.delete_pattern(&format!("customer:payments:*:{}", payment.customer_id)).await;
+        let _ = self.cache.delete_pattern("invoice:*").await;
 
+        Ok(())
+    }
+
+    /// Apply part of a locked payment's unapplied amount to invoices of its customer
+    pub async fn apply_to_invoices(
+        conn: &mut PgConnection,
+        payment: &Payment,
+        applications: &[PaymentApplicationRequest],
+    ) -> Result<Payment> {
         // Validate total applications don't exceed unapplied amount
         let total_applications: Decimal = applications.iter()
             .map(|app| app.amount_applied)
@@ -267,82 +299,454 @@ impl PaymentService {
             ));
         }
 
+        let amounts: Vec<(Uuid, Decimal)> = applications.iter()
+            .map(|app| (app.invoice_id, app.amount_applied))
+            .collect();
+        InvoiceService::lock_for_application(conn, payment.customer_id, &amounts).await?;
+
         // Process each application
         for application in applications {
-            // Validate invoice exists and belongs to the same customer
-            let invoice_exists = sqlx::query_scalar::<_, bool>(
-                "SELECT EXISTS(SELECT 1 FROM invoices WHERE id = $1 AND customer_id = $2)"
-            )
-            .bind(application.invoice_id)
-            .bind(payment.customer_id)
-            .fetch_one(&mut *tx)
-            .await
-            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
-
-            if !invoice_exists {
-                return Err(AppError::ValidationError(
-                    format!("Invoice {} does not exist or does not belong to customer", application.invoice_id)
-                ));
-            }
-
-            // Create payment application
-            sqlx::query_as::<_, PaymentApplication>(
+            sqlx::query(
                 r#"
                 INSERT INTO payment_applications
                     (id, payment_id, invoice_id, amount_applied, created_at)
                 VALUES ($1, $2, $3, $4, NOW())
-                RETURNING id, payment_id, invoice_id, amount_applied, created_at
                 "#,
             )
             .bind(Uuid::new_v4())
-            .bind(payment_id)
+            .bind(payment.id)
             .bind(application.invoice_id)
             .bind(application.amount_applied)
-            .fetch_one(&mut *tx)
-            .await
-            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+            .execute(&mut *conn)
+            .await?;
 
-            // Update invoice balance
-            sqlx::query(
-                r#"
-                UPDATE invoices
-                SET balance = balance - $1,
-                    status = CASE
-                        WHEN balance - $1 <= 0 THEN 'paid'
-                        WHEN balance - $1 < total_amount THEN 'partial'
-                        ELSE status
-                    END,
-                    updated_at = NOW()
-                WHERE id = $2
-                "#
-            )
-            .bind(application.amount_applied)
-            .bind(application.invoice_id)
-            .execute(&mut *tx)
-            .await
-            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+            InvoiceService::recalculate_balance(conn, application.invoice_id).await?;
         }
 
         // Update payment unapplied amount
-        let new_unapplied = payment.unapplied_amount.unwrap_or(Decimal::ZERO) - total_applications;
-        sqlx::query(
-            "UPDATE payments SET unapplied_amount = $1, updated_at = NOW() WHERE id = $2"
+        let payment = sqlx::query_as::<_, Payment>(
+            "UPDATE payments SET unapplied_amount = unapplied_amount - $1, updated_at = NOW() WHERE id = $2 RETURNING *"
+        )
+        .bind(total_applications)
+        .bind(payment.id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(payment)
+    }
+
+    /// List the invoices a payment has been applied to
+    pub async fn list_payment_applications(&self, pool: &PgPool, payment_id: Uuid) -> Result<Vec<PaymentApplication>> {
+        let applications = sqlx::query_as::<_, PaymentApplication>(
+            r#"
+            SELECT pa.* FROM payment_applications pa
+            INNER JOIN payments p ON p.id = pa.payment_id
+            WHERE pa.payment_id = $1 AND p.company_id IS NOT DISTINCT FROM $2
+            ORDER BY pa.created_at, pa.id
+            "#
         )
-        .bind(new_unapplied)
         .bind(payment_id)
-        .execute(&mut *tx)
-        .await
-        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        .bind(self.company_id)
+        .fetch_all(pool)
+        .await?;
 
-        // Commit transaction
-        tx.commit().await.map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Ok(applications)
+    }
 
-        // Invalidate cache
-        let _ = self.cache.delete_pattern(&format!("payment:*")).await;
-        let _ = self.cache.delete_pattern(&format!("customer:payments:{}", payment.customer_id)).await;
-        let _ = self.cache.delete_pattern(&format!("invoice:*")).await;
+    /// Remove one application, returning its amount to the invoice balance and to the payment's unapplied amount
+    pub async fn unapply_payment_application(
+        &self,
+        pool: &PgPool,
+        payment_id: Uuid,
+        application_id: Uuid,
+    ) -> Result<Payment> {
+        let mut tx = pool.begin().await?;
 
-        Ok(())
+        let payment = Self::lock_payment(&mut tx, self.company_id, payment_id).await?;
+        if payment.void_date.is_some() {
+            return Err(AppError::BadRequest("Payment has been voided".to_string()));
+        }
+
+        let application = sqlx::query_as::<_, PaymentApplication>(
+            "SELECT * FROM payment_applications WHERE id = $1 AND payment_id = $2 FOR UPDATE"
+        )
+        .bind(application_id)
+        .bind(payment_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Payment application not found".to_string()))?;
+
+        Self::remove_application(&mut tx, &application).await?;
+
+        let payment = sqlx::query_as::<_, Payment>(
+            r#"
+            UPDATE payments
+            SET unapplied_amount = COALESCE(unapplied_amount, 0) + $1, updated_at = NOW()
+            WHERE id = $2
+            RETURNING *
+            "#
+        )
+        .bind(application.amount_applied)
+        .bind(payment_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        self.invalidate_cache(payment.customer_id).await;
+
+        Ok(payment)
+    }
+
+    /// Void a payment, e.g. a bounced cheque.
+    ///
+    /// Every application is removed so the invoices are open again, the receipt is reversed in
+    /// the ledger, and any bank charge is taken from the deposit account. An NSF fee is billed
+    /// to the customer on a new invoice.
+    pub async fn void_payment(
+        &self,
+        pool: &PgPool,
+        payment_id: Uuid,
+        req: VoidPaymentRequest,
+        created_by: Option<Uuid>,
+    ) -> Result<Payment> {
+        req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+        let nsf_fee = Self::fee(req.nsf_fee, req.nsf_fee_account_id, "NSF fee")?;
+        let bank_fee = Self::fee(req.bank_fee, req.bank_fee_account_id, "Bank fee")?;
+
+        let mut tx = pool.begin().await?;
+
+        let payment = Self::lock_payment(&mut tx, self.company_id, payment_id).await?;
+        if payment.void_date.is_some() {
+            return Err(AppError::Conflict("Payment has already been voided".to_string()));
+        }
+        if req.void_date < payment.payment_date {
+            return Err(AppError::ValidationError("Void date cannot be before the payment date".to_string()));
+        }
+        if let Some((_, expense_account_id)) = bank_fee {
+            CompanyService::ensure_in_company(&mut tx, payment.company_id, CompanyRecord::Account, &[expense_account_id]).await?;
+        }
+
+        let refunded: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM customer_refunds WHERE payment_id = $1)"
+        )
+        .bind(payment_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if refunded {
+            return Err(AppError::BadRequest(
+                "Payment has been refunded and cannot be voided".to_string()
+            ));
+        }
+
+        let applications = sqlx::query_as::<_, PaymentApplication>(
+            "SELECT * FROM payment_applications WHERE payment_id = $1 ORDER BY invoice_id FOR UPDATE"
+        )
+        .bind(payment_id)
+        .fetch_all(&mut *tx)
+        .await?;
+        for application in &applications {
+            Self::remove_application(&mut tx, application).await?;
+        }
+
+        let void_transaction_id = match payment.transaction_id {
+            Some(transaction_id) => Some(
+                TransactionService::reverse_entry(&mut tx, transaction_id, req.void_date, created_by).await?
+            ),
+            None => None,
+        };
+
+        if let Some((amount, expense_account_id)) = bank_fee {
+            let deposit_account_id = payment.deposit_to_account_id.ok_or_else(|| AppError::ValidationError(
+                "A bank fee needs the payment's deposit account".to_string()
+            ))?;
+            Self::post_receipt(
+                &mut tx,
+                &payment,
+                req.void_date,
+                &[(expense_account_id, amount), (deposit_account_id, -amount)],
+                "Returned payment bank fee",
+                created_by,
+            ).await?;
+        }
+
+        // The NSF fee is billed with the void, so a failure leaves the payment as it was
+        let nsf_invoice_id = match nsf_fee {
+            Some((amount, income_account_id)) => {
+                let payment_label = payment.payment_number.clone().unwrap_or_else(|| payment.id.to_string());
+                let invoice = InvoiceService::insert_invoice(&mut tx, &CreateInvoiceRequest {
+                    invoice_number: None,
+                    customer_id: payment.customer_id,
+                    invoice_date: req.void_date,
+                    due_date: req.void_date,
+                    ship_date: None,
+                    customer_memo: Some(format!("Returned payment {}", payment_label)),
+                    billing_address: None,
+                    shipping_address: None,
+                    company_id: payment.company_id,
+                    ar_account_id: payment.ar_account_id,
+                    line_items: vec![CreateInvoiceLineItemRequest {
+                        line_number: 1,
+                        item_description: format!("NSF fee for returned payment {}", payment_label),
+                        quantity: Decimal::ONE,
+                        unit_price: amount,
+                        discount_percent: None,
+                        tax_code: None,
+                        revenue_account_id: income_account_id,
+                        item_id: None,
+                        dimension_value_ids: Vec::new(),
+                    }],
+                }).await?;
+
+                // Posting moves the fee to the income account
+                InvoiceService::post_draft(&mut tx, &invoice.invoice, InvoiceStatus::Sent).await?;
+                Some(invoice.invoice.id)
+            }
+            None => None,
+        };
+
+        let payment = sqlx::query_as::<_, Payment>(
+            r#"
+            UPDATE payments
+            SET unapplied_amount = 0, void_date = $1, void_reason = $2, void_transaction_id = $3,
+                nsf_invoice_id = $4, updated_at = NOW()
+            WHERE id = $5
+            RETURNING *
+            "#
+        )
+        .bind(req.void_date)
+        .bind(&req.reason)
+        .bind(void_transaction_id)
+        .bind(nsf_invoice_id)
+        .bind(payment_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        self.invalidate_cache(payment.customer_id).await;
+        let _ = self.cache.invalidate_all_account_balances().await;
+
+        Ok(payment)
+    }
+
+    /// Apply unapplied payments and credit memos to customers' oldest open invoices first.
+    ///
+    /// Each customer is applied in its own transaction with the invoices, payments and credit
+    /// memos involved locked, so a preview shows exactly what a commit would do at that moment.
+    pub async fn auto_apply(&self, pool: &PgPool, req: AutoApplyRequest) -> Result<AutoApplyRun> {
+        let customers = sqlx::query_as::<_, AutoApplyCustomer>(
+            r#"
+            SELECT c.id, c.name
+            FROM contacts c
+            WHERE c.contact_type = 'Customer'
+                AND ($1::uuid IS NULL OR c.id = $1)
+                AND c.company_id IS NOT DISTINCT FROM $2
+                AND EXISTS (
+                    SELECT 1 FROM invoices i
+                    WHERE i.customer_id = c.id AND i.status IN ('sent', 'partial', 'overdue') AND i.balance > 0
+                )
+                AND (
+                    EXISTS (
+                        SELECT 1 FROM payments p
+                        WHERE p.customer_id = c.id AND p.void_date IS NULL AND p.unapplied_amount > 0
+                    )
+                    OR EXISTS (
+                        SELECT 1 FROM credit_memos cm
+                        WHERE cm.customer_id = c.id AND cm.status = 'open' AND cm.balance > 0
+                    )
+                )
+            ORDER BY c.name
+            "#
+        )
+        .bind(req.customer_id)
+        .bind(CompanyService::record_company(self.company_id, req.company_id)?)
+        .fetch_all(pool)
+        .await?;
+
+        let mut results = Vec::with_capacity(customers.len());
+        for customer in customers {
+            let mut tx = pool.begin().await?;
+
+            let payments = sqlx::query_as::<_, Payment>(
+                r#"
+                SELECT * FROM payments
+                WHERE customer_id = $1 AND void_date IS NULL AND unapplied_amount > 0
+                ORDER BY payment_date, created_at
+                FOR UPDATE
+                "#
+            )
+            .bind(customer.id)
+            .fetch_all(&mut *tx)
+            .await?;
+
+            let credit_memos = sqlx::query_as::<_, CreditMemo>(
+                r#"
+                SELECT * FROM credit_memos
+                WHERE customer_id = $1 AND status = 'open' AND balance > 0
+                ORDER BY credit_memo_date, created_at
+                FOR UPDATE
+                "#
+            )
+            .bind(customer.id)
+            .fetch_all(&mut *tx)
+            .await?;
+
+            // Money is locked before invoices, and invoices in id order, as when applying by hand
+            let mut invoices = sqlx::query_as::<_, Invoice>(
+                r#"
+                SELECT * FROM invoices
+                WHERE customer_id = $1 AND status IN ('sent', 'partial', 'overdue') AND balance > 0
+                ORDER BY id
+                FOR UPDATE
+                "#
+            )
+            .bind(customer.id)
+            .fetch_all(&mut *tx)
+            .await?;
+            invoices.sort_by(|a, b| {
+                (a.due_date, a.invoice_date, &a.invoice_number).cmp(&(b.due_date, b.invoice_date, &b.invoice_number))
+            });
+
+            // Oldest money first; credits ahead of payments from the same day
+            let mut credits: Vec<OpenCredit> = credit_memos.iter()
+                .map(|cm| OpenCredit {
+                    source_type: AutoApplySource::CreditMemo,
+                    id: cm.id,
+                    number: Some(cm.credit_memo_number.clone()),
+                    date: cm.credit_memo_date,
+                    remaining: cm.balance,
+                })
+                .chain(payments.iter().map(|p| OpenCredit {
+                    source_type: AutoApplySource::Payment,
+                    id: p.id,
+                    number: p.payment_number.clone(),
+                    date: p.payment_date,
+                    remaining: p.unapplied_amount.unwrap_or(Decimal::ZERO),
+                }))
+                .collect();
+            credits.sort_by_key(|c| c.date);
+
+            let applications = Self::plan_applications(&mut credits, &invoices, req.prefer_exact_match);
+
+            if !req.preview {
+                for payment in &payments {
+                    let requests = Self::requests_for(&applications, payment.id)
+                        .map(|(invoice_id, amount_applied)| PaymentApplicationRequest { invoice_id, amount_applied })
+                        .collect::<Vec<_>>();
+                    if !requests.is_empty() {
+                        Self::apply_to_invoices(&mut tx, payment, &requests).await?;
+                    }
+                }
+                for credit_memo in &credit_memos {
+                    let requests = Self::requests_for(&applications, credit_memo.id)
+                        .map(|(invoice_id, amount_applied)| CreditMemoApplicationRequest { invoice_id, amount_applied })
+                        .collect::<Vec<_>>();
+                    if !requests.is_empty() {
+                        CreditMemoService::apply_to_invoices(&mut tx, credit_memo, &requests).await?;
+                    }
+                }
+                tx.commit().await?;
+            }
+            // A preview's transaction rolls back on drop, releasing the locks
+
+            if !applications.is_empty() {
+                results.push(CustomerAutoApplication {
+                    customer_id: customer.id,
+                    customer_name: customer.name,
+                    total_applied: applications.iter().map(|a| a.amount).sum(),
+                    applications,
+                });
+            }
+        }
+
+        if !req.preview && !results.is_empty() {
+            let _ = self.cache.delete_pattern("payment:*").await;
+            let _ = self.cache.delete_pattern("customer:*").await;
+            let _ = self.cache.delete_pattern("invoice:*").await;
+            let _ = self.cache.delete_pattern("credit_memo:*").await;
+            let _ = self.cache.delete_pattern("ar_aging:*").await;
+        }
+
+        Ok(AutoApplyRun {
+            preview: req.preview,
+            total_applied: results.iter().map(|c| c.total_applied).sum(),
+            customers: results,
+        })
+    }
+
+    /// Find invoices, bills, payments and credits whose stored balance or status has drifted
+    /// from the applications recorded against them
+    pub async fn check_application_consistency(&self, pool: &PgPool) -> Result<ApplicationConsistencyReport> {
+        let drifts = sqlx::query_as::<_, BalanceDrift>(
+            r#"
+            WITH expected AS (
+                SELECT 'invoice' AS document_type, id AS document_id, invoice_number AS document_number,
+                       balance AS recorded_balance,
+                       total_amount - invoice_applied_amount(id) AS expected_balance,
+                       status::varchar AS recorded_status,
+                       invoice_status_for_balance(status, total_amount - invoice_applied_amount(id), total_amount) AS expected_status
+                FROM invoices
+                WHERE company_id IS NOT DISTINCT FROM $1
+                UNION ALL
+                SELECT 'bill', id, bill_number,
+                       balance,
+                       total_amount - bill_applied_amount(id),
+                       status::varchar,
+                       bill_status_for_balance(status, total_amount - bill_applied_amount(id), total_amount)
+                FROM bills
+                WHERE company_id IS NOT DISTINCT FROM $1
+                UNION ALL
+                SELECT 'payment', p.id, p.payment_number,
+                       COALESCE(p.unapplied_amount, 0),
+                       CASE WHEN p.void_date IS NOT NULL THEN 0 ELSE
+                           p.amount
+                           - COALESCE((SELECT SUM(pa.amount_applied) FROM payment_applications pa WHERE pa.payment_id = p.id), 0)
+                           - COALESCE((SELECT SUM(r.amount) FROM customer_refunds r WHERE r.payment_id = p.id), 0)
+                       END,
+                       NULL::varchar,
+                       NULL::varchar
+                FROM payments p
+                WHERE p.company_id IS NOT DISTINCT FROM $1
+                UNION ALL
+                SELECT 'credit_memo', c.id, c.credit_memo_number, c.balance, e.balance, c.status::varchar,
+                       CASE WHEN c.status = 'void' THEN 'void' WHEN e.balance <= 0 THEN 'applied' ELSE 'open' END
+                FROM credit_memos c
+                CROSS JOIN LATERAL (
+                    SELECT CASE WHEN c.status = 'void' THEN 0 ELSE
+                        c.total_amount
+                        - COALESCE((SELECT SUM(ca.amount_applied) FROM credit_memo_applications ca WHERE ca.credit_memo_id = c.id), 0)
+                        - COALESCE((SELECT SUM(r.amount) FROM customer_refunds r WHERE r.credit_memo_id = c.id), 0)
+                    END AS balance
+                ) e
+                WHERE c.company_id IS NOT DISTINCT FROM $1
+                UNION ALL
+                SELECT 'vendor_credit', v.id, v.vendor_credit_number, v.balance, e.balance, v.status::varchar,
+                       CASE WHEN v.status = 'void' THEN 'void' WHEN e.balance <= 0 THEN 'applied' ELSE 'open' END
+                FROM vendor_credits v
+                CROSS JOIN LATERAL (
+                    SELECT CASE WHEN v.status = 'void' THEN 0 ELSE
+                        v.total_amount
+                        - COALESCE((SELECT SUM(va.amount_applied) FROM vendor_credit_applications va WHERE va.vendor_credit_id = v.id), 0)
+                    END AS balance
+                ) e
+                WHERE v.company_id IS NOT DISTINCT FROM $1
+            )
+            SELECT * FROM expected
+            WHERE recorded_balance <> expected_balance
+               OR recorded_status IS DISTINCT FROM expected_status
+            ORDER BY document_type, document_number
+            "#
+        )
+        .bind(self.company_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(ApplicationConsistencyReport {
+            consistent: drifts.is_empty(),
+            drifts,
+        })
     }
 
     /// Get payments applied to a specific invoice
@@ -351,11 +755,12 @@ impl PaymentService {
             r#"
             SELECT p.* FROM payments p
             INNER JOIN payment_applications pa ON p.id = pa.payment_id
-            WHERE pa.invoice_id = $1
+            WHERE pa.invoice_id = $1 AND p.company_id IS NOT DISTINCT FROM $2
             ORDER BY p.payment_date DESC, p.created_at DESC
             "#
         )
         .bind(invoice_id)
+        .bind(self.company_id)
         .fetch_all(pool)
         .await
         .map_err(|e| AppError::DatabaseError(e.to_string()))?;
@@ -367,10 +772,16 @@ impl PaymentService {
     pub async fn create_bill_payment(&self, pool: &PgPool, req: CreateBillPaymentRequest) -> Result<BillPayment> {
         // Validate request
         req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+        let company_id = CompanyService::record_company(self.company_id, req.company_id)?;
 
         // Start a transaction
         let mut tx = pool.begin().await?;
 
+        CompanyService::ensure_in_company(&mut tx, company_id, CompanyRecord::Contact, &[req.vendor_id]).await?;
+        if let Some(bank_account_id) = req.bank_account_id {
+            CompanyService::ensure_in_company(&mut tx, company_id, CompanyRecord::Account, &[bank_account_id]).await?;
+        }
+
         // Validate that total applications don't exceed payment amount
         let total_applications: Decimal = req.applications.iter()
             .map(|app| app.amount_applied)
@@ -382,6 +793,10 @@ impl PaymentService {
             ));
         }
 
+        let payment_number = NumberingService::assign_number(
+            &mut tx, company_id, SequenceDocumentType::Payment, req.payment_date, req.payment_number.clone(),
+        ).await?;
+
         // Create bill payment record
         let bill_payment = sqlx::query_as::<_, BillPayment>(
             r#"
@@ -395,7 +810,7 @@ impl PaymentService {
             "#,
         )
         .bind(Uuid::new_v4())
-        .bind(&req.payment_number)
+        .bind(&payment_number)
         .bind(req.vendor_id)
         .bind(req.payment_date)
         .bind(req.amount)
@@ -403,30 +818,19 @@ impl PaymentService {
         .bind(&req.reference_number)
         .bind(req.bank_account_id)
         .bind(&req.memo)
-        .bind(req.company_id)
+        .bind(company_id)
         .fetch_one(&mut *tx)
         .await
         .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
         // Create bill payment applications if any
         if !req.applications.is_empty() {
-            for application in req.applications {
-                // Validate that bill exists and belongs to the same vendor
-                let bill_exists = sqlx::query_scalar::<_, bool>(
-                    "SELECT EXISTS(SELECT 1 FROM bills WHERE id = $1 AND vendor_id = $2)"
-                )
-                .bind(application.bill_id)
-                .bind(req.vendor_id)
-                .fetch_one(&mut *tx)
-                .await
-                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
-
-                if !bill_exists {
-                    return Err(AppError::ValidationError(
-                        format!("Bill {} does not exist or does not belong to vendor", application.bill_id)
-                    ));
-                }
+            let amounts: Vec<(Uuid, Decimal)> = req.applications.iter()
+                .map(|app| (app.bill_id, app.amount_applied))
+                .collect();
+            BillService::lock_for_application(&mut tx, req.vendor_id, &amounts).await?;
 
+            for application in req.applications {
                 // Create bill payment application
                 sqlx::query_as::<_, BillPaymentApplication>(
                     r#"
@@ -444,25 +848,7 @@ impl PaymentService {
                 .await
                 .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
-                // Update bill balance
-                sqlx::query(
-                    r#"
-                    UPDATE bills
-                    SET balance = balance - $1,
-                        status = CASE
-                            WHEN balance - $1 <= 0 THEN 'paid'
-                            WHEN balance - $1 < total_amount THEN 'partial'
-                            ELSE status
-                        END,
-                        updated_at = NOW()
-                    WHERE id = $2
-                    "#
-                )
-                .bind(application.amount_applied)
-                .bind(application.bill_id)
-                .execute(&mut *tx)
-                .await
-                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+                BillService::recalculate_balance(&mut tx, application.bill_id).await?;
             }
         }
 
@@ -475,4 +861,132 @@ impl PaymentService {
 
         Ok(bill_payment)
     }
-}
\ No newline at end of file
+
+    /// Allocate credits to invoices, which are in the order they should be paid.
+    ///
+    /// With `prefer_exact_match`, a credit equal to an invoice's balance settles that invoice
+    /// first; whatever is left then goes oldest invoice first.
+    fn plan_applications(credits: &mut [OpenCredit], invoices: &[Invoice], prefer_exact_match: bool) -> Vec<AutoApplication> {
+        let mut balances: Vec<Decimal> = invoices.iter().map(|i| i.balance).collect();
+        let mut applications = Vec::new();
+
+        let mut allocate = |credit: &mut OpenCredit, index: usize, amount: Decimal, exact_match: bool, balances: &mut [Decimal]| {
+            credit.remaining -= amount;
+            balances[index] -= amount;
+            applications.push(AutoApplication {
+                source_type: credit.source_type,
+                source_id: credit.id,
+                source_number: credit.number.clone(),
+                invoice_id: invoices[index].id,
+                invoice_number: invoices[index].invoice_number.clone(),
+                amount,
+                exact_match,
+            });
+        };
+
+        if prefer_exact_match {
+            for credit in credits.iter_mut() {
+                if let Some(index) = balances.iter().position(|b| *b == credit.remaining) {
+                    let amount = credit.remaining;
+                    allocate(credit, index, amount, true, &mut balances);
+                }
+            }
+        }
+
+        for credit in credits.iter_mut() {
+            for index in 0..invoices.len() {
+                if credit.remaining <= Decimal::ZERO {
+                    break;
+                }
+                let amount = credit.remaining.min(balances[index]);
+                if amount > Decimal::ZERO {
+                    allocate(credit, index, amount, false, &mut balances);
+                }
+            }
+        }
+
+        applications
+    }
+
+    fn requests_for(applications: &[AutoApplication], source_id: Uuid) -> impl Iterator<Item = (Uuid, Decimal)> + '_ {
+        applications.iter()
+            .filter(move |a| a.source_id == source_id)
+            .map(|a| (a.invoice_id, a.amount))
+    }
+
+    async fn lock_payment(conn: &mut PgConnection, company_id: Option<Uuid>, payment_id: Uuid) -> Result<Payment> {
+        sqlx::query_as::<_, Payment>(
+            "SELECT * FROM payments WHERE id = $1 AND company_id IS NOT DISTINCT FROM $2 FOR UPDATE"
+        )
+        .bind(payment_id)
+        .bind(company_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Payment not found".to_string()))
+    }
+
+    /// Delete an application and reopen its invoice by the amount applied
+    async fn remove_application(conn: &mut PgConnection, application: &PaymentApplication) -> Result<()> {
+        let status: Option<String> = sqlx::query_scalar("SELECT status FROM invoices WHERE id = $1 FOR UPDATE")
+            .bind(application.invoice_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+        if let Some(status) = status.as_deref().filter(|s| matches!(*s, "void" | "written_off")) {
+            return Err(AppError::BadRequest(format!(
+                "Invoice {} is {} and cannot be reopened",
+                application.invoice_id,
+                status.replace('_', " ")
+            )));
+        }
+
+        sqlx::query("DELETE FROM payment_applications WHERE id = $1")
+            .bind(application.id)
+            .execute(&mut *conn)
+            .await?;
+
+        InvoiceService::recalculate_balance(conn, application.invoice_id).await?;
+
+        Ok(())
+    }
+
+    /// A fee amount paired with its account, if one was requested
+    fn fee(amount: Option<Decimal>, account_id: Option<Uuid>, label: &str) -> Result<Option<(Decimal, Uuid)>> {
+        match (amount.filter(|a| !a.is_zero()), account_id) {
+            (None, _) => Ok(None),
+            (Some(amount), _) if amount < Decimal::ZERO => Err(AppError::ValidationError(
+                format!("{} cannot be negative", label)
+            )),
+            (Some(amount), Some(account_id)) => Ok(Some((amount, account_id))),
+            (Some(_), None) => Err(AppError::ValidationError(format!("{} needs an account", label))),
+        }
+    }
+
+    async fn post_receipt(
+        conn: &mut PgConnection,
+        payment: &Payment,
+        date: NaiveDate,
+        amounts: &[(Uuid, Decimal)],
+        description: &str,
+        created_by: Option<Uuid>,
+    ) -> Result<Uuid> {
+        let entry = CreateTransactionRequest {
+            transaction_date: date,
+            description: Some(description.to_string()),
+            reference_number: payment.payment_number.clone(),
+            entry_number: None,
+            contact_id: Some(payment.customer_id),
+            company_id: payment.company_id,
+            journal_type: Some(JournalType::CashReceipts),
+            line_items: CreateLineItemRequest::from_signed_amounts(amounts, description),
+        };
+
+        TransactionService::post_entry(conn, &entry, created_by).await
+    }
+
+    async fn invalidate_cache(&self, customer_id: Uuid) {
+        let _ = self.cache.delete_pattern("payment:*").await;
+        let _ = self.cache.delete_pattern(&format!("customer:payments:*:{}", customer_id)).await;
+        let _ = self.cache.delete_pattern("invoice:*").await;
+        let _ = self.cache.delete_pattern("ar_aging:*").await;
+    }
+}
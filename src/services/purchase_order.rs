@@ -0,0 +1,570 @@
+use chrono::NaiveDate;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use validator::Validate;
+use rust_decimal::{Decimal, RoundingStrategy};
+use std::collections::HashMap;
+
+use crate::models::{
+    PurchaseOrder, PurchaseOrderLineItem, PurchaseOrderStatus, PurchaseOrderBill,
+    PurchaseOrderWithLineItems, PurchaseOrderConversion, CreatePurchaseOrderRequest,
+    ConvertPurchaseOrderRequest, OpenPurchaseOrder, CommittedSpend, OpenPurchaseOrdersReport,
+    CreateBillRequest, CreateBillLineItemRequest,
+};
+use crate::utils::{AppError, Result};
+use crate::services::{BillService, CacheService, CompanyRecord, CompanyService};
+
+#[derive(Clone)]
+pub struct PurchaseOrderService {
+    cache: CacheService,
+    bill_service: BillService,
+    company_id: Option<Uuid>,
+}
+
+impl PurchaseOrderService {
+    pub fn new_with_cache(cache: CacheService) -> Self {
+        Self {
+            bill_service: BillService::new_with_cache(cache.clone()),
+            cache,
+            company_id: None,
+        }
+    }
+
+    /// Scope the service to a company's records
+    pub fn for_company(&self, company_id: Option<Uuid>) -> Self {
+        Self {
+            cache: self.cache.clone(),
+            bill_service: self.bill_service.for_company(company_id),
+            company_id,
+        }
+    }
+
+    /// Create a purchase order. Purchase orders never post to the ledger.
+    pub async fn create_purchase_order(&self, pool: &PgPool, req: CreatePurchaseOrderRequest) -> Result<PurchaseOrderWithLineItems> {
+        // Validate request
+        req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+        let company_id = CompanyService::record_company(self.company_id, req.company_id)?;
+
+        for line_item in &req.line_items {
+            if line_item.quantity <= Decimal::ZERO {
+                return Err(AppError::ValidationError(
+                    format!("Line {} must have a positive quantity", line_item.line_number)
+                ));
+            }
+        }
+
+        let vendor_exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM contacts WHERE id = $1 AND contact_type = 'Vendor' AND company_id IS NOT DISTINCT FROM $2)"
+        )
+        .bind(req.vendor_id)
+        .bind(company_id)
+        .fetch_one(pool)
+        .await?;
+        if !vendor_exists {
+            return Err(AppError::ValidationError("Purchase orders must be for a vendor".to_string()));
+        }
+
+        let total_amount: Decimal = req.line_items.iter()
+            .map(|line| Self::line_amount(line.quantity, line.unit_price))
+            .sum();
+
+        let mut tx = pool.begin().await?;
+
+        let account_ids: Vec<Uuid> = req.line_items.iter().map(|l| l.expense_account_id).collect();
+        let item_ids: Vec<Uuid> = req.line_items.iter().filter_map(|l| l.item_id).collect();
+        CompanyService::ensure_in_company(&mut tx, company_id, CompanyRecord::Account, &account_ids).await?;
+        CompanyService::ensure_in_company(&mut tx, company_id, CompanyRecord::Item, &item_ids).await?;
+
+        let purchase_order = sqlx::query_as::<_, PurchaseOrder>(
+            r#"
+            INSERT INTO purchase_orders
+                (id, po_number, vendor_id, order_date, expected_date, total_amount, status,
+                 memo, shipping_address, company_id, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, NOW(), NOW())
+            RETURNING *
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(&req.po_number)
+        .bind(req.vendor_id)
+        .bind(req.order_date)
+        .bind(req.expected_date)
+        .bind(total_amount)
+        .bind(PurchaseOrderStatus::Open.to_string())
+        .bind(&req.memo)
+        .bind(&req.shipping_address)
+        .bind(company_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => AppError::Conflict(
+                format!("Purchase order number {} already exists", req.po_number)
+            ),
+            e => AppError::from(e),
+        })?;
+
+        let mut line_items = Vec::with_capacity(req.line_items.len());
+        for (index, line_item_req) in req.line_items.iter().enumerate() {
+            let line_item = sqlx::query_as::<_, PurchaseOrderLineItem>(
+                r#"
+                INSERT INTO purchase_order_line_items
+                    (id, purchase_order_id, line_number, description, quantity, unit_price, amount,
+                     expense_account_id, item_id, tax_code, expected_date, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, NOW(), NOW())
+                RETURNING *
+                "#,
+            )
+            .bind(Uuid::new_v4())
+            .bind(purchase_order.id)
+            .bind(index as i32 + 1) // Line numbers start at 1
+            .bind(&line_item_req.description)
+            .bind(line_item_req.quantity)
+            .bind(line_item_req.unit_price)
+            .bind(Self::line_amount(line_item_req.quantity, line_item_req.unit_price))
+            .bind(line_item_req.expense_account_id)
+            .bind(line_item_req.item_id)
+            .bind(&line_item_req.tax_code)
+            .bind(line_item_req.expected_date.or(req.expected_date))
+            .fetch_one(&mut *tx)
+            .await?;
+
+            line_items.push(line_item);
+        }
+
+        tx.commit().await?;
+
+        self.invalidate_cache().await;
+
+        Ok(PurchaseOrderWithLineItems {
+            purchase_order,
+            line_items,
+            bills: Vec::new(),
+        })
+    }
+
+    /// Get a purchase order with line items and the bills created from it
+    pub async fn get_purchase_order(&self, pool: &PgPool, purchase_order_id: Uuid) -> Result<PurchaseOrderWithLineItems> {
+        let cache_key = format!("purchase_order:{}", purchase_order_id);
+        if let Ok(Some(cached)) = self.cache.get::<PurchaseOrderWithLineItems>(&cache_key).await
+            && cached.purchase_order.company_id == self.company_id
+        {
+            return Ok(cached);
+        }
+
+        let purchase_order = sqlx::query_as::<_, PurchaseOrder>(
+            "SELECT * FROM purchase_orders WHERE id = $1 AND company_id IS NOT DISTINCT FROM $2"
+        )
+        .bind(purchase_order_id)
+        .bind(self.company_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Purchase order with id {} not found", purchase_order_id)))?;
+
+        let line_items = sqlx::query_as::<_, PurchaseOrderLineItem>(
+            "SELECT * FROM purchase_order_line_items WHERE purchase_order_id = $1 ORDER BY line_number"
+        )
+        .bind(purchase_order_id)
+        .fetch_all(pool)
+        .await?;
+
+        let bills = sqlx::query_as::<_, PurchaseOrderBill>(
+            "SELECT * FROM purchase_order_bills WHERE purchase_order_id = $1 ORDER BY created_at"
+        )
+        .bind(purchase_order_id)
+        .fetch_all(pool)
+        .await?;
+
+        let result = PurchaseOrderWithLineItems {
+            purchase_order,
+            line_items,
+            bills,
+        };
+
+        let _ = self.cache.set_with_ttl(&cache_key, &result, 600).await;
+
+        Ok(result)
+    }
+
+    /// List purchase orders, optionally by vendor and status
+    pub async fn list_purchase_orders(
+        &self,
+        pool: &PgPool,
+        vendor_id: Option<Uuid>,
+        status: Option<PurchaseOrderStatus>,
+    ) -> Result<Vec<PurchaseOrder>> {
+        let purchase_orders = sqlx::query_as::<_, PurchaseOrder>(
+            r#"
+            SELECT * FROM purchase_orders
+            WHERE ($1::uuid IS NULL OR vendor_id = $1)
+              AND ($2::varchar IS NULL OR status = $2)
+              AND company_id IS NOT DISTINCT FROM $3
+            ORDER BY order_date DESC, created_at DESC
+            "#
+        )
+        .bind(vendor_id)
+        .bind(status.map(|s| s.to_string()))
+        .bind(self.company_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(purchase_orders)
+    }
+
+    /// Bill request pre-filled from the order, for quantities not yet billed
+    /// (or the quantities given)
+    pub async fn prefill_bill_request(
+        &self,
+        pool: &PgPool,
+        purchase_order_id: Uuid,
+        req: ConvertPurchaseOrderRequest,
+    ) -> Result<CreateBillRequest> {
+        let mut conn = pool.acquire().await?;
+        let (purchase_order, line_items) = Self::load_for_billing(&mut conn, self.company_id, purchase_order_id, false).await?;
+        let quantities = Self::quantities_to_bill(&line_items, &req)?;
+
+        Self::build_bill_request(&purchase_order, &line_items, &quantities, req)
+    }
+
+    /// Create a bill from the order and record the quantities billed
+    pub async fn convert_to_bill(
+        &self,
+        pool: &PgPool,
+        purchase_order_id: Uuid,
+        req: ConvertPurchaseOrderRequest,
+    ) -> Result<PurchaseOrderConversion> {
+        // Validate request
+        req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+        let mut tx = pool.begin().await?;
+
+        // The order stays locked until the billed quantities are recorded, so
+        // concurrent conversions can't bill the same quantity twice
+        let (purchase_order, line_items) = Self::load_for_billing(&mut tx, self.company_id, purchase_order_id, true).await?;
+        let quantities = Self::quantities_to_bill(&line_items, &req)?;
+        let bill_request = Self::build_bill_request(&purchase_order, &line_items, &quantities, req)?;
+
+        let bill = self.bill_service.create_bill(pool, bill_request).await?;
+
+        let mut billed_amount = Decimal::ZERO;
+        for (line_item, quantity) in line_items.iter().zip(&quantities) {
+            if quantity.is_zero() {
+                continue;
+            }
+            let amount = Self::line_amount(*quantity, line_item.unit_price);
+            billed_amount += amount;
+
+            sqlx::query(
+                "UPDATE purchase_order_line_items SET billed_quantity = billed_quantity + $1, updated_at = NOW() WHERE id = $2"
+            )
+            .bind(quantity)
+            .bind(line_item.id)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO purchase_order_bills
+                    (id, purchase_order_id, purchase_order_line_id, bill_id, quantity, amount, created_at)
+                VALUES ($1, $2, $3, $4, $5, $6, NOW())
+                "#
+            )
+            .bind(Uuid::new_v4())
+            .bind(purchase_order_id)
+            .bind(line_item.id)
+            .bind(bill.id)
+            .bind(quantity)
+            .bind(amount)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let purchase_order = Self::refresh_status(&mut tx, purchase_order_id, billed_amount).await?;
+
+        tx.commit().await?;
+
+        self.invalidate_cache().await;
+
+        Ok(PurchaseOrderConversion { purchase_order, bill })
+    }
+
+    /// Close an order so its unbilled quantities no longer count as committed spend
+    pub async fn close_purchase_order(&self, pool: &PgPool, purchase_order_id: Uuid) -> Result<PurchaseOrder> {
+        let purchase_order = sqlx::query_as::<_, PurchaseOrder>(
+            "UPDATE purchase_orders SET status = $1, updated_at = NOW() WHERE id = $2 AND company_id IS NOT DISTINCT FROM $3 RETURNING *"
+        )
+        .bind(PurchaseOrderStatus::Closed.to_string())
+        .bind(purchase_order_id)
+        .bind(self.company_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Purchase order with id {} not found", purchase_order_id)))?;
+
+        self.invalidate_cache().await;
+
+        Ok(purchase_order)
+    }
+
+    /// Open purchase orders with their unbilled amounts, and committed spend by account
+    pub async fn generate_open_purchase_orders_report(&self, pool: &PgPool, as_of_date: NaiveDate) -> Result<OpenPurchaseOrdersReport> {
+        let purchase_orders = sqlx::query_as::<_, OpenPurchaseOrder>(
+            r#"
+            WITH billed AS (
+                SELECT pob.purchase_order_id, SUM(pob.amount) AS amount
+                FROM purchase_order_bills pob
+                INNER JOIN bills b ON b.id = pob.bill_id
+                WHERE b.bill_date <= $1
+                GROUP BY pob.purchase_order_id
+            )
+            SELECT
+                po.id,
+                po.po_number,
+                po.vendor_id,
+                c.name AS vendor_name,
+                po.order_date,
+                po.expected_date,
+                po.status,
+                po.total_amount,
+                COALESCE(billed.amount, 0) AS billed_amount,
+                po.total_amount - COALESCE(billed.amount, 0) AS open_amount
+            FROM purchase_orders po
+            INNER JOIN contacts c ON c.id = po.vendor_id
+            LEFT JOIN billed ON billed.purchase_order_id = po.id
+            WHERE po.status != 'closed'
+              AND po.order_date <= $1
+              AND po.company_id IS NOT DISTINCT FROM $2
+              AND po.total_amount - COALESCE(billed.amount, 0) > 0
+            ORDER BY po.expected_date NULLS LAST, po.order_date, po.po_number
+            "#
+        )
+        .bind(as_of_date)
+        .bind(self.company_id)
+        .fetch_all(pool)
+        .await?;
+
+        let committed_by_account = sqlx::query_as::<_, CommittedSpend>(
+            r#"
+            WITH billed AS (
+                SELECT pob.purchase_order_line_id, SUM(pob.amount) AS amount
+                FROM purchase_order_bills pob
+                INNER JOIN bills b ON b.id = pob.bill_id
+                WHERE b.bill_date <= $1
+                GROUP BY pob.purchase_order_line_id
+            )
+            SELECT
+                a.id AS account_id,
+                a.code AS account_code,
+                a.name AS account_name,
+                SUM(li.amount - COALESCE(billed.amount, 0)) AS amount
+            FROM purchase_order_line_items li
+            INNER JOIN purchase_orders po ON po.id = li.purchase_order_id
+            INNER JOIN chart_of_accounts a ON a.id = li.expense_account_id
+            LEFT JOIN billed ON billed.purchase_order_line_id = li.id
+            WHERE po.status != 'closed' AND po.order_date <= $1
+              AND po.company_id IS NOT DISTINCT FROM $2
+            GROUP BY a.id, a.code, a.name
+            HAVING SUM(li.amount - COALESCE(billed.amount, 0)) > 0
+            ORDER BY a.code
+            "#
+        )
+        .bind(as_of_date)
+        .bind(self.company_id)
+        .fetch_all(pool)
+        .await?;
+
+        let total_committed = purchase_orders.iter().map(|po| po.open_amount).sum();
+
+        Ok(OpenPurchaseOrdersReport {
+            as_of_date,
+            total_committed,
+            purchase_orders,
+            committed_by_account,
+        })
+    }
+
+    /// Release the quantities a bill billed against purchase orders, reopening them.
+    /// Called when a bill is voided or deleted; orders closed by hand stay closed.
+    pub async fn release_bill(conn: &mut PgConnection, bill_id: Uuid) -> Result<bool> {
+        let billed = sqlx::query_as::<_, PurchaseOrderBill>(
+            "SELECT * FROM purchase_order_bills WHERE bill_id = $1"
+        )
+        .bind(bill_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        if billed.is_empty() {
+            return Ok(false);
+        }
+
+        let mut released: HashMap<Uuid, Decimal> = HashMap::new();
+        for line in &billed {
+            sqlx::query(
+                "UPDATE purchase_order_line_items SET billed_quantity = billed_quantity - $1, updated_at = NOW() WHERE id = $2"
+            )
+            .bind(line.quantity)
+            .bind(line.purchase_order_line_id)
+            .execute(&mut *conn)
+            .await?;
+            *released.entry(line.purchase_order_id).or_default() += line.amount;
+        }
+
+        sqlx::query("DELETE FROM purchase_order_bills WHERE bill_id = $1")
+            .bind(bill_id)
+            .execute(&mut *conn)
+            .await?;
+
+        for (purchase_order_id, amount) in released {
+            Self::refresh_status(conn, purchase_order_id, -amount).await?;
+        }
+
+        Ok(true)
+    }
+
+    // Helper methods
+
+    async fn load_for_billing(
+        conn: &mut PgConnection,
+        company_id: Option<Uuid>,
+        purchase_order_id: Uuid,
+        lock: bool,
+    ) -> Result<(PurchaseOrder, Vec<PurchaseOrderLineItem>)> {
+        let lock_clause = if lock { " FOR UPDATE" } else { "" };
+
+        let purchase_order = sqlx::query_as::<_, PurchaseOrder>(
+            &format!("SELECT * FROM purchase_orders WHERE id = $1 AND company_id IS NOT DISTINCT FROM $2{}", lock_clause)
+        )
+        .bind(purchase_order_id)
+        .bind(company_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Purchase order with id {} not found", purchase_order_id)))?;
+
+        if purchase_order.status == PurchaseOrderStatus::Closed {
+            return Err(AppError::ValidationError("Purchase order is closed".to_string()));
+        }
+
+        let line_items = sqlx::query_as::<_, PurchaseOrderLineItem>(
+            &format!("SELECT * FROM purchase_order_line_items WHERE purchase_order_id = $1 ORDER BY line_number{}", lock_clause)
+        )
+        .bind(purchase_order_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok((purchase_order, line_items))
+    }
+
+    /// Quantity to bill on each order line, in line order
+    fn quantities_to_bill(line_items: &[PurchaseOrderLineItem], req: &ConvertPurchaseOrderRequest) -> Result<Vec<Decimal>> {
+        let remaining = |line: &PurchaseOrderLineItem| line.quantity - line.billed_quantity;
+
+        let Some(lines) = &req.lines else {
+            return Ok(line_items.iter().map(remaining).collect());
+        };
+
+        let mut requested: HashMap<Uuid, Decimal> = HashMap::new();
+        for line in lines {
+            if line.quantity <= Decimal::ZERO {
+                return Err(AppError::ValidationError("Quantities to bill must be positive".to_string()));
+            }
+            if !line_items.iter().any(|l| l.id == line.purchase_order_line_id) {
+                return Err(AppError::ValidationError(
+                    format!("Line {} is not on this purchase order", line.purchase_order_line_id)
+                ));
+            }
+            *requested.entry(line.purchase_order_line_id).or_default() += line.quantity;
+        }
+
+        line_items.iter()
+            .map(|line| {
+                let quantity = requested.get(&line.id).copied().unwrap_or(Decimal::ZERO);
+                if quantity > remaining(line) {
+                    return Err(AppError::ValidationError(format!(
+                        "Line {} has only {} left to bill", line.line_number, remaining(line)
+                    )));
+                }
+                Ok(quantity)
+            })
+            .collect()
+    }
+
+    fn build_bill_request(
+        purchase_order: &PurchaseOrder,
+        line_items: &[PurchaseOrderLineItem],
+        quantities: &[Decimal],
+        req: ConvertPurchaseOrderRequest,
+    ) -> Result<CreateBillRequest> {
+        let bill_lines: Vec<CreateBillLineItemRequest> = line_items.iter()
+            .zip(quantities)
+            .filter(|(_, quantity)| !quantity.is_zero())
+            .enumerate()
+            .map(|(index, (line_item, quantity))| CreateBillLineItemRequest {
+                line_number: index as i32 + 1,
+                description: line_item.description.clone(),
+                amount: Self::line_amount(*quantity, line_item.unit_price),
+                expense_account_id: line_item.expense_account_id,
+                billable: None,
+                customer_id: None,
+                item_id: line_item.item_id,
+                quantity: Some(*quantity),
+                tax_code: line_item.tax_code.clone(),
+                dimension_value_ids: Vec::new(),
+            })
+            .collect();
+
+        if bill_lines.is_empty() {
+            return Err(AppError::ValidationError(
+                "Nothing left to bill on this purchase order".to_string()
+            ));
+        }
+
+        Ok(CreateBillRequest {
+            bill_number: req.bill_number,
+            vendor_id: purchase_order.vendor_id,
+            bill_date: req.bill_date,
+            due_date: req.due_date,
+            memo: Some(format!("PO {}", purchase_order.po_number)),
+            company_id: purchase_order.company_id,
+            ap_account_id: req.ap_account_id,
+            line_items: bill_lines,
+        })
+    }
+
+    /// Adjust the billed amount and derive the status from the billed quantities
+    async fn refresh_status(conn: &mut PgConnection, purchase_order_id: Uuid, billed_change: Decimal) -> Result<PurchaseOrder> {
+        let purchase_order = sqlx::query_as::<_, PurchaseOrder>(
+            r#"
+            UPDATE purchase_orders po
+            SET billed_amount = po.billed_amount + $2,
+                status = CASE
+                    -- Orders closed by hand before being fully billed stay closed
+                    WHEN po.status = 'closed' AND po.billed_amount < po.total_amount THEN 'closed'
+                    WHEN NOT EXISTS (
+                        SELECT 1 FROM purchase_order_line_items li
+                        WHERE li.purchase_order_id = po.id AND li.billed_quantity < li.quantity
+                    ) THEN 'closed'
+                    WHEN EXISTS (
+                        SELECT 1 FROM purchase_order_line_items li
+                        WHERE li.purchase_order_id = po.id AND li.billed_quantity > 0
+                    ) THEN 'partially_received'
+                    ELSE 'open'
+                END,
+                updated_at = NOW()
+            WHERE po.id = $1
+            RETURNING *
+            "#
+        )
+        .bind(purchase_order_id)
+        .bind(billed_change)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(purchase_order)
+    }
+
+    fn line_amount(quantity: Decimal, unit_price: Decimal) -> Decimal {
+        (quantity * unit_price).round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero)
+    }
+
+    async fn invalidate_cache(&self) {
+        let _ = self.cache.delete_pattern("purchase_order:*").await;
+    }
+}
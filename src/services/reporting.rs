@@ -1,27 +1,34 @@
 use sqlx::PgPool;
 use rust_decimal::Decimal;
+use uuid::Uuid;
 
 use crate::models::{
     TrialBalance, TrialBalanceEntry, ProfitLossStatement, ProfitLossEntry,
     BalanceSheet, BalanceSheetEntry, AccountsReceivableAging, AgingBucket,
-    DateRangeRequest, DateRequest
+    DateRangeRequest, DateRequest, DimensionFilter
 };
 use crate::utils::{AppError, Result};
-use crate::services::CacheService;
+use crate::services::{CacheService, CompanyService};
 
 #[derive(Clone)]
 pub struct ReportingService {
     cache: CacheService,
+    company_id: Option<Uuid>,
 }
 
 impl ReportingService {
     pub fn new_with_cache(cache: CacheService) -> Self {
-        Self { cache }
+        Self { cache, company_id: None }
+    }
+
+    /// Scope the service to a company's records
+    pub fn for_company(&self, company_id: Option<Uuid>) -> Self {
+        Self { company_id, ..self.clone() }
     }
 
     /// Generate trial balance as of a specific date
-    pub async fn generate_trial_balance(&self, pool: &PgPool, req: DateRequest) -> Result<TrialBalance> {
-        let cache_key = format!("trial_balance:{}", req.as_of_date);
+    pub async fn generate_trial_balance(&self, pool: &PgPool, req: DateRequest, filter: &DimensionFilter) -> Result<TrialBalance> {
+        let cache_key = format!("trial_balance:{}:{}{}", CompanyService::cache_scope(self.company_id), req.as_of_date, filter.cache_key());
 
         // Try cache first
         if let Ok(Some(cached_report)) = self.cache.get::<TrialBalance>(&cache_key).await {
@@ -37,6 +44,8 @@ impl ReportingService {
                     a.code,
                     a.name,
                     a.account_type::text as account_type,
+                    gv.id as dimension_value_id,
+                    gv.name as dimension_value_name,
                     COALESCE(SUM(
                         CASE
                             WHEN a.account_type IN ('Asset', 'Expense') THEN tl.debit_amount - tl.credit_amount
@@ -45,10 +54,25 @@ impl ReportingService {
                     ), 0) as balance
                 FROM chart_of_accounts a
                 LEFT JOIN transaction_line_items tl ON a.id = tl.account_id
+                    AND NOT EXISTS (
+                        -- Each dimension named in the filter must match one of its listed values
+                        SELECT 1 FROM dimension_values fv
+                        WHERE fv.id = ANY($2)
+                          AND NOT EXISTS (
+                              SELECT 1 FROM transaction_line_dimensions fd
+                              WHERE fd.transaction_line_item_id = tl.id
+                                AND fd.dimension_type_id = fv.dimension_type_id
+                                AND fd.dimension_value_id = ANY($2)
+                          )
+                    )
                 LEFT JOIN transactions t ON tl.transaction_id = t.id
                     AND t.transaction_date <= $1
                     AND a.is_active = true
-                GROUP BY a.id, a.code, a.name, a.account_type
+                LEFT JOIN transaction_line_dimensions gd ON gd.transaction_line_item_id = tl.id
+                    AND gd.dimension_type_id = $3
+                LEFT JOIN dimension_values gv ON gv.id = gd.dimension_value_id
+                WHERE a.company_id IS NOT DISTINCT FROM $4
+                GROUP BY a.id, a.code, a.name, a.account_type, gv.id, gv.name
                 HAVING ABS(COALESCE(SUM(
                     CASE
                         WHEN a.account_type IN ('Asset', 'Expense') THEN tl.debit_amount - tl.credit_amount
@@ -71,12 +95,17 @@ impl ReportingService {
                     WHEN account_type IN ('Liability', 'Equity', 'Revenue') AND balance > 0 THEN balance
                     ELSE 0
                 END as credit,
-                balance
+                balance,
+                dimension_value_id,
+                dimension_value_name
             FROM account_balances
-            ORDER BY account_code
+            ORDER BY account_code, dimension_value_name NULLS FIRST
             "#
         )
         .bind(req.as_of_date)
+        .bind(&filter.dimension_value_ids)
+        .bind(filter.group_by_dimension_type_id)
+        .bind(self.company_id)
         .fetch_all(pool)
         .await
         .map_err(|e| AppError::DatabaseError(e.to_string()))?;
@@ -101,8 +130,8 @@ impl ReportingService {
     }
 
     /// Generate profit and loss statement for a date range
-    pub async fn generate_profit_loss(&self, pool: &PgPool, req: DateRangeRequest) -> Result<ProfitLossStatement> {
-        let cache_key = format!("profit_loss:{}:{}", req.start_date, req.end_date);
+    pub async fn generate_profit_loss(&self, pool: &PgPool, req: DateRangeRequest, filter: &DimensionFilter) -> Result<ProfitLossStatement> {
+        let cache_key = format!("profit_loss:{}:{}:{}{}", CompanyService::cache_scope(self.company_id), req.start_date, req.end_date, filter.cache_key());
 
         // Try cache first
         if let Ok(Some(cached_report)) = self.cache.get::<ProfitLossStatement>(&cache_key).await {
@@ -117,21 +146,41 @@ impl ReportingService {
                 a.code as account_code,
                 a.name as account_name,
                 a.account_type::text as account_type,
-                COALESCE(SUM(tl.credit_amount - tl.debit_amount), 0) as amount
+                COALESCE(SUM(tl.credit_amount - tl.debit_amount), 0) as amount,
+                gv.id as dimension_value_id,
+                gv.name as dimension_value_name
             FROM chart_of_accounts a
             INNER JOIN transaction_line_items tl ON a.id = tl.account_id
+                AND NOT EXISTS (
+                    -- Each dimension named in the filter must match one of its listed values
+                    SELECT 1 FROM dimension_values fv
+                    WHERE fv.id = ANY($3)
+                      AND NOT EXISTS (
+                          SELECT 1 FROM transaction_line_dimensions fd
+                          WHERE fd.transaction_line_item_id = tl.id
+                            AND fd.dimension_type_id = fv.dimension_type_id
+                            AND fd.dimension_value_id = ANY($3)
+                      )
+                )
             INNER JOIN transactions t ON tl.transaction_id = t.id
                 AND t.transaction_date BETWEEN $1 AND $2
                 AND a.account_type = 'Revenue'
                 AND a.is_active = true
                 AND t.status = 'posted'
-            GROUP BY a.id, a.code, a.name, a.account_type
+            LEFT JOIN transaction_line_dimensions gd ON gd.transaction_line_item_id = tl.id
+                AND gd.dimension_type_id = $4
+            LEFT JOIN dimension_values gv ON gv.id = gd.dimension_value_id
+            WHERE a.company_id IS NOT DISTINCT FROM $5
+            GROUP BY a.id, a.code, a.name, a.account_type, gv.id, gv.name
             HAVING ABS(COALESCE(SUM(tl.credit_amount - tl.debit_amount), 0)) > 0.01
-            ORDER BY a.code
+            ORDER BY a.code, gv.name NULLS FIRST
             "#
         )
         .bind(req.start_date)
         .bind(req.end_date)
+        .bind(&filter.dimension_value_ids)
+        .bind(filter.group_by_dimension_type_id)
+        .bind(self.company_id)
         .fetch_all(pool)
         .await
         .map_err(|e| AppError::DatabaseError(e.to_string()))?;
@@ -144,21 +193,41 @@ impl ReportingService {
                 a.code as account_code,
                 a.name as account_name,
                 a.account_type::text as account_type,
-                COALESCE(SUM(tl.debit_amount - tl.credit_amount), 0) as amount
+                COALESCE(SUM(tl.debit_amount - tl.credit_amount), 0) as amount,
+                gv.id as dimension_value_id,
+                gv.name as dimension_value_name
             FROM chart_of_accounts a
             INNER JOIN transaction_line_items tl ON a.id = tl.account_id
+                AND NOT EXISTS (
+                    -- Each dimension named in the filter must match one of its listed values
+                    SELECT 1 FROM dimension_values fv
+                    WHERE fv.id = ANY($3)
+                      AND NOT EXISTS (
+                          SELECT 1 FROM transaction_line_dimensions fd
+                          WHERE fd.transaction_line_item_id = tl.id
+                            AND fd.dimension_type_id = fv.dimension_type_id
+                            AND fd.dimension_value_id = ANY($3)
+                      )
+                )
             INNER JOIN transactions t ON tl.transaction_id = t.id
                 AND t.transaction_date BETWEEN $1 AND $2
                 AND a.account_type = 'Expense'
                 AND a.is_active = true
                 AND t.status = 'posted'
-            GROUP BY a.id, a.code, a.name, a.account_type
+            LEFT JOIN transaction_line_dimensions gd ON gd.transaction_line_item_id = tl.id
+                AND gd.dimension_type_id = $4
+            LEFT JOIN dimension_values gv ON gv.id = gd.dimension_value_id
+            WHERE a.company_id IS NOT DISTINCT FROM $5
+            GROUP BY a.id, a.code, a.name, a.account_type, gv.id, gv.name
             HAVING ABS(COALESCE(SUM(tl.debit_amount - tl.credit_amount), 0)) > 0.01
-            ORDER BY a.code
+            ORDER BY a.code, gv.name NULLS FIRST
             "#
         )
         .bind(req.start_date)
         .bind(req.end_date)
+        .bind(&filter.dimension_value_ids)
+        .bind(filter.group_by_dimension_type_id)
+        .bind(self.company_id)
         .fetch_all(pool)
         .await
         .map_err(|e| AppError::DatabaseError(e.to_string()))?;
@@ -185,8 +254,8 @@ impl ReportingService {
     }
 
     /// Generate balance sheet as of a specific date
-    pub async fn generate_balance_sheet(&self, pool: &PgPool, req: DateRequest) -> Result<BalanceSheet> {
-        let cache_key = format!("balance_sheet:{}", req.as_of_date);
+    pub async fn generate_balance_sheet(&self, pool: &PgPool, req: DateRequest, filter: &DimensionFilter) -> Result<BalanceSheet> {
+        let cache_key = format!("balance_sheet:{}:{}{}", CompanyService::cache_scope(self.company_id), req.as_of_date, filter.cache_key());
 
         // Try cache first
         if let Ok(Some(cached_report)) = self.cache.get::<BalanceSheet>(&cache_key).await {
@@ -200,20 +269,40 @@ impl ReportingService {
                 a.id as account_id,
                 a.code as account_code,
                 a.name as account_name,
-                COALESCE(SUM(tl.debit_amount - tl.credit_amount), 0) as amount
+                COALESCE(SUM(tl.debit_amount - tl.credit_amount), 0) as amount,
+                gv.id as dimension_value_id,
+                gv.name as dimension_value_name
             FROM chart_of_accounts a
             LEFT JOIN transaction_line_items tl ON a.id = tl.account_id
+                AND NOT EXISTS (
+                    -- Each dimension named in the filter must match one of its listed values
+                    SELECT 1 FROM dimension_values fv
+                    WHERE fv.id = ANY($2)
+                      AND NOT EXISTS (
+                          SELECT 1 FROM transaction_line_dimensions fd
+                          WHERE fd.transaction_line_item_id = tl.id
+                            AND fd.dimension_type_id = fv.dimension_type_id
+                            AND fd.dimension_value_id = ANY($2)
+                      )
+                )
             LEFT JOIN transactions t ON tl.transaction_id = t.id
                 AND t.transaction_date <= $1
                 AND a.account_type = 'Asset'
                 AND a.is_active = true
                 AND t.status = 'posted'
-            GROUP BY a.id, a.code, a.name
+            LEFT JOIN transaction_line_dimensions gd ON gd.transaction_line_item_id = tl.id
+                AND gd.dimension_type_id = $3
+            LEFT JOIN dimension_values gv ON gv.id = gd.dimension_value_id
+            WHERE a.company_id IS NOT DISTINCT FROM $4
+            GROUP BY a.id, a.code, a.name, gv.id, gv.name
             HAVING ABS(COALESCE(SUM(tl.debit_amount - tl.credit_amount), 0)) > 0.01
-            ORDER BY a.code
+            ORDER BY a.code, gv.name NULLS FIRST
             "#
         )
         .bind(req.as_of_date)
+        .bind(&filter.dimension_value_ids)
+        .bind(filter.group_by_dimension_type_id)
+        .bind(self.company_id)
         .fetch_all(pool)
         .await
         .map_err(|e| AppError::DatabaseError(e.to_string()))?;
@@ -225,20 +314,40 @@ impl ReportingService {
                 a.id as account_id,
                 a.code as account_code,
                 a.name as account_name,
-                COALESCE(SUM(tl.credit_amount - tl.debit_amount), 0) as amount
+                COALESCE(SUM(tl.credit_amount - tl.debit_amount), 0) as amount,
+                gv.id as dimension_value_id,
+                gv.name as dimension_value_name
             FROM chart_of_accounts a
             LEFT JOIN transaction_line_items tl ON a.id = tl.account_id
+                AND NOT EXISTS (
+                    -- Each dimension named in the filter must match one of its listed values
+                    SELECT 1 FROM dimension_values fv
+                    WHERE fv.id = ANY($2)
+                      AND NOT EXISTS (
+                          SELECT 1 FROM transaction_line_dimensions fd
+                          WHERE fd.transaction_line_item_id = tl.id
+                            AND fd.dimension_type_id = fv.dimension_type_id
+                            AND fd.dimension_value_id = ANY($2)
+                      )
+                )
             LEFT JOIN transactions t ON tl.transaction_id = t.id
                 AND t.transaction_date <= $1
                 AND a.account_type = 'Liability'
                 AND a.is_active = true
                 AND t.status = 'posted'
-            GROUP BY a.id, a.code, a.name
+            LEFT JOIN transaction_line_dimensions gd ON gd.transaction_line_item_id = tl.id
+                AND gd.dimension_type_id = $3
+            LEFT JOIN dimension_values gv ON gv.id = gd.dimension_value_id
+            WHERE a.company_id IS NOT DISTINCT FROM $4
+            GROUP BY a.id, a.code, a.name, gv.id, gv.name
             HAVING ABS(COALESCE(SUM(tl.credit_amount - tl.debit_amount), 0)) > 0.01
-            ORDER BY a.code
+            ORDER BY a.code, gv.name NULLS FIRST
             "#
         )
         .bind(req.as_of_date)
+        .bind(&filter.dimension_value_ids)
+        .bind(filter.group_by_dimension_type_id)
+        .bind(self.company_id)
         .fetch_all(pool)
         .await
         .map_err(|e| AppError::DatabaseError(e.to_string()))?;
@@ -250,20 +359,40 @@ impl ReportingService {
                 a.id as account_id,
                 a.code as account_code,
                 a.name as account_name,
-                COALESCE(SUM(tl.credit_amount - tl.debit_amount), 0) as amount
+                COALESCE(SUM(tl.credit_amount - tl.debit_amount), 0) as amount,
+                gv.id as dimension_value_id,
+                gv.name as dimension_value_name
             FROM chart_of_accounts a
             LEFT JOIN transaction_line_items tl ON a.id = tl.account_id
+                AND NOT EXISTS (
+                    -- Each dimension named in the filter must match one of its listed values
+                    SELECT 1 FROM dimension_values fv
+                    WHERE fv.id = ANY($2)
+                      AND NOT EXISTS (
+                          SELECT 1 FROM transaction_line_dimensions fd
+                          WHERE fd.transaction_line_item_id = tl.id
+                            AND fd.dimension_type_id = fv.dimension_type_id
+                            AND fd.dimension_value_id = ANY($2)
+                      )
+                )
             LEFT JOIN transactions t ON tl.transaction_id = t.id
                 AND t.transaction_date <= $1
                 AND a.account_type = 'Equity'
                 AND a.is_active = true
                 AND t.status = 'posted'
-            GROUP BY a.id, a.code, a.name
+            LEFT JOIN transaction_line_dimensions gd ON gd.transaction_line_item_id = tl.id
+                AND gd.dimension_type_id = $3
+            LEFT JOIN dimension_values gv ON gv.id = gd.dimension_value_id
+            WHERE a.company_id IS NOT DISTINCT FROM $4
+            GROUP BY a.id, a.code, a.name, gv.id, gv.name
             HAVING ABS(COALESCE(SUM(tl.credit_amount - tl.debit_amount), 0)) > 0.01
-            ORDER BY a.code
+            ORDER BY a.code, gv.name NULLS FIRST
             "#
         )
         .bind(req.as_of_date)
+        .bind(&filter.dimension_value_ids)
+        .bind(filter.group_by_dimension_type_id)
+        .bind(self.company_id)
         .fetch_all(pool)
         .await
         .map_err(|e| AppError::DatabaseError(e.to_string()))?;
@@ -290,8 +419,8 @@ impl ReportingService {
     }
 
     /// Generate accounts receivable aging report as of a specific date
-    pub async fn generate_ar_aging(&self, pool: &PgPool, req: DateRequest) -> Result<AccountsReceivableAging> {
-        let cache_key = format!("ar_aging:{}", req.as_of_date);
+    pub async fn generate_ar_aging(&self, pool: &PgPool, req: DateRequest, filter: &DimensionFilter) -> Result<AccountsReceivableAging> {
+        let cache_key = format!("ar_aging:{}:{}{}", CompanyService::cache_scope(self.company_id), req.as_of_date, filter.cache_key());
 
         // Try cache first
         if let Ok(Some(cached_report)) = self.cache.get::<AccountsReceivableAging>(&cache_key).await {
@@ -301,14 +430,49 @@ impl ReportingService {
         // Query aging buckets
         let buckets = sqlx::query_as::<_, AgingBucket>(
             r#"
-            WITH invoice_aging AS (
+            WITH line_totals AS (
+                SELECT invoice_id, SUM(amount) as total
+                FROM invoice_line_items
+                GROUP BY invoice_id
+            ),
+            -- Each invoice's balance is spread over its lines by amount; only lines matching
+            -- the filter count, split by the group-by dimension
+            invoice_shares AS (
+                SELECT
+                    il.invoice_id,
+                    gv.id as dimension_value_id,
+                    gv.name as dimension_value_name,
+                    COALESCE(SUM(il.amount) / NULLIF(lt.total, 0), 1) as share
+                FROM invoice_line_items il
+                INNER JOIN line_totals lt ON lt.invoice_id = il.invoice_id
+                LEFT JOIN invoice_line_dimensions gd ON gd.invoice_line_item_id = il.id
+                    AND gd.dimension_type_id = $3
+                LEFT JOIN dimension_values gv ON gv.id = gd.dimension_value_id
+                WHERE NOT EXISTS (
+                    SELECT 1 FROM dimension_values fv
+                    WHERE fv.id = ANY($2)
+                      AND NOT EXISTS (
+                          SELECT 1 FROM invoice_line_dimensions fd
+                          WHERE fd.invoice_line_item_id = il.id
+                            AND fd.dimension_type_id = fv.dimension_type_id
+                            AND fd.dimension_value_id = ANY($2)
+                      )
+                )
+                GROUP BY il.invoice_id, lt.total, gv.id, gv.name
+                UNION ALL
+                -- Invoices without lines can't be tagged and only appear unfiltered
+                SELECT i.id, NULL, NULL, 1
+                FROM invoices i
+                WHERE cardinality($2::uuid[]) = 0
+                    AND NOT EXISTS (SELECT 1 FROM invoice_line_items il WHERE il.invoice_id = i.id)
+            ),
+            invoice_aging AS (
                 SELECT
                     c.id as customer_id,
                     c.name as customer_name,
-                    i.id as invoice_id,
-                    i.invoice_date,
-                    i.due_date,
-                    i.balance,
+                    s.dimension_value_id,
+                    s.dimension_value_name,
+                    ROUND(i.balance * s.share, 2) as balance,
                     CASE
                         WHEN $1 - i.due_date < 0 THEN 0
                         WHEN $1 - i.due_date <= 30 THEN 1
@@ -318,9 +482,33 @@ impl ReportingService {
                     END as aging_bucket
                 FROM invoices i
                 INNER JOIN contacts c ON i.customer_id = c.id
+                INNER JOIN invoice_shares s ON s.invoice_id = i.id
                 WHERE i.balance > 0.01
+                    AND i.company_id IS NOT DISTINCT FROM $4
                     AND i.invoice_date <= $1
                     AND i.status != 'paid'
+            ),
+            -- Bad debt written off by the as-of date, less what has been recovered since
+            written_off AS (
+                SELECT
+                    w.customer_id,
+                    c.name as customer_name,
+                    s.dimension_value_id,
+                    s.dimension_value_name,
+                    ROUND(w.amount * s.share, 2) as written_off
+                FROM (
+                    SELECT invoice_id, customer_id, amount
+                    FROM invoice_write_offs
+                    WHERE write_off_date <= $1
+                    UNION ALL
+                    SELECT wo.invoice_id, wo.customer_id, -r.amount
+                    FROM write_off_recoveries r
+                    INNER JOIN invoice_write_offs wo ON wo.id = r.write_off_id
+                    WHERE r.recovery_date <= $1
+                ) w
+                INNER JOIN contacts c ON w.customer_id = c.id
+                INNER JOIN invoice_shares s ON s.invoice_id = w.invoice_id
+                WHERE c.company_id IS NOT DISTINCT FROM $4
             )
             SELECT
                 customer_id,
@@ -330,24 +518,40 @@ impl ReportingService {
                 COALESCE(SUM(CASE WHEN aging_bucket = 2 THEN balance ELSE 0 END), 0) as days_31_60,
                 COALESCE(SUM(CASE WHEN aging_bucket = 3 THEN balance ELSE 0 END), 0) as days_61_90,
                 COALESCE(SUM(CASE WHEN aging_bucket = 4 THEN balance ELSE 0 END), 0) as days_91_plus,
-                COALESCE(SUM(balance), 0) as total
-            FROM invoice_aging
-            GROUP BY customer_id, customer_name
-            HAVING COALESCE(SUM(balance), 0) > 0.01
-            ORDER BY customer_name
+                COALESCE(SUM(balance), 0) as total,
+                COALESCE(SUM(written_off), 0) as written_off,
+                dimension_value_id,
+                dimension_value_name
+            FROM (
+                SELECT customer_id, customer_name, dimension_value_id, dimension_value_name,
+                    aging_bucket, balance, 0 as written_off
+                FROM invoice_aging
+                UNION ALL
+                SELECT customer_id, customer_name, dimension_value_id, dimension_value_name,
+                    NULL, 0, written_off
+                FROM written_off
+            ) r
+            GROUP BY customer_id, customer_name, dimension_value_id, dimension_value_name
+            HAVING COALESCE(SUM(balance), 0) > 0.01 OR COALESCE(SUM(written_off), 0) > 0.01
+            ORDER BY customer_name, dimension_value_name NULLS FIRST
             "#
         )
         .bind(req.as_of_date)
+        .bind(&filter.dimension_value_ids)
+        .bind(filter.group_by_dimension_type_id)
+        .bind(self.company_id)
         .fetch_all(pool)
         .await
         .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
         // Calculate total outstanding
         let total_outstanding: Decimal = buckets.iter().map(|b| b.total).sum();
+        let total_written_off: Decimal = buckets.iter().map(|b| b.written_off).sum();
 
         let ar_aging = AccountsReceivableAging {
             as_of_date: req.as_of_date,
             total_outstanding,
+            total_written_off,
             buckets,
         };
 
@@ -0,0 +1,487 @@
+use chrono::{Datelike, NaiveDate};
+use sqlx::PgPool;
+use uuid::Uuid;
+use rust_decimal::Decimal;
+
+use crate::models::{
+    AgingBucket, Contact, CustomerStatement, StatementLine, StatementStyle,
+};
+use crate::utils::{AppError, Result};
+use crate::utils::pdf::{format_amount, Align, PdfDocument};
+use crate::services::{CacheService, CompanyService};
+
+#[derive(Clone)]
+pub struct StatementService {
+    // Statements are always computed live so payments show up immediately
+    #[allow(dead_code)]
+    cache: CacheService,
+    company_id: Option<Uuid>,
+}
+
+impl StatementService {
+    pub fn new_with_cache(cache: CacheService) -> Self {
+        Self { cache, company_id: None }
+    }
+
+    /// Scope the service to a company's records
+    pub fn for_company(&self, company_id: Option<Uuid>) -> Self {
+        Self { company_id, ..self.clone() }
+    }
+
+    /// Build a statement for one customer
+    pub async fn generate_statement(
+        &self,
+        pool: &PgPool,
+        customer_id: Uuid,
+        start_date: Option<NaiveDate>,
+        end_date: NaiveDate,
+        style: StatementStyle,
+    ) -> Result<CustomerStatement> {
+        let start_date = start_date.unwrap_or_else(|| end_date.with_day(1).unwrap_or(end_date));
+        if start_date > end_date {
+            return Err(AppError::ValidationError(
+                "Statement start date must be on or before the end date".to_string()
+            ));
+        }
+
+        let customer = sqlx::query_as::<_, Contact>(
+            "SELECT * FROM contacts WHERE id = $1 AND contact_type = 'Customer' AND company_id IS NOT DISTINCT FROM $2"
+        )
+        .bind(customer_id)
+        .bind(self.company_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Customer not found".to_string()))?;
+
+        let events = Self::fetch_events(pool, customer_id, end_date).await?;
+
+        let closing_balance: Decimal = events.iter().map(|e| e.amount).sum();
+        let aging = Self::age_open_invoices(&customer, &events, end_date);
+
+        let (opening_balance, mut lines) = match style {
+            StatementStyle::BalanceForward => {
+                let opening: Decimal = events.iter()
+                    .filter(|e| e.date < start_date)
+                    .map(|e| e.amount)
+                    .sum();
+                let lines: Vec<StatementLine> = events.into_iter()
+                    .filter(|e| e.date >= start_date)
+                    .collect();
+                (opening, lines)
+            }
+            StatementStyle::OpenItem => {
+                // Open items carry their remaining balance rather than the original amount
+                let lines: Vec<StatementLine> = events.into_iter()
+                    .filter(|e| !e.open_balance.is_zero())
+                    .collect();
+                (Decimal::ZERO, lines)
+            }
+        };
+
+        let mut running_balance = opening_balance;
+        for line in lines.iter_mut() {
+            running_balance += match style {
+                StatementStyle::BalanceForward => line.amount,
+                StatementStyle::OpenItem => line.open_balance,
+            };
+            line.running_balance = running_balance;
+        }
+
+        let total_charges = lines.iter()
+            .map(|l| l.amount)
+            .filter(|a| *a > Decimal::ZERO)
+            .sum();
+        let total_credits = lines.iter()
+            .map(|l| l.amount)
+            .filter(|a| *a < Decimal::ZERO)
+            .sum::<Decimal>()
+            .abs();
+
+        Ok(CustomerStatement {
+            customer_id: customer.id,
+            customer_name: customer.name,
+            billing_address: customer.billing_address,
+            style,
+            start_date,
+            end_date,
+            opening_balance,
+            lines,
+            total_charges,
+            total_credits,
+            closing_balance,
+            aging,
+        })
+    }
+
+    /// Build statements for every customer with a balance as of the end date
+    pub async fn generate_batch(
+        &self,
+        pool: &PgPool,
+        start_date: Option<NaiveDate>,
+        end_date: NaiveDate,
+        style: StatementStyle,
+        company_id: Option<Uuid>,
+    ) -> Result<Vec<CustomerStatement>> {
+        let company_id = CompanyService::record_company(self.company_id, company_id)?;
+        let customer_ids = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            WITH activity AS (
+                SELECT customer_id, total_amount AS amount FROM invoices
+                WHERE invoice_date <= $1 AND status NOT IN ('draft', 'void')
+                UNION ALL
+                SELECT customer_id, -amount FROM payments WHERE payment_date <= $1
+                UNION ALL
+                SELECT customer_id, amount FROM payments WHERE void_date <= $1
+                UNION ALL
+                SELECT customer_id, -total_amount FROM credit_memos
+                WHERE credit_memo_date <= $1 AND status != 'void'
+                UNION ALL
+                SELECT customer_id, amount FROM customer_refunds WHERE refund_date <= $1
+                UNION ALL
+                SELECT customer_id, -amount FROM invoice_write_offs WHERE write_off_date <= $1
+                UNION ALL
+                SELECT w.customer_id, r.amount FROM write_off_recoveries r
+                INNER JOIN invoice_write_offs w ON w.id = r.write_off_id
+                WHERE r.recovery_date <= $1
+            )
+            SELECT c.id
+            FROM contacts c
+            INNER JOIN activity a ON a.customer_id = c.id
+            WHERE c.contact_type = 'Customer'
+                AND c.company_id IS NOT DISTINCT FROM $2
+            GROUP BY c.id, c.name
+            HAVING ABS(SUM(a.amount)) >= 0.01
+            ORDER BY c.name
+            "#
+        )
+        .bind(end_date)
+        .bind(company_id)
+        .fetch_all(pool)
+        .await?;
+
+        let mut statements = Vec::with_capacity(customer_ids.len());
+        for customer_id in customer_ids {
+            statements.push(
+                self.generate_statement(pool, customer_id, start_date, end_date, style).await?
+            );
+        }
+
+        Ok(statements)
+    }
+
+    /// Render one or more statements into a single PDF, one statement per page run
+    pub fn render_pdf(statements: &[CustomerStatement]) -> Vec<u8> {
+        let mut doc = PdfDocument::new();
+
+        for (index, statement) in statements.iter().enumerate() {
+            if index > 0 {
+                doc.new_page();
+            }
+            Self::render_statement(&mut doc, statement);
+        }
+
+        doc.finish()
+    }
+
+    fn render_statement(doc: &mut PdfDocument, statement: &CustomerStatement) {
+        let left = doc.left();
+        let right = doc.right();
+
+        doc.heading("Statement of Account");
+        doc.text(&statement.customer_name);
+        if let Some(address) = &statement.billing_address {
+            for line in address.lines() {
+                doc.text(line);
+            }
+        }
+        doc.space(8.0);
+        match statement.style {
+            StatementStyle::BalanceForward => doc.text(&format!(
+                "Period: {} to {}", statement.start_date, statement.end_date
+            )),
+            StatementStyle::OpenItem => doc.text(&format!(
+                "Open items as of {}", statement.end_date
+            )),
+        }
+        doc.space(10.0);
+
+        let amount_col = right - 150.0;
+        let open_col = right - 75.0;
+        doc.row(&[
+            (left, Align::Left, "Date"),
+            (left + 70.0, Align::Left, "Type"),
+            (left + 150.0, Align::Left, "Number"),
+            (left + 250.0, Align::Left, "Due"),
+            (amount_col, Align::Right, "Amount"),
+            (open_col, Align::Right, "Open"),
+            (right, Align::Right, "Balance"),
+        ], true);
+        doc.rule();
+
+        if statement.style == StatementStyle::BalanceForward {
+            let opening = format_amount(statement.opening_balance);
+            doc.row(&[
+                (left, Align::Left, &statement.start_date.to_string()),
+                (left + 70.0, Align::Left, "Balance forward"),
+                (right, Align::Right, &opening),
+            ], false);
+        }
+
+        for line in &statement.lines {
+            let date = line.date.to_string();
+            let document_type = Self::document_label(&line.document_type);
+            let number = line.document_number.clone().unwrap_or_default();
+            let due = line.due_date.map(|d| d.to_string()).unwrap_or_default();
+            let amount = format_amount(line.amount);
+            let open = format_amount(line.open_balance);
+            let balance = format_amount(line.running_balance);
+            doc.row(&[
+                (left, Align::Left, &date),
+                (left + 70.0, Align::Left, document_type),
+                (left + 150.0, Align::Left, &number),
+                (left + 250.0, Align::Left, &due),
+                (amount_col, Align::Right, &amount),
+                (open_col, Align::Right, &open),
+                (right, Align::Right, &balance),
+            ], false);
+        }
+
+        doc.rule();
+        let closing = format_amount(statement.closing_balance);
+        doc.row(&[
+            (left, Align::Left, "Amount due"),
+            (right, Align::Right, &closing),
+        ], true);
+        doc.space(16.0);
+
+        let aging = &statement.aging;
+        let columns = [
+            ("Current", aging.current),
+            ("1-30", aging.days_1_30),
+            ("31-60", aging.days_31_60),
+            ("61-90", aging.days_61_90),
+            ("Over 90", aging.days_91_plus),
+            ("Total", aging.total),
+        ];
+        let width = (right - left) / columns.len() as f32;
+        let headers: Vec<(f32, Align, &str)> = columns.iter().enumerate()
+            .map(|(i, (label, _))| (left + width * (i + 1) as f32, Align::Right, *label))
+            .collect();
+        doc.row(&headers, true);
+        let amounts: Vec<String> = columns.iter().map(|(_, amount)| format_amount(*amount)).collect();
+        let cells: Vec<(f32, Align, &str)> = amounts.iter().enumerate()
+            .map(|(i, amount)| (left + width * (i + 1) as f32, Align::Right, amount.as_str()))
+            .collect();
+        doc.row(&cells, false);
+    }
+
+    fn document_label(document_type: &str) -> &'static str {
+        match document_type {
+            "invoice" => "Invoice",
+            "payment" => "Payment",
+            "payment_void" => "Returned payment",
+            "credit_memo" => "Credit memo",
+            "refund" => "Refund",
+            "write_off" => "Bad debt write-off",
+            "write_off_recovery" => "Bad debt recovery",
+            _ => "",
+        }
+    }
+
+    /// All invoices, payments and their voids, credit memos, refunds, write-offs and recoveries for a customer up to the end date,
+    /// with the amount of each still open as of that date
+    async fn fetch_events(pool: &PgPool, customer_id: Uuid, end_date: NaiveDate) -> Result<Vec<StatementLine>> {
+        let events = sqlx::query_as::<_, StatementLine>(
+            r#"
+            WITH invoice_docs AS (
+                SELECT id, invoice_date, invoice_number, due_date, total_amount
+                FROM invoices
+                WHERE customer_id = $1 AND invoice_date <= $2 AND status NOT IN ('draft', 'void')
+            ),
+            payment_docs AS (
+                SELECT id, payment_date, payment_number, amount,
+                       CASE WHEN void_date <= $2 THEN void_date END AS void_date
+                FROM payments
+                WHERE customer_id = $1 AND payment_date <= $2
+            ),
+            credit_docs AS (
+                SELECT id, credit_memo_date, credit_memo_number, total_amount
+                FROM credit_memos
+                WHERE customer_id = $1 AND credit_memo_date <= $2 AND status != 'void'
+            ),
+            write_off_docs AS (
+                SELECT w.id, w.invoice_id, w.write_off_date, i.invoice_number, w.amount
+                FROM invoice_write_offs w
+                INNER JOIN invoice_docs i ON i.id = w.invoice_id
+                WHERE w.write_off_date <= $2
+            ),
+            recovery_docs AS (
+                SELECT r.id, r.invoice_id, r.write_off_id, r.recovery_date, i.invoice_number, r.amount
+                FROM write_off_recoveries r
+                INNER JOIN invoice_docs i ON i.id = r.invoice_id
+                WHERE r.recovery_date <= $2
+            ),
+            -- Applications count once both sides exist as of the statement date
+            applications AS (
+                SELECT pa.invoice_id, pa.payment_id AS source_id, pa.amount_applied
+                FROM payment_applications pa
+                INNER JOIN payment_docs p ON p.id = pa.payment_id
+                INNER JOIN invoice_docs i ON i.id = pa.invoice_id
+                UNION ALL
+                SELECT ca.invoice_id, ca.credit_memo_id, ca.amount_applied
+                FROM credit_memo_applications ca
+                INNER JOIN credit_docs cm ON cm.id = ca.credit_memo_id
+                INNER JOIN invoice_docs i ON i.id = ca.invoice_id
+                UNION ALL
+                -- Write-offs close the invoice; recoveries reopen it
+                SELECT w.invoice_id, w.id, w.amount
+                FROM write_off_docs w
+                UNION ALL
+                SELECT r.invoice_id, r.write_off_id, -r.amount
+                FROM recovery_docs r
+            ),
+            refunds AS (
+                SELECT id, refund_date, refund_number, amount, COALESCE(payment_id, credit_memo_id) AS source_id
+                FROM customer_refunds
+                WHERE customer_id = $1 AND refund_date <= $2
+            )
+            SELECT
+                i.invoice_date AS date,
+                'invoice' AS document_type,
+                i.id AS document_id,
+                i.invoice_number AS document_number,
+                i.due_date,
+                i.total_amount AS amount,
+                i.total_amount - COALESCE((SELECT SUM(a.amount_applied) FROM applications a WHERE a.invoice_id = i.id), 0) AS open_balance,
+                0::numeric AS running_balance,
+                1 AS sort_order
+            FROM invoice_docs i
+            UNION ALL
+            SELECT
+                p.payment_date,
+                'payment',
+                p.id,
+                p.payment_number,
+                NULL::date,
+                -p.amount,
+                CASE WHEN p.void_date IS NOT NULL THEN 0 ELSE
+                    -(p.amount
+                      - COALESCE((SELECT SUM(a.amount_applied) FROM applications a WHERE a.source_id = p.id), 0)
+                      - COALESCE((SELECT SUM(r.amount) FROM refunds r WHERE r.source_id = p.id), 0))
+                END,
+                0::numeric,
+                2
+            FROM payment_docs p
+            UNION ALL
+            -- A returned payment puts the amount back on the account
+            SELECT
+                p.void_date,
+                'payment_void',
+                p.id,
+                p.payment_number,
+                NULL::date,
+                p.amount,
+                0::numeric,
+                0::numeric,
+                1
+            FROM payment_docs p
+            WHERE p.void_date IS NOT NULL
+            UNION ALL
+            SELECT
+                cm.credit_memo_date,
+                'credit_memo',
+                cm.id,
+                cm.credit_memo_number,
+                NULL::date,
+                -cm.total_amount,
+                -(cm.total_amount
+                  - COALESCE((SELECT SUM(a.amount_applied) FROM applications a WHERE a.source_id = cm.id), 0)
+                  - COALESCE((SELECT SUM(r.amount) FROM refunds r WHERE r.source_id = cm.id), 0)),
+                0::numeric,
+                3
+            FROM credit_docs cm
+            UNION ALL
+            SELECT
+                r.refund_date,
+                'refund',
+                r.id,
+                r.refund_number,
+                NULL::date,
+                r.amount,
+                0::numeric,
+                0::numeric,
+                4
+            FROM refunds r
+            UNION ALL
+            SELECT
+                w.write_off_date,
+                'write_off',
+                w.id,
+                w.invoice_number,
+                NULL::date,
+                -w.amount,
+                0::numeric,
+                0::numeric,
+                5
+            FROM write_off_docs w
+            UNION ALL
+            -- Reinstated before any payment received the same day
+            SELECT
+                r.recovery_date,
+                'write_off_recovery',
+                r.id,
+                r.invoice_number,
+                NULL::date,
+                r.amount,
+                0::numeric,
+                0::numeric,
+                1
+            FROM recovery_docs r
+            ORDER BY date, sort_order, document_number
+            "#
+        )
+        .bind(customer_id)
+        .bind(end_date)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    /// Bucket open invoice balances by days past due, as in the AR aging report
+    fn age_open_invoices(customer: &Contact, events: &[StatementLine], as_of_date: NaiveDate) -> AgingBucket {
+        let mut aging = AgingBucket {
+            customer_id: customer.id,
+            customer_name: customer.name.clone(),
+            current: Decimal::ZERO,
+            days_1_30: Decimal::ZERO,
+            days_31_60: Decimal::ZERO,
+            days_61_90: Decimal::ZERO,
+            days_91_plus: Decimal::ZERO,
+            total: Decimal::ZERO,
+            written_off: -events.iter()
+                .filter(|e| e.document_type == "write_off" || e.document_type == "write_off_recovery")
+                .map(|e| e.amount)
+                .sum::<Decimal>(),
+            dimension_value_id: None,
+            dimension_value_name: None,
+        };
+
+        for event in events.iter().filter(|e| e.document_type == "invoice" && e.open_balance > Decimal::ZERO) {
+            let days_past_due = event.due_date
+                .map(|due| (as_of_date - due).num_days())
+                .unwrap_or(0);
+            let bucket = match days_past_due {
+                d if d < 0 => &mut aging.current,
+                0..=30 => &mut aging.days_1_30,
+                31..=60 => &mut aging.days_31_60,
+                61..=90 => &mut aging.days_61_90,
+                _ => &mut aging.days_91_plus,
+            };
+            *bucket += event.open_balance;
+            aging.total += event.open_balance;
+        }
+
+        aging
+    }
+}
@@ -0,0 +1,791 @@
+use chrono::NaiveDate;
+use rust_decimal::{Decimal, RoundingStrategy};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{
+    TaxCode, TaxRate, TaxCodeWithRates, CreateTaxCodeRequest, CreateTaxRateRequest,
+    SetupCanadianTaxCodesRequest, CalculatedTax, TaxReturn, TaxReturnStatus, TaxReturnLines,
+    TaxReturnWorksheet, TaxReturnPeriodRequest, FileTaxReturnRequest, CreateTransactionRequest,
+    CreateLineItemRequest, JournalType,
+};
+use crate::utils::{AppError, Result};
+use crate::services::{CacheService, CompanyRecord, CompanyService, TransactionService};
+
+/// GST/HST is remitted to the Canada Revenue Agency
+const DEFAULT_AGENCY: &str = "CRA";
+
+#[derive(Clone)]
+pub struct TaxService {
+    cache: CacheService,
+    company_id: Option<Uuid>,
+}
+
+impl TaxService {
+    pub fn new_with_cache(cache: CacheService) -> Self {
+        Self { cache, company_id: None }
+    }
+
+    /// Scope the service to a company's records
+    pub fn for_company(&self, company_id: Option<Uuid>) -> Self {
+        Self { company_id, ..self.clone() }
+    }
+
+    /// Create a tax code with its component tax rates
+    pub async fn create_tax_code(&self, pool: &PgPool, mut req: CreateTaxCodeRequest) -> Result<TaxCodeWithRates> {
+        req.validate()?;
+        req.company_id = CompanyService::record_company(self.company_id, req.company_id)?;
+
+        let mut tx = pool.begin().await?;
+        let tax_code = Self::insert_tax_code(&mut tx, &req).await?;
+        tx.commit().await?;
+
+        self.invalidate_tax_cache().await;
+
+        Ok(tax_code)
+    }
+
+    /// List the company's tax codes
+    pub async fn list_tax_codes(&self, pool: &PgPool, include_inactive: bool) -> Result<Vec<TaxCode>> {
+        let tax_codes = sqlx::query_as::<_, TaxCode>(
+            "SELECT * FROM tax_codes WHERE (is_active OR $1) AND company_id IS NOT DISTINCT FROM $2 ORDER BY code"
+        )
+        .bind(include_inactive)
+        .bind(self.company_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(tax_codes)
+    }
+
+    /// Get a tax code with its full rate history
+    pub async fn get_tax_code(&self, pool: &PgPool, tax_code_id: Uuid) -> Result<TaxCodeWithRates> {
+        let cache_key = format!("tax_code:{}", tax_code_id);
+        if let Ok(Some(cached)) = self.cache.get::<TaxCodeWithRates>(&cache_key).await
+            && cached.tax_code.company_id == self.company_id
+        {
+            return Ok(cached);
+        }
+
+        let tax_code = sqlx::query_as::<_, TaxCode>(
+            "SELECT * FROM tax_codes WHERE id = $1 AND company_id IS NOT DISTINCT FROM $2"
+        )
+        .bind(tax_code_id)
+        .bind(self.company_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Tax code with id {} not found", tax_code_id)))?;
+
+        let rates = sqlx::query_as::<_, TaxRate>(
+            "SELECT * FROM tax_rates WHERE tax_code_id = $1 ORDER BY sequence, tax_name, effective_from"
+        )
+        .bind(tax_code_id)
+        .fetch_all(pool)
+        .await?;
+
+        let result = TaxCodeWithRates { tax_code, rates };
+        let _ = self.cache.set_with_ttl(&cache_key, &result, 1800).await;
+
+        Ok(result)
+    }
+
+    /// Add a rate to a tax code. An open-ended rate for the same tax is
+    /// closed off the day before the new rate takes effect (rate changes).
+    pub async fn add_tax_rate(&self, pool: &PgPool, tax_code_id: Uuid, req: CreateTaxRateRequest) -> Result<TaxRate> {
+        req.validate()?;
+
+        let mut tx = pool.begin().await?;
+
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM tax_codes WHERE id = $1 AND company_id IS NOT DISTINCT FROM $2)"
+        )
+        .bind(tax_code_id)
+        .bind(self.company_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if !exists {
+            return Err(AppError::NotFound(format!("Tax code with id {} not found", tax_code_id)));
+        }
+        CompanyService::ensure_in_company(&mut tx, self.company_id, CompanyRecord::Account, &rate_account_ids(&req)).await?;
+
+        sqlx::query(
+            r#"
+            UPDATE tax_rates
+            SET effective_to = $3::date - 1, updated_at = NOW()
+            WHERE tax_code_id = $1 AND tax_name = $2 AND effective_to IS NULL AND effective_from < $3
+            "#
+        )
+        .bind(tax_code_id)
+        .bind(&req.tax_name)
+        .bind(req.effective_from)
+        .execute(&mut *tx)
+        .await?;
+
+        let rate = Self::insert_tax_rate(&mut tx, tax_code_id, &req).await?;
+        tx.commit().await?;
+
+        self.invalidate_tax_cache().await;
+
+        Ok(rate)
+    }
+
+    /// Create the standard Canadian tax codes (GST, HST by province, GST + PST/QST, zero-rated, exempt)
+    pub async fn setup_canadian_tax_codes(
+        &self,
+        pool: &PgPool,
+        mut req: SetupCanadianTaxCodesRequest,
+    ) -> Result<Vec<TaxCodeWithRates>> {
+        req.validate()?;
+        req.company_id = CompanyService::record_company(self.company_id, req.company_id)?;
+
+        let gst = |rate: &str| {
+            let mut gst = canadian_rate("GST", "CRA", rate, 1, true, req.gst_hst_account_id, req.effective_from);
+            gst.recoverable_account_id = req.itc_account_id;
+            gst
+        };
+        let hst = |rate: &str| {
+            let mut hst = canadian_rate("HST", "CRA", rate, 1, true, req.gst_hst_account_id, req.effective_from);
+            hst.recoverable_account_id = req.itc_account_id;
+            hst
+        };
+
+        let mut codes = vec![
+            ("GST", "GST", vec![gst("5")]),
+            ("HST-ON", "HST Ontario", vec![hst("13")]),
+            ("HST-NS", "HST Nova Scotia", vec![hst("14")]),
+            ("HST-NB", "HST New Brunswick", vec![hst("15")]),
+            ("HST-NL", "HST Newfoundland and Labrador", vec![hst("15")]),
+            ("HST-PE", "HST Prince Edward Island", vec![hst("15")]),
+            ("Z", "Zero-rated", vec![gst("0")]),
+            ("E", "Exempt", vec![]),
+        ];
+
+        if let Some(pst_account_id) = req.pst_account_id {
+            let pst = |name: &str, agency: &str, rate: &str| {
+                canadian_rate(name, agency, rate, 2, false, pst_account_id, req.effective_from)
+            };
+            codes.push(("GST+PST-BC", "GST + PST British Columbia", vec![gst("5"), pst("PST", "BC Ministry of Finance", "7")]));
+            codes.push(("GST+PST-SK", "GST + PST Saskatchewan", vec![gst("5"), pst("PST", "Saskatchewan Finance", "6")]));
+            codes.push(("GST+RST-MB", "GST + RST Manitoba", vec![gst("5"), pst("RST", "Manitoba Finance", "7")]));
+        }
+
+        if let Some(qst_account_id) = req.qst_account_id {
+            let qst = canadian_rate("QST", "Revenu Québec", "9.975", 2, true, qst_account_id, req.effective_from);
+            codes.push(("GST+QST", "GST + QST Quebec", vec![gst("5"), qst]));
+        }
+
+        let mut tx = pool.begin().await?;
+        let mut created = Vec::new();
+        for (code, name, rates) in codes {
+            let request = CreateTaxCodeRequest {
+                code: code.to_string(),
+                name: name.to_string(),
+                description: None,
+                company_id: req.company_id,
+                rates,
+            };
+            created.push(Self::insert_tax_code(&mut tx, &request).await?);
+        }
+        tx.commit().await?;
+
+        self.invalidate_tax_cache().await;
+
+        Ok(created)
+    }
+
+    /// Calculate the component taxes on `amount` for a tax code as of `date`.
+    ///
+    /// Each component is rounded to cents. Compound components are charged on
+    /// the amount plus the taxes calculated before them.
+    pub async fn calculate_tax(
+        conn: &mut PgConnection,
+        company_id: Option<Uuid>,
+        tax_code: &str,
+        amount: Decimal,
+        date: NaiveDate,
+    ) -> Result<Vec<CalculatedTax>> {
+        let code = sqlx::query_as::<_, TaxCode>(
+            r#"
+            SELECT * FROM tax_codes
+            WHERE code = $1 AND company_id IS NOT DISTINCT FROM $2
+            "#
+        )
+        .bind(tax_code)
+        .bind(company_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| AppError::ValidationError(format!("Unknown tax code '{}'", tax_code)))?;
+
+        if !code.is_active {
+            return Err(AppError::ValidationError(format!("Tax code '{}' is inactive", tax_code)));
+        }
+
+        let rates = sqlx::query_as::<_, TaxRate>(
+            r#"
+            SELECT * FROM tax_rates
+            WHERE tax_code_id = $1
+              AND effective_from <= $2
+              AND (effective_to IS NULL OR effective_to >= $2)
+            ORDER BY sequence, tax_name
+            "#
+        )
+        .bind(code.id)
+        .bind(date)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        // Tax is posted to these accounts, which must be the company's own
+        let account_ids: Vec<Uuid> = rates
+            .iter()
+            .flat_map(|rate| std::iter::once(rate.liability_account_id).chain(rate.recoverable_account_id))
+            .collect();
+        CompanyService::ensure_in_company(conn, company_id, CompanyRecord::Account, &account_ids).await?;
+
+        let mut taxes: Vec<CalculatedTax> = Vec::new();
+        for rate in rates {
+            let taxable_amount = if rate.is_compound {
+                amount + taxes.iter().map(|t| t.tax_amount).sum::<Decimal>()
+            } else {
+                amount
+            };
+            let tax_amount = (taxable_amount * rate.rate / Decimal::ONE_HUNDRED)
+                .round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero);
+
+            taxes.push(CalculatedTax {
+                tax_rate_id: rate.id,
+                tax_name: rate.tax_name,
+                rate: rate.rate,
+                taxable_amount,
+                tax_amount,
+                liability_account_id: rate.liability_account_id,
+                recoverable_account_id: rate.recoverable_account_id,
+                is_recoverable: rate.is_recoverable,
+            });
+        }
+
+        Ok(taxes)
+    }
+
+    /// Ensure no filed tax return covers `date`; filed periods are locked
+    pub async fn ensure_period_open(conn: &mut PgConnection, company_id: Option<Uuid>, date: NaiveDate) -> Result<()> {
+        let locked: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM tax_returns
+                WHERE status = 'filed'
+                  AND company_id IS NOT DISTINCT FROM $1
+                  AND $2 BETWEEN period_start AND period_end
+            )
+            "#
+        )
+        .bind(company_id)
+        .bind(date)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        if locked {
+            return Err(AppError::ValidationError(format!(
+                "The tax period containing {} has been filed and is locked", date
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Sales tax return worksheet for a period, without saving it
+    pub async fn generate_return_worksheet(&self, pool: &PgPool, mut req: TaxReturnPeriodRequest) -> Result<TaxReturnWorksheet> {
+        req.validate()?;
+        req.company_id = CompanyService::record_company(self.company_id, req.company_id)?;
+        Self::validate_period(&req)?;
+
+        let agency = req.agency.clone().unwrap_or_else(|| DEFAULT_AGENCY.to_string());
+        let mut conn = pool.acquire().await?;
+        let lines = Self::calculate_return_lines(&mut conn, &agency, &req).await?;
+
+        Ok(TaxReturnWorksheet {
+            agency,
+            period_start: req.period_start,
+            period_end: req.period_end,
+            lines,
+        })
+    }
+
+    /// Prepare a draft return for a period
+    pub async fn create_tax_return(&self, pool: &PgPool, mut req: TaxReturnPeriodRequest) -> Result<TaxReturn> {
+        req.validate()?;
+        req.company_id = CompanyService::record_company(self.company_id, req.company_id)?;
+        Self::validate_period(&req)?;
+
+        let agency = req.agency.clone().unwrap_or_else(|| DEFAULT_AGENCY.to_string());
+        let mut tx = pool.begin().await?;
+
+        let overlapping: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM tax_returns
+                WHERE agency = $1
+                  AND company_id IS NOT DISTINCT FROM $2
+                  AND period_start <= $4 AND period_end >= $3
+            )
+            "#
+        )
+        .bind(&agency)
+        .bind(req.company_id)
+        .bind(req.period_start)
+        .bind(req.period_end)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if overlapping {
+            return Err(AppError::Conflict("A tax return already covers part of this period".to_string()));
+        }
+
+        let lines = Self::calculate_return_lines(&mut tx, &agency, &req).await?;
+
+        let tax_return = sqlx::query_as::<_, TaxReturn>(
+            r#"
+            INSERT INTO tax_returns
+                (id, agency, period_start, period_end, status, line_101_sales, taxable_sales,
+                 line_103_tax_collected, line_105_total_tax, line_106_itcs, line_108_total_itcs,
+                 line_109_net_tax, company_id, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, NOW(), NOW())
+            RETURNING *
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(&agency)
+        .bind(req.period_start)
+        .bind(req.period_end)
+        .bind(TaxReturnStatus::Draft.to_string())
+        .bind(lines.line_101_sales)
+        .bind(lines.taxable_sales)
+        .bind(lines.line_103_tax_collected)
+        .bind(lines.line_105_total_tax)
+        .bind(lines.line_106_itcs)
+        .bind(lines.line_108_total_itcs)
+        .bind(lines.line_109_net_tax)
+        .bind(req.company_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(tax_return)
+    }
+
+    /// List tax returns, most recent period first
+    pub async fn list_tax_returns(&self, pool: &PgPool) -> Result<Vec<TaxReturn>> {
+        let tax_returns = sqlx::query_as::<_, TaxReturn>(
+            "SELECT * FROM tax_returns WHERE company_id IS NOT DISTINCT FROM $1 ORDER BY period_end DESC, agency"
+        )
+        .bind(self.company_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(tax_returns)
+    }
+
+    /// Get a tax return by ID
+    pub async fn get_tax_return(&self, pool: &PgPool, tax_return_id: Uuid) -> Result<TaxReturn> {
+        sqlx::query_as::<_, TaxReturn>("SELECT * FROM tax_returns WHERE id = $1 AND company_id IS NOT DISTINCT FROM $2")
+            .bind(tax_return_id)
+            .bind(self.company_id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Tax return with id {} not found", tax_return_id)))
+    }
+
+    /// File a return: recalculate its lines, post the settlement entry and lock the period.
+    ///
+    /// The entry clears the tax collected from the liability accounts and the ITCs
+    /// from the receivable accounts, with the net tax against the settlement account.
+    pub async fn file_tax_return(
+        &self,
+        pool: &PgPool,
+        tax_return_id: Uuid,
+        req: FileTaxReturnRequest,
+        filed_by: Option<Uuid>,
+    ) -> Result<TaxReturn> {
+        req.validate()?;
+
+        let mut tx = pool.begin().await?;
+
+        let tax_return = sqlx::query_as::<_, TaxReturn>(
+            "SELECT * FROM tax_returns WHERE id = $1 AND company_id IS NOT DISTINCT FROM $2 FOR UPDATE"
+        )
+        .bind(tax_return_id)
+        .bind(self.company_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Tax return with id {} not found", tax_return_id)))?;
+
+        if tax_return.status == TaxReturnStatus::Filed {
+            return Err(AppError::Conflict("Tax return has already been filed".to_string()));
+        }
+        CompanyService::ensure_in_company(&mut tx, tax_return.company_id, CompanyRecord::Account, &[req.settlement_account_id]).await?;
+
+        let period = TaxReturnPeriodRequest {
+            agency: Some(tax_return.agency.clone()),
+            period_start: tax_return.period_start,
+            period_end: tax_return.period_end,
+            company_id: tax_return.company_id,
+        };
+        let lines = Self::calculate_return_lines(&mut tx, &tax_return.agency, &period).await?;
+
+        // Tax collected and ITCs by the accounts they were posted to
+        let collected: Vec<(Uuid, Decimal)> = sqlx::query_as(
+            r#"
+            SELECT tr.liability_account_id, SUM(taxes.tax_amount)
+            FROM (
+                SELECT ilt.tax_rate_id, ilt.tax_amount
+                FROM invoice_line_taxes ilt
+                INNER JOIN invoices i ON i.id = ilt.invoice_id
+                WHERE i.company_id IS NOT DISTINCT FROM $2
+                  AND i.invoice_date BETWEEN $3 AND $4
+                  AND i.status NOT IN ('draft', 'void')
+                  AND i.transaction_id IS NOT NULL
+                UNION ALL
+                SELECT cmlt.tax_rate_id, -cmlt.tax_amount
+                FROM credit_memo_line_taxes cmlt
+                INNER JOIN credit_memos cm ON cm.id = cmlt.credit_memo_id
+                WHERE cm.company_id IS NOT DISTINCT FROM $2
+                  AND cm.credit_memo_date BETWEEN $3 AND $4
+                  AND cm.status <> 'void'
+                  AND cm.transaction_id IS NOT NULL
+            ) taxes
+            INNER JOIN tax_rates tr ON tr.id = taxes.tax_rate_id
+            WHERE tr.agency = $1
+            GROUP BY tr.liability_account_id
+            "#
+        )
+        .bind(&tax_return.agency)
+        .bind(tax_return.company_id)
+        .bind(tax_return.period_start)
+        .bind(tax_return.period_end)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let credits: Vec<(Uuid, Decimal)> = sqlx::query_as(
+            r#"
+            SELECT COALESCE(tr.recoverable_account_id, tr.liability_account_id), SUM(taxes.tax_amount)
+            FROM (
+                SELECT blt.tax_rate_id, blt.tax_amount
+                FROM bill_line_taxes blt
+                INNER JOIN bills b ON b.id = blt.bill_id
+                WHERE blt.is_recoverable
+                  AND b.company_id IS NOT DISTINCT FROM $2
+                  AND b.bill_date BETWEEN $3 AND $4
+                  AND b.status <> 'void'
+                  AND b.transaction_id IS NOT NULL
+                UNION ALL
+                SELECT vclt.tax_rate_id, -vclt.tax_amount
+                FROM vendor_credit_line_taxes vclt
+                INNER JOIN vendor_credits vc ON vc.id = vclt.vendor_credit_id
+                WHERE vclt.is_recoverable
+                  AND vc.company_id IS NOT DISTINCT FROM $2
+                  AND vc.credit_date BETWEEN $3 AND $4
+                  AND vc.status <> 'void'
+                  AND vc.transaction_id IS NOT NULL
+            ) taxes
+            INNER JOIN tax_rates tr ON tr.id = taxes.tax_rate_id
+            WHERE tr.agency = $1
+            GROUP BY COALESCE(tr.recoverable_account_id, tr.liability_account_id)
+            "#
+        )
+        .bind(&tax_return.agency)
+        .bind(tax_return.company_id)
+        .bind(tax_return.period_start)
+        .bind(tax_return.period_end)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut amounts: Vec<(Uuid, Decimal)> = collected;
+        amounts.extend(credits.into_iter().map(|(account_id, amount)| (account_id, -amount)));
+        amounts.push((req.settlement_account_id, -lines.line_109_net_tax));
+
+        let description = format!(
+            "{} sales tax return {} to {}",
+            tax_return.agency, tax_return.period_start, tax_return.period_end
+        );
+        let line_items = CreateLineItemRequest::from_signed_amounts(&amounts, &description);
+        let transaction_id = if line_items.is_empty() {
+            None
+        } else {
+            let entry = CreateTransactionRequest {
+                transaction_date: req.filed_date,
+                description: Some(description),
+                reference_number: None,
+                entry_number: None,
+                contact_id: None,
+                company_id: tax_return.company_id,
+                journal_type: Some(JournalType::General),
+                line_items,
+            };
+            Some(TransactionService::post_entry(&mut tx, &entry, filed_by).await?)
+        };
+
+        let filed = sqlx::query_as::<_, TaxReturn>(
+            r#"
+            UPDATE tax_returns
+            SET status = $1, line_101_sales = $2, taxable_sales = $3, line_103_tax_collected = $4,
+                line_105_total_tax = $5, line_106_itcs = $6, line_108_total_itcs = $7,
+                line_109_net_tax = $8, settlement_account_id = $9, transaction_id = $10,
+                filed_date = $11, filed_by = $12, updated_at = NOW()
+            WHERE id = $13
+            RETURNING *
+            "#
+        )
+        .bind(TaxReturnStatus::Filed.to_string())
+        .bind(lines.line_101_sales)
+        .bind(lines.taxable_sales)
+        .bind(lines.line_103_tax_collected)
+        .bind(lines.line_105_total_tax)
+        .bind(lines.line_106_itcs)
+        .bind(lines.line_108_total_itcs)
+        .bind(lines.line_109_net_tax)
+        .bind(req.settlement_account_id)
+        .bind(transaction_id)
+        .bind(req.filed_date)
+        .bind(filed_by)
+        .bind(tax_return_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        let _ = self.cache.invalidate_all_account_balances().await;
+
+        Ok(filed)
+    }
+
+    // Helper methods
+
+    fn validate_period(req: &TaxReturnPeriodRequest) -> Result<()> {
+        if req.period_end < req.period_start {
+            return Err(AppError::ValidationError("period_end must not be before period_start".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Calculate the GST34-style lines from invoices, credit memos, bills and vendor credits dated in the period.
+    /// Only documents posted to the ledger count, so the settlement entry matches the tax accounts.
+    async fn calculate_return_lines(
+        conn: &mut PgConnection,
+        agency: &str,
+        req: &TaxReturnPeriodRequest,
+    ) -> Result<TaxReturnLines> {
+        let (invoiced_sales, invoiced_taxable, invoiced_tax): (Decimal, Decimal, Decimal) = sqlx::query_as(
+            r#"
+            SELECT
+                COALESCE(SUM(ili.amount), 0),
+                COALESCE(SUM(ili.amount) FILTER (WHERE agency_tax.line_id IS NOT NULL), 0),
+                COALESCE(SUM(agency_tax.tax_amount), 0)
+            FROM invoice_line_items ili
+            INNER JOIN invoices i ON i.id = ili.invoice_id
+            LEFT JOIN (
+                SELECT ilt.invoice_line_item_id as line_id, SUM(ilt.tax_amount) as tax_amount
+                FROM invoice_line_taxes ilt
+                INNER JOIN tax_rates tr ON tr.id = ilt.tax_rate_id
+                WHERE tr.agency = $1
+                GROUP BY ilt.invoice_line_item_id
+            ) agency_tax ON agency_tax.line_id = ili.id
+            WHERE i.company_id IS NOT DISTINCT FROM $2
+              AND i.invoice_date BETWEEN $3 AND $4
+              AND i.status NOT IN ('draft', 'void')
+              AND i.transaction_id IS NOT NULL
+            "#
+        )
+        .bind(agency)
+        .bind(req.company_id)
+        .bind(req.period_start)
+        .bind(req.period_end)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        // Credit memos reduce sales and tax collected
+        let (credited_sales, credited_taxable, credited_tax): (Decimal, Decimal, Decimal) = sqlx::query_as(
+            r#"
+            SELECT
+                COALESCE(SUM(cmli.amount), 0),
+                COALESCE(SUM(cmli.amount) FILTER (WHERE agency_tax.line_id IS NOT NULL), 0),
+                COALESCE(SUM(agency_tax.tax_amount), 0)
+            FROM credit_memo_line_items cmli
+            INNER JOIN credit_memos cm ON cm.id = cmli.credit_memo_id
+            LEFT JOIN (
+                SELECT cmlt.credit_memo_line_item_id as line_id, SUM(cmlt.tax_amount) as tax_amount
+                FROM credit_memo_line_taxes cmlt
+                INNER JOIN tax_rates tr ON tr.id = cmlt.tax_rate_id
+                WHERE tr.agency = $1
+                GROUP BY cmlt.credit_memo_line_item_id
+            ) agency_tax ON agency_tax.line_id = cmli.id
+            WHERE cm.company_id IS NOT DISTINCT FROM $2
+              AND cm.credit_memo_date BETWEEN $3 AND $4
+              AND cm.status <> 'void'
+              AND cm.transaction_id IS NOT NULL
+            "#
+        )
+        .bind(agency)
+        .bind(req.company_id)
+        .bind(req.period_start)
+        .bind(req.period_end)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        // Vendor credits reduce the input tax credits claimed
+        let line_106_itcs: Decimal = sqlx::query_scalar(
+            r#"
+            SELECT COALESCE(SUM(taxes.tax_amount), 0)
+            FROM (
+                SELECT blt.tax_rate_id, blt.tax_amount
+                FROM bill_line_taxes blt
+                INNER JOIN bills b ON b.id = blt.bill_id
+                WHERE blt.is_recoverable
+                  AND b.company_id IS NOT DISTINCT FROM $2
+                  AND b.bill_date BETWEEN $3 AND $4
+                  AND b.status <> 'void'
+                  AND b.transaction_id IS NOT NULL
+                UNION ALL
+                SELECT vclt.tax_rate_id, -vclt.tax_amount
+                FROM vendor_credit_line_taxes vclt
+                INNER JOIN vendor_credits vc ON vc.id = vclt.vendor_credit_id
+                WHERE vclt.is_recoverable
+                  AND vc.company_id IS NOT DISTINCT FROM $2
+                  AND vc.credit_date BETWEEN $3 AND $4
+                  AND vc.status <> 'void'
+                  AND vc.transaction_id IS NOT NULL
+            ) taxes
+            INNER JOIN tax_rates tr ON tr.id = taxes.tax_rate_id
+            WHERE tr.agency = $1
+            "#
+        )
+        .bind(agency)
+        .bind(req.company_id)
+        .bind(req.period_start)
+        .bind(req.period_end)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        let line_103_tax_collected = invoiced_tax - credited_tax;
+        let line_105_total_tax = line_103_tax_collected;
+        let line_108_total_itcs = line_106_itcs;
+
+        Ok(TaxReturnLines {
+            line_101_sales: invoiced_sales - credited_sales,
+            taxable_sales: invoiced_taxable - credited_taxable,
+            line_103_tax_collected,
+            line_105_total_tax,
+            line_106_itcs,
+            line_108_total_itcs,
+            line_109_net_tax: line_105_total_tax - line_108_total_itcs,
+        })
+    }
+
+    async fn insert_tax_code(conn: &mut PgConnection, req: &CreateTaxCodeRequest) -> Result<TaxCodeWithRates> {
+        let duplicate: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM tax_codes WHERE code = $1 AND company_id IS NOT DISTINCT FROM $2)"
+        )
+        .bind(&req.code)
+        .bind(req.company_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        if duplicate {
+            return Err(AppError::Conflict(format!("Tax code '{}' already exists", req.code)));
+        }
+
+        let account_ids: Vec<Uuid> = req.rates.iter().flat_map(rate_account_ids).collect();
+        CompanyService::ensure_in_company(conn, req.company_id, CompanyRecord::Account, &account_ids).await?;
+
+        let tax_code = sqlx::query_as::<_, TaxCode>(
+            r#"
+            INSERT INTO tax_codes (id, code, name, description, is_active, company_id, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, true, $5, NOW(), NOW())
+            RETURNING *
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(&req.code)
+        .bind(&req.name)
+        .bind(&req.description)
+        .bind(req.company_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        let mut rates = Vec::new();
+        for rate_req in &req.rates {
+            rates.push(Self::insert_tax_rate(conn, tax_code.id, rate_req).await?);
+        }
+
+        Ok(TaxCodeWithRates { tax_code, rates })
+    }
+
+    async fn insert_tax_rate(conn: &mut PgConnection, tax_code_id: Uuid, req: &CreateTaxRateRequest) -> Result<TaxRate> {
+        if req.rate < Decimal::ZERO || req.rate > Decimal::ONE_HUNDRED {
+            return Err(AppError::ValidationError("Tax rate must be between 0 and 100 percent".to_string()));
+        }
+
+        if req.effective_to.is_some_and(|to| to < req.effective_from) {
+            return Err(AppError::ValidationError("effective_to must not be before effective_from".to_string()));
+        }
+
+        let rate = sqlx::query_as::<_, TaxRate>(
+            r#"
+            INSERT INTO tax_rates
+                (id, tax_code_id, tax_name, agency, rate, sequence, is_compound, is_recoverable,
+                 liability_account_id, recoverable_account_id, effective_from, effective_to,
+                 created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, NOW(), NOW())
+            RETURNING *
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(tax_code_id)
+        .bind(&req.tax_name)
+        .bind(&req.agency)
+        .bind(req.rate)
+        .bind(req.sequence.unwrap_or(1))
+        .bind(req.is_compound.unwrap_or(false))
+        .bind(req.is_recoverable.unwrap_or(false))
+        .bind(req.liability_account_id)
+        .bind(req.recoverable_account_id)
+        .bind(req.effective_from)
+        .bind(req.effective_to)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(rate)
+    }
+
+    async fn invalidate_tax_cache(&self) {
+        let _ = self.cache.delete_pattern("tax_code:*").await;
+    }
+}
+
+/// Accounts a tax rate posts to
+fn rate_account_ids(req: &CreateTaxRateRequest) -> Vec<Uuid> {
+    std::iter::once(req.liability_account_id).chain(req.recoverable_account_id).collect()
+}
+
+fn canadian_rate(
+    tax_name: &str,
+    agency: &str,
+    rate: &str,
+    sequence: i32,
+    is_recoverable: bool,
+    liability_account_id: Uuid,
+    effective_from: NaiveDate,
+) -> CreateTaxRateRequest {
+    CreateTaxRateRequest {
+        tax_name: tax_name.to_string(),
+        agency: Some(agency.to_string()),
+        rate: rate.parse().unwrap_or_default(),
+        sequence: Some(sequence),
+        is_compound: Some(false),
+        is_recoverable: Some(is_recoverable),
+        liability_account_id,
+        recoverable_account_id: None,
+        effective_from,
+        effective_to: None,
+    }
+}
@@ -0,0 +1,72 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use base32::Alphabet;
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use sha1::Sha1;
+
+use crate::utils::{AppError, Result};
+
+/// Seconds each code is valid for
+const STEP_SECONDS: i64 = 30;
+/// Digits per code
+const DIGITS: u32 = 6;
+/// Codes from this many steps either side of now are accepted, to allow for clock drift
+const ALLOWED_DRIFT: i64 = 1;
+
+const SECRET_ALPHABET: Alphabet = Alphabet::Rfc4648 { padding: false };
+
+/// A new random 160-bit secret, base32 encoded as authenticator apps expect
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 20];
+    OsRng.fill_bytes(&mut bytes);
+    base32::encode(SECRET_ALPHABET, &bytes)
+}
+
+/// `otpauth://` URI for authenticator apps, usually shown as a QR code
+pub fn provisioning_uri(secret: &str, issuer: &str, account: &str) -> String {
+    let issuer = utf8_percent_encode(issuer, NON_ALPHANUMERIC).to_string();
+    let account = utf8_percent_encode(account, NON_ALPHANUMERIC);
+    format!(
+        "otpauth://totp/{issuer}:{account}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={DIGITS}&period={STEP_SECONDS}"
+    )
+}
+
+/// Time step a moment falls in
+pub fn time_step(time: DateTime<Utc>) -> i64 {
+    time.timestamp().div_euclid(STEP_SECONDS)
+}
+
+/// The code an authenticator app shows at a given time
+pub fn code_at(secret: &str, time: DateTime<Utc>) -> Result<String> {
+    let key = base32::decode(SECRET_ALPHABET, &secret.to_uppercase())
+        .ok_or_else(|| AppError::InternalError("Invalid TOTP secret".to_string()))?;
+    let mut mac = Hmac::<Sha1>::new_from_slice(&key)
+        .map_err(|e| AppError::InternalError(format!("Invalid TOTP secret: {}", e)))?;
+    mac.update(&(time_step(time) as u64).to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    // Dynamic truncation (RFC 4226 section 5.3)
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([digest[offset], digest[offset + 1], digest[offset + 2], digest[offset + 3]])
+        & 0x7fff_ffff;
+
+    Ok(format!("{:0width$}", binary % 10u32.pow(DIGITS), width = DIGITS as usize))
+}
+
+/// The time step a code belongs to, if it is valid around `now`
+pub fn matching_step(secret: &str, code: &str, now: DateTime<Utc>) -> Result<Option<i64>> {
+    for drift in -ALLOWED_DRIFT..=ALLOWED_DRIFT {
+        let time = now + Duration::seconds(drift * STEP_SECONDS);
+        if code_at(secret, time)? == code {
+            return Ok(Some(time_step(time)));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Whether a code looks like a TOTP code rather than a recovery code
+pub fn is_totp_code(code: &str) -> bool {
+    code.len() == DIGITS as usize && code.chars().all(|c| c.is_ascii_digit())
+}
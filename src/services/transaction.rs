@@ -1,29 +1,37 @@
-use sqlx::PgPool;
+use chrono::NaiveDate;
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 use validator::Validate;
 use rust_decimal::Decimal;
 
 use crate::models::{
     Transaction, TransactionLineItem, TransactionStatus, TransactionWithLineItems,
-    CreateTransactionRequest,
+    CreateTransactionRequest, CreateLineItemRequest, SequenceDocumentType, DimensionLine,
 };
 use crate::utils::{AppError, Result};
-use crate::services::CacheService;
+use crate::services::{CacheService, CompanyRecord, CompanyService, DimensionService, NumberingService};
 
 #[derive(Clone)]
 pub struct TransactionService {
     cache: CacheService,
+    company_id: Option<Uuid>,
 }
 
 impl TransactionService {
     pub fn new() -> Self {
         Self {
             cache: CacheService::default(),
+            company_id: None,
         }
     }
 
     pub fn new_with_cache(cache: CacheService) -> Self {
-        Self { cache }
+        Self { cache, company_id: None }
+    }
+
+    /// Scope the service to a company's records
+    pub fn for_company(&self, company_id: Option<Uuid>) -> Self {
+        Self { company_id, ..self.clone() }
     }
 
     /// Create a new transaction with line items
@@ -35,6 +43,7 @@ impl TransactionService {
     ) -> Result<TransactionWithLineItems> {
         // Validate request (includes double-entry balance validation)
         req.validate()?;
+        let company_id = CompanyService::record_company(self.company_id, req.company_id)?;
 
         // Validate all account IDs exist
         for line_item in &req.line_items {
@@ -44,15 +53,23 @@ impl TransactionService {
         // Start a transaction
         let mut tx = pool.begin().await?;
 
+        if let Some(contact_id) = req.contact_id {
+            CompanyService::ensure_in_company(&mut tx, company_id, CompanyRecord::Contact, &[contact_id]).await?;
+        }
+
+        let entry_number = NumberingService::assign_number(
+            &mut tx, company_id, SequenceDocumentType::JournalEntry, req.transaction_date, req.entry_number.clone(),
+        ).await?;
+
         // Create transaction header
         let transaction_id = Uuid::new_v4();
         let transaction = sqlx::query_as::<_, Transaction>(
             r#"
             INSERT INTO transactions
-                (id, transaction_date, description, reference_number, contact_id, company_id,
+                (id, transaction_date, description, reference_number, entry_number, contact_id, company_id,
                  journal_type, status, created_by, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, NOW(), NOW())
-            RETURNING id, transaction_date, description, reference_number, contact_id, company_id,
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, NOW(), NOW())
+            RETURNING id, transaction_date, description, reference_number, entry_number, contact_id, company_id,
                       journal_type, status, created_by, created_at, updated_at
             "#,
         )
@@ -60,13 +77,20 @@ impl TransactionService {
         .bind(req.transaction_date)
         .bind(&req.description)
         .bind(&req.reference_number)
+        .bind(&entry_number)
         .bind(req.contact_id)
-        .bind(req.company_id)
+        .bind(company_id)
         .bind(req.journal_type.as_ref().map(|jt| jt.to_string()))
         .bind(TransactionStatus::Draft.to_string())
         .bind(created_by)
         .fetch_one(&mut *tx)
-        .await?;
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => AppError::Conflict(
+                format!("Journal entry number {} already exists", entry_number)
+            ),
+            e => AppError::from(e),
+        })?;
 
         // Create line items
         let mut line_items = Vec::new();
@@ -91,6 +115,17 @@ impl TransactionService {
             .fetch_one(&mut *tx)
             .await?;
 
+            // Manual entries must carry the dimensions their accounts require
+            DimensionService::tag_line(
+                &mut tx,
+                company_id,
+                DimensionLine::Transaction,
+                item.id,
+                Some(item.account_id),
+                &line_item.dimension_value_ids,
+            )
+            .await?;
+
             line_items.push(item);
         }
 
@@ -103,12 +138,121 @@ impl TransactionService {
         })
     }
 
+    /// Insert an already-posted journal entry on an open database connection.
+    ///
+    /// Used by sub-ledger services (inventory, invoicing, ...) so that their
+    /// ledger postings commit or roll back together with their own writes.
+    pub async fn post_entry(
+        conn: &mut PgConnection,
+        req: &CreateTransactionRequest,
+        created_by: Option<Uuid>,
+    ) -> Result<Uuid> {
+        // Same double-entry validation as manual journal entries
+        req.validate()?;
+
+        let transaction_id = Uuid::new_v4();
+        sqlx::query(
+            r#"
+            INSERT INTO transactions
+                (id, transaction_date, description, reference_number, entry_number, contact_id, company_id,
+                 journal_type, status, created_by, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, NOW(), NOW())
+            "#,
+        )
+        .bind(transaction_id)
+        .bind(req.transaction_date)
+        .bind(&req.description)
+        .bind(&req.reference_number)
+        .bind(&req.entry_number)
+        .bind(req.contact_id)
+        .bind(req.company_id)
+        .bind(req.journal_type.as_ref().map(|jt| jt.to_string()))
+        .bind(TransactionStatus::Posted.to_string())
+        .bind(created_by)
+        .execute(&mut *conn)
+        .await?;
+
+        for line_item in &req.line_items {
+            let line_item_id = Uuid::new_v4();
+            sqlx::query(
+                r#"
+                INSERT INTO transaction_line_items
+                    (id, transaction_id, account_id, description, debit_amount, credit_amount, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, NOW(), NOW())
+                "#,
+            )
+            .bind(line_item_id)
+            .bind(transaction_id)
+            .bind(line_item.account_id)
+            .bind(&line_item.description)
+            .bind(line_item.debit_amount.unwrap_or(Decimal::ZERO))
+            .bind(line_item.credit_amount.unwrap_or(Decimal::ZERO))
+            .execute(&mut *conn)
+            .await?;
+
+            // Dimension requirements are checked on the source document's lines
+            if !line_item.dimension_value_ids.is_empty() {
+                DimensionService::tag_line(conn, req.company_id, DimensionLine::Transaction, line_item_id, None, &line_item.dimension_value_ids).await?;
+            }
+        }
+
+        Ok(transaction_id)
+    }
+
+    /// Post an entry that reverses a posted journal entry, dated `reversal_date`
+    pub async fn reverse_entry(
+        conn: &mut PgConnection,
+        transaction_id: Uuid,
+        reversal_date: NaiveDate,
+        created_by: Option<Uuid>,
+    ) -> Result<Uuid> {
+        let original = sqlx::query_as::<_, Transaction>("SELECT * FROM transactions WHERE id = $1")
+            .bind(transaction_id)
+            .fetch_optional(&mut *conn)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Transaction {} not found", transaction_id)))?;
+
+        let line_items = sqlx::query_as::<_, TransactionLineItem>(
+            "SELECT * FROM transaction_line_items WHERE transaction_id = $1 ORDER BY created_at"
+        )
+        .bind(transaction_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        // The reversal carries the same dimensions so it nets out in dimension reports
+        let line_ids: Vec<Uuid> = line_items.iter().map(|line| line.id).collect();
+        let mut dimensions = DimensionService::line_values(conn, DimensionLine::Transaction, &line_ids).await?;
+
+        let description = format!("Reversal: {}", original.description.as_deref().unwrap_or(""));
+        let reversal = CreateTransactionRequest {
+            transaction_date: reversal_date,
+            description: Some(description),
+            reference_number: original.reference_number.clone(),
+            entry_number: None,
+            contact_id: original.contact_id,
+            company_id: original.company_id,
+            journal_type: original.journal_type.clone(),
+            line_items: line_items
+                .into_iter()
+                .map(|line| CreateLineItemRequest {
+                    account_id: line.account_id,
+                    description: line.description,
+                    debit_amount: Some(line.credit_amount).filter(|a| !a.is_zero()),
+                    credit_amount: Some(line.debit_amount).filter(|a| !a.is_zero()),
+                    dimension_value_ids: dimensions.remove(&line.id).unwrap_or_default(),
+                })
+                .collect(),
+        };
+
+        Self::post_entry(conn, &reversal, created_by).await
+    }
+
     /// Get transaction by ID with line items
     pub async fn get_transaction_by_id(&self, pool: &PgPool, id: Uuid) -> Result<TransactionWithLineItems> {
         // Get transaction header
         let transaction = sqlx::query_as::<_, Transaction>(
             r#"
-            SELECT id, transaction_date, description, reference_number, contact_id, company_id,
+            SELECT id, transaction_date, description, reference_number, entry_number, contact_id, company_id,
                    journal_type, status, created_by, created_at, updated_at
             FROM transactions
             WHERE id = $1
@@ -119,6 +263,10 @@ impl TransactionService {
         .await?
         .ok_or_else(|| AppError::NotFound(format!("Transaction with id {} not found", id)))?;
 
+        if transaction.company_id != self.company_id {
+            return Err(AppError::NotFound(format!("Transaction with id {} not found", id)));
+        }
+
         // Get line items
         let line_items = sqlx::query_as::<_, TransactionLineItem>(
             r#"
@@ -143,56 +291,41 @@ impl TransactionService {
         &self,
         pool: &PgPool,
         status: Option<TransactionStatus>,
-        company_id: Option<Uuid>,
         limit: Option<i64>,
     ) -> Result<Vec<Transaction>> {
         let mut query = String::from(
             r#"
             SELECT 
-                t.id, t.transaction_date, t.description, t.reference_number, t.contact_id, t.company_id,
+                t.id, t.transaction_date, t.description, t.reference_number, t.entry_number, t.contact_id, t.company_id,
                 t.journal_type, t.status, t.created_by, t.created_at, t.updated_at
             FROM transactions t
-            WHERE 1=1
+            WHERE t.company_id IS NOT DISTINCT FROM $1
             "#
         );
 
         // Add filters
         if status.is_some() {
-            query.push_str(" AND t.status = $1");
-        }
-
-        if company_id.is_some() {
-            if status.is_some() {
-                query.push_str(" AND t.company_id = $2");
-            } else {
-                query.push_str(" AND t.company_id = $1");
-            }
+            query.push_str(" AND t.status = $2");
         }
 
         query.push_str(" ORDER BY t.transaction_date DESC, t.created_at DESC");
 
         // Add limit
         if limit.is_some() {
-            if status.is_some() && company_id.is_some() {
+            if status.is_some() {
                 query.push_str(" LIMIT $3");
-            } else if status.is_some() || company_id.is_some() {
-                query.push_str(" LIMIT $2");
             } else {
-                query.push_str(" LIMIT $1");
+                query.push_str(" LIMIT $2");
             }
         }
 
         // Build and execute query
-        let mut sql_query = sqlx::query_as::<_, Transaction>(&query);
+        let mut sql_query = sqlx::query_as::<_, Transaction>(&query).bind(self.company_id);
 
         if let Some(s) = status {
             sql_query = sql_query.bind(s.to_string());
         }
 
-        if let Some(cid) = company_id {
-            sql_query = sql_query.bind(cid);
-        }
-
         if let Some(_lim) = limit {
             sql_query = sql_query.bind(_lim);
         }
@@ -243,7 +376,7 @@ impl TransactionService {
             UPDATE transactions
             SET status = $1, updated_at = NOW()
             WHERE id = $2
-            RETURNING id, transaction_date, description, reference_number, contact_id, company_id,
+            RETURNING id, transaction_date, description, reference_number, entry_number, contact_id, company_id,
                       journal_type, status, created_by, created_at, updated_at
             "#,
         )
@@ -305,12 +438,13 @@ impl TransactionService {
         Ok(())
     }
 
-    /// Validate that an account exists
+    /// Validate that an account exists in the company
     async fn validate_account_exists(&self, pool: &PgPool, account_id: Uuid) -> Result<()> {
         let exists = sqlx::query_scalar::<_, bool>(
-            "SELECT EXISTS(SELECT 1 FROM chart_of_accounts WHERE id = $1)"
+            "SELECT EXISTS(SELECT 1 FROM chart_of_accounts WHERE id = $1 AND company_id IS NOT DISTINCT FROM $2)"
         )
         .bind(account_id)
+        .bind(self.company_id)
         .fetch_one(pool)
         .await?;
 
@@ -323,13 +457,13 @@ impl TransactionService {
 
     /// Get account balance (sum of all posted transactions)
     pub async fn get_account_balance(&self, pool: &PgPool, account_id: Uuid) -> Result<Decimal> {
-        // Try cache first
+        // Balances are cached per account, so check the account's company first
+        self.validate_account_exists(pool, account_id).await?;
+
         if let Some(cached_balance) = self.cache.get_account_balance(account_id).await? {
             return Ok(cached_balance);
         }
 
-        self.validate_account_exists(pool, account_id).await?;
-
         let balance = sqlx::query_scalar::<_, Option<Decimal>>(
             r#"
             SELECT COALESCE(SUM(debit_amount - credit_amount), 0)
@@ -0,0 +1,477 @@
+use chrono::Utc;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use validator::Validate;
+use rust_decimal::Decimal;
+
+use crate::models::{
+    VendorCredit, VendorCreditLineItem, VendorCreditStatus, VendorCreditApplication,
+    VendorCreditApplicationRequest, VendorCreditWithLineItems, CreateVendorCreditRequest,
+    TaxSummary, CreateTransactionRequest, CreateLineItemRequest, JournalType,
+};
+use crate::utils::{AppError, Result};
+use crate::services::{BillService, CacheService, CompanyRecord, CompanyService, TaxService, TransactionService};
+
+#[derive(Clone)]
+pub struct VendorCreditService {
+    cache: CacheService,
+    company_id: Option<Uuid>,
+}
+
+impl VendorCreditService {
+    pub fn new_with_cache(cache: CacheService) -> Self {
+        Self { cache, company_id: None }
+    }
+
+    /// Scope the service to a company's records
+    pub fn for_company(&self, company_id: Option<Uuid>) -> Self {
+        Self { company_id, ..self.clone() }
+    }
+
+    /// Create a vendor credit, post the expense and tax reversal and apply it to any bills given
+    pub async fn create_vendor_credit(&self, pool: &PgPool, req: CreateVendorCreditRequest) -> Result<VendorCreditWithLineItems> {
+        // Validate request
+        req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+        let company_id = CompanyService::record_company(self.company_id, req.company_id)?;
+
+        let mut tx = pool.begin().await?;
+
+        let mut account_ids: Vec<Uuid> = req.line_items.iter().map(|l| l.expense_account_id).collect();
+        account_ids.extend(req.ap_account_id);
+        CompanyService::ensure_in_company(&mut tx, company_id, CompanyRecord::Contact, &[req.vendor_id]).await?;
+        CompanyService::ensure_in_company(&mut tx, company_id, CompanyRecord::Account, &account_ids).await?;
+
+        // Vendor credits reduce the input tax credits for their date
+        TaxService::ensure_period_open(&mut tx, company_id, req.credit_date).await?;
+
+        // Calculate line tax
+        let mut subtotal = Decimal::ZERO;
+        let mut line_taxes = Vec::with_capacity(req.line_items.len());
+        for line_item_req in &req.line_items {
+            if line_item_req.amount <= Decimal::ZERO {
+                return Err(AppError::ValidationError(
+                    format!("Line {} must have a positive amount", line_item_req.line_number)
+                ));
+            }
+            subtotal += line_item_req.amount;
+
+            let taxes = match &line_item_req.tax_code {
+                Some(tax_code) => {
+                    TaxService::calculate_tax(&mut tx, company_id, tax_code, line_item_req.amount, req.credit_date).await?
+                }
+                None => Vec::new(),
+            };
+            line_taxes.push(taxes);
+        }
+        let tax_amount: Decimal = line_taxes.iter().flatten().map(|t| t.tax_amount).sum();
+        let total_amount = subtotal + tax_amount;
+
+        // Create vendor credit header
+        let mut vendor_credit = sqlx::query_as::<_, VendorCredit>(
+            r#"
+            INSERT INTO vendor_credits
+                (id, vendor_credit_number, vendor_id, credit_date, bill_id, subtotal, tax_amount,
+                 total_amount, balance, status, memo, ap_account_id, company_id, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, NOW(), NOW())
+            RETURNING *
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(&req.vendor_credit_number)
+        .bind(req.vendor_id)
+        .bind(req.credit_date)
+        .bind(req.bill_id)
+        .bind(subtotal)
+        .bind(tax_amount)
+        .bind(total_amount)
+        .bind(total_amount) // Initially, all of the credit is available
+        .bind(VendorCreditStatus::Open.to_string())
+        .bind(&req.memo)
+        .bind(req.ap_account_id)
+        .bind(company_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => AppError::Conflict(
+                format!("Vendor credit number {} already exists", req.vendor_credit_number)
+            ),
+            e => AppError::from(e),
+        })?;
+
+        // Create line items
+        let mut line_items = Vec::new();
+        for (line_item_req, taxes) in req.line_items.iter().zip(line_taxes) {
+            let line_tax: Decimal = taxes.iter().map(|t| t.tax_amount).sum();
+
+            let line_item = sqlx::query_as::<_, VendorCreditLineItem>(
+                r#"
+                INSERT INTO vendor_credit_line_items
+                    (id, vendor_credit_id, line_number, description, amount, expense_account_id,
+                     tax_code, tax_amount, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW(), NOW())
+                RETURNING *
+                "#,
+            )
+            .bind(Uuid::new_v4())
+            .bind(vendor_credit.id)
+            .bind(line_item_req.line_number)
+            .bind(&line_item_req.description)
+            .bind(line_item_req.amount)
+            .bind(line_item_req.expense_account_id)
+            .bind(&line_item_req.tax_code)
+            .bind(line_tax)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            // Record each component tax reversed on the line
+            for tax in &taxes {
+                sqlx::query(
+                    r#"
+                    INSERT INTO vendor_credit_line_taxes
+                        (id, vendor_credit_id, vendor_credit_line_item_id, tax_rate_id, tax_name, rate,
+                         taxable_amount, tax_amount, is_recoverable, created_at)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, NOW())
+                    "#,
+                )
+                .bind(Uuid::new_v4())
+                .bind(vendor_credit.id)
+                .bind(line_item.id)
+                .bind(tax.tax_rate_id)
+                .bind(&tax.tax_name)
+                .bind(tax.rate)
+                .bind(tax.taxable_amount)
+                .bind(tax.tax_amount)
+                .bind(tax.is_recoverable)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            line_items.push(line_item);
+        }
+
+        // Post the reversal: Dr accounts payable, Cr expenses and input tax credits
+        vendor_credit.transaction_id = Self::post_vendor_credit(&mut tx, &vendor_credit).await?;
+
+        // Apply to bills
+        let applications = if req.applications.is_empty() {
+            Vec::new()
+        } else {
+            let (updated, applications) = Self::apply_to_bills(&mut tx, &vendor_credit, &req.applications).await?;
+            vendor_credit = updated;
+            applications
+        };
+
+        let taxes = Self::tax_summary(&mut tx, vendor_credit.id).await?;
+
+        tx.commit().await?;
+
+        // Invalidate cache
+        self.invalidate_cache(vendor_credit.vendor_id, !applications.is_empty(), vendor_credit.transaction_id.is_some()).await;
+
+        Ok(VendorCreditWithLineItems {
+            vendor_credit,
+            line_items,
+            taxes,
+            applications,
+        })
+    }
+
+    /// Get a vendor credit with line items, taxes and applications
+    pub async fn get_vendor_credit(&self, pool: &PgPool, vendor_credit_id: Uuid) -> Result<VendorCreditWithLineItems> {
+        let cache_key = format!("vendor_credit:{}", vendor_credit_id);
+        if let Ok(Some(cached)) = self.cache.get::<VendorCreditWithLineItems>(&cache_key).await
+            && cached.vendor_credit.company_id == self.company_id
+        {
+            return Ok(cached);
+        }
+
+        let vendor_credit = sqlx::query_as::<_, VendorCredit>(
+            "SELECT * FROM vendor_credits WHERE id = $1 AND company_id IS NOT DISTINCT FROM $2"
+        )
+        .bind(vendor_credit_id)
+        .bind(self.company_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Vendor credit with id {} not found", vendor_credit_id)))?;
+
+        let line_items = sqlx::query_as::<_, VendorCreditLineItem>(
+            "SELECT * FROM vendor_credit_line_items WHERE vendor_credit_id = $1 ORDER BY line_number"
+        )
+        .bind(vendor_credit_id)
+        .fetch_all(pool)
+        .await?;
+
+        let applications = sqlx::query_as::<_, VendorCreditApplication>(
+            "SELECT * FROM vendor_credit_applications WHERE vendor_credit_id = $1 ORDER BY created_at"
+        )
+        .bind(vendor_credit_id)
+        .fetch_all(pool)
+        .await?;
+
+        let mut conn = pool.acquire().await?;
+        let taxes = Self::tax_summary(&mut conn, vendor_credit_id).await?;
+
+        let result = VendorCreditWithLineItems {
+            vendor_credit,
+            line_items,
+            taxes,
+            applications,
+        };
+        let _ = self.cache.set_with_ttl(&cache_key, &result, 3600).await;
+
+        Ok(result)
+    }
+
+    /// List vendor credits, optionally for one vendor
+    pub async fn list_vendor_credits(
+        &self,
+        pool: &PgPool,
+        vendor_id: Option<Uuid>,
+        status: Option<VendorCreditStatus>,
+    ) -> Result<Vec<VendorCredit>> {
+        let vendor_credits = sqlx::query_as::<_, VendorCredit>(
+            r#"
+            SELECT * FROM vendor_credits
+            WHERE ($1::uuid IS NULL OR vendor_id = $1)
+              AND ($2::varchar IS NULL OR status = $2)
+              AND company_id IS NOT DISTINCT FROM $3
+            ORDER BY credit_date DESC, created_at DESC
+            "#
+        )
+        .bind(vendor_id)
+        .bind(status.map(|s| s.to_string()))
+        .bind(self.company_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(vendor_credits)
+    }
+
+    /// Apply a vendor credit's remaining balance to open bills
+    pub async fn apply_vendor_credit(
+        &self,
+        pool: &PgPool,
+        vendor_credit_id: Uuid,
+        applications: Vec<VendorCreditApplicationRequest>,
+    ) -> Result<VendorCredit> {
+        let mut tx = pool.begin().await?;
+
+        let vendor_credit = Self::lock_vendor_credit(&mut tx, self.company_id, vendor_credit_id).await?;
+        let (vendor_credit, _) = Self::apply_to_bills(&mut tx, &vendor_credit, &applications).await?;
+
+        tx.commit().await?;
+
+        self.invalidate_cache(vendor_credit.vendor_id, true, false).await;
+
+        Ok(vendor_credit)
+    }
+
+    /// Void a vendor credit that hasn't been applied, reversing its posting
+    pub async fn void_vendor_credit(&self, pool: &PgPool, vendor_credit_id: Uuid) -> Result<VendorCredit> {
+        let mut tx = pool.begin().await?;
+
+        let vendor_credit = Self::lock_vendor_credit(&mut tx, self.company_id, vendor_credit_id).await?;
+        if vendor_credit.balance != vendor_credit.total_amount {
+            return Err(AppError::ValidationError(
+                "Cannot void a vendor credit that has been applied".to_string()
+            ));
+        }
+
+        TaxService::ensure_period_open(&mut tx, vendor_credit.company_id, vendor_credit.credit_date).await?;
+
+        let voided = sqlx::query_as::<_, VendorCredit>(
+            r#"
+            UPDATE vendor_credits
+            SET status = $1, balance = 0, updated_at = NOW()
+            WHERE id = $2
+            RETURNING *
+            "#
+        )
+        .bind(VendorCreditStatus::Void.to_string())
+        .bind(vendor_credit_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if let Some(transaction_id) = voided.transaction_id {
+            TransactionService::reverse_entry(&mut tx, transaction_id, Utc::now().date_naive(), None).await?;
+        }
+
+        tx.commit().await?;
+
+        self.invalidate_cache(voided.vendor_id, false, voided.transaction_id.is_some()).await;
+
+        Ok(voided)
+    }
+
+    // Helper methods
+
+    async fn lock_vendor_credit(conn: &mut PgConnection, company_id: Option<Uuid>, vendor_credit_id: Uuid) -> Result<VendorCredit> {
+        let vendor_credit = sqlx::query_as::<_, VendorCredit>(
+            "SELECT * FROM vendor_credits WHERE id = $1 AND company_id IS NOT DISTINCT FROM $2 FOR UPDATE"
+        )
+        .bind(vendor_credit_id)
+        .bind(company_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Vendor credit with id {} not found", vendor_credit_id)))?;
+
+        if vendor_credit.status == VendorCreditStatus::Void {
+            return Err(AppError::ValidationError("Vendor credit is void".to_string()));
+        }
+
+        Ok(vendor_credit)
+    }
+
+    /// Apply credit to bills of the same vendor, recalculating bill balances the way bill
+    /// payment applications do. No ledger entry: both sides are in payables.
+    async fn apply_to_bills(
+        conn: &mut PgConnection,
+        vendor_credit: &VendorCredit,
+        applications: &[VendorCreditApplicationRequest],
+    ) -> Result<(VendorCredit, Vec<VendorCreditApplication>)> {
+        let total_applications: Decimal = applications.iter().map(|app| app.amount_applied).sum();
+        if total_applications > vendor_credit.balance {
+            return Err(AppError::ValidationError(
+                "Total application amount cannot exceed the vendor credit's remaining credit".to_string()
+            ));
+        }
+
+        let amounts: Vec<(Uuid, Decimal)> = applications.iter()
+            .map(|app| (app.bill_id, app.amount_applied))
+            .collect();
+        BillService::lock_for_application(conn, vendor_credit.vendor_id, &amounts).await?;
+
+        let mut created = Vec::with_capacity(applications.len());
+        for application in applications {
+            let applied = sqlx::query_as::<_, VendorCreditApplication>(
+                r#"
+                INSERT INTO vendor_credit_applications
+                    (id, vendor_credit_id, bill_id, amount_applied, created_at)
+                VALUES ($1, $2, $3, $4, NOW())
+                RETURNING id, vendor_credit_id, bill_id, amount_applied, created_at
+                "#,
+            )
+            .bind(Uuid::new_v4())
+            .bind(vendor_credit.id)
+            .bind(application.bill_id)
+            .bind(application.amount_applied)
+            .fetch_one(&mut *conn)
+            .await?;
+
+            BillService::recalculate_balance(conn, application.bill_id).await?;
+
+            created.push(applied);
+        }
+
+        let updated = sqlx::query_as::<_, VendorCredit>(
+            r#"
+            UPDATE vendor_credits
+            SET balance = balance - $1,
+                status = CASE WHEN balance - $1 <= 0 THEN 'applied' ELSE status END,
+                updated_at = NOW()
+            WHERE id = $2
+            RETURNING *
+            "#
+        )
+        .bind(total_applications)
+        .bind(vendor_credit.id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok((updated, created))
+    }
+
+    /// Tax on a vendor credit totalled by component tax
+    async fn tax_summary(conn: &mut PgConnection, vendor_credit_id: Uuid) -> Result<Vec<TaxSummary>> {
+        let taxes = sqlx::query_as::<_, TaxSummary>(
+            r#"
+            SELECT tax_name, rate, SUM(taxable_amount) as taxable_amount, SUM(tax_amount) as tax_amount
+            FROM vendor_credit_line_taxes
+            WHERE vendor_credit_id = $1
+            GROUP BY tax_name, rate
+            ORDER BY tax_name, rate
+            "#
+        )
+        .bind(vendor_credit_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(taxes)
+    }
+
+    /// Post the vendor credit to the ledger, mirroring the bill posting: Dr accounts
+    /// payable, Cr expenses and input tax credits. Non-recoverable tax comes off the
+    /// expense. Vendor credits without an AP account are not posted.
+    async fn post_vendor_credit(conn: &mut PgConnection, vendor_credit: &VendorCredit) -> Result<Option<Uuid>> {
+        let Some(ap_account_id) = vendor_credit.ap_account_id else {
+            return Ok(None);
+        };
+
+        let expenses: Vec<(Uuid, Decimal)> = sqlx::query_as(
+            r#"
+            SELECT expense_account_id, -amount
+            FROM vendor_credit_line_items
+            WHERE vendor_credit_id = $1
+            ORDER BY line_number
+            "#
+        )
+        .bind(vendor_credit.id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let taxes: Vec<(Uuid, Decimal)> = sqlx::query_as(
+            r#"
+            SELECT
+                CASE WHEN vclt.is_recoverable
+                     THEN COALESCE(tr.recoverable_account_id, tr.liability_account_id)
+                     ELSE vcli.expense_account_id
+                END,
+                -vclt.tax_amount
+            FROM vendor_credit_line_taxes vclt
+            INNER JOIN tax_rates tr ON tr.id = vclt.tax_rate_id
+            INNER JOIN vendor_credit_line_items vcli ON vcli.id = vclt.vendor_credit_line_item_id
+            WHERE vclt.vendor_credit_id = $1
+            ORDER BY vcli.line_number, vclt.created_at
+            "#
+        )
+        .bind(vendor_credit.id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut amounts = expenses;
+        amounts.extend(taxes);
+        amounts.push((ap_account_id, vendor_credit.total_amount));
+
+        let description = format!("Vendor credit {}", vendor_credit.vendor_credit_number);
+        let entry = CreateTransactionRequest {
+            transaction_date: vendor_credit.credit_date,
+            description: Some(description.clone()),
+            reference_number: Some(vendor_credit.vendor_credit_number.clone()),
+            entry_number: None,
+            contact_id: Some(vendor_credit.vendor_id),
+            company_id: vendor_credit.company_id,
+            journal_type: Some(JournalType::Purchases),
+            line_items: CreateLineItemRequest::from_signed_amounts(&amounts, &description),
+        };
+        let transaction_id = TransactionService::post_entry(conn, &entry, None).await?;
+
+        sqlx::query("UPDATE vendor_credits SET transaction_id = $1 WHERE id = $2")
+            .bind(transaction_id)
+            .bind(vendor_credit.id)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(Some(transaction_id))
+    }
+
+    async fn invalidate_cache(&self, vendor_id: Uuid, bills_changed: bool, ledger_changed: bool) {
+        let _ = self.cache.delete_pattern("vendor_credit:*").await;
+        if bills_changed {
+            let _ = self.cache.delete_pattern("bill:*").await;
+            let _ = self.cache.delete_pattern(&format!("vendor:bills:*:{}", vendor_id)).await;
+        }
+        if ledger_changed {
+            let _ = self.cache.invalidate_all_account_balances().await;
+        }
+    }
+}
@@ -0,0 +1,329 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{
+    AccountType, CreateLineItemRequest, CreateTransactionRequest, Invoice, InvoiceStatus,
+    InvoiceWriteOff, JournalType, RecoverWriteOffRequest, WriteOffInvoicesRequest, WriteOffRecovery,
+    WriteOffWithRecoveries,
+};
+use crate::utils::{AppError, Result};
+use crate::services::{CacheService, InvoiceService, TransactionService};
+
+#[derive(Clone)]
+pub struct WriteOffService {
+    cache: CacheService,
+    company_id: Option<Uuid>,
+}
+
+impl WriteOffService {
+    pub fn new_with_cache(cache: CacheService) -> Self {
+        Self { cache, company_id: None }
+    }
+
+    /// Scope the service to a company's records
+    pub fn for_company(&self, company_id: Option<Uuid>) -> Self {
+        Self { company_id, ..self.clone() }
+    }
+
+    /// Write off the remaining balance of one or more invoices as bad debt.
+    ///
+    /// Each invoice is posted Dr bad-debt (or allowance) / Cr accounts receivable and
+    /// marked written off. The batch succeeds or fails as a whole.
+    pub async fn write_off_invoices(
+        &self,
+        pool: &PgPool,
+        req: WriteOffInvoicesRequest,
+        created_by: Option<Uuid>,
+    ) -> Result<Vec<InvoiceWriteOff>> {
+        req.validate().map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+        let account_type = sqlx::query_scalar::<_, AccountType>(
+            "SELECT account_type FROM chart_of_accounts WHERE id = $1 AND is_active = true AND company_id IS NOT DISTINCT FROM $2"
+        )
+        .bind(req.account_id)
+        .bind(self.company_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Write-off account not found".to_string()))?;
+
+        // Bad-debt expense, or the allowance for doubtful accounts (a contra asset)
+        if !matches!(account_type, AccountType::Expense | AccountType::Asset) {
+            return Err(AppError::ValidationError(
+                "Bad debt must be written off to an expense or allowance account".to_string()
+            ));
+        }
+
+        let mut invoice_ids = req.invoice_ids.clone();
+        invoice_ids.sort();
+        invoice_ids.dedup();
+
+        let mut tx = pool.begin().await?;
+
+        // Lock in a stable order so concurrent batches cannot deadlock
+        let invoices = sqlx::query_as::<_, Invoice>(
+            "SELECT * FROM invoices WHERE id = ANY($1) AND company_id IS NOT DISTINCT FROM $2 ORDER BY id FOR UPDATE"
+        )
+        .bind(&invoice_ids)
+        .bind(self.company_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if let Some(missing) = invoice_ids.iter().find(|id| invoices.iter().all(|i| i.id != **id)) {
+            return Err(AppError::NotFound(format!("Invoice with id {} not found", missing)));
+        }
+
+        let mut write_offs = Vec::with_capacity(invoices.len());
+        for invoice in &invoices {
+            if !matches!(invoice.status, InvoiceStatus::Sent | InvoiceStatus::Partial | InvoiceStatus::Overdue)
+                || invoice.balance <= Decimal::ZERO
+            {
+                return Err(AppError::ValidationError(
+                    format!("Invoice {} has no open balance to write off", invoice.invoice_number)
+                ));
+            }
+            if req.write_off_date < invoice.invoice_date {
+                return Err(AppError::ValidationError(
+                    format!("Invoice {} cannot be written off before its invoice date", invoice.invoice_number)
+                ));
+            }
+            let ar_account_id = invoice.ar_account_id.ok_or_else(|| AppError::ValidationError(
+                format!("Invoice {} has no accounts receivable account", invoice.invoice_number)
+            ))?;
+
+            let description = format!("Bad debt write-off, invoice {}", invoice.invoice_number);
+            let transaction_id = Self::post(
+                &mut tx,
+                invoice,
+                req.write_off_date,
+                &[(req.account_id, invoice.balance), (ar_account_id, -invoice.balance)],
+                &description,
+                created_by,
+            ).await?;
+
+            let write_off = sqlx::query_as::<_, InvoiceWriteOff>(
+                r#"
+                INSERT INTO invoice_write_offs
+                    (id, invoice_id, customer_id, write_off_date, amount, account_id, reason,
+                     transaction_id, company_id, created_by, created_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, NOW())
+                RETURNING *
+                "#
+            )
+            .bind(Uuid::new_v4())
+            .bind(invoice.id)
+            .bind(invoice.customer_id)
+            .bind(req.write_off_date)
+            .bind(invoice.balance)
+            .bind(req.account_id)
+            .bind(&req.reason)
+            .bind(transaction_id)
+            .bind(invoice.company_id)
+            .bind(created_by)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                "UPDATE invoices SET balance = 0, status = $1, updated_at = NOW() WHERE id = $2"
+            )
+            .bind(InvoiceStatus::WrittenOff.to_string())
+            .bind(invoice.id)
+            .execute(&mut *tx)
+            .await?;
+
+            write_offs.push(write_off);
+        }
+
+        tx.commit().await?;
+
+        self.invalidate_cache().await;
+
+        Ok(write_offs)
+    }
+
+    /// Reinstate written-off balance on an invoice so a late payment can be applied to it.
+    ///
+    /// Reverses the write-off posting (Dr accounts receivable / Cr the write-off account),
+    /// most recent write-off first.
+    pub async fn recover(
+        &self,
+        pool: &PgPool,
+        invoice_id: Uuid,
+        req: RecoverWriteOffRequest,
+        created_by: Option<Uuid>,
+    ) -> Result<Invoice> {
+        let mut tx = pool.begin().await?;
+
+        let invoice = sqlx::query_as::<_, Invoice>(
+            "SELECT * FROM invoices WHERE id = $1 AND company_id IS NOT DISTINCT FROM $2 FOR UPDATE"
+        )
+        .bind(invoice_id)
+        .bind(self.company_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Invoice not found".to_string()))?;
+
+        let write_offs = sqlx::query_as::<_, InvoiceWriteOff>(
+            r#"
+            SELECT * FROM invoice_write_offs
+            WHERE invoice_id = $1 AND recovered_amount < amount
+            ORDER BY write_off_date DESC, created_at DESC
+            FOR UPDATE
+            "#
+        )
+        .bind(invoice_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let outstanding: Decimal = write_offs.iter().map(|w| w.amount - w.recovered_amount).sum();
+        if outstanding <= Decimal::ZERO {
+            return Err(AppError::ValidationError(
+                format!("Invoice {} has no written-off balance to recover", invoice.invoice_number)
+            ));
+        }
+
+        let amount = req.amount.unwrap_or(outstanding);
+        if amount <= Decimal::ZERO || amount > outstanding {
+            return Err(AppError::ValidationError(
+                format!("Recovery amount must be greater than zero and at most {}", outstanding)
+            ));
+        }
+        if write_offs.iter().any(|w| req.recovery_date < w.write_off_date) {
+            return Err(AppError::ValidationError(
+                "Recovery date cannot be before the write-off date".to_string()
+            ));
+        }
+
+        let ar_account_id = invoice.ar_account_id.ok_or_else(|| AppError::ValidationError(
+            format!("Invoice {} has no accounts receivable account", invoice.invoice_number)
+        ))?;
+
+        let mut remaining = amount;
+        for write_off in &write_offs {
+            if remaining <= Decimal::ZERO {
+                break;
+            }
+            let portion = remaining.min(write_off.amount - write_off.recovered_amount);
+            remaining -= portion;
+
+            let description = format!("Bad debt recovery, invoice {}", invoice.invoice_number);
+            let transaction_id = Self::post(
+                &mut tx,
+                &invoice,
+                req.recovery_date,
+                &[(ar_account_id, portion), (write_off.account_id, -portion)],
+                &description,
+                created_by,
+            ).await?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO write_off_recoveries
+                    (id, write_off_id, invoice_id, recovery_date, amount, transaction_id, created_by, created_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, NOW())
+                "#
+            )
+            .bind(Uuid::new_v4())
+            .bind(write_off.id)
+            .bind(invoice_id)
+            .bind(req.recovery_date)
+            .bind(portion)
+            .bind(transaction_id)
+            .bind(created_by)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query("UPDATE invoice_write_offs SET recovered_amount = recovered_amount + $1 WHERE id = $2")
+                .bind(portion)
+                .bind(write_off.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        // The reinstated balance is collected like any other open invoice
+        sqlx::query("UPDATE invoices SET status = $1 WHERE id = $2")
+            .bind(InvoiceStatus::Sent.to_string())
+            .bind(invoice_id)
+            .execute(&mut *tx)
+            .await?;
+        let invoice = InvoiceService::recalculate_balance(&mut tx, invoice_id).await?;
+
+        tx.commit().await?;
+
+        self.invalidate_cache().await;
+
+        Ok(invoice)
+    }
+
+    /// List write-offs with their recoveries, newest first
+    pub async fn list_write_offs(
+        &self,
+        pool: &PgPool,
+        customer_id: Option<Uuid>,
+        invoice_id: Option<Uuid>,
+    ) -> Result<Vec<WriteOffWithRecoveries>> {
+        let write_offs = sqlx::query_as::<_, InvoiceWriteOff>(
+            r#"
+            SELECT * FROM invoice_write_offs
+            WHERE ($1::uuid IS NULL OR customer_id = $1)
+              AND ($2::uuid IS NULL OR invoice_id = $2)
+              AND company_id IS NOT DISTINCT FROM $3
+            ORDER BY write_off_date DESC, created_at DESC
+            "#
+        )
+        .bind(customer_id)
+        .bind(invoice_id)
+        .bind(self.company_id)
+        .fetch_all(pool)
+        .await?;
+
+        let ids: Vec<Uuid> = write_offs.iter().map(|w| w.id).collect();
+        let recoveries = sqlx::query_as::<_, WriteOffRecovery>(
+            "SELECT * FROM write_off_recoveries WHERE write_off_id = ANY($1) ORDER BY recovery_date, created_at"
+        )
+        .bind(&ids)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(write_offs
+            .into_iter()
+            .map(|write_off| {
+                let recoveries = recoveries.iter()
+                    .filter(|r| r.write_off_id == write_off.id)
+                    .cloned()
+                    .collect();
+                WriteOffWithRecoveries { write_off, recoveries }
+            })
+            .collect())
+    }
+
+    async fn post(
+        conn: &mut PgConnection,
+        invoice: &Invoice,
+        date: NaiveDate,
+        amounts: &[(Uuid, Decimal)],
+        description: &str,
+        created_by: Option<Uuid>,
+    ) -> Result<Uuid> {
+        let entry = CreateTransactionRequest {
+            transaction_date: date,
+            description: Some(description.to_string()),
+            reference_number: Some(invoice.invoice_number.clone()),
+            entry_number: None,
+            contact_id: Some(invoice.customer_id),
+            company_id: invoice.company_id,
+            journal_type: Some(JournalType::General),
+            line_items: CreateLineItemRequest::from_signed_amounts(amounts, description),
+        };
+
+        TransactionService::post_entry(conn, &entry, created_by).await
+    }
+
+    async fn invalidate_cache(&self) {
+        let _ = self.cache.delete_pattern("invoice:*").await;
+        let _ = self.cache.delete_pattern("ar_aging:*").await;
+        let _ = self.cache.invalidate_all_account_balances().await;
+    }
+}
@@ -13,6 +13,7 @@ pub enum AppError {
     InvalidCredentials,
     TokenExpired,
     InvalidToken,
+    Forbidden(String),
 
     // Validation errors
     ValidationError(String),
@@ -45,6 +46,7 @@ impl fmt::Display for AppError {
             AppError::InvalidCredentials => write!(f, "Invalid credentials"),
             AppError::TokenExpired => write!(f, "Token has expired"),
             AppError::InvalidToken => write!(f, "Invalid token"),
+            AppError::Forbidden(msg) => write!(f, "Forbidden: {}", msg),
             AppError::ValidationError(msg) => write!(f, "Validation error: {}", msg),
             AppError::DatabaseError(msg) => write!(f, "Database error: {}", msg),
             AppError::NotFound(msg) => write!(f, "Not found: {}", msg),
@@ -65,6 +67,7 @@ impl IntoResponse for AppError {
             AppError::InvalidCredentials => (StatusCode::UNAUTHORIZED, "Invalid credentials".to_string()),
             AppError::TokenExpired => (StatusCode::UNAUTHORIZED, "Token has expired".to_string()),
             AppError::InvalidToken => (StatusCode::UNAUTHORIZED, "Invalid token".to_string()),
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
             AppError::ValidationError(msg) => (StatusCode::BAD_REQUEST, msg),
             AppError::DatabaseError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Database error: {}", msg)),
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
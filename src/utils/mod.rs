@@ -1,7 +1,8 @@
 pub mod errors;
 pub mod response;
 pub mod csv_import;
+pub mod pdf;
 
 pub use errors::{AppError, Result};
-pub use response::{ApiResponse, HealthResponse, success, created, no_content};
+pub use response::{ApiResponse, HealthResponse, success, created, no_content, pdf};
 pub use csv_import::*;
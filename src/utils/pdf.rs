@@ -0,0 +1,356 @@
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref, Str};
+
+/// A4 portrait, in points
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 50.0;
+
+const REGULAR: Name<'static> = Name(b"F1");
+const BOLD: Name<'static> = Name(b"F2");
+
+/// Horizontal alignment of a table cell relative to its x position
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// One cell of a table row: x position, alignment and text
+pub type Cell<'a> = (f32, Align, &'a str);
+
+/// JPEG image embedded as-is (DCTDecode), so no image decoding is needed
+struct Image {
+    data: Vec<u8>,
+    width: u32,
+    height: u32,
+    components: u8,
+}
+
+/// Simple flowing document built on the standard Helvetica fonts, so no font
+/// files are needed. Text is laid out top to bottom and pages break automatically.
+pub struct PdfDocument {
+    pages: Vec<Content>,
+    /// Images drawn on each page, by index into `images`
+    page_images: Vec<Vec<usize>>,
+    images: Vec<Image>,
+    y: f32,
+}
+
+impl Default for PdfDocument {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PdfDocument {
+    pub fn new() -> Self {
+        Self {
+            pages: vec![Content::new()],
+            page_images: vec![Vec::new()],
+            images: Vec::new(),
+            y: PAGE_HEIGHT - MARGIN,
+        }
+    }
+
+    /// Left edge of the writable area
+    pub fn left(&self) -> f32 {
+        MARGIN
+    }
+
+    /// Right edge of the writable area
+    pub fn right(&self) -> f32 {
+        PAGE_WIDTH - MARGIN
+    }
+
+    /// Start a new page
+    pub fn new_page(&mut self) {
+        self.pages.push(Content::new());
+        self.page_images.push(Vec::new());
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    /// Large bold line of text
+    pub fn heading(&mut self, text: &str) {
+        self.line(&[(MARGIN, Align::Left, text)], true, 16.0);
+        self.space(4.0);
+    }
+
+    /// Regular line of text
+    pub fn text(&mut self, text: &str) {
+        self.line(&[(MARGIN, Align::Left, text)], false, 10.0);
+    }
+
+    /// Table row; bold rows are used for headers and totals
+    pub fn row(&mut self, cells: &[Cell], bold: bool) {
+        self.line(cells, bold, 10.0);
+    }
+
+    /// Horizontal rule across the page
+    pub fn rule(&mut self) {
+        self.ensure_space(6.0);
+        let y = self.y + 3.0;
+        self.current()
+            .set_line_width(0.5)
+            .move_to(MARGIN, y)
+            .line_to(PAGE_WIDTH - MARGIN, y)
+            .stroke();
+        self.y -= 6.0;
+    }
+
+    /// Vertical gap
+    pub fn space(&mut self, points: f32) {
+        self.y -= points;
+    }
+
+    /// JPEG image at the left margin, scaled to fit `max_width` x `max_height`.
+    /// Returns false, drawing nothing, when the data isn't a usable JPEG.
+    pub fn jpeg(&mut self, data: &[u8], max_width: f32, max_height: f32) -> bool {
+        let Some((width, height, components)) = jpeg_info(data) else {
+            return false;
+        };
+
+        let scale = (max_width / width as f32).min(max_height / height as f32);
+        let (draw_width, draw_height) = (width as f32 * scale, height as f32 * scale);
+        self.ensure_space(draw_height);
+        self.y -= draw_height;
+
+        let index = self.images.len();
+        self.images.push(Image { data: data.to_vec(), width, height, components });
+        self.page_images.last_mut().expect("document always has a page").push(index);
+
+        let y = self.y;
+        let name = image_name(index);
+        self.current()
+            .save_state()
+            .transform([draw_width, 0.0, 0.0, draw_height, MARGIN, y])
+            .x_object(Name(name.as_bytes()))
+            .restore_state();
+        self.y -= 6.0;
+
+        true
+    }
+
+    /// Render the document
+    pub fn finish(self) -> Vec<u8> {
+        let mut pdf = Pdf::new();
+        let catalog_id = Ref::new(1);
+        let page_tree_id = Ref::new(2);
+        let regular_id = Ref::new(3);
+        let bold_id = Ref::new(4);
+        let mut next_id = 5;
+
+        let mut image_ids = Vec::with_capacity(self.images.len());
+        for image in &self.images {
+            let image_id = Ref::new(next_id);
+            next_id += 1;
+
+            let mut xobject = pdf.image_xobject(image_id, &image.data);
+            xobject.filter(Filter::DctDecode);
+            xobject.width(image.width as i32);
+            xobject.height(image.height as i32);
+            match image.components {
+                1 => xobject.color_space().device_gray(),
+                4 => xobject.color_space().device_cmyk(),
+                _ => xobject.color_space().device_rgb(),
+            };
+            xobject.bits_per_component(8);
+            xobject.finish();
+            image_ids.push(image_id);
+        }
+
+        pdf.catalog(catalog_id).pages(page_tree_id);
+        pdf.type1_font(regular_id)
+            .base_font(Name(b"Helvetica"))
+            .encoding_predefined(Name(b"WinAnsiEncoding"));
+        pdf.type1_font(bold_id)
+            .base_font(Name(b"Helvetica-Bold"))
+            .encoding_predefined(Name(b"WinAnsiEncoding"));
+
+        let mut page_ids = Vec::with_capacity(self.pages.len());
+        for (content, images) in self.pages.into_iter().zip(self.page_images) {
+            let page_id = Ref::new(next_id);
+            let content_id = Ref::new(next_id + 1);
+            next_id += 2;
+
+            let mut page = pdf.page(page_id);
+            page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT));
+            page.parent(page_tree_id);
+            page.contents(content_id);
+            let mut resources = page.resources();
+            let mut fonts = resources.fonts();
+            fonts.pair(REGULAR, regular_id);
+            fonts.pair(BOLD, bold_id);
+            fonts.finish();
+            if !images.is_empty() {
+                let mut x_objects = resources.x_objects();
+                for index in images {
+                    let name = image_name(index);
+                    x_objects.pair(Name(name.as_bytes()), image_ids[index]);
+                }
+                x_objects.finish();
+            }
+            resources.finish();
+            page.finish();
+
+            pdf.stream(content_id, &content.finish());
+            page_ids.push(page_id);
+        }
+
+        let count = page_ids.len() as i32;
+        pdf.pages(page_tree_id).kids(page_ids).count(count);
+
+        pdf.finish()
+    }
+
+    fn line(&mut self, cells: &[Cell], bold: bool, size: f32) {
+        let leading = size * 1.4;
+        self.ensure_space(leading);
+        self.y -= size;
+
+        let y = self.y;
+        let font = if bold { BOLD } else { REGULAR };
+        let content = self.current();
+        for (x, align, text) in cells {
+            let encoded = encode(text);
+            let x = match align {
+                Align::Left => *x,
+                Align::Right => *x - text_width(&encoded, bold, size),
+            };
+            content
+                .begin_text()
+                .set_font(font, size)
+                .next_line(x, y)
+                .show(Str(&encoded))
+                .end_text();
+        }
+
+        self.y -= leading - size;
+    }
+
+    fn ensure_space(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            self.new_page();
+        }
+    }
+
+    fn current(&mut self) -> &mut Content {
+        self.pages.last_mut().expect("document always has a page")
+    }
+}
+
+/// Resource name of an embedded image, e.g. `Im0`
+fn image_name(index: usize) -> String {
+    format!("Im{}", index)
+}
+
+/// Width, height and colour components of a baseline or progressive JPEG, read from its
+/// start-of-frame marker
+pub fn jpeg_info(data: &[u8]) -> Option<(u32, u32, u8)> {
+    if data.len() < 4 || data[0] != 0xFF || data[1] != 0xD8 {
+        return None;
+    }
+
+    let mut pos = 2;
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return None;
+        }
+        let marker = data[pos + 1];
+        // Fill bytes and standalone markers have no length
+        if marker == 0xFF {
+            pos += 1;
+            continue;
+        }
+        if marker == 0x01 || (0xD0..=0xD7).contains(&marker) {
+            pos += 2;
+            continue;
+        }
+
+        let length = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        let is_frame = matches!(marker, 0xC0..=0xCF) && !matches!(marker, 0xC4 | 0xC8 | 0xCC);
+        if is_frame {
+            let frame = data.get(pos + 4..pos + 2 + length)?;
+            if frame.len() < 6 {
+                return None;
+            }
+            let height = u16::from_be_bytes([frame[1], frame[2]]) as u32;
+            let width = u16::from_be_bytes([frame[3], frame[4]]) as u32;
+            let components = frame[5];
+            if width == 0 || height == 0 || !matches!(components, 1 | 3 | 4) {
+                return None;
+            }
+            return Some((width, height, components));
+        }
+
+        pos += 2 + length;
+    }
+
+    None
+}
+
+/// Break text into lines of at most `max_chars` characters on word boundaries,
+/// keeping explicit line breaks
+pub fn wrap(text: &str, max_chars: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > max_chars {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Encode text as WinAnsi (Latin-1 covers it for our purposes); other characters become '?'
+fn encode(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| if (c as u32) < 256 { c as u8 } else { b'?' })
+        .collect()
+}
+
+/// Approximate Helvetica advance widths (per 1000 em) for right-aligning cells.
+/// Digits and common punctuation are exact so amount columns line up.
+fn text_width(text: &[u8], bold: bool, size: f32) -> f32 {
+    let units: u32 = text
+        .iter()
+        .map(|&b| match b {
+            b'0'..=b'9' | b'$' => 556,
+            b'.' | b',' | b' ' | b'/' => 278,
+            b'-' => 333,
+            b'(' | b')' => 333,
+            b'A'..=b'Z' => if bold { 722 } else { 667 },
+            b'i' | b'j' | b'l' => 222,
+            b'f' | b't' | b'r' => 333,
+            b'm' | b'w' => 833,
+            _ => if bold { 611 } else { 556 },
+        })
+        .sum();
+
+    units as f32 * size / 1000.0
+}
+
+/// Format an amount with two decimals and thousands separators, e.g. `-1,234.50`
+pub fn format_amount(amount: rust_decimal::Decimal) -> String {
+    let rounded = amount.round_dp(2);
+    let negative = rounded.is_sign_negative() && !rounded.is_zero();
+    let text = format!("{:.2}", rounded.abs());
+    let (whole, fraction) = text.split_once('.').unwrap_or((&text, "00"));
+
+    let mut grouped = String::with_capacity(whole.len() + whole.len() / 3);
+    for (i, digit) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+
+    format!("{}{}.{}", if negative { "-" } else { "" }, grouped, fraction)
+}
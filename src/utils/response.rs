@@ -117,3 +117,15 @@ pub fn created<T: Serialize>(data: T) -> impl IntoResponse {
 pub fn no_content() -> impl IntoResponse {
     StatusCode::NO_CONTENT
 }
+
+/// Helper to return a PDF document as a download
+pub fn pdf(bytes: Vec<u8>, filename: &str) -> Response {
+    (
+        StatusCode::OK,
+        [
+            ("Content-Type".to_string(), "application/pdf".to_string()),
+            ("Content-Disposition".to_string(), format!("attachment; filename=\"{}\"", filename)),
+        ],
+        bytes,
+    ).into_response()
+}
@@ -0,0 +1,229 @@
+use ledger_forge::models::{
+    AccountType, PaymentApplicationRequest, InvoiceStatus, CreateBillRequest,
+    CreateBillLineItemRequest, CreateBillPaymentRequest, BillPaymentApplicationRequest, BillStatus,
+};
+use ledger_forge::utils::AppError;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+mod common;
+use common::test_db::TestDb;
+use common::fixtures::{
+    date, dec, create_account, create_customer, create_vendor, create_sent_invoice, payment_request, get_invoice,
+    Receivables,
+};
+use common::services::{payment_service, invoice_service, bill_service};
+
+fn application(invoice_id: Uuid, amount: &str) -> PaymentApplicationRequest {
+    PaymentApplicationRequest { invoice_id, amount_applied: dec(amount) }
+}
+
+struct Receivable {
+    customer: Uuid,
+    /// 100.00 invoice
+    invoice: Uuid,
+}
+
+/// A customer and a 100.00 invoice, either sent (which posts it) or still a draft
+async fn receivable(pool: &PgPool, sent: bool) -> Receivable {
+    let customer = create_customer(pool, "Racing Payers Ltd").await;
+
+    let request = Receivables::create(pool).await.invoice_request("INV-CC-1", customer, "100.00");
+    let invoice = if sent {
+        create_sent_invoice(pool, request).await
+    } else {
+        invoice_service().create_invoice(pool, request).await.unwrap().invoice.id
+    };
+    Receivable { customer, invoice }
+}
+
+/// The receivable with 80.00 paid, and a 50.00 payment that covers the rest with 30.00 left over.
+/// Returns the receivable and the second payment.
+async fn settled(pool: &PgPool) -> (Receivable, Uuid) {
+    let r = receivable(pool, true).await;
+    payment_service().create_payment(pool, payment_request(r.customer, "80.00", &[(r.invoice, "80.00")])).await.unwrap();
+    let rest = payment_service().create_payment(pool, payment_request(r.customer, "50.00", &[(r.invoice, "20.00")])).await.unwrap();
+    (r, rest.id)
+}
+
+#[tokio::test]
+async fn test_racing_payments_cannot_both_apply() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let r = receivable(pool, true).await;
+
+    // Only one of them fits
+    let mut handles = Vec::new();
+    for _ in 0..2 {
+        let pool = pool.clone();
+        let (customer, invoice) = (r.customer, r.invoice);
+        handles.push(tokio::spawn(async move {
+            payment_service().create_payment(&pool, payment_request(customer, "80.00", &[(invoice, "80.00")])).await
+        }));
+    }
+    let mut results = Vec::new();
+    for handle in handles {
+        results.push(handle.await.unwrap());
+    }
+    assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 1);
+    assert!(results.iter().any(|r| matches!(r, Err(AppError::ValidationError(_)))));
+
+    let current = get_invoice(pool, r.invoice).await;
+    assert_eq!(current.balance, dec("20.00"));
+    assert_eq!(current.status, InvoiceStatus::Partial);
+}
+
+#[tokio::test]
+async fn test_over_application_split_across_lines_is_rejected() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let r = receivable(pool, true).await;
+    payment_service().create_payment(pool, payment_request(r.customer, "80.00", &[(r.invoice, "80.00")])).await.unwrap();
+
+    let unapplied = payment_service().create_payment(pool, payment_request(r.customer, "50.00", &[])).await.unwrap();
+    assert!(matches!(
+        payment_service().apply_payment_to_invoices(pool, unapplied.id, vec![
+            application(r.invoice, "15.00"),
+            application(r.invoice, "15.00"),
+        ]).await,
+        Err(AppError::ValidationError(_))
+    ));
+
+    // The balance itself still applies
+    payment_service().apply_payment_to_invoices(pool, unapplied.id, vec![application(r.invoice, "20.00")]).await.unwrap();
+    let current = get_invoice(pool, r.invoice).await;
+    assert_eq!(current.balance, Decimal::ZERO);
+    assert_eq!(current.status, InvoiceStatus::Paid);
+}
+
+#[tokio::test]
+async fn test_bill_payments_cannot_exceed_the_bill() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let ap_account = create_account(pool, "2000", "Accounts Payable", AccountType::Liability).await;
+    let expense_account = create_account(pool, "5000", "Supplies", AccountType::Expense).await;
+    let vendor = create_vendor(pool, "Supplier Co").await;
+
+    let bill = bill_service().create_bill(pool, CreateBillRequest {
+        bill_number: Some("B-CC-1".to_string()),
+        vendor_id: vendor,
+        bill_date: date(2025, 6, 1),
+        due_date: date(2025, 7, 1),
+        memo: None,
+        company_id: None,
+        ap_account_id: Some(ap_account),
+        line_items: vec![CreateBillLineItemRequest {
+            line_number: 1,
+            description: Some("Paper".to_string()),
+            amount: dec("60.00"),
+            expense_account_id: expense_account,
+            billable: None,
+            customer_id: None,
+            item_id: None,
+            quantity: None,
+            tax_code: None,
+            dimension_value_ids: Vec::new(),
+        }],
+    }).await.unwrap();
+    let bill_payment = |amount: &str| CreateBillPaymentRequest {
+        payment_number: None,
+        vendor_id: vendor,
+        payment_date: date(2025, 7, 1),
+        amount: dec(amount),
+        payment_method: "Cheque".to_string(),
+        reference_number: None,
+        bank_account_id: None,
+        memo: None,
+        company_id: None,
+        applications: vec![BillPaymentApplicationRequest { bill_id: bill.id, amount_applied: dec(amount) }],
+    };
+
+    assert!(payment_service().create_bill_payment(pool, bill_payment("70.00")).await.is_err());
+    payment_service().create_bill_payment(pool, bill_payment("60.00")).await.unwrap();
+    let paid = bill_service().get_bill_by_id(pool, bill.id).await.unwrap().unwrap();
+    assert_eq!(paid.bill.status, BillStatus::Paid);
+}
+
+#[tokio::test]
+async fn test_consistency_check_passes_for_service_written_balances() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    settled(pool).await;
+
+    let report = payment_service().check_application_consistency(pool).await.unwrap();
+    assert!(report.consistent, "{:?}", report.drifts);
+}
+
+#[tokio::test]
+async fn test_consistency_check_reports_drift() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (r, rest) = settled(pool).await;
+
+    // Written behind the service's back
+    sqlx::query("UPDATE invoices SET balance = 5, status = 'partial' WHERE id = $1")
+        .bind(r.invoice)
+        .execute(pool)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE payments SET unapplied_amount = 10 WHERE id = $1")
+        .bind(rest)
+        .execute(pool)
+        .await
+        .unwrap();
+
+    let report = payment_service().check_application_consistency(pool).await.unwrap();
+    assert!(!report.consistent);
+    assert_eq!(report.drifts.len(), 2);
+    let invoice_drift = report.drifts.iter().find(|d| d.document_type == "invoice").unwrap();
+    assert_eq!(invoice_drift.document_id, r.invoice);
+    assert_eq!(invoice_drift.recorded_balance, dec("5.00"));
+    assert_eq!(invoice_drift.expected_balance, Decimal::ZERO);
+    assert_eq!(invoice_drift.expected_status.as_deref(), Some("paid"));
+    let payment_drift = report.drifts.iter().find(|d| d.document_type == "payment").unwrap();
+    assert_eq!(payment_drift.expected_balance, dec("30.00"));
+
+    // Another company's check doesn't look at these books
+    let elsewhere = payment_service().for_company(Some(Uuid::new_v4())).check_application_consistency(pool).await.unwrap();
+    assert!(elsewhere.consistent);
+}
+
+#[tokio::test]
+async fn test_drafts_are_not_receivable() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let r = receivable(pool, false).await;
+
+    assert!(matches!(
+        payment_service().create_payment(pool, payment_request(r.customer, "30.00", &[(r.invoice, "30.00")])).await,
+        Err(AppError::ValidationError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_paid_and_partial_cannot_be_set_by_hand() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let r = receivable(pool, false).await;
+
+    assert!(invoice_service().update_invoice_status(pool, r.invoice, InvoiceStatus::Paid).await.is_err());
+    invoice_service().update_invoice_status(pool, r.invoice, InvoiceStatus::Sent).await.unwrap();
+    assert!(invoice_service().update_invoice_status(pool, r.invoice, InvoiceStatus::Partial).await.is_err());
+}
+
+#[tokio::test]
+async fn test_invoice_with_money_applied_cannot_be_voided() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let r = receivable(pool, true).await;
+    payment_service().create_payment(pool, payment_request(r.customer, "30.00", &[(r.invoice, "30.00")])).await.unwrap();
+
+    assert!(matches!(
+        invoice_service().update_invoice_status(pool, r.invoice, InvoiceStatus::Void).await,
+        Err(AppError::ValidationError(_))
+    ));
+    let current = get_invoice(pool, r.invoice).await;
+    assert_eq!(current.status, InvoiceStatus::Partial);
+    assert_eq!(current.balance, dec("70.00"));
+}
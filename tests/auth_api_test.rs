@@ -6,9 +6,12 @@ use serial_test::serial;
 mod common;
 use common::{setup_test_db, cleanup_test_db, TestUser, TEST_JWT_SECRET};
 use common::{assert_success_response, assert_error_response, assert_valid_uuid, assert_valid_jwt};
+use common::services::company_service;
 
-use ledger_forge::services::{AuthService, AccountService, TransactionService, ContactService, InvoiceService, PaymentService, BillService, ImportService, ReportingService, CacheService};
+use ledger_forge::services::{AuthService, AccountService, TransactionService, ContactService, InvoiceService, PaymentService, BillService, ImportService, ReportingService, InventoryService, TaxService, CreditMemoService, StatementService, EstimateService, PurchaseOrderService, VendorCreditService, EmailService, SmtpConfig, DunningService, FinanceChargeService, WriteOffService, NumberingService, BillableExpenseService, DimensionService, CompanyService, CacheService};
+use ledger_forge::models::{CreateCompanyRequest, CreateUserRequest};
 use ledger_forge::routes::create_routes;
+use ledger_forge::services::totp;
 
 async fn create_test_server() -> TestServer {
     let pool = setup_test_db().await;
@@ -24,7 +27,22 @@ async fn create_test_server() -> TestServer {
     let bill_service = BillService::new_with_cache(cache_service.clone());
     let import_service = ImportService::new(cache_service.clone());
     let reporting_service = ReportingService::new_with_cache(cache_service.clone());
-    let app = create_routes(pool, auth_service, account_service, transaction_service, contact_service, invoice_service, payment_service, bill_service, import_service, reporting_service, cache_service);
+    let inventory_service = InventoryService::new_with_cache(cache_service.clone());
+    let tax_service = TaxService::new_with_cache(cache_service.clone());
+    let credit_memo_service = CreditMemoService::new_with_cache(cache_service.clone());
+    let statement_service = StatementService::new_with_cache(cache_service.clone());
+    let estimate_service = EstimateService::new_with_cache(cache_service.clone());
+    let purchase_order_service = PurchaseOrderService::new_with_cache(cache_service.clone());
+    let vendor_credit_service = VendorCreditService::new_with_cache(cache_service.clone());
+    let email_service = EmailService::new(SmtpConfig::from_env(), cache_service.clone());
+    let dunning_service = DunningService::new(email_service.clone(), cache_service.clone());
+    let finance_charge_service = FinanceChargeService::new_with_cache(cache_service.clone());
+    let write_off_service = WriteOffService::new_with_cache(cache_service.clone());
+    let numbering_service = NumberingService::new();
+    let billable_expense_service = BillableExpenseService::new_with_cache(cache_service.clone());
+    let dimension_service = DimensionService::new_with_cache(cache_service.clone());
+    let company_service = CompanyService::new_with_cache(cache_service.clone());
+    let app = create_routes(pool, auth_service, account_service, transaction_service, contact_service, invoice_service, payment_service, bill_service, import_service, reporting_service, inventory_service, tax_service, credit_memo_service, statement_service, estimate_service, purchase_order_service, vendor_credit_service, email_service, dunning_service, finance_charge_service, write_off_service, numbering_service, billable_expense_service, dimension_service, company_service, cache_service);
 
     TestServer::new(app).unwrap()
 }
@@ -47,7 +65,7 @@ async fn test_health_check() {
 #[serial]
 async fn test_register_valid_user_returns_201() {
     let server = create_test_server().await;
-    let user = TestUser::admin();
+    let user = TestUser::regular();
 
     let response = server
         .post("/api/v1/auth/register")
@@ -66,7 +84,7 @@ async fn test_register_valid_user_returns_201() {
 
     let data = &json["data"];
     assert_valid_jwt(data["access_token"].as_str().unwrap());
-    assert_valid_jwt(data["refresh_token"].as_str().unwrap());
+    assert!(!data["refresh_token"].as_str().unwrap().is_empty());
 
     let user_data = &data["user"];
     assert_eq!(user_data["username"], user.username);
@@ -197,7 +215,7 @@ async fn test_login_valid_credentials_returns_200() {
 
     let data = &json["data"];
     assert_valid_jwt(data["access_token"].as_str().unwrap());
-    assert_valid_jwt(data["refresh_token"].as_str().unwrap());
+    assert!(!data["refresh_token"].as_str().unwrap().is_empty());
 
     let user_data = &data["user"];
     assert_eq!(user_data["username"], user.username);
@@ -357,6 +375,16 @@ async fn test_refresh_token_valid_returns_200() {
 
     let data = &json["data"];
     assert_valid_jwt(data["access_token"].as_str().unwrap());
+
+    // The refresh token was rotated and the old one is spent
+    assert_ne!(data["refresh_token"].as_str().unwrap(), refresh_token);
+    let response = server
+        .post("/api/v1/auth/refresh")
+        .json(&json!({
+            "refresh_token": refresh_token
+        }))
+        .await;
+    response.assert_status(axum::http::StatusCode::UNAUTHORIZED);
 }
 
 #[tokio::test]
@@ -418,3 +446,476 @@ async fn test_complete_auth_workflow() {
     let me_json: Value = me_response.json();
     assert_eq!(me_json["data"]["username"], user.username);
 }
+
+/// Create a user with a role in a company of their own and log in. Self-registration only
+/// creates viewers, so the account is created directly.
+async fn register_with_role(server: &TestServer, username: &str, role: &str) -> String {
+    let user = TestUser::with_username(username);
+    let pool = setup_test_db().await;
+    let created = AuthService::new(TEST_JWT_SECRET.to_string())
+        .register(&pool, CreateUserRequest {
+            username: user.username.clone(),
+            email: user.email.clone(),
+            password: user.password.clone(),
+            role: serde_json::from_value(json!(role)).unwrap(),
+        })
+        .await
+        .unwrap();
+    company_service()
+        .create_company(&pool, created.id, CreateCompanyRequest {
+            name: format!("{} Ltd", username),
+            address: None,
+        })
+        .await
+        .unwrap();
+
+    let response = server
+        .post("/api/v1/auth/login")
+        .json(&json!({
+            "username": user.username,
+            "password": user.password
+        }))
+        .await;
+
+    let json: Value = response.json();
+    format!("Bearer {}", json["data"]["access_token"].as_str().unwrap())
+}
+
+fn account_json(code: &str) -> Value {
+    json!({
+        "code": code,
+        "name": "Operating Cash",
+        "account_type": "Asset"
+    })
+}
+
+#[tokio::test]
+#[serial]
+async fn test_api_routes_require_token() {
+    let server = create_test_server().await;
+
+    let response = server.get("/api/v1/accounts").await;
+    response.assert_status(axum::http::StatusCode::UNAUTHORIZED);
+
+    let response = server.post("/api/v1/accounts").json(&account_json("1000")).await;
+    response.assert_status(axum::http::StatusCode::UNAUTHORIZED);
+
+    let response = server
+        .get("/api/v1/invoices")
+        .add_header(
+            HeaderName::from_static("authorization"),
+            HeaderValue::from_static("Bearer invalid.token.here")
+        )
+        .await;
+    response.assert_status(axum::http::StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_company_records_need_an_active_company() {
+    let server = create_test_server().await;
+    let user = TestUser::with_username("homeless");
+
+    // New users belong to no company yet
+    let response = server
+        .post("/api/v1/auth/register")
+        .json(&json!({
+            "username": user.username,
+            "email": user.email,
+            "password": user.password
+        }))
+        .await;
+    response.assert_status(axum::http::StatusCode::CREATED);
+    let json: Value = response.json();
+    let authorization = HeaderValue::from_str(
+        &format!("Bearer {}", json["data"]["access_token"].as_str().unwrap())
+    ).unwrap();
+
+    let response = server
+        .get("/api/v1/accounts")
+        .add_header(HeaderName::from_static("authorization"), authorization.clone())
+        .await;
+    response.assert_status(axum::http::StatusCode::FORBIDDEN);
+    assert_error_response(&response.json());
+
+    let response = server
+        .get("/api/v1/auth/me")
+        .add_header(HeaderName::from_static("authorization"), authorization)
+        .await;
+    response.assert_status_ok();
+}
+
+#[tokio::test]
+#[serial]
+async fn test_viewer_is_read_only() {
+    let server = create_test_server().await;
+    let token = register_with_role(&server, "readonly", "viewer").await;
+    let authorization = HeaderValue::from_str(&token).unwrap();
+
+    let response = server
+        .get("/api/v1/accounts")
+        .add_header(HeaderName::from_static("authorization"), authorization.clone())
+        .await;
+    response.assert_status_ok();
+
+    let response = server
+        .post("/api/v1/accounts")
+        .add_header(HeaderName::from_static("authorization"), authorization.clone())
+        .json(&account_json("1000"))
+        .await;
+    response.assert_status(axum::http::StatusCode::FORBIDDEN);
+    assert_error_response(&response.json());
+
+    let response = server
+        .delete(&format!("/api/v1/contacts/{}", uuid::Uuid::new_v4()))
+        .add_header(HeaderName::from_static("authorization"), authorization)
+        .await;
+    response.assert_status(axum::http::StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_accountant_posts_and_admin_manages() {
+    let server = create_test_server().await;
+    let accountant = HeaderValue::from_str(&register_with_role(&server, "bookkeeper", "accountant").await).unwrap();
+    let admin = HeaderValue::from_str(&register_with_role(&server, "controller", "admin").await).unwrap();
+
+    let response = server
+        .post("/api/v1/accounts")
+        .add_header(HeaderName::from_static("authorization"), accountant.clone())
+        .json(&account_json("1000"))
+        .await;
+    response.assert_status(axum::http::StatusCode::CREATED);
+
+    // Managing users and filing periods is left to admins
+    let company_id = uuid::Uuid::new_v4();
+    let response = server
+        .post(&format!("/api/v1/companies/{}/members", company_id))
+        .add_header(HeaderName::from_static("authorization"), accountant.clone())
+        .json(&json!({ "user_id": uuid::Uuid::new_v4() }))
+        .await;
+    response.assert_status(axum::http::StatusCode::FORBIDDEN);
+
+    let response = server
+        .post(&format!("/api/v1/tax-returns/{}/file", uuid::Uuid::new_v4()))
+        .add_header(HeaderName::from_static("authorization"), accountant)
+        .json(&json!({ "filed_date": "2025-04-30", "settlement_account_id": uuid::Uuid::new_v4() }))
+        .await;
+    response.assert_status(axum::http::StatusCode::FORBIDDEN);
+
+    let response = server
+        .post(&format!("/api/v1/companies/{}/members", company_id))
+        .add_header(HeaderName::from_static("authorization"), admin)
+        .json(&json!({ "user_id": uuid::Uuid::new_v4() }))
+        .await;
+    response.assert_status(axum::http::StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_logout_revokes_refresh_token() {
+    let server = create_test_server().await;
+    let user = TestUser::with_username("logouttest");
+
+    let register_response = server
+        .post("/api/v1/auth/register")
+        .json(&json!({
+            "username": user.username,
+            "email": user.email,
+            "password": user.password,
+            "role": user.role
+        }))
+        .await;
+
+    let register_json: Value = register_response.json();
+    let access_token = register_json["data"]["access_token"].as_str().unwrap();
+    let refresh_token = register_json["data"]["refresh_token"].as_str().unwrap();
+
+    // Viewers can log out too
+    let response = server
+        .post("/api/v1/auth/logout")
+        .add_header(
+            HeaderName::from_static("authorization"),
+            HeaderValue::from_str(&format!("Bearer {}", access_token)).unwrap()
+        )
+        .json(&json!({
+            "refresh_token": refresh_token
+        }))
+        .await;
+    response.assert_status(axum::http::StatusCode::NO_CONTENT);
+
+    let response = server
+        .post("/api/v1/auth/refresh")
+        .json(&json!({
+            "refresh_token": refresh_token
+        }))
+        .await;
+    response.assert_status(axum::http::StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_change_and_forgot_password() {
+    let server = create_test_server().await;
+    let user = TestUser::with_username("passwordtest");
+
+    let register_response = server
+        .post("/api/v1/auth/register")
+        .json(&json!({
+            "username": user.username,
+            "email": user.email,
+            "password": user.password,
+            "role": user.role
+        }))
+        .await;
+
+    let register_json: Value = register_response.json();
+    let access_token = register_json["data"]["access_token"].as_str().unwrap();
+    let refresh_token = register_json["data"]["refresh_token"].as_str().unwrap();
+
+    // Viewers can change their own password and get a new session
+    let response = server
+        .post("/api/v1/auth/change-password")
+        .add_header(
+            HeaderName::from_static("authorization"),
+            HeaderValue::from_str(&format!("Bearer {}", access_token)).unwrap()
+        )
+        .json(&json!({
+            "current_password": user.password,
+            "new_password": "ChangedPass456!"
+        }))
+        .await;
+    response.assert_status_ok();
+    let json: Value = response.json();
+    assert_valid_jwt(json["data"]["access_token"].as_str().unwrap());
+
+    let response = server
+        .post("/api/v1/auth/refresh")
+        .json(&json!({
+            "refresh_token": refresh_token
+        }))
+        .await;
+    response.assert_status(axum::http::StatusCode::UNAUTHORIZED);
+
+    // No token needed, and the answer doesn't depend on the address
+    for email in [user.email.as_str(), "nobody@test.com"] {
+        let response = server
+            .post("/api/v1/auth/forgot-password")
+            .json(&json!({
+                "email": email
+            }))
+            .await;
+        response.assert_status(axum::http::StatusCode::NO_CONTENT);
+    }
+
+    let response = server
+        .post("/api/v1/auth/reset-password")
+        .json(&json!({
+            "token": "not-a-reset-token",
+            "new_password": "ResetPass789!"
+        }))
+        .await;
+    response.assert_status(axum::http::StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_two_factor_login_and_policy() {
+    let server = create_test_server().await;
+    let user = TestUser::with_username("twofactor");
+    let admin_token = register_with_role(&server, &user.username, "admin").await;
+    let authorization = HeaderValue::from_str(&admin_token).unwrap();
+    let viewer_token = register_with_role(&server, "twofactorviewer", "viewer").await;
+
+    // Only admins set the policy
+    let response = server
+        .put("/api/v1/auth/mfa/policy")
+        .add_header(HeaderName::from_static("authorization"), HeaderValue::from_str(&viewer_token).unwrap())
+        .json(&json!({ "required_roles": ["admin"] }))
+        .await;
+    response.assert_status(axum::http::StatusCode::FORBIDDEN);
+
+    let response = server
+        .put("/api/v1/auth/mfa/policy")
+        .add_header(HeaderName::from_static("authorization"), authorization.clone())
+        .json(&json!({ "required_roles": ["admin"] }))
+        .await;
+    response.assert_status_ok();
+
+    // Until set up, admins can only set up two-factor authentication
+    let response = server
+        .get("/api/v1/accounts")
+        .add_header(HeaderName::from_static("authorization"), authorization.clone())
+        .await;
+    response.assert_status(axum::http::StatusCode::FORBIDDEN);
+
+    let response = server
+        .post("/api/v1/auth/mfa/setup")
+        .add_header(HeaderName::from_static("authorization"), authorization.clone())
+        .await;
+    response.assert_status_ok();
+    let json: Value = response.json();
+    let secret = json["data"]["secret"].as_str().unwrap().to_string();
+    assert!(json["data"]["provisioning_uri"].as_str().unwrap().starts_with("otpauth://totp/"));
+
+    let response = server
+        .post("/api/v1/auth/mfa/confirm")
+        .add_header(HeaderName::from_static("authorization"), authorization.clone())
+        .json(&json!({ "code": totp::code_at(&secret, chrono::Utc::now()).unwrap() }))
+        .await;
+    response.assert_status_ok();
+    let json: Value = response.json();
+    let recovery_codes = json["data"]["recovery_codes"].as_array().unwrap();
+    assert_eq!(recovery_codes.len(), 10);
+
+    let response = server
+        .get("/api/v1/accounts")
+        .add_header(HeaderName::from_static("authorization"), authorization)
+        .await;
+    response.assert_status_ok();
+
+    // The password alone only gets a challenge
+    let response = server
+        .post("/api/v1/auth/login")
+        .json(&json!({
+            "username": user.username,
+            "password": user.password
+        }))
+        .await;
+    response.assert_status_ok();
+    let json: Value = response.json();
+    assert_eq!(json["data"]["mfa_required"], true);
+    assert!(json["data"]["access_token"].is_null());
+    let challenge_token = json["data"]["challenge_token"].as_str().unwrap();
+
+    let response = server
+        .get("/api/v1/accounts")
+        .add_header(
+            HeaderName::from_static("authorization"),
+            HeaderValue::from_str(&format!("Bearer {}", challenge_token)).unwrap()
+        )
+        .await;
+    response.assert_status(axum::http::StatusCode::UNAUTHORIZED);
+
+    let response = server
+        .post("/api/v1/auth/mfa/verify")
+        .json(&json!({
+            "challenge_token": challenge_token,
+            "code": recovery_codes[0]
+        }))
+        .await;
+    response.assert_status_ok();
+    let json: Value = response.json();
+    assert_valid_jwt(json["data"]["access_token"].as_str().unwrap());
+    assert!(!json["data"]["refresh_token"].as_str().unwrap().is_empty());
+}
+
+#[tokio::test]
+#[serial]
+async fn test_user_administration() {
+    let server = create_test_server().await;
+    let admin = HeaderValue::from_str(&register_with_role(&server, "useradmin", "admin").await).unwrap();
+
+    // Self-registration can't pick a privileged role
+    let response = server
+        .post("/api/v1/auth/register")
+        .json(&json!({
+            "username": "wannabe",
+            "email": "wannabe@test.com",
+            "password": "TestPass123!",
+            "role": "admin"
+        }))
+        .await;
+    response.assert_status(axum::http::StatusCode::FORBIDDEN);
+
+    let response = server
+        .post("/api/v1/auth/register")
+        .json(&json!({
+            "username": "newcomer",
+            "email": "newcomer@test.com",
+            "password": "TestPass123!"
+        }))
+        .await;
+    response.assert_status(axum::http::StatusCode::CREATED);
+    let json: Value = response.json();
+    assert_eq!(json["data"]["user"]["role"], "viewer");
+    let newcomer_id = json["data"]["user"]["id"].as_str().unwrap().to_string();
+    let newcomer = HeaderValue::from_str(
+        &format!("Bearer {}", json["data"]["access_token"].as_str().unwrap())
+    ).unwrap();
+
+    let response = server
+        .get("/api/v1/users")
+        .add_header(HeaderName::from_static("authorization"), newcomer.clone())
+        .await;
+    response.assert_status(axum::http::StatusCode::FORBIDDEN);
+
+    let response = server
+        .get("/api/v1/users?search=NEWCOM")
+        .add_header(HeaderName::from_static("authorization"), admin.clone())
+        .await;
+    response.assert_status_ok();
+    let json: Value = response.json();
+    let users = json["data"].as_array().unwrap();
+    assert_eq!(users.len(), 1);
+    assert_eq!(users[0]["id"], newcomer_id.as_str());
+    assert_eq!(users[0]["mfa_enabled"], false);
+
+    let response = server
+        .put(&format!("/api/v1/users/{}/role", newcomer_id))
+        .add_header(HeaderName::from_static("authorization"), admin.clone())
+        .json(&json!({ "role": "accountant" }))
+        .await;
+    response.assert_status_ok();
+    let json: Value = response.json();
+    assert_eq!(json["data"]["role"], "accountant");
+
+    // Disabled users are locked out right away
+    let response = server
+        .post(&format!("/api/v1/users/{}/disable", newcomer_id))
+        .add_header(HeaderName::from_static("authorization"), admin.clone())
+        .await;
+    response.assert_status_ok();
+
+    let response = server
+        .get("/api/v1/accounts")
+        .add_header(HeaderName::from_static("authorization"), newcomer)
+        .await;
+    response.assert_status(axum::http::StatusCode::UNAUTHORIZED);
+
+    let login = json!({
+        "username": "newcomer",
+        "password": "TestPass123!"
+    });
+    let response = server.post("/api/v1/auth/login").json(&login).await;
+    response.assert_status(axum::http::StatusCode::FORBIDDEN);
+
+    let response = server
+        .post(&format!("/api/v1/users/{}/enable", newcomer_id))
+        .add_header(HeaderName::from_static("authorization"), admin.clone())
+        .await;
+    response.assert_status_ok();
+    let response = server.post("/api/v1/auth/login").json(&login).await;
+    response.assert_status_ok();
+
+    // Invited users choose their password from the email
+    let response = server
+        .post("/api/v1/users")
+        .add_header(HeaderName::from_static("authorization"), admin.clone())
+        .json(&json!({
+            "username": "invitee",
+            "email": "invitee@test.com",
+            "role": "accountant"
+        }))
+        .await;
+    response.assert_status(axum::http::StatusCode::CREATED);
+    let json: Value = response.json();
+    assert_eq!(json["data"]["role"], "accountant");
+    let invitee_id = json["data"]["id"].as_str().unwrap();
+
+    let response = server
+        .post(&format!("/api/v1/users/{}/reset-password", invitee_id))
+        .add_header(HeaderName::from_static("authorization"), admin)
+        .await;
+    response.assert_status(axum::http::StatusCode::NO_CONTENT);
+}
@@ -18,6 +18,8 @@ fn create_test_user() -> User {
         email: "test@example.com".to_string(),
         password_hash: "fake_hash".to_string(),
         role: UserRole::Viewer,
+        password_changed_at: None,
+        disabled_at: None,
         created_at: Utc::now(),
         updated_at: Utc::now(),
     }
@@ -114,15 +116,16 @@ mod password_tests {
 #[cfg(test)]
 mod jwt_tests {
     use super::*;
-    use jsonwebtoken::{decode, DecodingKey, Validation};
-    use ledger_forge::services::auth::Claims;
+    use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+    use ledger_forge::services::auth::{Claims, TokenType};
+    use serde_json::json;
 
     #[test]
     fn test_generate_access_token_success() {
         let auth_service = create_test_auth_service();
         let user = create_test_user();
 
-        let result = auth_service.generate_access_token(&user);
+        let result = auth_service.generate_access_token(&user, None);
 
         assert!(result.is_ok());
         let token = result.unwrap();
@@ -130,36 +133,12 @@ mod jwt_tests {
         assert_eq!(token.split('.').count(), 3); // JWT has 3 parts
     }
 
-    #[test]
-    fn test_generate_refresh_token_success() {
-        let auth_service = create_test_auth_service();
-        let user = create_test_user();
-
-        let result = auth_service.generate_refresh_token(&user);
-
-        assert!(result.is_ok());
-        let token = result.unwrap();
-        assert!(!token.is_empty());
-        assert_eq!(token.split('.').count(), 3);
-    }
-
-    #[test]
-    fn test_access_and_refresh_tokens_are_different() {
-        let auth_service = create_test_auth_service();
-        let user = create_test_user();
-
-        let access_token = auth_service.generate_access_token(&user).unwrap();
-        let refresh_token = auth_service.generate_refresh_token(&user).unwrap();
-
-        assert_ne!(access_token, refresh_token);
-    }
-
     #[test]
     fn test_validate_token_success() {
         let auth_service = create_test_auth_service();
         let user = create_test_user();
 
-        let token = auth_service.generate_access_token(&user).unwrap();
+        let token = auth_service.generate_access_token(&user, None).unwrap();
         let result = auth_service.validate_token(&token);
 
         assert!(result.is_ok());
@@ -175,7 +154,7 @@ mod jwt_tests {
         let auth_service2 = AuthService::new("different-secret".to_string());
         let user = create_test_user();
 
-        let token = auth_service1.generate_access_token(&user).unwrap();
+        let token = auth_service1.generate_access_token(&user, None).unwrap();
         let result = auth_service2.validate_token(&token);
 
         assert!(result.is_err());
@@ -205,7 +184,7 @@ mod jwt_tests {
         let auth_service = create_test_auth_service();
         let user = create_test_user();
 
-        let token = auth_service.generate_access_token(&user).unwrap();
+        let token = auth_service.generate_access_token(&user, None).unwrap();
 
         // Manually decode to verify claims
         let token_data = decode::<Claims>(
@@ -226,33 +205,44 @@ mod jwt_tests {
         let mut user = create_test_user();
         user.role = UserRole::Admin;
 
-        let token = auth_service.generate_access_token(&user).unwrap();
+        let token = auth_service.generate_access_token(&user, None).unwrap();
         let token_data = auth_service.validate_token(&token).unwrap();
 
         assert_eq!(token_data.claims.role, UserRole::Admin);
     }
 
     #[test]
-    fn test_refresh_token_has_longer_expiry() {
+    fn test_access_token_has_token_type() {
         let auth_service = create_test_auth_service();
         let user = create_test_user();
 
-        let access_token = auth_service.generate_access_token(&user).unwrap();
-        let refresh_token = auth_service.generate_refresh_token(&user).unwrap();
+        let token = auth_service.generate_access_token(&user, None).unwrap();
+        let token_data = auth_service.validate_token(&token).unwrap();
 
-        let access_data = decode::<Claims>(
-            &access_token,
-            &DecodingKey::from_secret(TEST_SECRET.as_bytes()),
-            &Validation::default(),
-        ).unwrap();
+        assert_eq!(token_data.claims.token_type, TokenType::Access);
+    }
 
-        let refresh_data = decode::<Claims>(
-            &refresh_token,
-            &DecodingKey::from_secret(TEST_SECRET.as_bytes()),
-            &Validation::default(),
+    #[test]
+    fn test_token_without_token_type_rejected() {
+        let auth_service = create_test_auth_service();
+        let user = create_test_user();
+        let now = Utc::now().timestamp();
+
+        // Tokens issued before token types existed, e.g. the old JWT refresh tokens
+        let untyped = encode(
+            &Header::default(),
+            &json!({
+                "sub": user.id.to_string(),
+                "username": user.username,
+                "email": user.email,
+                "role": "viewer",
+                "exp": now + 3600,
+                "iat": now,
+            }),
+            &EncodingKey::from_secret(TEST_SECRET.as_bytes()),
         ).unwrap();
 
-        assert!(refresh_data.claims.exp > access_data.claims.exp);
+        assert!(auth_service.validate_token(&untyped).is_err());
     }
 }
 
@@ -268,7 +258,7 @@ mod auth_service_creation_tests {
         // Service should be created successfully
         // We can't directly test private fields, but we can test it works
         let user = create_test_user();
-        assert!(auth_service.generate_access_token(&user).is_ok());
+        assert!(auth_service.generate_access_token(&user, None).is_ok());
     }
 
     #[test]
@@ -277,6 +267,6 @@ mod auth_service_creation_tests {
         let user = create_test_user();
 
         // Should still work (though not recommended for production)
-        assert!(auth_service.generate_access_token(&user).is_ok());
+        assert!(auth_service.generate_access_token(&user, None).is_ok());
     }
 }
@@ -0,0 +1,161 @@
+use ledger_forge::models::{
+    CreateInvoiceRequest, CreatePaymentRequest, CreateCreditMemoRequest, CreateCreditMemoLineItemRequest,
+    InvoiceStatus, CreditMemoStatus, AutoApplyRequest, AutoApplyRun, AutoApplySource,
+};
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+mod common;
+use common::test_db::TestDb;
+use common::fixtures::{
+    date, dec, create_customer, create_sent_invoice, payment_request, get_invoice, Receivables,
+};
+use common::services::{payment_service, credit_memo_service};
+
+async fn auto_apply(pool: &PgPool, customer_id: Option<Uuid>, prefer_exact_match: bool, preview: bool) -> AutoApplyRun {
+    payment_service().auto_apply(pool, AutoApplyRequest {
+        customer_id,
+        company_id: None,
+        prefer_exact_match,
+        preview,
+    }).await.unwrap()
+}
+
+async fn create_unapplied_payment(pool: &PgPool, number: &str, customer_id: Uuid, payment_date: NaiveDate, amount: &str) -> Uuid {
+    payment_service().create_payment(pool, CreatePaymentRequest {
+        payment_number: Some(number.to_string()),
+        payment_date,
+        ..payment_request(customer_id, amount, &[])
+    }).await.unwrap().id
+}
+
+struct OpenItems {
+    receivables: Receivables,
+    customer: Uuid,
+    /// 100.00 due 2025-06-15
+    oldest: Uuid,
+    /// 250.00 due 2025-07-01
+    middle: Uuid,
+    /// 80.00 due 2025-07-15
+    newest: Uuid,
+    /// 300.00 received 2025-07-01
+    payment: Uuid,
+    /// 80.00 issued 2025-07-05
+    credit_memo: Uuid,
+}
+
+/// A posted invoice issued 2025-06-01
+async fn posted_invoice(pool: &PgPool, receivables: &Receivables, number: &str, customer_id: Uuid, due_date: NaiveDate, amount: &str) -> Uuid {
+    create_sent_invoice(pool, CreateInvoiceRequest {
+        due_date,
+        ..receivables.invoice_request(number, customer_id, amount)
+    }).await
+}
+
+/// A customer with three open invoices, an unapplied payment and an unapplied credit memo
+async fn open_items(pool: &PgPool) -> OpenItems {
+    let receivables = Receivables::create(pool).await;
+
+    let customer = create_customer(pool, "Early Payer Inc").await;
+    let oldest = posted_invoice(pool, &receivables, "INV-AA-1", customer, date(2025, 6, 15), "100.00").await;
+    let middle = posted_invoice(pool, &receivables, "INV-AA-2", customer, date(2025, 7, 1), "250.00").await;
+    let newest = posted_invoice(pool, &receivables, "INV-AA-3", customer, date(2025, 7, 15), "80.00").await;
+
+    let payment = create_unapplied_payment(pool, "PMT-AA-1", customer, date(2025, 7, 1), "300.00").await;
+    let credit_memo = credit_memo_service().create_credit_memo(pool, CreateCreditMemoRequest {
+        credit_memo_number: Some("CM-AA-1".to_string()),
+        customer_id: customer,
+        credit_memo_date: date(2025, 7, 5),
+        invoice_id: None,
+        memo: None,
+        company_id: None,
+        ar_account_id: Some(receivables.ar_account),
+        line_items: vec![CreateCreditMemoLineItemRequest {
+            line_number: 1,
+            item_description: "Service credit".to_string(),
+            quantity: Decimal::ONE,
+            unit_price: dec("80.00"),
+            tax_code: None,
+            revenue_account_id: receivables.revenue_account,
+        }],
+        applications: Vec::new(),
+    }).await.unwrap().credit_memo.id;
+
+    OpenItems { receivables, customer, oldest, middle, newest, payment, credit_memo }
+}
+
+#[tokio::test]
+async fn test_preview_plans_oldest_invoice_and_oldest_money_first() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let s = open_items(pool).await;
+
+    let preview = auto_apply(pool, Some(s.customer), false, true).await;
+    assert!(preview.preview);
+    assert_eq!(preview.total_applied, dec("380.00"));
+    let planned: Vec<(Uuid, Uuid, Decimal)> = preview.customers[0].applications.iter()
+        .map(|a| (a.source_id, a.invoice_id, a.amount))
+        .collect();
+    assert_eq!(planned, vec![
+        (s.payment, s.oldest, dec("100.00")),
+        (s.payment, s.middle, dec("200.00")),
+        (s.credit_memo, s.middle, dec("50.00")),
+        (s.credit_memo, s.newest, dec("30.00")),
+    ]);
+
+    // A preview changes nothing
+    assert_eq!(get_invoice(pool, s.oldest).await.balance, dec("100.00"));
+}
+
+#[tokio::test]
+async fn test_exact_match_is_settled_before_oldest_first() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let s = open_items(pool).await;
+
+    let run = auto_apply(pool, Some(s.customer), true, false).await;
+    let applications = &run.customers[0].applications;
+    assert_eq!(applications.len(), 3);
+    let exact = applications.iter().find(|a| a.exact_match).unwrap();
+    assert_eq!(exact.source_type, AutoApplySource::CreditMemo);
+    assert_eq!(exact.invoice_id, s.newest);
+}
+
+#[tokio::test]
+async fn test_auto_apply_updates_invoices_and_uses_up_the_money() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let s = open_items(pool).await;
+
+    auto_apply(pool, Some(s.customer), true, false).await;
+    assert_eq!(get_invoice(pool, s.newest).await.status, InvoiceStatus::Paid);
+    let middle = get_invoice(pool, s.middle).await;
+    assert_eq!(middle.balance, dec("50.00"));
+    assert_eq!(middle.status, InvoiceStatus::Partial);
+
+    let applied_payment = payment_service().get_payment_by_id(pool, s.payment).await.unwrap().unwrap();
+    assert_eq!(applied_payment.unapplied_amount, Some(Decimal::ZERO));
+    let applied_credit = credit_memo_service()
+        .get_credit_memo(pool, s.credit_memo).await.unwrap().credit_memo;
+    assert_eq!(applied_credit.status, CreditMemoStatus::Applied);
+}
+
+#[tokio::test]
+async fn test_batch_covers_every_customer_with_money_left_to_apply() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let s = open_items(pool).await;
+    auto_apply(pool, Some(s.customer), true, false).await;
+
+    let other = create_customer(pool, "Second Customer").await;
+    let other_invoice = posted_invoice(pool, &s.receivables, "INV-AA-4", other, date(2025, 7, 1), "50.00").await;
+    create_unapplied_payment(pool, "PMT-AA-2", other, date(2025, 7, 2), "20.00").await;
+
+    let batch = auto_apply(pool, None, true, false).await;
+    assert_eq!(batch.customers.len(), 1);
+    assert_eq!(batch.customers[0].customer_id, other);
+    assert_eq!(batch.total_applied, dec("20.00"));
+    assert_eq!(get_invoice(pool, other_invoice).await.balance, dec("30.00"));
+}
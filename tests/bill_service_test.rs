@@ -52,6 +52,7 @@ async fn test_create_bill_with_line_items() {
         due_date: NaiveDate::from_ymd_opt(2024, 10, 31).unwrap(),
         memo: Some("Office supplies purchase".to_string()),
         company_id: None,
+        ap_account_id: None,
         line_items: vec![
             CreateBillLineItemRequest {
                 line_number: 1,
@@ -60,6 +61,10 @@ async fn test_create_bill_with_line_items() {
                 expense_account_id: expense_account.id,
                 billable: Some(false),
                 customer_id: None,
+                item_id: None,
+                quantity: None,
+                tax_code: None,
+                dimension_value_ids: Vec::new(),
             },
             CreateBillLineItemRequest {
                 line_number: 2,
@@ -68,6 +73,10 @@ async fn test_create_bill_with_line_items() {
                 expense_account_id: expense_account.id,
                 billable: Some(false),
                 customer_id: None,
+                item_id: None,
+                quantity: None,
+                tax_code: None,
+                dimension_value_ids: Vec::new(),
             }
         ],
     };
@@ -121,6 +130,7 @@ async fn test_get_bill_by_id() {
         due_date: NaiveDate::from_ymd_opt(2024, 11, 5).unwrap(),
         memo: None,
         company_id: None,
+        ap_account_id: None,
         line_items: vec![
             CreateBillLineItemRequest {
                 line_number: 1,
@@ -129,6 +139,10 @@ async fn test_get_bill_by_id() {
                 expense_account_id: expense_account.id,
                 billable: Some(false),
                 customer_id: None,
+                item_id: None,
+                quantity: None,
+                tax_code: None,
+                dimension_value_ids: Vec::new(),
             }
         ],
     };
@@ -184,6 +198,7 @@ async fn test_list_bills() {
             due_date: NaiveDate::from_ymd_opt(2024, 10, (i + 30) as u32).unwrap(),
             memo: None,
             company_id: None,
+            ap_account_id: None,
             line_items: vec![
                 CreateBillLineItemRequest {
                     line_number: 1,
@@ -192,6 +207,10 @@ async fn test_list_bills() {
                     expense_account_id: expense_account.id,
                     billable: Some(false),
                     customer_id: None,
+                    item_id: None,
+                    quantity: None,
+                    tax_code: None,
+                    dimension_value_ids: Vec::new(),
                 }
             ],
         };
@@ -246,6 +265,7 @@ async fn test_update_bill_status() {
         due_date: NaiveDate::from_ymd_opt(2024, 11, 10).unwrap(),
         memo: None,
         company_id: None,
+        ap_account_id: None,
         line_items: vec![
             CreateBillLineItemRequest {
                 line_number: 1,
@@ -254,6 +274,10 @@ async fn test_update_bill_status() {
                 expense_account_id: expense_account.id,
                 billable: Some(true),
                 customer_id: None,
+                item_id: None,
+                quantity: None,
+                tax_code: None,
+                dimension_value_ids: Vec::new(),
             }
         ],
     };
@@ -306,6 +330,7 @@ async fn test_delete_bill() {
         due_date: NaiveDate::from_ymd_opt(2024, 11, 15).unwrap(),
         memo: None,
         company_id: None,
+        ap_account_id: None,
         line_items: vec![
             CreateBillLineItemRequest {
                 line_number: 1,
@@ -314,6 +339,10 @@ async fn test_delete_bill() {
                 expense_account_id: expense_account.id,
                 billable: Some(false),
                 customer_id: None,
+                item_id: None,
+                quantity: None,
+                tax_code: None,
+                dimension_value_ids: Vec::new(),
             }
         ],
     };
@@ -367,6 +396,7 @@ for i in 1..=3 {
         due_date: NaiveDate::from_ymd_opt(2024, 11, i as u32).unwrap(),
         memo: None,
         company_id: None,
+        ap_account_id: None,
         line_items: vec![
             CreateBillLineItemRequest {
                 line_number: 1,
@@ -375,6 +405,10 @@ for i in 1..=3 {
                 expense_account_id: expense_account.id,
                 billable: Some(false),
                 customer_id: None,
+                item_id: None,
+                quantity: None,
+                tax_code: None,
+                dimension_value_ids: Vec::new(),
             }
         ],
     };
@@ -428,6 +462,7 @@ let bill_req = CreateBillRequest {
     due_date: NaiveDate::from_ymd_opt(2024, 9, 1).unwrap(), // Past due date
     memo: Some("Overdue insurance bill".to_string()),
     company_id: None,
+    ap_account_id: None,
     line_items: vec![
         CreateBillLineItemRequest {
             line_number: 1,
@@ -436,6 +471,10 @@ let bill_req = CreateBillRequest {
             expense_account_id: expense_account.id,
             billable: Some(false),
             customer_id: None,
+            item_id: None,
+            quantity: None,
+            tax_code: None,
+            dimension_value_ids: Vec::new(),
         }
     ],
 };
@@ -491,6 +530,7 @@ let bill_req = CreateBillRequest {
     due_date: NaiveDate::from_ymd_opt(2024, 11, 20).unwrap(),
     memo: None,
     company_id: None,
+    ap_account_id: None,
     line_items: vec![
         CreateBillLineItemRequest {
             line_number: 1,
@@ -499,6 +539,10 @@ let bill_req = CreateBillRequest {
             expense_account_id: expense_account.id,
             billable: Some(false),
             customer_id: None,
+            item_id: None,
+            quantity: None,
+            tax_code: None,
+            dimension_value_ids: Vec::new(),
         },
         CreateBillLineItemRequest {
             line_number: 2,
@@ -507,6 +551,10 @@ let bill_req = CreateBillRequest {
             expense_account_id: expense_account.id,
             billable: Some(false),
             customer_id: None,
+            item_id: None,
+            quantity: None,
+            tax_code: None,
+            dimension_value_ids: Vec::new(),
         },
         CreateBillLineItemRequest {
             line_number: 3,
@@ -515,6 +563,10 @@ let bill_req = CreateBillRequest {
             expense_account_id: expense_account.id,
             billable: Some(false),
             customer_id: None,
+            item_id: None,
+            quantity: None,
+            tax_code: None,
+            dimension_value_ids: Vec::new(),
         }
     ],
 };
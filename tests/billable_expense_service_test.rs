@@ -0,0 +1,181 @@
+use ledger_forge::models::{
+    AccountType, CreateBillRequest, CreateBillLineItemRequest, BillExpensesRequest, InvoiceStatus,
+};
+use ledger_forge::utils::AppError;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+mod common;
+use common::test_db::TestDb;
+use common::fixtures::{date, dec, create_account, create_customer, create_vendor};
+use common::services::{billable_expense_service, bill_service, invoice_service};
+
+fn bill_line(line_number: i32, description: Option<&str>, amount: &str, expense_account_id: Uuid, customer_id: Option<Uuid>) -> CreateBillLineItemRequest {
+    CreateBillLineItemRequest {
+        line_number,
+        description: description.map(str::to_string),
+        amount: dec(amount),
+        expense_account_id,
+        billable: Some(customer_id.is_some()),
+        customer_id,
+        item_id: None,
+        quantity: None,
+        tax_code: None,
+        dimension_value_ids: Vec::new(),
+    }
+}
+
+fn bill_request(line_ids: Vec<Uuid>, invoice_id: Option<Uuid>, markup_percent: Option<&str>, revenue_account_id: Option<Uuid>) -> BillExpensesRequest {
+    BillExpensesRequest {
+        bill_line_item_ids: line_ids,
+        invoice_id,
+        markup_percent: markup_percent.map(dec),
+        revenue_account_id,
+        invoice_number: Some("INV-BE-1".to_string()),
+        invoice_date: Some(date(2025, 7, 1)),
+        due_date: Some(date(2025, 7, 31)),
+        ar_account_id: None,
+    }
+}
+
+struct Expenses {
+    recharge_account: Uuid,
+    travel_account: Uuid,
+    customer: Uuid,
+    /// 100.00, billable to the customer
+    flights: Uuid,
+    /// 40.00 with no description, billable to the customer
+    hotel: Uuid,
+    /// 25.00, not billable
+    internal: Uuid,
+}
+
+/// A travel bill with two lines billable to a customer and one kept in-house
+async fn expenses(pool: &PgPool) -> Expenses {
+    let ap_account = create_account(pool, "2000", "Accounts Payable", AccountType::Liability).await;
+    let recharge_account = create_account(pool, "4500", "Reimbursed Expenses", AccountType::Revenue).await;
+    let travel_account = create_account(pool, "6200", "Travel", AccountType::Expense).await;
+    let customer = create_customer(pool, "Site Client Ltd").await;
+    let vendor = create_vendor(pool, "Airline Co").await;
+
+    let bill = bill_service().create_bill(pool, CreateBillRequest {
+        bill_number: Some("B-BE-1".to_string()),
+        vendor_id: vendor,
+        bill_date: date(2025, 6, 15),
+        due_date: date(2025, 7, 15),
+        memo: None,
+        company_id: None,
+        ap_account_id: Some(ap_account),
+        line_items: vec![
+            bill_line(1, Some("Flights to site"), "100.00", travel_account, Some(customer)),
+            bill_line(2, None, "40.00", travel_account, Some(customer)),
+            bill_line(3, Some("Office travel"), "25.00", travel_account, None),
+        ],
+    }).await.unwrap();
+    let lines = bill_service().get_bill_by_id(pool, bill.id).await.unwrap().unwrap().line_items;
+
+    Expenses {
+        recharge_account,
+        travel_account,
+        customer,
+        flights: lines[0].id,
+        hotel: lines[1].id,
+        internal: lines[2].id,
+    }
+}
+
+/// Bill the flights on a new draft invoice with a 10% markup, returning the invoice
+async fn bill_flights(pool: &PgPool, e: &Expenses) -> Uuid {
+    billable_expense_service().bill_expenses(pool, e.customer, bill_request(vec![e.flights], None, Some("10"), None))
+        .await
+        .unwrap()
+        .invoice
+        .invoice
+        .id
+}
+
+#[tokio::test]
+async fn test_unbilled_expenses_list_lines_billable_to_the_customer() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let e = expenses(pool).await;
+
+    let unbilled = billable_expense_service().list_unbilled_expenses(pool, e.customer).await.unwrap();
+    assert_eq!(unbilled.iter().map(|x| x.bill_line_item_id).collect::<Vec<_>>(), vec![e.flights, e.hotel]);
+    assert_eq!(unbilled[0].vendor_name, "Airline Co");
+}
+
+#[tokio::test]
+async fn test_expenses_go_on_a_new_draft_with_markup() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let e = expenses(pool).await;
+
+    let billed = billable_expense_service().bill_expenses(pool, e.customer, bill_request(vec![e.flights], None, Some("10"), None)).await.unwrap();
+    assert_eq!(billed.expense_total, dec("100.00"));
+    assert_eq!(billed.markup_amount, dec("10.00"));
+    assert_eq!(billed.invoice.invoice.status, InvoiceStatus::Draft);
+    assert_eq!(billed.invoice.invoice.total_amount, dec("110.00"));
+    // The line keeps its description and, without a revenue account, its expense account
+    assert_eq!(billed.invoice.line_items[0].item_description, "Flights to site");
+    assert_eq!(billed.invoice.line_items[0].revenue_account_id, e.travel_account);
+}
+
+#[tokio::test]
+async fn test_billed_lines_cannot_be_billed_again() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let e = expenses(pool).await;
+    let invoice_id = bill_flights(pool, &e).await;
+
+    assert!(matches!(
+        billable_expense_service().bill_expenses(pool, e.customer, bill_request(vec![e.flights], Some(invoice_id), None, None)).await,
+        Err(AppError::ValidationError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_lines_not_billable_to_the_customer_are_rejected() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let e = expenses(pool).await;
+
+    assert!(matches!(
+        billable_expense_service().bill_expenses(pool, e.customer, bill_request(vec![e.internal], None, None, None)).await,
+        Err(AppError::ValidationError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_more_expenses_are_added_to_an_existing_draft_at_cost() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let e = expenses(pool).await;
+    let invoice_id = bill_flights(pool, &e).await;
+
+    let billed = billable_expense_service()
+        .bill_expenses(pool, e.customer, bill_request(vec![e.hotel], Some(invoice_id), None, Some(e.recharge_account)))
+        .await
+        .unwrap();
+    assert_eq!(billed.invoice.invoice.id, invoice_id);
+    assert_eq!(billed.invoice.invoice.total_amount, dec("150.00"));
+    assert_eq!(billed.invoice.invoice.balance, dec("150.00"));
+    assert_eq!(billed.invoice.line_items.len(), 2);
+    let added = &billed.invoice.line_items[1];
+    assert_eq!(added.line_number, 2);
+    assert_eq!(added.item_description, "Airline Co - bill B-BE-1");
+    assert_eq!(added.revenue_account_id, e.recharge_account);
+    assert!(billable_expense_service().list_unbilled_expenses(pool, e.customer).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_voiding_the_invoice_frees_its_expenses() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let e = expenses(pool).await;
+    let invoice_id = bill_flights(pool, &e).await;
+    assert_eq!(billable_expense_service().list_unbilled_expenses(pool, e.customer).await.unwrap().len(), 1);
+
+    invoice_service().update_invoice_status(pool, invoice_id, InvoiceStatus::Void).await.unwrap();
+    assert_eq!(billable_expense_service().list_unbilled_expenses(pool, e.customer).await.unwrap().len(), 2);
+}
@@ -44,6 +44,7 @@ async fn test_account_balance_caching() {
         transaction_date: Utc::now().date_naive(),
         description: Some("Test transaction".to_string()),
         reference_number: Some("TXN001".to_string()),
+        entry_number: None,
         contact_id: None,
         company_id: None,
         journal_type: Some(JournalType::General),
@@ -53,12 +54,14 @@ async fn test_account_balance_caching() {
                 description: Some("Cash received".to_string()),
                 debit_amount: Some(Decimal::new(10000, 2)), // $100.00
                 credit_amount: None,
+                dimension_value_ids: Vec::new(),
             },
             CreateLineItemRequest {
                 account_id: revenue_account.id,
                 description: Some("Sales revenue".to_string()),
                 debit_amount: None,
                 credit_amount: Some(Decimal::new(10000, 2)), // $100.00
+                dimension_value_ids: Vec::new(),
             },
         ],
     };
@@ -125,6 +128,7 @@ async fn test_cache_invalidation_on_transaction_post() {
         transaction_date: Utc::now().date_naive(),
         description: Some("Test transaction".to_string()),
         reference_number: Some("TXN002".to_string()),
+        entry_number: None,
         contact_id: None,
         company_id: None,
         journal_type: Some(JournalType::General),
@@ -134,12 +138,14 @@ async fn test_cache_invalidation_on_transaction_post() {
                 description: Some("Cash received".to_string()),
                 debit_amount: Some(Decimal::new(5000, 2)), // $50.00
                 credit_amount: None,
+                dimension_value_ids: Vec::new(),
             },
             CreateLineItemRequest {
                 account_id: revenue_account.id,
                 description: Some("Sales revenue".to_string()),
                 debit_amount: None,
                 credit_amount: Some(Decimal::new(5000, 2)), // $50.00
+                dimension_value_ids: Vec::new(),
             },
         ],
     };
@@ -1,6 +1,17 @@
+use chrono::NaiveDate;
 use fake::faker::internet::en::*;
 use fake::faker::name::en::*;
 use fake::Fake;
+use ledger_forge::models::{
+    AccountType, ContactType, CreateAccountRequest, CreateContactRequest, CreateInvoiceLineItemRequest,
+    CreateInvoiceRequest, CreatePaymentRequest, Invoice, InvoiceStatus, PaymentApplicationRequest,
+};
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use super::services::{account_service, contact_service, invoice_service, transaction_service};
 
 /// Generate test user data
 pub struct TestUser {
@@ -74,3 +85,143 @@ pub mod emails {
     pub const INVALID: &str = "not-an-email";
     pub const EMPTY: &str = "";
 }
+
+pub fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+pub fn dec(s: &str) -> Decimal {
+    Decimal::from_str(s).unwrap()
+}
+
+/// Create an account outside any company
+pub async fn create_account(pool: &PgPool, code: &str, name: &str, account_type: AccountType) -> Uuid {
+    account_service()
+        .create_account(pool, CreateAccountRequest {
+            code: code.to_string(),
+            name: name.to_string(),
+            account_type,
+            parent_account_id: None,
+            company_id: None,
+        })
+        .await
+        .unwrap()
+        .id
+}
+
+/// A contact with only its type and name filled in
+pub fn contact_request(contact_type: ContactType, name: &str) -> CreateContactRequest {
+    CreateContactRequest {
+        contact_type,
+        name: name.to_string(),
+        email: None,
+        phone: None,
+        billing_address: None,
+        shipping_address: None,
+        company_id: None,
+    }
+}
+
+pub async fn create_contact(pool: &PgPool, req: CreateContactRequest) -> Uuid {
+    contact_service()
+        .create_contact(pool, req)
+        .await
+        .unwrap()
+        .id
+}
+
+pub async fn create_customer(pool: &PgPool, name: &str) -> Uuid {
+    create_contact(pool, contact_request(ContactType::Customer, name)).await
+}
+
+pub async fn create_vendor(pool: &PgPool, name: &str) -> Uuid {
+    create_contact(pool, contact_request(ContactType::Vendor, name)).await
+}
+
+/// A one-line invoice for services, dated 2025-06-01 and due 2025-07-01, not posted to the ledger
+pub fn invoice_request(number: &str, customer_id: Uuid, revenue_account_id: Uuid, amount: &str) -> CreateInvoiceRequest {
+    CreateInvoiceRequest {
+        invoice_number: Some(number.to_string()),
+        customer_id,
+        invoice_date: date(2025, 6, 1),
+        due_date: date(2025, 7, 1),
+        ship_date: None,
+        customer_memo: None,
+        billing_address: None,
+        shipping_address: None,
+        company_id: None,
+        ar_account_id: None,
+        line_items: vec![CreateInvoiceLineItemRequest {
+            line_number: 1,
+            item_description: "Services".to_string(),
+            quantity: Decimal::ONE,
+            unit_price: dec(amount),
+            discount_percent: None,
+            tax_code: None,
+            revenue_account_id,
+            item_id: None,
+            dimension_value_ids: Vec::new(),
+        }],
+    }
+}
+
+/// Create an invoice and send it, which posts it when it has an AR account
+pub async fn create_sent_invoice(pool: &PgPool, req: CreateInvoiceRequest) -> Uuid {
+    let service = invoice_service();
+    let invoice = service.create_invoice(pool, req).await.unwrap();
+    service.update_invoice_status(pool, invoice.invoice.id, InvoiceStatus::Sent).await.unwrap();
+    invoice.invoice.id
+}
+
+/// A cheque dated 2025-07-01, applied to invoices as listed; anything left over stays unapplied
+pub fn payment_request(customer_id: Uuid, amount: &str, applications: &[(Uuid, &str)]) -> CreatePaymentRequest {
+    CreatePaymentRequest {
+        payment_number: None,
+        customer_id,
+        payment_date: date(2025, 7, 1),
+        amount: dec(amount),
+        payment_method: "Check".to_string(),
+        reference_number: None,
+        deposit_to_account_id: None,
+        ar_account_id: None,
+        memo: None,
+        company_id: None,
+        applications: applications
+            .iter()
+            .map(|(invoice_id, amount)| PaymentApplicationRequest { invoice_id: *invoice_id, amount_applied: dec(amount) })
+            .collect(),
+    }
+}
+
+/// Receivable and revenue accounts, 1200 and 4000, outside any company
+pub struct Receivables {
+    pub ar_account: Uuid,
+    pub revenue_account: Uuid,
+}
+
+impl Receivables {
+    pub async fn create(pool: &PgPool) -> Self {
+        Self {
+            ar_account: create_account(pool, "1200", "Accounts Receivable", AccountType::Asset).await,
+            revenue_account: create_account(pool, "4000", "Revenue", AccountType::Revenue).await,
+        }
+    }
+
+    /// Like `invoice_request`, but posted to these accounts once sent
+    pub fn invoice_request(&self, number: &str, customer_id: Uuid, amount: &str) -> CreateInvoiceRequest {
+        CreateInvoiceRequest {
+            ar_account_id: Some(self.ar_account),
+            ..invoice_request(number, customer_id, self.revenue_account, amount)
+        }
+    }
+}
+
+/// An account's balance from the ledger
+pub async fn balance(pool: &PgPool, account_id: Uuid) -> Decimal {
+    transaction_service().get_account_balance(pool, account_id).await.unwrap()
+}
+
+/// An invoice header as it is stored now
+pub async fn get_invoice(pool: &PgPool, invoice_id: Uuid) -> Invoice {
+    invoice_service().get_invoice(pool, invoice_id).await.unwrap().unwrap().invoice
+}
@@ -1,7 +1,12 @@
+// Each test binary uses only some of these helpers
+#![allow(dead_code)]
+
 pub mod test_db;
 pub mod fixtures;
 pub mod assertions;
 pub mod cache_test;
+pub mod smtp;
+pub mod services;
 
 pub use test_db::*;
 pub use fixtures::*;
@@ -0,0 +1,85 @@
+//! Services on the shared Redis cache, outside any company. Call `for_company` on one to
+//! work with a company's records.
+
+use ledger_forge::services::{
+    AccountService, BillService, BillableExpenseService, CacheService, CompanyService, ContactService,
+    CreditMemoService, DimensionService, EstimateService, FinanceChargeService, InventoryService, InvoiceService,
+    PaymentService, PurchaseOrderService, ReportingService, StatementService, TaxService, TransactionService,
+    VendorCreditService, WriteOffService,
+};
+
+pub fn account_service() -> AccountService {
+    AccountService::new_with_cache(CacheService::default())
+}
+
+pub fn bill_service() -> BillService {
+    BillService::new_with_cache(CacheService::default())
+}
+
+pub fn billable_expense_service() -> BillableExpenseService {
+    BillableExpenseService::new_with_cache(CacheService::default())
+}
+
+pub fn company_service() -> CompanyService {
+    CompanyService::new_with_cache(CacheService::default())
+}
+
+pub fn contact_service() -> ContactService {
+    ContactService::new_with_cache(CacheService::default())
+}
+
+pub fn credit_memo_service() -> CreditMemoService {
+    CreditMemoService::new_with_cache(CacheService::default())
+}
+
+pub fn dimension_service() -> DimensionService {
+    DimensionService::new_with_cache(CacheService::default())
+}
+
+pub fn estimate_service() -> EstimateService {
+    EstimateService::new_with_cache(CacheService::default())
+}
+
+pub fn finance_charge_service() -> FinanceChargeService {
+    FinanceChargeService::new_with_cache(CacheService::default())
+}
+
+pub fn inventory_service() -> InventoryService {
+    InventoryService::new_with_cache(CacheService::default())
+}
+
+pub fn invoice_service() -> InvoiceService {
+    InvoiceService::new_with_cache(CacheService::default())
+}
+
+pub fn payment_service() -> PaymentService {
+    PaymentService::new_with_cache(CacheService::default())
+}
+
+pub fn purchase_order_service() -> PurchaseOrderService {
+    PurchaseOrderService::new_with_cache(CacheService::default())
+}
+
+pub fn reporting_service() -> ReportingService {
+    ReportingService::new_with_cache(CacheService::default())
+}
+
+pub fn statement_service() -> StatementService {
+    StatementService::new_with_cache(CacheService::default())
+}
+
+pub fn tax_service() -> TaxService {
+    TaxService::new_with_cache(CacheService::default())
+}
+
+pub fn transaction_service() -> TransactionService {
+    TransactionService::new_with_cache(CacheService::default())
+}
+
+pub fn vendor_credit_service() -> VendorCreditService {
+    VendorCreditService::new_with_cache(CacheService::default())
+}
+
+pub fn write_off_service() -> WriteOffService {
+    WriteOffService::new_with_cache(CacheService::default())
+}
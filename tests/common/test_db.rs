@@ -30,6 +30,51 @@ pub async fn cleanup_test_db(pool: &PgPool) {
     // Truncate all tables in reverse order of dependencies
     let _ = sqlx::query(
         "TRUNCATE
+            mfa_recovery_codes,
+            totp_credentials,
+            mfa_required_roles,
+            password_reset_tokens,
+            refresh_tokens,
+            company_members,
+            transaction_line_dimensions,
+            invoice_line_dimensions,
+            bill_line_dimensions,
+            dimension_requirements,
+            dimension_values,
+            dimension_types,
+            numbering_sequence_counters,
+            numbering_sequences,
+            write_off_recoveries,
+            invoice_write_offs,
+            finance_charges,
+            finance_charge_settings,
+            invoice_reminders,
+            dunning_levels,
+            email_deliveries,
+            email_templates,
+            invoice_templates,
+            vendor_credit_applications,
+            vendor_credit_line_taxes,
+            vendor_credit_line_items,
+            vendor_credits,
+            purchase_order_bills,
+            purchase_order_line_items,
+            purchase_orders,
+            estimate_invoices,
+            estimate_line_items,
+            estimates,
+            customer_refunds,
+            credit_memo_applications,
+            credit_memo_line_taxes,
+            credit_memo_line_items,
+            credit_memos,
+            tax_returns,
+            bill_line_taxes,
+            invoice_line_taxes,
+            tax_rates,
+            tax_codes,
+            inventory_movements,
+            inventory_cost_layers,
             bill_payment_applications,
             bill_payments,
             payment_applications,
@@ -0,0 +1,366 @@
+use ledger_forge::models::{
+    CreateAccountRequest, AccountType, ContactType, CreateInvoiceRequest, CreateCompanyRequest,
+    UpdateCompanyRequest, AddCompanyMemberRequest, CreateUserRequest, UserRole, CreatePaymentRequest,
+    CreateTaxCodeRequest, CreateTaxRateRequest,
+};
+use ledger_forge::services::{CompanyService, AuthService};
+use ledger_forge::utils::AppError;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+mod common;
+use common::test_db::TestDb;
+use common::fixtures::{date, create_account, contact_request, invoice_request, payment_request};
+use common::services::{company_service, account_service, contact_service, invoice_service, payment_service, tax_service};
+
+async fn create_user(pool: &PgPool, username: &str) -> Uuid {
+    AuthService::new("test-secret-key".to_string()).register(pool, CreateUserRequest {
+        username: username.to_string(),
+        email: format!("{}@example.com", username),
+        password: "SecurePass123".to_string(),
+        role: UserRole::Accountant,
+    }).await.unwrap().id
+}
+
+async fn create_company(pool: &PgPool, user_id: Uuid, name: &str) -> Uuid {
+    company_service().create_company(pool, user_id, CreateCompanyRequest {
+        name: name.to_string(),
+        address: None,
+    }).await.unwrap().id
+}
+
+/// A revenue account in the company's chart
+async fn company_account(pool: &PgPool, company_id: Uuid, code: &str) -> Uuid {
+    account_service().for_company(Some(company_id)).create_account(pool, CreateAccountRequest {
+        code: code.to_string(),
+        name: "Revenue".to_string(),
+        account_type: AccountType::Revenue,
+        parent_account_id: None,
+        company_id: None,
+    }).await.unwrap().id
+}
+
+async fn company_customer(pool: &PgPool, company_id: Uuid, name: &str) -> Uuid {
+    contact_service().for_company(Some(company_id)).create_contact(pool, contact_request(ContactType::Customer, name)).await.unwrap().id
+}
+
+/// An owner and the company they created, which has no other members
+async fn owned_company(pool: &PgPool) -> (Uuid, Uuid) {
+    let owner = create_user(pool, "owner").await;
+    let company = create_company(pool, owner, "Maple Consulting Inc.").await;
+    (owner, company)
+}
+
+#[tokio::test]
+async fn test_creator_becomes_the_first_member() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (owner, company) = owned_company(pool).await;
+    let outsider = create_user(pool, "outsider").await;
+
+    // And starts there after logging in
+    assert!(CompanyService::is_member(pool, company, owner).await.unwrap());
+    assert_eq!(CompanyService::default_company(pool, owner).await.unwrap(), Some(company));
+    assert_eq!(CompanyService::default_company(pool, outsider).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_non_members_cannot_see_the_company() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (_, company) = owned_company(pool).await;
+    let outsider = create_user(pool, "outsider").await;
+
+    assert!(matches!(
+        company_service().get_company(pool, outsider, company).await,
+        Err(AppError::NotFound(_))
+    ));
+    assert!(company_service().list_companies(pool, outsider).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_member_can_rename_the_company() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (owner, company) = owned_company(pool).await;
+
+    let renamed = company_service().update_company(pool, owner, company, UpdateCompanyRequest {
+        name: Some("Maple Consulting Ltd.".to_string()),
+        address: None,
+    }).await.unwrap();
+    assert_eq!(renamed.name, "Maple Consulting Ltd.");
+}
+
+#[tokio::test]
+async fn test_added_member_shares_the_company_once() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (owner, company) = owned_company(pool).await;
+    let bookkeeper = create_user(pool, "bookkeeper").await;
+
+    let member = company_service().add_member(pool, owner, company, AddCompanyMemberRequest {
+        user_id: bookkeeper,
+    }).await.unwrap();
+    assert_eq!(member.username, "bookkeeper");
+    assert!(matches!(
+        company_service().add_member(pool, owner, company, AddCompanyMemberRequest { user_id: bookkeeper }).await,
+        Err(AppError::Conflict(_))
+    ));
+    assert_eq!(company_service().list_members(pool, bookkeeper, company).await.unwrap().len(), 2);
+    assert_eq!(company_service().list_companies(pool, bookkeeper).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_last_member_cannot_be_removed() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (owner, company) = owned_company(pool).await;
+    let bookkeeper = create_user(pool, "bookkeeper").await;
+    company_service().add_member(pool, owner, company, AddCompanyMemberRequest { user_id: bookkeeper }).await.unwrap();
+
+    company_service().remove_member(pool, bookkeeper, company, owner).await.unwrap();
+    assert!(!CompanyService::is_member(pool, company, owner).await.unwrap());
+    assert!(matches!(
+        company_service().remove_member(pool, bookkeeper, company, bookkeeper).await,
+        Err(AppError::ValidationError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_named_company_must_be_the_active_one() {
+    let company = Uuid::new_v4();
+
+    assert!(CompanyService::record_company(Some(company), None).is_ok());
+    assert!(matches!(
+        CompanyService::record_company(None, Some(company)),
+        Err(AppError::ValidationError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_only_companies_without_records_can_be_deleted() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (owner, company) = owned_company(pool).await;
+
+    company_account(pool, company, "4000").await;
+    assert!(matches!(
+        company_service().delete_company(pool, owner, company).await,
+        Err(AppError::Conflict(_))
+    ));
+
+    let empty = create_company(pool, owner, "Shelf Co").await;
+    company_service().delete_company(pool, owner, empty).await.unwrap();
+    assert_eq!(company_service().list_companies(pool, owner).await.unwrap().len(), 1);
+}
+
+struct TwoBooks {
+    north: Uuid,
+    south: Uuid,
+    north_revenue: Uuid,
+    south_revenue: Uuid,
+    north_customer: Uuid,
+    south_customer: Uuid,
+    /// INV-1 in the north's books
+    north_invoice: Uuid,
+}
+
+/// Two companies of one user, each with a revenue account 4000, a customer and an invoice INV-1
+async fn two_books(pool: &PgPool) -> TwoBooks {
+    let user = create_user(pool, "accountant").await;
+    let north = create_company(pool, user, "North Ltd").await;
+    let south = create_company(pool, user, "South Ltd").await;
+
+    let north_revenue = company_account(pool, north, "4000").await;
+    let south_revenue = company_account(pool, south, "4000").await;
+    let north_customer = company_customer(pool, north, "Northern Client").await;
+    let south_customer = company_customer(pool, south, "Southern Client").await;
+
+    let north_invoice = invoice_service().for_company(Some(north))
+        .create_invoice(pool, invoice_request("INV-1", north_customer, north_revenue, "100.00"))
+        .await.unwrap().invoice.id;
+    invoice_service().for_company(Some(south))
+        .create_invoice(pool, invoice_request("INV-1", south_customer, south_revenue, "100.00"))
+        .await.unwrap();
+
+    TwoBooks { north, south, north_revenue, south_revenue, north_customer, south_customer, north_invoice }
+}
+
+#[tokio::test]
+async fn test_codes_and_numbers_are_unique_per_company() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let books = two_books(pool).await;
+
+    // Both companies already use account 4000 and invoice INV-1
+    assert!(account_service().for_company(Some(books.north)).create_account(pool, CreateAccountRequest {
+        code: "4000".to_string(),
+        name: "Duplicate".to_string(),
+        account_type: AccountType::Revenue,
+        parent_account_id: None,
+        company_id: None,
+    }).await.is_err());
+    assert!(matches!(
+        invoice_service().for_company(Some(books.north))
+            .create_invoice(pool, invoice_request("INV-1", books.north_customer, books.north_revenue, "100.00"))
+            .await,
+        Err(AppError::Conflict(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_other_company_records_are_not_found() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let books = two_books(pool).await;
+
+    assert!(matches!(
+        account_service().for_company(Some(books.south)).get_account_by_id(pool, books.north_revenue).await,
+        Err(AppError::NotFound(_))
+    ));
+    assert!(matches!(
+        contact_service().for_company(Some(books.south)).get_contact_by_id(pool, books.north_customer).await,
+        Err(AppError::NotFound(_))
+    ));
+    assert!(invoice_service().for_company(Some(books.south)).get_invoice(pool, books.north_invoice).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_other_company_records_cannot_be_referenced() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let books = two_books(pool).await;
+
+    let south_invoices = invoice_service().for_company(Some(books.south));
+    assert!(matches!(
+        south_invoices.create_invoice(pool, invoice_request("INV-2", books.north_customer, books.south_revenue, "100.00")).await,
+        Err(AppError::NotFound(_))
+    ));
+    assert!(matches!(
+        south_invoices.create_invoice(pool, invoice_request("INV-2", books.south_customer, books.north_revenue, "100.00")).await,
+        Err(AppError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_records_cannot_be_filed_under_another_company() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let books = two_books(pool).await;
+
+    let request = CreateInvoiceRequest {
+        company_id: Some(books.south),
+        ..invoice_request("INV-3", books.north_customer, books.north_revenue, "100.00")
+    };
+    assert!(matches!(
+        invoice_service().for_company(Some(books.north)).create_invoice(pool, request).await,
+        Err(AppError::ValidationError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_lists_only_show_the_active_company_records() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let books = two_books(pool).await;
+
+    let north_accounts = account_service().for_company(Some(books.north)).list_accounts(pool, None, None, false).await.unwrap();
+    assert_eq!(north_accounts.iter().map(|a| a.id).collect::<Vec<_>>(), vec![books.north_revenue]);
+    let south_contacts = contact_service().for_company(Some(books.south)).list_contacts(pool, None, None).await.unwrap();
+    assert_eq!(south_contacts.iter().map(|c| c.id).collect::<Vec<_>>(), vec![books.south_customer]);
+    let north_list = invoice_service().for_company(Some(books.north)).list_invoices(pool, None, None, None, None).await.unwrap();
+    assert_eq!(north_list.iter().map(|i| i.id).collect::<Vec<_>>(), vec![books.north_invoice]);
+
+    let unowned = invoice_service();
+    assert!(unowned.list_invoices(pool, None, None, None, None).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_cached_lists_stay_with_their_company() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let user = create_user(pool, "accountant").await;
+    let north = create_company(pool, user, "North Ltd").await;
+    let south = create_company(pool, user, "South Ltd").await;
+
+    let customer = company_customer(pool, north, "Northern Client").await;
+    let payment_service = payment_service();
+    let north_payments = payment_service.for_company(Some(north));
+    north_payments.create_payment(pool, CreatePaymentRequest {
+        payment_number: Some("PMT-1".to_string()),
+        payment_date: date(2025, 9, 15),
+        payment_method: "Cheque".to_string(),
+        ..payment_request(customer, "100.00", &[])
+    }).await.unwrap();
+
+    // Another company asking first doesn't hide the payments from their owner
+    let south_payments = payment_service.for_company(Some(south));
+    assert!(south_payments.list_customer_payments(pool, customer).await.unwrap().is_empty());
+    assert_eq!(north_payments.list_customer_payments(pool, customer).await.unwrap().len(), 1);
+    assert!(south_payments.list_customer_payments(pool, customer).await.unwrap().is_empty());
+}
+
+fn gst_code(liability_account_id: Uuid) -> CreateTaxCodeRequest {
+    CreateTaxCodeRequest {
+        code: "GST".to_string(),
+        name: "GST".to_string(),
+        description: None,
+        company_id: None,
+        rates: vec![CreateTaxRateRequest {
+            tax_name: "GST".to_string(),
+            agency: None,
+            rate: Decimal::from(5),
+            sequence: None,
+            is_compound: None,
+            is_recoverable: None,
+            liability_account_id,
+            recoverable_account_id: None,
+            effective_from: date(2025, 1, 1),
+            effective_to: None,
+        }],
+    }
+}
+
+#[tokio::test]
+async fn test_tax_codes_outside_a_company_are_not_visible_in_it() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let user = create_user(pool, "accountant").await;
+    let north = create_company(pool, user, "North Ltd").await;
+
+    // A code without a company, posting to an account without one
+    let unowned_liability = create_account(pool, "2200", "GST Payable", AccountType::Liability).await;
+    let unowned = tax_service()
+        .create_tax_code(pool, gst_code(unowned_liability)).await.unwrap();
+
+    assert!(tax_service().for_company(Some(north)).list_tax_codes(pool, true).await.unwrap().is_empty());
+    assert!(matches!(
+        tax_service().for_company(Some(north)).get_tax_code(pool, unowned.tax_code.id).await,
+        Err(AppError::NotFound(_))
+    ));
+
+    let north_revenue = company_account(pool, north, "4000").await;
+    let north_customer = company_customer(pool, north, "Northern Client").await;
+    let mut request = invoice_request("INV-1", north_customer, north_revenue, "100.00");
+    request.line_items[0].tax_code = Some("GST".to_string());
+    assert!(matches!(
+        invoice_service().for_company(Some(north)).create_invoice(pool, request).await,
+        Err(AppError::ValidationError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_company_tax_codes_post_to_its_own_accounts() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let user = create_user(pool, "accountant").await;
+    let north = create_company(pool, user, "North Ltd").await;
+    let south = create_company(pool, user, "South Ltd").await;
+
+    let south_liability = company_account(pool, south, "2200").await;
+    assert!(matches!(
+        tax_service().for_company(Some(north)).create_tax_code(pool, gst_code(south_liability)).await,
+        Err(AppError::NotFound(_))
+    ));
+}
@@ -201,7 +201,7 @@ async fn test_list_contacts() {
 
     // List all contacts
     let all_contacts = service
-        .list_contacts(&pool, None, None, None)
+        .list_contacts(&pool, None, None)
         .await
         .expect("Failed to list contacts");
 
@@ -244,7 +244,7 @@ async fn test_list_contacts_filtered_by_type() {
 
     // Filter by customer type
     let customers = service
-        .list_contacts(&pool, Some(ContactType::Customer), None, None)
+        .list_contacts(&pool, Some(ContactType::Customer), None)
         .await
         .expect("Failed to list customers");
 
@@ -252,7 +252,7 @@ async fn test_list_contacts_filtered_by_type() {
 
     // Filter by vendor type
     let vendors = service
-        .list_contacts(&pool, Some(ContactType::Vendor), None, None)
+        .list_contacts(&pool, Some(ContactType::Vendor), None)
         .await
         .expect("Failed to list vendors");
 
@@ -285,7 +285,7 @@ async fn test_list_contacts_with_limit() {
 
     // List with limit of 3
     let limited = service
-        .list_contacts(&pool, None, None, Some(3))
+        .list_contacts(&pool, None, Some(3))
         .await
         .expect("Failed to list with limit");
 
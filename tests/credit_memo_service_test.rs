@@ -0,0 +1,254 @@
+use ledger_forge::models::{
+    AccountType, SetupCanadianTaxCodesRequest, CreateInvoiceRequest, CreateInvoiceLineItemRequest,
+    InvoiceStatus, CreateCreditMemoRequest, CreateCreditMemoLineItemRequest, CreditMemoApplicationRequest,
+    CreditMemoStatus, CreateCustomerRefundRequest, CreatePaymentRequest, TaxReturnPeriodRequest,
+};
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+mod common;
+use common::test_db::TestDb;
+use common::fixtures::{
+    date, dec, create_account, create_customer, create_sent_invoice, invoice_request, payment_request, balance,
+    get_invoice,
+};
+use common::services::{credit_memo_service, tax_service, payment_service};
+
+fn refund_request(customer_id: Uuid, bank_account_id: Uuid, amount: &str) -> CreateCustomerRefundRequest {
+    CreateCustomerRefundRequest {
+        refund_number: Some("RF-1".to_string()),
+        customer_id,
+        refund_date: date(2025, 10, 20),
+        amount: dec(amount),
+        payment_method: "Cheque".to_string(),
+        reference_number: None,
+        bank_account_id,
+        credit_memo_id: None,
+        payment_id: None,
+        ar_account_id: None,
+        memo: None,
+        company_id: None,
+    }
+}
+
+struct Credited {
+    bank_account: Uuid,
+    ar_account: Uuid,
+    hst_account: Uuid,
+    revenue_account: Uuid,
+    customer: Uuid,
+    /// 1000.00 + HST
+    invoice: Uuid,
+    /// 200.00 + HST against the invoice, 100.00 of it applied
+    credit_memo: Uuid,
+}
+
+/// A posted, HST-taxed consulting invoice with a credit memo for two of its ten hours,
+/// part of which is applied to it
+async fn credited(pool: &PgPool) -> Credited {
+    let bank_account = create_account(pool, "1000", "Bank", AccountType::Asset).await;
+    let ar_account = create_account(pool, "1200", "Accounts Receivable", AccountType::Asset).await;
+    let hst_account = create_account(pool, "2200", "GST/HST Payable", AccountType::Liability).await;
+    let revenue_account = create_account(pool, "4000", "Sales Revenue", AccountType::Revenue).await;
+
+    tax_service().setup_canadian_tax_codes(pool, SetupCanadianTaxCodesRequest {
+        gst_hst_account_id: hst_account,
+        itc_account_id: None,
+        pst_account_id: None,
+        qst_account_id: None,
+        effective_from: date(2025, 4, 1),
+        company_id: None,
+    }).await.unwrap();
+
+    let customer = create_customer(pool, "Credit Customer").await;
+    let invoice = create_sent_invoice(pool, CreateInvoiceRequest {
+        invoice_date: date(2025, 10, 1),
+        due_date: date(2025, 10, 31),
+        ar_account_id: Some(ar_account),
+        line_items: vec![CreateInvoiceLineItemRequest {
+            line_number: 1,
+            item_description: "Consulting".to_string(),
+            quantity: Decimal::from(10),
+            unit_price: dec("100.00"),
+            discount_percent: None,
+            tax_code: Some("HST-ON".to_string()),
+            revenue_account_id: revenue_account,
+            item_id: None,
+            dimension_value_ids: Vec::new(),
+        }],
+        ..invoice_request("INV-CM-1", customer, revenue_account, "1000.00")
+    }).await;
+
+    let credit_memo = credit_memo_service().create_credit_memo(pool, CreateCreditMemoRequest {
+        credit_memo_number: Some("CM-1".to_string()),
+        customer_id: customer,
+        credit_memo_date: date(2025, 10, 15),
+        invoice_id: Some(invoice),
+        memo: None,
+        company_id: None,
+        ar_account_id: Some(ar_account),
+        line_items: vec![CreateCreditMemoLineItemRequest {
+            line_number: 1,
+            item_description: "Consulting hours credited".to_string(),
+            quantity: Decimal::from(2),
+            unit_price: dec("100.00"),
+            tax_code: Some("HST-ON".to_string()),
+            revenue_account_id: revenue_account,
+        }],
+        applications: vec![CreditMemoApplicationRequest {
+            invoice_id: invoice,
+            amount_applied: dec("100.00"),
+        }],
+    }).await.unwrap().credit_memo.id;
+
+    Credited { bank_account, ar_account, hst_account, revenue_account, customer, invoice, credit_memo }
+}
+
+#[tokio::test]
+async fn test_applied_credit_memo_reduces_the_invoice() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let c = credited(pool).await;
+
+    let credit_memo = credit_memo_service().get_credit_memo(pool, c.credit_memo).await.unwrap();
+    assert_eq!(credit_memo.credit_memo.total_amount, dec("226.00"));
+    assert_eq!(credit_memo.credit_memo.balance, dec("126.00"));
+    assert_eq!(credit_memo.credit_memo.status, CreditMemoStatus::Open);
+    assert_eq!(credit_memo.applications.len(), 1);
+
+    let invoice_after = get_invoice(pool, c.invoice).await;
+    assert_eq!(invoice_after.balance, dec("1030.00"));
+    assert_eq!(invoice_after.status, InvoiceStatus::Partial);
+}
+
+#[tokio::test]
+async fn test_credit_memo_reverses_revenue_and_tax() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let c = credited(pool).await;
+
+    // Against receivables
+    assert_eq!(balance(pool, c.ar_account).await, dec("904.00"));
+    assert_eq!(balance(pool, c.revenue_account).await, dec("-800.00"));
+    assert_eq!(balance(pool, c.hst_account).await, dec("-104.00"));
+
+    let worksheet = tax_service().generate_return_worksheet(pool, TaxReturnPeriodRequest {
+        agency: None,
+        period_start: date(2025, 10, 1),
+        period_end: date(2025, 12, 31),
+        company_id: None,
+    }).await.unwrap();
+    assert_eq!(worksheet.lines.line_101_sales, dec("800.00"));
+    assert_eq!(worksheet.lines.line_103_tax_collected, dec("104.00"));
+}
+
+#[tokio::test]
+async fn test_used_credit_cannot_be_over_applied_or_voided() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let c = credited(pool).await;
+
+    // Only 126.00 is left
+    let over = credit_memo_service().apply_credit_memo(pool, c.credit_memo, vec![CreditMemoApplicationRequest {
+        invoice_id: c.invoice,
+        amount_applied: dec("200.00"),
+    }]).await;
+    assert!(over.is_err());
+    assert!(credit_memo_service().void_credit_memo(pool, c.credit_memo).await.is_err());
+}
+
+#[tokio::test]
+async fn test_remaining_credit_is_refunded_from_the_bank() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let c = credited(pool).await;
+
+    let refund = credit_memo_service().create_refund(pool, CreateCustomerRefundRequest {
+        credit_memo_id: Some(c.credit_memo),
+        ..refund_request(c.customer, c.bank_account, "126.00")
+    }).await.unwrap();
+    assert!(refund.transaction_id.is_some());
+
+    let refunded = credit_memo_service().get_credit_memo(pool, c.credit_memo).await.unwrap();
+    assert_eq!(refunded.credit_memo.balance, Decimal::ZERO);
+    assert_eq!(refunded.credit_memo.status, CreditMemoStatus::Applied);
+    assert_eq!(balance(pool, c.ar_account).await, dec("1030.00"));
+    assert_eq!(balance(pool, c.bank_account).await, dec("-126.00"));
+}
+
+/// A customer with a 50.00 cash payment applied to nothing. Returns the bank account,
+/// customer and payment.
+async fn overpaid(pool: &PgPool) -> (Uuid, Uuid, Uuid) {
+    let bank_account = create_account(pool, "1000", "Bank", AccountType::Asset).await;
+    let customer = create_customer(pool, "Overpaying Customer").await;
+    let payment = payment_service().create_payment(pool, CreatePaymentRequest {
+        payment_number: Some("PMT-1".to_string()),
+        payment_date: date(2025, 10, 1),
+        payment_method: "Cash".to_string(),
+        deposit_to_account_id: Some(bank_account),
+        ..payment_request(customer, "50.00", &[])
+    }).await.unwrap();
+    (bank_account, customer, payment.id)
+}
+
+#[tokio::test]
+async fn test_refund_cannot_exceed_the_unapplied_payment() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (bank_account, customer, payment) = overpaid(pool).await;
+
+    assert!(credit_memo_service().create_refund(pool, CreateCustomerRefundRequest {
+        payment_id: Some(payment),
+        ..refund_request(customer, bank_account, "60.00")
+    }).await.is_err());
+}
+
+#[tokio::test]
+async fn test_refunding_an_overpayment_uses_it_up() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (bank_account, customer, payment) = overpaid(pool).await;
+
+    credit_memo_service().create_refund(pool, CreateCustomerRefundRequest {
+        payment_id: Some(payment),
+        ..refund_request(customer, bank_account, "50.00")
+    }).await.unwrap();
+    let payment = payment_service()
+        .get_payment_by_id(pool, payment).await.unwrap().unwrap();
+    assert_eq!(payment.unapplied_amount, Some(Decimal::ZERO));
+}
+
+#[tokio::test]
+async fn test_unused_credit_memo_can_be_voided() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let ar_account = create_account(pool, "1200", "Accounts Receivable", AccountType::Asset).await;
+    let revenue_account = create_account(pool, "4000", "Sales Revenue", AccountType::Revenue).await;
+    let customer = create_customer(pool, "Goodwill Customer").await;
+
+    let credit_memo = credit_memo_service().create_credit_memo(pool, CreateCreditMemoRequest {
+        credit_memo_number: Some("CM-2".to_string()),
+        customer_id: customer,
+        credit_memo_date: date(2025, 10, 5),
+        invoice_id: None,
+        memo: None,
+        company_id: None,
+        ar_account_id: Some(ar_account),
+        line_items: vec![CreateCreditMemoLineItemRequest {
+            line_number: 1,
+            item_description: "Goodwill credit".to_string(),
+            quantity: Decimal::ONE,
+            unit_price: dec("40.00"),
+            tax_code: None,
+            revenue_account_id: revenue_account,
+        }],
+        applications: vec![],
+    }).await.unwrap();
+    assert_eq!(balance(pool, ar_account).await, dec("-40.00"));
+
+    // Voiding reverses its posting
+    let voided = credit_memo_service().void_credit_memo(pool, credit_memo.credit_memo.id).await.unwrap();
+    assert_eq!(voided.status, CreditMemoStatus::Void);
+    assert_eq!(balance(pool, ar_account).await, Decimal::ZERO);
+}
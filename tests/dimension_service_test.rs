@@ -0,0 +1,244 @@
+use ledger_forge::models::{
+    AccountType, CreateBillRequest, CreateBillLineItemRequest, CreateInvoiceRequest, CreateInvoiceLineItemRequest,
+    CreateDimensionTypeRequest, CreateDimensionValueRequest, CreateDimensionRequirementRequest, DateRangeRequest,
+    DateRequest, DimensionFilter,
+};
+use ledger_forge::utils::AppError;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+mod common;
+use common::test_db::TestDb;
+use common::fixtures::{
+    date, dec, create_account, create_customer, create_vendor, create_sent_invoice, invoice_request,
+};
+use common::services::{dimension_service, bill_service, reporting_service};
+
+async fn create_type(pool: &PgPool, code: &str, name: &str) -> Uuid {
+    dimension_service().create_dimension_type(pool, CreateDimensionTypeRequest {
+        code: code.to_string(),
+        name: name.to_string(),
+        company_id: None,
+    }).await.unwrap().id
+}
+
+async fn create_value(pool: &PgPool, dimension_type_id: Uuid, code: &str, name: &str) -> Uuid {
+    dimension_service().create_dimension_value(pool, dimension_type_id, CreateDimensionValueRequest {
+        code: code.to_string(),
+        name: name.to_string(),
+    }).await.unwrap().id
+}
+
+fn bill_line(line_number: i32, amount: &str, expense_account_id: Uuid, dimension_value_ids: Vec<Uuid>) -> CreateBillLineItemRequest {
+    CreateBillLineItemRequest {
+        line_number,
+        description: None,
+        amount: dec(amount),
+        expense_account_id,
+        billable: None,
+        customer_id: None,
+        item_id: None,
+        quantity: None,
+        tax_code: None,
+        dimension_value_ids,
+    }
+}
+
+fn invoice_line(line_number: i32, description: &str, amount: &str, revenue_account_id: Uuid, dimension_value_ids: Vec<Uuid>) -> CreateInvoiceLineItemRequest {
+    CreateInvoiceLineItemRequest {
+        line_number,
+        item_description: description.to_string(),
+        quantity: dec("1"),
+        unit_price: dec(amount),
+        discount_percent: None,
+        tax_code: None,
+        revenue_account_id,
+        item_id: None,
+        dimension_value_ids,
+    }
+}
+
+fn march() -> DateRangeRequest {
+    DateRangeRequest { start_date: date(2025, 3, 1), end_date: date(2025, 3, 31) }
+}
+
+fn grouped_by(dimension_type_id: Uuid) -> DimensionFilter {
+    DimensionFilter { dimension_value_ids: Vec::new(), group_by_dimension_type_id: Some(dimension_type_id) }
+}
+
+struct Dimensions {
+    ar_account: Uuid,
+    ap_account: Uuid,
+    revenue_account: Uuid,
+    rent_account: Uuid,
+    vendor: Uuid,
+    customer: Uuid,
+    department: Uuid,
+    sales: Uuid,
+    ops: Uuid,
+    project: Uuid,
+    apollo: Uuid,
+}
+
+impl Dimensions {
+    fn rent_bill(&self, line_items: Vec<CreateBillLineItemRequest>) -> CreateBillRequest {
+        CreateBillRequest {
+            bill_number: Some("R-1".to_string()),
+            vendor_id: self.vendor,
+            bill_date: date(2025, 3, 1),
+            due_date: date(2025, 3, 31),
+            memo: None,
+            company_id: None,
+            ap_account_id: Some(self.ap_account),
+            line_items,
+        }
+    }
+}
+
+/// Department (Sales, Operations) and Project (Apollo) dimensions, with a department
+/// required on expense lines
+async fn dimensions(pool: &PgPool) -> Dimensions {
+    let department = create_type(pool, "DEPT", "Department").await;
+    let project = create_type(pool, "PROJ", "Project").await;
+    dimension_service().add_requirement(pool, department, CreateDimensionRequirementRequest {
+        account_type: Some(AccountType::Expense),
+        account_id: None,
+    }).await.unwrap();
+
+    Dimensions {
+        ar_account: create_account(pool, "1200", "Accounts Receivable", AccountType::Asset).await,
+        ap_account: create_account(pool, "2000", "Accounts Payable", AccountType::Liability).await,
+        revenue_account: create_account(pool, "4000", "Consulting Revenue", AccountType::Revenue).await,
+        rent_account: create_account(pool, "6100", "Rent", AccountType::Expense).await,
+        vendor: create_vendor(pool, "Landlord Inc").await,
+        customer: create_customer(pool, "Acme Corp").await,
+        department,
+        sales: create_value(pool, department, "SALES", "Sales").await,
+        ops: create_value(pool, department, "OPS", "Operations").await,
+        project,
+        apollo: create_value(pool, project, "APOLLO", "Apollo").await,
+    }
+}
+
+/// March rent split 600.00 Sales (Apollo) / 300.00 Operations, and a sent invoice
+/// for 1500.00 of Apollo work plus 500.00 untagged
+async fn tagged_activity(pool: &PgPool) -> Dimensions {
+    let d = dimensions(pool).await;
+    bill_service().create_bill(pool, d.rent_bill(vec![
+        bill_line(1, "600.00", d.rent_account, vec![d.sales, d.apollo]),
+        bill_line(2, "300.00", d.rent_account, vec![d.ops]),
+    ])).await.unwrap();
+
+    create_sent_invoice(pool, CreateInvoiceRequest {
+        invoice_date: date(2025, 3, 5),
+        due_date: date(2025, 4, 4),
+        ar_account_id: Some(d.ar_account),
+        line_items: vec![
+            invoice_line(1, "Apollo design", "1500.00", d.revenue_account, vec![d.apollo]),
+            invoice_line(2, "General advice", "500.00", d.revenue_account, Vec::new()),
+        ],
+        ..invoice_request("INV-D-1", d.customer, d.revenue_account, "0")
+    }).await;
+    d
+}
+
+#[tokio::test]
+async fn test_types_are_listed_for_the_service_company_only() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let department = create_type(pool, "DEPT", "Department").await;
+
+    let listed = dimension_service().list_dimension_types(pool).await.unwrap();
+    assert_eq!(listed.iter().map(|t| t.id).collect::<Vec<_>>(), vec![department]);
+    assert!(dimension_service().for_company(Some(Uuid::new_v4())).list_dimension_types(pool).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_requirement_needs_an_account_or_account_type() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let department = create_type(pool, "DEPT", "Department").await;
+
+    assert!(matches!(
+        dimension_service().add_requirement(pool, department, CreateDimensionRequirementRequest {
+            account_type: None,
+            account_id: None,
+        }).await,
+        Err(AppError::ValidationError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_required_dimension_needs_exactly_one_value_per_line() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let d = dimensions(pool).await;
+
+    // No department, then two departments
+    assert!(matches!(
+        bill_service().create_bill(pool, d.rent_bill(vec![bill_line(1, "900.00", d.rent_account, vec![d.apollo])])).await,
+        Err(AppError::ValidationError(_))
+    ));
+    assert!(matches!(
+        bill_service().create_bill(pool, d.rent_bill(vec![bill_line(1, "900.00", d.rent_account, vec![d.sales, d.ops])])).await,
+        Err(AppError::ValidationError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_profit_loss_grouped_by_department() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let d = tagged_activity(pool).await;
+
+    let pl = reporting_service().generate_profit_loss(pool, march(), &grouped_by(d.department)).await.unwrap();
+    let rent: Vec<_> = pl.expense_entries.iter()
+        .map(|e| (e.dimension_value_name.clone(), e.amount))
+        .collect();
+    assert_eq!(rent, vec![(Some("Operations".to_string()), dec("300.00")), (Some("Sales".to_string()), dec("600.00"))]);
+    assert_eq!(pl.total_expenses, dec("900.00"));
+}
+
+#[tokio::test]
+async fn test_profit_loss_filtered_to_a_project() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let d = tagged_activity(pool).await;
+
+    // Only the tagged rent and revenue remain; the invoice carried its project to the revenue posting
+    let apollo_only = DimensionFilter { dimension_value_ids: vec![d.apollo], group_by_dimension_type_id: None };
+    let pl = reporting_service().generate_profit_loss(pool, march(), &apollo_only).await.unwrap();
+    assert_eq!(pl.total_revenue, dec("1500.00"));
+    assert_eq!(pl.total_expenses, dec("600.00"));
+}
+
+#[tokio::test]
+async fn test_grouped_trial_balance_stays_balanced() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let d = tagged_activity(pool).await;
+
+    let tb = reporting_service()
+        .generate_trial_balance(pool, DateRequest { as_of_date: date(2025, 3, 31) }, &grouped_by(d.department))
+        .await
+        .unwrap();
+    assert!(tb.is_balanced);
+    assert_eq!(tb.entries.iter().filter(|e| e.account_id == d.rent_account).count(), 2);
+}
+
+#[tokio::test]
+async fn test_aging_splits_open_invoices_by_project() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let d = tagged_activity(pool).await;
+
+    let aging = reporting_service()
+        .generate_ar_aging(pool, DateRequest { as_of_date: date(2025, 3, 31) }, &grouped_by(d.project))
+        .await
+        .unwrap();
+    let split: Vec<_> = aging.buckets.iter()
+        .map(|b| (b.dimension_value_name.clone(), b.total))
+        .collect();
+    assert_eq!(split, vec![(None, dec("500.00")), (Some("Apollo".to_string()), dec("1500.00"))]);
+    assert_eq!(aging.total_outstanding, dec("2000.00"));
+}
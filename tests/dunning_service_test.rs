@@ -0,0 +1,293 @@
+use ledger_forge::models::{
+    AccountType, ContactType, CreateContactRequest, CreateInvoiceRequest, InvoiceStatus, ReminderStatus,
+    SetDunningLevelsRequest, DunningLevelRequest,
+};
+use ledger_forge::services::email::SmtpSecurity;
+use ledger_forge::services::{DunningService, EmailService, SmtpConfig, CacheService};
+use sqlx::PgPool;
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+mod common;
+use common::test_db::TestDb;
+use common::smtp::start_smtp_catcher;
+use common::fixtures::{
+    date, create_account, contact_request, create_contact, create_sent_invoice, invoice_request, get_invoice,
+};
+use common::services::invoice_service;
+
+fn dunning_service(port: u16) -> DunningService {
+    let cache_service = CacheService::default();
+    let email_service = EmailService::new(SmtpConfig {
+        host: "127.0.0.1".to_string(),
+        port,
+        security: SmtpSecurity::None,
+        username: None,
+        password: None,
+        from_address: "billing@maple.example".to_string(),
+        from_name: None,
+    }, cache_service.clone());
+    DunningService::new(email_service, cache_service)
+}
+
+async fn create_customer(pool: &PgPool, name: &str, email: Option<&str>) -> Uuid {
+    create_contact(pool, CreateContactRequest {
+        email: email.map(|e| e.to_string()),
+        ..contact_request(ContactType::Customer, name)
+    }).await
+}
+
+/// A sent 500.00 invoice issued 2025-09-01
+async fn invoice(pool: &PgPool, number: &str, customer_id: Uuid, revenue_account_id: Uuid, due_date: NaiveDate) -> Uuid {
+    create_sent_invoice(pool, CreateInvoiceRequest {
+        invoice_date: date(2025, 9, 1),
+        due_date,
+        ..invoice_request(number, customer_id, revenue_account_id, "500.00")
+    }).await
+}
+
+struct Invoices {
+    /// Due 2025-10-01, customer has an email address
+    overdue: Uuid,
+    /// Due 2025-10-01, customer opted out of reminders
+    skipped: Uuid,
+    opted_out: Uuid,
+    /// Due 2025-10-01, customer has no email address
+    unreachable: Uuid,
+    /// Due 2025-12-31
+    current: Uuid,
+}
+
+async fn invoices(pool: &PgPool, dunning_service: &DunningService) -> Invoices {
+    let revenue_account = create_account(pool, "4000", "Revenue", AccountType::Revenue).await;
+    let reminded = create_customer(pool, "Reminded Customer", Some("ap@reminded.example")).await;
+    let opted_out = create_customer(pool, "Opted Out Customer", Some("ap@optout.example")).await;
+    let no_email = create_customer(pool, "No Email Customer", None).await;
+    assert!(dunning_service.set_opt_out(pool, opted_out, true).await.unwrap().dunning_opt_out);
+
+    Invoices {
+        overdue: invoice(pool, "INV-D1", reminded, revenue_account, date(2025, 10, 1)).await,
+        skipped: invoice(pool, "INV-D2", opted_out, revenue_account, date(2025, 10, 1)).await,
+        opted_out,
+        unreachable: invoice(pool, "INV-D3", no_email, revenue_account, date(2025, 10, 1)).await,
+        current: invoice(pool, "INV-D4", reminded, revenue_account, date(2025, 12, 31)).await,
+    }
+}
+
+#[tokio::test]
+async fn test_run_marks_past_due_invoices_overdue() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (port, _messages) = start_smtp_catcher().await;
+    let dunning_service = dunning_service(port);
+    let invoices = invoices(pool, &dunning_service).await;
+
+    let run = dunning_service.run(pool, date(2025, 10, 10)).await.unwrap();
+    let mut marked: Vec<Uuid> = run.marked_overdue.iter().map(|i| i.id).collect();
+    marked.sort();
+    let mut expected = vec![invoices.overdue, invoices.skipped, invoices.unreachable];
+    expected.sort();
+    assert_eq!(marked, expected);
+
+    assert_eq!(get_invoice(pool, invoices.current).await.status, InvoiceStatus::Sent);
+    assert_eq!(get_invoice(pool, invoices.overdue).await.status, InvoiceStatus::Overdue);
+    let overdue_list = invoice_service().get_overdue_invoices(pool).await.unwrap();
+    assert!(overdue_list.iter().any(|i| i.id == invoices.overdue));
+}
+
+#[tokio::test]
+async fn test_first_reminder_is_sent_after_seven_days() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (port, messages) = start_smtp_catcher().await;
+    let dunning_service = dunning_service(port);
+    let invoices = invoices(pool, &dunning_service).await;
+
+    // Nine days late reaches the 7-day reminder
+    let run = dunning_service.run(pool, date(2025, 10, 10)).await.unwrap();
+    let sent = run.reminders.iter().find(|r| r.invoice_id == invoices.overdue).unwrap();
+    assert_eq!(sent.level, 1);
+    assert_eq!(sent.status, ReminderStatus::Sent);
+    assert!(sent.email_delivery_id.is_some());
+
+    let messages = messages.lock().unwrap();
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].contains("To: ap@reminded.example"));
+    assert!(messages[0].contains("Subject: Reminder: invoice INV-D1 is past due"));
+}
+
+#[tokio::test]
+async fn test_opted_out_customers_are_not_reminded() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (port, _messages) = start_smtp_catcher().await;
+    let dunning_service = dunning_service(port);
+    let invoices = invoices(pool, &dunning_service).await;
+
+    let run = dunning_service.run(pool, date(2025, 10, 10)).await.unwrap();
+    assert_eq!(run.reminders.len(), 2);
+    assert!(run.reminders.iter().all(|r| r.invoice_id != invoices.skipped));
+}
+
+#[tokio::test]
+async fn test_reminder_fails_for_a_customer_without_email() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (port, _messages) = start_smtp_catcher().await;
+    let dunning_service = dunning_service(port);
+    let invoices = invoices(pool, &dunning_service).await;
+
+    let run = dunning_service.run(pool, date(2025, 10, 10)).await.unwrap();
+    let failed = run.reminders.iter().find(|r| r.invoice_id == invoices.unreachable).unwrap();
+    assert_eq!(failed.status, ReminderStatus::Failed);
+    assert!(failed.error.as_deref().unwrap().contains("no email address"));
+}
+
+#[tokio::test]
+async fn test_running_again_the_same_day_only_retries_failures() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (port, messages) = start_smtp_catcher().await;
+    let dunning_service = dunning_service(port);
+    let invoices = invoices(pool, &dunning_service).await;
+    dunning_service.run(pool, date(2025, 10, 10)).await.unwrap();
+
+    let rerun = dunning_service.run(pool, date(2025, 10, 10)).await.unwrap();
+    assert!(rerun.marked_overdue.is_empty());
+    assert_eq!(rerun.reminders.len(), 1);
+    assert_eq!(rerun.reminders[0].invoice_id, invoices.unreachable);
+    assert_eq!(messages.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_long_overdue_invoice_jumps_to_the_final_notice() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (port, messages) = start_smtp_catcher().await;
+    let dunning_service = dunning_service(port);
+    let invoices = invoices(pool, &dunning_service).await;
+    dunning_service.run(pool, date(2025, 10, 10)).await.unwrap();
+
+    // 75 days late skips the second reminder
+    let late = dunning_service.run(pool, date(2025, 12, 15)).await.unwrap();
+    let final_notice = late.reminders.iter().find(|r| r.invoice_id == invoices.overdue).unwrap();
+    assert_eq!(final_notice.level, 3);
+    assert_eq!(final_notice.days_overdue, 75);
+    assert!(messages.lock().unwrap()[1].contains("Subject: Final notice: invoice INV-D1 is 75 days overdue"));
+    assert_eq!(dunning_service.list_reminders(pool, Some(invoices.overdue), None).await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_custom_schedule_applies_once_the_customer_opts_back_in() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (port, messages) = start_smtp_catcher().await;
+    let dunning_service = dunning_service(port);
+    let invoices = invoices(pool, &dunning_service).await;
+
+    // Levels are numbered in order of days overdue
+    let levels = dunning_service.set_dunning_levels(pool, SetDunningLevelsRequest {
+        company_id: None,
+        levels: vec![
+            DunningLevelRequest {
+                days_overdue: 14,
+                subject: "Past due: {{invoice_number}} ({{days_overdue}} days)".to_string(),
+                body: "Please pay {{balance}}".to_string(),
+            },
+            DunningLevelRequest {
+                days_overdue: 3,
+                subject: "Due reminder: {{invoice_number}}".to_string(),
+                body: "Please pay {{balance}}".to_string(),
+            },
+        ],
+    }).await.unwrap();
+    assert_eq!(levels.len(), 2);
+    assert_eq!(levels[0].days_overdue, 3);
+    assert_eq!(levels[1].level, 2);
+
+    dunning_service.set_opt_out(pool, invoices.opted_out, false).await.unwrap();
+    let run = dunning_service.run(pool, date(2025, 12, 15)).await.unwrap();
+    let custom = run.reminders.iter().find(|r| r.invoice_id == invoices.skipped).unwrap();
+    assert_eq!(custom.level, 2);
+    assert_eq!(custom.status, ReminderStatus::Sent);
+    assert!(messages.lock().unwrap().iter().any(|m| m.contains("Subject: Past due: INV-D2 (75 days)")));
+}
+
+/// An overdue invoice for a customer with an email address whose reminder another run is
+/// sending right now, and one for a customer without. Returns them in that order.
+async fn claimed_and_unreachable(pool: &PgPool, dunning_service: &DunningService) -> (Uuid, Uuid) {
+    let revenue_account = create_account(pool, "4000", "Revenue", AccountType::Revenue).await;
+    let reminded = create_customer(pool, "Reminded Customer", Some("ap@reminded.example")).await;
+    let no_email = create_customer(pool, "No Email Customer", None).await;
+    let in_flight = invoice(pool, "INV-C1", reminded, revenue_account, date(2025, 10, 1)).await;
+    let unreachable = invoice(pool, "INV-C2", no_email, revenue_account, date(2025, 10, 1)).await;
+
+    dunning_service.mark_overdue(pool, date(2025, 10, 10)).await.unwrap();
+    sqlx::query(
+        r#"
+        INSERT INTO invoice_reminders (id, invoice_id, level, days_overdue, status, attempts, claimed_at)
+        VALUES ($1, $2, 1, 9, 'sending', 1, NOW())
+        "#
+    )
+    .bind(Uuid::new_v4())
+    .bind(in_flight)
+    .execute(pool)
+    .await
+    .unwrap();
+
+    (in_flight, unreachable)
+}
+
+#[tokio::test]
+async fn test_reminder_claimed_by_another_run_is_left_to_it() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (port, messages) = start_smtp_catcher().await;
+    let dunning_service = dunning_service(port);
+    let (_, unreachable) = claimed_and_unreachable(pool, &dunning_service).await;
+
+    let run = dunning_service.run(pool, date(2025, 10, 10)).await.unwrap();
+    assert_eq!(run.reminders.len(), 1);
+    assert_eq!(run.reminders[0].invoice_id, unreachable);
+    assert_eq!(run.reminders[0].attempts, 1);
+    assert!(messages.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_failing_reminder_is_given_up_after_five_attempts() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (port, _messages) = start_smtp_catcher().await;
+    let dunning_service = dunning_service(port);
+    let (_, unreachable) = claimed_and_unreachable(pool, &dunning_service).await;
+
+    for attempt in 1..=5 {
+        let run = dunning_service.run(pool, date(2025, 10, 10)).await.unwrap();
+        assert_eq!(run.reminders.len(), 1);
+        assert_eq!(run.reminders[0].invoice_id, unreachable);
+        assert_eq!(run.reminders[0].attempts, attempt);
+        assert_eq!(run.reminders[0].status, ReminderStatus::Failed);
+    }
+    assert!(dunning_service.run(pool, date(2025, 10, 10)).await.unwrap().reminders.is_empty());
+}
+
+#[tokio::test]
+async fn test_stale_claim_is_taken_over() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (port, messages) = start_smtp_catcher().await;
+    let dunning_service = dunning_service(port);
+    let (in_flight, _) = claimed_and_unreachable(pool, &dunning_service).await;
+
+    // The run that claimed it went away two hours ago
+    sqlx::query("UPDATE invoice_reminders SET claimed_at = NOW() - INTERVAL '2 hours' WHERE invoice_id = $1")
+        .bind(in_flight)
+        .execute(pool)
+        .await
+        .unwrap();
+    let run = dunning_service.run(pool, date(2025, 10, 10)).await.unwrap();
+    let taken_over = run.reminders.iter().find(|r| r.invoice_id == in_flight).unwrap();
+    assert_eq!(taken_over.status, ReminderStatus::Sent);
+    assert_eq!(taken_over.attempts, 2);
+    assert_eq!(messages.lock().unwrap().len(), 1);
+}
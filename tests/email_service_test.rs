@@ -0,0 +1,200 @@
+use ledger_forge::models::{
+    AccountType, ContactType, CreateContactRequest, CreateInvoiceRequest, CreateInvoiceLineItemRequest,
+    InvoiceStatus, EmailDocumentType, EmailDeliveryStatus, UpdateEmailTemplateRequest, SendStatementRequest,
+    StatementStyle, TaxReturnPeriodRequest, FileTaxReturnRequest,
+};
+use ledger_forge::services::email::SmtpSecurity;
+use ledger_forge::services::{EmailService, SmtpConfig, CacheService};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+mod common;
+use common::test_db::TestDb;
+use common::smtp::start_smtp_catcher;
+use common::fixtures::{date, dec, create_account, contact_request, create_contact, get_invoice};
+use common::services::{invoice_service, tax_service};
+
+fn smtp_config(port: u16) -> SmtpConfig {
+    SmtpConfig {
+        host: "127.0.0.1".to_string(),
+        port,
+        security: SmtpSecurity::None,
+        username: None,
+        password: None,
+        from_address: "billing@maple.example".to_string(),
+        from_name: Some("Maple Billing".to_string()),
+    }
+}
+
+/// Sends through the SMTP server on `port`
+fn email_service(port: u16) -> EmailService {
+    EmailService::new(smtp_config(port), CacheService::default())
+}
+
+/// A customer with an email address and a 1,000.00 draft invoice due 2025-10-31.
+/// Returns the customer and invoice.
+async fn draft_invoice(pool: &PgPool) -> (Uuid, Uuid) {
+    let revenue_account = create_account(pool, "4000", "Consulting Revenue", AccountType::Revenue).await;
+    let customer = create_contact(pool, CreateContactRequest {
+        email: Some("ap@customer.example".to_string()),
+        ..contact_request(ContactType::Customer, "Emailed Customer")
+    }).await;
+
+    let invoice = invoice_service().create_invoice(pool, CreateInvoiceRequest {
+        invoice_number: Some("INV-MAIL-1".to_string()),
+        customer_id: customer,
+        invoice_date: date(2025, 10, 1),
+        due_date: date(2025, 10, 31),
+        ship_date: None,
+        customer_memo: None,
+        billing_address: None,
+        shipping_address: None,
+        company_id: None,
+        ar_account_id: None,
+        line_items: vec![CreateInvoiceLineItemRequest {
+            line_number: 1,
+            item_description: "Advisory services".to_string(),
+            quantity: dec("4"),
+            unit_price: dec("250.00"),
+            discount_percent: None,
+            tax_code: None,
+            revenue_account_id: revenue_account,
+            item_id: None,
+            dimension_value_ids: Vec::new(),
+        }],
+    }).await.unwrap().invoice;
+    assert_eq!(invoice.status, InvoiceStatus::Draft);
+
+    (customer, invoice.id)
+}
+
+fn statement_request(to: Option<&str>) -> SendStatementRequest {
+    SendStatementRequest {
+        to: to.map(str::to_string),
+        start_date: None,
+        end_date: date(2025, 10, 31),
+        style: StatementStyle::OpenItem,
+    }
+}
+
+#[tokio::test]
+async fn test_send_invoice_with_company_template() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (port, messages) = start_smtp_catcher().await;
+    let email_service = email_service(port);
+    let (_, invoice_id) = draft_invoice(pool).await;
+
+    email_service.update_email_template(pool, UpdateEmailTemplateRequest {
+        company_id: None,
+        document_type: EmailDocumentType::Invoice,
+        subject: "Invoice {{invoice_number}} for {{customer_name}}".to_string(),
+        body: "Amount due: {{balance}} by {{due_date}}".to_string(),
+    }).await.unwrap();
+
+    let delivery = email_service.send_invoice(pool, invoice_id, None, None).await.unwrap();
+    assert_eq!(delivery.status, EmailDeliveryStatus::Sent);
+    assert_eq!(delivery.recipient, "ap@customer.example");
+    assert_eq!(delivery.subject, "Invoice INV-MAIL-1 for Emailed Customer");
+    assert_eq!(delivery.document_id, Some(invoice_id));
+
+    let messages = messages.lock().unwrap();
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].contains("Subject: Invoice INV-MAIL-1 for Emailed Customer"));
+    assert!(messages[0].contains("To: ap@customer.example"));
+    assert!(messages[0].contains("Content-Type: application/pdf"));
+    assert!(messages[0].contains("invoice_INV-MAIL-1.pdf"));
+    assert!(messages[0].contains("Amount due: 1,000.00 by 2025-10-31"));
+}
+
+#[tokio::test]
+async fn test_sending_a_draft_invoice_marks_it_sent() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (port, _messages) = start_smtp_catcher().await;
+    let (_, invoice_id) = draft_invoice(pool).await;
+
+    email_service(port).send_invoice(pool, invoice_id, None, None).await.unwrap();
+    assert_eq!(get_invoice(pool, invoice_id).await.status, InvoiceStatus::Sent);
+}
+
+#[tokio::test]
+async fn test_invoice_that_cannot_be_posted_is_not_emailed() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (port, messages) = start_smtp_catcher().await;
+    let email_service = email_service(port);
+    let (_, invoice_id) = draft_invoice(pool).await;
+
+    // Filing the quarter locks the invoice date
+    let bank_account = create_account(pool, "1000", "Bank", AccountType::Asset).await;
+    let tax_service = tax_service();
+    let tax_return = tax_service.create_tax_return(pool, TaxReturnPeriodRequest {
+        agency: None,
+        period_start: date(2025, 10, 1),
+        period_end: date(2025, 12, 31),
+        company_id: None,
+    }).await.unwrap();
+    tax_service.file_tax_return(pool, tax_return.id, FileTaxReturnRequest {
+        filed_date: date(2026, 1, 31),
+        settlement_account_id: bank_account,
+    }, None).await.unwrap();
+
+    assert!(email_service.send_invoice(pool, invoice_id, None, None).await.is_err());
+    assert!(messages.lock().unwrap().is_empty());
+    let history = email_service.list_deliveries(pool, Some(EmailDocumentType::Invoice), Some(invoice_id), None).await.unwrap();
+    assert!(history.is_empty());
+    assert_eq!(get_invoice(pool, invoice_id).await.status, InvoiceStatus::Draft);
+}
+
+#[tokio::test]
+async fn test_statement_uses_built_in_wording_and_another_address() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (port, messages) = start_smtp_catcher().await;
+    let (customer, _) = draft_invoice(pool).await;
+
+    let statement_delivery = email_service(port)
+        .send_statement(pool, customer, statement_request(Some("controller@customer.example")), None)
+        .await
+        .unwrap();
+    assert_eq!(statement_delivery.document_type, EmailDocumentType::Statement);
+    assert_eq!(statement_delivery.recipient, "controller@customer.example");
+    assert!(statement_delivery.subject.contains("2025-10-31"));
+    assert_eq!(messages.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_failed_deliveries_are_recorded() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (port, _messages) = start_smtp_catcher().await;
+    let unreachable = email_service(1);
+    let email_service = email_service(port);
+    let (_, invoice_id) = draft_invoice(pool).await;
+    email_service.send_invoice(pool, invoice_id, None, None).await.unwrap();
+
+    assert!(unreachable.send_invoice(pool, invoice_id, None, None).await.is_err());
+
+    // Newest first
+    let history = email_service.list_deliveries(pool, Some(EmailDocumentType::Invoice), Some(invoice_id), None).await.unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].status, EmailDeliveryStatus::Failed);
+    assert!(history[0].error.is_some());
+    assert_eq!(history[1].status, EmailDeliveryStatus::Sent);
+}
+
+#[tokio::test]
+async fn test_deliveries_are_listed_by_customer() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (port, _messages) = start_smtp_catcher().await;
+    let email_service = email_service(port);
+    let (customer, invoice_id) = draft_invoice(pool).await;
+
+    email_service.send_invoice(pool, invoice_id, None, None).await.unwrap();
+    email_service.send_statement(pool, customer, statement_request(None), None).await.unwrap();
+
+    let all = email_service.list_deliveries(pool, None, None, Some(customer)).await.unwrap();
+    assert_eq!(all.len(), 2);
+}
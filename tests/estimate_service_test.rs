@@ -0,0 +1,214 @@
+use ledger_forge::models::{
+    AccountType, CreateInvoiceLineItemRequest, CreateEstimateRequest, ConvertEstimateRequest,
+    ConvertEstimateLineRequest, EstimateStatus,
+};
+use sqlx::PgPool;
+use chrono::{Duration, NaiveDate, Utc};
+use uuid::Uuid;
+
+mod common;
+use common::test_db::TestDb;
+use common::fixtures::{dec, create_account, create_customer, get_invoice};
+use common::services::estimate_service;
+
+fn line(line_number: i32, description: &str, quantity: &str, unit_price: &str, revenue_account_id: Uuid) -> CreateInvoiceLineItemRequest {
+    CreateInvoiceLineItemRequest {
+        line_number,
+        item_description: description.to_string(),
+        quantity: dec(quantity),
+        unit_price: dec(unit_price),
+        discount_percent: None,
+        tax_code: None,
+        revenue_account_id,
+        item_id: None,
+        dimension_value_ids: Vec::new(),
+    }
+}
+
+fn estimate_request(number: &str, customer_id: Uuid, estimate_date: NaiveDate, revenue_account_id: Uuid) -> CreateEstimateRequest {
+    CreateEstimateRequest {
+        estimate_number: number.to_string(),
+        customer_id,
+        estimate_date,
+        expiry_date: estimate_date + Duration::days(30),
+        customer_memo: Some("Phase 1 engagement".to_string()),
+        billing_address: None,
+        shipping_address: None,
+        company_id: None,
+        line_items: vec![
+            line(1, "Design", "10", "100.00", revenue_account_id),
+            line(2, "Build", "20", "50.00", revenue_account_id),
+        ],
+    }
+}
+
+fn convert_request(invoice_number: &str) -> ConvertEstimateRequest {
+    let today = Utc::now().date_naive();
+    ConvertEstimateRequest {
+        invoice_number: Some(invoice_number.to_string()),
+        invoice_date: today,
+        due_date: today + Duration::days(30),
+        ar_account_id: None,
+        percent: None,
+        lines: None,
+    }
+}
+
+fn build_request(invoice_number: &str, build_line: Uuid, quantity: &str) -> ConvertEstimateRequest {
+    ConvertEstimateRequest {
+        lines: Some(vec![ConvertEstimateLineRequest { estimate_line_item_id: build_line, quantity: dec(quantity) }]),
+        ..convert_request(invoice_number)
+    }
+}
+
+/// Revenue account and customer to quote against
+async fn quote_parties(pool: &PgPool, customer_name: &str) -> (Uuid, Uuid) {
+    let revenue_account = create_account(pool, "4000", "Consulting Revenue", AccountType::Revenue).await;
+    let customer = create_customer(pool, customer_name).await;
+    (revenue_account, customer)
+}
+
+/// A draft estimate for 10 hours of design at 100.00 and 20 of build at 50.00. Returns the
+/// estimate and its build line.
+async fn draft_estimate(pool: &PgPool) -> (Uuid, Uuid) {
+    let (revenue_account, customer) = quote_parties(pool, "Quote Customer").await;
+    let estimate = estimate_service().create_estimate(
+        pool, estimate_request("EST-1", customer, Utc::now().date_naive(), revenue_account),
+    ).await.unwrap();
+    (estimate.estimate.id, estimate.line_items[1].id)
+}
+
+/// The draft estimate, sent and accepted
+async fn accepted_estimate(pool: &PgPool) -> (Uuid, Uuid) {
+    let (estimate_id, build_line) = draft_estimate(pool).await;
+    estimate_service().update_estimate_status(pool, estimate_id, EstimateStatus::Sent).await.unwrap();
+    estimate_service().update_estimate_status(pool, estimate_id, EstimateStatus::Accepted).await.unwrap();
+    (estimate_id, build_line)
+}
+
+#[tokio::test]
+async fn test_only_accepted_estimates_convert() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (estimate_id, _) = draft_estimate(pool).await;
+
+    let estimate = estimate_service().get_estimate(pool, estimate_id).await.unwrap();
+    assert_eq!(estimate.estimate.status, EstimateStatus::Draft);
+    assert_eq!(estimate.estimate.total_amount, dec("2000.00"));
+    assert!(estimate_service().convert_to_invoice(pool, estimate_id, convert_request("INV-E-0")).await.is_err());
+}
+
+#[tokio::test]
+async fn test_percent_progress_bill_takes_a_share_of_every_line() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (estimate_id, _) = accepted_estimate(pool).await;
+
+    let first = estimate_service().convert_to_invoice(pool, estimate_id, ConvertEstimateRequest {
+        percent: Some(dec("25")),
+        ..convert_request("INV-E-1")
+    }).await.unwrap();
+    assert_eq!(first.invoice.invoice.total_amount, dec("500.00"));
+    assert_eq!(first.invoice.line_items.len(), 2);
+    assert_eq!(first.invoice.invoice.customer_memo.as_deref(), Some("Phase 1 engagement"));
+    assert_eq!(first.estimate.invoiced_amount, dec("500.00"));
+}
+
+#[tokio::test]
+async fn test_line_progress_bill_cannot_exceed_what_is_left() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (estimate_id, build_line) = accepted_estimate(pool).await;
+
+    assert!(estimate_service().convert_to_invoice(pool, estimate_id, build_request("INV-E-X", build_line, "21")).await.is_err());
+
+    let partial = estimate_service().convert_to_invoice(pool, estimate_id, build_request("INV-E-1", build_line, "5")).await.unwrap();
+    assert_eq!(partial.invoice.invoice.total_amount, dec("250.00"));
+    assert_eq!(partial.invoice.line_items.len(), 1);
+
+    // Only 15 hours of build are left
+    assert!(estimate_service().convert_to_invoice(pool, estimate_id, build_request("INV-E-X", build_line, "16")).await.is_err());
+}
+
+#[tokio::test]
+async fn test_final_conversion_bills_the_remainder() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (estimate_id, build_line) = accepted_estimate(pool).await;
+    estimate_service().convert_to_invoice(pool, estimate_id, build_request("INV-E-1", build_line, "5")).await.unwrap();
+
+    let last = estimate_service().convert_to_invoice(pool, estimate_id, convert_request("INV-E-2")).await.unwrap();
+    assert_eq!(last.invoice.invoice.total_amount, dec("1750.00"));
+    assert_eq!(last.estimate.invoiced_amount, last.estimate.total_amount);
+    assert!(estimate_service().convert_to_invoice(pool, estimate_id, convert_request("INV-E-3")).await.is_err());
+}
+
+#[tokio::test]
+async fn test_estimate_keeps_its_link_to_every_invoice() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (estimate_id, _) = accepted_estimate(pool).await;
+    estimate_service().convert_to_invoice(pool, estimate_id, ConvertEstimateRequest {
+        percent: Some(dec("25")),
+        ..convert_request("INV-E-1")
+    }).await.unwrap();
+    estimate_service().convert_to_invoice(pool, estimate_id, convert_request("INV-E-2")).await.unwrap();
+
+    let estimate = estimate_service().get_estimate(pool, estimate_id).await.unwrap();
+    assert_eq!(estimate.invoices.len(), 2);
+    assert!(estimate.line_items.iter().all(|l| l.invoiced_quantity == l.quantity));
+    let invoice = get_invoice(pool, estimate.invoices[0].invoice_id).await;
+    assert_eq!(invoice.invoice_number, "INV-E-1");
+}
+
+/// A sent estimate issued 60 days ago with a 30-day expiry
+async fn stale_estimate(pool: &PgPool) -> Uuid {
+    let (revenue_account, customer) = quote_parties(pool, "Slow Customer").await;
+    let old_date = Utc::now().date_naive() - Duration::days(60);
+    let stale = estimate_service().create_estimate(pool, estimate_request("EST-OLD", customer, old_date, revenue_account)).await.unwrap();
+    estimate_service().update_estimate_status(pool, stale.estimate.id, EstimateStatus::Sent).await.unwrap();
+    stale.estimate.id
+}
+
+#[tokio::test]
+async fn test_estimate_cannot_be_accepted_past_expiry() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let stale = stale_estimate(pool).await;
+
+    assert!(estimate_service().update_estimate_status(pool, stale, EstimateStatus::Accepted).await.is_err());
+}
+
+#[tokio::test]
+async fn test_expired_estimates_are_final() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let stale = stale_estimate(pool).await;
+
+    let expired = estimate_service().expire_estimates(pool, Utc::now().date_naive()).await.unwrap();
+    assert_eq!(expired.len(), 1);
+    assert_eq!(expired[0].status, EstimateStatus::Expired);
+    assert!(estimate_service().update_estimate_status(pool, stale, EstimateStatus::Sent).await.is_err());
+}
+
+#[tokio::test]
+async fn test_declined_estimates_are_final() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (estimate_id, _) = draft_estimate(pool).await;
+
+    estimate_service().update_estimate_status(pool, estimate_id, EstimateStatus::Declined).await.unwrap();
+    assert!(estimate_service().update_estimate_status(pool, estimate_id, EstimateStatus::Accepted).await.is_err());
+}
+
+#[tokio::test]
+async fn test_estimate_numbers_are_unique() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (revenue_account, customer) = quote_parties(pool, "Quote Customer").await;
+
+    estimate_service().create_estimate(pool, estimate_request("EST-1", customer, Utc::now().date_naive(), revenue_account)).await.unwrap();
+    assert!(estimate_service().create_estimate(
+        pool, estimate_request("EST-1", customer, Utc::now().date_naive(), revenue_account),
+    ).await.is_err());
+}
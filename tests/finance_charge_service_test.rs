@@ -0,0 +1,160 @@
+use ledger_forge::models::{
+    AccountType, CreateInvoiceRequest, InvoiceStatus, UpdateFinanceChargeSettingsRequest,
+};
+use sqlx::PgPool;
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+mod common;
+use common::test_db::TestDb;
+use common::fixtures::{date, dec, create_account, create_customer, create_sent_invoice, balance, Receivables};
+use common::services::{finance_charge_service, invoice_service};
+
+/// 2% a month with a 5.00 minimum and 10 grace days
+fn settings(interest_account: Uuid, ar_account: Uuid, compound: bool) -> UpdateFinanceChargeSettingsRequest {
+    UpdateFinanceChargeSettingsRequest {
+        company_id: None,
+        monthly_rate: dec("2"),
+        minimum_charge: dec("5.00"),
+        grace_days: 10,
+        compound,
+        income_account_id: interest_account,
+        ar_account_id: ar_account,
+    }
+}
+
+struct OverdueCustomers {
+    ar_account: Uuid,
+    interest_account: Uuid,
+    large: Uuid,
+    exempt: Uuid,
+    small: Uuid,
+    /// 1000.00 due 2025-09-30
+    large_invoice: Uuid,
+}
+
+/// Simple-interest settings and three customers with invoices due 2025-09-30: a large
+/// balance, an exempt customer and a small balance. The large customer also has an
+/// invoice due 2025-10-25.
+async fn overdue_customers(pool: &PgPool) -> OverdueCustomers {
+    let receivables = Receivables::create(pool).await;
+    let interest_account = create_account(pool, "4900", "Interest Income", AccountType::Revenue).await;
+    finance_charge_service().update_settings(pool, settings(interest_account, receivables.ar_account, false)).await.unwrap();
+    // Issued 2025-09-01
+    let invoice = |number: &str, customer_id: Uuid, amount: &str, due_date: NaiveDate| create_sent_invoice(pool, CreateInvoiceRequest {
+        invoice_date: date(2025, 9, 1),
+        due_date,
+        ..receivables.invoice_request(number, customer_id, amount)
+    });
+
+    let large = create_customer(pool, "Large Balance Ltd").await;
+    let exempt = create_customer(pool, "Exempt Charity").await;
+    let small = create_customer(pool, "Small Balance Inc").await;
+
+    let large_invoice = invoice("INV-FC-1", large, "1000.00", date(2025, 9, 30)).await;
+    invoice("INV-FC-2", exempt, "500.00", date(2025, 9, 30)).await;
+    invoice("INV-FC-3", small, "50.00", date(2025, 9, 30)).await;
+    invoice("INV-FC-4", large, "800.00", date(2025, 10, 25)).await;
+    assert!(finance_charge_service().set_exempt(pool, exempt, true).await.unwrap().finance_charge_exempt);
+
+    OverdueCustomers { ar_account: receivables.ar_account, interest_account, large, exempt, small, large_invoice }
+}
+
+#[tokio::test]
+async fn test_run_needs_finance_charge_settings() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+
+    assert!(finance_charge_service().run(pool, date(2025, 10, 30), None, true).await.is_err());
+}
+
+#[tokio::test]
+async fn test_preview_charges_past_grace_invoices_of_non_exempt_customers() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let overdue = overdue_customers(pool).await;
+
+    // 30 days at 2% per month: 1000 x 0.24 / 365 x 30 = 19.73; INV-FC-4 is still in its grace days
+    let preview = finance_charge_service().run(pool, date(2025, 10, 30), None, true).await.unwrap();
+    assert!(preview.preview);
+    assert_eq!(preview.customers.len(), 2);
+    assert!(preview.customers.iter().all(|c| c.customer_id != overdue.exempt));
+    let large_charge = preview.customers.iter().find(|c| c.customer_id == overdue.large).unwrap();
+    assert_eq!(large_charge.lines.len(), 1);
+    assert_eq!(large_charge.lines[0].invoice_id, overdue.large_invoice);
+    assert_eq!(large_charge.lines[0].days, 30);
+    assert_eq!(large_charge.total, dec("19.73"));
+    assert!(large_charge.finance_charge_invoice_id.is_none());
+    assert_eq!(preview.total, dec("24.73"));
+
+    // Nothing is recorded
+    assert!(finance_charge_service().list_finance_charges(pool, None, None).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_small_balances_pay_the_minimum_charge() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let overdue = overdue_customers(pool).await;
+
+    let preview = finance_charge_service().run(pool, date(2025, 10, 30), None, true).await.unwrap();
+    let small_charge = preview.customers.iter().find(|c| c.customer_id == overdue.small).unwrap();
+    assert_eq!(small_charge.interest, dec("0.99"));
+    assert!(small_charge.minimum_applied);
+    assert_eq!(small_charge.total, dec("5.00"));
+}
+
+#[tokio::test]
+async fn test_committed_run_bills_each_customer_on_a_posted_invoice() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let overdue = overdue_customers(pool).await;
+
+    let run = finance_charge_service().run(pool, date(2025, 10, 30), None, false).await.unwrap();
+    assert_eq!(run.total, dec("24.73"));
+    let fc_invoice_id = run.customers.iter().find(|c| c.customer_id == overdue.small).unwrap()
+        .finance_charge_invoice_id.unwrap();
+    let fc_invoice = invoice_service()
+        .get_invoice(pool, fc_invoice_id).await.unwrap().unwrap();
+    assert_eq!(fc_invoice.invoice.status, InvoiceStatus::Sent);
+    assert_eq!(fc_invoice.invoice.total_amount, dec("5.00"));
+    assert_eq!(fc_invoice.line_items.len(), 2);
+    assert_eq!(fc_invoice.invoice.due_date, date(2025, 11, 29));
+
+    assert_eq!(balance(pool, overdue.interest_account).await, dec("-24.73"));
+}
+
+#[tokio::test]
+async fn test_same_period_is_never_charged_twice() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    overdue_customers(pool).await;
+    finance_charge_service().run(pool, date(2025, 10, 30), None, false).await.unwrap();
+
+    let again = finance_charge_service().run(pool, date(2025, 10, 30), None, false).await.unwrap();
+    assert!(again.customers.is_empty());
+}
+
+#[tokio::test]
+async fn test_compounding_adds_unpaid_finance_charges_to_the_base() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let overdue = overdue_customers(pool).await;
+    finance_charge_service().run(pool, date(2025, 10, 30), None, false).await.unwrap();
+    finance_charge_service().update_settings(pool, settings(overdue.interest_account, overdue.ar_account, true)).await.unwrap();
+
+    // 1019.73 x 0.24 / 365 x 30 = 20.12
+    let compounded = finance_charge_service().run(pool, date(2025, 11, 29), None, false).await.unwrap();
+    let large_charge = compounded.customers.iter().find(|c| c.customer_id == overdue.large).unwrap();
+    let first = large_charge.lines.iter().find(|l| l.invoice_id == overdue.large_invoice).unwrap();
+    assert_eq!(first.period_start, date(2025, 10, 30));
+    assert_eq!(first.base_amount, dec("1019.73"));
+    assert_eq!(first.amount, dec("20.12"));
+    // The invoice that was in its grace period is now charged from its due date
+    assert_eq!(large_charge.lines.len(), 2);
+    assert!(compounded.customers.iter().all(|c| c.customer_id != overdue.exempt));
+
+    let history = finance_charge_service().list_finance_charges(pool, Some(overdue.large), None).await.unwrap();
+    assert_eq!(history.len(), 3);
+    assert_eq!(finance_charge_service().list_finance_charges(pool, None, Some(overdue.large_invoice)).await.unwrap().len(), 2);
+}
@@ -4,7 +4,7 @@ use serde_json::Value;
 mod common;
 use common::{setup_test_db, cleanup_test_db, TEST_JWT_SECRET};
 
-use ledger_forge::services::{AuthService, AccountService, TransactionService, ContactService, InvoiceService, PaymentService, BillService, ImportService, ReportingService, CacheService};
+use ledger_forge::services::{AuthService, AccountService, TransactionService, ContactService, InvoiceService, PaymentService, BillService, ImportService, ReportingService, InventoryService, TaxService, CreditMemoService, StatementService, EstimateService, PurchaseOrderService, VendorCreditService, EmailService, SmtpConfig, DunningService, FinanceChargeService, WriteOffService, NumberingService, BillableExpenseService, DimensionService, CompanyService, CacheService};
 use ledger_forge::routes::create_routes;
 
 #[tokio::test]
@@ -20,7 +20,22 @@ async fn test_health_endpoint_returns_200() {
     let bill_service = BillService::new_with_cache(cache_service.clone());
     let import_service = ImportService::new(cache_service.clone());
     let reporting_service = ReportingService::new_with_cache(cache_service.clone());
-    let app = create_routes(pool, auth_service, account_service, transaction_service, contact_service, invoice_service, payment_service, bill_service, import_service, reporting_service, cache_service);
+    let inventory_service = InventoryService::new_with_cache(cache_service.clone());
+    let tax_service = TaxService::new_with_cache(cache_service.clone());
+    let credit_memo_service = CreditMemoService::new_with_cache(cache_service.clone());
+    let statement_service = StatementService::new_with_cache(cache_service.clone());
+    let estimate_service = EstimateService::new_with_cache(cache_service.clone());
+    let purchase_order_service = PurchaseOrderService::new_with_cache(cache_service.clone());
+    let vendor_credit_service = VendorCreditService::new_with_cache(cache_service.clone());
+    let email_service = EmailService::new(SmtpConfig::from_env(), cache_service.clone());
+    let dunning_service = DunningService::new(email_service.clone(), cache_service.clone());
+    let finance_charge_service = FinanceChargeService::new_with_cache(cache_service.clone());
+    let write_off_service = WriteOffService::new_with_cache(cache_service.clone());
+    let numbering_service = NumberingService::new();
+    let billable_expense_service = BillableExpenseService::new_with_cache(cache_service.clone());
+    let dimension_service = DimensionService::new_with_cache(cache_service.clone());
+    let company_service = CompanyService::new_with_cache(cache_service.clone());
+    let app = create_routes(pool, auth_service, account_service, transaction_service, contact_service, invoice_service, payment_service, bill_service, import_service, reporting_service, inventory_service, tax_service, credit_memo_service, statement_service, estimate_service, purchase_order_service, vendor_credit_service, email_service, dunning_service, finance_charge_service, write_off_service, numbering_service, billable_expense_service, dimension_service, company_service, cache_service);
     let server = TestServer::new(app).unwrap();
 
     let response = server.get("/api/v1/health").await;
@@ -44,7 +59,22 @@ async fn test_health_endpoint_checks_database() {
     let bill_service = BillService::new_with_cache(cache_service.clone());
     let import_service = ImportService::new(cache_service.clone());
     let reporting_service = ReportingService::new_with_cache(cache_service.clone());
-    let app = create_routes(pool, auth_service, account_service, transaction_service, contact_service, invoice_service, payment_service, bill_service, import_service, reporting_service, cache_service);
+    let inventory_service = InventoryService::new_with_cache(cache_service.clone());
+    let tax_service = TaxService::new_with_cache(cache_service.clone());
+    let credit_memo_service = CreditMemoService::new_with_cache(cache_service.clone());
+    let statement_service = StatementService::new_with_cache(cache_service.clone());
+    let estimate_service = EstimateService::new_with_cache(cache_service.clone());
+    let purchase_order_service = PurchaseOrderService::new_with_cache(cache_service.clone());
+    let vendor_credit_service = VendorCreditService::new_with_cache(cache_service.clone());
+    let email_service = EmailService::new(SmtpConfig::from_env(), cache_service.clone());
+    let dunning_service = DunningService::new(email_service.clone(), cache_service.clone());
+    let finance_charge_service = FinanceChargeService::new_with_cache(cache_service.clone());
+    let write_off_service = WriteOffService::new_with_cache(cache_service.clone());
+    let numbering_service = NumberingService::new();
+    let billable_expense_service = BillableExpenseService::new_with_cache(cache_service.clone());
+    let dimension_service = DimensionService::new_with_cache(cache_service.clone());
+    let company_service = CompanyService::new_with_cache(cache_service.clone());
+    let app = create_routes(pool, auth_service, account_service, transaction_service, contact_service, invoice_service, payment_service, bill_service, import_service, reporting_service, inventory_service, tax_service, credit_memo_service, statement_service, estimate_service, purchase_order_service, vendor_credit_service, email_service, dunning_service, finance_charge_service, write_off_service, numbering_service, billable_expense_service, dimension_service, company_service, cache_service);
     let server = TestServer::new(app).unwrap();
 
     let response = server.get("/api/v1/health").await;
@@ -0,0 +1,337 @@
+use ledger_forge::models::{
+    CreateAccountRequest, AccountType, ContactType, CreateItemRequest, ItemType,
+    CreateBillRequest, CreateBillLineItemRequest, CreateInvoiceRequest, CreateInvoiceLineItemRequest,
+    CreateStockAdjustmentRequest, InventoryCostingMethod, Invoice, InvoiceStatus, DateRequest, Item,
+};
+use ledger_forge::services::{InventoryService, BillService, InvoiceService, TransactionService};
+use ledger_forge::utils::Result;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+mod common;
+use common::test_db::TestDb;
+use common::fixtures::{date, dec, contact_request};
+use common::services::{
+    account_service, bill_service, contact_service, inventory_service, invoice_service, transaction_service,
+};
+
+struct Fixture {
+    inventory_service: InventoryService,
+    bill_service: BillService,
+    invoice_service: InvoiceService,
+    transaction_service: TransactionService,
+    company_id: Uuid,
+    vendor_id: Uuid,
+    customer_id: Uuid,
+    inventory_account_id: Uuid,
+    cogs_account_id: Uuid,
+    revenue_account_id: Uuid,
+    shrinkage_account_id: Uuid,
+    payables_account_id: Uuid,
+    item: Item,
+}
+
+async fn setup(pool: &PgPool, method: InventoryCostingMethod) -> Fixture {
+    let company_id: Uuid = sqlx::query_scalar("INSERT INTO companies (name) VALUES ('Test Co') RETURNING id")
+        .fetch_one(pool)
+        .await
+        .unwrap();
+
+    // Everything in the fixture belongs to the company
+    let inventory_service = inventory_service().for_company(Some(company_id));
+    let bill_service = bill_service().for_company(Some(company_id));
+    let invoice_service = invoice_service().for_company(Some(company_id));
+    let transaction_service = transaction_service().for_company(Some(company_id));
+    let contact_service = contact_service().for_company(Some(company_id));
+    let account_service = account_service().for_company(Some(company_id));
+    inventory_service.set_costing_method(pool, company_id, method).await.unwrap();
+
+    let mut account_ids = Vec::new();
+    for (code, name, account_type) in [
+        ("1300", "Inventory", AccountType::Asset),
+        ("5000", "Cost of Goods Sold", AccountType::Expense),
+        ("4000", "Sales Revenue", AccountType::Revenue),
+        ("5100", "Inventory Shrinkage", AccountType::Expense),
+        ("2000", "Accounts Payable", AccountType::Liability),
+    ] {
+        let account = account_service.create_account(pool, CreateAccountRequest {
+            code: code.to_string(),
+            name: name.to_string(),
+            account_type,
+            parent_account_id: None,
+            company_id: None,
+        }).await.unwrap();
+        account_ids.push(account.id);
+    }
+
+    let vendor = contact_service.create_contact(pool, contact_request(ContactType::Vendor, "Widget Supplier")).await.unwrap();
+
+    let customer = contact_service.create_contact(pool, contact_request(ContactType::Customer, "Widget Buyer")).await.unwrap();
+
+    let item = inventory_service.create_item(pool, CreateItemRequest {
+        name: "Widget".to_string(),
+        sku: Some("WID-001".to_string()),
+        item_type: ItemType::Inventory,
+        description: None,
+        unit_price: Some(dec("20.00")),
+        purchase_cost: None,
+        income_account_id: Some(account_ids[2]),
+        expense_account_id: Some(account_ids[1]),
+        asset_account_id: Some(account_ids[0]),
+        taxable: None,
+        company_id: Some(company_id),
+    }).await.unwrap();
+
+    Fixture {
+        inventory_service,
+        bill_service,
+        invoice_service,
+        transaction_service,
+        company_id,
+        vendor_id: vendor.id,
+        customer_id: customer.id,
+        inventory_account_id: account_ids[0],
+        cogs_account_id: account_ids[1],
+        revenue_account_id: account_ids[2],
+        shrinkage_account_id: account_ids[3],
+        payables_account_id: account_ids[4],
+        item,
+    }
+}
+
+async fn receive(pool: &PgPool, f: &Fixture, day: u32, quantity: &str, amount: &str) {
+    f.bill_service.create_bill(pool, CreateBillRequest {
+        bill_number: None,
+        vendor_id: f.vendor_id,
+        bill_date: date(2025, 10, day),
+        due_date: date(2025, 11, day),
+        memo: None,
+        company_id: Some(f.company_id),
+        ap_account_id: Some(f.payables_account_id),
+        line_items: vec![CreateBillLineItemRequest {
+            line_number: 1,
+            description: Some("Widgets".to_string()),
+            amount: dec(amount),
+            // Stock goes to the item's inventory account whatever the line names
+            expense_account_id: f.cogs_account_id,
+            billable: Some(false),
+            customer_id: None,
+            item_id: Some(f.item.id),
+            quantity: Some(dec(quantity)),
+            tax_code: None,
+            dimension_value_ids: Vec::new(),
+        }],
+    }).await.unwrap();
+}
+
+fn sale_request(f: &Fixture, number: &str, day: u32, quantity: &str) -> CreateInvoiceRequest {
+    CreateInvoiceRequest {
+        invoice_number: Some(number.to_string()),
+        customer_id: f.customer_id,
+        invoice_date: date(2025, 10, day),
+        due_date: date(2025, 11, day),
+        ship_date: None,
+        customer_memo: None,
+        billing_address: None,
+        shipping_address: None,
+        company_id: Some(f.company_id),
+        ar_account_id: None,
+        line_items: vec![CreateInvoiceLineItemRequest {
+            line_number: 1,
+            item_description: "Widgets".to_string(),
+            quantity: dec(quantity),
+            unit_price: dec("20.00"),
+            discount_percent: None,
+            tax_code: None,
+            revenue_account_id: f.revenue_account_id,
+            item_id: Some(f.item.id),
+            dimension_value_ids: Vec::new(),
+        }],
+    }
+}
+
+/// Create an invoice and send it, which is when its stock leaves
+async fn sell(pool: &PgPool, f: &Fixture, number: &str, day: u32, quantity: &str) -> Result<Invoice> {
+    let invoice = f.invoice_service.create_invoice(pool, sale_request(f, number, day, quantity)).await?;
+    f.invoice_service.update_invoice_status(pool, invoice.invoice.id, InvoiceStatus::Sent).await
+}
+
+async fn balance(pool: &PgPool, f: &Fixture, account_id: Uuid) -> Decimal {
+    f.transaction_service.get_account_balance(pool, account_id).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_fifo_cost_of_goods_sold() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let f = setup(pool, InventoryCostingMethod::Fifo).await;
+
+    receive(pool, &f, 1, "10", "50.00").await;
+    receive(pool, &f, 2, "10", "70.00").await;
+
+    // 10 @ 5.00 from the first layer plus 5 @ 7.00 from the second
+    sell(pool, &f, "INV-1", 3, "15").await.unwrap();
+
+    assert_eq!(balance(pool, &f, f.cogs_account_id).await, dec("85.00"));
+
+    let item = f.inventory_service.get_item(pool, f.item.id).await.unwrap();
+    assert_eq!(item.quantity_on_hand.unwrap(), Decimal::from(5));
+
+    let layers = f.inventory_service.list_cost_layers(pool, f.item.id).await.unwrap();
+    // Only the second layer is still open
+    assert_eq!(layers.len(), 1);
+    assert_eq!(layers[0].quantity_remaining, Decimal::from(5));
+    assert_eq!(layers[0].unit_cost, dec("7.00"));
+}
+
+#[tokio::test]
+async fn test_average_cost_of_goods_sold() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let f = setup(pool, InventoryCostingMethod::Average).await;
+
+    receive(pool, &f, 1, "10", "50.00").await;
+    receive(pool, &f, 2, "10", "70.00").await;
+
+    // Average cost is 120.00 / 20 = 6.00
+    sell(pool, &f, "INV-1", 3, "15").await.unwrap();
+
+    assert_eq!(balance(pool, &f, f.cogs_account_id).await, dec("90.00"));
+
+    let report = f.inventory_service
+        .generate_valuation_report(pool, DateRequest { as_of_date: date(2025, 10, 31) })
+        .await
+        .unwrap();
+    assert_eq!(report.total_value, dec("30.00"));
+    assert_eq!(report.entries[0].quantity_on_hand, Decimal::from(5));
+}
+
+#[tokio::test]
+async fn test_sale_exceeding_stock_is_rejected() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let f = setup(pool, InventoryCostingMethod::Fifo).await;
+
+    receive(pool, &f, 1, "5", "25.00").await;
+
+    let invoice = f.invoice_service.create_invoice(pool, sale_request(&f, "INV-1", 3, "6")).await.unwrap();
+    let result = f.invoice_service.update_invoice_status(pool, invoice.invoice.id, InvoiceStatus::Sent).await;
+    assert!(result.is_err());
+
+    // Nothing was relieved and the invoice is still a draft
+    let item = f.inventory_service.get_item(pool, f.item.id).await.unwrap();
+    assert_eq!(item.quantity_on_hand.unwrap(), Decimal::from(5));
+    let invoice = f.invoice_service.get_invoice(pool, invoice.invoice.id).await.unwrap().unwrap();
+    assert_eq!(invoice.invoice.status, InvoiceStatus::Draft);
+}
+
+#[tokio::test]
+async fn test_stock_adjustment_posts_to_adjustment_account() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let f = setup(pool, InventoryCostingMethod::Fifo).await;
+
+    receive(pool, &f, 1, "10", "50.00").await;
+
+    let movement = f.inventory_service.create_stock_adjustment(pool, CreateStockAdjustmentRequest {
+        item_id: f.item.id,
+        adjustment_date: date(2025, 10, 5),
+        quantity_change: Decimal::from(-2),
+        unit_cost: None,
+        adjustment_account_id: f.shrinkage_account_id,
+        memo: Some("Damaged in storage".to_string()),
+    }, None).await.unwrap();
+
+    assert_eq!(movement.total_cost, dec("-10.00"));
+    assert!(movement.transaction_id.is_some());
+
+    let shrinkage = f.transaction_service.get_account_balance(pool, f.shrinkage_account_id).await.unwrap();
+    assert_eq!(shrinkage, dec("10.00"));
+
+    assert_eq!(balance(pool, &f, f.inventory_account_id).await, dec("40.00"));
+}
+
+#[tokio::test]
+async fn test_void_invoice_returns_stock() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let f = setup(pool, InventoryCostingMethod::Fifo).await;
+
+    receive(pool, &f, 1, "10", "50.00").await;
+    let invoice = sell(pool, &f, "INV-1", 3, "4").await.unwrap();
+    assert_eq!(balance(pool, &f, f.cogs_account_id).await, dec("20.00"));
+
+    f.invoice_service.update_invoice_status(pool, invoice.id, InvoiceStatus::Void).await.unwrap();
+
+    let item = f.inventory_service.get_item(pool, f.item.id).await.unwrap();
+    assert_eq!(item.quantity_on_hand.unwrap(), Decimal::from(10));
+
+    assert_eq!(balance(pool, &f, f.cogs_account_id).await, Decimal::ZERO);
+    assert_eq!(balance(pool, &f, f.inventory_account_id).await, dec("50.00"));
+}
+
+#[tokio::test]
+async fn test_purchases_are_capitalized_and_expensed_when_sold() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let f = setup(pool, InventoryCostingMethod::Fifo).await;
+
+    receive(pool, &f, 1, "10", "50.00").await;
+
+    // The bill line named the COGS account, but the cost waits in inventory
+    assert_eq!(balance(pool, &f, f.inventory_account_id).await, dec("50.00"));
+    assert_eq!(balance(pool, &f, f.cogs_account_id).await, Decimal::ZERO);
+    assert_eq!(balance(pool, &f, f.payables_account_id).await, dec("-50.00"));
+
+    sell(pool, &f, "INV-1", 3, "4").await.unwrap();
+
+    assert_eq!(balance(pool, &f, f.inventory_account_id).await, dec("30.00"));
+    assert_eq!(balance(pool, &f, f.cogs_account_id).await, dec("20.00"));
+}
+
+#[tokio::test]
+async fn test_draft_invoices_leave_stock_alone() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let f = setup(pool, InventoryCostingMethod::Fifo).await;
+
+    receive(pool, &f, 1, "10", "50.00").await;
+    let draft = f.invoice_service.create_invoice(pool, sale_request(&f, "INV-1", 3, "4")).await.unwrap();
+
+    let item = f.inventory_service.get_item(pool, f.item.id).await.unwrap();
+    assert_eq!(item.quantity_on_hand.unwrap(), Decimal::from(10));
+    assert_eq!(balance(pool, &f, f.cogs_account_id).await, Decimal::ZERO);
+
+    // Voiding a draft has no stock to return
+    f.invoice_service.update_invoice_status(pool, draft.invoice.id, InvoiceStatus::Void).await.unwrap();
+    let item = f.inventory_service.get_item(pool, f.item.id).await.unwrap();
+    assert_eq!(item.quantity_on_hand.unwrap(), Decimal::from(10));
+    assert_eq!(balance(pool, &f, f.inventory_account_id).await, dec("50.00"));
+}
+
+#[tokio::test]
+async fn test_average_cost_keeps_purchase_layers() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let f = setup(pool, InventoryCostingMethod::Average).await;
+
+    // 3.3333... each, which doesn't round to cents
+    receive(pool, &f, 1, "3", "10.00").await;
+    for (number, day) in [("INV-1", 2), ("INV-2", 3), ("INV-3", 4)] {
+        sell(pool, &f, number, day, "1").await.unwrap();
+    }
+
+    // Selling everything expenses exactly what it cost
+    assert_eq!(balance(pool, &f, f.cogs_account_id).await, dec("10.00"));
+    assert_eq!(balance(pool, &f, f.inventory_account_id).await, Decimal::ZERO);
+
+    let layers = sqlx::query_as::<_, (Decimal, Decimal)>(
+        "SELECT quantity_received, unit_cost FROM inventory_cost_layers WHERE item_id = $1"
+    )
+    .bind(f.item.id)
+    .fetch_all(pool)
+    .await
+    .unwrap();
+    assert_eq!(layers, vec![(Decimal::from(3), dec("3.3333"))]);
+}
@@ -0,0 +1,148 @@
+use ledger_forge::models::{
+    AccountType, ContactType, CreateContactRequest, SetupCanadianTaxCodesRequest, CreateInvoiceRequest,
+    CreateInvoiceLineItemRequest, UpdateInvoiceTemplateRequest,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+mod common;
+use common::test_db::TestDb;
+use common::fixtures::{date, dec, create_account, contact_request, create_contact};
+use common::services::{invoice_service, tax_service};
+
+fn contains(haystack: &[u8], needle: &str) -> bool {
+    haystack.windows(needle.len()).any(|window| window == needle.as_bytes())
+}
+
+/// Smallest JPEG header the renderer accepts: SOI and a 32x16 RGB baseline frame
+const LOGO_BASE64: &str = "/9j/wAARCAAQACADAREAAhEBAxEB/9k=";
+
+fn template_request() -> UpdateInvoiceTemplateRequest {
+    UpdateInvoiceTemplateRequest {
+        company_id: None,
+        business_name: Some("Maple Consulting Ltd.".to_string()),
+        address: Some("100 King St W\nToronto ON M5X 1A9".to_string()),
+        phone: Some("416-555-0100".to_string()),
+        email: Some("billing@maple.example".to_string()),
+        tax_registration_number: Some("123456789 RT0001".to_string()),
+        payment_instructions: Some("E-transfer to billing@maple.example".to_string()),
+        footer: Some("Thank you for your business".to_string()),
+        logo_base64: Some(LOGO_BASE64.to_string()),
+        remove_logo: false,
+    }
+}
+
+/// A discounted, HST-taxed invoice with a memo and separate billing and shipping addresses
+async fn printed_invoice(pool: &PgPool) -> Uuid {
+    let revenue_account = create_account(pool, "4000", "Consulting Revenue", AccountType::Revenue).await;
+    let hst_account = create_account(pool, "2200", "GST/HST Payable", AccountType::Liability).await;
+
+    tax_service().setup_canadian_tax_codes(pool, SetupCanadianTaxCodesRequest {
+        gst_hst_account_id: hst_account,
+        itc_account_id: None,
+        pst_account_id: None,
+        qst_account_id: None,
+        effective_from: date(2025, 4, 1),
+        company_id: None,
+    }).await.unwrap();
+
+    let customer = create_contact(pool, CreateContactRequest {
+        billing_address: Some("1 Front St\nOttawa ON".to_string()),
+        ..contact_request(ContactType::Customer, "Printed Customer")
+    }).await;
+
+    invoice_service().create_invoice(pool, CreateInvoiceRequest {
+        invoice_number: Some("INV-PDF-1".to_string()),
+        customer_id: customer,
+        invoice_date: date(2025, 10, 1),
+        due_date: date(2025, 10, 31),
+        ship_date: None,
+        customer_memo: Some("Project Aurora, phase one".to_string()),
+        billing_address: Some("1 Front St\nOttawa ON".to_string()),
+        shipping_address: Some("2 Queen St\nOttawa ON".to_string()),
+        company_id: None,
+        ar_account_id: None,
+        line_items: vec![CreateInvoiceLineItemRequest {
+            line_number: 1,
+            item_description: "Advisory services".to_string(),
+            quantity: dec("10"),
+            unit_price: dec("150.00"),
+            discount_percent: Some(dec("10")),
+            tax_code: Some("HST-ON".to_string()),
+            revenue_account_id: revenue_account,
+            item_id: None,
+            dimension_value_ids: Vec::new(),
+        }],
+    }).await.unwrap().invoice.id
+}
+
+#[tokio::test]
+async fn test_invoice_renders_without_a_template() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let invoice_id = printed_invoice(pool).await;
+
+    let plain = invoice_service().render_invoice_pdf(pool, invoice_id).await.unwrap();
+    assert!(plain.starts_with(b"%PDF"));
+    assert!(!contains(&plain, "DCTDecode"));
+}
+
+#[tokio::test]
+async fn test_invalid_logo_is_rejected() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+
+    let bad_logo = UpdateInvoiceTemplateRequest {
+        logo_base64: Some("aGVsbG8=".to_string()),
+        ..template_request()
+    };
+    assert!(invoice_service().update_invoice_template(pool, bad_logo).await.is_err());
+}
+
+#[tokio::test]
+async fn test_invoice_pdf_uses_company_template() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let invoice_id = printed_invoice(pool).await;
+
+    let template = invoice_service().update_invoice_template(pool, template_request()).await.unwrap();
+    assert!(template.logo.is_some());
+
+    let pdf = invoice_service().render_invoice_pdf(pool, invoice_id).await.unwrap();
+    assert!(pdf.starts_with(b"%PDF"));
+    assert!(contains(&pdf, "DCTDecode"));
+    assert!(contains(&pdf, "Maple Consulting Ltd."));
+    assert!(contains(&pdf, "Invoice INV-PDF-1"));
+    assert!(contains(&pdf, "2 Queen St"));
+    assert!(contains(&pdf, "10%"));
+    assert!(contains(&pdf, "HST 13%"));
+    assert!(contains(&pdf, "1,525.50"));
+    assert!(contains(&pdf, "Project Aurora, phase one"));
+    assert!(contains(&pdf, "E-transfer to billing@maple.example"));
+}
+
+#[tokio::test]
+async fn test_saving_again_keeps_the_logo_unless_removed() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let template = invoice_service().update_invoice_template(pool, template_request()).await.unwrap();
+
+    let kept = invoice_service().update_invoice_template(pool, UpdateInvoiceTemplateRequest {
+        logo_base64: None,
+        footer: None,
+        ..template_request()
+    }).await.unwrap();
+    assert_eq!(kept.id, template.id);
+    assert!(kept.logo.is_some());
+    assert!(kept.footer.is_none());
+
+    let removed = invoice_service().update_invoice_template(pool, UpdateInvoiceTemplateRequest {
+        logo_base64: None,
+        remove_logo: true,
+        ..template_request()
+    }).await.unwrap();
+    assert!(removed.logo.is_none());
+
+    let fetched = invoice_service().get_invoice_template(pool, None).await.unwrap();
+    assert_eq!(fetched.business_name.as_deref(), Some("Maple Consulting Ltd."));
+}
@@ -0,0 +1,256 @@
+use chrono::{DateTime, Duration, Utc};
+use ledger_forge::models::{
+    CreateUserRequest, DisableMfaRequest, LoginRequest, MfaCodeRequest, MfaLoginRequest, MfaPolicy, User, UserRole,
+};
+use ledger_forge::services::auth::LoginOutcome;
+use ledger_forge::services::{totp, AuthService};
+use ledger_forge::utils::AppError;
+use sqlx::PgPool;
+
+mod common;
+use common::test_db::TestDb;
+
+async fn create_user(pool: &PgPool, auth_service: &AuthService, username: &str, role: UserRole) -> User {
+    auth_service.register(pool, CreateUserRequest {
+        username: username.to_string(),
+        email: format!("{}@example.com", username),
+        password: "SecurePass123".to_string(),
+        role,
+    }).await.unwrap()
+}
+
+async fn login(pool: &PgPool, auth_service: &AuthService, username: &str) -> LoginOutcome {
+    auth_service.login(pool, LoginRequest {
+        username: username.to_string(),
+        password: "SecurePass123".to_string(),
+    }).await.unwrap()
+}
+
+fn code(code: &str) -> MfaCodeRequest {
+    MfaCodeRequest { code: code.to_string() }
+}
+
+/// Log in with the password, returning the two-factor challenge token
+async fn challenge(pool: &PgPool, auth_service: &AuthService, username: &str) -> String {
+    let LoginOutcome::MfaRequired(challenge) = login(pool, auth_service, username).await else {
+        panic!("expected a two-factor challenge");
+    };
+    challenge.challenge_token
+}
+
+fn verify(challenge_token: &str, code: &str) -> MfaLoginRequest {
+    MfaLoginRequest {
+        challenge_token: challenge_token.to_string(),
+        code: code.to_string(),
+    }
+}
+
+/// Set up two-factor authentication, returning the secret and recovery codes
+async fn enroll(pool: &PgPool, auth_service: &AuthService, user: &User) -> (String, Vec<String>) {
+    let setup = auth_service.begin_mfa_enrollment(pool, user).await.unwrap();
+    let current = totp::code_at(&setup.secret, Utc::now()).unwrap();
+    let recovery_codes = auth_service.confirm_mfa_enrollment(pool, user.id, code(&current)).await.unwrap();
+    (setup.secret, recovery_codes)
+}
+
+#[test]
+fn test_totp_codes() {
+    // RFC 6238 test vectors for SHA-1, truncated to six digits
+    let secret = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+    let at = |seconds: i64| DateTime::<Utc>::from_timestamp(seconds, 0).unwrap();
+    assert_eq!(totp::code_at(secret, at(59)).unwrap(), "287082");
+    assert_eq!(totp::code_at(secret, at(1111111109)).unwrap(), "081804");
+    assert_eq!(totp::code_at(secret, at(1234567890)).unwrap(), "005924");
+
+    // One step of clock drift either way is accepted
+    let now = at(1234567890);
+    let previous = totp::code_at(secret, now - Duration::seconds(30)).unwrap();
+    assert_eq!(totp::matching_step(secret, &previous, now).unwrap(), Some(totp::time_step(now) - 1));
+    let stale = totp::code_at(secret, now - Duration::seconds(90)).unwrap();
+    assert_eq!(totp::matching_step(secret, &stale, now).unwrap(), None);
+
+    let uri = totp::provisioning_uri(secret, "LedgerForge", "jane doe");
+    assert!(uri.starts_with("otpauth://totp/LedgerForge:jane%20doe?"));
+    assert!(uri.contains(&format!("secret={}", secret)));
+    assert!(uri.contains("issuer=LedgerForge"));
+}
+
+#[tokio::test]
+async fn test_mfa_enrollment_and_login() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let auth_service = AuthService::new("test-secret-key".to_string());
+    let user = create_user(pool, &auth_service, "careful", UserRole::Accountant).await;
+
+    assert!(matches!(login(pool, &auth_service, "careful").await, LoginOutcome::Authenticated(_)));
+    assert!(matches!(
+        auth_service.confirm_mfa_enrollment(pool, user.id, code("123456")).await,
+        Err(AppError::ValidationError(_))
+    ));
+
+    // Nothing changes until the secret is confirmed
+    let setup = auth_service.begin_mfa_enrollment(pool, &user).await.unwrap();
+    assert!(setup.provisioning_uri.contains(&setup.secret));
+    assert!(matches!(login(pool, &auth_service, "careful").await, LoginOutcome::Authenticated(_)));
+    let wrong = if totp::code_at(&setup.secret, Utc::now()).unwrap() == "000000" { "111111" } else { "000000" };
+    assert!(matches!(
+        auth_service.confirm_mfa_enrollment(pool, user.id, code(wrong)).await,
+        Err(AppError::Unauthorized(_))
+    ));
+
+    // Starting again replaces the unconfirmed secret
+    let secret = auth_service.begin_mfa_enrollment(pool, &user).await.unwrap().secret;
+    assert_ne!(secret, setup.secret);
+    let confirmed_code = totp::code_at(&secret, Utc::now()).unwrap();
+    let recovery_codes = auth_service.confirm_mfa_enrollment(pool, user.id, code(&confirmed_code)).await.unwrap();
+    assert_eq!(recovery_codes.len(), 10);
+    assert!(matches!(
+        auth_service.begin_mfa_enrollment(pool, &user).await,
+        Err(AppError::Conflict(_))
+    ));
+
+    let status = auth_service.mfa_status(pool, &user).await.unwrap();
+    assert!(status.enabled);
+    assert!(!status.required);
+    assert_eq!(status.recovery_codes_left, 10);
+
+    // Only the hashes of recovery codes are stored
+    let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM mfa_recovery_codes WHERE code_hash = $1")
+        .bind(&recovery_codes[0])
+        .fetch_one(pool)
+        .await
+        .unwrap();
+    assert_eq!(stored, 0);
+
+    // The password alone now only gets a challenge
+    let LoginOutcome::MfaRequired(first) = login(pool, &auth_service, "careful").await else {
+        panic!("expected a two-factor challenge");
+    };
+    assert!(first.mfa_required);
+    assert!(auth_service.validate_token(&first.challenge_token).is_err());
+
+    // The code used to confirm can't be replayed
+    assert!(auth_service.complete_mfa_login(pool, verify(&first.challenge_token, &confirmed_code)).await.is_err());
+
+    // A challenge completes one login
+    let next_code = totp::code_at(&secret, Utc::now() + Duration::seconds(30)).unwrap();
+    let logged_in = auth_service.complete_mfa_login(pool, verify(&first.challenge_token, &next_code)).await.unwrap();
+    assert_eq!(logged_in.id, user.id);
+    assert!(matches!(
+        auth_service.complete_mfa_login(pool, verify(&first.challenge_token, &recovery_codes[0])).await,
+        Err(AppError::InvalidToken)
+    ));
+    assert!(matches!(
+        auth_service.complete_mfa_login(pool, verify(&challenge(pool, &auth_service, "careful").await, &next_code)).await,
+        Err(AppError::Unauthorized(_))
+    ));
+
+    // Recovery codes work once, however they are typed
+    let typed = format!(" {} ", recovery_codes[0].to_uppercase());
+    assert!(auth_service.complete_mfa_login(pool, verify(&challenge(pool, &auth_service, "careful").await, &typed)).await.is_ok());
+    assert!(matches!(
+        auth_service.complete_mfa_login(pool, verify(&challenge(pool, &auth_service, "careful").await, &recovery_codes[0])).await,
+        Err(AppError::Unauthorized(_))
+    ));
+    assert_eq!(auth_service.mfa_status(pool, &user).await.unwrap().recovery_codes_left, 9);
+
+    // An access token is no challenge token
+    let access_token = auth_service.generate_access_token(&user, None).unwrap();
+    assert!(matches!(
+        auth_service.complete_mfa_login(pool, verify(&access_token, &recovery_codes[1])).await,
+        Err(AppError::InvalidToken)
+    ));
+
+    // New recovery codes replace the old ones
+    let new_codes = auth_service.regenerate_recovery_codes(pool, user.id, code(&recovery_codes[1])).await.unwrap();
+    assert!(auth_service.complete_mfa_login(pool, verify(&challenge(pool, &auth_service, "careful").await, &recovery_codes[2])).await.is_err());
+    assert!(auth_service.complete_mfa_login(pool, verify(&challenge(pool, &auth_service, "careful").await, &new_codes[0])).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_mfa_challenge_attempt_limits() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let auth_service = AuthService::new("test-secret-key".to_string());
+    let user = create_user(pool, &auth_service, "guessed", UserRole::Accountant).await;
+    let (_, recovery_codes) = enroll(pool, &auth_service, &user).await;
+
+    // Five wrong codes spend a challenge, even for a right code after them
+    let spent = challenge(pool, &auth_service, "guessed").await;
+    for _ in 0..5 {
+        assert!(matches!(
+            auth_service.complete_mfa_login(pool, verify(&spent, "wrong-guess")).await,
+            Err(AppError::Unauthorized(_))
+        ));
+    }
+    assert!(matches!(
+        auth_service.complete_mfa_login(pool, verify(&spent, &recovery_codes[0])).await,
+        Err(AppError::InvalidToken)
+    ));
+
+    // Logging in again for fresh challenges only goes so far
+    let second = challenge(pool, &auth_service, "guessed").await;
+    for _ in 0..5 {
+        assert!(auth_service.complete_mfa_login(pool, verify(&second, "wrong-guess")).await.is_err());
+    }
+    assert!(matches!(
+        auth_service.login(pool, LoginRequest {
+            username: "guessed".to_string(),
+            password: "SecurePass123".to_string(),
+        }).await,
+        Err(AppError::Unauthorized(_))
+    ));
+    assert_eq!(auth_service.mfa_status(pool, &user).await.unwrap().recovery_codes_left, 10);
+}
+
+#[tokio::test]
+async fn test_mfa_policy_and_disabling() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let auth_service = AuthService::new("test-secret-key".to_string());
+    let admin = create_user(pool, &auth_service, "boss", UserRole::Admin).await;
+    let accountant = create_user(pool, &auth_service, "counter", UserRole::Accountant).await;
+
+    assert!(auth_service.mfa_policy(pool).await.unwrap().required_roles.is_empty());
+    let policy = auth_service.set_mfa_policy(pool, MfaPolicy {
+        required_roles: vec![UserRole::Admin, UserRole::Admin],
+    }).await.unwrap();
+    assert_eq!(policy.required_roles, vec![UserRole::Admin]);
+
+    assert!(auth_service.mfa_setup_required(pool, &admin).await.unwrap());
+    assert!(!auth_service.mfa_setup_required(pool, &accountant).await.unwrap());
+
+    let (admin_secret, _) = enroll(pool, &auth_service, &admin).await;
+    assert!(!auth_service.mfa_setup_required(pool, &admin).await.unwrap());
+
+    // Required roles can't turn it off
+    let next_code = totp::code_at(&admin_secret, Utc::now() + Duration::seconds(30)).unwrap();
+    assert!(matches!(
+        auth_service.disable_mfa(pool, &admin, DisableMfaRequest {
+            password: "SecurePass123".to_string(),
+            code: next_code,
+        }).await,
+        Err(AppError::Forbidden(_))
+    ));
+
+    // Everyone else can, with their password and a second factor
+    let (_, recovery_codes) = enroll(pool, &auth_service, &accountant).await;
+    assert!(matches!(
+        auth_service.disable_mfa(pool, &accountant, DisableMfaRequest {
+            password: "WrongPass123".to_string(),
+            code: recovery_codes[0].clone(),
+        }).await,
+        Err(AppError::InvalidCredentials)
+    ));
+    auth_service.disable_mfa(pool, &accountant, DisableMfaRequest {
+        password: "SecurePass123".to_string(),
+        code: recovery_codes[0].clone(),
+    }).await.unwrap();
+
+    assert!(matches!(login(pool, &auth_service, "counter").await, LoginOutcome::Authenticated(_)));
+    assert_eq!(auth_service.mfa_status(pool, &accountant).await.unwrap().recovery_codes_left, 0);
+
+    // Once no longer required, the admin may turn it off too
+    auth_service.set_mfa_policy(pool, MfaPolicy { required_roles: vec![] }).await.unwrap();
+    assert!(!auth_service.mfa_setup_required(pool, &admin).await.unwrap());
+}
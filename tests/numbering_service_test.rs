@@ -0,0 +1,246 @@
+use ledger_forge::models::{
+    CreateAccountRequest, AccountType, ContactType, CreateInvoiceRequest,
+    CreatePaymentRequest, CreateTransactionRequest, CreateLineItemRequest, JournalType, SequenceDocumentType,
+    UpdateNumberingSequenceRequest,
+};
+use ledger_forge::services::NumberingService;
+use ledger_forge::utils::{AppError, Result};
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+mod common;
+use common::test_db::TestDb;
+use common::fixtures::{date, create_account, contact_request, create_customer, invoice_request, payment_request};
+use common::services::{invoice_service, payment_service, transaction_service, contact_service, account_service};
+
+struct Accounts {
+    revenue: Uuid,
+    cash: Uuid,
+    customer: Uuid,
+}
+
+async fn accounts(pool: &PgPool) -> Accounts {
+    Accounts {
+        revenue: create_account(pool, "4000", "Revenue", AccountType::Revenue).await,
+        cash: create_account(pool, "1000", "Cash", AccountType::Asset).await,
+        customer: create_customer(pool, "Numbered Customer").await,
+    }
+}
+
+async fn create_invoice(
+    pool: &PgPool,
+    number: Option<&str>,
+    customer_id: Uuid,
+    revenue_account_id: Uuid,
+    invoice_date: NaiveDate,
+    company_id: Option<Uuid>,
+) -> Result<String> {
+    let invoice = invoice_service()
+        .for_company(company_id)
+        .create_invoice(pool, CreateInvoiceRequest {
+            invoice_number: number.map(str::to_string),
+            invoice_date,
+            due_date: invoice_date,
+            company_id,
+            ..invoice_request("", customer_id, revenue_account_id, "100.00")
+        })
+        .await?;
+    Ok(invoice.invoice.invoice_number)
+}
+
+/// Invoice numbers that reset every year, like INV-2025-0001
+fn yearly(prefix: &str, next_number: Option<i64>) -> UpdateNumberingSequenceRequest {
+    UpdateNumberingSequenceRequest {
+        company_id: None,
+        prefix: prefix.to_string(),
+        padding: 4,
+        yearly_reset: true,
+        next_number,
+        year: Some(2025),
+    }
+}
+
+async fn set_yearly_invoice_sequence(pool: &PgPool, next_number: Option<i64>) {
+    NumberingService::new()
+        .update_sequence(pool, SequenceDocumentType::Invoice, yearly("INV-{YYYY}-", next_number))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_built_in_numbering_until_a_sequence_is_configured() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let a = accounts(pool).await;
+
+    assert_eq!(create_invoice(pool, None, a.customer, a.revenue, date(2025, 3, 1), None).await.unwrap(), "INV-00001");
+    assert_eq!(create_invoice(pool, None, a.customer, a.revenue, date(2025, 3, 2), None).await.unwrap(), "INV-00002");
+}
+
+#[tokio::test]
+async fn test_yearly_reset_needs_the_year_in_the_number() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let numbering_service = NumberingService::new();
+
+    assert!(numbering_service.update_sequence(pool, SequenceDocumentType::Invoice, yearly("INV-", None)).await.is_err());
+    let sequence = numbering_service.update_sequence(pool, SequenceDocumentType::Invoice, yearly("INV-{YYYY}-", None)).await.unwrap();
+    assert_eq!(sequence.next_document_number, "INV-2025-0001");
+}
+
+#[tokio::test]
+async fn test_yearly_sequence_restarts_each_year() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let a = accounts(pool).await;
+    set_yearly_invoice_sequence(pool, None).await;
+
+    assert_eq!(create_invoice(pool, None, a.customer, a.revenue, date(2025, 4, 1), None).await.unwrap(), "INV-2025-0001");
+    assert_eq!(create_invoice(pool, None, a.customer, a.revenue, date(2026, 1, 5), None).await.unwrap(), "INV-2026-0001");
+}
+
+#[tokio::test]
+async fn test_manual_numbers_skip_the_sequence_but_must_be_unique() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let a = accounts(pool).await;
+    set_yearly_invoice_sequence(pool, None).await;
+
+    assert_eq!(create_invoice(pool, Some("CUSTOM-1"), a.customer, a.revenue, date(2025, 4, 2), None).await.unwrap(), "CUSTOM-1");
+    assert!(matches!(
+        create_invoice(pool, Some("CUSTOM-1"), a.customer, a.revenue, date(2025, 4, 2), None).await,
+        Err(AppError::Conflict(_))
+    ));
+    assert_eq!(create_invoice(pool, None, a.customer, a.revenue, date(2025, 4, 3), None).await.unwrap(), "INV-2025-0001");
+}
+
+#[tokio::test]
+async fn test_failed_invoice_gives_its_number_back() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let a = accounts(pool).await;
+
+    // The counter can be moved by hand
+    set_yearly_invoice_sequence(pool, Some(100)).await;
+    assert!(create_invoice(pool, None, Uuid::new_v4(), a.revenue, date(2025, 5, 1), None).await.is_err());
+    assert_eq!(create_invoice(pool, None, a.customer, a.revenue, date(2025, 5, 1), None).await.unwrap(), "INV-2025-0100");
+}
+
+#[tokio::test]
+async fn test_manual_number_ahead_of_the_counter_moves_it_past() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let a = accounts(pool).await;
+    set_yearly_invoice_sequence(pool, Some(100)).await;
+
+    assert_eq!(create_invoice(pool, Some("INV-2025-0105"), a.customer, a.revenue, date(2025, 5, 2), None).await.unwrap(), "INV-2025-0105");
+    // One the counter has already passed leaves it alone
+    assert_eq!(create_invoice(pool, Some("INV-2025-0003"), a.customer, a.revenue, date(2025, 5, 2), None).await.unwrap(), "INV-2025-0003");
+    assert_eq!(create_invoice(pool, None, a.customer, a.revenue, date(2025, 5, 3), None).await.unwrap(), "INV-2025-0106");
+}
+
+#[tokio::test]
+async fn test_companies_get_their_own_counters() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let a = accounts(pool).await;
+    set_yearly_invoice_sequence(pool, None).await;
+    create_invoice(pool, None, a.customer, a.revenue, date(2025, 6, 1), None).await.unwrap();
+    create_invoice(pool, Some("CUSTOM-1"), a.customer, a.revenue, date(2025, 6, 1), None).await.unwrap();
+
+    let company_id: Uuid = sqlx::query_scalar("INSERT INTO companies (name) VALUES ('Second Co') RETURNING id")
+        .fetch_one(pool)
+        .await
+        .unwrap();
+    let company_revenue = account_service()
+        .for_company(Some(company_id))
+        .create_account(pool, CreateAccountRequest {
+            code: "4000".to_string(),
+            name: "Revenue".to_string(),
+            account_type: AccountType::Revenue,
+            parent_account_id: None,
+            company_id: None,
+        })
+        .await
+        .unwrap()
+        .id;
+    let company_customer = contact_service()
+        .for_company(Some(company_id))
+        .create_contact(pool, contact_request(ContactType::Customer, "Numbered Customer"))
+        .await
+        .unwrap()
+        .id;
+
+    // Starting from the default settings, and with their own manual numbers
+    assert_eq!(create_invoice(pool, None, company_customer, company_revenue, date(2025, 6, 1), Some(company_id)).await.unwrap(), "INV-2025-0001");
+    assert_eq!(create_invoice(pool, Some("CUSTOM-1"), company_customer, company_revenue, date(2025, 6, 1), Some(company_id)).await.unwrap(), "CUSTOM-1");
+    let company_sequences = NumberingService::new().for_company(Some(company_id)).list_sequences(pool).await.unwrap();
+    let company_invoices = company_sequences.iter().find(|s| s.sequence.document_type == SequenceDocumentType::Invoice).unwrap();
+    assert_eq!(company_invoices.sequence.company_id, Some(company_id));
+}
+
+fn cash_payment(customer_id: Uuid) -> CreatePaymentRequest {
+    CreatePaymentRequest {
+        payment_method: "Cash".to_string(),
+        ..payment_request(customer_id, "50.00", &[])
+    }
+}
+
+#[tokio::test]
+async fn test_concurrent_payments_never_share_or_skip_a_number() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let a = accounts(pool).await;
+    let payment_service = payment_service();
+
+    let mut handles = Vec::new();
+    for _ in 0..8 {
+        let pool = pool.clone();
+        let payment_service = payment_service.clone();
+        let customer = a.customer;
+        handles.push(tokio::spawn(async move {
+            payment_service.create_payment(&pool, cash_payment(customer)).await.unwrap().payment_number.unwrap()
+        }));
+    }
+    let mut numbers = Vec::new();
+    for handle in handles {
+        numbers.push(handle.await.unwrap());
+    }
+    numbers.sort();
+    let expected: Vec<String> = (1..=8).map(|n| format!("PMT-{:05}", n)).collect();
+    assert_eq!(numbers, expected);
+}
+
+#[tokio::test]
+async fn test_manual_journal_entries_are_numbered() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let a = accounts(pool).await;
+
+    let entry = transaction_service().create_transaction(pool, CreateTransactionRequest {
+        transaction_date: date(2025, 7, 2),
+        description: Some("Owner contribution".to_string()),
+        reference_number: None,
+        entry_number: None,
+        contact_id: None,
+        company_id: None,
+        journal_type: Some(JournalType::General),
+        line_items: CreateLineItemRequest::from_signed_amounts(&[(a.cash, Decimal::ONE_HUNDRED), (a.revenue, -Decimal::ONE_HUNDRED)], "Contribution"),
+    }, None).await.unwrap();
+    assert_eq!(entry.transaction.entry_number.as_deref(), Some("JE-00001"));
+}
+
+#[tokio::test]
+async fn test_sequences_list_every_document_type_with_its_next_number() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let a = accounts(pool).await;
+    payment_service().create_payment(pool, cash_payment(a.customer)).await.unwrap();
+
+    let sequences = NumberingService::new().list_sequences(pool).await.unwrap();
+    assert_eq!(sequences.len(), 5);
+    let payments = sequences.iter().find(|s| s.sequence.document_type == SequenceDocumentType::Payment).unwrap();
+    assert_eq!(payments.next_document_number, "PMT-00002");
+}
@@ -0,0 +1,210 @@
+use ledger_forge::models::{
+    ChangePasswordRequest, CreateUserRequest, ForgotPasswordRequest, LoginRequest, ResetPasswordRequest, User,
+    UserRole,
+};
+use ledger_forge::services::auth::{hash_token, LoginOutcome, PasswordPolicy};
+use ledger_forge::services::{AuthService, Mailer};
+use ledger_forge::utils::AppError;
+use sqlx::PgPool;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+mod common;
+use common::test_db::TestDb;
+
+async fn create_user(pool: &PgPool, auth_service: &AuthService, username: &str) -> User {
+    auth_service.register(pool, CreateUserRequest {
+        username: username.to_string(),
+        email: format!("{}@example.com", username),
+        password: "SecurePass123".to_string(),
+        role: UserRole::Accountant,
+    }).await.unwrap()
+}
+
+async fn login(pool: &PgPool, auth_service: &AuthService, username: &str, password: &str) -> Result<User, AppError> {
+    auth_service.login(pool, LoginRequest {
+        username: username.to_string(),
+        password: password.to_string(),
+    }).await.map(|outcome| match outcome {
+        LoginOutcome::Authenticated(user) => user,
+        LoginOutcome::MfaRequired(_) => panic!("no second factor was set up"),
+    })
+}
+
+/// Reset codes sent so far, in order
+fn sent_codes(mail_file: &PathBuf) -> Vec<String> {
+    let mail = std::fs::read_to_string(mail_file).unwrap_or_default();
+    let lines: Vec<&str> = mail.lines().collect();
+    lines
+        .windows(2)
+        .filter(|pair| pair[0] == "Use this code to choose a new password:")
+        .map(|pair| pair[1].to_string())
+        .collect()
+}
+
+#[test]
+fn test_password_policy() {
+    let policy = PasswordPolicy::default();
+    assert!(policy.check("SecurePass123").is_ok());
+    assert!(policy.check("Short1").is_err());
+    assert!(policy.check("alllowercase123").is_err());
+    assert!(policy.check("NoDigitsHere").is_err());
+
+    let strict = PasswordPolicy {
+        min_length: 12,
+        require_symbol: true,
+        ..PasswordPolicy::default()
+    };
+    match strict.check("SecurePass1") {
+        Err(AppError::ValidationError(message)) => {
+            assert!(message.contains("at least 12 characters"));
+            assert!(message.contains("symbol"));
+        }
+        other => panic!("expected a validation error, got {:?}", other.map(|_| ())),
+    }
+    assert!(strict.check("Secure-Pass-123").is_ok());
+
+    let relaxed = PasswordPolicy {
+        min_length: 4,
+        require_uppercase: false,
+        require_lowercase: false,
+        require_digit: false,
+        require_symbol: false,
+    };
+    assert!(relaxed.check("abcd").is_ok());
+}
+
+#[tokio::test]
+async fn test_change_password() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let auth_service = AuthService::new("test-secret-key".to_string());
+    let user = create_user(pool, &auth_service, "changer").await;
+
+    // Registration follows the policy too
+    assert!(matches!(
+        auth_service.register(pool, CreateUserRequest {
+            username: "weakling".to_string(),
+            email: "weakling@example.com".to_string(),
+            password: "password".to_string(),
+            role: UserRole::Viewer,
+        }).await,
+        Err(AppError::ValidationError(_))
+    ));
+
+    let refresh_token = auth_service.generate_refresh_token(pool, &user, None).await.unwrap();
+    let access_token = auth_service.generate_access_token(&user, None).unwrap();
+    let claims = auth_service.validate_token(&access_token).unwrap().claims;
+
+    let change = |current: &str, new: &str| ChangePasswordRequest {
+        current_password: current.to_string(),
+        new_password: new.to_string(),
+    };
+    assert!(matches!(
+        auth_service.change_password(pool, user.id, change("WrongPass123", "NewSecurePass456")).await,
+        Err(AppError::InvalidCredentials)
+    ));
+    assert!(matches!(
+        auth_service.change_password(pool, user.id, change("SecurePass123", "weak")).await,
+        Err(AppError::ValidationError(_))
+    ));
+    assert!(matches!(
+        auth_service.change_password(pool, user.id, change("SecurePass123", "SecurePass123")).await,
+        Err(AppError::ValidationError(_))
+    ));
+    assert!(auth_service.authenticated_user(pool, &claims).await.is_ok());
+
+    let changed = auth_service.change_password(pool, user.id, change("SecurePass123", "NewSecurePass456")).await.unwrap();
+    assert!(changed.password_changed_at.is_some());
+
+    assert!(matches!(login(pool, &auth_service, "changer", "SecurePass123").await, Err(AppError::InvalidCredentials)));
+    assert!(login(pool, &auth_service, "changer", "NewSecurePass456").await.is_ok());
+
+    // Existing sessions are over
+    assert!(auth_service.rotate_refresh_token(pool, &refresh_token).await.is_err());
+    assert!(matches!(
+        auth_service.authenticated_user(pool, &claims).await,
+        Err(AppError::TokenExpired)
+    ));
+    let fresh_token = auth_service.generate_access_token(&changed, None).unwrap();
+    let fresh_claims = auth_service.validate_token(&fresh_token).unwrap().claims;
+    assert!(auth_service.authenticated_user(pool, &fresh_claims).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_password_reset() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let mail_file = std::env::temp_dir().join(format!("ledger-forge-mail-{}.log", Uuid::new_v4()));
+    let auth_service = AuthService::new("test-secret-key".to_string())
+        .with_mailer(Mailer::File(mail_file.clone()));
+    let user = create_user(pool, &auth_service, "forgetful").await;
+    let refresh_token = auth_service.generate_refresh_token(pool, &user, None).await.unwrap();
+
+    // Unknown addresses get the same answer but no email
+    auth_service.request_password_reset(pool, ForgotPasswordRequest {
+        email: "nobody@example.com".to_string(),
+    }).await.unwrap();
+    assert!(sent_codes(&mail_file).is_empty());
+
+    let forgot = ForgotPasswordRequest { email: "Forgetful@Example.com".to_string() };
+    auth_service.request_password_reset(pool, forgot).await.unwrap();
+    auth_service.request_password_reset(pool, ForgotPasswordRequest {
+        email: "forgetful@example.com".to_string(),
+    }).await.unwrap();
+    let codes = sent_codes(&mail_file);
+    assert_eq!(codes.len(), 2);
+
+    // Only the hash is stored
+    let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM password_reset_tokens WHERE token_hash = $1")
+        .bind(hash_token(&codes[1]))
+        .fetch_one(pool)
+        .await
+        .unwrap();
+    assert_eq!(stored, 1);
+
+    let reset = |token: &str, password: &str| ResetPasswordRequest {
+        token: token.to_string(),
+        new_password: password.to_string(),
+    };
+    assert!(matches!(
+        auth_service.reset_password(pool, reset("not-a-code", "ResetPass789")).await,
+        Err(AppError::InvalidToken)
+    ));
+
+    // A rejected password leaves the code usable
+    assert!(matches!(
+        auth_service.reset_password(pool, reset(&codes[1], "weak")).await,
+        Err(AppError::ValidationError(_))
+    ));
+    auth_service.reset_password(pool, reset(&codes[1], "ResetPass789")).await.unwrap();
+    assert!(login(pool, &auth_service, "forgetful", "ResetPass789").await.is_ok());
+    assert!(auth_service.rotate_refresh_token(pool, &refresh_token).await.is_err());
+
+    // Codes are single use, and resetting uses up the older ones as well
+    assert!(matches!(
+        auth_service.reset_password(pool, reset(&codes[1], "AnotherPass789")).await,
+        Err(AppError::InvalidToken)
+    ));
+    assert!(matches!(
+        auth_service.reset_password(pool, reset(&codes[0], "AnotherPass789")).await,
+        Err(AppError::InvalidToken)
+    ));
+
+    // Codes expire
+    auth_service.request_password_reset(pool, ForgotPasswordRequest {
+        email: "forgetful@example.com".to_string(),
+    }).await.unwrap();
+    let expiring = sent_codes(&mail_file).pop().unwrap();
+    sqlx::query("UPDATE password_reset_tokens SET expires_at = NOW() - INTERVAL '1 minute' WHERE token_hash = $1")
+        .bind(hash_token(&expiring))
+        .execute(pool)
+        .await
+        .unwrap();
+    assert!(matches!(
+        auth_service.reset_password(pool, reset(&expiring, "AnotherPass789")).await,
+        Err(AppError::TokenExpired)
+    ));
+
+    let _ = std::fs::remove_file(&mail_file);
+}
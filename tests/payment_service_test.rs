@@ -59,7 +59,7 @@ async fn test_create_payment_with_applications() {
 
     // Create an invoice
     let invoice_req = CreateInvoiceRequest {
-        invoice_number: "INV-001".to_string(),
+        invoice_number: Some("INV-001".to_string()),
         customer_id: customer.id,
         invoice_date: NaiveDate::from_ymd_opt(2024, 10, 1).unwrap(),
         due_date: NaiveDate::from_ymd_opt(2024, 10, 31).unwrap(),
@@ -68,6 +68,7 @@ async fn test_create_payment_with_applications() {
         billing_address: Some("123 Test St".to_string()),
         shipping_address: None,
         company_id: None,
+        ar_account_id: None,
         line_items: vec![
             CreateInvoiceLineItemRequest {
                 line_number: 1,
@@ -77,10 +78,13 @@ async fn test_create_payment_with_applications() {
                 discount_percent: None,
                 tax_code: None,
                 revenue_account_id: revenue_account.id,
+                item_id: None,
+                dimension_value_ids: Vec::new(),
             }
         ],
     };
     let invoice = invoice_service.create_invoice(pool, invoice_req).await.unwrap();
+    invoice_service.update_invoice_status(pool, invoice.invoice.id, InvoiceStatus::Sent).await.unwrap();
 
     // Create a payment with application
     let payment_req = CreatePaymentRequest {
@@ -91,6 +95,7 @@ async fn test_create_payment_with_applications() {
         payment_method: "Check".to_string(),
         reference_number: Some("CHK-12345".to_string()),
         deposit_to_account_id: Some(bank_account.id),
+        ar_account_id: None,
         memo: Some("Payment for invoice INV-001".to_string()),
         company_id: None,
         applications: vec![
@@ -149,7 +154,7 @@ async fn test_create_payment_with_partial_application() {
 
     // Create invoice for $2000
     let invoice_req = CreateInvoiceRequest {
-        invoice_number: "INV-002".to_string(),
+        invoice_number: Some("INV-002".to_string()),
         customer_id: customer.id,
         invoice_date: NaiveDate::from_ymd_opt(2024, 10, 1).unwrap(),
         due_date: NaiveDate::from_ymd_opt(2024, 10, 31).unwrap(),
@@ -158,6 +163,7 @@ async fn test_create_payment_with_partial_application() {
         billing_address: None,
         shipping_address: None,
         company_id: None,
+        ar_account_id: None,
         line_items: vec![
             CreateInvoiceLineItemRequest {
                 line_number: 1,
@@ -167,10 +173,13 @@ async fn test_create_payment_with_partial_application() {
                 discount_percent: None,
                 tax_code: None,
                 revenue_account_id: revenue_account.id,
+                item_id: None,
+                dimension_value_ids: Vec::new(),
             }
         ],
     };
     let invoice = invoice_service.create_invoice(pool, invoice_req).await.unwrap();
+    invoice_service.update_invoice_status(pool, invoice.invoice.id, InvoiceStatus::Sent).await.unwrap();
 
     // Create partial payment of $1200
     let payment_req = CreatePaymentRequest {
@@ -181,6 +190,7 @@ async fn test_create_payment_with_partial_application() {
         payment_method: "Credit Card".to_string(),
         reference_number: Some("CC-98765".to_string()),
         deposit_to_account_id: None,
+        ar_account_id: None,
         memo: None,
         company_id: None,
         applications: vec![
@@ -233,6 +243,7 @@ async fn test_create_payment_with_unapplied_amount() {
         payment_method: "Cash".to_string(),
         reference_number: None,
         deposit_to_account_id: None,
+        ar_account_id: None,
         memo: Some("Advance payment".to_string()),
         company_id: None,
         applications: vec![], // No applications
@@ -275,6 +286,7 @@ async fn test_list_unapplied_payments() {
         payment_method: "Bank Transfer".to_string(),
         reference_number: Some("TRF-54321".to_string()),
         deposit_to_account_id: None,
+        ar_account_id: None,
         memo: None,
         company_id: None,
         applications: vec![],
@@ -323,7 +335,7 @@ async fn test_apply_payment_to_invoices() {
 
     // Create invoice
     let invoice_req = CreateInvoiceRequest {
-        invoice_number: "INV-005".to_string(),
+        invoice_number: Some("INV-005".to_string()),
         customer_id: customer.id,
         invoice_date: NaiveDate::from_ymd_opt(2024, 10, 1).unwrap(),
         due_date: NaiveDate::from_ymd_opt(2024, 10, 31).unwrap(),
@@ -332,6 +344,7 @@ async fn test_apply_payment_to_invoices() {
         billing_address: None,
         shipping_address: None,
         company_id: None,
+        ar_account_id: None,
         line_items: vec![
             CreateInvoiceLineItemRequest {
                 line_number: 1,
@@ -341,10 +354,13 @@ async fn test_apply_payment_to_invoices() {
                 discount_percent: None,
                 tax_code: None,
                 revenue_account_id: revenue_account.id,
+                item_id: None,
+                dimension_value_ids: Vec::new(),
             }
         ],
     };
     let invoice = invoice_service.create_invoice(pool, invoice_req).await.unwrap();
+    invoice_service.update_invoice_status(pool, invoice.invoice.id, InvoiceStatus::Sent).await.unwrap();
 
     // Create unapplied payment
     let payment_req = CreatePaymentRequest {
@@ -355,6 +371,7 @@ async fn test_apply_payment_to_invoices() {
         payment_method: "Check".to_string(),
         reference_number: Some("CHK-99999".to_string()),
         deposit_to_account_id: None,
+        ar_account_id: None,
         memo: None,
         company_id: None,
         applications: vec![], // Create as unapplied
@@ -418,7 +435,7 @@ async fn test_get_invoice_payments() {
 
     // Create invoice
     let invoice_req = CreateInvoiceRequest {
-        invoice_number: "INV-006".to_string(),
+        invoice_number: Some("INV-006".to_string()),
         customer_id: customer.id,
         invoice_date: NaiveDate::from_ymd_opt(2024, 10, 1).unwrap(),
         due_date: NaiveDate::from_ymd_opt(2024, 10, 31).unwrap(),
@@ -427,6 +444,7 @@ async fn test_get_invoice_payments() {
         billing_address: None,
         shipping_address: None,
         company_id: None,
+        ar_account_id: None,
         line_items: vec![
             CreateInvoiceLineItemRequest {
                 line_number: 1,
@@ -436,10 +454,13 @@ async fn test_get_invoice_payments() {
                 discount_percent: None,
                 tax_code: None,
                 revenue_account_id: revenue_account.id,
+                item_id: None,
+                dimension_value_ids: Vec::new(),
             }
         ],
     };
     let invoice = invoice_service.create_invoice(pool, invoice_req).await.unwrap();
+    invoice_service.update_invoice_status(pool, invoice.invoice.id, InvoiceStatus::Sent).await.unwrap();
 
     // Create two payments for the invoice
     let payment1_req = CreatePaymentRequest {
@@ -450,6 +471,7 @@ async fn test_get_invoice_payments() {
         payment_method: "Check".to_string(),
         reference_number: Some("CHK-111".to_string()),
         deposit_to_account_id: None,
+        ar_account_id: None,
         memo: None,
         company_id: None,
         applications: vec![
@@ -469,6 +491,7 @@ async fn test_get_invoice_payments() {
         payment_method: "Credit Card".to_string(),
         reference_number: Some("CC-222".to_string()),
         deposit_to_account_id: None,
+        ar_account_id: None,
         memo: None,
         company_id: None,
         applications: vec![
@@ -571,7 +594,7 @@ async fn test_payment_validation_exceeds_amount() {
 
     // Create invoice
     let invoice_req = CreateInvoiceRequest {
-        invoice_number: "INV-007".to_string(),
+        invoice_number: Some("INV-007".to_string()),
         customer_id: customer.id,
         invoice_date: NaiveDate::from_ymd_opt(2024, 10, 1).unwrap(),
         due_date: NaiveDate::from_ymd_opt(2024, 10, 31).unwrap(),
@@ -580,6 +603,7 @@ async fn test_payment_validation_exceeds_amount() {
         billing_address: None,
         shipping_address: None,
         company_id: None,
+        ar_account_id: None,
         line_items: vec![
             CreateInvoiceLineItemRequest {
                 line_number: 1,
@@ -589,10 +613,13 @@ async fn test_payment_validation_exceeds_amount() {
                 discount_percent: None,
                 tax_code: None,
                 revenue_account_id: revenue_account.id,
+                item_id: None,
+                dimension_value_ids: Vec::new(),
             }
         ],
     };
     let invoice = invoice_service.create_invoice(pool, invoice_req).await.unwrap();
+    invoice_service.update_invoice_status(pool, invoice.invoice.id, InvoiceStatus::Sent).await.unwrap();
 
     // Try to create payment with application exceeding payment amount
     let payment_req = CreatePaymentRequest {
@@ -603,6 +630,7 @@ async fn test_payment_validation_exceeds_amount() {
         payment_method: "Check".to_string(),
         reference_number: None,
         deposit_to_account_id: None,
+        ar_account_id: None,
         memo: None,
         company_id: None,
         applications: vec![
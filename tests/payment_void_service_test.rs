@@ -0,0 +1,209 @@
+use ledger_forge::models::{
+    AccountType, CreatePaymentRequest, InvoiceStatus, Payment,
+    VoidPaymentRequest, StatementStyle,
+};
+use ledger_forge::utils::AppError;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+mod common;
+use common::test_db::TestDb;
+use common::fixtures::{
+    date, dec, create_account, create_customer, create_sent_invoice, payment_request, balance, get_invoice,
+    Receivables,
+};
+use common::services::{payment_service, statement_service};
+
+struct AppliedCheque {
+    bank_account: Uuid,
+    ar_account: Uuid,
+    fee_account: Uuid,
+    bank_charges_account: Uuid,
+    customer: Uuid,
+    /// 500.00, paid in full
+    first: Uuid,
+    /// 300.00, 200.00 of it paid
+    second: Uuid,
+    payment: Payment,
+}
+
+impl AppliedCheque {
+    /// Returned NSF with a 25.00 fee billed to the customer and a 10.00 bank charge
+    fn void_request(&self, nsf_fee_account_id: Option<Uuid>) -> VoidPaymentRequest {
+        VoidPaymentRequest {
+            void_date: date(2025, 7, 20),
+            reason: Some("Returned NSF".to_string()),
+            nsf_fee: Some(dec("25.00")),
+            nsf_fee_account_id,
+            bank_fee: Some(dec("10.00")),
+            bank_fee_account_id: Some(self.bank_charges_account),
+        }
+    }
+
+    async fn void(&self, pool: &PgPool) -> Payment {
+        payment_service().void_payment(pool, self.payment.id, self.void_request(Some(self.fee_account)), None).await.unwrap()
+    }
+}
+
+/// A posted 700.00 cheque applied across two posted invoices
+async fn applied_cheque(pool: &PgPool) -> AppliedCheque {
+    let bank_account = create_account(pool, "1000", "Checking", AccountType::Asset).await;
+    let receivables = Receivables::create(pool).await;
+    let fee_account = create_account(pool, "4900", "Returned Check Fees", AccountType::Revenue).await;
+    let bank_charges_account = create_account(pool, "6100", "Bank Charges", AccountType::Expense).await;
+    let customer = create_customer(pool, "Bounced Cheque Co").await;
+
+    let first = create_sent_invoice(pool, receivables.invoice_request("INV-PV-1", customer, "500.00")).await;
+    let second = create_sent_invoice(pool, receivables.invoice_request("INV-PV-2", customer, "300.00")).await;
+
+    let payment = payment_service().create_payment(pool, CreatePaymentRequest {
+        payment_number: Some("PMT-PV-1".to_string()),
+        payment_date: date(2025, 7, 10),
+        reference_number: Some("CHK-1001".to_string()),
+        deposit_to_account_id: Some(bank_account),
+        ar_account_id: Some(receivables.ar_account),
+        ..payment_request(customer, "700.00", &[(first, "500.00"), (second, "200.00")])
+    }).await.unwrap();
+
+    AppliedCheque {
+        bank_account,
+        ar_account: receivables.ar_account,
+        fee_account,
+        bank_charges_account,
+        customer,
+        first,
+        second,
+        payment,
+    }
+}
+
+#[tokio::test]
+async fn test_payment_with_both_accounts_posts_the_receipt() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let cheque = applied_cheque(pool).await;
+
+    assert!(cheque.payment.transaction_id.is_some());
+    assert_eq!(balance(pool, cheque.ar_account).await, dec("100.00"));
+    assert_eq!(balance(pool, cheque.bank_account).await, dec("700.00"));
+}
+
+#[tokio::test]
+async fn test_unapplying_reopens_that_invoice_only() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let cheque = applied_cheque(pool).await;
+
+    let applications = payment_service().list_payment_applications(pool, cheque.payment.id).await.unwrap();
+    assert_eq!(applications.len(), 2);
+    let second_application = applications.iter().find(|a| a.invoice_id == cheque.second).unwrap();
+    let updated = payment_service().unapply_payment_application(pool, cheque.payment.id, second_application.id).await.unwrap();
+    assert_eq!(updated.unapplied_amount, Some(dec("200.00")));
+
+    let second = get_invoice(pool, cheque.second).await;
+    assert_eq!(second.balance, dec("300.00"));
+    assert_eq!(second.status, InvoiceStatus::Sent);
+    assert_eq!(get_invoice(pool, cheque.first).await.status, InvoiceStatus::Paid);
+
+    // It's gone once unapplied
+    assert!(matches!(
+        payment_service().unapply_payment_application(pool, cheque.payment.id, second_application.id).await,
+        Err(AppError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_nsf_fee_needs_an_income_account() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let cheque = applied_cheque(pool).await;
+
+    assert!(matches!(
+        payment_service().void_payment(pool, cheque.payment.id, cheque.void_request(None), None).await,
+        Err(AppError::ValidationError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_failed_nsf_fee_leaves_the_payment_unvoided() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let cheque = applied_cheque(pool).await;
+
+    // Billing the fee is part of the void
+    let unknown_account = cheque.void_request(Some(Uuid::new_v4()));
+    assert!(payment_service().void_payment(pool, cheque.payment.id, unknown_account, None).await.is_err());
+    let unchanged = payment_service().get_payment_by_id(pool, cheque.payment.id).await.unwrap().unwrap();
+    assert_eq!(unchanged.void_date, None);
+    assert_eq!(payment_service().list_payment_applications(pool, cheque.payment.id).await.unwrap().len(), 2);
+    assert_eq!(balance(pool, cheque.bank_account).await, dec("700.00"));
+}
+
+#[tokio::test]
+async fn test_void_removes_applications_and_reopens_invoices() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let cheque = applied_cheque(pool).await;
+
+    let voided = cheque.void(pool).await;
+    assert_eq!(voided.void_date, Some(date(2025, 7, 20)));
+    assert_eq!(voided.unapplied_amount, Some(Decimal::ZERO));
+    assert!(voided.void_transaction_id.is_some());
+    assert!(payment_service().list_payment_applications(pool, cheque.payment.id).await.unwrap().is_empty());
+
+    let first = get_invoice(pool, cheque.first).await;
+    assert_eq!(first.balance, dec("500.00"));
+    assert_eq!(first.status, InvoiceStatus::Sent);
+}
+
+#[tokio::test]
+async fn test_void_bills_the_nsf_fee_and_books_the_bank_charge() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let cheque = applied_cheque(pool).await;
+
+    let voided = cheque.void(pool).await;
+    let nsf_invoice = get_invoice(pool, voided.nsf_invoice_id.unwrap()).await;
+    assert_eq!(nsf_invoice.total_amount, dec("25.00"));
+    assert_eq!(nsf_invoice.status, InvoiceStatus::Sent);
+
+    // The bank charge comes out of the deposit account
+    assert_eq!(balance(pool, cheque.ar_account).await, dec("825.00"));
+    assert_eq!(balance(pool, cheque.bank_account).await, dec("-10.00"));
+    assert_eq!(balance(pool, cheque.bank_charges_account).await, dec("10.00"));
+}
+
+#[tokio::test]
+async fn test_voided_payment_cannot_be_changed() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let cheque = applied_cheque(pool).await;
+    cheque.void(pool).await;
+
+    assert!(matches!(
+        payment_service().void_payment(pool, cheque.payment.id, cheque.void_request(Some(cheque.fee_account)), None).await,
+        Err(AppError::Conflict(_))
+    ));
+    assert!(matches!(
+        payment_service().unapply_payment_application(pool, cheque.payment.id, Uuid::new_v4()).await,
+        Err(AppError::BadRequest(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_statement_shows_the_payment_and_its_return() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let cheque = applied_cheque(pool).await;
+    cheque.void(pool).await;
+
+    let statement = statement_service()
+        .generate_statement(pool, cheque.customer, Some(date(2025, 6, 1)), date(2025, 7, 31), StatementStyle::BalanceForward)
+        .await
+        .unwrap();
+    assert_eq!(statement.closing_balance, dec("825.00"));
+    let returned = statement.lines.iter().find(|l| l.document_type == "payment_void").unwrap();
+    assert_eq!(returned.amount, dec("700.00"));
+    assert_eq!(returned.date, date(2025, 7, 20));
+}
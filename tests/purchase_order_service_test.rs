@@ -0,0 +1,183 @@
+use ledger_forge::models::{
+    AccountType, BillStatus, CreatePurchaseOrderRequest, CreatePurchaseOrderLineItemRequest,
+    ConvertPurchaseOrderRequest, ConvertPurchaseOrderLineRequest, PurchaseOrderStatus,
+};
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+mod common;
+use common::test_db::TestDb;
+use common::fixtures::{date, dec, create_account, create_vendor, balance};
+use common::services::{purchase_order_service, bill_service};
+
+fn po_line(line_number: i32, description: &str, quantity: &str, unit_price: &str, expense_account_id: Uuid) -> CreatePurchaseOrderLineItemRequest {
+    CreatePurchaseOrderLineItemRequest {
+        line_number,
+        description: Some(description.to_string()),
+        quantity: dec(quantity),
+        unit_price: dec(unit_price),
+        expense_account_id,
+        item_id: None,
+        tax_code: None,
+        expected_date: None,
+    }
+}
+
+fn convert_request(bill_number: &str, ap_account_id: Uuid) -> ConvertPurchaseOrderRequest {
+    ConvertPurchaseOrderRequest {
+        bill_number: Some(bill_number.to_string()),
+        bill_date: date(2025, 10, 20),
+        due_date: date(2025, 11, 19),
+        ap_account_id: Some(ap_account_id),
+        lines: None,
+    }
+}
+
+fn partial_request(bill_number: &str, ap_account_id: Uuid, line_id: Uuid, quantity: &str) -> ConvertPurchaseOrderRequest {
+    ConvertPurchaseOrderRequest {
+        lines: Some(vec![ConvertPurchaseOrderLineRequest { purchase_order_line_id: line_id, quantity: dec(quantity) }]),
+        ..convert_request(bill_number, ap_account_id)
+    }
+}
+
+struct OpenOrder {
+    ap_account: Uuid,
+    contractors: Uuid,
+    vendor: Uuid,
+    order_id: Uuid,
+    widgets: Uuid,
+}
+
+/// An open order for 10 widgets at 20.00 (supplies) and 5 hours of installation at 100.00 (contractors)
+async fn open_order(pool: &PgPool) -> OpenOrder {
+    let ap_account = create_account(pool, "2000", "Accounts Payable", AccountType::Liability).await;
+    let supplies = create_account(pool, "5100", "Supplies", AccountType::Expense).await;
+    let contractors = create_account(pool, "5200", "Contractors", AccountType::Expense).await;
+    let vendor = create_vendor(pool, "Parts Supplier").await;
+
+    let order = purchase_order_service().create_purchase_order(pool, CreatePurchaseOrderRequest {
+        po_number: "PO-1".to_string(),
+        vendor_id: vendor,
+        order_date: date(2025, 10, 1),
+        expected_date: Some(date(2025, 10, 15)),
+        memo: None,
+        shipping_address: None,
+        company_id: None,
+        line_items: vec![
+            po_line(1, "Widgets", "10", "20.00", supplies),
+            po_line(2, "Installation", "5", "100.00", contractors),
+        ],
+    }).await.unwrap();
+
+    OpenOrder {
+        ap_account,
+        contractors,
+        vendor,
+        order_id: order.purchase_order.id,
+        widgets: order.line_items[0].id,
+    }
+}
+
+#[tokio::test]
+async fn test_open_order_commits_spend_without_touching_the_ledger() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let setup = open_order(pool).await;
+
+    let order = purchase_order_service().get_purchase_order(pool, setup.order_id).await.unwrap();
+    assert_eq!(order.purchase_order.total_amount, dec("700.00"));
+    assert_eq!(order.purchase_order.status, PurchaseOrderStatus::Open);
+    assert_eq!(order.line_items[0].expected_date, Some(date(2025, 10, 15)));
+
+    let report = purchase_order_service().generate_open_purchase_orders_report(pool, date(2025, 10, 31)).await.unwrap();
+    assert_eq!(report.total_committed, dec("700.00"));
+    assert_eq!(report.committed_by_account.len(), 2);
+    assert_eq!(balance(pool, setup.ap_account).await, Decimal::ZERO);
+}
+
+#[tokio::test]
+async fn test_bill_is_prefilled_from_order_lines() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let setup = open_order(pool).await;
+
+    let preview = purchase_order_service().prefill_bill_request(pool, setup.order_id, convert_request("B-1", setup.ap_account)).await.unwrap();
+    assert_eq!(preview.vendor_id, setup.vendor);
+    assert_eq!(preview.line_items.len(), 2);
+    assert_eq!(preview.line_items[1].amount, dec("500.00"));
+    assert_eq!(preview.line_items[1].expense_account_id, setup.contractors);
+}
+
+#[tokio::test]
+async fn test_partial_billing_cannot_exceed_ordered_quantity() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let setup = open_order(pool).await;
+
+    let first = purchase_order_service().convert_to_bill(
+        pool, setup.order_id, partial_request("B-1", setup.ap_account, setup.widgets, "4"),
+    ).await.unwrap();
+    assert_eq!(first.bill.total_amount, dec("80.00"));
+    assert_eq!(first.purchase_order.status, PurchaseOrderStatus::PartiallyReceived);
+    assert_eq!(first.purchase_order.billed_amount, dec("80.00"));
+
+    // Only 6 widgets are left to bill
+    let too_many = partial_request("B-X", setup.ap_account, setup.widgets, "7");
+    assert!(purchase_order_service().convert_to_bill(pool, setup.order_id, too_many).await.is_err());
+}
+
+#[tokio::test]
+async fn test_billing_the_rest_closes_the_order() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let setup = open_order(pool).await;
+    purchase_order_service().convert_to_bill(
+        pool, setup.order_id, partial_request("B-1", setup.ap_account, setup.widgets, "4"),
+    ).await.unwrap();
+
+    let second = purchase_order_service().convert_to_bill(pool, setup.order_id, convert_request("B-2", setup.ap_account)).await.unwrap();
+    assert_eq!(second.bill.total_amount, dec("620.00"));
+    assert_eq!(second.purchase_order.status, PurchaseOrderStatus::Closed);
+    assert_eq!(balance(pool, setup.ap_account).await, dec("-700.00"));
+
+    let report = purchase_order_service().generate_open_purchase_orders_report(pool, date(2025, 10, 31)).await.unwrap();
+    assert_eq!(report.total_committed, Decimal::ZERO);
+    assert!(purchase_order_service().convert_to_bill(pool, setup.order_id, convert_request("B-3", setup.ap_account)).await.is_err());
+}
+
+#[tokio::test]
+async fn test_voiding_a_bill_reopens_its_quantities() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let setup = open_order(pool).await;
+    purchase_order_service().convert_to_bill(
+        pool, setup.order_id, partial_request("B-1", setup.ap_account, setup.widgets, "4"),
+    ).await.unwrap();
+    let second = purchase_order_service().convert_to_bill(pool, setup.order_id, convert_request("B-2", setup.ap_account)).await.unwrap();
+
+    bill_service()
+        .update_bill_status(pool, second.bill.id, BillStatus::Void).await.unwrap();
+    let reopened = purchase_order_service().get_purchase_order(pool, setup.order_id).await.unwrap();
+    assert_eq!(reopened.purchase_order.status, PurchaseOrderStatus::PartiallyReceived);
+    assert_eq!(reopened.purchase_order.billed_amount, dec("80.00"));
+    assert_eq!(reopened.line_items[0].billed_quantity, dec("4"));
+    assert_eq!(reopened.bills.len(), 1);
+
+    let report = purchase_order_service().generate_open_purchase_orders_report(pool, date(2025, 10, 31)).await.unwrap();
+    assert_eq!(report.total_committed, dec("620.00"));
+}
+
+#[tokio::test]
+async fn test_closing_by_hand_drops_the_remaining_commitment() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let setup = open_order(pool).await;
+    purchase_order_service().convert_to_bill(
+        pool, setup.order_id, partial_request("B-1", setup.ap_account, setup.widgets, "4"),
+    ).await.unwrap();
+
+    purchase_order_service().close_purchase_order(pool, setup.order_id).await.unwrap();
+    let report = purchase_order_service().generate_open_purchase_orders_report(pool, date(2025, 10, 31)).await.unwrap();
+    assert!(report.purchase_orders.is_empty());
+}
@@ -0,0 +1,145 @@
+use ledger_forge::models::{CreateUserRequest, User, UserRole};
+use ledger_forge::services::AuthService;
+use ledger_forge::services::auth::hash_token;
+use ledger_forge::utils::AppError;
+use sqlx::PgPool;
+
+mod common;
+use common::test_db::TestDb;
+
+async fn create_user(pool: &PgPool, auth_service: &AuthService, username: &str) -> User {
+    auth_service.register(pool, CreateUserRequest {
+        username: username.to_string(),
+        email: format!("{}@example.com", username),
+        password: "SecurePass123".to_string(),
+        role: UserRole::Accountant,
+    }).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_refresh_token_rotation_and_reuse() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let auth_service = AuthService::new("test-secret-key".to_string());
+    let user = create_user(pool, &auth_service, "rotating").await;
+
+    // Opaque and stored only as a hash
+    let first = auth_service.generate_refresh_token(pool, &user, None).await.unwrap();
+    assert_eq!(first.split('.').count(), 1);
+    let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM refresh_tokens WHERE token_hash = $1")
+        .bind(hash_token(&first))
+        .fetch_one(pool)
+        .await
+        .unwrap();
+    assert_eq!(stored, 1);
+
+    // Refresh tokens don't work as bearer tokens
+    assert!(auth_service.validate_token(&first).is_err());
+
+    // Each refresh hands out the next token of the family
+    let second = auth_service.rotate_refresh_token(pool, &first).await.unwrap();
+    assert_eq!(second.user.id, user.id);
+    assert_ne!(second.refresh_token, first);
+    let third = auth_service.rotate_refresh_token(pool, &second.refresh_token).await.unwrap();
+
+    // A second session is a family of its own
+    let other_session = auth_service.generate_refresh_token(pool, &user, None).await.unwrap();
+
+    // Replaying a used token revokes its family, including the latest token
+    assert!(matches!(
+        auth_service.rotate_refresh_token(pool, &first).await,
+        Err(AppError::Unauthorized(_))
+    ));
+    assert!(matches!(
+        auth_service.rotate_refresh_token(pool, &third.refresh_token).await,
+        Err(AppError::InvalidToken)
+    ));
+    assert!(auth_service.rotate_refresh_token(pool, &other_session).await.is_ok());
+
+    assert!(matches!(
+        auth_service.rotate_refresh_token(pool, "not-a-refresh-token").await,
+        Err(AppError::InvalidToken)
+    ));
+
+    // Expired tokens can't be rotated
+    let expiring = auth_service.generate_refresh_token(pool, &user, None).await.unwrap();
+    sqlx::query("UPDATE refresh_tokens SET expires_at = NOW() - INTERVAL '1 minute' WHERE token_hash = $1")
+        .bind(hash_token(&expiring))
+        .execute(pool)
+        .await
+        .unwrap();
+    assert!(matches!(
+        auth_service.rotate_refresh_token(pool, &expiring).await,
+        Err(AppError::TokenExpired)
+    ));
+}
+
+#[tokio::test]
+async fn test_logout_and_logout_everywhere() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let auth_service = AuthService::new("test-secret-key".to_string());
+    let user = create_user(pool, &auth_service, "leaving").await;
+    let someone_else = create_user(pool, &auth_service, "staying").await;
+
+    let laptop = auth_service.generate_refresh_token(pool, &user, None).await.unwrap();
+    let phone = auth_service.generate_refresh_token(pool, &user, None).await.unwrap();
+    let rotated_laptop = auth_service.rotate_refresh_token(pool, &laptop).await.unwrap().refresh_token;
+
+    // Users can only log out their own sessions
+    assert!(matches!(
+        auth_service.revoke_refresh_token(pool, someone_else.id, &phone).await,
+        Err(AppError::InvalidToken)
+    ));
+
+    // Logging out with an older token of the session ends the whole session
+    auth_service.revoke_refresh_token(pool, user.id, &laptop).await.unwrap();
+    assert!(auth_service.rotate_refresh_token(pool, &rotated_laptop).await.is_err());
+    let phone = auth_service.rotate_refresh_token(pool, &phone).await.unwrap().refresh_token;
+
+    let tablet = auth_service.generate_refresh_token(pool, &user, None).await.unwrap();
+    let other_user_session = auth_service.generate_refresh_token(pool, &someone_else, None).await.unwrap();
+
+    assert_eq!(auth_service.revoke_all_refresh_tokens(pool, user.id).await.unwrap(), 2);
+    assert!(auth_service.rotate_refresh_token(pool, &phone).await.is_err());
+    assert!(auth_service.rotate_refresh_token(pool, &tablet).await.is_err());
+    assert!(auth_service.rotate_refresh_token(pool, &other_user_session).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_refresh_needs_an_active_user_with_an_unchanged_password() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let auth_service = AuthService::new("test-secret-key".to_string());
+    let user = create_user(pool, &auth_service, "lapsed").await;
+
+    // Checked on the user itself, not only through the revocations that go with these changes
+    let token = auth_service.generate_refresh_token(pool, &user, None).await.unwrap();
+    sqlx::query("UPDATE users SET disabled_at = NOW() WHERE id = $1")
+        .bind(user.id)
+        .execute(pool)
+        .await
+        .unwrap();
+    assert!(matches!(
+        auth_service.rotate_refresh_token(pool, &token).await,
+        Err(AppError::Unauthorized(_))
+    ));
+
+    sqlx::query("UPDATE users SET disabled_at = NULL, password_changed_at = NOW() WHERE id = $1")
+        .bind(user.id)
+        .execute(pool)
+        .await
+        .unwrap();
+    assert!(matches!(
+        auth_service.rotate_refresh_token(pool, &token).await,
+        Err(AppError::TokenExpired)
+    ));
+
+    // A refused refresh doesn't use the token up
+    sqlx::query("UPDATE users SET password_changed_at = NULL WHERE id = $1")
+        .bind(user.id)
+        .execute(pool)
+        .await
+        .unwrap();
+    assert!(auth_service.rotate_refresh_token(pool, &token).await.is_ok());
+}
@@ -4,7 +4,7 @@ use sqlx::PgPool;
 use uuid::Uuid;
 
 use ledger_forge::services::{ReportingService, CacheService};
-use ledger_forge::models::{DateRequest, DateRangeRequest, TrialBalance, ProfitLossStatement, BalanceSheet, AccountsReceivableAging};
+use ledger_forge::models::{DateRequest, DateRangeRequest, TrialBalance, ProfitLossStatement, BalanceSheet, AccountsReceivableAging, DimensionFilter};
 use ledger_forge::utils::AppError;
 
 /// Unit tests for the reporting service methods
@@ -22,7 +22,7 @@ async fn test_reporting_service_trial_balance_generation() -> Result<(), sqlx::E
     };
 
     let trial_balance = reporting_service
-        .generate_trial_balance(&pool, date_request.clone())
+        .generate_trial_balance(&pool, date_request.clone(), &DimensionFilter::default())
         .await
         .map_err(|e| match e {
             AppError::DatabaseError(msg) => sqlx::Error::Protocol(format!("{}", msg)),
@@ -76,7 +76,7 @@ async fn test_reporting_service_trial_balance_earlier_date() -> Result<(), sqlx:
     };
 
     let trial_balance = reporting_service
-        .generate_trial_balance(&pool, date_request)
+        .generate_trial_balance(&pool, date_request, &DimensionFilter::default())
         .await
         .map_err(|e| match e {
             AppError::DatabaseError(msg) => sqlx::Error::Protocol(format!("{}", msg)),
@@ -94,7 +94,7 @@ async fn test_reporting_service_trial_balance_earlier_date() -> Result<(), sqlx:
         as_of_date: NaiveDate::from_ymd_opt(2024, 12, 31).unwrap(),
     };
     let year_end_balance = reporting_service
-        .generate_trial_balance(&pool, year_end_request)
+        .generate_trial_balance(&pool, year_end_request, &DimensionFilter::default())
         .await
         .map_err(|e| match e {
             AppError::DatabaseError(msg) => sqlx::Error::Protocol(format!("{}", msg)),
@@ -123,7 +123,7 @@ async fn test_reporting_service_profit_loss_generation() -> Result<(), sqlx::Err
     };
 
     let profit_loss = reporting_service
-        .generate_profit_loss(&pool, date_range.clone())
+        .generate_profit_loss(&pool, date_range.clone(), &DimensionFilter::default())
         .await
         .map_err(|e| match e {
             AppError::DatabaseError(msg) => sqlx::Error::Protocol(format!("{}", msg)),
@@ -181,7 +181,7 @@ async fn test_reporting_service_profit_loss_quarterly() -> Result<(), sqlx::Erro
     };
 
     let q1_profit_loss = reporting_service
-        .generate_profit_loss(&pool, q1_range)
+        .generate_profit_loss(&pool, q1_range, &DimensionFilter::default())
         .await
         .map_err(|e| match e {
             AppError::DatabaseError(msg) => sqlx::Error::Protocol(format!("{}", msg)),
@@ -197,7 +197,7 @@ async fn test_reporting_service_profit_loss_quarterly() -> Result<(), sqlx::Erro
     };
 
     let q2_profit_loss = reporting_service
-        .generate_profit_loss(&pool, q2_range)
+        .generate_profit_loss(&pool, q2_range, &DimensionFilter::default())
         .await
         .map_err(|e| match e {
             AppError::DatabaseError(msg) => sqlx::Error::Protocol(format!("{}", msg)),
@@ -234,7 +234,7 @@ async fn test_reporting_service_balance_sheet_generation() -> Result<(), sqlx::E
     };
 
     let balance_sheet = reporting_service
-        .generate_balance_sheet(&pool, date_request.clone())
+        .generate_balance_sheet(&pool, date_request.clone(), &DimensionFilter::default())
         .await
         .map_err(|e| match e {
             AppError::DatabaseError(msg) => sqlx::Error::Protocol(format!("{}", msg)),
@@ -303,7 +303,7 @@ async fn test_reporting_service_balance_sheet_different_dates() -> Result<(), sq
     };
 
     let mid_year_bs = reporting_service
-        .generate_balance_sheet(&pool, mid_year_request)
+        .generate_balance_sheet(&pool, mid_year_request, &DimensionFilter::default())
         .await
         .map_err(|e| match e {
             AppError::DatabaseError(msg) => sqlx::Error::Protocol(format!("{}", msg)),
@@ -313,7 +313,7 @@ async fn test_reporting_service_balance_sheet_different_dates() -> Result<(), sq
         })?;
 
     let year_end_bs = reporting_service
-        .generate_balance_sheet(&pool, year_end_request)
+        .generate_balance_sheet(&pool, year_end_request, &DimensionFilter::default())
         .await
         .map_err(|e| match e {
             AppError::DatabaseError(msg) => sqlx::Error::Protocol(format!("{}", msg)),
@@ -351,7 +351,7 @@ async fn test_reporting_service_ar_aging_generation() -> Result<(), sqlx::Error>
     };
 
     let ar_aging = reporting_service
-        .generate_ar_aging(&pool, date_request.clone())
+        .generate_ar_aging(&pool, date_request.clone(), &DimensionFilter::default())
         .await
         .map_err(|e| match e {
             AppError::DatabaseError(msg) => sqlx::Error::Protocol(format!("{}", msg)),
@@ -404,7 +404,7 @@ async fn test_reporting_service_cache_functionality() -> Result<(), sqlx::Error>
     // First call should query database
     let start1 = std::time::Instant::now();
     let trial_balance1 = reporting_service
-        .generate_trial_balance(&pool, date_request.clone())
+        .generate_trial_balance(&pool, date_request.clone(), &DimensionFilter::default())
         .await
         .map_err(|e| match e {
             AppError::DatabaseError(msg) => sqlx::Error::Protocol(format!("{}", msg)),
@@ -417,7 +417,7 @@ async fn test_reporting_service_cache_functionality() -> Result<(), sqlx::Error>
     // Second call should use cache (faster)
     let start2 = std::time::Instant::now();
     let trial_balance2 = reporting_service
-        .generate_trial_balance(&pool, date_request)
+        .generate_trial_balance(&pool, date_request, &DimensionFilter::default())
         .await
         .map_err(|e| match e {
             AppError::DatabaseError(msg) => sqlx::Error::Protocol(format!("{}", msg)),
@@ -452,7 +452,7 @@ async fn test_reporting_service_invalid_date_handling() -> Result<(), sqlx::Erro
     };
 
     let trial_balance = reporting_service
-        .generate_trial_balance(&pool, early_date_request)
+        .generate_trial_balance(&pool, early_date_request, &DimensionFilter::default())
         .await
         .map_err(|e| match e {
             AppError::DatabaseError(msg) => sqlx::Error::Protocol(format!("{}", msg)),
@@ -484,7 +484,7 @@ async fn test_reporting_service_date_range_edge_cases() -> Result<(), sqlx::Erro
     };
 
     let profit_loss = reporting_service
-        .generate_profit_loss(&pool, single_day_range)
+        .generate_profit_loss(&pool, single_day_range, &DimensionFilter::default())
         .await
         .map_err(|e| match e {
             AppError::DatabaseError(msg) => sqlx::Error::Protocol(format!("{}", msg)),
@@ -504,7 +504,7 @@ async fn test_reporting_service_date_range_edge_cases() -> Result<(), sqlx::Erro
     };
 
     let empty_profit_loss = reporting_service
-        .generate_profit_loss(&pool, empty_range)
+        .generate_profit_loss(&pool, empty_range, &DimensionFilter::default())
         .await
         .map_err(|e| match e {
             AppError::DatabaseError(msg) => sqlx::Error::Protocol(format!("{}", msg)),
@@ -540,7 +540,7 @@ async fn test_reporting_service_comprehensive_financial_validation() -> Result<(
     };
 
     let trial_balance = reporting_service
-        .generate_trial_balance(&pool, date_request.clone())
+        .generate_trial_balance(&pool, date_request.clone(), &DimensionFilter::default())
         .await
         .map_err(|e| match e {
             AppError::DatabaseError(msg) => sqlx::Error::Protocol(format!("{}", msg)),
@@ -550,7 +550,7 @@ async fn test_reporting_service_comprehensive_financial_validation() -> Result<(
         })?;
 
     let profit_loss = reporting_service
-        .generate_profit_loss(&pool, date_range)
+        .generate_profit_loss(&pool, date_range, &DimensionFilter::default())
         .await
         .map_err(|e| match e {
             AppError::DatabaseError(msg) => sqlx::Error::Protocol(format!("{}", msg)),
@@ -560,7 +560,7 @@ async fn test_reporting_service_comprehensive_financial_validation() -> Result<(
         })?;
 
     let balance_sheet = reporting_service
-        .generate_balance_sheet(&pool, date_request)
+        .generate_balance_sheet(&pool, date_request, &DimensionFilter::default())
         .await
         .map_err(|e| match e {
             AppError::DatabaseError(msg) => sqlx::Error::Protocol(format!("{}", msg)),
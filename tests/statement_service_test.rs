@@ -0,0 +1,151 @@
+use ledger_forge::models::{
+    AccountType, ContactType, CreateContactRequest, CreateInvoiceRequest, CreatePaymentRequest, CreateCreditMemoRequest,
+    CreateCreditMemoLineItemRequest, StatementStyle,
+};
+use ledger_forge::services::StatementService;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+mod common;
+use common::test_db::TestDb;
+use common::fixtures::{
+    date, dec, create_account, contact_request, create_contact, create_customer, create_sent_invoice, invoice_request,
+    payment_request,
+};
+use common::services::{statement_service, payment_service, credit_memo_service};
+
+/// A sent invoice due 29 days after it is issued
+async fn invoice(pool: &PgPool, number: &str, customer_id: Uuid, invoice_date: NaiveDate, amount: &str, revenue_account: Uuid) -> Uuid {
+    create_sent_invoice(pool, CreateInvoiceRequest {
+        invoice_date,
+        due_date: invoice_date + chrono::Duration::days(29),
+        ..invoice_request(number, customer_id, revenue_account, amount)
+    }).await
+}
+
+async fn pay(pool: &PgPool, customer_id: Uuid, number: &str, payment_date: NaiveDate, amount: &str, applications: &[(Uuid, &str)]) {
+    payment_service()
+        .create_payment(pool, CreatePaymentRequest {
+            payment_number: Some(number.to_string()),
+            payment_date,
+            payment_method: "Cheque".to_string(),
+            ..payment_request(customer_id, amount, applications)
+        })
+        .await
+        .unwrap();
+}
+
+/// A customer with a partly paid September invoice and, in October, a new invoice, an
+/// unapplied credit memo and an unapplied payment. Returns the revenue account and customer.
+async fn customer_with_activity(pool: &PgPool) -> (Uuid, Uuid) {
+    let revenue_account = create_account(pool, "4000", "Sales Revenue", AccountType::Revenue).await;
+    let customer = create_contact(pool, CreateContactRequest {
+        billing_address: Some("1 Main St\nToronto ON".to_string()),
+        ..contact_request(ContactType::Customer, "Statement Customer")
+    }).await;
+
+    // September: invoice due on the 30th, partly paid
+    let september = invoice(pool, "INV-S-1", customer, date(2025, 9, 1), "1000.00", revenue_account).await;
+    pay(pool, customer, "PMT-S-1", date(2025, 9, 15), "400.00", &[(september, "400.00")]).await;
+
+    // October: new invoice, an unapplied credit memo and an unapplied payment
+    invoice(pool, "INV-S-2", customer, date(2025, 10, 5), "500.00", revenue_account).await;
+    credit_memo_service().create_credit_memo(pool, CreateCreditMemoRequest {
+        credit_memo_number: Some("CM-S-1".to_string()),
+        customer_id: customer,
+        credit_memo_date: date(2025, 10, 10),
+        invoice_id: None,
+        memo: None,
+        company_id: None,
+        ar_account_id: None,
+        line_items: vec![CreateCreditMemoLineItemRequest {
+            line_number: 1,
+            item_description: "Goodwill".to_string(),
+            quantity: Decimal::ONE,
+            unit_price: dec("100.00"),
+            tax_code: None,
+            revenue_account_id: revenue_account,
+        }],
+        applications: vec![],
+    }).await.unwrap();
+    pay(pool, customer, "PMT-S-2", date(2025, 10, 20), "200.00", &[]).await;
+
+    (revenue_account, customer)
+}
+
+#[tokio::test]
+async fn test_balance_forward_statement_and_aging() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (_, customer) = customer_with_activity(pool).await;
+
+    // September activity rolls into the opening balance
+    let statement = statement_service().generate_statement(
+        pool, customer, None, date(2025, 10, 31), StatementStyle::BalanceForward,
+    ).await.unwrap();
+    assert_eq!(statement.start_date, date(2025, 10, 1));
+    assert_eq!(statement.opening_balance, dec("600.00"));
+    assert_eq!(statement.lines.len(), 3);
+    assert_eq!(statement.total_charges, dec("500.00"));
+    assert_eq!(statement.total_credits, dec("300.00"));
+    assert_eq!(statement.lines.last().unwrap().running_balance, dec("800.00"));
+    assert_eq!(statement.closing_balance, dec("800.00"));
+
+    // Aging matches the AR aging buckets: 31 days past due and not yet due
+    assert_eq!(statement.aging.days_31_60, dec("600.00"));
+    assert_eq!(statement.aging.current, dec("500.00"));
+    assert_eq!(statement.aging.total, dec("1100.00"));
+}
+
+#[tokio::test]
+async fn test_open_item_statement_lists_open_amounts() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (_, customer) = customer_with_activity(pool).await;
+
+    // Only unpaid invoices and unused credits, at their open amounts
+    let open_items = statement_service().generate_statement(
+        pool, customer, None, date(2025, 10, 31), StatementStyle::OpenItem,
+    ).await.unwrap();
+    let open_amounts: Vec<Decimal> = open_items.lines.iter().map(|l| l.open_balance).collect();
+    assert_eq!(open_amounts, vec![dec("600.00"), dec("500.00"), dec("-100.00"), dec("-200.00")]);
+    assert_eq!(open_items.lines.last().unwrap().running_balance, open_items.closing_balance);
+}
+
+#[tokio::test]
+async fn test_statement_ignores_later_activity() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (_, customer) = customer_with_activity(pool).await;
+
+    // As of mid-September the later payment and invoices don't exist yet
+    let earlier = statement_service().generate_statement(
+        pool, customer, Some(date(2025, 9, 1)), date(2025, 9, 10), StatementStyle::OpenItem,
+    ).await.unwrap();
+    assert_eq!(earlier.closing_balance, dec("1000.00"));
+    assert_eq!(earlier.lines.len(), 1);
+}
+
+#[tokio::test]
+async fn test_batch_covers_customers_with_a_balance() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let (revenue_account, customer) = customer_with_activity(pool).await;
+
+    // A customer whose only invoice is fully paid has no balance
+    let settled_customer = create_customer(pool, "Settled Customer").await;
+    let paid = invoice(pool, "INV-S-3", settled_customer, date(2025, 10, 1), "50.00", revenue_account).await;
+    pay(pool, settled_customer, "PMT-S-3", date(2025, 10, 2), "50.00", &[(paid, "50.00")]).await;
+
+    let batch = statement_service().generate_batch(
+        pool, None, date(2025, 10, 31), StatementStyle::BalanceForward, None,
+    ).await.unwrap();
+    assert_eq!(batch.len(), 1);
+    assert_eq!(batch[0].customer_id, customer);
+
+    // The batch renders to one PDF
+    let pdf = StatementService::render_pdf(&batch);
+    assert!(pdf.starts_with(b"%PDF"));
+}
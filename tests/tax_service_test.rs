@@ -0,0 +1,418 @@
+use ledger_forge::models::{
+    AccountType, CreateTaxCodeRequest, CreateTaxRateRequest, SetupCanadianTaxCodesRequest, CreateInvoiceRequest,
+    CreateInvoiceLineItemRequest, InvoiceStatus, CreateBillRequest, CreateBillLineItemRequest,
+    TaxReturnPeriodRequest, FileTaxReturnRequest, TaxReturnStatus, TaxReturn,
+};
+use ledger_forge::services::TaxService;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+mod common;
+use common::test_db::TestDb;
+use common::fixtures::{
+    date, dec, create_account, create_customer, create_vendor, create_sent_invoice, invoice_request, balance,
+    get_invoice,
+};
+use common::services::{tax_service, invoice_service, bill_service};
+
+fn rate_request(tax_name: &str, rate: &str, account_id: Uuid, effective_from: NaiveDate) -> CreateTaxRateRequest {
+    CreateTaxRateRequest {
+        tax_name: tax_name.to_string(),
+        agency: None,
+        rate: dec(rate),
+        sequence: None,
+        is_compound: None,
+        is_recoverable: None,
+        liability_account_id: account_id,
+        recoverable_account_id: None,
+        effective_from,
+        effective_to: None,
+    }
+}
+
+fn tax_line(line_number: i32, amount: &str, tax_code: &str, revenue_account_id: Uuid) -> CreateInvoiceLineItemRequest {
+    CreateInvoiceLineItemRequest {
+        line_number,
+        item_description: format!("Line {}", line_number),
+        quantity: Decimal::ONE,
+        unit_price: dec(amount),
+        discount_percent: None,
+        tax_code: Some(tax_code.to_string()),
+        revenue_account_id,
+        item_id: None,
+        dimension_value_ids: Vec::new(),
+    }
+}
+
+#[tokio::test]
+async fn test_rate_change_uses_effective_dates() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let hst_account = create_account(pool, "2200", "GST/HST Payable", AccountType::Liability).await;
+
+    let tax_code = tax_service().create_tax_code(pool, CreateTaxCodeRequest {
+        code: "HST-NS".to_string(),
+        name: "HST Nova Scotia".to_string(),
+        description: None,
+        company_id: None,
+        rates: vec![rate_request("HST", "15", hst_account, date(2024, 1, 1))],
+    }).await.unwrap();
+
+    // Nova Scotia HST dropped to 14% on April 1, 2025
+    tax_service().add_tax_rate(pool, tax_code.tax_code.id, rate_request("HST", "14", hst_account, date(2025, 4, 1)))
+        .await
+        .unwrap();
+
+    let history = tax_service().get_tax_code(pool, tax_code.tax_code.id).await.unwrap();
+    assert_eq!(history.rates.len(), 2);
+    assert_eq!(history.rates[0].effective_to, Some(date(2025, 3, 31)));
+
+    let mut conn = pool.acquire().await.unwrap();
+    let before = TaxService::calculate_tax(&mut conn, None, "HST-NS", Decimal::from(100), date(2025, 3, 31)).await.unwrap();
+    let after = TaxService::calculate_tax(&mut conn, None, "HST-NS", Decimal::from(100), date(2025, 4, 1)).await.unwrap();
+
+    assert_eq!(before[0].tax_amount, dec("15.00"));
+    assert_eq!(after[0].tax_amount, dec("14.00"));
+}
+
+#[tokio::test]
+async fn test_compound_tax_is_charged_on_preceding_tax() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let gst_account = create_account(pool, "2200", "GST/HST Payable", AccountType::Liability).await;
+    let pst_account = create_account(pool, "2210", "PST Payable", AccountType::Liability).await;
+
+    let compound = CreateTaxRateRequest {
+        sequence: Some(2),
+        is_compound: Some(true),
+        ..rate_request("PST", "9.5", pst_account, date(2010, 1, 1))
+    };
+    tax_service().create_tax_code(pool, CreateTaxCodeRequest {
+        code: "GST+PST-C".to_string(),
+        name: "GST + compound PST".to_string(),
+        description: None,
+        company_id: None,
+        rates: vec![rate_request("GST", "5", gst_account, date(2010, 1, 1)), compound],
+    }).await.unwrap();
+
+    let mut conn = pool.acquire().await.unwrap();
+    let taxes = TaxService::calculate_tax(&mut conn, None, "GST+PST-C", Decimal::from(100), date(2025, 1, 1)).await.unwrap();
+
+    assert_eq!(taxes.len(), 2);
+    assert_eq!(taxes[0].tax_amount, dec("5.00"));
+    // 9.5% of 105.00
+    assert_eq!(taxes[1].taxable_amount, dec("105.00"));
+    assert_eq!(taxes[1].tax_amount, dec("9.98"));
+}
+
+#[tokio::test]
+async fn test_unknown_tax_code_is_rejected() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+
+    let mut conn = pool.acquire().await.unwrap();
+    let unknown = TaxService::calculate_tax(&mut conn, None, "NOPE", Decimal::from(100), date(2025, 1, 1)).await;
+    assert!(unknown.is_err());
+}
+
+struct TaxedInvoice {
+    ar_account: Uuid,
+    gst_account: Uuid,
+    pst_account: Uuid,
+    revenue_account: Uuid,
+    invoice: Uuid,
+}
+
+/// A draft invoice with an HST-ON line of 100.00, a GST+PST-BC line of 200.00 and an
+/// exempt line of 50.00
+async fn taxed_invoice(pool: &PgPool) -> TaxedInvoice {
+    let ar_account = create_account(pool, "1200", "Accounts Receivable", AccountType::Asset).await;
+    let gst_account = create_account(pool, "2200", "GST/HST Payable", AccountType::Liability).await;
+    let pst_account = create_account(pool, "2210", "PST Payable", AccountType::Liability).await;
+    let revenue_account = create_account(pool, "4000", "Sales Revenue", AccountType::Revenue).await;
+
+    tax_service().setup_canadian_tax_codes(pool, SetupCanadianTaxCodesRequest {
+        gst_hst_account_id: gst_account,
+        itc_account_id: None,
+        pst_account_id: Some(pst_account),
+        qst_account_id: None,
+        effective_from: date(2025, 4, 1),
+        company_id: None,
+    }).await.unwrap();
+
+    let customer = create_customer(pool, "Tax Customer").await;
+    let invoice = invoice_service().create_invoice(pool, CreateInvoiceRequest {
+        invoice_date: date(2025, 10, 1),
+        due_date: date(2025, 10, 31),
+        ar_account_id: Some(ar_account),
+        line_items: vec![
+            tax_line(1, "100.00", "HST-ON", revenue_account),
+            tax_line(2, "200.00", "GST+PST-BC", revenue_account),
+            tax_line(3, "50.00", "E", revenue_account),
+        ],
+        ..invoice_request("INV-TAX-1", customer, revenue_account, "350.00")
+    }).await.unwrap();
+
+    TaxedInvoice { ar_account, gst_account, pst_account, revenue_account, invoice: invoice.invoice.id }
+}
+
+#[tokio::test]
+async fn test_invoice_totals_tax_per_line() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let setup = taxed_invoice(pool).await;
+
+    let invoice = invoice_service().get_invoice(pool, setup.invoice).await.unwrap().unwrap();
+    // 13.00 HST + 10.00 GST + 14.00 PST
+    assert_eq!(invoice.invoice.subtotal, dec("350.00"));
+    assert_eq!(invoice.invoice.tax_amount, dec("37.00"));
+    assert_eq!(invoice.invoice.total_amount, dec("387.00"));
+    assert_eq!(invoice.invoice.balance, dec("387.00"));
+    assert_eq!(invoice.line_items[0].tax_amount, dec("13.00"));
+    assert_eq!(invoice.line_items[1].tax_amount, dec("24.00"));
+    assert_eq!(invoice.line_items[2].tax_amount, Decimal::ZERO);
+    assert_eq!(invoice.taxes.len(), 3);
+
+    // Drafts are not posted
+    assert_eq!(balance(pool, setup.ar_account).await, Decimal::ZERO);
+}
+
+#[tokio::test]
+async fn test_sent_invoice_posts_tax_to_liability_accounts() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let setup = taxed_invoice(pool).await;
+
+    let sent = invoice_service().update_invoice_status(pool, setup.invoice, InvoiceStatus::Sent).await.unwrap();
+    assert!(sent.transaction_id.is_some());
+
+    assert_eq!(balance(pool, setup.ar_account).await, dec("387.00"));
+    assert_eq!(balance(pool, setup.gst_account).await, dec("-23.00"));
+    assert_eq!(balance(pool, setup.pst_account).await, dec("-14.00"));
+    assert_eq!(balance(pool, setup.revenue_account).await, dec("-350.00"));
+}
+
+#[tokio::test]
+async fn test_voiding_a_taxed_invoice_reverses_the_posting() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let setup = taxed_invoice(pool).await;
+    invoice_service().update_invoice_status(pool, setup.invoice, InvoiceStatus::Sent).await.unwrap();
+
+    invoice_service().update_invoice_status(pool, setup.invoice, InvoiceStatus::Void).await.unwrap();
+    assert_eq!(balance(pool, setup.ar_account).await, Decimal::ZERO);
+    assert_eq!(balance(pool, setup.gst_account).await, Decimal::ZERO);
+}
+
+struct Quarter {
+    bank_account: Uuid,
+    ar_account: Uuid,
+    ap_account: Uuid,
+    itc_account: Uuid,
+    hst_account: Uuid,
+    revenue_account: Uuid,
+    expense_account: Uuid,
+    customer: Uuid,
+    vendor: Uuid,
+    invoice: Uuid,
+    bill: Uuid,
+}
+
+/// An HST-ON supplies bill of 300.00
+fn bill_request(quarter: &Quarter, bill_date: NaiveDate) -> CreateBillRequest {
+    CreateBillRequest {
+        bill_number: Some("V-100".to_string()),
+        vendor_id: quarter.vendor,
+        bill_date,
+        due_date: bill_date,
+        memo: None,
+        company_id: None,
+        ap_account_id: Some(quarter.ap_account),
+        line_items: vec![CreateBillLineItemRequest {
+            line_number: 1,
+            description: Some("Supplies".to_string()),
+            amount: dec("300.00"),
+            expense_account_id: quarter.expense_account,
+            billable: None,
+            customer_id: None,
+            item_id: None,
+            quantity: None,
+            tax_code: Some("HST-ON".to_string()),
+            dimension_value_ids: Vec::new(),
+        }],
+    }
+}
+
+/// An invoice with an HST-ON line of 1000.00 and an exempt line of 200.00
+fn sales_request(quarter: &Quarter, invoice_number: &str, invoice_date: NaiveDate) -> CreateInvoiceRequest {
+    CreateInvoiceRequest {
+        invoice_date,
+        due_date: invoice_date,
+        ar_account_id: Some(quarter.ar_account),
+        line_items: vec![
+            tax_line(1, "1000.00", "HST-ON", quarter.revenue_account),
+            tax_line(2, "200.00", "E", quarter.revenue_account),
+        ],
+        ..invoice_request(invoice_number, quarter.customer, quarter.revenue_account, "1200.00")
+    }
+}
+
+/// Third quarter of 2025: a sent HST invoice in July and an HST bill in August
+async fn quarter(pool: &PgPool) -> Quarter {
+    let bank_account = create_account(pool, "1000", "Bank", AccountType::Asset).await;
+    let ar_account = create_account(pool, "1200", "Accounts Receivable", AccountType::Asset).await;
+    let itc_account = create_account(pool, "1300", "GST/HST ITC Receivable", AccountType::Asset).await;
+    let ap_account = create_account(pool, "2000", "Accounts Payable", AccountType::Liability).await;
+    let hst_account = create_account(pool, "2200", "GST/HST Payable", AccountType::Liability).await;
+    let revenue_account = create_account(pool, "4000", "Sales Revenue", AccountType::Revenue).await;
+    let expense_account = create_account(pool, "5000", "Supplies Expense", AccountType::Expense).await;
+
+    tax_service().setup_canadian_tax_codes(pool, SetupCanadianTaxCodesRequest {
+        gst_hst_account_id: hst_account,
+        itc_account_id: Some(itc_account),
+        pst_account_id: None,
+        qst_account_id: None,
+        effective_from: date(2025, 4, 1),
+        company_id: None,
+    }).await.unwrap();
+
+    let mut quarter = Quarter {
+        bank_account,
+        ar_account,
+        ap_account,
+        itc_account,
+        hst_account,
+        revenue_account,
+        expense_account,
+        customer: create_customer(pool, "Return Customer").await,
+        vendor: create_vendor(pool, "Return Vendor").await,
+        invoice: Uuid::nil(),
+        bill: Uuid::nil(),
+    };
+    quarter.invoice = create_sent_invoice(pool, sales_request(&quarter, "INV-R1", date(2025, 7, 15))).await;
+    quarter.bill = bill_service().create_bill(pool, bill_request(&quarter, date(2025, 8, 10))).await.unwrap().id;
+    quarter
+}
+
+fn period() -> TaxReturnPeriodRequest {
+    TaxReturnPeriodRequest {
+        agency: None,
+        period_start: date(2025, 7, 1),
+        period_end: date(2025, 9, 30),
+        company_id: None,
+    }
+}
+
+fn file_request(quarter: &Quarter) -> FileTaxReturnRequest {
+    FileTaxReturnRequest {
+        filed_date: date(2025, 10, 31),
+        settlement_account_id: quarter.bank_account,
+    }
+}
+
+async fn file_quarter(pool: &PgPool, quarter: &Quarter) -> TaxReturn {
+    let tax_return = tax_service().create_tax_return(pool, period()).await.unwrap();
+    tax_service().file_tax_return(pool, tax_return.id, file_request(quarter), None).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_recoverable_bill_tax_posts_to_itc_receivable() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let quarter = quarter(pool).await;
+
+    let bill = bill_service().get_bill_by_id(pool, quarter.bill).await.unwrap().unwrap().bill;
+    assert_eq!(bill.tax_amount, dec("39.00"));
+    assert_eq!(bill.total_amount, dec("339.00"));
+    assert!(bill.transaction_id.is_some());
+    assert_eq!(balance(pool, quarter.itc_account).await, dec("39.00"));
+    assert_eq!(balance(pool, quarter.expense_account).await, dec("300.00"));
+    assert_eq!(balance(pool, quarter.ap_account).await, dec("-339.00"));
+}
+
+#[tokio::test]
+async fn test_return_worksheet_nets_itcs_against_tax_collected() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    quarter(pool).await;
+
+    let worksheet = tax_service().generate_return_worksheet(pool, period()).await.unwrap();
+    assert_eq!(worksheet.agency, "CRA");
+    assert_eq!(worksheet.lines.line_101_sales, dec("1200.00"));
+    assert_eq!(worksheet.lines.taxable_sales, dec("1000.00"));
+    assert_eq!(worksheet.lines.line_103_tax_collected, dec("130.00"));
+    assert_eq!(worksheet.lines.line_108_total_itcs, dec("39.00"));
+    assert_eq!(worksheet.lines.line_109_net_tax, dec("91.00"));
+}
+
+#[tokio::test]
+async fn test_return_leaves_out_documents_not_in_the_ledger() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let quarter = quarter(pool).await;
+
+    // Sent without an AR account, so its HST was never posted
+    let unposted = create_sent_invoice(pool, CreateInvoiceRequest {
+        ar_account_id: None,
+        ..sales_request(&quarter, "INV-R3", date(2025, 8, 20))
+    }).await;
+    let unposted = get_invoice(pool, unposted).await;
+    assert!(unposted.transaction_id.is_none());
+    assert_eq!(unposted.tax_amount, dec("130.00"));
+
+    let worksheet = tax_service().generate_return_worksheet(pool, period()).await.unwrap();
+    assert_eq!(worksheet.lines.line_101_sales, dec("1200.00"));
+    assert_eq!(worksheet.lines.line_103_tax_collected, dec("130.00"));
+
+    // The settlement clears only what the ledger holds
+    file_quarter(pool, &quarter).await;
+    assert_eq!(balance(pool, quarter.hst_account).await, Decimal::ZERO);
+    assert_eq!(balance(pool, quarter.bank_account).await, dec("-91.00"));
+}
+
+#[tokio::test]
+async fn test_one_return_per_period() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    quarter(pool).await;
+
+    let tax_return = tax_service().create_tax_return(pool, period()).await.unwrap();
+    assert_eq!(tax_return.status, TaxReturnStatus::Draft);
+    assert!(tax_service().create_tax_return(pool, period()).await.is_err());
+}
+
+#[tokio::test]
+async fn test_filing_settles_tax_accounts_against_the_bank() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let quarter = quarter(pool).await;
+
+    let filed = file_quarter(pool, &quarter).await;
+    assert_eq!(filed.status, TaxReturnStatus::Filed);
+    assert!(filed.transaction_id.is_some());
+
+    assert_eq!(balance(pool, quarter.hst_account).await, Decimal::ZERO);
+    assert_eq!(balance(pool, quarter.itc_account).await, Decimal::ZERO);
+    assert_eq!(balance(pool, quarter.bank_account).await, dec("-91.00"));
+
+    assert!(tax_service().file_tax_return(pool, filed.id, file_request(&quarter), None).await.is_err());
+}
+
+#[tokio::test]
+async fn test_filed_period_is_locked() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let quarter = quarter(pool).await;
+    file_quarter(pool, &quarter).await;
+
+    assert!(bill_service().create_bill(pool, bill_request(&quarter, date(2025, 9, 1))).await.is_err());
+    assert!(invoice_service().update_invoice_status(pool, quarter.invoice, InvoiceStatus::Void).await.is_err());
+
+    let late = invoice_service().create_invoice(pool, sales_request(&quarter, "INV-R2", date(2025, 9, 30))).await.unwrap();
+    assert!(invoice_service().update_invoice_status(pool, late.invoice.id, InvoiceStatus::Sent).await.is_err());
+
+    // The next period is still open
+    bill_service().create_bill(pool, bill_request(&quarter, date(2025, 10, 1))).await.unwrap();
+}
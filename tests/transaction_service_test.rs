@@ -42,6 +42,7 @@ async fn test_create_transaction_success() {
         transaction_date: Utc::now().date_naive(),
         description: Some("Test transaction".to_string()),
         reference_number: Some("TXN001".to_string()),
+        entry_number: None,
         contact_id: None,
         company_id: None,
         journal_type: Some(JournalType::General),
@@ -51,12 +52,14 @@ async fn test_create_transaction_success() {
                 description: Some("Cash received".to_string()),
                 debit_amount: Some(Decimal::new(10000, 2)), // $100.00
                 credit_amount: None,
+                dimension_value_ids: Vec::new(),
             },
             CreateLineItemRequest {
                 account_id: revenue_account.id,
                 description: Some("Sales revenue".to_string()),
                 debit_amount: None,
                 credit_amount: Some(Decimal::new(10000, 2)), // $100.00
+                dimension_value_ids: Vec::new(),
             },
         ],
     };
@@ -91,6 +94,7 @@ async fn test_create_transaction_unbalanced() {
         transaction_date: Utc::now().date_naive(),
         description: Some("Unbalanced transaction".to_string()),
         reference_number: None,
+        entry_number: None,
         contact_id: None,
         company_id: None,
         journal_type: None,
@@ -100,12 +104,14 @@ async fn test_create_transaction_unbalanced() {
                 description: None,
                 debit_amount: Some(Decimal::new(10000, 2)), // $100.00
                 credit_amount: None,
+                dimension_value_ids: Vec::new(),
             },
             CreateLineItemRequest {
                 account_id: revenue_account.id,
                 description: None,
                 debit_amount: None,
                 credit_amount: Some(Decimal::new(5000, 2)), // $50.00 - UNBALANCED!
+                dimension_value_ids: Vec::new(),
             },
         ],
     };
@@ -133,6 +139,7 @@ async fn test_create_transaction_both_debit_and_credit() {
         transaction_date: Utc::now().date_naive(),
         description: Some("Invalid transaction".to_string()),
         reference_number: None,
+        entry_number: None,
         contact_id: None,
         company_id: None,
         journal_type: None,
@@ -142,6 +149,7 @@ async fn test_create_transaction_both_debit_and_credit() {
                 description: None,
                 debit_amount: Some(Decimal::new(10000, 2)),
                 credit_amount: Some(Decimal::new(10000, 2)), // Both debit and credit!
+                dimension_value_ids: Vec::new(),
             },
         ],
     };
@@ -165,6 +173,7 @@ async fn test_create_transaction_invalid_account() {
         transaction_date: Utc::now().date_naive(),
         description: Some("Transaction with invalid account".to_string()),
         reference_number: None,
+        entry_number: None,
         contact_id: None,
         company_id: None,
         journal_type: None,
@@ -174,12 +183,14 @@ async fn test_create_transaction_invalid_account() {
                 description: None,
                 debit_amount: Some(Decimal::new(10000, 2)),
                 credit_amount: None,
+                dimension_value_ids: Vec::new(),
             },
             CreateLineItemRequest {
                 account_id: fake_account_id,
                 description: None,
                 debit_amount: None,
                 credit_amount: Some(Decimal::new(10000, 2)),
+                dimension_value_ids: Vec::new(),
             },
         ],
     };
@@ -208,6 +219,7 @@ async fn test_get_transaction_by_id() {
         transaction_date: Utc::now().date_naive(),
         description: Some("Get test".to_string()),
         reference_number: None,
+        entry_number: None,
         contact_id: None,
         company_id: None,
         journal_type: None,
@@ -217,12 +229,14 @@ async fn test_get_transaction_by_id() {
                 description: None,
                 debit_amount: Some(Decimal::new(5000, 2)),
                 credit_amount: None,
+                dimension_value_ids: Vec::new(),
             },
             CreateLineItemRequest {
                 account_id: revenue_account.id,
                 description: None,
                 debit_amount: None,
                 credit_amount: Some(Decimal::new(5000, 2)),
+                dimension_value_ids: Vec::new(),
             },
         ],
     };
@@ -277,6 +291,7 @@ async fn test_list_transactions() {
             transaction_date: Utc::now().date_naive(),
             description: Some(format!("Transaction {}", i)),
             reference_number: None,
+            entry_number: None,
             contact_id: None,
             company_id: None,
             journal_type: None,
@@ -286,12 +301,14 @@ async fn test_list_transactions() {
                     description: None,
                     debit_amount: Some(Decimal::new(1000 * i, 2)),
                     credit_amount: None,
+                    dimension_value_ids: Vec::new(),
                 },
                 CreateLineItemRequest {
                     account_id: revenue_account.id,
                     description: None,
                     debit_amount: None,
                     credit_amount: Some(Decimal::new(1000 * i, 2)),
+                    dimension_value_ids: Vec::new(),
                 },
             ],
         };
@@ -299,7 +316,7 @@ async fn test_list_transactions() {
     }
 
     // List all transactions
-    let result = service.list_transactions(&pool, None, None, None).await;
+    let result = service.list_transactions(&pool, None, None).await;
     assert!(result.is_ok());
     let transactions = result.unwrap();
     assert_eq!(transactions.len(), 3);
@@ -327,6 +344,7 @@ async fn test_list_transactions_with_status_filter() {
         transaction_date: Utc::now().date_naive(),
         description: Some("Draft transaction".to_string()),
         reference_number: None,
+        entry_number: None,
         contact_id: None,
         company_id: None,
         journal_type: None,
@@ -336,19 +354,21 @@ async fn test_list_transactions_with_status_filter() {
                 description: None,
                 debit_amount: Some(Decimal::new(10000, 2)),
                 credit_amount: None,
+                dimension_value_ids: Vec::new(),
             },
             CreateLineItemRequest {
                 account_id: revenue_account.id,
                 description: None,
                 debit_amount: None,
                 credit_amount: Some(Decimal::new(10000, 2)),
+                dimension_value_ids: Vec::new(),
             },
         ],
     };
     service.create_transaction(&pool, req, None).await.unwrap();
 
     // List draft transactions
-    let result = service.list_transactions(&pool, Some(TransactionStatus::Draft), None, None).await;
+    let result = service.list_transactions(&pool, Some(TransactionStatus::Draft), None).await;
     assert!(result.is_ok());
     let transactions = result.unwrap();
     assert_eq!(transactions.len(), 1);
@@ -375,6 +395,7 @@ async fn test_update_transaction_status_draft_to_posted() {
         transaction_date: Utc::now().date_naive(),
         description: Some("Status test".to_string()),
         reference_number: None,
+        entry_number: None,
         contact_id: None,
         company_id: None,
         journal_type: None,
@@ -384,12 +405,14 @@ async fn test_update_transaction_status_draft_to_posted() {
                 description: None,
                 debit_amount: Some(Decimal::new(10000, 2)),
                 credit_amount: None,
+                dimension_value_ids: Vec::new(),
             },
             CreateLineItemRequest {
                 account_id: revenue_account.id,
                 description: None,
                 debit_amount: None,
                 credit_amount: Some(Decimal::new(10000, 2)),
+                dimension_value_ids: Vec::new(),
             },
         ],
     };
@@ -425,6 +448,7 @@ async fn test_update_transaction_status_posted_to_void() {
         transaction_date: Utc::now().date_naive(),
         description: Some("Void test".to_string()),
         reference_number: None,
+        entry_number: None,
         contact_id: None,
         company_id: None,
         journal_type: None,
@@ -434,12 +458,14 @@ async fn test_update_transaction_status_posted_to_void() {
                 description: None,
                 debit_amount: Some(Decimal::new(10000, 2)),
                 credit_amount: None,
+                dimension_value_ids: Vec::new(),
             },
             CreateLineItemRequest {
                 account_id: revenue_account.id,
                 description: None,
                 debit_amount: None,
                 credit_amount: Some(Decimal::new(10000, 2)),
+                dimension_value_ids: Vec::new(),
             },
         ],
     };
@@ -477,6 +503,7 @@ async fn test_update_transaction_status_invalid_transition() {
         transaction_date: Utc::now().date_naive(),
         description: Some("Invalid transition test".to_string()),
         reference_number: None,
+        entry_number: None,
         contact_id: None,
         company_id: None,
         journal_type: None,
@@ -486,12 +513,14 @@ async fn test_update_transaction_status_invalid_transition() {
                 description: None,
                 debit_amount: Some(Decimal::new(10000, 2)),
                 credit_amount: None,
+                dimension_value_ids: Vec::new(),
             },
             CreateLineItemRequest {
                 account_id: revenue_account.id,
                 description: None,
                 debit_amount: None,
                 credit_amount: Some(Decimal::new(10000, 2)),
+                dimension_value_ids: Vec::new(),
             },
         ],
     };
@@ -526,6 +555,7 @@ async fn test_delete_transaction_draft() {
         transaction_date: Utc::now().date_naive(),
         description: Some("Delete test".to_string()),
         reference_number: None,
+        entry_number: None,
         contact_id: None,
         company_id: None,
         journal_type: None,
@@ -535,12 +565,14 @@ async fn test_delete_transaction_draft() {
                 description: None,
                 debit_amount: Some(Decimal::new(10000, 2)),
                 credit_amount: None,
+                dimension_value_ids: Vec::new(),
             },
             CreateLineItemRequest {
                 account_id: revenue_account.id,
                 description: None,
                 debit_amount: None,
                 credit_amount: Some(Decimal::new(10000, 2)),
+                dimension_value_ids: Vec::new(),
             },
         ],
     };
@@ -576,6 +608,7 @@ async fn test_delete_transaction_posted_fails() {
         transaction_date: Utc::now().date_naive(),
         description: Some("Delete posted test".to_string()),
         reference_number: None,
+        entry_number: None,
         contact_id: None,
         company_id: None,
         journal_type: None,
@@ -585,12 +618,14 @@ async fn test_delete_transaction_posted_fails() {
                 description: None,
                 debit_amount: Some(Decimal::new(10000, 2)),
                 credit_amount: None,
+                dimension_value_ids: Vec::new(),
             },
             CreateLineItemRequest {
                 account_id: revenue_account.id,
                 description: None,
                 debit_amount: None,
                 credit_amount: Some(Decimal::new(10000, 2)),
+                dimension_value_ids: Vec::new(),
             },
         ],
     };
@@ -627,6 +662,7 @@ async fn test_get_account_balance() {
         transaction_date: Utc::now().date_naive(),
         description: Some("Balance test".to_string()),
         reference_number: None,
+        entry_number: None,
         contact_id: None,
         company_id: None,
         journal_type: None,
@@ -636,12 +672,14 @@ async fn test_get_account_balance() {
                 description: None,
                 debit_amount: Some(Decimal::new(10000, 2)), // $100.00
                 credit_amount: None,
+                dimension_value_ids: Vec::new(),
             },
             CreateLineItemRequest {
                 account_id: revenue_account.id,
                 description: None,
                 debit_amount: None,
                 credit_amount: Some(Decimal::new(10000, 2)), // $100.00
+                dimension_value_ids: Vec::new(),
             },
         ],
     };
@@ -682,6 +720,7 @@ async fn test_get_account_balance_draft_not_included() {
         transaction_date: Utc::now().date_naive(),
         description: Some("Draft balance test".to_string()),
         reference_number: None,
+        entry_number: None,
         contact_id: None,
         company_id: None,
         journal_type: None,
@@ -691,12 +730,14 @@ async fn test_get_account_balance_draft_not_included() {
                 description: None,
                 debit_amount: Some(Decimal::new(10000, 2)),
                 credit_amount: None,
+                dimension_value_ids: Vec::new(),
             },
             CreateLineItemRequest {
                 account_id: revenue_account.id,
                 description: None,
                 debit_amount: None,
                 credit_amount: Some(Decimal::new(10000, 2)),
+                dimension_value_ids: Vec::new(),
             },
         ],
     };
@@ -0,0 +1,211 @@
+use ledger_forge::models::{
+    CreateUserRequest, InviteUserRequest, LoginRequest, ResetPasswordRequest, User, UserRole,
+};
+use ledger_forge::services::auth::{LoginOutcome, RegistrationMode};
+use ledger_forge::services::{AuthService, Mailer};
+use ledger_forge::utils::AppError;
+use sqlx::PgPool;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+mod common;
+use common::test_db::TestDb;
+
+fn signup(username: &str, role: UserRole) -> CreateUserRequest {
+    CreateUserRequest {
+        username: username.to_string(),
+        email: format!("{}@example.com", username),
+        password: "SecurePass123".to_string(),
+        role,
+    }
+}
+
+async fn create_user(pool: &PgPool, auth_service: &AuthService, username: &str, role: UserRole) -> User {
+    auth_service.register(pool, signup(username, role)).await.unwrap()
+}
+
+async fn login(pool: &PgPool, auth_service: &AuthService, username: &str, password: &str) -> Result<LoginOutcome, AppError> {
+    auth_service.login(pool, LoginRequest {
+        username: username.to_string(),
+        password: password.to_string(),
+    }).await
+}
+
+/// Codes emailed so far, in order
+fn sent_codes(mail_file: &PathBuf) -> Vec<String> {
+    let mail = std::fs::read_to_string(mail_file).unwrap_or_default();
+    let lines: Vec<&str> = mail.lines().collect();
+    lines
+        .windows(2)
+        .filter(|pair| pair[0] == "Use this code to choose a new password:")
+        .map(|pair| pair[1].to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_self_registration_and_bootstrap() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+
+    // Viewers only by default
+    let auth_service = AuthService::new("test-secret-key".to_string());
+    assert!(matches!(
+        auth_service.self_register(pool, signup("climber", UserRole::Admin)).await,
+        Err(AppError::Forbidden(_))
+    ));
+    let viewer = auth_service.self_register(pool, signup("watcher", UserRole::Viewer)).await.unwrap();
+    assert_eq!(viewer.role, UserRole::Viewer);
+
+    let closed = AuthService::new("test-secret-key".to_string()).with_registration(RegistrationMode::Disabled);
+    assert!(matches!(
+        closed.self_register(pool, signup("latecomer", UserRole::Viewer)).await,
+        Err(AppError::Forbidden(_))
+    ));
+
+    let open = AuthService::new("test-secret-key".to_string()).with_registration(RegistrationMode::Open);
+    assert_eq!(open.self_register(pool, signup("developer", UserRole::Accountant)).await.unwrap().role, UserRole::Accountant);
+
+    // The first admin is created once, whatever role is asked for
+    let admin = auth_service.bootstrap_admin(pool, signup("founder", UserRole::Viewer)).await.unwrap().unwrap();
+    assert_eq!(admin.role, UserRole::Admin);
+    assert!(auth_service.bootstrap_admin(pool, signup("second", UserRole::Admin)).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_invite_and_list_users() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let mail_file = std::env::temp_dir().join(format!("ledger-forge-mail-{}.log", Uuid::new_v4()));
+    let auth_service = AuthService::new("test-secret-key".to_string())
+        .with_mailer(Mailer::File(mail_file.clone()));
+    create_user(pool, &auth_service, "boss", UserRole::Admin).await;
+    create_user(pool, &auth_service, "reader", UserRole::Viewer).await;
+
+    let invited = auth_service.invite_user(pool, InviteUserRequest {
+        username: "newhire".to_string(),
+        email: "newhire@example.com".to_string(),
+        role: UserRole::Accountant,
+    }).await.unwrap();
+    assert_eq!(invited.role, UserRole::Accountant);
+    assert!(invited.disabled_at.is_none());
+    assert!(!invited.mfa_enabled);
+
+    assert!(matches!(
+        auth_service.invite_user(pool, InviteUserRequest {
+            username: "newhire".to_string(),
+            email: "other@example.com".to_string(),
+            role: UserRole::Viewer,
+        }).await,
+        Err(AppError::Conflict(_))
+    ));
+
+    // The invitation lasts a week and sets the first password
+    let mail = std::fs::read_to_string(&mail_file).unwrap();
+    assert!(mail.contains("Subject: You're invited to LedgerForge"));
+    assert!(mail.contains("7 days"));
+    let codes = sent_codes(&mail_file);
+    assert_eq!(codes.len(), 1);
+    auth_service.reset_password(pool, ResetPasswordRequest {
+        token: codes[0].clone(),
+        new_password: "FirstPass123".to_string(),
+    }).await.unwrap();
+    assert!(login(pool, &auth_service, "newhire", "FirstPass123").await.is_ok());
+
+    // Search is case-insensitive over usernames and emails
+    let names = |users: Vec<ledger_forge::models::UserAccount>| {
+        users.into_iter().map(|user| user.username).collect::<Vec<_>>()
+    };
+    assert_eq!(names(auth_service.list_users(pool, None, None, None).await.unwrap()), ["boss", "newhire", "reader"]);
+    assert_eq!(names(auth_service.list_users(pool, Some("NEW".to_string()), None, None).await.unwrap()), ["newhire"]);
+    assert_eq!(names(auth_service.list_users(pool, Some("reader@".to_string()), None, None).await.unwrap()), ["reader"]);
+    assert_eq!(names(auth_service.list_users(pool, Some("%".to_string()), None, None).await.unwrap()), Vec::<String>::new());
+    assert_eq!(names(auth_service.list_users(pool, None, Some(UserRole::Admin), None).await.unwrap()), ["boss"]);
+    assert_eq!(names(auth_service.list_users(pool, None, None, Some(true)).await.unwrap()), Vec::<String>::new());
+
+    assert!(matches!(
+        auth_service.get_user_account(pool, Uuid::new_v4()).await,
+        Err(AppError::NotFound(_))
+    ));
+
+    let _ = std::fs::remove_file(&mail_file);
+}
+
+#[tokio::test]
+async fn test_roles_disabling_and_forced_resets() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let mail_file = std::env::temp_dir().join(format!("ledger-forge-mail-{}.log", Uuid::new_v4()));
+    let auth_service = AuthService::new("test-secret-key".to_string())
+        .with_mailer(Mailer::File(mail_file.clone()));
+    let admin = create_user(pool, &auth_service, "boss", UserRole::Admin).await;
+    let clerk = create_user(pool, &auth_service, "clerk", UserRole::Viewer).await;
+
+    // There is always an active admin
+    assert!(matches!(
+        auth_service.set_user_role(pool, admin.id, UserRole::Accountant).await,
+        Err(AppError::Conflict(_))
+    ));
+    assert!(matches!(
+        auth_service.set_user_disabled(pool, admin.id, admin.id, true).await,
+        Err(AppError::Conflict(_))
+    ));
+    let promoted = auth_service.set_user_role(pool, clerk.id, UserRole::Admin).await.unwrap();
+    assert_eq!(promoted.role, UserRole::Admin);
+    auth_service.set_user_role(pool, admin.id, UserRole::Accountant).await.unwrap();
+    assert!(matches!(
+        auth_service.set_user_role(pool, clerk.id, UserRole::Viewer).await,
+        Err(AppError::Conflict(_))
+    ));
+    auth_service.set_user_role(pool, admin.id, UserRole::Admin).await.unwrap();
+    auth_service.set_user_role(pool, clerk.id, UserRole::Accountant).await.unwrap();
+    assert!(matches!(
+        auth_service.set_user_role(pool, Uuid::new_v4(), UserRole::Viewer).await,
+        Err(AppError::NotFound(_))
+    ));
+
+    // Disabling ends every session and blocks logging in
+    let refresh_token = auth_service.generate_refresh_token(pool, &clerk, None).await.unwrap();
+    let access_token = auth_service.generate_access_token(&clerk, None).unwrap();
+    let claims = auth_service.validate_token(&access_token).unwrap().claims;
+
+    let disabled = auth_service.set_user_disabled(pool, admin.id, clerk.id, true).await.unwrap();
+    assert!(disabled.disabled_at.is_some());
+    assert!(matches!(
+        auth_service.authenticated_user(pool, &claims).await,
+        Err(AppError::Unauthorized(_))
+    ));
+    assert!(auth_service.rotate_refresh_token(pool, &refresh_token).await.is_err());
+    assert!(matches!(
+        login(pool, &auth_service, "clerk", "SecurePass123").await,
+        Err(AppError::Forbidden(_))
+    ));
+    // Wrong passwords still look like wrong passwords
+    assert!(matches!(
+        login(pool, &auth_service, "clerk", "WrongPass123").await,
+        Err(AppError::InvalidCredentials)
+    ));
+
+    let enabled = auth_service.set_user_disabled(pool, admin.id, clerk.id, false).await.unwrap();
+    assert!(enabled.disabled_at.is_none());
+    assert!(auth_service.authenticated_user(pool, &claims).await.is_ok());
+    assert!(login(pool, &auth_service, "clerk", "SecurePass123").await.is_ok());
+
+    // A forced reset retires the password and sessions, and emails a reset code
+    let refresh_token = auth_service.generate_refresh_token(pool, &clerk, None).await.unwrap();
+    auth_service.force_password_reset(pool, clerk.id).await.unwrap();
+    assert!(matches!(
+        login(pool, &auth_service, "clerk", "SecurePass123").await,
+        Err(AppError::InvalidCredentials)
+    ));
+    assert!(auth_service.rotate_refresh_token(pool, &refresh_token).await.is_err());
+
+    let codes = sent_codes(&mail_file);
+    assert_eq!(codes.len(), 1);
+    auth_service.reset_password(pool, ResetPasswordRequest {
+        token: codes[0].clone(),
+        new_password: "ChosenPass456".to_string(),
+    }).await.unwrap();
+    assert!(login(pool, &auth_service, "clerk", "ChosenPass456").await.is_ok());
+
+    let _ = std::fs::remove_file(&mail_file);
+}
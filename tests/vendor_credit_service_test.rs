@@ -0,0 +1,209 @@
+use ledger_forge::models::{
+    AccountType, SetupCanadianTaxCodesRequest, CreateBillRequest, CreateBillLineItemRequest, BillStatus,
+    CreateBillPaymentRequest, BillPaymentApplicationRequest, CreateVendorCreditRequest,
+    CreateVendorCreditLineItemRequest, VendorCreditApplicationRequest, VendorCreditStatus, TaxReturnPeriodRequest,
+};
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+mod common;
+use common::test_db::TestDb;
+use common::fixtures::{date, dec, create_account, create_vendor, balance};
+use common::services::{vendor_credit_service, bill_service, payment_service, tax_service};
+
+fn vendor_credit_request(number: &str, vendor_id: Uuid, ap_account_id: Uuid, expense_account_id: Uuid, amount: &str) -> CreateVendorCreditRequest {
+    CreateVendorCreditRequest {
+        vendor_credit_number: number.to_string(),
+        vendor_id,
+        credit_date: date(2025, 10, 10),
+        bill_id: None,
+        memo: Some("Returned goods".to_string()),
+        company_id: None,
+        ap_account_id: Some(ap_account_id),
+        line_items: vec![CreateVendorCreditLineItemRequest {
+            line_number: 1,
+            description: Some("Returned widgets".to_string()),
+            amount: dec(amount),
+            expense_account_id,
+            tax_code: Some("HST-ON".to_string()),
+        }],
+        applications: Vec::new(),
+    }
+}
+
+fn application(bill_id: Uuid, amount: &str) -> VendorCreditApplicationRequest {
+    VendorCreditApplicationRequest { bill_id, amount_applied: dec(amount) }
+}
+
+struct OpenBill {
+    bank_account: Uuid,
+    itc_account: Uuid,
+    ap_account: Uuid,
+    expense_account: Uuid,
+    vendor: Uuid,
+    bill_id: Uuid,
+}
+
+impl OpenBill {
+    fn credit(&self, number: &str, amount: &str) -> CreateVendorCreditRequest {
+        vendor_credit_request(number, self.vendor, self.ap_account, self.expense_account, amount)
+    }
+
+    /// A credit created against the bill and applied to it in full
+    fn applied_credit(&self, number: &str, amount: &str, total: &str) -> CreateVendorCreditRequest {
+        CreateVendorCreditRequest {
+            bill_id: Some(self.bill_id),
+            applications: vec![application(self.bill_id, total)],
+            ..self.credit(number, amount)
+        }
+    }
+}
+
+/// Canadian tax codes and a posted 1000.00 + HST bill for supplies
+async fn open_bill(pool: &PgPool) -> OpenBill {
+    let bank_account = create_account(pool, "1000", "Bank", AccountType::Asset).await;
+    let itc_account = create_account(pool, "1300", "GST/HST ITC Receivable", AccountType::Asset).await;
+    let ap_account = create_account(pool, "2000", "Accounts Payable", AccountType::Liability).await;
+    let hst_account = create_account(pool, "2200", "GST/HST Payable", AccountType::Liability).await;
+    let expense_account = create_account(pool, "5100", "Supplies", AccountType::Expense).await;
+
+    tax_service().setup_canadian_tax_codes(pool, SetupCanadianTaxCodesRequest {
+        gst_hst_account_id: hst_account,
+        itc_account_id: Some(itc_account),
+        pst_account_id: None,
+        qst_account_id: None,
+        effective_from: date(2025, 4, 1),
+        company_id: None,
+    }).await.unwrap();
+
+    let vendor = create_vendor(pool, "Returns Supplier").await;
+    let bill = bill_service().create_bill(pool, CreateBillRequest {
+        bill_number: Some("B-VC-1".to_string()),
+        vendor_id: vendor,
+        bill_date: date(2025, 10, 1),
+        due_date: date(2025, 10, 31),
+        memo: None,
+        company_id: None,
+        ap_account_id: Some(ap_account),
+        line_items: vec![CreateBillLineItemRequest {
+            line_number: 1,
+            description: Some("Widgets".to_string()),
+            amount: dec("1000.00"),
+            expense_account_id: expense_account,
+            billable: None,
+            customer_id: None,
+            item_id: None,
+            quantity: None,
+            tax_code: Some("HST-ON".to_string()),
+            dimension_value_ids: Vec::new(),
+        }],
+    }).await.unwrap();
+    assert_eq!(bill.total_amount, dec("1130.00"));
+
+    OpenBill { bank_account, itc_account, ap_account, expense_account, vendor, bill_id: bill.id }
+}
+
+#[tokio::test]
+async fn test_applied_credit_reverses_expense_and_reduces_bill_balance() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let setup = open_bill(pool).await;
+
+    let vendor_credit = vendor_credit_service().create_vendor_credit(pool, setup.applied_credit("VC-1", "200.00", "226.00")).await.unwrap();
+    assert_eq!(vendor_credit.vendor_credit.total_amount, dec("226.00"));
+    assert_eq!(vendor_credit.vendor_credit.balance, Decimal::ZERO);
+    assert_eq!(vendor_credit.vendor_credit.status, VendorCreditStatus::Applied);
+    assert_eq!(vendor_credit.taxes.len(), 1);
+    assert_eq!(vendor_credit.applications.len(), 1);
+
+    let bill_after = bill_service()
+        .get_bill_by_id(pool, setup.bill_id).await.unwrap().unwrap();
+    assert_eq!(bill_after.bill.balance, dec("904.00"));
+    assert_eq!(bill_after.bill.status, BillStatus::Partial);
+
+    // Expense and input tax credits are reversed against payables
+    assert_eq!(balance(pool, setup.ap_account).await, dec("-904.00"));
+    assert_eq!(balance(pool, setup.expense_account).await, dec("800.00"));
+    assert_eq!(balance(pool, setup.itc_account).await, dec("104.00"));
+
+    let worksheet = tax_service().generate_return_worksheet(pool, TaxReturnPeriodRequest {
+        agency: None,
+        period_start: date(2025, 10, 1),
+        period_end: date(2025, 12, 31),
+        company_id: None,
+    }).await.unwrap();
+    assert_eq!(worksheet.lines.line_106_itcs, dec("104.00"));
+}
+
+#[tokio::test]
+async fn test_applied_credit_cannot_be_voided_and_keeps_the_bill() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let setup = open_bill(pool).await;
+    let vendor_credit = vendor_credit_service().create_vendor_credit(pool, setup.applied_credit("VC-1", "200.00", "226.00")).await.unwrap();
+
+    assert!(vendor_credit_service().void_vendor_credit(pool, vendor_credit.vendor_credit.id).await.is_err());
+    assert!(bill_service().delete_bill(pool, setup.bill_id).await.is_err());
+}
+
+#[tokio::test]
+async fn test_credit_cannot_be_applied_past_the_bill_balance() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let setup = open_bill(pool).await;
+    vendor_credit_service().create_vendor_credit(pool, setup.applied_credit("VC-1", "200.00", "226.00")).await.unwrap();
+
+    let second = vendor_credit_service().create_vendor_credit(pool, setup.credit("VC-2", "900.00")).await.unwrap();
+    assert_eq!(second.vendor_credit.total_amount, dec("1017.00"));
+    let over = vendor_credit_service().apply_vendor_credit(pool, second.vendor_credit.id, vec![application(setup.bill_id, "1000.00")]).await;
+    assert!(over.is_err());
+
+    // Part of it still applies, leaving the rest open
+    let partly = vendor_credit_service().apply_vendor_credit(pool, second.vendor_credit.id, vec![application(setup.bill_id, "404.00")]).await.unwrap();
+    assert_eq!(partly.balance, dec("613.00"));
+    assert_eq!(partly.status, VendorCreditStatus::Open);
+
+    let listed = vendor_credit_service().list_vendor_credits(pool, Some(setup.vendor), Some(VendorCreditStatus::Open)).await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].vendor_credit_number, "VC-2");
+}
+
+#[tokio::test]
+async fn test_paying_the_rest_after_a_credit_settles_the_bill() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let setup = open_bill(pool).await;
+    vendor_credit_service().create_vendor_credit(pool, setup.applied_credit("VC-1", "500.00", "565.00")).await.unwrap();
+
+    payment_service().create_bill_payment(pool, CreateBillPaymentRequest {
+        payment_number: None,
+        vendor_id: setup.vendor,
+        payment_date: date(2025, 10, 20),
+        amount: dec("565.00"),
+        payment_method: "Cheque".to_string(),
+        reference_number: None,
+        bank_account_id: Some(setup.bank_account),
+        memo: None,
+        company_id: None,
+        applications: vec![BillPaymentApplicationRequest { bill_id: setup.bill_id, amount_applied: dec("565.00") }],
+    }).await.unwrap();
+    let paid = bill_service()
+        .get_bill_by_id(pool, setup.bill_id).await.unwrap().unwrap();
+    assert_eq!(paid.bill.balance, Decimal::ZERO);
+    assert_eq!(paid.bill.status, BillStatus::Paid);
+}
+
+#[tokio::test]
+async fn test_voiding_an_unapplied_credit_reverses_its_posting() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let setup = open_bill(pool).await;
+
+    let credit = vendor_credit_service().create_vendor_credit(pool, setup.credit("VC-3", "100.00")).await.unwrap();
+    assert_eq!(balance(pool, setup.expense_account).await, dec("900.00"));
+
+    let voided = vendor_credit_service().void_vendor_credit(pool, credit.vendor_credit.id).await.unwrap();
+    assert_eq!(voided.status, VendorCreditStatus::Void);
+    assert_eq!(balance(pool, setup.expense_account).await, dec("1000.00"));
+}
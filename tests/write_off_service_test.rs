@@ -0,0 +1,215 @@
+use ledger_forge::models::{
+    AccountType, CreatePaymentRequest, InvoiceStatus, WriteOffInvoicesRequest,
+    RecoverWriteOffRequest, DateRequest, DimensionFilter, StatementStyle,
+};
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+mod common;
+use common::test_db::TestDb;
+use common::fixtures::{
+    date, dec, create_account, create_customer, create_sent_invoice, payment_request, balance, get_invoice,
+    Receivables,
+};
+use common::services::{write_off_service, payment_service, invoice_service, reporting_service, statement_service};
+
+async fn pay(pool: &PgPool, number: &str, customer_id: Uuid, invoice_id: Uuid, amount: &str, payment_date: NaiveDate) {
+    payment_service().create_payment(pool, CreatePaymentRequest {
+        payment_number: Some(number.to_string()),
+        payment_date,
+        ..payment_request(customer_id, amount, &[(invoice_id, amount)])
+    }).await.unwrap();
+}
+
+fn write_off_request(invoice_ids: Vec<Uuid>, account_id: Uuid) -> WriteOffInvoicesRequest {
+    WriteOffInvoicesRequest {
+        invoice_ids,
+        write_off_date: date(2025, 12, 31),
+        account_id,
+        reason: Some("Customer ceased trading".to_string()),
+    }
+}
+
+fn recovery(amount: &str) -> RecoverWriteOffRequest {
+    RecoverWriteOffRequest {
+        recovery_date: date(2026, 2, 10),
+        amount: Some(dec(amount)),
+    }
+}
+
+struct Debtor {
+    ar_account: Uuid,
+    revenue_account: Uuid,
+    bad_debt_account: Uuid,
+    customer: Uuid,
+    /// 1000.00, 300.00 of it paid
+    first: Uuid,
+    /// 200.00, unpaid
+    second: Uuid,
+}
+
+/// A customer owing 900.00 over two posted invoices
+async fn debtor(pool: &PgPool) -> Debtor {
+    let receivables = Receivables::create(pool).await;
+    let bad_debt_account = create_account(pool, "6900", "Bad Debt Expense", AccountType::Expense).await;
+    let customer = create_customer(pool, "Gone Away Ltd").await;
+
+    let first = create_sent_invoice(pool, receivables.invoice_request("INV-WO-1", customer, "1000.00")).await;
+    let second = create_sent_invoice(pool, receivables.invoice_request("INV-WO-2", customer, "200.00")).await;
+    pay(pool, "PMT-WO-1", customer, first, "300.00", date(2025, 7, 15)).await;
+
+    Debtor {
+        ar_account: receivables.ar_account,
+        revenue_account: receivables.revenue_account,
+        bad_debt_account,
+        customer,
+        first,
+        second,
+    }
+}
+
+/// The debtor with both invoices written off at year end
+async fn written_off(pool: &PgPool) -> Debtor {
+    let debtor = debtor(pool).await;
+    write_off_service().write_off_invoices(
+        pool, write_off_request(vec![debtor.first, debtor.second], debtor.bad_debt_account), None,
+    ).await.unwrap();
+    debtor
+}
+
+#[tokio::test]
+async fn test_bad_debt_cannot_go_to_a_revenue_account() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let debtor = debtor(pool).await;
+
+    assert!(write_off_service().write_off_invoices(
+        pool, write_off_request(vec![debtor.first, debtor.second], debtor.revenue_account), None,
+    ).await.is_err());
+}
+
+#[tokio::test]
+async fn test_write_off_clears_balances_against_bad_debt() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let debtor = debtor(pool).await;
+    let receivable = balance(pool, debtor.ar_account).await;
+
+    let write_offs = write_off_service().write_off_invoices(
+        pool, write_off_request(vec![debtor.first, debtor.second], debtor.bad_debt_account), None,
+    ).await.unwrap();
+    assert_eq!(write_offs.len(), 2);
+    assert_eq!(write_offs.iter().find(|w| w.invoice_id == debtor.first).unwrap().amount, dec("700.00"));
+
+    let first = get_invoice(pool, debtor.first).await;
+    assert_eq!(first.status, InvoiceStatus::WrittenOff);
+    assert_eq!(first.balance, Decimal::ZERO);
+    assert_eq!(balance(pool, debtor.bad_debt_account).await, dec("900.00"));
+    assert_eq!(balance(pool, debtor.ar_account).await, receivable - dec("900.00"));
+}
+
+#[tokio::test]
+async fn test_written_off_invoice_is_locked() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let debtor = written_off(pool).await;
+
+    // Neither written off again nor changed directly
+    assert!(write_off_service().write_off_invoices(
+        pool, write_off_request(vec![debtor.second], debtor.bad_debt_account), None,
+    ).await.is_err());
+    assert!(invoice_service().update_invoice_status(pool, debtor.second, InvoiceStatus::Void).await.is_err());
+}
+
+#[tokio::test]
+async fn test_aging_moves_written_off_balances_to_their_own_column() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    written_off(pool).await;
+
+    let aging = reporting_service()
+        .generate_ar_aging(pool, DateRequest { as_of_date: date(2026, 1, 15) }, &DimensionFilter::default())
+        .await
+        .unwrap();
+    assert_eq!(aging.total_outstanding, Decimal::ZERO);
+    assert_eq!(aging.total_written_off, dec("900.00"));
+    assert_eq!(aging.buckets.len(), 1);
+    assert_eq!(aging.buckets[0].written_off, dec("900.00"));
+}
+
+#[tokio::test]
+async fn test_statement_lists_write_offs() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let debtor = written_off(pool).await;
+
+    let statement = statement_service()
+        .generate_statement(pool, debtor.customer, Some(date(2025, 12, 1)), date(2026, 1, 31), StatementStyle::BalanceForward)
+        .await.unwrap();
+    assert_eq!(statement.opening_balance, dec("900.00"));
+    assert_eq!(statement.lines.iter().filter(|l| l.document_type == "write_off").count(), 2);
+    assert_eq!(statement.closing_balance, Decimal::ZERO);
+    assert_eq!(statement.aging.written_off, dec("900.00"));
+}
+
+#[tokio::test]
+async fn test_recovery_cannot_exceed_the_written_off_amount() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let debtor = written_off(pool).await;
+
+    // Only 700.00 of the first invoice was written off
+    assert!(write_off_service().recover(pool, debtor.first, recovery("800.00"), None).await.is_err());
+}
+
+#[tokio::test]
+async fn test_recovery_reopens_the_invoice_and_reverses_bad_debt() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let debtor = debtor(pool).await;
+    let receivable = balance(pool, debtor.ar_account).await;
+    write_off_service().write_off_invoices(
+        pool, write_off_request(vec![debtor.first, debtor.second], debtor.bad_debt_account), None,
+    ).await.unwrap();
+
+    let invoice = write_off_service().recover(pool, debtor.first, recovery("250.00"), None).await.unwrap();
+    assert_eq!(invoice.status, InvoiceStatus::Partial);
+    assert_eq!(invoice.balance, dec("250.00"));
+    assert_eq!(balance(pool, debtor.bad_debt_account).await, dec("650.00"));
+    assert_eq!(balance(pool, debtor.ar_account).await, receivable - dec("650.00"));
+}
+
+#[tokio::test]
+async fn test_recovered_amount_can_be_paid() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let debtor = written_off(pool).await;
+    write_off_service().recover(pool, debtor.first, recovery("250.00"), None).await.unwrap();
+
+    pay(pool, "PMT-WO-2", debtor.customer, debtor.first, "250.00", date(2026, 2, 10)).await;
+    assert_eq!(get_invoice(pool, debtor.first).await.status, InvoiceStatus::Paid);
+
+    let statement = statement_service()
+        .generate_statement(pool, debtor.customer, Some(date(2026, 2, 1)), date(2026, 2, 28), StatementStyle::BalanceForward)
+        .await.unwrap();
+    assert_eq!(statement.lines.len(), 2);
+    assert_eq!(statement.lines[0].document_type, "write_off_recovery");
+    assert_eq!(statement.closing_balance, Decimal::ZERO);
+    assert_eq!(statement.aging.written_off, dec("650.00"));
+}
+
+#[tokio::test]
+async fn test_write_off_history_tracks_recoveries() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+    let debtor = written_off(pool).await;
+    write_off_service().recover(pool, debtor.first, recovery("250.00"), None).await.unwrap();
+
+    let history = write_off_service().list_write_offs(pool, Some(debtor.customer), None).await.unwrap();
+    assert_eq!(history.len(), 2);
+    let recovered = history.iter().find(|w| w.write_off.invoice_id == debtor.first).unwrap();
+    assert_eq!(recovered.write_off.recovered_amount, dec("250.00"));
+    assert_eq!(recovered.recoveries.len(), 1);
+}